@@ -0,0 +1,5 @@
+DROP TABLE IF EXISTS no_show_charges;
+DROP TYPE IF EXISTS no_show_charge_status;
+
+-- Note: PostgreSQL doesn't support removing enum values directly,
+-- so 'no_show' stays in booking_status
@@ -0,0 +1,25 @@
+-- Add no_show status to booking_status enum
+ALTER TYPE booking_status ADD VALUE IF NOT EXISTS 'no_show';
+
+-- Review state of an automatically raised no-show charge
+CREATE TYPE no_show_charge_status AS ENUM ('pending', 'confirmed', 'waived', 'voided');
+
+CREATE TABLE no_show_charges (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    -- One charge per booking, so re-running the stale-booking job never double-charges
+    booking_id UUID NOT NULL UNIQUE REFERENCES bookings(id) ON DELETE CASCADE,
+    amount DECIMAL(12, 2) NOT NULL CHECK (amount >= 0),
+    -- 'one_night' or 'deposit'
+    basis VARCHAR(20) NOT NULL,
+    status no_show_charge_status NOT NULL DEFAULT 'pending',
+    reason TEXT,
+    reviewed_by_user_id UUID REFERENCES users(id),
+    reviewed_at TIMESTAMPTZ,
+    payment_id UUID REFERENCES payments(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_no_show_charges_status ON no_show_charges(status);
+
+SELECT diesel_manage_updated_at('no_show_charges');
@@ -0,0 +1,2 @@
+DELETE FROM system_settings WHERE key = 'read_only_mode';
+DROP TABLE IF EXISTS audit_logs;
@@ -0,0 +1,19 @@
+-- Append-only record of sensitive admin actions
+CREATE TABLE audit_logs (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    actor_user_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    -- e.g. 'read_only_mode.enabled'
+    action VARCHAR(100) NOT NULL,
+    entity_type VARCHAR(50) NOT NULL,
+    entity_id VARCHAR(100),
+    details TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_audit_logs_entity ON audit_logs(entity_type, entity_id);
+CREATE INDEX idx_audit_logs_created_at ON audit_logs(created_at);
+
+-- Operational read-only switch used during maintenance windows
+INSERT INTO system_settings (key, value, description) VALUES
+('read_only_mode', 'false', 'Refuse writes while database or storage maintenance is in progress')
+ON CONFLICT (key) DO NOTHING;
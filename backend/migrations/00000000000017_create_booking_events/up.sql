@@ -0,0 +1,15 @@
+-- History of changes made to a booking after it was created
+CREATE TABLE booking_events (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    booking_id UUID NOT NULL REFERENCES bookings(id) ON DELETE CASCADE,
+    -- 'status_change' or 'date_change'
+    event_type VARCHAR(30) NOT NULL,
+    from_status booking_status,
+    to_status booking_status,
+    -- NULL for system jobs and unauthenticated staff routes
+    actor_user_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    details TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_booking_events_booking_id ON booking_events(booking_id, created_at);
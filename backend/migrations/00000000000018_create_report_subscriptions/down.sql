@@ -0,0 +1,5 @@
+DELETE FROM system_settings WHERE key = 'hotel_timezone';
+DROP TABLE IF EXISTS report_deliveries;
+DROP TABLE IF EXISTS report_subscriptions;
+DROP TYPE IF EXISTS report_delivery_status;
+DROP TYPE IF EXISTS report_type;
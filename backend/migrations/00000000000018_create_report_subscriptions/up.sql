@@ -0,0 +1,40 @@
+-- Summary reports emailed to admins on a schedule
+CREATE TYPE report_type AS ENUM ('daily', 'weekly', 'monthly');
+CREATE TYPE report_delivery_status AS ENUM ('pending', 'sent', 'failed');
+
+CREATE TABLE report_subscriptions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    recipient_email VARCHAR(255) NOT NULL,
+    report_type report_type NOT NULL,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    created_by_user_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- One row per subscription and reporting period, so a period is never sent twice
+CREATE TABLE report_deliveries (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    subscription_id UUID NOT NULL REFERENCES report_subscriptions(id) ON DELETE CASCADE,
+    period_start DATE NOT NULL,
+    period_end DATE NOT NULL,
+    status report_delivery_status NOT NULL DEFAULT 'pending',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT,
+    -- When the next send attempt is due; NULL once sent or given up
+    next_attempt_at TIMESTAMPTZ,
+    sent_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (subscription_id, period_start)
+);
+
+CREATE INDEX idx_report_deliveries_due ON report_deliveries(status, next_attempt_at);
+
+SELECT diesel_manage_updated_at('report_subscriptions');
+SELECT diesel_manage_updated_at('report_deliveries');
+
+-- Hotel timezone as a fixed UTC offset, used to decide when reports are sent
+INSERT INTO system_settings (key, value, description) VALUES
+('hotel_timezone', '+07:00', 'Hotel timezone as a UTC offset, e.g. +07:00')
+ON CONFLICT (key) DO NOTHING;
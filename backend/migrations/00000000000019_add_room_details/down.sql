@@ -0,0 +1,4 @@
+ALTER TABLE rooms
+  DROP COLUMN IF EXISTS photo_urls,
+  DROP COLUMN IF EXISTS amenities,
+  DROP COLUMN IF EXISTS description;
@@ -0,0 +1,5 @@
+-- Guest-facing room details, also surfaced to the AI concierge
+ALTER TABLE rooms
+  ADD COLUMN description TEXT,
+  ADD COLUMN amenities TEXT[] NOT NULL DEFAULT '{}',
+  ADD COLUMN photo_urls TEXT[] NOT NULL DEFAULT '{}';
@@ -0,0 +1,5 @@
+DELETE FROM system_settings WHERE key = 'cash_discrepancy_threshold';
+DROP TABLE IF EXISTS admin_notifications;
+DROP TRIGGER IF EXISTS cash_reconciliations_immutable ON cash_reconciliations;
+DROP TABLE IF EXISTS cash_reconciliations;
+DROP FUNCTION IF EXISTS reject_cash_reconciliation_changes();
@@ -0,0 +1,51 @@
+-- End-of-shift cash drawer counts. Rows are immutable; a recount is a new row.
+CREATE TABLE cash_reconciliations (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    -- Hotel-local business date and optional shift the count covers
+    business_date DATE NOT NULL,
+    shift VARCHAR(20),
+    period_start TIMESTAMPTZ NOT NULL,
+    period_end TIMESTAMPTZ NOT NULL,
+    expected_cash DECIMAL(12, 0) NOT NULL,
+    counted_cash DECIMAL(12, 0) NOT NULL,
+    -- counted_cash - expected_cash
+    discrepancy DECIMAL(12, 0) NOT NULL,
+    note TEXT,
+    recorded_by_user_id UUID NOT NULL REFERENCES users(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT chk_counted_cash_non_negative CHECK (counted_cash >= 0)
+);
+
+CREATE INDEX idx_cash_reconciliations_business_date ON cash_reconciliations(business_date);
+
+CREATE OR REPLACE FUNCTION reject_cash_reconciliation_changes()
+RETURNS TRIGGER AS $$
+BEGIN
+    RAISE EXCEPTION 'cash_reconciliations rows are immutable';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER cash_reconciliations_immutable
+    BEFORE UPDATE OR DELETE ON cash_reconciliations
+    FOR EACH ROW
+    EXECUTE FUNCTION reject_cash_reconciliation_changes();
+
+-- Alerts for admins, shared by all admin accounts
+CREATE TABLE admin_notifications (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    -- e.g. 'cash_discrepancy'
+    kind VARCHAR(50) NOT NULL,
+    message TEXT NOT NULL,
+    entity_type VARCHAR(50),
+    entity_id VARCHAR(100),
+    read_at TIMESTAMPTZ,
+    read_by_user_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_admin_notifications_unread ON admin_notifications(created_at) WHERE read_at IS NULL;
+
+-- Absolute cash difference (VND) above which admins are notified
+INSERT INTO system_settings (key, value, description) VALUES
+('cash_discrepancy_threshold', '100000', 'Notify admins when a cash count differs from the expected amount by more than this (VND)')
+ON CONFLICT (key) DO NOTHING;
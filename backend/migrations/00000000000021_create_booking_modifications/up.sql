@@ -0,0 +1,14 @@
+-- Field-level before/after history of edits to a booking
+CREATE TABLE booking_modifications (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    booking_id UUID NOT NULL REFERENCES bookings(id) ON DELETE CASCADE,
+    -- NULL for system jobs and unauthenticated staff routes
+    actor_user_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    -- What made the change, e.g. 'staff_update'
+    source VARCHAR(30) NOT NULL,
+    -- JSON object of field name -> {"before": ..., "after": ...}
+    changes TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_booking_modifications_booking_id ON booking_modifications(booking_id, created_at);
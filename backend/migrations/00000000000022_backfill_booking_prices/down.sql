@@ -0,0 +1,3 @@
+-- Backfilled prices cannot be told apart from prices entered at booking time,
+-- so there is nothing to undo.
+SELECT 1;
@@ -0,0 +1,22 @@
+-- Bookings created before the price column existed were stored with the
+-- column default of 0, so financial reports undercount them. Backfill those
+-- from the room's current nightly price (the best estimate available) times
+-- the number of nights.
+--
+-- Only rows older than migration 7 got the default; later zero prices were
+-- set on purpose (comps, waived charges) and are left alone. Where diesel did
+-- not record when migration 7 ran, e.g. a database built by the init scripts,
+-- nothing predates it and there is nothing to backfill.
+DO $$
+BEGIN
+  IF to_regclass('__diesel_schema_migrations') IS NOT NULL THEN
+    UPDATE bookings
+    SET price = rooms.price * GREATEST(bookings.check_out_date - bookings.check_in_date, 1)
+    FROM rooms
+    WHERE bookings.room_id = rooms.id
+      AND bookings.price = 0
+      AND bookings.created_at < (
+        SELECT run_on FROM __diesel_schema_migrations WHERE version = '00000000000007'
+      );
+  END IF;
+END $$;
@@ -0,0 +1,6 @@
+-- Rooms booked together for one party (e.g. a tour group) share a group
+-- reference; NULL for ordinary single-room bookings
+ALTER TABLE bookings ADD COLUMN group_reference VARCHAR(20);
+
+CREATE INDEX idx_bookings_group_reference ON bookings(group_reference)
+    WHERE group_reference IS NOT NULL;
@@ -0,0 +1,2 @@
+DROP TRIGGER IF EXISTS update_booking_notes_updated_at ON booking_notes;
+DROP TABLE IF EXISTS booking_notes;
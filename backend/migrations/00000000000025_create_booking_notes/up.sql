@@ -0,0 +1,17 @@
+-- Free-form staff notes on a single reservation, e.g. late arrival or allergies
+CREATE TABLE booking_notes (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    booking_id UUID NOT NULL REFERENCES bookings(id) ON DELETE CASCADE,
+    author_user_id UUID NOT NULL REFERENCES users(id),
+    note TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT chk_booking_note_not_empty CHECK (LENGTH(TRIM(note)) > 0)
+);
+
+CREATE INDEX idx_booking_notes_booking_id ON booking_notes(booking_id, created_at);
+
+CREATE TRIGGER update_booking_notes_updated_at
+    BEFORE UPDATE ON booking_notes
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
@@ -0,0 +1,3 @@
+DROP TABLE IF EXISTS booking_emails;
+DROP TYPE IF EXISTS booking_email_status;
+DROP TYPE IF EXISTS booking_email_kind;
@@ -0,0 +1,20 @@
+-- Transactional emails sent to guests about a booking, so admins can see what went out
+CREATE TYPE booking_email_kind AS ENUM ('confirmation', 'cancellation');
+CREATE TYPE booking_email_status AS ENUM ('pending', 'sent', 'failed');
+
+CREATE TABLE booking_emails (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    booking_id UUID NOT NULL REFERENCES bookings(id) ON DELETE CASCADE,
+    kind booking_email_kind NOT NULL,
+    recipient_email VARCHAR(255) NOT NULL,
+    status booking_email_status NOT NULL DEFAULT 'pending',
+    last_error TEXT,
+    sent_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_booking_emails_booking_id ON booking_emails(booking_id);
+CREATE INDEX idx_booking_emails_created_at ON booking_emails(created_at DESC);
+
+SELECT diesel_manage_updated_at('booking_emails');
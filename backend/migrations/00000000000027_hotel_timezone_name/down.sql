@@ -0,0 +1,4 @@
+UPDATE system_settings
+SET value = '+07:00',
+    description = 'Hotel timezone as a UTC offset, e.g. +07:00'
+WHERE key = 'hotel_timezone' AND value = 'Asia/Ho_Chi_Minh';
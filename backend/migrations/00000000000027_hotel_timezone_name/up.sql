@@ -0,0 +1,5 @@
+-- The hotel timezone is now an IANA zone name; the seeded offset was Vietnam's
+UPDATE system_settings
+SET value = 'Asia/Ho_Chi_Minh',
+    description = 'Hotel time zone as an IANA name, e.g. Asia/Ho_Chi_Minh'
+WHERE key = 'hotel_timezone' AND value IN ('+07:00', '+7');
@@ -0,0 +1,16 @@
+-- Short-lived hold on a room while a guest completes payment. Unexpired holds
+-- by other users block the room like a booking; expired ones are ignored and
+-- deleted by the stale-booking sweep.
+CREATE TABLE booking_holds (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    room_id UUID NOT NULL REFERENCES rooms(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    check_in_date DATE NOT NULL,
+    check_out_date DATE NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT chk_booking_hold_dates CHECK (check_out_date > check_in_date)
+);
+
+CREATE INDEX idx_booking_holds_room ON booking_holds(room_id, expires_at);
+CREATE INDEX idx_booking_holds_user ON booking_holds(user_id, expires_at);
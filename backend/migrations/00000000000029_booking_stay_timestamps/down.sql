@@ -0,0 +1,3 @@
+ALTER TABLE bookings
+    DROP COLUMN checked_out_at,
+    DROP COLUMN checked_in_at;
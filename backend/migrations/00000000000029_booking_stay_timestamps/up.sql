@@ -0,0 +1,4 @@
+-- When the guest actually arrived and left; NULL for stays recorded before
+ALTER TABLE bookings
+    ADD COLUMN checked_in_at TIMESTAMPTZ,
+    ADD COLUMN checked_out_at TIMESTAMPTZ;
@@ -0,0 +1,13 @@
+-- Every change to a room's nightly price, so revenue can later be worked out
+-- with the rate that applied at the time
+CREATE TABLE room_price_history (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    room_id UUID NOT NULL REFERENCES rooms(id) ON DELETE CASCADE,
+    old_price DECIMAL(12, 0) NOT NULL,
+    new_price DECIMAL(12, 0) NOT NULL,
+    changed_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    changed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT chk_room_price_history_positive CHECK (new_price > 0)
+);
+
+CREATE INDEX idx_room_price_history_room ON room_price_history(room_id, changed_at);
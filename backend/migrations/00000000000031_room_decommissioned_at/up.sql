@@ -0,0 +1,3 @@
+-- Rooms taken out of service are kept for their booking history but hidden
+-- from room lists and never offered for new stays
+ALTER TABLE rooms ADD COLUMN decommissioned_at TIMESTAMPTZ;
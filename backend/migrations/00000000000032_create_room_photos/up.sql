@@ -0,0 +1,12 @@
+-- Photos of a room stored in the room-photos bucket, shown to guests in
+-- sort_order
+CREATE TABLE room_photos (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    room_id UUID NOT NULL REFERENCES rooms(id) ON DELETE CASCADE,
+    object_key TEXT NOT NULL UNIQUE,
+    caption TEXT,
+    sort_order INTEGER NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_room_photos_room ON room_photos(room_id, sort_order);
@@ -0,0 +1,13 @@
+-- Every change of a room's status and who made it, so housekeeping can see
+-- when a room was marked dirty or clean
+CREATE TABLE room_status_events (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    room_id UUID NOT NULL REFERENCES rooms(id) ON DELETE CASCADE,
+    from_status room_status NOT NULL,
+    to_status room_status NOT NULL,
+    -- NULL for system transitions
+    actor_user_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_room_status_events_room ON room_status_events(room_id, created_at DESC);
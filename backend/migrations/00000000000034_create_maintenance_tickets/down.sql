@@ -0,0 +1,3 @@
+DROP TABLE IF EXISTS maintenance_tickets;
+DROP TYPE IF EXISTS ticket_status;
+DROP TYPE IF EXISTS ticket_severity;
@@ -0,0 +1,22 @@
+-- What is broken in a room under maintenance and how far the fix has got
+CREATE TYPE ticket_severity AS ENUM ('low', 'medium', 'high', 'critical');
+CREATE TYPE ticket_status AS ENUM ('open', 'in_progress', 'resolved');
+
+CREATE TABLE maintenance_tickets (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    room_id UUID NOT NULL REFERENCES rooms(id) ON DELETE CASCADE,
+    title VARCHAR(200) NOT NULL,
+    description TEXT,
+    severity ticket_severity NOT NULL DEFAULT 'medium',
+    status ticket_status NOT NULL DEFAULT 'open',
+    reported_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    -- Set while the ticket is resolved
+    resolved_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_maintenance_tickets_room ON maintenance_tickets(room_id, status);
+CREATE INDEX idx_maintenance_tickets_status ON maintenance_tickets(status, created_at);
+
+SELECT diesel_manage_updated_at('maintenance_tickets');
@@ -0,0 +1,28 @@
+-- Nightly prices that differ from a room's own price on certain dates, e.g.
+-- Tet or weekends. A rule covers every room, one room type or one room; on a
+-- night covered by several rules the highest priority wins.
+CREATE TABLE pricing_rules (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name VARCHAR(100) NOT NULL,
+    -- Both NULL for a rule covering every room
+    room_type room_type,
+    room_id UUID REFERENCES rooms(id) ON DELETE CASCADE,
+    -- Nights from start_date to end_date, both included
+    start_date DATE NOT NULL,
+    end_date DATE NOT NULL,
+    -- Fixed nightly price in VND, or a percentage added to the room's price
+    price DECIMAL(12, 0),
+    percentage DECIMAL(6, 2),
+    priority INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT chk_pricing_rule_dates CHECK (end_date >= start_date),
+    CONSTRAINT chk_pricing_rule_scope CHECK (room_type IS NULL OR room_id IS NULL),
+    CONSTRAINT chk_pricing_rule_modifier CHECK ((price IS NULL) <> (percentage IS NULL)),
+    CONSTRAINT chk_pricing_rule_price CHECK (price > 0),
+    CONSTRAINT chk_pricing_rule_percentage CHECK (percentage > -100)
+);
+
+CREATE INDEX idx_pricing_rules_dates ON pricing_rules(start_date, end_date);
+
+SELECT diesel_manage_updated_at('pricing_rules');
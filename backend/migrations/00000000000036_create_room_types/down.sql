@@ -0,0 +1,19 @@
+-- Only the original three types can be represented by the enum; rooms of
+-- any other type make this fail
+CREATE TYPE room_type AS ENUM ('single', 'double', 'suite');
+
+ALTER TABLE pricing_rules ADD COLUMN room_type room_type;
+UPDATE pricing_rules SET room_type = room_types.code::room_type
+FROM room_types
+WHERE room_types.id = pricing_rules.room_type_id;
+ALTER TABLE pricing_rules DROP CONSTRAINT chk_pricing_rule_scope;
+ALTER TABLE pricing_rules DROP COLUMN room_type_id;
+ALTER TABLE pricing_rules
+    ADD CONSTRAINT chk_pricing_rule_scope CHECK (room_type IS NULL OR room_id IS NULL);
+
+DROP INDEX IF EXISTS idx_rooms_room_type;
+ALTER TABLE rooms DROP CONSTRAINT fk_rooms_room_type;
+ALTER TABLE rooms ALTER COLUMN room_type TYPE room_type USING room_type::room_type;
+ALTER TABLE rooms DROP COLUMN room_type_id;
+
+DROP TABLE IF EXISTS room_types;
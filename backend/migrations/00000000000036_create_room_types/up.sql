@@ -0,0 +1,49 @@
+-- Room types become data so a property can add its own (e.g. family or
+-- deluxe rooms). The original enum values are seeded with the prices and
+-- capacities that used to be hard-coded.
+CREATE TABLE room_types (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    code VARCHAR(30) NOT NULL UNIQUE,
+    display_name VARCHAR(100) NOT NULL,
+    default_price DECIMAL(12, 0) NOT NULL,
+    default_capacity INTEGER NOT NULL,
+    -- Inactive types keep their rooms but are not offered for new ones
+    active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT chk_room_type_default_price CHECK (default_price > 0),
+    CONSTRAINT chk_room_type_default_capacity CHECK (default_capacity > 0),
+    CONSTRAINT uq_room_types_id_code UNIQUE (id, code)
+);
+
+SELECT diesel_manage_updated_at('room_types');
+
+INSERT INTO room_types (code, display_name, default_price, default_capacity) VALUES
+    ('single', 'Single', 1000000, 2),
+    ('double', 'Double', 1500000, 4),
+    ('suite', 'Suite', 2500000, 6);
+
+-- Rooms reference their type by id. The code stays beside it so rooms can
+-- be filtered and shown without a join; the composite key keeps both in step.
+ALTER TABLE rooms ADD COLUMN room_type_id UUID;
+UPDATE rooms SET room_type_id = room_types.id
+FROM room_types
+WHERE room_types.code = rooms.room_type::text;
+ALTER TABLE rooms ALTER COLUMN room_type_id SET NOT NULL;
+ALTER TABLE rooms ALTER COLUMN room_type TYPE VARCHAR(30) USING room_type::text;
+ALTER TABLE rooms
+    ADD CONSTRAINT fk_rooms_room_type FOREIGN KEY (room_type_id, room_type)
+    REFERENCES room_types(id, code) ON UPDATE CASCADE;
+CREATE INDEX idx_rooms_room_type ON rooms(room_type);
+
+-- Pricing rules for a room type reference it by id
+ALTER TABLE pricing_rules ADD COLUMN room_type_id UUID REFERENCES room_types(id) ON DELETE CASCADE;
+UPDATE pricing_rules SET room_type_id = room_types.id
+FROM room_types
+WHERE room_types.code = pricing_rules.room_type::text;
+ALTER TABLE pricing_rules DROP CONSTRAINT chk_pricing_rule_scope;
+ALTER TABLE pricing_rules DROP COLUMN room_type;
+ALTER TABLE pricing_rules
+    ADD CONSTRAINT chk_pricing_rule_scope CHECK (room_type_id IS NULL OR room_id IS NULL);
+
+DROP TYPE room_type;
@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS idx_rooms_location;
+ALTER TABLE rooms DROP COLUMN IF EXISTS building;
+ALTER TABLE rooms DROP COLUMN IF EXISTS floor;
@@ -0,0 +1,15 @@
+-- Where each room is, so rooms can be listed building by building and
+-- floor by floor. A NULL building is the main (or only) building.
+ALTER TABLE rooms
+    ADD COLUMN floor INTEGER NOT NULL DEFAULT 1,
+    ADD COLUMN building VARCHAR(50);
+
+-- Existing rooms numbered floor then room (305, 1204) get that floor
+UPDATE rooms SET floor = number::INTEGER / 100 WHERE number ~ '^[0-9]{3,4}$';
+
+ALTER TABLE rooms ALTER COLUMN floor DROP DEFAULT;
+ALTER TABLE rooms
+    ADD CONSTRAINT chk_rooms_floor CHECK (floor BETWEEN -10 AND 200),
+    ADD CONSTRAINT chk_rooms_building CHECK (building IS NULL OR LENGTH(TRIM(building)) > 0);
+
+CREATE INDEX idx_rooms_location ON rooms(building, floor);
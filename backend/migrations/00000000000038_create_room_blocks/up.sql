@@ -0,0 +1,19 @@
+-- Dates the owner keeps a room off sale, e.g. for personal use, without a
+-- booking or putting the room under maintenance. Blocks may overlap each
+-- other but not a booking.
+CREATE TABLE room_blocks (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    room_id UUID NOT NULL REFERENCES rooms(id) ON DELETE CASCADE,
+    -- Nights from start_date to end_date, both included
+    start_date DATE NOT NULL,
+    end_date DATE NOT NULL,
+    reason VARCHAR(200),
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT chk_room_block_dates CHECK (end_date >= start_date)
+);
+
+CREATE INDEX idx_room_blocks_room_dates ON room_blocks(room_id, start_date, end_date);
+
+SELECT diesel_manage_updated_at('room_blocks');
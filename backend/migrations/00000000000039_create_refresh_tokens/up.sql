@@ -0,0 +1,19 @@
+-- Long-lived tokens exchanged for new access tokens. Only a SHA-256 hash of
+-- each token is kept; every exchange revokes the token and issues the next
+-- one in the same family.
+CREATE TABLE refresh_tokens (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    -- Tokens descending from one login; reusing a revoked one revokes them all
+    family_id UUID NOT NULL,
+    -- Login session the access tokens belong to; not a foreign key, since
+    -- ended sessions are deleted while their tokens stay revoked
+    session_id UUID NOT NULL,
+    token_hash VARCHAR(64) NOT NULL UNIQUE,
+    expires_at TIMESTAMPTZ NOT NULL,
+    revoked_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_refresh_tokens_family_id ON refresh_tokens(family_id);
+CREATE INDEX idx_refresh_tokens_user_id ON refresh_tokens(user_id);
@@ -0,0 +1,13 @@
+-- Access tokens revoked before they expire, by their JWT id. A row is only
+-- needed until the token would have expired anyway.
+CREATE TABLE revoked_tokens (
+    jti UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    revoked_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX idx_revoked_tokens_user_id ON revoked_tokens(user_id);
+
+-- Access tokens of the user issued at or before this time are refused
+ALTER TABLE users ADD COLUMN tokens_not_before TIMESTAMPTZ;
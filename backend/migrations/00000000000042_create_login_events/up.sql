@@ -0,0 +1,16 @@
+-- Staff and guest login attempts against existing accounts, successful or
+-- not, for the admin's login history. Attempts on unknown usernames are not
+-- recorded; the login lockout already covers them.
+CREATE TABLE login_events (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    success BOOLEAN NOT NULL,
+    -- Client IP, from X-Forwarded-For when TRUST_PROXY_HEADERS is on
+    ip VARCHAR(45) NOT NULL,
+    user_agent VARCHAR(512),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_login_events_user_id_created_at ON login_events(user_id, created_at DESC);
+
+ALTER TABLE users ADD COLUMN last_login_at TIMESTAMPTZ;
@@ -0,0 +1,3 @@
+-- Set when an admin chooses an employee's password; the employee can only
+-- change it, or read their own account, until they pick their own
+ALTER TABLE users ADD COLUMN must_change_password BOOLEAN NOT NULL DEFAULT FALSE;
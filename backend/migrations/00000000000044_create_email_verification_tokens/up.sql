@@ -0,0 +1,18 @@
+-- Single-use tokens emailed to guests to confirm the address they
+-- registered with. Only a SHA-256 hash of each token is kept.
+CREATE TABLE email_verification_tokens (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    token_hash VARCHAR(64) NOT NULL UNIQUE,
+    expires_at TIMESTAMPTZ NOT NULL,
+    -- Set when the token is used or replaced by a newer one
+    used_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_email_verification_tokens_user_id ON email_verification_tokens(user_id, created_at DESC);
+
+-- Guests must verify their email before booking. Guests who registered
+-- before verification existed have been booking already and are trusted.
+ALTER TABLE users ADD COLUMN email_verified_at TIMESTAMPTZ;
+UPDATE users SET email_verified_at = created_at WHERE role = 'guest';
@@ -0,0 +1,19 @@
+CREATE OR REPLACE FUNCTION check_single_admin()
+RETURNS TRIGGER AS $$
+DECLARE
+  admin_count INTEGER;
+BEGIN
+  -- Only check if role is being set to admin
+  IF NEW.role = 'admin' AND (OLD.role IS NULL OR OLD.role != 'admin') THEN
+    SELECT COUNT(*) INTO admin_count
+    FROM users
+    WHERE role = 'admin' AND deactivated_at IS NULL AND id != NEW.id;
+    
+    IF admin_count > 0 THEN
+      RAISE EXCEPTION 'Only one admin account is allowed. An admin account already exists.';
+    END IF;
+  END IF;
+  
+  RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
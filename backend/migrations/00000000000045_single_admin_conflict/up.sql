@@ -0,0 +1,21 @@
+-- Report a second admin as a conflict, so the API answers 409 when two
+-- requests race past the check in the application
+CREATE OR REPLACE FUNCTION check_single_admin()
+RETURNS TRIGGER AS $$
+DECLARE
+  admin_count INTEGER;
+BEGIN
+  IF NEW.role = 'admin' AND NEW.deactivated_at IS NULL
+     AND (TG_OP = 'INSERT' OR OLD.role != 'admin' OR OLD.deactivated_at IS NOT NULL) THEN
+    SELECT COUNT(*) INTO admin_count
+    FROM users
+    WHERE role = 'admin' AND deactivated_at IS NULL AND id != NEW.id;
+
+    IF admin_count > 0 THEN
+      RAISE EXCEPTION 'Conflict: Only one admin account is allowed in the system. An admin account already exists.';
+    END IF;
+  END IF;
+
+  RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
@@ -0,0 +1,18 @@
+-- Keys for machine-to-machine integrations such as a channel manager.
+-- Only a SHA-256 hash of each key is kept; the short prefix lets admins
+-- tell keys apart. A key acts with its own role on behalf of the admin
+-- who created it.
+CREATE TABLE api_keys (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    label VARCHAR(100) NOT NULL,
+    key_hash VARCHAR(64) NOT NULL UNIQUE,
+    key_prefix VARCHAR(16) NOT NULL,
+    role user_role NOT NULL CHECK (role IN ('receptionist', 'cleaner')),
+    created_by UUID NOT NULL REFERENCES users(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    -- Updated at most once a minute
+    last_used_at TIMESTAMPTZ,
+    revoked_at TIMESTAMPTZ
+);
+
+CREATE INDEX idx_api_keys_created_at ON api_keys(created_at DESC);
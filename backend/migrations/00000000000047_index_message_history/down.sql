@@ -0,0 +1,2 @@
+CREATE INDEX idx_messages_conversation ON messages(sender_id, receiver_id);
+DROP INDEX idx_messages_conversation_created;
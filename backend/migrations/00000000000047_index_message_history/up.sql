@@ -0,0 +1,5 @@
+-- Serve a page of one direction of a conversation, newest first, straight
+-- from the index; it covers what idx_messages_conversation did
+CREATE INDEX idx_messages_conversation_created
+    ON messages(sender_id, receiver_id, created_at DESC, id DESC);
+DROP INDEX idx_messages_conversation;
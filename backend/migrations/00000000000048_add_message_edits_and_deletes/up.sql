@@ -0,0 +1,6 @@
+-- A sender may edit or delete a message shortly after sending it. A
+-- deleted message keeps its row, without content, so both sides see where
+-- it was.
+ALTER TABLE messages
+    ADD COLUMN edited_at TIMESTAMPTZ,
+    ADD COLUMN deleted_at TIMESTAMPTZ;
@@ -0,0 +1,42 @@
+use axum::{
+    extract::{Extension, Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use uuid::Uuid;
+
+use crate::api::middleware::AuthUser;
+use crate::api::AppState;
+use crate::errors::AppError;
+use crate::services::api_key_service::CreateApiKeyRequest;
+use crate::services::ApiKeyService;
+
+/// API keys, newest first (admin only)
+/// GET /admin/api-keys
+pub async fn list_api_keys(State(state): State<AppState>) -> Result<impl IntoResponse, AppError> {
+    let keys = ApiKeyService::new(state.pool).list()?;
+    Ok((StatusCode::OK, Json(keys)))
+}
+
+/// Create an API key (admin only). The response is the only place the key
+/// itself is shown.
+/// POST /admin/api-keys
+pub async fn create_api_key(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(payload): Json<CreateApiKeyRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let created = ApiKeyService::new(state.pool).create(&payload, auth_user.user_id)?;
+    Ok((StatusCode::CREATED, Json(created)))
+}
+
+/// Revoke an API key (admin only)
+/// POST /admin/api-keys/:id/revoke
+pub async fn revoke_api_key(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    let key = ApiKeyService::new(state.pool).revoke(id)?;
+    Ok((StatusCode::OK, Json(key)))
+}
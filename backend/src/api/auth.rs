@@ -1,11 +1,26 @@
-use axum::{extract::State, http::StatusCode, response::IntoResponse, Extension, Json};
+use std::net::SocketAddr;
+use std::time::Instant;
+
+use axum::{
+    extract::{ConnectInfo, State},
+    http::{
+        header::{AUTHORIZATION, USER_AGENT},
+        HeaderMap, StatusCode,
+    },
+    response::IntoResponse,
+    Extension, Json,
+};
 use serde::Deserialize;
 
 use crate::api::middleware::AuthUser;
+use crate::api::public_bookings::client_ip;
 use crate::api::AppState;
-use crate::errors::AppError;
-use crate::models::{UserInfo, UserRole};
-use crate::services::{AuthService, ChangePasswordRequest, CreateUserRequest, LoginRequest};
+use crate::errors::{AppError, AppResult};
+use crate::models::{ProfileResponse, UserRole};
+use crate::services::{
+    AuthService, ChangePasswordRequest, CreateUserRequest, LoginEventService, LoginOrigin, LoginRequest,
+    RefreshRequest, UpdateProfileRequest,
+};
 
 /// Login request DTO
 #[derive(Debug, Deserialize)]
@@ -20,25 +35,125 @@ pub struct CreateUserDto {
     pub username: String,
     pub password: String,
     pub role: UserRole,
+    #[serde(default)]
+    pub must_change_password: bool,
 }
 
-/// Login handler
+/// Client IP and user agent of a login request, for the login history
+pub(crate) fn login_origin(
+    state: &AppState,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    headers: &HeaderMap,
+) -> LoginOrigin {
+    LoginOrigin {
+        ip: client_ip(connect_info, headers, state.trust_proxy_headers),
+        user_agent: headers
+            .get(USER_AGENT)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string),
+    }
+}
+
+/// Logins do not fail because their history could not be written
+pub(crate) fn warn_unrecorded_login(result: AppResult<()>) {
+    if let Err(e) = result {
+        tracing::warn!("Could not record login event: {}", e);
+    }
+}
+
+/// Login handler. Repeated failures lock the username and the client IP;
+/// a locked login is refused before the password is checked, so the answer
+/// does not tell whether it was right. Attempts that get that far are added
+/// to the account's login history.
 pub async fn login(
     State(state): State<AppState>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    headers: HeaderMap,
     Json(payload): Json<LoginDto>,
 ) -> Result<impl IntoResponse, AppError> {
-    let auth_service = AuthService::new(state.pool, state.jwt_secret);
+    let origin = login_origin(&state, connect_info, &headers);
+    let keys = [
+        format!("user:{}", payload.username.trim().to_lowercase()),
+        format!("ip:{}", origin.ip),
+    ];
+    let now = Instant::now();
+
+    if let Some(retry_after) = keys.iter().filter_map(|key| state.login_attempts.locked_for(key, now)).max() {
+        return Err(AppError::RateLimited(
+            "Too many failed login attempts. Please try again later.".to_string(),
+            retry_after,
+        ));
+    }
+
+    let auth_service =
+        AuthService::with_token_lifetime(state.pool.clone(), state.jwt_secret.clone(), state.access_token_lifetime)
+            .with_issuer(state.token_issuer.clone());
+    let login_events = LoginEventService::new(state.pool.clone());
 
     let request = LoginRequest {
         username: payload.username,
         password: payload.password,
     };
 
-    let response = auth_service.login(&request)?;
+    match auth_service.login(&request) {
+        Ok(response) => {
+            for key in &keys {
+                state.login_attempts.reset(key);
+            }
+            warn_unrecorded_login(login_events.record(response.user.id, true, &origin));
+            Ok((StatusCode::OK, Json(response)))
+        }
+        Err(err @ AppError::Unauthorized(_)) => {
+            for key in &keys {
+                if let Some(lock) = state.login_attempts.record_failure(key, now) {
+                    tracing::warn!("Login locked for {} for {}s after repeated failures", key, lock.as_secs());
+                }
+            }
+            warn_unrecorded_login(login_events.record_staff_failure(&request.username, &origin));
+            Err(err)
+        }
+        Err(err) => Err(err),
+    }
+}
+
+/// Exchange a refresh token for a new access token and refresh token
+/// POST /auth/refresh
+pub async fn refresh(
+    State(state): State<AppState>,
+    Json(payload): Json<RefreshRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let auth_service = AuthService::with_token_lifetime(state.pool, state.jwt_secret, state.access_token_lifetime)
+        .with_issuer(state.token_issuer);
+
+    let response = auth_service.refresh(&payload.refresh_token)?;
 
     Ok((StatusCode::OK, Json(response)))
 }
 
+/// Revoke a refresh token and end its session. An access token sent along
+/// is revoked as well.
+/// POST /auth/logout
+pub async fn logout(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<RefreshRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let auth_service = AuthService::new(state.pool, state.jwt_secret)
+        .with_issuer(state.token_issuer)
+        .with_revocations(state.revocations);
+
+    let access_token = headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    if let Some(claims) = access_token.and_then(|token| auth_service.validate_token(token).ok()) {
+        auth_service.revocation_service().revoke_token(&claims)?;
+    }
+    auth_service.logout(&payload.refresh_token)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
 /// Get current user handler (requires auth)
 pub async fn me(
     State(state): State<AppState>,
@@ -47,9 +162,23 @@ pub async fn me(
     let auth_service = AuthService::new(state.pool, state.jwt_secret);
 
     let user = auth_service.get_user_by_id(auth_user.user_id)?;
-    let user_info: UserInfo = user.into();
+    let profile: ProfileResponse = user.into();
+
+    Ok((StatusCode::OK, Json(profile)))
+}
+
+/// Update the current user's own profile
+pub async fn update_me(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(payload): Json<UpdateProfileRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let auth_service = AuthService::new(state.pool, state.jwt_secret);
+
+    let user = auth_service.update_profile(auth_user.user_id, &payload)?;
+    let profile: ProfileResponse = user.into();
 
-    Ok((StatusCode::OK, Json(user_info)))
+    Ok((StatusCode::OK, Json(profile)))
 }
 
 /// Create user handler (requires admin)
@@ -64,6 +193,7 @@ pub async fn create_user(
         username: payload.username,
         password: payload.password,
         role: payload.role,
+        must_change_password: payload.must_change_password,
     };
 
     let user_info = auth_service.create_user(&request)?;
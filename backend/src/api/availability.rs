@@ -0,0 +1,96 @@
+use axum::{
+    extract::{Extension, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::api::middleware::{is_staff_role, AuthUser};
+use crate::api::AppState;
+use crate::errors::AppError;
+use crate::models::RoomType;
+use crate::services::availability_service::QuickRoomTypeAvailability;
+use crate::services::{AvailabilityCalendarDay, AvailabilityService};
+
+/// Query parameters for the availability calendar
+#[derive(Debug, Deserialize)]
+pub struct AvailabilityCalendarQuery {
+    pub room_type: Option<RoomType>,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+}
+
+/// Availability calendar response (counts only, no room or guest details)
+#[derive(Debug, Serialize)]
+pub struct AvailabilityCalendarResponse {
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub days: Vec<AvailabilityCalendarDay>,
+}
+
+/// Free room counts per room type for each date in a range
+/// GET /availability/calendar?room_type=&start_date=&end_date=
+pub async fn get_calendar(
+    State(state): State<AppState>,
+    Query(query): Query<AvailabilityCalendarQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let service = AvailabilityService::new(state.pool);
+    let days = service.get_calendar(query.room_type, query.start_date, query.end_date)?;
+
+    Ok((
+        StatusCode::OK,
+        Json(AvailabilityCalendarResponse {
+            start_date: query.start_date,
+            end_date: query.end_date,
+            days,
+        }),
+    ))
+}
+
+/// Query parameters for the quick availability check
+#[derive(Debug, Deserialize)]
+pub struct QuickAvailabilityQuery {
+    pub check_in_date: NaiveDate,
+    pub check_out_date: NaiveDate,
+    pub guests: u32,
+}
+
+/// Quick availability response
+#[derive(Debug, Serialize)]
+pub struct QuickAvailabilityResponse {
+    pub check_in_date: NaiveDate,
+    pub check_out_date: NaiveDate,
+    pub guests: u32,
+    pub room_types: Vec<QuickRoomTypeAvailability>,
+}
+
+/// Compact per-room-type availability for a stay and party size, with a few
+/// ready-to-book candidate rooms
+/// GET /staff/quick-availability?check_in_date=&check_out_date=&guests=
+pub async fn get_quick_availability(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(query): Query<QuickAvailabilityQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    if !is_staff_role(auth_user.role) {
+        return Err(AppError::Forbidden(
+            "Only front desk staff can check availability".to_string(),
+        ));
+    }
+
+    let service = AvailabilityService::new(state.pool);
+    let room_types =
+        service.quick_availability(query.check_in_date, query.check_out_date, query.guests)?;
+
+    Ok((
+        StatusCode::OK,
+        Json(QuickAvailabilityResponse {
+            check_in_date: query.check_in_date,
+            check_out_date: query.check_out_date,
+            guests: query.guests,
+            room_types,
+        }),
+    ))
+}
@@ -0,0 +1,31 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::api::AppState;
+use crate::errors::AppError;
+use crate::models::BookingEmailStatus;
+use crate::services::BookingEmailService;
+
+/// Query parameters for the booking email log
+#[derive(Debug, Deserialize)]
+pub struct ListBookingEmailsQuery {
+    pub booking_id: Option<Uuid>,
+    pub status: Option<BookingEmailStatus>,
+}
+
+/// Confirmation and cancellation emails sent to guests, newest first
+/// GET /admin/booking-emails
+pub async fn list_booking_emails(
+    State(state): State<AppState>,
+    Query(query): Query<ListBookingEmailsQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let service = BookingEmailService::new(state.pool);
+    let emails = service.list_emails(query.booking_id, query.status)?;
+    Ok((StatusCode::OK, Json(emails)))
+}
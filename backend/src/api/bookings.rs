@@ -1,32 +1,37 @@
 use axum::{
-    extract::{Path, Query, State},
-    http::StatusCode,
-    response::IntoResponse,
+    body::Body,
+    extract::{Extension, Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
     Json,
 };
 use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
 use uuid::Uuid;
 
+use crate::api::middleware::{is_staff_role, AuthUser};
 use crate::api::AppState;
 use crate::errors::AppError;
-use crate::models::BookingStatus;
+use crate::models::{BookingStatus, UserRole};
+use crate::scheduler;
+use crate::services::booking_service::{
+    BookingExportFilter, BookingListFilter, StaffBookingRequest, BOOKING_EXPORT_HEADER, EXPORT_CHUNK_SIZE,
+};
 use crate::services::BookingService;
 
-/// Create booking request DTO
+/// Create group booking request DTO
 #[derive(Debug, Deserialize)]
-pub struct CreateBookingDto {
+pub struct CreateGroupBookingDto {
     pub guest_name: String,
-    pub room_id: Uuid,
+    pub room_ids: Vec<Uuid>,
     pub check_in_date: NaiveDate,
     pub check_out_date: NaiveDate,
-    #[serde(default)]
-    pub price: Option<bigdecimal::BigDecimal>,
 }
 
 /// Update booking request DTO
 #[derive(Debug, Deserialize)]
-#[allow(dead_code)]
 pub struct UpdateBookingDto {
     pub guest_name: Option<String>,
     pub check_in_date: Option<NaiveDate>,
@@ -46,22 +51,49 @@ pub struct ListBookingsQuery {
     pub guest_name: Option<String>,
     pub from_date: Option<NaiveDate>,
     pub to_date: Option<NaiveDate>,
+    pub group_reference: Option<String>,
+    pub room_id: Option<Uuid>,
+    /// "staff" or "guest"
+    pub creation_source: Option<String>,
 }
 
-/// Create a new booking
+/// Create a new booking, recording the staff member who took it. Only admins
+/// may set `override_conflict`; the response then lists the overridden stays.
 pub async fn create_booking(
     State(state): State<AppState>,
-    Json(payload): Json<CreateBookingDto>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(payload): Json<StaffBookingRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    require_staff_actor(&auth_user, "create bookings")?;
+    if payload.override_conflict && auth_user.role != UserRole::Admin {
+        return Err(AppError::Forbidden(
+            "Only admins can override availability conflicts".to_string(),
+        ));
+    }
+
+    let booking_service = BookingService::new(state.pool);
+    let created = booking_service.create_staff_booking(&payload, Some(auth_user.user_id))?;
+    Ok((StatusCode::CREATED, Json(created)))
+}
+
+/// Book several rooms for one party under a shared group reference
+/// POST /bookings/group
+pub async fn create_group_booking(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(payload): Json<CreateGroupBookingDto>,
 ) -> Result<impl IntoResponse, AppError> {
+    require_staff_actor(&auth_user, "create group bookings")?;
+
     let booking_service = BookingService::new(state.pool);
-    let booking = booking_service.create_booking(
+    let bookings = booking_service.create_group_booking(
         &payload.guest_name,
-        payload.room_id,
+        payload.room_ids,
         payload.check_in_date,
         payload.check_out_date,
-        payload.price,
+        auth_user.user_id,
     )?;
-    Ok((StatusCode::CREATED, Json(booking)))
+    Ok((StatusCode::CREATED, Json(bookings)))
 }
 
 /// List bookings with optional filters
@@ -69,22 +101,38 @@ pub async fn list_bookings(
     State(state): State<AppState>,
     Query(query): Query<ListBookingsQuery>,
 ) -> Result<impl IntoResponse, AppError> {
-    let booking_service = BookingService::new(state.pool.clone());
-    
-    // Auto-update statuses based on today's date before fetching the list
-    let mut conn = state.pool.get().map_err(|e| AppError::DatabaseError(e.to_string()))?;
-    booking_service.handle_stale_bookings(&mut conn)
-        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
-
-    // let (no_show_count, overstay_count) = booking_service.handle_stale_bookings(&mut conn)
-    //     .map_err(|e| AppError::DatabaseError(e.to_string()))?;
-
-    let bookings = booking_service.list_bookings(
-        query.status, 
-        query.guest_name.as_deref(),
-        query.from_date, 
-        query.to_date
-    )?;
+    // Stale statuses are synchronized by the background scheduler
+    let booking_service = BookingService::new(state.pool);
+
+    let bookings = booking_service.list_bookings(&BookingListFilter {
+        status: query.status,
+        guest_name: query.guest_name.as_deref(),
+        from_date: query.from_date,
+        to_date: query.to_date,
+        group_reference: query.group_reference.as_deref(),
+        room_id: query.room_id,
+        creation_source: query.creation_source.as_deref(),
+    })?;
+    Ok((StatusCode::OK, Json(bookings)))
+}
+
+/// Query parameters for searching bookings
+#[derive(Debug, Deserialize)]
+pub struct SearchBookingsQuery {
+    pub q: String,
+}
+
+/// Search bookings by reference prefix, guest name, guest phone or room number
+/// GET /bookings/search?q=query
+pub async fn search_bookings(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(query): Query<SearchBookingsQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    require_staff_actor(&auth_user, "search bookings")?;
+
+    let booking_service = BookingService::new(state.pool);
+    let bookings = booking_service.search_bookings(&query.q)?;
     Ok((StatusCode::OK, Json(bookings)))
 }
 
@@ -98,68 +146,177 @@ pub async fn get_booking(
     Ok((StatusCode::OK, Json(booking)))
 }
 
-/// Get a booking by reference
+/// Get a booking by reference, with the guest's details. Guests without an
+/// account use GET /public/bookings/:reference/status instead.
+/// GET /bookings/reference/:reference
 pub async fn get_booking_by_reference(
     State(state): State<AppState>,
     Path(reference): Path<String>,
+    Extension(auth_user): Extension<AuthUser>,
 ) -> Result<impl IntoResponse, AppError> {
+    require_staff_actor(&auth_user, "look up bookings by reference")?;
+
     let booking_service = BookingService::new(state.pool);
     let booking = booking_service.get_booking_by_reference(&reference)?;
     Ok((StatusCode::OK, Json(booking)))
 }
 
-/// Update a booking
+/// Update a booking's guest name or dates, recording the change in its
+/// modification history
+/// PATCH /bookings/:id
 pub async fn update_booking(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
+    Extension(auth_user): Extension<AuthUser>,
     Json(payload): Json<UpdateBookingDto>,
 ) -> Result<impl IntoResponse, AppError> {
-    // For MVP, we only allow updating guest_name before check-in
-    // Date changes would require re-validation of availability
-    let booking_service = BookingService::new(state.pool);
-    
-    // Get current booking
-    let current = booking_service.get_booking_by_id(id)?;
-    
-    // Only allow updates for upcoming bookings
-    if current.status != BookingStatus::Upcoming {
-        return Err(AppError::ValidationError(
-            "Can only update upcoming bookings".to_string(),
+    let booking_service = BookingService::new(state.pool);
+    let booking = booking_service.update_booking(
+        id,
+        payload.guest_name.as_deref(),
+        payload.check_in_date,
+        payload.check_out_date,
+        Some(auth_user.user_id),
+    )?;
+    let booking = booking_service.get_booking_with_room(booking.id)?;
+    Ok((StatusCode::OK, Json(booking)))
+}
+
+/// List a booking's field-level edits with before/after values
+/// GET /bookings/:id/modifications
+pub async fn get_booking_modifications(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<impl IntoResponse, AppError> {
+    if !is_staff_role(auth_user.role) {
+        return Err(AppError::Forbidden(
+            "Only staff can view booking modifications".to_string(),
         ));
     }
-    
-    // For date changes, validate availability
-    if payload.check_in_date.is_some() || payload.check_out_date.is_some() {
-        let new_check_in = payload.check_in_date.unwrap_or(current.check_in_date);
-        let new_check_out = payload.check_out_date.unwrap_or(current.check_out_date);
-        
-        // Validate dates
-        booking_service.validate_dates(new_check_in, new_check_out)?;
-        
-        // Check availability (excluding current booking)
-        if !booking_service.check_availability(current.room_id, new_check_in, new_check_out, Some(id))? {
-            return Err(AppError::RoomUnavailable(
-                "Room is not available for the selected dates".to_string(),
-            ));
-        }
+
+    let booking_service = BookingService::new(state.pool);
+    let modifications = booking_service.list_modifications(id)?;
+    Ok((StatusCode::OK, Json(modifications)))
+}
+
+/// Add booking note request
+#[derive(Debug, Deserialize)]
+pub struct AddBookingNoteDto {
+    pub note: String,
+}
+
+/// List a booking's staff notes, newest first
+/// GET /bookings/:id/notes
+pub async fn list_booking_notes(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<impl IntoResponse, AppError> {
+    require_staff_actor(&auth_user, "view booking notes")?;
+
+    let booking_service = BookingService::new(state.pool);
+    let notes = booking_service.list_booking_notes(id)?;
+    Ok((StatusCode::OK, Json(notes)))
+}
+
+/// Attach a note to a booking, authored by the logged-in staff member
+/// POST /bookings/:id/notes
+pub async fn add_booking_note(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(payload): Json<AddBookingNoteDto>,
+) -> Result<impl IntoResponse, AppError> {
+    require_staff_actor(&auth_user, "add booking notes")?;
+
+    let booking_service = BookingService::new(state.pool);
+    let note = booking_service.add_booking_note(id, auth_user.user_id, &payload.note)?;
+    Ok((StatusCode::CREATED, Json(note)))
+}
+
+/// Query parameters for a booking timeline
+#[derive(Debug, Deserialize)]
+pub struct BookingTimelineQuery {
+    pub page: Option<u64>,
+    pub per_page: Option<u64>,
+}
+
+/// Get a booking's history as one chronological feed.
+/// Staff see everything; the owning guest sees a redacted subset.
+/// GET /bookings/:id/timeline?page=1&per_page=50
+pub async fn get_booking_timeline(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(query): Query<BookingTimelineQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let guest_id = if is_staff_role(auth_user.role) {
+        None
+    } else if auth_user.role == UserRole::Guest {
+        Some(auth_user.user_id)
+    } else {
+        return Err(AppError::Forbidden(
+            "Only staff or the booking's guest can view its timeline".to_string(),
+        ));
+    };
+
+    let booking_service = BookingService::new(state.pool);
+    let timeline = booking_service.booking_timeline(id, guest_id, query.page, query.per_page)?;
+    Ok((StatusCode::OK, Json(timeline)))
+}
+
+/// List a booking's status and date changes with who made them, oldest first
+/// GET /bookings/:id/history
+pub async fn get_booking_history(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<impl IntoResponse, AppError> {
+    require_staff_actor(&auth_user, "view booking history")?;
+
+    let booking_service = BookingService::new(state.pool);
+    let events = booking_service.list_events(id)?;
+    Ok((StatusCode::OK, Json(events)))
+}
+
+/// Only front-desk staff may act on bookings through these routes
+fn require_staff_actor(auth_user: &AuthUser, action: &str) -> Result<(), AppError> {
+    if is_staff_role(auth_user.role) {
+        Ok(())
+    } else {
+        Err(AppError::Forbidden(format!("Only staff can {}", action)))
     }
-    
-    // Note: For MVP, we're returning the current booking
-    // Full update implementation would use UpdateBooking changeset
-    Ok((StatusCode::OK, Json(current)))
 }
 
 /// Check in a guest
 pub async fn check_in(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
+    Extension(auth_user): Extension<AuthUser>,
     _payload: Json<CheckInDto>,
 ) -> Result<impl IntoResponse, AppError> {
+    require_staff_actor(&auth_user, "check in guests")?;
+
     let booking_service = BookingService::new(state.pool);
-    let booking = booking_service.check_in(id)?;
+    let booking = booking_service.check_in(id, auth_user.user_id)?;
     Ok((StatusCode::OK, Json(booking)))
 }
 
+/// Create a booking for a walk-in guest and check them in at once
+/// POST /bookings/walk-in
+pub async fn create_walk_in(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(payload): Json<StaffBookingRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    require_staff_actor(&auth_user, "check in walk-in guests")?;
+
+    let booking_service = BookingService::new(state.pool);
+    let booking = booking_service.create_walk_in(&payload, auth_user.user_id)?;
+    Ok((StatusCode::CREATED, Json(booking)))
+}
+
 /// Check out request DTO
 #[derive(Debug, Deserialize)]
 pub struct CheckOutDto {
@@ -171,10 +328,13 @@ pub struct CheckOutDto {
 pub async fn check_out(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
+    Extension(auth_user): Extension<AuthUser>,
     Json(payload): Json<CheckOutDto>,
 ) -> Result<impl IntoResponse, AppError> {
+    require_staff_actor(&auth_user, "check out guests")?;
+
     let booking_service = BookingService::new(state.pool);
-    let booking = booking_service.check_out(id, payload.confirm_early)?;
+    let booking = booking_service.check_out(id, payload.confirm_early, auth_user.user_id)?;
     Ok((StatusCode::OK, Json(booking)))
 }
 
@@ -182,43 +342,174 @@ pub async fn check_out(
 pub async fn cancel(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
+    Extension(auth_user): Extension<AuthUser>,
 ) -> Result<impl IntoResponse, AppError> {
+    require_staff_actor(&auth_user, "cancel bookings")?;
+
     let booking_service = BookingService::new(state.pool);
-    let booking = booking_service.cancel(id)?;
+    let booking = booking_service.cancel(id, auth_user.user_id)?;
+    Ok((StatusCode::OK, Json(booking)))
+}
+
+/// Room move request
+#[derive(Debug, Deserialize)]
+pub struct MoveRoomDto {
+    pub new_room_id: Uuid,
+}
+
+/// Move an upcoming or checked-in booking to another room
+/// POST /bookings/:id/move-room
+pub async fn move_room(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(payload): Json<MoveRoomDto>,
+) -> Result<impl IntoResponse, AppError> {
+    require_staff_actor(&auth_user, "move bookings between rooms")?;
+
+    let booking_service = BookingService::new(state.pool);
+    let booking = booking_service.move_room(id, payload.new_room_id, auth_user.user_id)?;
     Ok((StatusCode::OK, Json(booking)))
 }
 
 /// Sync booking statuses response
-#[allow(dead_code)]
 #[derive(Debug, Serialize)]
 pub struct SyncBookingStatusesResponse {
     pub message: String,
-    pub overstay_count: Option<usize>,
+    pub no_show_count: usize,
+    pub overstay_count: usize,
+    /// Rooms that now hold an overstaying guest
+    pub overstay_room_ids: Vec<Uuid>,
 }
 
-/// Sync booking statuses
-/// 
+/// Sync booking statuses now instead of waiting for the scheduler
+/// POST /bookings/sync-statuses
+///
 /// Updates stale bookings:
+/// - 'Upcoming' bookings with check_in_date before today → 'NoShow' (with a pending no-show charge)
 /// - 'CheckedIn' bookings with check_out_date before today → 'Overstay'
-#[allow(dead_code)]
 pub async fn sync_booking_statuses(
     State(state): State<AppState>,
 ) -> Result<impl IntoResponse, AppError> {
-    let booking_service = BookingService::new(state.pool.clone());
-    
-    let mut conn = state.pool
-        .get()
-        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
-
-    let (_no_show_count, overstay_count) = booking_service
-        .handle_stale_bookings(&mut conn)
-        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+    let sweep = scheduler::sync_stale_bookings(&state.pool, &state.jobs)
+        .await
+        .ok_or_else(|| AppError::Conflict("A booking status sync is already running".to_string()))?
+        .map_err(AppError::DatabaseError)?;
 
     Ok((
         StatusCode::OK,
         Json(SyncBookingStatusesResponse {
             message: "Booking statuses synchronized successfully".to_string(),
-            overstay_count: Some(overstay_count),
+            no_show_count: sweep.no_shows,
+            overstay_count: sweep.overstays,
+            overstay_room_ids: sweep.overstay_room_ids,
         }),
     ))
 }
+
+/// Query parameters for the double-booking report
+#[derive(Debug, Deserialize)]
+pub struct BookingConflictsQuery {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub room_id: Option<Uuid>,
+}
+
+/// Pairs of active bookings holding the same room on the same nights
+/// GET /admin/bookings/conflicts?from=2025-01-01&to=2025-12-31&room_id=...
+pub async fn list_booking_conflicts(
+    State(state): State<AppState>,
+    Query(query): Query<BookingConflictsQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let booking_service = BookingService::new(state.pool);
+    let conflicts = booking_service.find_overlapping_bookings(query.from, query.to, query.room_id)?;
+    Ok((StatusCode::OK, Json(conflicts)))
+}
+
+/// Query parameters for the booking statistics
+#[derive(Debug, Deserialize)]
+pub struct BookingStatsQuery {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+}
+
+/// Bookings created per day and source, cancellation rate and nights per
+/// booking, for the admin dashboard. The range is capped at one year.
+/// GET /admin/stats/bookings?from=2025-05-01&to=2025-05-30
+pub async fn get_booking_stats(
+    State(state): State<AppState>,
+    Query(query): Query<BookingStatsQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let booking_service = BookingService::new(state.pool);
+    let stats = booking_service.get_booking_stats(query.from, query.to)?;
+    Ok((StatusCode::OK, Json(stats)))
+}
+
+/// Query parameters for the bookings export
+#[derive(Debug, Deserialize)]
+pub struct ExportBookingsQuery {
+    pub from_date: Option<NaiveDate>,
+    pub to_date: Option<NaiveDate>,
+    pub status: Option<BookingStatus>,
+}
+
+/// Download bookings as CSV for accounting, streamed in chunks
+/// GET /admin/bookings/export?from_date=2025-06-01&to_date=2025-06-30&status=checked_out
+pub async fn export_bookings(
+    State(state): State<AppState>,
+    Query(query): Query<ExportBookingsQuery>,
+) -> Result<Response, AppError> {
+    if let (Some(from), Some(to)) = (query.from_date, query.to_date) {
+        if from > to {
+            return Err(AppError::ValidationError(
+                "from_date must not be after to_date".to_string(),
+            ));
+        }
+    }
+
+    let filename = match (query.from_date, query.to_date) {
+        (Some(from), Some(to)) => format!("bookings-{}-to-{}.csv", from, to),
+        (Some(from), None) => format!("bookings-from-{}.csv", from),
+        (None, Some(to)) => format!("bookings-to-{}.csv", to),
+        (None, None) => "bookings.csv".to_string(),
+    };
+    let filter = BookingExportFilter {
+        from_date: query.from_date,
+        to_date: query.to_date,
+        status: query.status,
+    };
+
+    // A small buffer keeps at most a couple of chunks in memory when the
+    // client reads slower than the database
+    let (tx, rx) = mpsc::channel::<Result<String, std::io::Error>>(2);
+    let booking_service = BookingService::new(state.pool);
+    tokio::task::spawn_blocking(move || {
+        if tx.blocking_send(Ok(BOOKING_EXPORT_HEADER.to_string())).is_err() {
+            return;
+        }
+
+        let result = booking_service.export_bookings(&filter, EXPORT_CHUNK_SIZE, |rows| {
+            let chunk: String = rows.iter().map(|row| row.to_csv_line()).collect();
+            tx.blocking_send(Ok(chunk)).is_ok()
+        });
+
+        if let Err(e) = result {
+            // Headers are already sent, so aborting the body is all that is left
+            tracing::error!("Bookings export failed: {}", e);
+            let _ = tx.blocking_send(Err(std::io::Error::other(e.to_string())));
+        }
+    });
+
+    Ok((
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", filename),
+            ),
+        ],
+        Body::from_stream(ReceiverStream::new(rx)),
+    )
+        .into_response())
+}
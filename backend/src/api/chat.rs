@@ -1,23 +1,39 @@
 use axum::{
     extract::{
         ws::{Message as WsMessage, WebSocket, WebSocketUpgrade},
-        Extension, Multipart, Query, State,
+        multipart::MultipartError,
+        Extension, Multipart, Path, Query, State,
     },
-    response::IntoResponse,
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
     Json,
 };
 use diesel::prelude::*;
 use futures::{sink::SinkExt, stream::StreamExt};
-use std::{collections::HashMap, sync::Arc, sync::Mutex};
-use tokio::sync::broadcast;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tokio::sync::{broadcast, mpsc};
 use uuid::Uuid;
 use crate::{
-    api::{middleware::AuthUser, AppState},
+    api::{middleware::{check_session, AuthUser}, AppState},
+    config::ChatLimits,
     db::get_conn,
     errors::{AppError, AppResult},
     models::{message::*, user::*},
     schema::{messages, users},
-    services::ai_service::AiService,
+    services::ai_service::{extract_room_photo, AiService},
+    services::auth_service::PASSWORD_CHANGE_REQUIRED_MESSAGE,
+    services::chat_privacy_service::{
+        export_csv, ChatExportFormat, ChatHistoryScope, ChatPrivacyService,
+        PRIVACY_REQUESTS_PER_HOUR,
+    },
+    services::rate_limit_service::{FixedWindowLimiter, TokenBucketLimiter},
+    services::maintenance_service::READ_ONLY_MESSAGE,
+    services::AuditService,
+    utils::redact::message_content_for_log,
 };
 use serde::{Deserialize, Serialize};
 use chrono::Utc;
@@ -25,13 +41,124 @@ use chrono::Utc;
 // Global state for chat connections
 #[derive(Clone)]
 pub struct ChatState {
-    pub active_connections: Arc<Mutex<HashMap<Uuid, broadcast::Sender<String>>>>,
+    /// Users with the chat open, by id
+    pub active_connections: Arc<Mutex<HashMap<Uuid, UserConnections>>>,
+    /// Chat export and deletion requests per guest
+    pub privacy_requests: Arc<FixedWindowLimiter>,
+    /// Messages and image uploads per user
+    pub sent_messages: Arc<TokenBucketLimiter>,
+    /// Messages to Pupinn per user, on top of `sent_messages`; going over
+    /// it only mutes the user towards Pupinn
+    pub assistant_messages: Arc<TokenBucketLimiter>,
+}
+
+/// One channel per user, shared by all of their open sockets (say a phone
+/// and a laptop); each socket holds a receiver
+pub struct UserConnections {
+    pub tx: broadcast::Sender<String>,
+    /// Decides which connected users see the user's presence
+    pub role: UserRole,
 }
 
 impl Default for ChatState {
     fn default() -> Self {
+        Self::new(ChatLimits::default())
+    }
+}
+
+impl ChatState {
+    pub fn new(limits: ChatLimits) -> Self {
         Self {
             active_connections: Arc::new(Mutex::new(HashMap::new())),
+            privacy_requests: Arc::new(FixedWindowLimiter::new(
+                PRIVACY_REQUESTS_PER_HOUR,
+                Duration::from_secs(3600),
+            )),
+            sent_messages: Arc::new(TokenBucketLimiter::new(limits.messages, limits.window, limits.mute)),
+            assistant_messages: Arc::new(TokenBucketLimiter::new(
+                limits.assistant_messages,
+                limits.window,
+                limits.mute,
+            )),
+        }
+    }
+
+    /// Count a message or upload against the user's limits; one to Pupinn
+    /// also counts against the stricter assistant limit
+    ///
+    /// # Returns
+    /// * `Err(retry_after)` - The user is muted for sending too much
+    pub fn check_send_rate(&self, user_id: Uuid, to_assistant: bool, now: Instant) -> Result<(), Duration> {
+        let key = user_id.to_string();
+        self.sent_messages.check(&key, now)?;
+        if to_assistant {
+            self.assistant_messages.check(&key, now)?;
+        }
+        Ok(())
+    }
+
+    /// Open a receiver for one of a user's sockets. The user's first socket
+    /// announces them online.
+    pub fn subscribe(&self, user_id: Uuid, role: UserRole) -> broadcast::Receiver<String> {
+        let mut connections = self.active_connections.lock().unwrap();
+        let user = connections.entry(user_id).or_insert_with(|| UserConnections {
+            tx: broadcast::channel(100).0,
+            role,
+        });
+        let first = user.tx.receiver_count() == 0;
+        let rx = user.tx.subscribe();
+        if first {
+            Self::announce_presence(&connections, user_id, role, true);
+        }
+        rx
+    }
+
+    /// Deliver a message to every open socket of a user. Returns whether
+    /// the user had any.
+    pub fn send_to(&self, user_id: Uuid, message: String) -> bool {
+        let connections = self.active_connections.lock().unwrap();
+        connections
+            .get(&user_id)
+            .is_some_and(|user| user.tx.send(message).is_ok())
+    }
+
+    /// Forget a user's channel once the receiver of their last socket has
+    /// been dropped, announcing them offline
+    pub fn disconnect(&self, user_id: Uuid) {
+        let mut connections = self.active_connections.lock().unwrap();
+        if let Some(user) = connections.get(&user_id).filter(|user| user.tx.receiver_count() == 0) {
+            let role = user.role;
+            connections.remove(&user_id);
+            Self::announce_presence(&connections, user_id, role, false);
+        }
+    }
+
+    /// Whether a user has the chat open anywhere
+    pub fn is_online(&self, user_id: Uuid) -> bool {
+        let connections = self.active_connections.lock().unwrap();
+        connections
+            .get(&user_id)
+            .is_some_and(|user| user.tx.receiver_count() > 0)
+    }
+
+    /// Tell the connected users who may chat with a user that they came
+    /// online or went offline
+    fn announce_presence(
+        connections: &HashMap<Uuid, UserConnections>,
+        user_id: Uuid,
+        role: UserRole,
+        online: bool,
+    ) {
+        let frame = serde_json::json!({
+            "type": "presence",
+            "user_id": user_id,
+            "online": online,
+        })
+        .to_string();
+        for (other_id, other) in connections {
+            if *other_id != user_id && can_chat(role, other.role) {
+                let _ = other.tx.send(frame.clone());
+            }
         }
     }
 }
@@ -39,6 +166,24 @@ impl Default for ChatState {
 #[derive(Deserialize)]
 pub struct ChatHistoryParams {
     other_user_id: Uuid,
+    /// Page size for /chat/v2/history, see `HISTORY_PAGE_DEFAULT`
+    limit: Option<i64>,
+    /// Only messages older than this message id or RFC 3339 timestamp
+    before: Option<String>,
+}
+
+/// Messages in a history page unless the client asks for another size
+pub const HISTORY_PAGE_DEFAULT: i64 = 50;
+/// Largest history page a client may ask for
+pub const HISTORY_PAGE_MAX: i64 = 200;
+
+#[derive(Serialize)]
+pub struct ChatHistoryPage {
+    /// Newest first
+    messages: Vec<MessageResponse>,
+    /// `before` for the next, older page; none once the conversation's
+    /// first message is in this one
+    next_cursor: Option<Uuid>,
 }
 
 #[derive(Serialize, Clone)]
@@ -47,6 +192,8 @@ pub struct Contact {
     name: String,
     role: UserRole,
     unread_count: i64,
+    /// Has the chat open; Pupinn always answers
+    online: bool,
 }
 
 #[derive(Serialize, Clone)]
@@ -58,6 +205,55 @@ pub struct MessageResponse {
     image_url: Option<String>,
     is_read: bool,
     created_at: chrono::DateTime<Utc>,
+    edited_at: Option<chrono::DateTime<Utc>>,
+    /// Deleted by its sender; content is empty and there is no image
+    deleted: bool,
+}
+
+impl From<Message> for MessageResponse {
+    fn from(m: Message) -> Self {
+        let deleted = m.deleted_at.is_some();
+        Self {
+            id: m.id,
+            sender_id: m.sender_id,
+            receiver_id: m.receiver_id,
+            content: if deleted { String::new() } else { m.content },
+            image_url: if deleted { None } else { m.image_url },
+            is_read: m.is_read,
+            created_at: m.created_at,
+            edited_at: m.edited_at,
+            deleted,
+        }
+    }
+}
+
+/// Most messages one sync returns; a client with more missed messages asks
+/// again from the last one it got
+pub const SYNC_LIMIT: i64 = 500;
+
+#[derive(Deserialize)]
+pub struct UnsyncedMessagesParams {
+    since: chrono::DateTime<Utc>,
+}
+
+/// Messages to a user the socket may not have delivered, oldest first:
+/// those sent after `since`, or without it the unread ones. Nothing is
+/// marked read.
+fn unsynced_messages(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    since: Option<chrono::DateTime<Utc>>,
+) -> QueryResult<Vec<Message>> {
+    let mut query = messages::table
+        .filter(messages::receiver_id.eq(user_id))
+        .order((messages::created_at.asc(), messages::id.asc()))
+        .limit(SYNC_LIMIT)
+        .into_boxed();
+    query = match since {
+        Some(since) => query.filter(messages::created_at.gt(since)),
+        None => query.filter(messages::is_read.eq(false)),
+    };
+    query.load(conn)
 }
 
 #[derive(Deserialize)]
@@ -67,6 +263,195 @@ pub struct IncomingChatMessage {
     image_url: Option<String>,
 }
 
+/// Longest message content, in characters
+pub const MAX_MESSAGE_CHARS: usize = 4000;
+
+const SENDING_TOO_FAST_MESSAGE: &str = "You are sending messages too quickly. Please wait a moment.";
+
+/// Most messages one `mark_read` frame may mark
+pub const MARK_READ_LIMIT: usize = 500;
+
+/// Frames a client sends besides chat messages, which carry no type
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ClientFrame {
+    /// The user has seen these messages addressed to them
+    MarkRead { message_ids: Vec<Uuid> },
+    /// The user is writing to `receiver_id`; not stored
+    Typing { receiver_id: Uuid },
+}
+
+/// Forward a typing indicator to a user the sender may chat with. Anyone
+/// else, existing or not, is ignored the same way so the frame cannot be
+/// used to probe for users.
+fn forward_typing(state: &AppState, sender_id: Uuid, sender_role: UserRole, receiver_id: Uuid) -> AppResult<()> {
+    let mut conn = get_conn(&state.pool)
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+    let receiver_role: Option<UserRole> = users::table
+        .find(receiver_id)
+        .filter(users::deactivated_at.is_null())
+        .select(users::role)
+        .first(&mut conn)
+        .optional()?;
+
+    if receiver_role.is_some_and(|role| can_chat(sender_role, role)) {
+        let frame = serde_json::json!({
+            "type": "typing",
+            "sender_id": sender_id,
+        });
+        state.chat_state.send_to(receiver_id, frame.to_string());
+    }
+    Ok(())
+}
+
+/// Frame telling one socket its request failed
+fn error_frame(code: &str, message: &str) -> String {
+    serde_json::json!({
+        "type": "error",
+        "code": code,
+        "message": message,
+    })
+    .to_string()
+}
+
+/// Error frame for a muted sender, saying when they may send again
+fn rate_limited_frame(retry_after: Duration) -> String {
+    serde_json::json!({
+        "type": "error",
+        "code": "RATE_LIMITED",
+        "message": SENDING_TOO_FAST_MESSAGE,
+        "retry_after": retry_after.as_secs().max(1),
+    })
+    .to_string()
+}
+
+/// Mark messages addressed to `reader` read and send each sender a `read`
+/// receipt for theirs. Messages that are someone else's or already read
+/// are skipped.
+fn mark_read(state: &AppState, reader: Uuid, message_ids: &[Uuid]) -> AppResult<()> {
+    let mut conn = get_conn(&state.pool)
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+    let marked: Vec<(Uuid, Uuid)> = diesel::update(
+        messages::table
+            .filter(messages::id.eq_any(message_ids))
+            .filter(messages::receiver_id.eq(reader))
+            .filter(messages::is_read.eq(false)),
+    )
+    .set(messages::is_read.eq(true))
+    .returning((messages::id, messages::sender_id))
+    .get_results(&mut conn)?;
+
+    let mut by_sender: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+    for (id, sender_id) in marked {
+        by_sender.entry(sender_id).or_default().push(id);
+    }
+    for (sender_id, ids) in by_sender {
+        let receipt = serde_json::json!({
+            "type": "read",
+            "by": reader,
+            "message_ids": ids,
+        });
+        state.chat_state.send_to(sender_id, receipt.to_string());
+    }
+    Ok(())
+}
+
+/// Frame for a socket request that failed on our side; the cause is only
+/// logged. Database trouble is usually brief, so DB_ERROR is worth a retry.
+fn failure_frame(e: &AppError) -> String {
+    match e {
+        AppError::DatabaseError(_) => error_frame("DB_ERROR", "The database is unavailable, please try again"),
+        _ => error_frame("INTERNAL_ERROR", "An internal error occurred"),
+    }
+}
+
+/// Store a message sent over a socket. `None` when the receiver does not
+/// exist or the sender may not message them, which the socket ignores.
+fn store_message(
+    state: &AppState,
+    sender_id: Uuid,
+    sender_role: UserRole,
+    incoming: &IncomingChatMessage,
+) -> AppResult<Option<Message>> {
+    let mut conn = get_conn(&state.pool)
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+    if incoming.receiver_id != PUPINN_ID {
+        let receiver_role: Option<UserRole> = users::table
+            .find(incoming.receiver_id)
+            .select(users::role)
+            .first(&mut conn)
+            .optional()?;
+        if !receiver_role.is_some_and(|role| can_chat(sender_role, role)) {
+            return Ok(None);
+        }
+    }
+
+    let saved = diesel::insert_into(messages::table)
+        .values(&NewMessage {
+            sender_id,
+            receiver_id: incoming.receiver_id,
+            content: incoming.content.clone(),
+            image_url: incoming.image_url.clone(),
+        })
+        .get_result(&mut conn)?;
+    Ok(Some(saved))
+}
+
+/// Send a stored message to its receiver's sockets
+fn deliver(state: &AppState, message: Message) {
+    let receiver_id = message.receiver_id;
+    let frame = serde_json::to_string(&MessageResponse::from(message)).unwrap_or_default();
+    state.chat_state.send_to(receiver_id, frame);
+}
+
+/// Have Pupinn answer a user's message. A booking proposal in the reply is
+/// stored and sent as its own message, followed by any text after it; a
+/// plain reply carries a shared room photo as its image.
+async fn reply_as_pupinn(state: &AppState, user_id: Uuid, user_name: &str, content: &str) -> AppResult<()> {
+    let Some(reply) = AiService::new(state.pool.clone())
+        .generate_reply(user_id, user_name, content)
+        .await
+    else {
+        return Ok(());
+    };
+
+    let parts: Vec<(String, Option<String>)> = match reply.find("BOOKING_PROPOSAL:") {
+        Some(proposal_start) => {
+            let proposal_part = &reply[proposal_start..];
+            // The proposal JSON ends at its first closing brace
+            match proposal_part.find('}') {
+                Some(json_end) => {
+                    let mut parts = vec![(proposal_part[..=json_end].to_string(), None)];
+                    let remaining_text = reply[(proposal_start + json_end + 1)..].trim();
+                    if !remaining_text.is_empty() {
+                        parts.push((remaining_text.to_string(), None));
+                    }
+                    parts
+                }
+                None => Vec::new(),
+            }
+        }
+        None => vec![extract_room_photo(&reply)],
+    };
+
+    let mut conn = get_conn(&state.pool)
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+    for (content, image_url) in parts {
+        let saved: Message = diesel::insert_into(messages::table)
+            .values(&NewMessage {
+                sender_id: PUPINN_ID,
+                receiver_id: user_id,
+                content,
+                image_url,
+            })
+            .get_result(&mut conn)?;
+        deliver(state, saved);
+    }
+    Ok(())
+}
+
 // RBAC Validation Logic
 fn can_chat(role_a: UserRole, role_b: UserRole) -> bool {
     // Pupinn (Bot) can chat with everyone
@@ -138,19 +523,23 @@ pub async fn get_contacts(
         all_users.insert(0, p);
     }
 
-    // Calculate unread counts for each contact
+    // Messages stay unread until the client sends a mark_read frame
+    let unread_counts: HashMap<Uuid, i64> = messages::table
+        .filter(messages::receiver_id.eq(auth_user.user_id))
+        .filter(messages::is_read.eq(false))
+        .group_by(messages::sender_id)
+        .select((messages::sender_id, diesel::dsl::count_star()))
+        .load::<(Uuid, i64)>(&mut conn)
+        .unwrap_or_else(|e| {
+            tracing::warn!("Failed to get unread counts for user {}: {}", auth_user.user_id, e);
+            Vec::new()
+        })
+        .into_iter()
+        .collect();
+
     let mut contacts = Vec::new();
     for user in all_users {
-        let unread_count: i64 = messages::table
-            .filter(messages::sender_id.eq(user.id))
-            .filter(messages::receiver_id.eq(auth_user.user_id))
-            .filter(messages::is_read.eq(false))
-            .count()
-            .get_result(&mut conn)
-            .unwrap_or_else(|e| {
-                tracing::warn!("Failed to get unread count for user {}: {}", user.id, e);
-                0
-            });
+        let unread_count = unread_counts.get(&user.id).copied().unwrap_or(0);
         
         let name = user.username
             .clone()
@@ -162,13 +551,29 @@ pub async fn get_contacts(
             name,
             role: user.role,
             unread_count,
+            online: user.id == PUPINN_ID || state.chat_state.is_online(user.id),
         });
     }
     
     Ok(Json(contacts))
 }
 
-// Get message history with another user
+/// Fail unless the caller may read a conversation with `other_user_id`
+fn check_conversation(conn: &mut PgConnection, auth_user: &AuthUser, other_user_id: Uuid) -> AppResult<()> {
+    let other_user: User = users::table
+        .find(other_user_id)
+        .first(conn)
+        .map_err(|_| AppError::NotFound("User not found".to_string()))?;
+
+    if !can_chat(auth_user.role, other_user.role) {
+        return Err(AppError::Forbidden("Cannot chat with this user".to_string()));
+    }
+    Ok(())
+}
+
+// Get the whole message history with another user, oldest first. Kept for
+// old clients; new ones page through /chat/v2/history. Fetching it marks
+// nothing read; clients send a mark_read frame for the messages they show.
 pub async fn get_chat_history(
     State(state): State<AppState>,
     Extension(auth_user): Extension<AuthUser>,
@@ -177,14 +582,7 @@ pub async fn get_chat_history(
     let mut conn = get_conn(&state.pool)
         .map_err(|e| AppError::DatabaseError(e.to_string()))?;
     
-    let other_user: User = users::table
-        .find(params.other_user_id)
-        .first(&mut conn)
-        .map_err(|_| AppError::NotFound("User not found".to_string()))?;
-    
-    if !can_chat(auth_user.role, other_user.role) {
-        return Err(AppError::Forbidden("Cannot chat with this user".to_string()));
-    }
+    check_conversation(&mut conn, &auth_user, params.other_user_id)?;
     
     let message_list: Vec<Message> = messages::table
         .filter(
@@ -197,33 +595,329 @@ pub async fn get_chat_history(
         .load(&mut conn)
         .map_err(|e| AppError::DatabaseError(e.to_string()))?;
     
-    // Mark messages as read
-    diesel::update(
-        messages::table
-            .filter(messages::sender_id.eq(params.other_user_id))
-            .filter(messages::receiver_id.eq(auth_user.user_id))
-            .filter(messages::is_read.eq(false)),
-    )
-    .set(messages::is_read.eq(true))
-    .execute(&mut conn)
-    .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+    let response: Vec<MessageResponse> = message_list.into_iter().map(MessageResponse::from).collect();
     
-    let response: Vec<MessageResponse> = message_list
-        .into_iter()
-        .map(|m| MessageResponse {
-            id: m.id,
-            sender_id: m.sender_id,
-            receiver_id: m.receiver_id,
-            content: m.content,
-            image_url: m.image_url,
-            is_read: m.is_read,
-            created_at: m.created_at,
+    Ok(Json(response))
+}
+
+/// Where a history page starts: the conversation's messages strictly older
+/// than `before`. A message id also orders same-instant messages by id, so
+/// paging by the returned cursor neither skips nor repeats any while new
+/// messages arrive.
+fn history_cursor(
+    conn: &mut PgConnection,
+    me: Uuid,
+    other: Uuid,
+    before: &str,
+) -> AppResult<(chrono::DateTime<Utc>, Option<Uuid>)> {
+    if let Ok(id) = before.parse::<Uuid>() {
+        let message: Option<Message> = messages::table.find(id).first(conn).optional()?;
+        return match message {
+            Some(m)
+                if (m.sender_id == me && m.receiver_id == other)
+                    || (m.sender_id == other && m.receiver_id == me) =>
+            {
+                Ok((m.created_at, Some(m.id)))
+            }
+            _ => Err(AppError::ValidationError(
+                "before must be a message in this conversation".to_string(),
+            )),
+        };
+    }
+    before
+        .parse::<chrono::DateTime<Utc>>()
+        .map(|at| (at, None))
+        .map_err(|_| {
+            AppError::ValidationError("before must be a message id or an RFC 3339 timestamp".to_string())
         })
+}
+
+/// A page of the message history with another user, newest first. Pass
+/// `next_cursor` back as `before` for older messages. Marks nothing read.
+/// GET /chat/v2/history?other_user_id=<id>&limit=<n>&before=<cursor>
+pub async fn get_chat_history_page(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(params): Query<ChatHistoryParams>,
+) -> AppResult<Json<ChatHistoryPage>> {
+    let mut conn = get_conn(&state.pool)
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+    check_conversation(&mut conn, &auth_user, params.other_user_id)?;
+
+    let me = auth_user.user_id;
+    let other = params.other_user_id;
+    let limit = params.limit.unwrap_or(HISTORY_PAGE_DEFAULT).clamp(1, HISTORY_PAGE_MAX);
+
+    // One more than the page shows whether an older page exists
+    let mut query = messages::table
+        .filter(
+            messages::sender_id.eq(me).and(messages::receiver_id.eq(other))
+                .or(messages::sender_id.eq(other).and(messages::receiver_id.eq(me))),
+        )
+        .order((messages::created_at.desc(), messages::id.desc()))
+        .limit(limit + 1)
+        .into_boxed();
+    if let Some(before) = params.before.as_deref() {
+        query = match history_cursor(&mut conn, me, other, before)? {
+            (at, Some(id)) => query.filter(
+                messages::created_at.lt(at)
+                    .or(messages::created_at.eq(at).and(messages::id.lt(id))),
+            ),
+            (at, None) => query.filter(messages::created_at.lt(at)),
+        };
+    }
+    let mut message_list: Vec<Message> = query.load(&mut conn)?;
+
+    let next_cursor = if message_list.len() as i64 > limit {
+        message_list.truncate(limit as usize);
+        message_list.last().map(|m| m.id)
+    } else {
+        None
+    };
+
+    Ok(Json(ChatHistoryPage {
+        messages: message_list.into_iter().map(MessageResponse::from).collect(),
+        next_cursor,
+    }))
+}
+
+/// How long after sending a message its sender may edit or delete it
+pub const MESSAGE_CHANGE_WINDOW_MINUTES: i64 = 15;
+
+#[derive(Deserialize)]
+pub struct UpdateMessageRequest {
+    content: String,
+}
+
+/// A message the caller sent and may still change. Anyone else's message is
+/// not found, so ids cannot be probed. Admins are not held to the window.
+fn changeable_message(conn: &mut PgConnection, auth_user: &AuthUser, message_id: Uuid) -> AppResult<Message> {
+    let message: Message = messages::table
+        .find(message_id)
+        .filter(messages::sender_id.eq(auth_user.user_id))
+        .first(conn)
+        .optional()?
+        .ok_or_else(|| AppError::NotFound("Message not found".to_string()))?;
+
+    let window = chrono::Duration::minutes(MESSAGE_CHANGE_WINDOW_MINUTES);
+    if auth_user.role != UserRole::Admin && Utc::now() - message.created_at > window {
+        return Err(AppError::Forbidden(format!(
+            "Messages can only be changed within {} minutes of sending",
+            MESSAGE_CHANGE_WINDOW_MINUTES
+        )));
+    }
+    Ok(message)
+}
+
+/// Tell both sides of a conversation, on every socket, that a message changed
+fn announce_message_change(state: &AppState, kind: &str, message: Message) {
+    let (sender_id, receiver_id) = (message.sender_id, message.receiver_id);
+    let frame = serde_json::json!({
+        "type": kind,
+        "message": MessageResponse::from(message),
+    })
+    .to_string();
+    state.chat_state.send_to(receiver_id, frame.clone());
+    state.chat_state.send_to(sender_id, frame);
+}
+
+/// Change the content of a message the caller sent
+/// PATCH /chat/messages/:id
+pub async fn update_message(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(message_id): Path<Uuid>,
+    Json(req): Json<UpdateMessageRequest>,
+) -> AppResult<Json<MessageResponse>> {
+    if req.content.chars().count() > MAX_MESSAGE_CHARS {
+        return Err(AppError::ValidationError(format!(
+            "Messages can be at most {} characters",
+            MAX_MESSAGE_CHARS
+        )));
+    }
+
+    let mut conn = get_conn(&state.pool)
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+    let updated = conn.transaction::<_, AppError, _>(|conn| {
+        let message = changeable_message(conn, &auth_user, message_id)?;
+        if message.deleted_at.is_some() {
+            return Err(AppError::Conflict("Message has been deleted".to_string()));
+        }
+        if req.content.trim().is_empty() && message.image_url.is_none() {
+            return Err(AppError::ValidationError("Message content cannot be empty".to_string()));
+        }
+
+        let now = Utc::now();
+        let updated: Message = diesel::update(messages::table.find(message_id))
+            .set((
+                messages::content.eq(&req.content),
+                messages::edited_at.eq(now),
+                messages::updated_at.eq(now),
+            ))
+            .get_result(conn)?;
+
+        AuditService::record(
+            conn,
+            Some(auth_user.user_id),
+            "chat_message.edited",
+            "message",
+            Some(&message_id.to_string()),
+            None,
+        )?;
+        Ok(updated)
+    })?;
+
+    announce_message_change(&state, "message_updated", updated.clone());
+    Ok(Json(MessageResponse::from(updated)))
+}
+
+/// Delete a message the caller sent, leaving a tombstone in its place.
+/// Deleting it again changes nothing.
+/// DELETE /chat/messages/:id
+pub async fn delete_message(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(message_id): Path<Uuid>,
+) -> AppResult<Json<MessageResponse>> {
+    let mut conn = get_conn(&state.pool)
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+    let (deleted, changed) = conn.transaction::<_, AppError, _>(|conn| {
+        let message = changeable_message(conn, &auth_user, message_id)?;
+        if message.deleted_at.is_some() {
+            return Ok((message, false));
+        }
+
+        let now = Utc::now();
+        // The content is erased, not hidden: it may be someone's details
+        let deleted: Message = diesel::update(messages::table.find(message_id))
+            .set((
+                messages::content.eq(""),
+                messages::image_url.eq(None::<String>),
+                messages::deleted_at.eq(now),
+                messages::updated_at.eq(now),
+            ))
+            .get_result(conn)?;
+
+        AuditService::record(
+            conn,
+            Some(auth_user.user_id),
+            "chat_message.deleted",
+            "message",
+            Some(&message_id.to_string()),
+            None,
+        )?;
+        Ok((deleted, true))
+    })?;
+
+    if changed {
+        announce_message_change(&state, "message_deleted", deleted.clone());
+    }
+    Ok(Json(MessageResponse::from(deleted)))
+}
+
+/// Messages addressed to the caller since a moment, oldest first, for a
+/// client catching up after being offline. Unlike the history it leaves
+/// them unread.
+/// GET /chat/messages/unsynced?since=<timestamp>
+pub async fn get_unsynced_messages(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(params): Query<UnsyncedMessagesParams>,
+) -> AppResult<Json<Vec<MessageResponse>>> {
+    let mut conn = get_conn(&state.pool)
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+    let response = unsynced_messages(&mut conn, auth_user.user_id, Some(params.since))?
+        .into_iter()
+        .map(MessageResponse::from)
         .collect();
-    
     Ok(Json(response))
 }
 
+#[derive(Deserialize)]
+pub struct ChatExportParams {
+    #[serde(default)]
+    format: ChatExportFormat,
+}
+
+#[derive(Deserialize)]
+pub struct DeleteChatHistoryParams {
+    #[serde(default)]
+    with: ChatHistoryScope,
+}
+
+#[derive(Serialize)]
+pub struct DeleteChatHistoryResponse {
+    deleted_count: usize,
+}
+
+/// Count a privacy request against the guest's hourly allowance
+fn check_privacy_rate(state: &AppState, guest_id: Uuid) -> AppResult<()> {
+    state
+        .chat_state
+        .privacy_requests
+        .check(&guest_id.to_string(), Instant::now())
+        .map_err(|retry_after| {
+            AppError::RateLimited(
+                "Too many chat privacy requests. Please try again later.".to_string(),
+                retry_after,
+            )
+        })
+}
+
+/// Download everything the guest said to and received from the assistant
+/// and staff, with staff names replaced by role labels
+/// GET /guest/chat/export?format=json|csv
+pub async fn export_guest_chat(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(params): Query<ChatExportParams>,
+) -> AppResult<Response> {
+    check_privacy_rate(&state, auth_user.user_id)?;
+
+    let entries = ChatPrivacyService::new(state.pool).export(auth_user.user_id, params.format)?;
+    let (content_type, body) = match params.format {
+        ChatExportFormat::Json => (
+            "application/json",
+            serde_json::to_string_pretty(&entries)
+                .map_err(|e| AppError::InternalError(e.to_string()))?,
+        ),
+        ChatExportFormat::Csv => ("text/csv; charset=utf-8", export_csv(&entries)),
+    };
+    let disposition = format!(
+        "attachment; filename=\"chat-export.{}\"",
+        params.format.extension()
+    );
+
+    Ok((
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        body,
+    )
+        .into_response())
+}
+
+/// Delete the guest's conversations with the assistant. Messages are kept
+/// as tombstones; staff conversations require the admin flow.
+/// DELETE /guest/chat/history?with=bot|all
+pub async fn delete_guest_chat_history(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(params): Query<DeleteChatHistoryParams>,
+) -> AppResult<Json<DeleteChatHistoryResponse>> {
+    check_privacy_rate(&state, auth_user.user_id)?;
+
+    let deleted_count =
+        ChatPrivacyService::new(state.pool).delete_history(auth_user.user_id, params.with)?;
+    Ok(Json(DeleteChatHistoryResponse { deleted_count }))
+}
+
 // WebSocket handler
 pub async fn chat_websocket_handler(
     ws: WebSocketUpgrade,
@@ -236,7 +930,9 @@ pub async fn chat_websocket_handler(
         let auth_service = crate::services::AuthService::new(
             state.pool.clone(),
             state.jwt_secret.clone(),
-        );
+        )
+        .with_issuer(state.token_issuer.clone())
+        .with_revocations(state.revocations.clone());
         auth_service.validate_token(token_str)
     } else {
         return axum::response::Response::builder()
@@ -250,7 +946,28 @@ pub async fn chat_websocket_handler(
         Ok(claims) => claims,
         Err(_) => return axum::response::Response::builder().status(axum::http::StatusCode::UNAUTHORIZED).body(axum::body::Body::from("Invalid")).unwrap().into_response(),
     };
+
+    if let Err(e) = check_session(&state, claims.sid, claims.role) {
+        return e.into_response();
+    }
+    let auth_service = crate::services::AuthService::new(state.pool.clone(), state.jwt_secret.clone());
+    match auth_service.ensure_active(claims.sub) {
+        Ok(false) => {}
+        Ok(true) => {
+            return AppError::PasswordChangeRequired(PASSWORD_CHANGE_REQUIRED_MESSAGE.to_string()).into_response()
+        }
+        Err(e) => return e.into_response(),
+    }
     
+    // A reconnecting client says when its newest message was sent
+    let since = match params.get("since").map(|since| since.parse::<chrono::DateTime<Utc>>()) {
+        Some(Ok(since)) => Some(since),
+        Some(Err(_)) => {
+            return AppError::ValidationError("since must be an RFC 3339 timestamp".to_string()).into_response()
+        }
+        None => None,
+    };
+
     let state_arc = std::sync::Arc::new(state);
     
     ws.on_upgrade(move |socket| {
@@ -259,6 +976,8 @@ pub async fn chat_websocket_handler(
             state_arc,
             claims.sub,
             claims.role,
+            claims.sid,
+            since,
         )
     })
 }
@@ -268,19 +987,10 @@ async fn handle_socket(
     state: Arc<AppState>,
     my_id: Uuid,
     my_role: UserRole,
+    session_id: Option<Uuid>,
+    since: Option<chrono::DateTime<Utc>>,
 ) {
     let (mut sender, mut receiver) = socket.split();
-    
-    // Subscribe to messages
-    let tx = {
-        let mut connections = state.chat_state.active_connections.lock().unwrap();
-        connections.entry(my_id).or_insert_with(|| {
-            let (tx, _rx) = broadcast::channel(100);
-            tx
-        }).clone()
-    };
-    
-    let mut rx = tx.subscribe();
 
     // Verify user exists and fetch user name for AI context
     let user_name = {
@@ -301,11 +1011,49 @@ async fn handle_socket(
         }
     };
     let user_name = Arc::new(user_name);
+
+    // Subscribe to messages, alongside the user's other sockets
+    let mut rx = state.chat_state.subscribe(my_id, my_role);
+
+    // Then catch up on what was sent while the user was away. Subscribing
+    // first means nothing falls in between; a message may arrive twice, so
+    // clients keep the first copy by id.
+    let missed = match get_conn(&state.pool) {
+        Ok(mut conn) => unsynced_messages(&mut conn, my_id, since),
+        Err(e) => {
+            tracing::error!("Failed to get DB connection: {}", e);
+            drop(rx);
+            state.chat_state.disconnect(my_id);
+            return;
+        }
+    };
+    let missed: Vec<MessageResponse> = match missed {
+        Ok(missed) => missed.into_iter().map(MessageResponse::from).collect(),
+        Err(e) => {
+            tracing::error!("Failed to load unsynced messages: {}", e);
+            Vec::new()
+        }
+    };
+    let sync_frame = serde_json::json!({ "type": "sync", "messages": missed });
+    if sender.send(WsMessage::Text(sync_frame.to_string())).await.is_err() {
+        drop(rx);
+        state.chat_state.disconnect(my_id);
+        return;
+    }
+    // Error frames are only for this socket
+    let (own_tx, mut own_rx) = mpsc::unbounded_channel::<String>();
     
     // Task 1: Send incoming messages from other users to this socket
     let mut send_task = tokio::spawn(async move {
-        while let Ok(msg) = rx.recv().await {
-            if sender.send(WsMessage::Text(msg)).await.is_err() {
+        loop {
+            let frame = tokio::select! {
+                msg = rx.recv() => match msg {
+                    Ok(msg) => msg,
+                    Err(_) => break,
+                },
+                Some(frame) = own_rx.recv() => frame,
+            };
+            if sender.send(WsMessage::Text(frame)).await.is_err() {
                 break;
             }
         }
@@ -315,212 +1063,101 @@ async fn handle_socket(
     let recv_user_name = user_name.clone();
     let mut recv_task = tokio::spawn({
         let state = state.clone();
+        let own_tx = own_tx.clone();
         async move {
             while let Some(Ok(msg)) = receiver.next().await {
                 if let WsMessage::Text(text) = msg {
-                    if let Ok(incoming) = serde_json::from_str::<IncomingChatMessage>(&text) {
-                        
-                        // Check if receiver is Pupinn (The Bot)
-                        if incoming.receiver_id == PUPINN_ID {
-                            // 1. Save user message to DB
-                            let mut conn = match get_conn(&state.pool) {
-                                Ok(conn) => conn,
-                                Err(e) => {
-                                    tracing::error!("Failed to get DB connection: {}", e);
-                                    continue;
+                    // Messages sent over the socket count as session activity.
+                    // Only an ended session closes it; if the check itself
+                    // fails, this frame is refused and the socket stays up.
+                    match check_session(&state, session_id, my_role) {
+                        Ok(()) => {}
+                        Err(AppError::SessionExpired(message)) => {
+                            let _ = own_tx.send(error_frame("SESSION_EXPIRED", &message));
+                            break;
+                        }
+                        Err(e) => {
+                            tracing::error!("Session check failed: {}", e);
+                            let _ = own_tx.send(failure_frame(&e));
+                            continue;
+                        }
+                    }
+
+                    if let Ok(frame) = serde_json::from_str::<ClientFrame>(&text) {
+                        match frame {
+                            ClientFrame::MarkRead { message_ids } => {
+                                if state.read_only.is_enabled() {
+                                    let _ = own_tx.send(error_frame("READ_ONLY_MODE", READ_ONLY_MESSAGE));
+                                } else if message_ids.len() > MARK_READ_LIMIT {
+                                    let _ = own_tx.send(error_frame(
+                                        "VALIDATION_ERROR",
+                                        &format!("At most {} messages can be marked read at once", MARK_READ_LIMIT),
+                                    ));
+                                } else if let Err(e) = mark_read(&state, my_id, &message_ids) {
+                                    tracing::error!("Failed to mark messages read: {}", e);
+                                    let _ = own_tx.send(failure_frame(&e));
                                 }
-                            };
-                            
-                            let user_message = NewMessage {
-                                sender_id: my_id,
-                                receiver_id: PUPINN_ID,
-                                content: incoming.content.clone(),
-                                image_url: incoming.image_url.clone(),
-                            };
-
-                            let _saved_msg: Message = match diesel::insert_into(messages::table)
-                                .values(&user_message)
-                                .get_result(&mut conn)
-                            {
-                                Ok(msg) => msg,
-                                Err(e) => {
-                                    tracing::error!("Failed to save message: {}", e);
-                                    continue;
+                            }
+                            // Not stored, so allowed in read-only mode
+                            ClientFrame::Typing { receiver_id } => {
+                                if let Err(e) = forward_typing(&state, my_id, my_role, receiver_id) {
+                                    tracing::error!("Failed to forward typing indicator: {}", e);
                                 }
-                            };
-
-                            // 2. Trigger AI Response (Async)
-                            let ai_service = AiService::new(state.pool.clone());
-                            let content_clone = incoming.content.clone();
-                            let state_clone = state.clone();
-                            let name_clone = recv_user_name.to_string();
-                            
+                            }
+                        }
+                        continue;
+                    }
+
+                    if let Ok(incoming) = serde_json::from_str::<IncomingChatMessage>(&text) {
+                        tracing::debug!(
+                            "Chat message from {} to {}: {}",
+                            my_id,
+                            incoming.receiver_id,
+                            message_content_for_log(&incoming.content)
+                        );
+
+                        // Keep the socket open during maintenance but refuse to store messages
+                        if state.read_only.is_enabled() {
+                            let _ = own_tx.send(error_frame("READ_ONLY_MODE", READ_ONLY_MESSAGE));
+                            continue;
+                        }
+
+                        if incoming.content.chars().count() > MAX_MESSAGE_CHARS {
+                            let _ = own_tx.send(error_frame(
+                                "VALIDATION_ERROR",
+                                &format!("Messages can be at most {} characters", MAX_MESSAGE_CHARS),
+                            ));
+                            continue;
+                        }
+
+                        // Floods are refused before they reach the database or the AI
+                        let to_assistant = incoming.receiver_id == PUPINN_ID;
+                        if let Err(retry_after) = state.chat_state.check_send_rate(my_id, to_assistant, Instant::now()) {
+                            let _ = own_tx.send(rate_limited_frame(retry_after));
+                            continue;
+                        }
+
+                        let saved = match store_message(&state, my_id, my_role, &incoming) {
+                            Ok(Some(saved)) => saved,
+                            Ok(None) => continue,
+                            Err(e) => {
+                                tracing::error!("Failed to save chat message: {}", e);
+                                let _ = own_tx.send(failure_frame(&e));
+                                continue;
+                            }
+                        };
+
+                        if saved.receiver_id == PUPINN_ID {
+                            // Pupinn answers in the background; a failure only loses that reply
+                            let state = state.clone();
+                            let user_name = recv_user_name.clone();
                             tokio::spawn(async move {
-                                let reply_content = ai_service.generate_reply(my_id, &name_clone, &content_clone).await;
-                                
-                                if let Some(reply) = reply_content {
-                                    let mut conn = match get_conn(&state_clone.pool) {
-                                        Ok(conn) => conn,
-                                        Err(e) => {
-                                            tracing::error!("Failed to get DB connection in AI reply handler: {}", e);
-                                            return;
-                                        }
-                                    };
-                                    
-                                    // Check if the reply contains a BOOKING_PROPOSAL
-                                    if let Some(proposal_start) = reply.find("BOOKING_PROPOSAL:") {
-                                        // Extract the booking proposal JSON
-                                        let proposal_part = &reply[proposal_start..];
-                                        
-                                        // Find the end of the JSON (look for the closing brace)
-                                        if let Some(json_end) = proposal_part.find('}') {
-                                            let booking_proposal = &proposal_part[..=json_end];
-                                            
-                                            // Send the booking proposal as a separate message first
-                                            let proposal_msg = NewMessage {
-                                                sender_id: PUPINN_ID,
-                                                receiver_id: my_id,
-                                                content: booking_proposal.to_string(),
-                                                image_url: None,
-                                            };
-                                            
-                                            if let Ok(saved_proposal_msg) = diesel::insert_into(messages::table)
-                                                .values(&proposal_msg)
-                                                .get_result::<Message>(&mut conn)
-                                            {
-                                                // Notify user about the booking proposal
-                                                let connections = state_clone.chat_state.active_connections.lock().unwrap();
-                                                if let Some(user_tx) = connections.get(&my_id) {
-                                                    let message_json = serde_json::json!({
-                                                        "id": saved_proposal_msg.id,
-                                                        "sender_id": saved_proposal_msg.sender_id,
-                                                        "receiver_id": saved_proposal_msg.receiver_id,
-                                                        "content": saved_proposal_msg.content,
-                                                        "image_url": saved_proposal_msg.image_url,
-                                                        "is_read": saved_proposal_msg.is_read,
-                                                        "created_at": saved_proposal_msg.created_at,
-                                                    });
-                                                    let _ = user_tx.send(serde_json::to_string(&message_json).unwrap_or_default());
-                                                }
-                                                drop(connections);
-                                            }
-                                            
-                                            // Extract the conversational text (everything after the JSON)
-                                            let remaining_text = reply[(proposal_start + json_end + 1)..].trim();
-                                            
-                                            // If there's conversational text, send it as a separate message
-                                            if !remaining_text.is_empty() {
-                                                let text_msg = NewMessage {
-                                                    sender_id: PUPINN_ID,
-                                                    receiver_id: my_id,
-                                                    content: remaining_text.to_string(),
-                                                    image_url: None,
-                                                };
-                                                
-                                                if let Ok(saved_text_msg) = diesel::insert_into(messages::table)
-                                                    .values(&text_msg)
-                                                    .get_result::<Message>(&mut conn)
-                                                {
-                                                    let connections = state_clone.chat_state.active_connections.lock().unwrap();
-                                                    if let Some(user_tx) = connections.get(&my_id) {
-                                                        let message_json = serde_json::json!({
-                                                            "id": saved_text_msg.id,
-                                                            "sender_id": saved_text_msg.sender_id,
-                                                            "receiver_id": saved_text_msg.receiver_id,
-                                                            "content": saved_text_msg.content,
-                                                            "image_url": saved_text_msg.image_url,
-                                                            "is_read": saved_text_msg.is_read,
-                                                            "created_at": saved_text_msg.created_at,
-                                                        });
-                                                        let _ = user_tx.send(serde_json::to_string(&message_json).unwrap_or_default());
-                                                    }
-                                                }
-                                            }
-                                        }
-                                    } else {
-                                        // No booking proposal, send the reply as normal
-                                        let bot_msg = NewMessage {
-                                            sender_id: PUPINN_ID,
-                                            receiver_id: my_id,
-                                            content: reply,
-                                            image_url: None,
-                                        };
-                                        
-                                        let saved_bot_msg: Message = match diesel::insert_into(messages::table)
-                                            .values(&bot_msg)
-                                            .get_result(&mut conn)
-                                        {
-                                            Ok(msg) => msg,
-                                            Err(e) => {
-                                                tracing::error!("Failed to save bot message: {}", e);
-                                                return;
-                                            }
-                                        };
-                                        
-                                        // Notify User
-                                        let connections = state_clone.chat_state.active_connections.lock().unwrap();
-                                        if let Some(user_tx) = connections.get(&my_id) {
-                                            let message_json = serde_json::json!({
-                                                "id": saved_bot_msg.id,
-                                                "sender_id": saved_bot_msg.sender_id,
-                                                "receiver_id": saved_bot_msg.receiver_id,
-                                                "content": saved_bot_msg.content,
-                                                "image_url": saved_bot_msg.image_url,
-                                                "is_read": saved_bot_msg.is_read,
-                                                "created_at": saved_bot_msg.created_at,
-                                            });
-                                            let _ = user_tx.send(serde_json::to_string(&message_json).unwrap_or_default());
-                                        }
-                                    }
+                                if let Err(e) = reply_as_pupinn(&state, my_id, &user_name, &saved.content).await {
+                                    tracing::error!("Failed to send Pupinn's reply to {}: {}", my_id, e);
                                 }
                             });
-
                         } else {
-                            // Standard P2P Chat Logic
-                            let mut conn = match get_conn(&state.pool) {
-                                Ok(conn) => conn,
-                                Err(e) => {
-                                    tracing::error!("Failed to get DB connection: {}", e);
-                                    continue;
-                                }
-                            };
-                            
-                            let receiver_user: Option<User> = users::table
-                                .find(incoming.receiver_id)
-                                .first(&mut conn)
-                                .optional()
-                                .ok()
-                                .flatten();
-                            
-                            if let Some(receiver_user) = receiver_user {
-                                if !can_chat(my_role, receiver_user.role) { continue; }
-                                
-                                let new_message = NewMessage {
-                                    sender_id: my_id,
-                                    receiver_id: incoming.receiver_id,
-                                    content: incoming.content.clone(),
-                                    image_url: incoming.image_url.clone(),
-                                };
-                                
-                                if let Ok(saved_message) = diesel::insert_into(messages::table)
-                                    .values(&new_message)
-                                    .get_result::<Message>(&mut conn) 
-                                {
-                                    let connections = state.chat_state.active_connections.lock().unwrap();
-                                    if let Some(receiver_tx) = connections.get(&incoming.receiver_id) {
-                                        let message_json = serde_json::json!({
-                                            "id": saved_message.id,
-                                            "sender_id": saved_message.sender_id,
-                                            "receiver_id": saved_message.receiver_id,
-                                            "content": saved_message.content,
-                                            "image_url": saved_message.image_url,
-                                            "is_read": saved_message.is_read,
-                                            "created_at": saved_message.created_at,
-                                        });
-                                        let _ = receiver_tx.send(serde_json::to_string(&message_json).unwrap_or_default());
-                                    }
-                                }
-                            }
+                            deliver(&state, saved);
                         }
                     }
                 }
@@ -528,13 +1165,29 @@ async fn handle_socket(
         }
     });
     
+    // Wait for the other task to stop so this socket's receiver is gone
+    // before checking for the user's other sockets
     tokio::select! {
-        _ = &mut send_task => recv_task.abort(),
-        _ = &mut recv_task => send_task.abort(),
+        _ = &mut send_task => {
+            recv_task.abort();
+            let _ = recv_task.await;
+        }
+        _ = &mut recv_task => {
+            send_task.abort();
+            let _ = send_task.await;
+        }
     };
-    
-    let mut connections = state.chat_state.active_connections.lock().unwrap();
-    connections.remove(&my_id);
+
+    state.chat_state.disconnect(my_id);
+}
+
+/// Map a multipart read failure, keeping body-limit rejections as 413
+pub(crate) fn multipart_error(e: MultipartError, context: &str) -> AppError {
+    if e.status() == StatusCode::PAYLOAD_TOO_LARGE {
+        AppError::PayloadTooLarge("Upload is too large".to_string())
+    } else {
+        AppError::InternalError(format!("{}: {}", context, e))
+    }
 }
 
 // Image upload handler
@@ -544,18 +1197,24 @@ pub async fn upload_image(
     mut multipart: Multipart,
 ) -> AppResult<Json<serde_json::Value>> {
     tracing::info!("upload_image called for user_id={}", auth_user.user_id);
+
+    // An upload is the first half of an image message, so it spends the same budget
+    state
+        .chat_state
+        .check_send_rate(auth_user.user_id, false, Instant::now())
+        .map_err(|retry_after| AppError::RateLimited(SENDING_TOO_FAST_MESSAGE.to_string(), retry_after))?;
     
     // Extract file from multipart
     while let Some(field) = multipart.next_field().await.map_err(|e| {
         tracing::error!("Failed to read multipart field: {}", e);
-        AppError::InternalError(format!("Failed to read multipart field: {}", e))
+        multipart_error(e, "Failed to read multipart field")
     })? {
         if field.name() == Some("file") {
             tracing::debug!("Processing file upload field");
             
             // Extract filename and extension before consuming field
             let file_ext = field.file_name()
-                .and_then(|n| n.split('.').last())
+                .and_then(|n| n.split('.').next_back())
                 .unwrap_or("jpg")
                 .to_string();
             
@@ -564,14 +1223,13 @@ pub async fn upload_image(
             // Read file data
             let data = field.bytes().await.map_err(|e| {
                 tracing::error!("Failed to read file data: {}", e);
-                AppError::InternalError(format!("Failed to read file data: {}", e))
+                multipart_error(e, "Failed to read file data")
             })?;
             
             tracing::info!("Read {} bytes from uploaded file", data.len());
             
             // Generate unique filename
             let file_name = format!("{}_{}.{}", auth_user.user_id, Uuid::new_v4(), file_ext);
-            tracing::info!("Generated filename: {}", file_name);
             
             // Upload to MinIO
             let bucket = "chat-images";
@@ -592,11 +1250,10 @@ pub async fn upload_image(
             tracing::info!("Successfully uploaded file to MinIO");
             
             // Return MinIO URL (use public URL for browser access)
-            let minio_public_url = std::env::var("MINIO_PUBLIC_URL")
-                .unwrap_or_else(|_| "http://localhost:9000".to_string());
-            let image_url = format!("{}/{}/{}", minio_public_url, bucket, file_name);
+            let image_url = crate::services::storage_service::public_url(bucket, &file_name);
             
-            tracing::info!("Image uploaded successfully, URL: {}", image_url);
+            // The URL embeds the uploader's id, so it is not logged
+            tracing::info!("Image uploaded successfully");
             return Ok(Json(serde_json::json!({ "url": image_url })));
         }
     }
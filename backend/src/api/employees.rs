@@ -10,7 +10,7 @@ use uuid::Uuid;
 use crate::api::{middleware::AuthUser, AppState};
 use crate::errors::AppError;
 use crate::models::{UpdateUser, User, UserRole};
-use crate::services::{AuthService, CreateUserRequest};
+use crate::services::{AuthService, CreateUserRequest, LoginEventService};
 use crate::utils::{validate_email, validate_username};
 
 /// Employee list query parameters
@@ -42,6 +42,7 @@ pub struct EmployeeResponse {
     pub full_name: Option<String>,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub deactivated_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub last_login_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 impl From<User> for EmployeeResponse {
@@ -54,10 +55,18 @@ impl From<User> for EmployeeResponse {
             full_name: user.full_name,
             created_at: user.created_at,
             deactivated_at: user.deactivated_at,
+            last_login_at: user.last_login_at,
         }
     }
 }
 
+/// Login history query parameters
+#[derive(Debug, Deserialize)]
+pub struct LoginHistoryQuery {
+    pub page: Option<i64>,
+    pub per_page: Option<i64>,
+}
+
 /// Create employee request
 #[derive(Debug, Deserialize)]
 pub struct CreateEmployeeRequest {
@@ -66,6 +75,9 @@ pub struct CreateEmployeeRequest {
     pub role: UserRole,
     pub email: Option<String>,
     pub full_name: Option<String>,
+    /// Make the employee choose their own password on first login
+    #[serde(default)]
+    pub must_change_password: bool,
 }
 
 /// Update employee request
@@ -83,6 +95,19 @@ pub struct ResetPasswordRequest {
     pub new_password: String,
 }
 
+/// Transfer admin request
+#[derive(Debug, Deserialize)]
+pub struct TransferAdminRequest {
+    pub employee_id: Uuid,
+}
+
+/// Transfer admin response
+#[derive(Debug, Serialize)]
+pub struct TransferAdminResponse {
+    pub previous_admin: EmployeeResponse,
+    pub admin: EmployeeResponse,
+}
+
 /// List employees endpoint
 /// GET /admin/employees
 pub async fn list_employees(
@@ -157,6 +182,7 @@ pub async fn create_employee(
         username,
         password: request.password,
         role: request.role,
+        must_change_password: request.must_change_password,
     };
 
     let user_info = auth_service.create_user(&create_request)?;
@@ -237,7 +263,9 @@ pub async fn delete_employee(
     Extension(_auth_user): Extension<AuthUser>,
 ) -> Result<impl IntoResponse, AppError> {
 
-    let auth_service = AuthService::new(state.pool.clone(), state.jwt_secret.clone());
+    // Deactivating also revokes the employee's outstanding tokens
+    let auth_service = AuthService::new(state.pool.clone(), state.jwt_secret.clone())
+        .with_revocations(state.revocations.clone());
     auth_service.delete_employee(id)?;
 
     Ok(StatusCode::NO_CONTENT)
@@ -257,6 +285,36 @@ pub async fn reactivate_employee(
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// Revoke all of an employee's sessions, e.g. after a token leaked
+/// POST /admin/employees/:id/revoke-sessions
+pub async fn revoke_sessions(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Extension(_auth_user): Extension<AuthUser>,
+) -> Result<impl IntoResponse, AppError> {
+    let auth_service = AuthService::new(state.pool.clone(), state.jwt_secret.clone())
+        .with_revocations(state.revocations.clone());
+    auth_service.revoke_employee_sessions(id)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Employee login history endpoint, newest first
+/// GET /admin/employees/:id/login-history
+pub async fn login_history(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Extension(_auth_user): Extension<AuthUser>,
+    Query(query): Query<LoginHistoryQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let auth_service = AuthService::new(state.pool.clone(), state.jwt_secret.clone());
+    let employee = auth_service.get_employee_by_id(id)?;
+
+    let history = LoginEventService::new(state.pool.clone()).history(employee.id, query.page, query.per_page)?;
+
+    Ok(Json(history))
+}
+
 /// Reset employee password endpoint
 /// POST /admin/employees/:id/reset-password
 pub async fn reset_password(
@@ -272,3 +330,20 @@ pub async fn reset_password(
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// Hand the admin role to another employee; the caller becomes a receptionist
+/// and both are signed out so their next login carries the new roles
+/// POST /admin/transfer-admin
+pub async fn transfer_admin(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(request): Json<TransferAdminRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let auth_service = AuthService::new(state.pool.clone(), state.jwt_secret.clone())
+        .with_revocations(state.revocations.clone());
+    let (previous_admin, admin) = auth_service.transfer_admin(auth_user.user_id, request.employee_id)?;
+
+    Ok(Json(TransferAdminResponse {
+        previous_admin: previous_admin.into(),
+        admin: admin.into(),
+    }))
+}
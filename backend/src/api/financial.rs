@@ -9,6 +9,7 @@ use uuid::Uuid;
 
 use crate::api::{middleware::AuthUser, AppState};
 use crate::errors::AppError;
+use crate::services::room_service::RoomFilter;
 use crate::services::{BookingService, RoomService};
 use crate::utils::validate_date_format;
 
@@ -124,7 +125,10 @@ pub async fn list_rooms_with_financials(
         .and_then(|s| NaiveDate::parse_from_str(s, "%Y-%m-%d").ok());
 
     // Get all rooms
-    let rooms = room_service.list_rooms(None, None)?;
+    let rooms = room_service.list_rooms(&RoomFilter {
+        include_decommissioned: true,
+        ..RoomFilter::default()
+    })?;
 
     // Calculate financials for each room
     let use_payments = query.use_payments.unwrap_or(false);
@@ -141,8 +145,8 @@ pub async fn list_rooms_with_financials(
             room: RoomSummary {
                 id: room.id,
                 number: room.number,
-                room_type: format!("{:?}", room.room_type),
-                status: format!("{:?}", room.status),
+                room_type: room.room_type.to_string(),
+                status: room.status.to_string(),
             },
             financials: financials.into(),
         });
@@ -209,8 +213,8 @@ pub async fn get_room_financials(
         room: RoomSummary {
             id: room.id,
             number: room.number,
-            room_type: format!("{:?}", room.room_type),
-            status: format!("{:?}", room.status),
+            room_type: room.room_type.to_string(),
+            status: room.status.to_string(),
         },
         financials: RoomFinancialsResponse::from_financials_with_flag(financials, use_payments),
     }))
@@ -257,8 +261,8 @@ pub async fn compare_rooms(
         // Verify room exists
         let room = room_service.get_room_by_id(room_id)?;
 
-        let use_payments = request.start_date.as_ref().and_then(|_| Some(false))
-            .or_else(|| request.end_date.as_ref().and_then(|_| Some(false)))
+        let use_payments = request.start_date.as_ref().map(|_| false)
+            .or_else(|| request.end_date.as_ref().map(|_| false))
             .unwrap_or(false);
         let financials = booking_service.calculate_room_financials_with_payments(
             room_id,
@@ -271,8 +275,8 @@ pub async fn compare_rooms(
             room: RoomSummary {
                 id: room.id,
                 number: room.number,
-                room_type: format!("{:?}", room.room_type),
-                status: format!("{:?}", room.status),
+                room_type: room.room_type.to_string(),
+                status: room.status.to_string(),
             },
             financials: financials.into(),
         });
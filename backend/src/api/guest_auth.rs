@@ -1,16 +1,27 @@
 //! Guest authentication API handlers
 //!
-//! Handles guest registration, login, and profile operations.
+//! Handles guest registration, login, password reset, and profile operations.
 
-use axum::{extract::State, http::StatusCode, Extension, Json};
-use serde::Serialize;
+use std::net::SocketAddr;
 
+use axum::{
+    extract::{ConnectInfo, State},
+    http::{HeaderMap, StatusCode},
+    Extension, Json,
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use crate::api::auth::{login_origin, warn_unrecorded_login};
 use crate::api::middleware::AuthUser;
 use crate::api::AppState;
 use crate::errors::AppError;
 use crate::models::GuestInfo;
+use crate::services::email_verification_service::{self, spawn_verification_email};
+use crate::services::password_reset_service::spawn_password_reset_email;
 use crate::services::{
-    AuthService, ChangePasswordRequest, GuestAuthResponse, GuestLoginRequest, GuestRegisterRequest,
+    AuthService, ChangePasswordRequest, EmailVerificationService, GuestAuthResponse, GuestLoginRequest,
+    GuestRegisterRequest, LoginEventService, PasswordResetService,
 };
 
 /// Response wrapper for authentication (matches API contract)
@@ -20,6 +31,25 @@ pub struct AuthResponse {
     pub token: String,
 }
 
+/// Forgot password request DTO
+#[derive(Debug, Deserialize)]
+pub struct ForgotPasswordDto {
+    pub email: String,
+}
+
+/// Reset password request DTO
+#[derive(Debug, Deserialize)]
+pub struct ResetPasswordDto {
+    pub token: String,
+    pub new_password: String,
+}
+
+/// Verify email request DTO
+#[derive(Debug, Deserialize)]
+pub struct VerifyEmailDto {
+    pub token: String,
+}
+
 impl From<GuestAuthResponse> for AuthResponse {
     fn from(response: GuestAuthResponse) -> Self {
         Self {
@@ -32,7 +62,8 @@ impl From<GuestAuthResponse> for AuthResponse {
 /// POST /auth/register - Register a new guest account
 ///
 /// Creates a new guest user account with email, password, and full name.
-/// Returns the user info and a JWT token on success.
+/// Returns the user info and a JWT token on success. A verification link is
+/// emailed in the background; the guest cannot book until they follow it.
 ///
 /// # Request Body
 /// ```json
@@ -50,7 +81,8 @@ impl From<GuestAuthResponse> for AuthResponse {
 ///     "id": "uuid",
 ///     "email": "guest@example.com",
 ///     "full_name": "John Doe",
-///     "role": "guest"
+///     "role": "guest",
+///     "email_verified_at": null
 ///   },
 ///   "token": "jwt-token-here"
 /// }
@@ -63,9 +95,12 @@ pub async fn register(
     State(state): State<AppState>,
     Json(request): Json<GuestRegisterRequest>,
 ) -> Result<(StatusCode, Json<AuthResponse>), AppError> {
-    let auth_service = AuthService::new(state.pool.clone(), state.jwt_secret.clone());
+    let auth_service =
+        AuthService::with_token_lifetime(state.pool.clone(), state.jwt_secret.clone(), state.access_token_lifetime)
+            .with_issuer(state.token_issuer.clone());
 
     let response = auth_service.register_guest(&request)?;
+    spawn_verification_email(state.pool.clone(), response.user.id);
 
     Ok((StatusCode::CREATED, Json(response.into())))
 }
@@ -100,13 +135,118 @@ pub async fn register(
 /// - 401 Unauthorized: Invalid email or password
 pub async fn login(
     State(state): State<AppState>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    headers: HeaderMap,
     Json(request): Json<GuestLoginRequest>,
 ) -> Result<Json<AuthResponse>, AppError> {
-    let auth_service = AuthService::new(state.pool.clone(), state.jwt_secret.clone());
+    let origin = login_origin(&state, connect_info, &headers);
+    let auth_service =
+        AuthService::with_token_lifetime(state.pool.clone(), state.jwt_secret.clone(), state.access_token_lifetime)
+            .with_issuer(state.token_issuer.clone());
+    let login_events = LoginEventService::new(state.pool.clone());
+
+    match auth_service.login_guest(&request) {
+        Ok(response) => {
+            warn_unrecorded_login(login_events.record(response.user.id, true, &origin));
+            Ok(Json(response.into()))
+        }
+        Err(err @ AppError::Unauthorized(_)) => {
+            warn_unrecorded_login(login_events.record_guest_failure(&request.email, &origin));
+            Err(err)
+        }
+        Err(err) => Err(err),
+    }
+}
+
+/// POST /auth/forgot-password - Email a password reset link
+///
+/// Emails a single-use link, valid for 30 minutes, to the guest account with
+/// the given email. The answer is the same whether or not such an account
+/// exists, and the email is sent in the background so the response time
+/// does not tell either.
+///
+/// # Request Body
+/// ```json
+/// {
+///   "email": "guest@example.com"
+/// }
+/// ```
+///
+/// # Errors
+/// - 400 Bad Request: Not an email address
+pub async fn forgot_password(
+    State(state): State<AppState>,
+    Json(request): Json<ForgotPasswordDto>,
+) -> Result<StatusCode, AppError> {
+    AuthService::validate_email(&request.email)?;
+
+    spawn_password_reset_email(state.pool, request.email);
+
+    Ok(StatusCode::OK)
+}
+
+/// POST /auth/reset-password - Set a new password with an emailed token
+///
+/// # Request Body
+/// ```json
+/// {
+///   "token": "token-from-the-link",
+///   "new_password": "SecurePass123"
+/// }
+/// ```
+///
+/// # Errors
+/// - 400 Bad Request: Weak password, or the token is invalid, used or expired
+pub async fn reset_password(
+    State(state): State<AppState>,
+    Json(request): Json<ResetPasswordDto>,
+) -> Result<StatusCode, AppError> {
+    let service = PasswordResetService::new(state.pool);
+
+    service.reset_password(&request.token, &request.new_password, Utc::now())?;
+
+    Ok(StatusCode::OK)
+}
+
+/// POST /auth/verify-email - Confirm a guest's email with an emailed token
+///
+/// # Request Body
+/// ```json
+/// {
+///   "token": "token-from-the-link"
+/// }
+/// ```
+///
+/// # Errors
+/// - 400 Bad Request: The token is invalid, used or expired
+pub async fn verify_email(
+    State(state): State<AppState>,
+    Json(request): Json<VerifyEmailDto>,
+) -> Result<StatusCode, AppError> {
+    let service = EmailVerificationService::new(state.pool);
+
+    service.verify(&request.token, Utc::now())?;
+
+    Ok(StatusCode::OK)
+}
+
+/// POST /auth/resend-verification - Email the verification link again
+///
+/// Requires a valid guest JWT token. The email is sent in the background.
+///
+/// # Errors
+/// - 400 Bad Request: The email is already verified
+/// - 429 Too Many Requests: A link was sent less than 5 minutes ago
+pub async fn resend_verification(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<StatusCode, AppError> {
+    let service = EmailVerificationService::new(state.pool);
 
-    let response = auth_service.login_guest(&request)?;
+    let outgoing = service.resend(auth_user.user_id, Utc::now())?;
+    email_verification_service::spawn_send(service.mailer()?, outgoing);
 
-    Ok(Json(response.into()))
+    Ok(StatusCode::ACCEPTED)
 }
 
 /// GET /auth/guest/me - Get current guest user info
@@ -1,11 +1,13 @@
 //! Guest booking API handlers
 //!
-//! Handles guest booking creation, listing, and cancellation.
+//! Handles guest booking creation, listing, cancellation, room holds and the
+//! current stay.
 //! All endpoints require guest authentication.
 
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
+    http::{header, StatusCode},
+    response::IntoResponse,
     Extension, Json,
 };
 use chrono::NaiveDate;
@@ -15,8 +17,10 @@ use uuid::Uuid;
 use crate::api::middleware::AuthUser;
 use crate::api::AppState;
 use crate::errors::AppError;
-use crate::models::{BookingStatus, BookingWithRoom, GuestInfo};
-use crate::services::{AuthService, BookingService};
+use crate::models::{BookingEmailKind, BookingHold, BookingStatus, BookingWithRoom, GuestInfo};
+use crate::services::booking_email_service::spawn_booking_email;
+use crate::services::booking_service::CurrentStay;
+use crate::services::{AuthService, BookingService, HoldService};
 
 /// Request body for creating a guest booking
 #[derive(Debug, Deserialize)]
@@ -26,6 +30,17 @@ pub struct CreateGuestBookingRequest {
     pub check_out_date: NaiveDate,
     #[serde(default)]
     pub price: Option<bigdecimal::BigDecimal>,
+    /// Staff-only flag; read only so a guest sending it is refused
+    #[serde(default)]
+    pub override_conflict: Option<bool>,
+}
+
+/// Request body for holding a room while the guest pays
+#[derive(Debug, Deserialize)]
+pub struct CreateHoldRequest {
+    pub room_id: Uuid,
+    pub check_in_date: NaiveDate,
+    pub check_out_date: NaiveDate,
 }
 
 /// Query parameters for listing bookings
@@ -58,10 +73,12 @@ pub struct CancelBookingResponse {
 /// ```
 ///
 /// # Response (201 Created)
-/// Returns the created booking with room details.
+/// Returns the created booking with room details. A confirmation email is
+/// sent to the guest in the background.
 ///
 /// # Errors
 /// - 400 Bad Request: Invalid dates or room under maintenance
+/// - 403 Forbidden: `override_conflict` was sent
 /// - 404 Not Found: Room not found
 /// - 409 Conflict: Room not available for selected dates
 pub async fn create_booking(
@@ -69,6 +86,12 @@ pub async fn create_booking(
     Extension(auth_user): Extension<AuthUser>,
     Json(request): Json<CreateGuestBookingRequest>,
 ) -> Result<(StatusCode, Json<BookingWithRoom>), AppError> {
+    if request.override_conflict.is_some() {
+        return Err(AppError::Forbidden(
+            "Availability conflicts cannot be overridden from the guest portal".to_string(),
+        ));
+    }
+
     // Get guest info to use their name
     let auth_service = AuthService::new(state.pool.clone(), state.jwt_secret.clone());
     let guest_info: GuestInfo = auth_service.get_guest_by_id(auth_user.user_id)?;
@@ -83,6 +106,7 @@ pub async fn create_booking(
         request.check_out_date,
         request.price,
     )?;
+    spawn_booking_email(state.pool.clone(), booking.booking.id, BookingEmailKind::Confirmation);
 
     Ok((StatusCode::CREATED, Json(booking)))
 }
@@ -92,7 +116,7 @@ pub async fn create_booking(
 /// Returns bookings created by the current guest user.
 ///
 /// # Query Parameters
-/// - `status`: Optional filter (upcoming, checked_in, checked_out, cancelled)
+/// - `status`: Optional filter (upcoming, checked_in, checked_out, cancelled, overstay, no_show)
 ///
 /// # Response (200 OK)
 /// Returns an array of bookings with room details.
@@ -103,14 +127,12 @@ pub async fn list_bookings(
 ) -> Result<Json<Vec<BookingWithRoom>>, AppError> {
     let booking_service = BookingService::new(state.pool.clone());
 
-    // Parse status filter
-    let status_filter = query.status.as_ref().and_then(|s| match s.as_str() {
-        "upcoming" => Some(BookingStatus::Upcoming),
-        "checked_in" => Some(BookingStatus::CheckedIn),
-        "checked_out" => Some(BookingStatus::CheckedOut),
-        "cancelled" => Some(BookingStatus::Cancelled),
-        _ => None,
-    });
+    // Parse status filter, rejecting unknown values with the list of valid ones
+    let status_filter = query
+        .status
+        .as_deref()
+        .map(str::parse::<BookingStatus>)
+        .transpose()?;
 
     let bookings = booking_service.list_bookings_by_user(auth_user.user_id, status_filter)?;
 
@@ -140,12 +162,45 @@ pub async fn get_booking(
     Ok(Json(booking))
 }
 
+/// GET /guest/bookings/:id/ics - Download the stay as a calendar event
+///
+/// Returns an iCalendar file with the stay as an all-day event from the
+/// check-in date to the check-out date, for Google or Apple Calendar.
+///
+/// # Path Parameters
+/// - `id`: Booking UUID
+///
+/// # Errors
+/// - 404 Not Found: Booking not found or not owned by user
+pub async fn get_booking_ics(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(booking_id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    let booking_service = BookingService::new(state.pool.clone());
+    let calendar = booking_service.get_guest_booking_ics(booking_id, auth_user.user_id)?;
+
+    Ok((
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, "text/calendar; charset=utf-8".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"booking-{}.ics\"", booking_id),
+            ),
+        ],
+        calendar,
+    ))
+}
+
 /// POST /guest/bookings/:id/cancel - Cancel an upcoming booking
 ///
 /// Cancels the booking only if:
 /// - It belongs to the authenticated guest
 /// - It has status "upcoming"
 ///
+/// A cancellation email is sent to the guest in the background.
+///
 /// # Path Parameters
 /// - `id`: Booking UUID
 ///
@@ -169,12 +224,66 @@ pub async fn cancel_booking(
 ) -> Result<Json<CancelBookingResponse>, AppError> {
     let booking_service = BookingService::new(state.pool.clone());
     let booking = booking_service.cancel_guest_booking(booking_id, auth_user.user_id)?;
+    spawn_booking_email(state.pool.clone(), booking.id, BookingEmailKind::Cancellation);
 
     Ok(Json(CancelBookingResponse {
         id: booking.id,
         reference: booking.reference,
-        status: "cancelled".to_string(),
+        status: booking.status.to_string(),
         message: "Booking cancelled successfully".to_string(),
     }))
 }
 
+/// POST /guest/holds - Hold a room for 15 minutes while the guest pays
+///
+/// Other guests and staff see the room as unavailable for the held dates until
+/// the hold expires or the guest books the same room and dates. Holding the
+/// same stay again renews the hold.
+///
+/// # Request Body
+/// ```json
+/// {
+///   "room_id": "uuid",
+///   "check_in_date": "2025-12-15",
+///   "check_out_date": "2025-12-18"
+/// }
+/// ```
+///
+/// # Response (201 Created)
+/// Returns the hold with its `expires_at` time.
+///
+/// # Errors
+/// - 400 Bad Request: Invalid dates
+/// - 404 Not Found: Room not found
+/// - 409 Conflict: Room not available, or the guest already holds two rooms
+pub async fn create_hold(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(request): Json<CreateHoldRequest>,
+) -> Result<(StatusCode, Json<BookingHold>), AppError> {
+    let hold = HoldService::new(state.pool.clone()).create_hold(
+        auth_user.user_id,
+        request.room_id,
+        request.check_in_date,
+        request.check_out_date,
+    )?;
+
+    Ok((StatusCode::CREATED, Json(hold)))
+}
+
+/// GET /guest/current-stay - Get the guest's in-progress stay
+///
+/// Returns the checked-in (or overstaying) booking with room details,
+/// remaining nights and outstanding balance.
+///
+/// # Errors
+/// - 404 Not Found (`NOT_IN_HOUSE`): The guest is not currently checked in
+pub async fn get_current_stay(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<Json<CurrentStay>, AppError> {
+    let booking_service = BookingService::new(state.pool.clone());
+    let stay = booking_service.current_stay(auth_user.user_id)?;
+
+    Ok(Json(stay))
+}
@@ -7,10 +7,13 @@ use axum::{
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::api::{middleware::AuthUser, AppState};
+use crate::api::{
+    middleware::{is_staff_role, AuthUser},
+    AppState,
+};
 use crate::errors::AppError;
 use crate::models::{BookingWithRoom, GuestNote, UpdateUser, User};
-use crate::services::GuestService;
+use crate::services::{GuestBookingStats, GuestService, InHouseGuest};
 use crate::utils::{validate_email, validate_phone, validate_search_query};
 
 /// Guest search query parameters
@@ -22,7 +25,16 @@ pub struct SearchGuestsQuery {
 /// Guest search response
 #[derive(Debug, Serialize)]
 pub struct GuestSearchResponse {
-    pub guests: Vec<GuestResponse>,
+    pub guests: Vec<GuestSearchResult>,
+}
+
+/// Guest entry in list/search results with booking counts
+#[derive(Debug, Serialize)]
+pub struct GuestSearchResult {
+    #[serde(flatten)]
+    pub guest: GuestResponse,
+    #[serde(flatten)]
+    pub stats: GuestBookingStats,
 }
 
 /// Guest response with full PII
@@ -49,6 +61,19 @@ impl From<User> for GuestResponse {
     }
 }
 
+/// Attach booking stats to each guest, keeping the original order
+fn with_booking_stats(guest_service: &GuestService, guests: Vec<User>) -> Result<Vec<GuestSearchResult>, AppError> {
+    let mut stats = guest_service.get_booking_stats(&guests)?;
+
+    Ok(guests
+        .into_iter()
+        .map(|user| GuestSearchResult {
+            stats: stats.remove(&user.id).unwrap_or_default(),
+            guest: GuestResponse::from(user),
+        })
+        .collect())
+}
+
 /// Guest profile response with booking history
 /// This includes the full BookingWithRoom struct (so the frontend sees the Price)
 #[derive(Debug, Serialize)]
@@ -90,6 +115,19 @@ impl From<GuestNote> for GuestNoteResponse {
     }
 }
 
+/// In-house directory query parameters
+#[derive(Debug, Deserialize)]
+pub struct InHouseQuery {
+    /// Exact room number, for "who is in 204?"
+    pub room_number: Option<String>,
+}
+
+/// In-house directory response
+#[derive(Debug, Serialize)]
+pub struct InHouseResponse {
+    pub guests: Vec<InHouseGuest>,
+}
+
 /// Add guest note request
 #[derive(Debug, Deserialize)]
 pub struct AddGuestNoteRequest {
@@ -98,6 +136,25 @@ pub struct AddGuestNoteRequest {
 
 // ---------------- HANDLERS ----------------
 
+/// List checked-in and overstaying guests by room number with contact
+/// details, balance due and unread chat
+/// GET /staff/in-house?room_number=204
+pub async fn list_in_house_guests(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(query): Query<InHouseQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    if !is_staff_role(auth_user.role) {
+        return Err(AppError::Forbidden(
+            "Only front desk staff can view in-house guests".to_string(),
+        ));
+    }
+
+    let room_number = query.room_number.as_deref().map(str::trim).filter(|n| !n.is_empty());
+    let guests = GuestService::new(state.pool).in_house_directory(room_number)?;
+    Ok(Json(InHouseResponse { guests }))
+}
+
 /// List all guest user accounts (role = guest)
 /// GET /admin/guests
 pub async fn list_guests(
@@ -108,7 +165,7 @@ pub async fn list_guests(
     let guests = guest_service.list_guests()?;
 
     Ok(Json(GuestSearchResponse {
-        guests: guests.into_iter().map(GuestResponse::from).collect(),
+        guests: with_booking_stats(&guest_service, guests)?,
     }))
 }
 
@@ -131,7 +188,7 @@ pub async fn search_guests(
     }
 
     Ok(Json(GuestSearchResponse {
-        guests: guests.into_iter().map(GuestResponse::from).collect(),
+        guests: with_booking_stats(&guest_service, guests)?,
     }))
 }
 
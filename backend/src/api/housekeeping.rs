@@ -0,0 +1,50 @@
+use axum::{
+    extract::{Extension, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use chrono::Utc;
+use serde::Deserialize;
+
+use crate::api::{middleware::AuthUser, AppState};
+use crate::errors::AppError;
+use crate::scheduler;
+use crate::services::HoldService;
+
+/// Purge expired booking holds request DTO
+#[derive(Debug, Deserialize)]
+pub struct PurgeHoldsDto {
+    /// Only count the holds a purge would delete
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// Booking holds by state and age, with the most recent ones
+/// GET /admin/housekeeping-data/holds
+pub async fn get_holds_overview(State(state): State<AppState>) -> Result<impl IntoResponse, AppError> {
+    let service = HoldService::new(state.pool);
+    let overview = service.overview(Utc::now())?;
+    Ok((StatusCode::OK, Json(overview)))
+}
+
+/// Delete holds expired for longer than the retention setting now, instead
+/// of waiting for the cleanup job
+/// POST /admin/housekeeping-data/holds/purge
+pub async fn purge_holds(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(payload): Json<PurgeHoldsDto>,
+) -> Result<impl IntoResponse, AppError> {
+    if payload.dry_run {
+        let service = HoldService::new(state.pool);
+        let purge = service.purge_expired(Utc::now(), true, Some(auth_user.user_id))?;
+        return Ok((StatusCode::OK, Json(purge)));
+    }
+
+    let purge = scheduler::purge_expired_holds(&state.pool, &state.jobs, Some(auth_user.user_id))
+        .await
+        .ok_or_else(|| AppError::Conflict("A hold cleanup is already running".to_string()))?
+        .map_err(AppError::DatabaseError)?;
+    Ok((StatusCode::OK, Json(purge)))
+}
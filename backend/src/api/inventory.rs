@@ -66,10 +66,8 @@ pub async fn update_inventory_item(
     // Cleaner can ONLY update 'status', 'notes', and 'quantity' (reporting usage).
     // Cleaner CANNOT update 'price' or 'name'.
     
-    if auth_user.role != UserRole::Admin {
-        if payload.price.is_some() || payload.name.is_some() {
-            return Err(AppError::Forbidden("Cleaners cannot edit price or name".to_string()));
-        }
+    if auth_user.role != UserRole::Admin && (payload.price.is_some() || payload.name.is_some()) {
+        return Err(AppError::Forbidden("Cleaners cannot edit price or name".to_string()));
     }
 
     let item = service.update_item(id, payload)?;
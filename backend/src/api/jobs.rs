@@ -0,0 +1,33 @@
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use serde::Serialize;
+
+use crate::api::AppState;
+use crate::errors::AppError;
+use crate::models::ReportDelivery;
+use crate::scheduler::JobStatus;
+use crate::services::ReportService;
+
+/// Background jobs overview response
+#[derive(Debug, Serialize)]
+pub struct JobsResponse {
+    pub read_only: bool,
+    pub jobs: Vec<JobStatus>,
+    /// Report emails that failed or are waiting to retry after an error
+    pub failing_report_deliveries: Vec<ReportDelivery>,
+}
+
+/// Show background job state and failing deliveries
+/// GET /admin/jobs
+pub async fn list_jobs(State(state): State<AppState>) -> Result<impl IntoResponse, AppError> {
+    let service = ReportService::new(state.pool.clone());
+    let failing_report_deliveries = service.troubled_deliveries(50)?;
+
+    Ok((
+        StatusCode::OK,
+        Json(JobsResponse {
+            read_only: state.read_only.is_enabled(),
+            jobs: state.jobs.snapshot(),
+            failing_report_deliveries,
+        }),
+    ))
+}
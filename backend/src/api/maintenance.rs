@@ -0,0 +1,46 @@
+use axum::{
+    extract::{Extension, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use serde::Deserialize;
+
+use crate::api::{middleware::AuthUser, AppState};
+use crate::errors::AppError;
+use crate::services::MaintenanceService;
+
+/// Toggle read-only mode request DTO
+#[derive(Debug, Deserialize)]
+pub struct SetReadOnlyModeDto {
+    pub enabled: bool,
+    /// Stored in the audit log
+    pub reason: Option<String>,
+}
+
+/// Get the current read-only mode
+/// GET /admin/read-only
+pub async fn get_read_only_mode(State(state): State<AppState>) -> impl IntoResponse {
+    (StatusCode::OK, Json(state.read_only.status()))
+}
+
+/// Turn read-only mode on or off. The READ_ONLY_MODE env override, when set,
+/// still wins; the response reports the effective state.
+/// POST /admin/read-only
+pub async fn set_read_only_mode(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(payload): Json<SetReadOnlyModeDto>,
+) -> Result<impl IntoResponse, AppError> {
+    let service = MaintenanceService::new(state.pool.clone());
+    service.set_read_only_setting(payload.enabled, auth_user.user_id, payload.reason.as_deref())?;
+    state.read_only.set_setting(payload.enabled);
+
+    tracing::warn!(
+        "Read-only mode setting {} by admin {}",
+        if payload.enabled { "enabled" } else { "disabled" },
+        auth_user.user_id
+    );
+
+    Ok((StatusCode::OK, Json(state.read_only.status())))
+}
@@ -1,14 +1,24 @@
 use axum::{
     extract::{Request, State},
-    http::{header::AUTHORIZATION, StatusCode},
-    middleware::Next,
-    response::Response,
+    http::{
+        header::{AUTHORIZATION, CONTENT_TYPE, RETRY_AFTER},
+        Method, StatusCode,
+    },
+    middleware::{from_fn_with_state, FromFnLayer, Next},
+    response::{IntoResponse, Response},
 };
+use futures::future::BoxFuture;
+
+use chrono::Utc;
+use uuid::Uuid;
 
 use crate::api::AppState;
-use crate::errors::AppError;
+use crate::errors::{AppError, AppResult};
 use crate::models::UserRole;
-use crate::services::AuthService;
+use crate::services::maintenance_service::{READ_ONLY_MESSAGE, READ_ONLY_RETRY_AFTER_SECS};
+use crate::services::session_service::{Activity, SESSION_EXPIRED_MESSAGE};
+use crate::services::auth_service::PASSWORD_CHANGE_REQUIRED_MESSAGE;
+use crate::services::{ApiKeyService, AuthService, ReadOnlyMode, SessionService};
 
 /// Extension to hold authenticated user info
 #[derive(Clone, Debug)]
@@ -27,118 +37,180 @@ fn extract_token(request: &Request) -> Option<String> {
         .and_then(|value| value.strip_prefix("Bearer ").map(|s| s.to_string()))
 }
 
-/// Middleware to require authentication
-#[allow(dead_code)]
-pub async fn require_auth(
-    State(state): State<AppState>,
-    mut request: Request,
-    next: Next,
-) -> Result<Response, (StatusCode, axum::Json<serde_json::Value>)> {
-    let token = extract_token(&request).ok_or_else(|| {
-        (
-            StatusCode::UNAUTHORIZED,
-            axum::Json(serde_json::json!({
-                "code": "UNAUTHORIZED",
-                "message": "Missing or invalid authorization header"
-            })),
-        )
-    })?;
+/// Extract an integration's key from an `Authorization: ApiKey <key>` header
+fn extract_api_key(request: &Request) -> Option<String> {
+    request
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("ApiKey ").map(|s| s.trim().to_string()))
+}
 
-    let auth_service = AuthService::new(state.pool.clone(), state.jwt_secret.clone());
+/// Path of the request relative to the API router, recorded before any
+/// nested router strips its prefix
+#[derive(Clone, Debug)]
+struct ApiPath(String);
+
+/// Record the API-relative path for layers inside nested routers. Unlike
+/// `OriginalUri` it leaves out the prefix the API is mounted under.
+pub async fn record_api_path(mut request: Request, next: Next) -> Response {
+    let path = ApiPath(request.uri().path().to_string());
+    request.extensions_mut().insert(path);
+    next.run(request).await
+}
 
-    let claims = auth_service.validate_token(&token).map_err(|e| {
-        (
-            StatusCode::UNAUTHORIZED,
-            axum::Json(serde_json::json!({
-                "code": "UNAUTHORIZED",
-                "message": e.to_string()
-            })),
-        )
-    })?;
+/// Path of the request relative to the API router, e.g. `/auth/me`
+fn api_path(request: &Request) -> &str {
+    request
+        .extensions()
+        .get::<ApiPath>()
+        .map_or(request.uri().path(), |path| path.0.as_str())
+}
 
-    // Add user info to request extensions
-    let auth_user = AuthUser {
-        user_id: claims.sub,
-        role: claims.role,
+/// Record activity on a token's session and refuse it once the session has
+/// been idle past the limit for the role. Tokens issued before sessions were
+/// tracked carry no session and only expire with the JWT.
+pub fn check_session(state: &AppState, session_id: Option<Uuid>, role: UserRole) -> AppResult<()> {
+    let Some(session_id) = session_id else {
+        return Ok(());
+    };
+    let now = Utc::now();
+    let sessions = SessionService::new(state.pool.clone());
+
+    let activity = match state.sessions.touch(session_id, role, now) {
+        Activity::Unknown => match sessions.last_seen(session_id)? {
+            Some(last_seen) => state.sessions.resume(session_id, role, last_seen, now),
+            None => Activity::Expired,
+        },
+        activity => activity,
     };
-    request.extensions_mut().insert(auth_user);
 
-    Ok(next.run(request).await)
+    match activity {
+        Activity::Active { flush } => {
+            if flush {
+                // Write-behind: a failed write only makes a restart see older activity
+                if let Err(e) = sessions.record_seen(session_id, now) {
+                    tracing::warn!("Could not record session activity: {}", e);
+                }
+            }
+            Ok(())
+        }
+        Activity::Expired | Activity::Unknown => {
+            sessions.end(session_id)?;
+            Err(AppError::SessionExpired(SESSION_EXPIRED_MESSAGE.to_string()))
+        }
+    }
 }
 
-/// Middleware to require admin role
-#[allow(dead_code)]
-pub async fn require_admin(
-    State(state): State<AppState>,
-    mut request: Request,
-    next: Next,
-) -> Result<Response, (StatusCode, axum::Json<serde_json::Value>)> {
-    let token = extract_token(&request).ok_or_else(|| {
-        (
-            StatusCode::UNAUTHORIZED,
-            axum::Json(serde_json::json!({
-                "code": "UNAUTHORIZED",
-                "message": "Missing or invalid authorization header"
-            })),
-        )
-    })?;
-
-    let auth_service = AuthService::new(state.pool.clone(), state.jwt_secret.clone());
-
-    let claims = auth_service.validate_token(&token).map_err(|e| {
-        (
-            StatusCode::UNAUTHORIZED,
-            axum::Json(serde_json::json!({
-                "code": "UNAUTHORIZED",
-                "message": e.to_string()
-            })),
-        )
-    })?;
+/// Marks a request whose account was already found active, so stacked auth
+/// layers only look the account up once
+#[derive(Clone, Copy, Debug)]
+struct ActiveAccount {
+    user_id: Uuid,
+    must_change_password: bool,
+}
 
-    // Check if user is admin
-    if claims.role != UserRole::Admin {
-        return Err((
-            StatusCode::FORBIDDEN,
-            axum::Json(serde_json::json!({
-                "code": "FORBIDDEN",
-                "message": "Admin access required"
-            })),
-        ));
+/// Refuse tokens of accounts deactivated after the token was issued.
+/// Returns whether the account must change its password first.
+fn check_account_active(state: &AppState, request: &mut Request, user_id: Uuid) -> AppResult<bool> {
+    if let Some(active) = request
+        .extensions()
+        .get::<ActiveAccount>()
+        .filter(|active| active.user_id == user_id)
+    {
+        return Ok(active.must_change_password);
     }
 
-    // Add user info to request extensions
-    let auth_user = AuthUser {
-        user_id: claims.sub,
-        role: claims.role,
-    };
-    request.extensions_mut().insert(auth_user);
+    let must_change_password =
+        AuthService::new(state.pool.clone(), state.jwt_secret.clone()).ensure_active(user_id)?;
+    request.extensions_mut().insert(ActiveAccount {
+        user_id,
+        must_change_password,
+    });
+    Ok(must_change_password)
+}
 
-    Ok(next.run(request).await)
+/// Routes open to a user who must change their password. They may read
+/// their profile but not edit it.
+const PASSWORD_CHANGE_ROUTES: &[(Method, &str)] = &[
+    (Method::POST, "/auth/change-password"),
+    (Method::GET, "/auth/me"),
+];
+
+/// Refuse everything else while the account must change its password. The
+/// flag is read from the account rather than the token, so it applies to
+/// tokens issued before an admin reset and lifts as soon as it is changed.
+fn check_password_change(request: &Request, must_change_password: bool) -> AppResult<()> {
+    if !must_change_password {
+        return Ok(());
+    }
+    let path = api_path(request);
+    if PASSWORD_CHANGE_ROUTES
+        .iter()
+        .any(|(method, allowed)| method == request.method() && *allowed == path)
+    {
+        return Ok(());
+    }
+    Err(AppError::PasswordChangeRequired(PASSWORD_CHANGE_REQUIRED_MESSAGE.to_string()))
 }
 
-/// Helper to get authenticated user from request extensions
-#[allow(dead_code)]
-pub fn get_auth_user(request: &Request) -> Result<AuthUser, AppError> {
-    request
-        .extensions()
-        .get::<AuthUser>()
-        .cloned()
-        .ok_or_else(|| AppError::Unauthorized("Not authenticated".to_string()))
+/// Turn a failed token, session or account check into the middleware error body
+fn session_rejection(error: AppError) -> (StatusCode, axum::Json<serde_json::Value>) {
+    let (status, code, message) = match error {
+        AppError::SessionExpired(msg) => (StatusCode::UNAUTHORIZED, "SESSION_EXPIRED", msg),
+        AppError::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, "UNAUTHORIZED", msg),
+        AppError::Forbidden(msg) => (StatusCode::FORBIDDEN, "FORBIDDEN", msg),
+        AppError::PasswordChangeRequired(msg) => (StatusCode::FORBIDDEN, "PASSWORD_CHANGE_REQUIRED", msg),
+        other => {
+            tracing::error!("Session check failed: {}", other);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "INTERNAL_ERROR",
+                "An internal error occurred".to_string(),
+            )
+        }
+    };
+    (
+        status,
+        axum::Json(serde_json::json!({
+            "code": code,
+            "message": message
+        })),
+    )
 }
 
-/// Helper to check if a role is considered admin
-pub fn is_admin_role(role: UserRole) -> bool {
-    matches!(role, UserRole::Admin)
+/// Endpoints that act as the signed-in person (their account, chat and
+/// guest portal), which an integration must not reach through its creator
+const API_KEY_REFUSED_PREFIXES: &[&str] = &["/auth/", "/chat/", "/guest/"];
+
+/// Authenticate an integration by its API key. The key acts with its own
+/// role on behalf of the admin who created it, and cannot use the personal
+/// endpoints in `API_KEY_REFUSED_PREFIXES`.
+fn authenticate_api_key(state: &AppState, request: &Request, key: &str) -> AppResult<AuthUser> {
+    let path = api_path(request);
+    if API_KEY_REFUSED_PREFIXES.iter().any(|prefix| path.starts_with(prefix)) {
+        return Err(AppError::Forbidden(
+            "API keys cannot be used for personal endpoints".to_string(),
+        ));
+    }
+    let api_key = ApiKeyService::new(state.pool.clone()).authenticate(key, Utc::now())?;
+    Ok(AuthUser {
+        user_id: api_key.created_by,
+        role: api_key.role,
+    })
 }
 
+/// Validate the bearer token, its session and account, and return the user.
+/// Integrations may present an API key instead.
+fn authenticate(
+    state: &AppState,
+    request: &mut Request,
+) -> Result<AuthUser, (StatusCode, axum::Json<serde_json::Value>)> {
+    if let Some(key) = extract_api_key(request) {
+        return authenticate_api_key(state, request, &key).map_err(session_rejection);
+    }
 
-/// Middleware to require guest role
-pub async fn require_guest(
-    State(state): State<AppState>,
-    mut request: Request,
-    next: Next,
-) -> Result<Response, (StatusCode, axum::Json<serde_json::Value>)> {
-    let token = extract_token(&request).ok_or_else(|| {
+    let token = extract_token(request).ok_or_else(|| {
         (
             StatusCode::UNAUTHORIZED,
             axum::Json(serde_json::json!({
@@ -148,183 +220,193 @@ pub async fn require_guest(
         )
     })?;
 
-    let auth_service = AuthService::new(state.pool.clone(), state.jwt_secret.clone());
-
-    let claims = auth_service.validate_token(&token).map_err(|e| {
-        (
-            StatusCode::UNAUTHORIZED,
-            axum::Json(serde_json::json!({
-                "code": "UNAUTHORIZED",
-                "message": e.to_string()
-            })),
-        )
-    })?;
+    let auth_service = AuthService::new(state.pool.clone(), state.jwt_secret.clone())
+        .with_issuer(state.token_issuer.clone())
+        .with_revocations(state.revocations.clone());
 
-    // Check if user is a guest
-    if claims.role != UserRole::Guest {
-        return Err((
-            StatusCode::FORBIDDEN,
-            axum::Json(serde_json::json!({
-                "code": "FORBIDDEN",
-                "message": "Guest access only"
-            })),
-        ));
-    }
+    // Checking revocation reads the database, whose errors are not the token's fault
+    let claims = auth_service.validate_token(&token).map_err(session_rejection)?;
+    check_session(state, claims.sid, claims.role).map_err(session_rejection)?;
+    let must_change_password = check_account_active(state, request, claims.sub).map_err(session_rejection)?;
+    check_password_change(request, must_change_password).map_err(session_rejection)?;
 
-    // Add user info to request extensions
-    let auth_user = AuthUser {
+    Ok(AuthUser {
         user_id: claims.sub,
         role: claims.role,
-    };
-    request.extensions_mut().insert(auth_user);
-
-    Ok(next.run(request).await)
+    })
 }
 
-/// Middleware to require admin role
-#[allow(dead_code)]
-pub async fn require_staff(
+/// Middleware to require authentication
+pub async fn require_auth(
     State(state): State<AppState>,
     mut request: Request,
     next: Next,
 ) -> Result<Response, (StatusCode, axum::Json<serde_json::Value>)> {
-    let token = extract_token(&request).ok_or_else(|| {
-        (
-            StatusCode::UNAUTHORIZED,
-            axum::Json(serde_json::json!({
-                "code": "UNAUTHORIZED",
-                "message": "Missing or invalid authorization header"
-            })),
-        )
-    })?;
-
-    let auth_service = AuthService::new(state.pool.clone(), state.jwt_secret.clone());
-
-    let claims = auth_service.validate_token(&token).map_err(|e| {
-        (
-            StatusCode::UNAUTHORIZED,
-            axum::Json(serde_json::json!({
-                "code": "UNAUTHORIZED",
-                "message": e.to_string()
-            })),
-        )
-    })?;
-
-    // Check if user is admin
-    if !is_admin_role(claims.role) {
-        return Err((
-            StatusCode::FORBIDDEN,
-            axum::Json(serde_json::json!({
-                "code": "FORBIDDEN",
-                "message": "Admin access required"
-            })),
-        ));
-    }
+    let auth_user = authenticate(&state, &mut request)?;
 
     // Add user info to request extensions
-    let auth_user = AuthUser {
-        user_id: claims.sub,
-        role: claims.role,
-    };
     request.extensions_mut().insert(auth_user);
 
     Ok(next.run(request).await)
 }
 
-/// Middleware to require cleaner role
-pub async fn require_cleaner(
+/// Middleware for public routes that behave differently for signed-in users:
+/// a valid token adds the user info, anything else (including a user who
+/// must change their password) continues anonymously
+pub async fn attach_optional_auth(
     State(state): State<AppState>,
     mut request: Request,
     next: Next,
-) -> Result<Response, (StatusCode, axum::Json<serde_json::Value>)> {
-    let token = extract_token(&request).ok_or_else(|| {
-        (
-            StatusCode::UNAUTHORIZED,
-            axum::Json(serde_json::json!({
-                "code": "UNAUTHORIZED",
-                "message": "Missing or invalid authorization header"
-            })),
-        )
-    })?;
+) -> Response {
+    if let Some(token) = extract_token(&request) {
+        let auth_service = AuthService::new(state.pool.clone(), state.jwt_secret.clone())
+            .with_issuer(state.token_issuer.clone())
+            .with_revocations(state.revocations.clone());
+        if let Ok(claims) = auth_service.validate_token(&token) {
+            if check_session(&state, claims.sid, claims.role).is_ok()
+                && matches!(check_account_active(&state, &mut request, claims.sub), Ok(false))
+            {
+                request.extensions_mut().insert(AuthUser {
+                    user_id: claims.sub,
+                    role: claims.role,
+                });
+            }
+        }
+    }
 
-    let auth_service = AuthService::new(state.pool.clone(), state.jwt_secret.clone());
+    next.run(request).await
+}
 
-    let claims = auth_service.validate_token(&token).map_err(|e| {
-        (
-            StatusCode::UNAUTHORIZED,
-            axum::Json(serde_json::json!({
-                "code": "UNAUTHORIZED",
-                "message": e.to_string()
-            })),
-        )
-    })?;
+/// Front-desk staff roles
+pub const STAFF_ROLES: &[UserRole] = &[UserRole::Admin, UserRole::Receptionist];
 
-    // Check if user is a cleaner
-    if claims.role != UserRole::Cleaner {
-        return Err((
-            StatusCode::FORBIDDEN,
-            axum::Json(serde_json::json!({
-                "code": "FORBIDDEN",
-                "message": "Cleaner access required"
-            })),
-        ));
-    }
+/// State of a `require_roles` layer
+#[derive(Clone)]
+pub struct RoleGuard {
+    state: AppState,
+    roles: &'static [UserRole],
+}
 
-    // Add user info to request extensions
-    let auth_user = AuthUser {
-        user_id: claims.sub,
-        role: claims.role,
-    };
-    request.extensions_mut().insert(auth_user);
+type RoleGuardFn = fn(State<RoleGuard>, Request, Next) -> BoxFuture<'static, Response>;
+
+/// Layer built by `require_roles`
+pub type RoleLayer = FromFnLayer<RoleGuardFn, RoleGuard, (State<RoleGuard>, Request)>;
+
+/// Middleware layer requiring authentication by a user with one of `roles`.
+/// Other users get 403; the user info is added to the request extensions.
+pub fn require_roles(state: &AppState, roles: &'static [UserRole]) -> RoleLayer {
+    from_fn_with_state(
+        RoleGuard {
+            state: state.clone(),
+            roles,
+        },
+        guard_roles as RoleGuardFn,
+    )
+}
 
-    Ok(next.run(request).await)
+fn guard_roles(State(guard): State<RoleGuard>, mut request: Request, next: Next) -> BoxFuture<'static, Response> {
+    Box::pin(async move {
+        let auth_user = match authenticate(&guard.state, &mut request) {
+            Ok(auth_user) => auth_user,
+            Err(rejection) => return rejection.into_response(),
+        };
+
+        if !guard.roles.contains(&auth_user.role) {
+            let roles: Vec<String> = guard.roles.iter().map(UserRole::to_string).collect();
+            return (
+                StatusCode::FORBIDDEN,
+                axum::Json(serde_json::json!({
+                    "code": "FORBIDDEN",
+                    "message": format!("{} access required", roles.join(" or "))
+                })),
+            )
+                .into_response();
+        }
+
+        request.extensions_mut().insert(auth_user);
+        next.run(request).await
+    })
+}
+
+/// Middleware layer requiring admin role
+pub fn require_admin(state: &AppState) -> RoleLayer {
+    require_roles(state, &[UserRole::Admin])
+}
+
+/// Middleware layer requiring guest role
+pub fn require_guest(state: &AppState) -> RoleLayer {
+    require_roles(state, &[UserRole::Guest])
+}
+
+/// Middleware layer requiring cleaner role
+pub fn require_cleaner(state: &AppState) -> RoleLayer {
+    require_roles(state, &[UserRole::Cleaner])
+}
+
+/// Middleware layer requiring admin or cleaner role
+pub fn require_admin_or_cleaner(state: &AppState) -> RoleLayer {
+    require_roles(state, &[UserRole::Admin, UserRole::Cleaner])
+}
+
+/// Helper to get authenticated user from request extensions
+#[allow(dead_code)]
+pub fn get_auth_user(request: &Request) -> Result<AuthUser, AppError> {
+    request
+        .extensions()
+        .get::<AuthUser>()
+        .cloned()
+        .ok_or_else(|| AppError::Unauthorized("Not authenticated".to_string()))
+}
+
+/// Helper to check if a role is considered admin
+pub fn is_admin_role(role: UserRole) -> bool {
+    matches!(role, UserRole::Admin)
 }
 
-/// Middleware to require admin or cleaner role
-pub async fn require_admin_or_cleaner(
+/// Check if a role is front-desk staff (admin or receptionist)
+pub fn is_staff_role(role: UserRole) -> bool {
+    STAFF_ROLES.contains(&role)
+}
+
+/// Middleware refusing mutating requests while read-only mode is on.
+/// Reads and auth endpoints pass through; everything else gets a 503 with
+/// `Retry-After` so clients back off until maintenance is over.
+pub async fn reject_writes_when_read_only(
     State(state): State<AppState>,
-    mut request: Request,
+    request: Request,
     next: Next,
-) -> Result<Response, (StatusCode, axum::Json<serde_json::Value>)> {
-    let token = extract_token(&request).ok_or_else(|| {
-        (
-            StatusCode::UNAUTHORIZED,
+) -> Response {
+    if state.read_only.is_enabled()
+        && !ReadOnlyMode::allows_request(request.method(), request.uri().path())
+    {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            [(RETRY_AFTER, READ_ONLY_RETRY_AFTER_SECS.to_string())],
             axum::Json(serde_json::json!({
-                "code": "UNAUTHORIZED",
-                "message": "Missing or invalid authorization header"
+                "code": "READ_ONLY_MODE",
+                "message": READ_ONLY_MESSAGE
             })),
         )
-    })?;
+            .into_response();
+    }
 
-    let auth_service = AuthService::new(state.pool.clone(), state.jwt_secret.clone());
+    next.run(request).await
+}
 
-    let claims = auth_service.validate_token(&token).map_err(|e| {
-        (
-            StatusCode::UNAUTHORIZED,
-            axum::Json(serde_json::json!({
-                "code": "UNAUTHORIZED",
-                "message": e.to_string()
-            })),
-        )
-    })?;
+/// Middleware turning the plain-text 413 produced by body size limits into the
+/// usual JSON `ErrorResponse`. Responses that are already JSON pass through.
+pub async fn structured_payload_too_large(request: Request, next: Next) -> Response {
+    let response = next.run(request).await;
 
-    // Check if user is admin or cleaner
-    if claims.role != UserRole::Admin && claims.role != UserRole::Cleaner {
-        return Err((
-            StatusCode::FORBIDDEN,
-            axum::Json(serde_json::json!({
-                "code": "FORBIDDEN",
-                "message": "Admin or Cleaner access required"
-            })),
-        )); 
-    }
+    let is_json = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/json"));
 
-    // Add user info to request extensions
-    let auth_user = AuthUser {
-        user_id: claims.sub,
-        role: claims.role,
-    };
-    request.extensions_mut().insert(auth_user);
+    if response.status() == StatusCode::PAYLOAD_TOO_LARGE && !is_json {
+        return AppError::PayloadTooLarge("Request body is too large".to_string()).into_response();
+    }
 
-    Ok(next.run(request).await)
-}
\ No newline at end of file
+    response
+}
@@ -1,4 +1,7 @@
+pub mod api_keys;
 pub mod auth;
+pub mod availability;
+pub mod booking_emails;
 pub mod bookings;
 pub mod chat;
 pub mod employees;
@@ -6,20 +9,38 @@ pub mod financial;
 pub mod guest_auth;
 pub mod guest_bookings;
 pub mod guests;
+pub mod housekeeping;
 pub mod middleware;
+pub mod no_show_charges;
 pub mod payments;
+pub mod pricing_rules;
+pub mod public_bookings;
+pub mod reconciliations;
+pub mod report_subscriptions;
+pub mod room_blocks;
+pub mod room_photos;
+pub mod room_types;
 pub mod rooms;
+pub mod tickets;
 pub mod inventory;
+pub mod jobs;
+pub mod maintenance;
 mod settings;
 
 use axum::{
+    extract::DefaultBodyLimit,
     middleware as axum_middleware,
     routing::{delete, get, patch, post},
     Router,
 };
 
+use crate::config::BodyLimits;
 use crate::db::DbPool;
 use crate::api::chat::ChatState;
+use crate::api::public_bookings::PublicLookupLimits;
+use crate::scheduler::JobBoard;
+use crate::services::rate_limit_service::LoginAttemptStore;
+use crate::services::{ReadOnlyMode, RevocationCache, SessionTracker, TokenIssuer};
 use std::sync::Arc;
 
 /// Application state shared across handlers
@@ -29,6 +50,22 @@ pub struct AppState {
     pub jwt_secret: String,
     pub chat_state: Arc<ChatState>,
     pub s3_client: aws_sdk_s3::Client,
+    pub read_only: Arc<ReadOnlyMode>,
+    pub public_lookup: Arc<PublicLookupLimits>,
+    pub jobs: Arc<JobBoard>,
+    pub body_limits: BodyLimits,
+    /// Last activity per login session, for the idle timeout
+    pub sessions: Arc<SessionTracker>,
+    /// Lifetime of issued access tokens
+    pub access_token_lifetime: chrono::Duration,
+    /// Failed staff logins per username and client IP, for the lockout
+    pub login_attempts: Arc<dyn LoginAttemptStore>,
+    /// Revoked access tokens per user, cached between requests
+    pub revocations: Arc<RevocationCache>,
+    /// Whether the client IP is taken from X-Forwarded-For
+    pub trust_proxy_headers: bool,
+    /// Issuer and audience of access tokens
+    pub token_issuer: TokenIssuer,
 }
 
 /// Create the API router with all routes
@@ -36,8 +73,21 @@ pub fn create_router(state: AppState) -> Router {
     // Staff auth routes
     let auth_routes = Router::new()
         .route("/login", post(auth::login))
-        .route("/me", get(auth::me))
-        .route("/users", post(auth::create_user))
+        // Refresh and logout take the refresh token, so an expired access
+        // token does not stand in the way
+        .route("/refresh", post(auth::refresh))
+        .route("/logout", post(auth::logout))
+        .route(
+            "/me",
+            get(auth::me).patch(auth::update_me).layer(axum_middleware::from_fn_with_state(
+                state.clone(),
+                middleware::require_auth,
+            )),
+        )
+        .route(
+            "/users",
+            post(auth::create_user).layer(middleware::require_admin(&state)),
+        )
         .route(
             "/change-password",
             post(auth::change_password).layer(axum_middleware::from_fn_with_state(
@@ -49,21 +99,24 @@ pub fn create_router(state: AppState) -> Router {
         .route("/register", post(guest_auth::register))
         // Guest login (public)
         .route("/guest/login", post(guest_auth::login))
+        // Guest password reset by emailed link (public)
+        .route("/forgot-password", post(guest_auth::forgot_password))
+        .route("/reset-password", post(guest_auth::reset_password))
+        // Guest email verification by emailed link (public), and resending it
+        .route("/verify-email", post(guest_auth::verify_email))
+        .route(
+            "/resend-verification",
+            post(guest_auth::resend_verification).layer(middleware::require_guest(&state)),
+        )
         // Guest me (requires guest auth)
         .route(
             "/guest/me",
-            get(guest_auth::me).layer(axum_middleware::from_fn_with_state(
-                state.clone(),
-                middleware::require_guest,
-            )),
+            get(guest_auth::me).layer(middleware::require_guest(&state)),
         )
         // Guest change password (requires guest auth)
         .route(
             "/guest/change-password",
-            post(guest_auth::change_password).layer(axum_middleware::from_fn_with_state(
-                state.clone(),
-                middleware::require_guest,
-            )),
+            post(guest_auth::change_password).layer(middleware::require_guest(&state)),
         );
 
 
@@ -72,28 +125,65 @@ pub fn create_router(state: AppState) -> Router {
         // Available rooms endpoint is public (no auth required) for guests to search
         .route("/available", get(rooms::available_rooms))
         .route("/", get(rooms::list_rooms))
-        .route("/:id", get(rooms::get_room));
+        .route("/grouped", get(rooms::list_rooms_grouped))
+        .route("/:id", get(rooms::get_room))
+        .route("/:id/photos", get(room_photos::list_room_photos))
+        .route("/types", get(room_types::list_active_room_types))
+        // Admins may also list decommissioned rooms
+        .layer(axum_middleware::from_fn_with_state(
+            state.clone(),
+            middleware::attach_optional_auth,
+        ));
     
     // Protected room routes (require staff auth)
     let protected_room_routes = Router::new()
         // .route("/", get(rooms::list_rooms).post(rooms::create_room))
         // .route("/:id", get(rooms::get_room).patch(rooms::update_room))
         .route("/", post(rooms::create_room))
+        .route("/:id", delete(rooms::decommission_room))
+        // Only admins manage the room inventory
+        .layer(middleware::require_admin(&state));
+    
+    // Room occupancy calendars, overview, summary, number search, booking and status history, and room
+    // edits (front desk only; the handler lets only admins change the price)
+    let room_calendar_routes = Router::new()
         .route("/:id", patch(rooms::update_room))
-        // Require staff authentication for room management (admin/receptionist)
-        // Note: Middleware is applied bottom-up, so require_auth (outermost) is added last
-        .layer(axum_middleware::from_fn_with_state(
-            state.clone(),
-            middleware::require_staff,
-        ))
+        .route("/calendar", get(rooms::get_rooms_calendar))
+        .route("/summary", get(rooms::get_rooms_summary))
+        .route("/search", get(rooms::search_rooms))
+        .route("/:id/calendar", get(rooms::get_room_calendar))
+        .route("/:id/bookings", get(rooms::get_room_bookings))
+        .route("/:id/overview", get(rooms::get_room_overview))
+        .route("/:id/status-history", get(rooms::get_room_status_history))
+        .layer(middleware::require_roles(&state, middleware::STAFF_ROLES));
+
+    let room_routes = Router::new()
+        .merge(public_room_routes)
+        .merge(protected_room_routes)
+        .merge(room_calendar_routes);
+
+    // Availability calendar (any authenticated user, counts only)
+    let availability_routes = Router::new()
+        .route("/calendar", get(availability::get_calendar))
         .layer(axum_middleware::from_fn_with_state(
             state.clone(),
             middleware::require_auth,
         ));
-    
-    let room_routes = Router::new()
-        .merge(public_room_routes)
-        .merge(protected_room_routes);
+
+    // Front desk tools
+    let staff_routes = Router::new()
+        .route("/quick-availability", get(availability::get_quick_availability))
+        .route("/in-house", get(guests::list_in_house_guests))
+        .layer(middleware::require_roles(&state, middleware::STAFF_ROLES));
+
+    // Public booking lookup (no auth, rate limited in the handler)
+    let public_booking_routes = Router::new()
+        .route("/lookup", post(public_bookings::lookup_booking))
+        .route("/:reference/status", get(public_bookings::booking_status));
+
+    // Availability widget for the marketing site (no auth, rate limited per IP)
+    let public_availability_routes =
+        Router::new().route("/", get(public_bookings::public_availability));
 
     // Payment routes for bookings (requires staff auth)
     let booking_payment_routes = Router::new()
@@ -102,44 +192,64 @@ pub fn create_router(state: AppState) -> Router {
             get(payments::list_payments).post(payments::create_payment),
         )
         .route("/:id/payments/summary", get(payments::get_payment_summary))
-        .layer(axum_middleware::from_fn_with_state(
-            state.clone(),
-            middleware::require_staff,
-        ))
-        .layer(axum_middleware::from_fn_with_state(
-            state.clone(),
-            middleware::require_auth,
-        ));
+        .layer(middleware::require_admin(&state));
 
-    let booking_routes = Router::new()
+    // Manual stale-status sync (admin only; the scheduler runs it anyway)
+    let booking_sync_routes = Router::new()
+        .route("/sync-statuses", post(bookings::sync_booking_statuses))
+        .layer(middleware::require_admin(&state));
+
+    // New bookings, status changes and their history record the acting staff
+    // member; the detailed reference lookup and the search expose guest data,
+    // so they are front-desk only too
+    let booking_action_routes = Router::new()
+        .route("/", post(bookings::create_booking))
+        .route("/search", get(bookings::search_bookings))
+        .route("/group", post(bookings::create_group_booking))
+        .route("/walk-in", post(bookings::create_walk_in))
+        .route("/:id/check-in", post(bookings::check_in))
+        .route("/:id/check-out", post(bookings::check_out))
+        .route("/:id/cancel", post(bookings::cancel))
+        .route("/:id/move-room", post(bookings::move_room))
+        .route("/:id/history", get(bookings::get_booking_history))
         .route(
-            "/",
-            get(bookings::list_bookings).post(bookings::create_booking),
+            "/:id/notes",
+            get(bookings::list_booking_notes).post(bookings::add_booking_note),
         )
+        .route("/reference/:reference", get(bookings::get_booking_by_reference))
+        .layer(middleware::require_roles(&state, middleware::STAFF_ROLES));
+
+    // The booking list and booking details are front-desk only
+    let booking_desk_routes = Router::new()
+        .route("/", get(bookings::list_bookings))
         .route(
             "/:id",
             get(bookings::get_booking).patch(bookings::update_booking),
         )
-        .route("/:id/check-in", post(bookings::check_in))
-        .route("/:id/check-out", post(bookings::check_out))
-        .route("/:id/cancel", post(bookings::cancel))
+        .layer(middleware::require_roles(&state, middleware::STAFF_ROLES));
+
+    let booking_routes = Router::new()
+        .merge(booking_desk_routes)
+        .merge(booking_action_routes)
         .route(
-            "/reference/:reference",
-            get(bookings::get_booking_by_reference),
+            "/:id/timeline",
+            get(bookings::get_booking_timeline).layer(axum_middleware::from_fn_with_state(
+                state.clone(),
+                middleware::require_auth,
+            )),
         )
+        .route(
+            "/:id/modifications",
+            get(bookings::get_booking_modifications)
+                .layer(middleware::require_roles(&state, middleware::STAFF_ROLES)),
+        )
+        .merge(booking_sync_routes)
         .merge(booking_payment_routes);
 
     // Payment routes (requires staff auth)
     let payment_routes = Router::new()
         .route("/:id", get(payments::get_payment).patch(payments::update_payment).delete(payments::delete_payment))
-        .layer(axum_middleware::from_fn_with_state(
-            state.clone(),
-            middleware::require_staff,
-        ))
-        .layer(axum_middleware::from_fn_with_state(
-            state.clone(),
-            middleware::require_auth,
-        ));
+        .layer(middleware::require_admin(&state));
 
     // Guest booking routes (requires guest auth)
     let guest_booking_routes = Router::new()
@@ -148,24 +258,23 @@ pub fn create_router(state: AppState) -> Router {
             get(guest_bookings::list_bookings).post(guest_bookings::create_booking),
         )
         .route("/:id", get(guest_bookings::get_booking))
+        .route("/:id/ics", get(guest_bookings::get_booking_ics))
         .route("/:id/cancel", post(guest_bookings::cancel_booking))
-        .layer(axum_middleware::from_fn_with_state(
-            state.clone(),
-            middleware::require_guest,
-        ));
+        .layer(middleware::require_guest(&state));
+
+    // Guest portal routes (requires guest auth)
+    let guest_portal_routes = Router::new()
+        .route("/current-stay", get(guest_bookings::get_current_stay))
+        .route("/holds", post(guest_bookings::create_hold))
+        .route("/chat/export", get(chat::export_guest_chat))
+        .route("/chat/history", delete(chat::delete_guest_chat_history))
+        .layer(middleware::require_guest(&state));
 
     // Cleaner routes (requires cleaner auth)
     let cleaner_routes = Router::new()
         .route("/rooms", get(rooms::list_cleaner_rooms))
         .route("/rooms/:id/status", patch(rooms::update_cleaner_room_status))
-        .layer(axum_middleware::from_fn_with_state(
-            state.clone(),
-            middleware::require_cleaner,
-        ))
-        .layer(axum_middleware::from_fn_with_state(
-            state.clone(),
-            middleware::require_auth,
-        ));
+        .layer(middleware::require_cleaner(&state));
 
     // Admin employee management routes (requires admin auth)
     let admin_employee_routes = Router::new()
@@ -173,15 +282,13 @@ pub fn create_router(state: AppState) -> Router {
         .route("/employees/:id", get(employees::get_employee).patch(employees::update_employee).delete(employees::delete_employee))
         .route("/employees/:id/reactivate", post(employees::reactivate_employee))
         .route("/employees/:id/reset-password", post(employees::reset_password))
+        .route("/employees/:id/revoke-sessions", post(employees::revoke_sessions))
+        .route("/employees/:id/login-history", get(employees::login_history))
+        .route("/transfer-admin", post(employees::transfer_admin))
+        .route("/api-keys", get(api_keys::list_api_keys).post(api_keys::create_api_key))
+        .route("/api-keys/:id/revoke", post(api_keys::revoke_api_key))
         .route("/ai", get(settings::get_ai_settings).post(settings::update_ai_settings))
-        .layer(axum_middleware::from_fn_with_state(
-            state.clone(),
-            middleware::require_admin,
-        ))
-        .layer(axum_middleware::from_fn_with_state(
-            state.clone(),
-            middleware::require_auth,
-        ));
+        .layer(middleware::require_admin(&state));
 
     // Admin financial reporting routes (requires admin auth)
     let admin_financial_routes = Router::new()
@@ -191,14 +298,89 @@ pub fn create_router(state: AppState) -> Router {
         .route("/financial/revenue/time-series", get(financial::get_revenue_time_series))
         .route("/financial/rooms/:roomId/revenue/time-series", get(financial::get_room_revenue_time_series))
         .route("/financial/rooms/:roomId/bookings", get(financial::get_room_booking_history))
-        .layer(axum_middleware::from_fn_with_state(
-            state.clone(),
-            middleware::require_admin,
-        ))
-        .layer(axum_middleware::from_fn_with_state(
-            state.clone(),
-            middleware::require_auth,
-        ));
+        .route("/bookings/export", get(bookings::export_bookings))
+        .route("/bookings/conflicts", get(bookings::list_booking_conflicts))
+        .route("/stats/bookings", get(bookings::get_booking_stats))
+        .layer(middleware::require_admin(&state));
+
+    // Cash reconciliation routes (front desk, so receptionists can count the
+    // drawer; only admins may list past counts)
+    let staff_reconciliation_routes = Router::new()
+        .route("/financial/payments/summary", get(reconciliations::get_payment_method_summary))
+        .route(
+            "/financial/reconciliations",
+            get(reconciliations::list_reconciliations).post(reconciliations::create_reconciliation),
+        )
+        .layer(middleware::require_roles(&state, middleware::STAFF_ROLES));
+
+    // Overstay board (front desk; reception needs it as much as admins)
+    let staff_overstay_routes = Router::new()
+        .route("/rooms/overstays", get(rooms::list_overstay_rooms))
+        .layer(middleware::require_roles(&state, middleware::STAFF_ROLES));
+
+    // Clearing a room for maintenance, its tickets, blocked dates and price history (requires admin auth)
+    let admin_room_routes = Router::new()
+        .route("/rooms/bulk", post(rooms::create_rooms_bulk))
+        .route("/rooms/:id/cancel-upcoming", post(rooms::cancel_upcoming_bookings))
+        .route("/rooms/:id/price-history", get(rooms::get_room_price_history))
+        .route(
+            "/rooms/:id/photos",
+            post(room_photos::upload_room_photo).layer(DefaultBodyLimit::max(state.body_limits.upload_bytes)),
+        )
+        .route("/rooms/:id/photos/:photo_id", delete(room_photos::delete_room_photo))
+        .route("/rooms/:id/tickets", get(tickets::list_room_tickets).post(tickets::create_ticket))
+        .route(
+            "/rooms/:id/tickets/:ticket_id",
+            get(tickets::get_ticket)
+                .patch(tickets::update_ticket)
+                .delete(tickets::delete_ticket),
+        )
+        .route("/tickets", get(tickets::list_tickets))
+        .route(
+            "/rooms/:id/blocks",
+            get(room_blocks::list_room_blocks).post(room_blocks::create_room_block),
+        )
+        .route(
+            "/rooms/:id/blocks/:block_id",
+            get(room_blocks::get_room_block)
+                .put(room_blocks::update_room_block)
+                .delete(room_blocks::delete_room_block),
+        )
+        .layer(middleware::require_admin(&state));
+
+    // Admin notification routes (requires admin auth)
+    let admin_notification_routes = Router::new()
+        .route("/notifications", get(reconciliations::list_notifications))
+        .route("/notifications/:id/read", post(reconciliations::mark_notification_read))
+        .layer(middleware::require_admin(&state));
+
+    // Seasonal pricing rules (requires admin auth)
+    let admin_pricing_routes = Router::new()
+        .route(
+            "/pricing-rules",
+            get(pricing_rules::list_pricing_rules).post(pricing_rules::create_pricing_rule),
+        )
+        .route(
+            "/pricing-rules/:id",
+            get(pricing_rules::get_pricing_rule)
+                .put(pricing_rules::update_pricing_rule)
+                .delete(pricing_rules::delete_pricing_rule),
+        )
+        .layer(middleware::require_admin(&state));
+
+    // Configurable room types (requires admin auth)
+    let admin_room_type_routes = Router::new()
+        .route(
+            "/room-types",
+            get(room_types::list_room_types).post(room_types::create_room_type),
+        )
+        .route(
+            "/room-types/:id",
+            get(room_types::get_room_type)
+                .patch(room_types::update_room_type)
+                .delete(room_types::delete_room_type),
+        )
+        .layer(middleware::require_admin(&state));
 
     // Admin guest CRM routes (requires admin auth)
     let admin_guest_routes = Router::new()
@@ -206,25 +388,47 @@ pub fn create_router(state: AppState) -> Router {
         .route("/guests/search", get(guests::search_guests))
         .route("/guests/:guestId", get(guests::get_guest_profile).patch(guests::update_guest))
         .route("/guests/:guestId/notes", get(guests::get_guest_notes).post(guests::add_guest_note))
-        .layer(axum_middleware::from_fn_with_state(
-            state.clone(),
-            middleware::require_admin,
-        ))
-        .layer(axum_middleware::from_fn_with_state(
-            state.clone(),
-            middleware::require_auth,
-        ));
+        .layer(middleware::require_admin(&state));
+
+    // Admin no-show charge review routes (requires admin auth)
+    let admin_no_show_routes = Router::new()
+        .route("/no-show-charges", get(no_show_charges::list_no_show_charges))
+        .route("/no-show-charges/:id/confirm", post(no_show_charges::confirm_no_show_charge))
+        .route("/no-show-charges/:id/waive", post(no_show_charges::waive_no_show_charge))
+        .layer(middleware::require_admin(&state));
+
+    // Admin scheduled report, outgoing email and background job routes (requires admin auth)
+    let admin_report_routes = Router::new()
+        .route(
+            "/report-subscriptions",
+            get(report_subscriptions::list_report_subscriptions)
+                .post(report_subscriptions::create_report_subscription),
+        )
+        .route(
+            "/report-subscriptions/:id",
+            patch(report_subscriptions::update_report_subscription)
+                .delete(report_subscriptions::delete_report_subscription),
+        )
+        .route(
+            "/report-subscriptions/:id/deliveries",
+            get(report_subscriptions::list_report_deliveries),
+        )
+        .route("/booking-emails", get(booking_emails::list_booking_emails))
+        .route("/jobs", get(jobs::list_jobs))
+        .layer(middleware::require_admin(&state));
+
+    // Admin housekeeping of auxiliary tables (requires admin auth)
+    let admin_housekeeping_routes = Router::new()
+        .route("/housekeeping-data/holds", get(housekeeping::get_holds_overview))
+        .route("/housekeeping-data/holds/purge", post(housekeeping::purge_holds))
+        .layer(middleware::require_admin(&state));
 
     let admin_settings_routes = Router::new()
+        .route("/settings", get(settings::list_settings).patch(settings::update_settings))
+        .route("/settings/schema", get(settings::get_settings_schema))
         .route("/settings/ai", get(settings::get_ai_settings).post(settings::update_ai_settings))
-        .layer(axum_middleware::from_fn_with_state(
-            state.clone(),
-            middleware::require_admin,
-        ))
-        .layer(axum_middleware::from_fn_with_state(
-            state.clone(),
-            middleware::require_auth,
-        ));
+        .route("/read-only", get(maintenance::get_read_only_mode).post(maintenance::set_read_only_mode))
+        .layer(middleware::require_admin(&state));
 
     // Health check endpoint
     let health_route = Router::new().route("/health", get(health_check));
@@ -233,7 +437,14 @@ pub fn create_router(state: AppState) -> Router {
     let chat_routes = Router::new()
         .route("/contacts", get(chat::get_contacts))
         .route("/history", get(chat::get_chat_history))
-        .route("/upload", post(chat::upload_image))
+        .route("/v2/history", get(chat::get_chat_history_page))
+        .route("/messages/unsynced", get(chat::get_unsynced_messages))
+        .route("/messages/:id", patch(chat::update_message).delete(chat::delete_message))
+        // Uploads may exceed the default body limit, up to the storage limit
+        .route(
+            "/upload",
+            post(chat::upload_image).layer(DefaultBodyLimit::max(state.body_limits.upload_bytes)),
+        )
         .layer(axum_middleware::from_fn_with_state(
             state.clone(),
             middleware::require_auth,
@@ -248,14 +459,7 @@ pub fn create_router(state: AppState) -> Router {
     let inventory_routes = Router::new()
         .route("/", get(inventory::list_inventory))
         .route("/:id", patch(inventory::update_inventory_item))
-        .layer(axum_middleware::from_fn_with_state(
-            state.clone(),
-            middleware::require_admin_or_cleaner, // Admin and Cleaner
-        ))
-        .layer(axum_middleware::from_fn_with_state(
-            state.clone(),
-            middleware::require_auth,
-        ));
+        .layer(middleware::require_admin_or_cleaner(&state));
 
     // Admin-only Inventory Routes (Create, Delete)
     let admin_inventory_routes = Router::new()
@@ -263,36 +467,58 @@ pub fn create_router(state: AppState) -> Router {
         .route("/:id", delete(inventory::delete_inventory_item))
         // New Financial Endpoint for Inventory
         .route("/financial/inventory-value", get(inventory::get_inventory_value)) 
-        .layer(axum_middleware::from_fn_with_state(
-            state.clone(),
-            middleware::require_admin,
-        ))
-        .layer(axum_middleware::from_fn_with_state(
-            state.clone(),
-            middleware::require_auth,
-        ));
+        .layer(middleware::require_admin(&state));
 
     Router::new()
         .nest("/auth", auth_routes)
         .nest("/rooms", room_routes)
+        .nest("/availability", availability_routes)
+        .nest("/staff", staff_routes)
         .nest("/bookings", booking_routes)
+        .nest("/public/bookings", public_booking_routes)
+        .nest("/public/availability", public_availability_routes)
         .nest("/payments", payment_routes)
         .nest("/guest/bookings", guest_booking_routes)
+        .nest("/guest", guest_portal_routes)
         .nest("/cleaner", cleaner_routes)
         .nest(
             "/admin",
             admin_employee_routes
                 .merge(admin_financial_routes)
+                .merge(staff_reconciliation_routes)
+                .merge(staff_overstay_routes)
+                .merge(admin_room_routes)
+                .merge(admin_notification_routes)
+                .merge(admin_pricing_routes)
+                .merge(admin_room_type_routes)
                 .merge(admin_guest_routes)
+                .merge(admin_no_show_routes)
+                .merge(admin_report_routes)
+                .merge(admin_housekeeping_routes)
                 .merge(admin_settings_routes),
         )
         .nest("/inventory", inventory_routes.merge(admin_inventory_routes))
         .nest("/chat", chat_routes.merge(chat_ws_route))
         .merge(health_route)
+        // Refuse writes during maintenance windows (see ReadOnlyMode::allows_request)
+        .layer(axum_middleware::from_fn_with_state(
+            state.clone(),
+            middleware::reject_writes_when_read_only,
+        ))
+        // Small default so oversized bodies are refused before they are parsed
+        .layer(DefaultBodyLimit::max(state.body_limits.default_bytes))
+        .layer(axum_middleware::from_fn(middleware::structured_payload_too_large))
+        // Outermost, so auth layers in nested routers see paths like `/auth/me`
+        .layer(axum_middleware::from_fn(middleware::record_api_path))
         .with_state(state)
 }
 
 /// Health check handler
-async fn health_check() -> axum::Json<serde_json::Value> {
-    axum::Json(serde_json::json!({ "status": "ok" }))
+async fn health_check(
+    axum::extract::State(state): axum::extract::State<AppState>,
+) -> axum::Json<serde_json::Value> {
+    axum::Json(serde_json::json!({
+        "status": "ok",
+        "read_only": state.read_only.is_enabled(),
+    }))
 }
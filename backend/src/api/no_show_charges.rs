@@ -0,0 +1,71 @@
+use axum::{
+    extract::{Extension, Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::api::{middleware::AuthUser, AppState};
+use crate::errors::AppError;
+use crate::models::NoShowChargeStatus;
+use crate::services::NoShowService;
+
+/// Query parameters for listing no-show charges
+#[derive(Debug, Deserialize)]
+pub struct ListNoShowChargesQuery {
+    /// Defaults to pending (the review queue)
+    pub status: Option<NoShowChargeStatus>,
+}
+
+/// Confirm no-show charge request DTO
+#[derive(Debug, Deserialize)]
+pub struct ConfirmNoShowChargeDto {
+    pub reason: Option<String>,
+    /// Payment method for one-night charges (defaults to card)
+    pub payment_method: Option<String>,
+}
+
+/// Waive no-show charge request DTO
+#[derive(Debug, Deserialize)]
+pub struct WaiveNoShowChargeDto {
+    pub reason: String,
+}
+
+/// List no-show charges awaiting review
+/// GET /admin/no-show-charges?status=pending
+pub async fn list_no_show_charges(
+    State(state): State<AppState>,
+    Query(query): Query<ListNoShowChargesQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let service = NoShowService::new(state.pool);
+    let charges = service.list_charges(Some(query.status.unwrap_or(NoShowChargeStatus::Pending)))?;
+    Ok((StatusCode::OK, Json(charges)))
+}
+
+/// Confirm a no-show charge
+/// POST /admin/no-show-charges/:id/confirm
+pub async fn confirm_no_show_charge(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(payload): Json<ConfirmNoShowChargeDto>,
+) -> Result<impl IntoResponse, AppError> {
+    let service = NoShowService::new(state.pool);
+    let charge = service.confirm_charge(id, auth_user.user_id, payload.reason, payload.payment_method)?;
+    Ok((StatusCode::OK, Json(charge)))
+}
+
+/// Waive a no-show charge
+/// POST /admin/no-show-charges/:id/waive
+pub async fn waive_no_show_charge(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(payload): Json<WaiveNoShowChargeDto>,
+) -> Result<impl IntoResponse, AppError> {
+    let service = NoShowService::new(state.pool);
+    let charge = service.waive_charge(id, auth_user.user_id, &payload.reason)?;
+    Ok((StatusCode::OK, Json(charge)))
+}
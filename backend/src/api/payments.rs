@@ -115,9 +115,9 @@ pub async fn update_payment(
 pub async fn delete_payment(
     State(state): State<AppState>,
     Path(payment_id): Path<Uuid>,
-    Extension(_auth_user): Extension<AuthUser>,
+    Extension(auth_user): Extension<AuthUser>,
 ) -> Result<impl IntoResponse, AppError> {
     let payment_service = PaymentService::new(state.pool);
-    payment_service.delete_payment(payment_id)?;
+    payment_service.delete_payment(payment_id, auth_user.user_id)?;
     Ok(StatusCode::NO_CONTENT)
 }
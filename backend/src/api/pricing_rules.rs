@@ -0,0 +1,60 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use uuid::Uuid;
+
+use crate::api::AppState;
+use crate::errors::AppError;
+use crate::services::pricing_service::PricingRuleRequest;
+use crate::services::PricingService;
+
+/// Every pricing rule, soonest first (admin only)
+/// GET /admin/pricing-rules
+pub async fn list_pricing_rules(State(state): State<AppState>) -> Result<impl IntoResponse, AppError> {
+    let rules = PricingService::new(state.pool).list_rules()?;
+    Ok((StatusCode::OK, Json(rules)))
+}
+
+/// Add a pricing rule (admin only)
+/// POST /admin/pricing-rules
+pub async fn create_pricing_rule(
+    State(state): State<AppState>,
+    Json(payload): Json<PricingRuleRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let rule = PricingService::new(state.pool).create_rule(&payload)?;
+    Ok((StatusCode::CREATED, Json(rule)))
+}
+
+/// One pricing rule (admin only)
+/// GET /admin/pricing-rules/:id
+pub async fn get_pricing_rule(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    let rule = PricingService::new(state.pool).get_rule(id)?;
+    Ok((StatusCode::OK, Json(rule)))
+}
+
+/// Replace a pricing rule (admin only). Existing bookings keep their price.
+/// PUT /admin/pricing-rules/:id
+pub async fn update_pricing_rule(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<PricingRuleRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let rule = PricingService::new(state.pool).update_rule(id, &payload)?;
+    Ok((StatusCode::OK, Json(rule)))
+}
+
+/// Delete a pricing rule (admin only)
+/// DELETE /admin/pricing-rules/:id
+pub async fn delete_pricing_rule(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    PricingService::new(state.pool).delete_rule(id)?;
+    Ok(StatusCode::NO_CONTENT)
+}
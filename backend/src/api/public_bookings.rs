@@ -0,0 +1,203 @@
+use std::net::{IpAddr, SocketAddr};
+use std::time::{Duration, Instant};
+
+use axum::{
+    extract::{ConnectInfo, Path, Query, State},
+    http::{
+        header::{HeaderName, RETRY_AFTER},
+        HeaderMap, StatusCode,
+    },
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::NaiveDate;
+use serde::Deserialize;
+
+use crate::api::AppState;
+use crate::errors::AppError;
+use crate::models::RoomType;
+use crate::services::rate_limit_service::{FailureBackoff, FixedWindowLimiter};
+use crate::services::BookingService;
+
+/// Lookups allowed per client IP per minute
+pub const LOOKUPS_PER_IP_PER_MINUTE: u32 = 10;
+/// Availability searches allowed per client IP per minute
+pub const AVAILABILITY_SEARCHES_PER_IP_PER_MINUTE: u32 = 30;
+/// Failed lookups for one reference before it starts backing off
+pub const FREE_FAILED_LOOKUPS: u32 = 3;
+
+/// Set by reverse proxies to the addresses a request came through
+const X_FORWARDED_FOR: HeaderName = HeaderName::from_static("x-forwarded-for");
+
+/// Rate limits for the public booking lookup and availability search
+#[derive(Debug)]
+pub struct PublicLookupLimits {
+    pub per_ip: FixedWindowLimiter,
+    pub per_reference: FailureBackoff,
+    /// Separate budget so the marketing widget does not eat into lookups
+    pub availability_per_ip: FixedWindowLimiter,
+}
+
+impl Default for PublicLookupLimits {
+    fn default() -> Self {
+        Self {
+            per_ip: FixedWindowLimiter::new(LOOKUPS_PER_IP_PER_MINUTE, Duration::from_secs(60)),
+            per_reference: FailureBackoff::new(
+                FREE_FAILED_LOOKUPS,
+                Duration::from_secs(30),
+                Duration::from_secs(3600),
+            ),
+            availability_per_ip: FixedWindowLimiter::new(
+                AVAILABILITY_SEARCHES_PER_IP_PER_MINUTE,
+                Duration::from_secs(60),
+            ),
+        }
+    }
+}
+
+/// Public availability search query
+#[derive(Debug, Deserialize)]
+pub struct PublicAvailabilityQuery {
+    pub check_in_date: NaiveDate,
+    pub check_out_date: NaiveDate,
+    pub room_type: Option<RoomType>,
+}
+
+/// Public booking lookup request DTO
+#[derive(Debug, Deserialize)]
+pub struct PublicBookingLookupDto {
+    pub reference: String,
+    pub last_name: String,
+}
+
+/// 429 response with Retry-After
+fn too_many_requests(retry_after: Duration) -> Response {
+    (
+        StatusCode::TOO_MANY_REQUESTS,
+        [(RETRY_AFTER, retry_after.as_secs().max(1).to_string())],
+        Json(serde_json::json!({
+            "code": "RATE_LIMITED",
+            "message": "Too many lookup attempts. Please try again later."
+        })),
+    )
+        .into_response()
+}
+
+/// Client IP used for rate limiting and the login history. Behind a trusted
+/// proxy it is the last X-Forwarded-For entry, the address the proxy itself
+/// saw; entries before it are whatever the client sent.
+pub(crate) fn client_ip(
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    headers: &HeaderMap,
+    trust_proxy_headers: bool,
+) -> String {
+    let forwarded = trust_proxy_headers
+        .then(|| headers.get_all(X_FORWARDED_FOR).iter().next_back())
+        .flatten()
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.rsplit(',').next())
+        .and_then(|entry| entry.trim().parse::<IpAddr>().ok());
+
+    match (forwarded, connect_info) {
+        (Some(ip), _) => ip.to_string(),
+        (None, Some(ConnectInfo(addr))) => addr.ip().to_string(),
+        (None, None) => "unknown".to_string(),
+    }
+}
+
+/// Look up a booking without an account, using the reference and the guest's
+/// surname. Returns a redacted view; an unknown reference and a wrong surname
+/// get the same 404.
+/// POST /public/bookings/lookup
+pub async fn lookup_booking(
+    State(state): State<AppState>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    headers: HeaderMap,
+    Json(payload): Json<PublicBookingLookupDto>,
+) -> Result<Response, AppError> {
+    let client_ip = client_ip(connect_info, &headers, state.trust_proxy_headers);
+    let reference = payload.reference.trim().to_uppercase();
+    let now = Instant::now();
+
+    if reference.is_empty() || payload.last_name.trim().is_empty() {
+        return Err(AppError::ValidationError(
+            "Reference and last name are required".to_string(),
+        ));
+    }
+
+    if let Err(retry_after) = state.public_lookup.per_ip.check(&client_ip, now) {
+        return Ok(too_many_requests(retry_after));
+    }
+    if let Err(retry_after) = state.public_lookup.per_reference.check(&reference, now) {
+        tracing::warn!("Public lookup for {} from {} refused during backoff", reference, client_ip);
+        return Ok(too_many_requests(retry_after));
+    }
+
+    let booking_service = BookingService::new(state.pool);
+    match booking_service.public_lookup(&reference, &payload.last_name)? {
+        Some(view) => {
+            state.public_lookup.per_reference.reset(&reference);
+            Ok((StatusCode::OK, Json(view)).into_response())
+        }
+        None => {
+            let failures = state.public_lookup.per_reference.record_failure(&reference, now);
+            tracing::warn!(
+                "Failed public lookup for {} from {} ({} consecutive)",
+                reference,
+                client_ip,
+                failures
+            );
+            Err(AppError::NotFound(
+                "No booking matches that reference and last name".to_string(),
+            ))
+        }
+    }
+}
+
+/// Check a reservation from a confirmation email without logging in. Only
+/// the reference, room number, dates and status are returned, never the
+/// guest's name. Shares the per-IP limit with the surname lookup.
+/// GET /public/bookings/:reference/status
+pub async fn booking_status(
+    State(state): State<AppState>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    headers: HeaderMap,
+    Path(reference): Path<String>,
+) -> Result<Response, AppError> {
+    let client_ip = client_ip(connect_info, &headers, state.trust_proxy_headers);
+
+    if let Err(retry_after) = state.public_lookup.per_ip.check(&client_ip, Instant::now()) {
+        return Ok(too_many_requests(retry_after));
+    }
+
+    let booking_service = BookingService::new(state.pool);
+    match booking_service.public_status(&reference)? {
+        Some(status) => Ok((StatusCode::OK, Json(status)).into_response()),
+        None => {
+            tracing::warn!("Public status check for unknown reference from {}", client_ip);
+            Err(AppError::NotFound("No booking matches that reference".to_string()))
+        }
+    }
+}
+
+/// Free room counts and nightly prices per room type for a stay, for the
+/// marketing site's availability widget. No login; rate limited per IP.
+/// GET /public/availability?check_in_date=&check_out_date=&room_type=
+pub async fn public_availability(
+    State(state): State<AppState>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    headers: HeaderMap,
+    Query(query): Query<PublicAvailabilityQuery>,
+) -> Result<Response, AppError> {
+    let client_ip = client_ip(connect_info, &headers, state.trust_proxy_headers);
+
+    if let Err(retry_after) = state.public_lookup.availability_per_ip.check(&client_ip, Instant::now()) {
+        return Ok(too_many_requests(retry_after));
+    }
+
+    let booking_service = BookingService::new(state.pool);
+    let room_types =
+        booking_service.public_availability(query.check_in_date, query.check_out_date, query.room_type)?;
+
+    Ok((StatusCode::OK, Json(room_types)).into_response())
+}
@@ -0,0 +1,133 @@
+use axum::{
+    extract::{Extension, Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use bigdecimal::BigDecimal;
+use chrono::NaiveDate;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::api::middleware::{is_admin_role, is_staff_role, AuthUser};
+use crate::api::AppState;
+use crate::errors::AppError;
+use crate::models::Shift;
+use crate::services::{NotificationService, ReconciliationService};
+
+/// Query parameters for the payment method summary
+#[derive(Debug, Deserialize)]
+pub struct PaymentSummaryQuery {
+    /// Hotel-local business date (defaults to today)
+    pub date: Option<NaiveDate>,
+    /// Limit to one shift of the date
+    pub shift: Option<Shift>,
+}
+
+/// Record cash reconciliation request DTO
+#[derive(Debug, Deserialize)]
+pub struct CreateReconciliationDto {
+    pub date: NaiveDate,
+    pub shift: Option<Shift>,
+    pub counted_cash: BigDecimal,
+    pub note: Option<String>,
+}
+
+/// Query parameters for listing reconciliations
+#[derive(Debug, Deserialize)]
+pub struct ListReconciliationsQuery {
+    pub from_date: Option<NaiveDate>,
+    pub to_date: Option<NaiveDate>,
+}
+
+/// Query parameters for listing admin notifications
+#[derive(Debug, Deserialize)]
+pub struct ListNotificationsQuery {
+    #[serde(default)]
+    pub unread_only: bool,
+}
+
+fn require_front_desk(auth_user: &AuthUser) -> Result<(), AppError> {
+    if is_staff_role(auth_user.role) {
+        Ok(())
+    } else {
+        Err(AppError::Forbidden(
+            "Only front desk staff can reconcile cash".to_string(),
+        ))
+    }
+}
+
+/// Payments for a business date or shift, grouped by method with the expected cash
+/// GET /admin/financial/payments/summary?date=2025-04-01&shift=morning
+pub async fn get_payment_method_summary(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(query): Query<PaymentSummaryQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    require_front_desk(&auth_user)?;
+
+    let service = ReconciliationService::new(state.pool);
+    let summary = service.payment_method_summary(query.date, query.shift)?;
+    Ok((StatusCode::OK, Json(summary)))
+}
+
+/// Record a counted cash drawer amount
+/// POST /admin/financial/reconciliations
+pub async fn create_reconciliation(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(payload): Json<CreateReconciliationDto>,
+) -> Result<impl IntoResponse, AppError> {
+    require_front_desk(&auth_user)?;
+
+    let service = ReconciliationService::new(state.pool);
+    let recorded = service.record_reconciliation(
+        payload.date,
+        payload.shift,
+        payload.counted_cash,
+        payload.note.as_deref(),
+        auth_user.user_id,
+    )?;
+    Ok((StatusCode::CREATED, Json(recorded)))
+}
+
+/// List recorded reconciliations (admin only)
+/// GET /admin/financial/reconciliations?from_date=&to_date=
+pub async fn list_reconciliations(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(query): Query<ListReconciliationsQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    if !is_admin_role(auth_user.role) {
+        return Err(AppError::Forbidden(
+            "Only admins can list cash reconciliations".to_string(),
+        ));
+    }
+
+    let service = ReconciliationService::new(state.pool);
+    let reconciliations = service.list_reconciliations(query.from_date, query.to_date)?;
+    Ok((StatusCode::OK, Json(reconciliations)))
+}
+
+/// List admin notifications, newest first
+/// GET /admin/notifications?unread_only=true
+pub async fn list_notifications(
+    State(state): State<AppState>,
+    Query(query): Query<ListNotificationsQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let service = NotificationService::new(state.pool);
+    let notifications = service.list(query.unread_only)?;
+    Ok((StatusCode::OK, Json(notifications)))
+}
+
+/// Mark an admin notification read
+/// POST /admin/notifications/:id/read
+pub async fn mark_notification_read(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<impl IntoResponse, AppError> {
+    let service = NotificationService::new(state.pool);
+    let notification = service.mark_read(id, auth_user.user_id)?;
+    Ok((StatusCode::OK, Json(notification)))
+}
@@ -0,0 +1,87 @@
+use axum::{
+    extract::{Extension, Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::api::{middleware::AuthUser, AppState};
+use crate::errors::AppError;
+use crate::models::{ReportType, UpdateReportSubscription};
+use crate::services::ReportService;
+
+/// Create report subscription request DTO
+#[derive(Debug, Deserialize)]
+pub struct CreateReportSubscriptionDto {
+    pub recipient_email: String,
+    pub report_type: ReportType,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// List report subscriptions
+/// GET /admin/report-subscriptions
+pub async fn list_report_subscriptions(
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let service = ReportService::new(state.pool);
+    let subscriptions = service.list_subscriptions()?;
+    Ok((StatusCode::OK, Json(subscriptions)))
+}
+
+/// Create a report subscription
+/// POST /admin/report-subscriptions
+pub async fn create_report_subscription(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(payload): Json<CreateReportSubscriptionDto>,
+) -> Result<impl IntoResponse, AppError> {
+    let service = ReportService::new(state.pool);
+    let subscription = service.create_subscription(
+        &payload.recipient_email,
+        payload.report_type,
+        payload.enabled,
+        auth_user.user_id,
+    )?;
+    Ok((StatusCode::CREATED, Json(subscription)))
+}
+
+/// Update a report subscription
+/// PATCH /admin/report-subscriptions/:id
+pub async fn update_report_subscription(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<UpdateReportSubscription>,
+) -> Result<impl IntoResponse, AppError> {
+    let service = ReportService::new(state.pool);
+    let subscription = service.update_subscription(id, payload)?;
+    Ok((StatusCode::OK, Json(subscription)))
+}
+
+/// Delete a report subscription
+/// DELETE /admin/report-subscriptions/:id
+pub async fn delete_report_subscription(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    let service = ReportService::new(state.pool);
+    service.delete_subscription(id)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Delivery log of a report subscription
+/// GET /admin/report-subscriptions/:id/deliveries
+pub async fn list_report_deliveries(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    let service = ReportService::new(state.pool);
+    let deliveries = service.list_deliveries(id)?;
+    Ok((StatusCode::OK, Json(deliveries)))
+}
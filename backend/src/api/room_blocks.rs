@@ -0,0 +1,67 @@
+use axum::{
+    extract::{Extension, Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use uuid::Uuid;
+
+use crate::api::middleware::AuthUser;
+use crate::api::AppState;
+use crate::errors::AppError;
+use crate::services::room_block_service::RoomBlockRequest;
+use crate::services::RoomBlockService;
+
+/// Blocked dates of a room, earliest first (admin only)
+/// GET /admin/rooms/:id/blocks
+pub async fn list_room_blocks(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    let blocks = RoomBlockService::new(state.pool).list_room_blocks(id)?;
+    Ok((StatusCode::OK, Json(blocks)))
+}
+
+/// Keep a room off sale for some nights (admin only). Refused with 409 if
+/// a booking covers any of them.
+/// POST /admin/rooms/:id/blocks
+pub async fn create_room_block(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(payload): Json<RoomBlockRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let block = RoomBlockService::new(state.pool).create_block(id, &payload, Some(auth_user.user_id))?;
+    Ok((StatusCode::CREATED, Json(block)))
+}
+
+/// One block of a room (admin only)
+/// GET /admin/rooms/:id/blocks/:block_id
+pub async fn get_room_block(
+    State(state): State<AppState>,
+    Path((id, block_id)): Path<(Uuid, Uuid)>,
+) -> Result<impl IntoResponse, AppError> {
+    let block = RoomBlockService::new(state.pool).get_block(id, block_id)?;
+    Ok((StatusCode::OK, Json(block)))
+}
+
+/// Replace a block's dates and reason (admin only)
+/// PUT /admin/rooms/:id/blocks/:block_id
+pub async fn update_room_block(
+    State(state): State<AppState>,
+    Path((id, block_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<RoomBlockRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let block = RoomBlockService::new(state.pool).update_block(id, block_id, &payload)?;
+    Ok((StatusCode::OK, Json(block)))
+}
+
+/// Lift a block (admin only)
+/// DELETE /admin/rooms/:id/blocks/:block_id
+pub async fn delete_room_block(
+    State(state): State<AppState>,
+    Path((id, block_id)): Path<(Uuid, Uuid)>,
+) -> Result<impl IntoResponse, AppError> {
+    RoomBlockService::new(state.pool).delete_block(id, block_id)?;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,138 @@
+use axum::{
+    extract::{Extension, Multipart, Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::api::chat::multipart_error;
+use crate::api::middleware::AuthUser;
+use crate::api::AppState;
+use crate::errors::AppError;
+use crate::models::RoomPhoto;
+use crate::services::room_photo_service::ROOM_PHOTOS_BUCKET;
+use crate::services::{storage_service, RoomPhotoService};
+
+/// Room photo with the URL browsers load it from
+#[derive(Debug, Serialize)]
+pub struct RoomPhotoResponse {
+    pub id: Uuid,
+    pub room_id: Uuid,
+    pub url: String,
+    pub caption: Option<String>,
+    pub sort_order: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<RoomPhoto> for RoomPhotoResponse {
+    fn from(photo: RoomPhoto) -> Self {
+        Self {
+            url: storage_service::public_url(ROOM_PHOTOS_BUCKET, &photo.object_key),
+            id: photo.id,
+            room_id: photo.room_id,
+            caption: photo.caption,
+            sort_order: photo.sort_order,
+            created_at: photo.created_at,
+        }
+    }
+}
+
+/// Photos of a room in display order (public)
+/// GET /rooms/:id/photos
+pub async fn list_room_photos(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    let photos = RoomPhotoService::new(state.pool).list_photos(id)?;
+    let photos: Vec<RoomPhotoResponse> = photos.into_iter().map(Into::into).collect();
+    Ok((StatusCode::OK, Json(photos)))
+}
+
+/// Upload a room photo (admin only). Multipart with a `file` field and an
+/// optional `caption` field.
+/// POST /admin/rooms/:id/photos
+pub async fn upload_room_photo(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Extension(auth_user): Extension<AuthUser>,
+    mut multipart: Multipart,
+) -> Result<impl IntoResponse, AppError> {
+    let mut data = None;
+    let mut caption = None;
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| multipart_error(e, "Failed to read multipart field"))?
+    {
+        match field.name() {
+            Some("file") => {
+                let bytes = field
+                    .bytes()
+                    .await
+                    .map_err(|e| multipart_error(e, "Failed to read file data"))?;
+                data = Some(bytes);
+            }
+            Some("caption") => {
+                let text = field
+                    .text()
+                    .await
+                    .map_err(|e| multipart_error(e, "Failed to read caption"))?;
+                caption = Some(text);
+            }
+            _ => {}
+        }
+    }
+    let data = data.ok_or_else(|| AppError::BadRequest("No file provided".to_string()))?;
+
+    let extension = RoomPhotoService::validate_photo(&data)?;
+    let caption = RoomPhotoService::validate_caption(caption.as_deref())?;
+    let service = RoomPhotoService::new(state.pool);
+    service.ensure_room_has_space(id)?;
+
+    let object_key = format!("{}/{}.{}", id, Uuid::new_v4(), extension);
+    storage_service::upload_image(&state.s3_client, ROOM_PHOTOS_BUCKET, &object_key, data.to_vec())
+        .await
+        .map_err(|e| AppError::InternalError(format!("Failed to upload to MinIO: {}", e)))?;
+
+    let photo = match service.add_photo(id, &object_key, caption) {
+        Ok(photo) => photo,
+        Err(e) => {
+            // Another upload filled the room meanwhile; do not leave the object behind
+            if let Err(cleanup) =
+                storage_service::delete_object(&state.s3_client, ROOM_PHOTOS_BUCKET, &object_key).await
+            {
+                tracing::warn!("Could not remove unrecorded room photo: {}", cleanup);
+            }
+            return Err(e);
+        }
+    };
+
+    tracing::info!(
+        "Room photo {} added to room {} by user {}",
+        photo.id,
+        id,
+        auth_user.user_id
+    );
+    Ok((StatusCode::CREATED, Json(RoomPhotoResponse::from(photo))))
+}
+
+/// Delete a room photo and its stored object (admin only)
+/// DELETE /admin/rooms/:id/photos/:photo_id
+pub async fn delete_room_photo(
+    State(state): State<AppState>,
+    Path((id, photo_id)): Path<(Uuid, Uuid)>,
+) -> Result<impl IntoResponse, AppError> {
+    let service = RoomPhotoService::new(state.pool);
+    let photo = service.get_photo(id, photo_id)?;
+
+    // The object goes first: a failed removal leaves the photo listed and retryable
+    storage_service::delete_object(&state.s3_client, ROOM_PHOTOS_BUCKET, &photo.object_key)
+        .await
+        .map_err(|e| AppError::InternalError(format!("Failed to delete from MinIO: {}", e)))?;
+    service.delete_photo(id, photo_id)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
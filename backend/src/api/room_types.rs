@@ -0,0 +1,78 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::api::AppState;
+use crate::errors::AppError;
+use crate::services::room_type_service::{CreateRoomTypeRequest, UpdateRoomTypeRequest};
+use crate::services::RoomTypeService;
+
+/// Query parameters for listing room types
+#[derive(Debug, Deserialize)]
+pub struct ListRoomTypesQuery {
+    #[serde(default)]
+    pub include_inactive: bool,
+}
+
+/// Room types on offer, cheapest first, for room forms and search filters
+/// GET /rooms/types
+pub async fn list_active_room_types(State(state): State<AppState>) -> Result<impl IntoResponse, AppError> {
+    let room_types = RoomTypeService::new(state.pool).list_room_types(false)?;
+    Ok((StatusCode::OK, Json(room_types)))
+}
+
+/// Every room type, inactive ones on request (admin only)
+/// GET /admin/room-types?include_inactive=true
+pub async fn list_room_types(
+    State(state): State<AppState>,
+    Query(query): Query<ListRoomTypesQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let room_types = RoomTypeService::new(state.pool).list_room_types(query.include_inactive)?;
+    Ok((StatusCode::OK, Json(room_types)))
+}
+
+/// Add a room type (admin only)
+/// POST /admin/room-types
+pub async fn create_room_type(
+    State(state): State<AppState>,
+    Json(payload): Json<CreateRoomTypeRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let room_type = RoomTypeService::new(state.pool).create_room_type(&payload)?;
+    Ok((StatusCode::CREATED, Json(room_type)))
+}
+
+/// One room type (admin only)
+/// GET /admin/room-types/:id
+pub async fn get_room_type(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    let room_type = RoomTypeService::new(state.pool).get_room_type(id)?;
+    Ok((StatusCode::OK, Json(room_type)))
+}
+
+/// Rename a room type, change its defaults or stop offering it (admin only)
+/// PATCH /admin/room-types/:id
+pub async fn update_room_type(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<UpdateRoomTypeRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let room_type = RoomTypeService::new(state.pool).update_room_type(id, &payload)?;
+    Ok((StatusCode::OK, Json(room_type)))
+}
+
+/// Delete a room type no room uses (admin only)
+/// DELETE /admin/room-types/:id
+pub async fn delete_room_type(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    RoomTypeService::new(state.pool).delete_room_type(id)?;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -4,16 +4,20 @@ use axum::{
     response::IntoResponse,
     Json,
 };
+use bigdecimal::BigDecimal;
 use diesel::prelude::*;
-use chrono::NaiveDate;
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use uuid::Uuid;
 
 use crate::api::AppState;
 use crate::errors::AppError;
-use crate::models::{Room, RoomStatus, RoomType};
+use crate::models::{BookingStatus, Room, RoomDetailsUpdate, RoomStatus, RoomType, UserRole};
 use crate::services::{BookingService, RoomService};
-use crate::api::middleware::AuthUser;
+use crate::api::middleware::{is_staff_role, AuthUser};
+use crate::services::booking_service::{RoomCalendarNight, RoomHistoryFilter};
+use crate::services::room_service::{BulkRoomRequest, RoomEdit, RoomFilter, RoomLocation};
 use crate::schema::rooms::dsl as rooms_dsl;
 
 /// Create room request DTO
@@ -21,6 +25,9 @@ use crate::schema::rooms::dsl as rooms_dsl;
 pub struct CreateRoomDto {
     pub number: String,
     pub room_type: RoomType,
+    /// Floor and building; the floor defaults to the one the number implies
+    #[serde(flatten)]
+    pub location: RoomLocation,
 }
 
 /// Update room request DTO
@@ -29,6 +36,19 @@ pub struct UpdateRoomDto {
     pub room_type: Option<RoomType>,
     pub status: Option<RoomStatus>,
     pub assigned_cleaner_id: Option<Uuid>,
+    /// Nightly price in VND; admins only
+    pub price: Option<BigDecimal>,
+    /// Why the room goes under maintenance; opens a ticket when it has none
+    pub reason: Option<String>,
+    pub floor: Option<i32>,
+    /// An empty name moves the room to the main building
+    pub building: Option<String>,
+    /// The room's `updated_at` as last seen; a 409 with the current room is
+    /// returned if someone has changed it since
+    pub expected_updated_at: Option<DateTime<Utc>>,
+    /// Description, amenities and photo URLs shown to guests
+    #[serde(flatten)]
+    pub details: RoomDetailsUpdate,
 }
 
 /// Query parameters for listing rooms
@@ -36,6 +56,24 @@ pub struct UpdateRoomDto {
 pub struct ListRoomsQuery {
     pub status: Option<RoomStatus>,
     pub room_type: Option<RoomType>,
+    pub floor: Option<i32>,
+    /// Empty for the main building
+    pub building: Option<String>,
+    /// Also list decommissioned rooms; admins only
+    #[serde(default)]
+    pub include_decommissioned: bool,
+}
+
+impl From<ListRoomsQuery> for RoomFilter {
+    fn from(query: ListRoomsQuery) -> Self {
+        RoomFilter {
+            status: query.status,
+            room_type: query.room_type,
+            floor: query.floor,
+            building: query.building,
+            include_decommissioned: query.include_decommissioned,
+        }
+    }
 }
 
 /// Query parameters for available rooms
@@ -44,6 +82,9 @@ pub struct AvailableRoomsQuery {
     pub check_in_date: NaiveDate,
     pub check_out_date: NaiveDate,
     pub room_type: Option<RoomType>,
+    /// Also list decommissioned rooms (never available); admins only
+    #[serde(default)]
+    pub include_decommissioned: bool,
 }
 
 /// Room availability response
@@ -54,16 +95,46 @@ pub struct AvailableRoom {
     pub is_available: bool,
 }
 
+/// Only admins may look at decommissioned rooms
+fn require_decommissioned_access(auth_user: Option<&AuthUser>) -> Result<(), AppError> {
+    match auth_user {
+        Some(user) if user.role == UserRole::Admin => Ok(()),
+        _ => Err(AppError::Forbidden(
+            "Only admins can list decommissioned rooms".to_string(),
+        )),
+    }
+}
+
 /// List all rooms
 pub async fn list_rooms(
     State(state): State<AppState>,
+    auth_user: Option<Extension<AuthUser>>,
     Query(query): Query<ListRoomsQuery>,
 ) -> Result<impl IntoResponse, AppError> {
+    if query.include_decommissioned {
+        require_decommissioned_access(auth_user.as_deref())?;
+    }
     let room_service = RoomService::new(state.pool);
-    let rooms = room_service.list_rooms(query.status, query.room_type)?;
+    let rooms = room_service.list_rooms(&query.into())?;
     Ok((StatusCode::OK, Json(rooms)))
 }
 
+/// List rooms grouped by building and floor, with the same filters as
+/// [`list_rooms`]
+/// GET /rooms/grouped
+pub async fn list_rooms_grouped(
+    State(state): State<AppState>,
+    auth_user: Option<Extension<AuthUser>>,
+    Query(query): Query<ListRoomsQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    if query.include_decommissioned {
+        require_decommissioned_access(auth_user.as_deref())?;
+    }
+    let room_service = RoomService::new(state.pool);
+    let buildings = room_service.list_rooms_grouped(&query.into())?;
+    Ok((StatusCode::OK, Json(buildings)))
+}
+
 /// Get a single room by ID
 pub async fn get_room(
     State(state): State<AppState>,
@@ -74,22 +145,317 @@ pub async fn get_room(
     Ok((StatusCode::OK, Json(room)))
 }
 
+/// Query parameters for the room occupancy calendar
+#[derive(Debug, Deserialize)]
+pub struct RoomCalendarQuery {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+}
+
+/// Occupancy calendar of one room
+#[derive(Debug, Serialize)]
+pub struct RoomCalendarResponse {
+    pub room_id: Uuid,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub nights: Vec<RoomCalendarNight>,
+}
+
+/// Occupancy calendars of all rooms, keyed by room id
+#[derive(Debug, Serialize)]
+pub struct RoomsCalendarResponse {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub rooms: HashMap<Uuid, Vec<RoomCalendarNight>>,
+}
+
+fn require_calendar_access(auth_user: &AuthUser) -> Result<(), AppError> {
+    if is_staff_role(auth_user.role) {
+        Ok(())
+    } else {
+        Err(AppError::Forbidden(
+            "Only staff can view room calendars".to_string(),
+        ))
+    }
+}
+
+/// Query parameters for a room's booking history
+#[derive(Debug, Deserialize)]
+pub struct RoomBookingsQuery {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub status: Option<String>,
+    pub page: Option<u64>,
+    pub per_page: Option<u64>,
+}
+
+/// Stays in a room overlapping the date range, latest check-in first.
+/// Receptionists get the same list as admins without prices.
+/// GET /rooms/:id/bookings?from=2025-06-01&to=2025-06-07&status=checked_out&page=1&per_page=20
+pub async fn get_room_bookings(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(query): Query<RoomBookingsQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    if !is_staff_role(auth_user.role) {
+        return Err(AppError::Forbidden(
+            "Only staff can view a room's bookings".to_string(),
+        ));
+    }
+
+    let filter = RoomHistoryFilter {
+        from: query.from,
+        to: query.to,
+        status: query
+            .status
+            .as_deref()
+            .map(str::parse::<BookingStatus>)
+            .transpose()?,
+    };
+
+    let booking_service = BookingService::new(state.pool);
+    let history = booking_service.get_room_history_page(
+        id,
+        &filter,
+        auth_user.role == UserRole::Admin,
+        query.page,
+        query.per_page,
+    )?;
+
+    Ok((StatusCode::OK, Json(history)))
+}
+
+/// Query parameters for the room number search
+#[derive(Debug, Deserialize)]
+pub struct SearchRoomsQuery {
+    pub q: String,
+}
+
+/// Rooms whose number starts with the query, for the reception desk
+/// GET /rooms/search?q=20
+pub async fn search_rooms(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(query): Query<SearchRoomsQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    if !is_staff_role(auth_user.role) {
+        return Err(AppError::Forbidden(
+            "Only staff can search rooms".to_string(),
+        ));
+    }
+
+    let rooms = RoomService::new(state.pool).search_rooms(&query.q)?;
+    Ok((StatusCode::OK, Json(rooms)))
+}
+
+/// Room counts by status and by type for the dashboard tiles
+/// GET /rooms/summary
+pub async fn get_rooms_summary(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<impl IntoResponse, AppError> {
+    if !is_staff_role(auth_user.role) {
+        return Err(AppError::Forbidden(
+            "Only staff can view the room summary".to_string(),
+        ));
+    }
+
+    let summary = RoomService::new(state.pool).status_summary()?;
+    Ok((StatusCode::OK, Json(summary)))
+}
+
+/// A room with the guest staying in it and the next arrival
+/// GET /rooms/:id/overview
+pub async fn get_room_overview(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<impl IntoResponse, AppError> {
+    if !is_staff_role(auth_user.role) {
+        return Err(AppError::Forbidden(
+            "Only staff can view a room's overview".to_string(),
+        ));
+    }
+
+    let overview = RoomService::new(state.pool).get_room_overview(id)?;
+    Ok((StatusCode::OK, Json(overview)))
+}
+
+/// Optional stay range for cancelling a room's upcoming bookings
+#[derive(Debug, Deserialize)]
+pub struct CancelUpcomingQuery {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+/// Cancel a room's upcoming bookings before maintenance. Returns the cancelled
+/// references and, separately, guests already in the room who were skipped.
+/// POST /admin/rooms/:id/cancel-upcoming?from=2025-06-01&to=2025-06-21
+pub async fn cancel_upcoming_bookings(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(query): Query<CancelUpcomingQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let booking_service = BookingService::new(state.pool);
+    let result = booking_service.cancel_upcoming_for_room(id, query.from, query.to, auth_user.user_id)?;
+    Ok((StatusCode::OK, Json(result)))
+}
+
+/// Query parameters for a room's status history
+#[derive(Debug, Deserialize)]
+pub struct RoomStatusHistoryQuery {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub page: Option<u64>,
+    pub per_page: Option<u64>,
+}
+
+/// Status changes of a room, newest first, with who made each one
+/// GET /rooms/:id/status-history?from=2025-06-01&to=2025-06-07&page=1&per_page=20
+pub async fn get_room_status_history(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(query): Query<RoomStatusHistoryQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    if !is_staff_role(auth_user.role) {
+        return Err(AppError::Forbidden(
+            "Only staff can view a room's status history".to_string(),
+        ));
+    }
+
+    let room_service = RoomService::new(state.pool);
+    let history = room_service.get_status_history(id, query.from, query.to, query.page, query.per_page)?;
+    Ok((StatusCode::OK, Json(history)))
+}
+
+/// Nightly price changes of a room, newest first
+/// GET /admin/rooms/:id/price-history
+pub async fn get_room_price_history(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    let room_service = RoomService::new(state.pool);
+    let history = room_service.get_price_history(id)?;
+    Ok((StatusCode::OK, Json(history)))
+}
+
+/// Booking reference and status occupying each night of a room
+/// GET /rooms/:id/calendar?from=2025-06-01&to=2025-06-30
+pub async fn get_room_calendar(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(query): Query<RoomCalendarQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    require_calendar_access(&auth_user)?;
+
+    let booking_service = BookingService::new(state.pool);
+    let nights = booking_service.get_room_calendar(id, query.from, query.to)?;
+
+    Ok((
+        StatusCode::OK,
+        Json(RoomCalendarResponse {
+            room_id: id,
+            from: query.from,
+            to: query.to,
+            nights,
+        }),
+    ))
+}
+
+/// The per-night calendar of every room, for the month grid
+/// GET /rooms/calendar?from=2025-06-01&to=2025-06-30
+pub async fn get_rooms_calendar(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(query): Query<RoomCalendarQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    require_calendar_access(&auth_user)?;
+
+    let booking_service = BookingService::new(state.pool);
+    let rooms = booking_service.get_rooms_calendar(query.from, query.to)?;
+
+    Ok((
+        StatusCode::OK,
+        Json(RoomsCalendarResponse {
+            from: query.from,
+            to: query.to,
+            rooms,
+        }),
+    ))
+}
+
+/// Rooms held by guests past their check-out date, with how many days over
+/// GET /admin/rooms/overstays
+pub async fn list_overstay_rooms(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<impl IntoResponse, AppError> {
+    if !is_staff_role(auth_user.role) {
+        return Err(AppError::Forbidden(
+            "Only staff can view overstays".to_string(),
+        ));
+    }
+
+    let booking_service = BookingService::new(state.pool);
+    let overstays = booking_service.list_overstay_rooms()?;
+    Ok((StatusCode::OK, Json(overstays)))
+}
+
 /// Create a new room (admin only)
 pub async fn create_room(
     State(state): State<AppState>,
     Json(payload): Json<CreateRoomDto>,
 ) -> Result<impl IntoResponse, AppError> {
     let room_service = RoomService::new(state.pool);
-    let room = room_service.create_room(&payload.number, payload.room_type)?;
+    let room = room_service.create_room_at(&payload.number, payload.room_type, &payload.location)?;
     Ok((StatusCode::CREATED, Json(room)))
 }
 
-/// Update a room
+/// Create many rooms at once for a new property (admin only). Takes a list
+/// of rooms or a floor range; numbers that are duplicated or taken are
+/// listed as skipped.
+/// POST /admin/rooms/bulk
+pub async fn create_rooms_bulk(
+    State(state): State<AppState>,
+    Json(payload): Json<BulkRoomRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let room_service = RoomService::new(state.pool);
+    let result = room_service.create_rooms_bulk(payload)?;
+    Ok((StatusCode::CREATED, Json(result)))
+}
+
+/// Decommission a room (admin only). The room is kept for its booking
+/// history but no longer listed or bookable.
+pub async fn decommission_room(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    let room_service = RoomService::new(state.pool);
+    let room = room_service.decommission_room(id)?;
+    Ok((StatusCode::OK, Json(room)))
+}
+
+/// Update a room. Receptionists may change status and type; only admins may
+/// change the price, and each price change is recorded in the price history.
 pub async fn update_room(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
+    Extension(auth_user): Extension<AuthUser>,
     Json(payload): Json<UpdateRoomDto>,
 ) -> Result<impl IntoResponse, AppError> {
+    if !is_staff_role(auth_user.role) {
+        return Err(AppError::Forbidden("Only staff can update rooms".to_string()));
+    }
+    if payload.price.is_some() && auth_user.role != UserRole::Admin {
+        return Err(AppError::Forbidden(
+            "Only admins can change room prices".to_string(),
+        ));
+    }
+
     let room_service = RoomService::new(state.pool);
     // Wrap the cleaner ID in Option<Option<Uuid>> to allow passing it through
     // If payload.assigned_cleaner_id is Some(id), we pass Some(Some(id)).
@@ -97,26 +463,48 @@ pub async fn update_room(
     // For now, let's assume if it's sent, we update it.
     let assigned_id_update = payload.assigned_cleaner_id.map(Some);
     
-    let room = room_service.update_room(id, payload.room_type, payload.status, assigned_id_update)?;
+    let room = room_service.update_room(
+        id,
+        RoomEdit {
+            room_type: payload.room_type,
+            status: payload.status,
+            assigned_cleaner_id: assigned_id_update,
+            details: payload.details,
+            price: payload.price,
+            maintenance_reason: payload.reason,
+            floor: payload.floor,
+            building: payload.building,
+            expected_updated_at: payload.expected_updated_at,
+        },
+        Some(auth_user.user_id),
+    )?;
     Ok((StatusCode::OK, Json(room)))
 }
 
 /// Get available rooms for a date range
 pub async fn available_rooms(
     State(state): State<AppState>,
+    auth_user: Option<Extension<AuthUser>>,
     Query(query): Query<AvailableRoomsQuery>,
 ) -> Result<impl IntoResponse, AppError> {
+    if query.include_decommissioned {
+        require_decommissioned_access(auth_user.as_deref())?;
+    }
     let room_service = RoomService::new(state.pool.clone());
     let booking_service = BookingService::new(state.pool);
 
     // Get all rooms (optionally filtered by type)
-    let rooms = room_service.list_rooms(None, query.room_type)?;
+    let rooms = room_service.list_rooms(&RoomFilter {
+        room_type: query.room_type,
+        include_decommissioned: query.include_decommissioned,
+        ..RoomFilter::default()
+    })?;
 
     // Check availability for each room
     let mut available_rooms: Vec<AvailableRoom> = Vec::new();
     for room in rooms {
-        // Maintenance rooms are always unavailable
-        if room.status == RoomStatus::Maintenance {
+        // Maintenance and decommissioned rooms are always unavailable
+        if room.status == RoomStatus::Maintenance || room.decommissioned_at.is_some() {
             available_rooms.push(AvailableRoom {
                 room,
                 is_available: false,
@@ -125,12 +513,14 @@ pub async fn available_rooms(
         }
 
         // check_availability handles both booking conflicts and room status checks
-        // (e.g., Occupied rooms can't be booked for same-day check-in, but future bookings are OK)
+        // (e.g., a same-day check-in needs the previous guest gone and the room cleaned,
+        // but future bookings are OK)
         let is_available = booking_service.check_availability(
             room.id,
             query.check_in_date,
             query.check_out_date,
             None,
+            None,
         )?;
 
         available_rooms.push(AvailableRoom { room, is_available });
@@ -144,6 +534,9 @@ pub async fn available_rooms(
 pub struct CleanerRoomsQuery {
     pub status: Option<RoomStatus>,
     pub room_type: Option<RoomType>,
+    pub floor: Option<i32>,
+    /// Empty for the main building
+    pub building: Option<String>,
 }
 
 /// Update room status request for cleaner
@@ -153,7 +546,9 @@ pub struct UpdateRoomStatusRequest {
 }
 
 /// List rooms for cleaner dashboard
-/// Defaults to showing dirty rooms if no status filter is provided
+/// Defaults to showing dirty rooms if no status filter is provided, also
+/// when narrowed to a floor or building. Rooms with a guest arriving today
+/// come first, then the ones that have waited longest.
 pub async fn list_cleaner_rooms(
     State(state): State<AppState>,
     Query(query): Query<CleanerRoomsQuery>,
@@ -161,7 +556,13 @@ pub async fn list_cleaner_rooms(
     let room_service = RoomService::new(state.pool);
     // Default to dirty rooms if no status filter is provided
     let status_filter = query.status.or(Some(RoomStatus::Dirty));
-    let rooms = room_service.list_rooms(status_filter, query.room_type)?;
+    let rooms = room_service.list_cleaner_rooms(&RoomFilter {
+        status: status_filter,
+        room_type: query.room_type,
+        floor: query.floor,
+        building: query.building,
+        include_decommissioned: false,
+    })?;
     Ok((StatusCode::OK, Json(rooms)))
 }
 
@@ -177,7 +578,7 @@ pub async fn update_cleaner_room_status(
     // Role-based validation: cleaners cannot set status to Occupied or Maintenance
     if !payload.status.is_allowed_for_role(auth_user.role) {
         return Err(AppError::Forbidden(format!(
-            "Cleaners cannot set room status to {:?}. Allowed statuses: dirty, cleaning, available.",
+            "Cleaners cannot set room status to {}. Allowed statuses: dirty, cleaning, available.",
             payload.status
         )));
     }
@@ -190,7 +591,7 @@ pub async fn update_cleaner_room_status(
     // Validate status transition
     if !current_room.status.can_transition_to(payload.status) {
         return Err(AppError::InvalidStatusTransition(format!(
-            "Cannot transition room from {:?} to {:?}",
+            "Cannot transition room from {} to {}",
             current_room.status, payload.status
         )));
     }
@@ -201,38 +602,49 @@ pub async fn update_cleaner_room_status(
         .get()
         .map_err(|e| AppError::DatabaseError(e.to_string()))?;
 
-    // Build update query - use tuple syntax when setting multiple fields
-    let rows_updated = if payload.status == RoomStatus::Available {
-        // If finishing cleaning, clear the assignment and update status
-        diesel::update(
-            rooms_dsl::rooms
-                .filter(rooms_dsl::id.eq(id))
-                .filter(rooms_dsl::status.eq(current_room.status)),
-        )
-        .set((
-            rooms_dsl::status.eq(payload.status),
-            rooms_dsl::assigned_cleaner_id.eq(None::<Uuid>),
-        ))
-        .execute(&mut conn)
-        .map_err(|e| AppError::DatabaseError(e.to_string()))?
-    } else {
-        // Only update status
-        diesel::update(
-            rooms_dsl::rooms
-                .filter(rooms_dsl::id.eq(id))
-                .filter(rooms_dsl::status.eq(current_room.status)),
-        )
-        .set(rooms_dsl::status.eq(payload.status))
-        .execute(&mut conn)
-        .map_err(|e| AppError::DatabaseError(e.to_string()))?
-    };
+    // The status history entry commits together with the change it records;
+    // the room lock keeps a check-in from slipping in after the guest check
+    conn.transaction::<_, AppError, _>(|conn| {
+        BookingService::lock_room(conn, id)?;
+        RoomService::check_no_guest_on(conn, &current_room, payload.status)?;
 
-    if rows_updated == 0 {
-        return Err(AppError::Conflict(
-            "Room status was updated by someone else. Please refresh and try again."
-                .to_string(),
-        ));
-    }
+        let rooms_to_update = rooms_dsl::rooms
+            .filter(rooms_dsl::id.eq(id))
+            .filter(rooms_dsl::status.eq(current_room.status));
+
+        // Build update query - use tuple syntax when setting multiple fields
+        let rows_updated = if payload.status == RoomStatus::Available {
+            // If finishing cleaning, clear the assignment and update status
+            diesel::update(rooms_to_update)
+                .set((
+                    rooms_dsl::status.eq(payload.status),
+                    rooms_dsl::assigned_cleaner_id.eq(None::<Uuid>),
+                ))
+                .execute(conn)
+                .map_err(|e| AppError::DatabaseError(e.to_string()))?
+        } else {
+            // Only update status
+            diesel::update(rooms_to_update)
+                .set(rooms_dsl::status.eq(payload.status))
+                .execute(conn)
+                .map_err(|e| AppError::DatabaseError(e.to_string()))?
+        };
+
+        if rows_updated == 0 {
+            return Err(AppError::Conflict(
+                "Room status was updated by someone else. Please refresh and try again."
+                    .to_string(),
+            ));
+        }
+
+        RoomService::record_status_change(
+            conn,
+            id,
+            current_room.status,
+            payload.status,
+            Some(auth_user.user_id),
+        )
+    })?;
 
     let updated_room = room_service.get_room_by_id(id)?;
     Ok((StatusCode::OK, Json(updated_room)))
@@ -1,16 +1,16 @@
 use axum::{
-    extract::{State},
+    extract::{Extension, State},
     Json,
 };
 use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
 
 use crate::{
-    api::AppState,
+    api::{middleware::AuthUser, AppState},
     db::get_conn,
     errors::{AppError, AppResult},
-    schema::system_settings,
+    services::AuditService,
+    settings::{self, SettingSchema, Settings},
 };
 
 #[derive(Serialize, Deserialize)]
@@ -21,55 +21,119 @@ pub struct AdminAiSettings {
     pub ai_model: String,
 }
 
-pub async fn get_ai_settings(
-    State(state): State<AppState>,
-) -> AppResult<Json<AdminAiSettings>> {
+/// One stored setting as shown to admins
+#[derive(Debug, Serialize)]
+pub struct SettingEntry {
+    pub key: &'static str,
+    /// Effective value (stored if valid, else the default); secrets are masked
+    pub value: String,
+    /// False when no value is stored or the stored value is invalid
+    pub is_set: bool,
+}
+
+const SECRET_MASK: &str = "********";
+
+fn load_settings(state: &AppState) -> AppResult<Settings> {
     let mut conn = get_conn(&state.pool).map_err(|e| AppError::DatabaseError(e.to_string()))?;
+    Settings::load(&mut conn).map_err(|e| AppError::DatabaseError(e.to_string()))
+}
+
+/// Validate and store settings, audit-logging which keys changed
+fn apply_updates(
+    state: &AppState,
+    actor: &AuthUser,
+    updates: &serde_json::Map<String, serde_json::Value>,
+) -> AppResult<()> {
+    let values = settings::validate_updates(updates)?;
+    let keys: Vec<&str> = values.iter().map(|(key, _)| *key).collect();
 
-    let settings: Vec<(String, String)> = system_settings::table
-        .select((system_settings::key, system_settings::value))
-        .load::<(String, String)>(&mut conn)
-        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+    let mut conn = get_conn(&state.pool).map_err(|e| AppError::DatabaseError(e.to_string()))?;
+    conn.transaction::<_, AppError, _>(|conn| {
+        settings::save(conn, &values)?;
+        AuditService::record(
+            conn,
+            Some(actor.user_id),
+            "system_setting.updated",
+            "system_setting",
+            None,
+            Some(&keys.join(", ")),
+        )?;
+        Ok(())
+    })
+}
 
-    let map: HashMap<String, String> = settings.into_iter().collect();
+pub async fn get_ai_settings(
+    State(state): State<AppState>,
+) -> AppResult<Json<AdminAiSettings>> {
+    let settings = load_settings(&state)?;
 
     Ok(Json(AdminAiSettings {
-        ai_enabled: map.get("ai_enabled").map(|v| v == "true").unwrap_or(false),
-        ai_provider: map.get("ai_provider").cloned().unwrap_or("openai".to_string()),
-        ai_api_key: map.get("ai_api_key").cloned().unwrap_or_default(),
-        ai_model: map.get("ai_model").cloned().unwrap_or("gpt-3.5-turbo".to_string()),
+        ai_enabled: settings.bool(settings::AI_ENABLED),
+        ai_provider: settings.text(settings::AI_PROVIDER),
+        ai_api_key: settings.text(settings::AI_API_KEY),
+        ai_model: settings.text(settings::AI_MODEL),
     }))
 }
 
 pub async fn update_ai_settings(
     State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
     Json(payload): Json<AdminAiSettings>,
 ) -> AppResult<Json<AdminAiSettings>> {
-    let mut conn = get_conn(&state.pool).map_err(|e| AppError::DatabaseError(e.to_string()))?;
+    let updates = match serde_json::to_value(&payload) {
+        Ok(serde_json::Value::Object(map)) => map,
+        _ => return Err(AppError::InternalError("Failed to encode AI settings".to_string())),
+    };
+    apply_updates(&state, &auth_user, &updates)?;
+
+    get_ai_settings(State(state)).await
+}
+
+/// Describe every known setting so the frontend can render form controls
+/// GET /admin/settings/schema
+pub async fn get_settings_schema() -> Json<Vec<SettingSchema>> {
+    Json(settings::schema())
+}
 
-    let updates = vec![
-        ("ai_enabled", if payload.ai_enabled { "true" } else { "false" }),
-        ("ai_provider", payload.ai_provider.as_str()),
-        ("ai_api_key", payload.ai_api_key.as_str()),
-        ("ai_model", payload.ai_model.as_str()),
-    ];
-
-    for (key, val) in updates {
-        diesel::insert_into(system_settings::table)
-            .values((
-                system_settings::key.eq(key),
-                system_settings::value.eq(val),
-                system_settings::updated_at.eq(chrono::Utc::now())
-            ))
-            .on_conflict(system_settings::key)
-            .do_update()
-            .set((
-                system_settings::value.eq(val),
-                system_settings::updated_at.eq(chrono::Utc::now())
-            ))
-            .execute(&mut conn)
-            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+/// List the effective value of every known setting
+/// GET /admin/settings
+pub async fn list_settings(State(state): State<AppState>) -> AppResult<Json<Vec<SettingEntry>>> {
+    let stored = load_settings(&state)?;
+
+    let entries = settings::REGISTRY
+        .iter()
+        .map(|def| {
+            let value = stored.text(def.key);
+            let is_set = stored.raw(def.key).is_some_and(|raw| {
+                settings::validate_value(def, &serde_json::Value::String(raw.to_string())).is_ok()
+            });
+            SettingEntry {
+                key: def.key,
+                value: if def.secret && !value.is_empty() {
+                    SECRET_MASK.to_string()
+                } else {
+                    value
+                },
+                is_set,
+            }
+        })
+        .collect();
+
+    Ok(Json(entries))
+}
+
+/// Update settings by key. The whole batch is rejected if any key is unknown,
+/// managed elsewhere or has a value of the wrong type.
+/// PATCH /admin/settings
+pub async fn update_settings(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(payload): Json<serde_json::Map<String, serde_json::Value>>,
+) -> AppResult<Json<Vec<SettingEntry>>> {
+    if payload.is_empty() {
+        return Err(AppError::ValidationError("No settings provided".to_string()));
     }
+    apply_updates(&state, &auth_user, &payload)?;
 
-    Ok(Json(payload))
-}
\ No newline at end of file
+    list_settings(State(state)).await
+}
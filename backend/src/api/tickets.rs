@@ -0,0 +1,88 @@
+use axum::{
+    extract::{Extension, Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::api::middleware::AuthUser;
+use crate::api::AppState;
+use crate::errors::AppError;
+use crate::models::TicketStatus;
+use crate::services::ticket_service::{CreateTicketRequest, UpdateTicketRequest};
+use crate::services::TicketService;
+
+/// Query parameters for listing tickets
+#[derive(Debug, Deserialize)]
+pub struct ListTicketsQuery {
+    pub status: Option<TicketStatus>,
+}
+
+/// Tickets across all rooms, most severe first (admin only)
+/// GET /admin/tickets?status=open
+pub async fn list_tickets(
+    State(state): State<AppState>,
+    Query(query): Query<ListTicketsQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let tickets = TicketService::new(state.pool).list_tickets(query.status)?;
+    Ok((StatusCode::OK, Json(tickets)))
+}
+
+/// Tickets of a room, newest first (admin only)
+/// GET /admin/rooms/:id/tickets
+pub async fn list_room_tickets(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<ListTicketsQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let tickets = TicketService::new(state.pool).list_room_tickets(id, query.status)?;
+    Ok((StatusCode::OK, Json(tickets)))
+}
+
+/// Report a problem with a room (admin only)
+/// POST /admin/rooms/:id/tickets
+pub async fn create_ticket(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(payload): Json<CreateTicketRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let ticket = TicketService::new(state.pool).create_ticket(id, &payload, Some(auth_user.user_id))?;
+    Ok((StatusCode::CREATED, Json(ticket)))
+}
+
+/// One ticket of a room (admin only)
+/// GET /admin/rooms/:id/tickets/:ticket_id
+pub async fn get_ticket(
+    State(state): State<AppState>,
+    Path((id, ticket_id)): Path<(Uuid, Uuid)>,
+) -> Result<impl IntoResponse, AppError> {
+    let ticket = TicketService::new(state.pool).get_ticket(id, ticket_id)?;
+    Ok((StatusCode::OK, Json(ticket)))
+}
+
+/// Update a ticket (admin only). The response says whether the room may now
+/// return to Available; `set_available` does so in the same request.
+/// PATCH /admin/rooms/:id/tickets/:ticket_id
+pub async fn update_ticket(
+    State(state): State<AppState>,
+    Path((id, ticket_id)): Path<(Uuid, Uuid)>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(payload): Json<UpdateTicketRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let result =
+        TicketService::new(state.pool).update_ticket(id, ticket_id, &payload, Some(auth_user.user_id))?;
+    Ok((StatusCode::OK, Json(result)))
+}
+
+/// Delete a ticket (admin only)
+/// DELETE /admin/rooms/:id/tickets/:ticket_id
+pub async fn delete_ticket(
+    State(state): State<AppState>,
+    Path((id, ticket_id)): Path<(Uuid, Uuid)>,
+) -> Result<impl IntoResponse, AppError> {
+    TicketService::new(state.pool).delete_ticket(id, ticket_id)?;
+    Ok(StatusCode::NO_CONTENT)
+}
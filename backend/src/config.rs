@@ -1,5 +1,70 @@
 use std::env;
 
+use crate::scheduler::DEFAULT_STALE_SYNC_INTERVAL_SECS;
+use crate::services::auth_service::{
+    PasswordHashing, TokenIssuer, DEFAULT_ACCESS_TOKEN_LIFETIME, DEFAULT_JWT_ISSUER,
+};
+use crate::services::maintenance_service::parse_env_override;
+use crate::services::rate_limit_service::{
+    LockoutPolicy, DEFAULT_LOGIN_LOCKOUT_MINUTES, DEFAULT_LOGIN_LOCKOUT_THRESHOLD,
+};
+use crate::services::session_service::{IdleLimits, DEFAULT_IDLE_TIMEOUT_MINUTES};
+use crate::services::storage_service::MAX_UPLOAD_BYTES;
+use crate::utils::redact::{env_value_for_log, redact_url};
+
+/// Messages a user may send per chat rate window by default
+pub const DEFAULT_CHAT_MESSAGES: u32 = 20;
+/// Messages to Pupinn per chat rate window by default; each one is an AI call
+pub const DEFAULT_CHAT_ASSISTANT_MESSAGES: u32 = 5;
+/// Default chat rate window, in seconds
+pub const DEFAULT_CHAT_RATE_WINDOW_SECS: u64 = 10;
+/// How long a user over a chat rate limit is muted by default, in seconds
+pub const DEFAULT_CHAT_MUTE_SECS: u64 = 30;
+
+/// Default cap on request bodies (JSON and everything else)
+pub const DEFAULT_BODY_LIMIT_BYTES: usize = 64 * 1024;
+
+/// Request body size limits, enforced before handlers parse the body
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BodyLimits {
+    /// Applied to every route unless raised below
+    pub default_bytes: usize,
+    /// Applied to multipart upload routes; never above the storage limit
+    pub upload_bytes: usize,
+}
+
+impl Default for BodyLimits {
+    fn default() -> Self {
+        Self {
+            default_bytes: DEFAULT_BODY_LIMIT_BYTES,
+            upload_bytes: MAX_UPLOAD_BYTES,
+        }
+    }
+}
+
+/// Per-user chat flood limits, each a token bucket refilled over `window`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChatLimits {
+    /// Messages and image uploads
+    pub messages: u32,
+    /// Messages to Pupinn, which count against `messages` as well
+    pub assistant_messages: u32,
+    pub window: std::time::Duration,
+    /// How long a user who goes over a limit cannot send what it covers
+    pub mute: std::time::Duration,
+}
+
+impl Default for ChatLimits {
+    fn default() -> Self {
+        Self {
+            messages: DEFAULT_CHAT_MESSAGES,
+            assistant_messages: DEFAULT_CHAT_ASSISTANT_MESSAGES,
+            window: std::time::Duration::from_secs(DEFAULT_CHAT_RATE_WINDOW_SECS),
+            mute: std::time::Duration::from_secs(DEFAULT_CHAT_MUTE_SECS),
+        }
+    }
+}
+
 /// Application configuration loaded from environment variables
 #[derive(Debug, Clone)]
 pub struct Config {
@@ -12,6 +77,47 @@ pub struct Config {
     pub minio_public_url: String,
     pub minio_root_user: String,
     pub minio_root_password: String,
+    /// How long startup waits for Postgres and MinIO before giving up
+    pub startup_wait_secs: u64,
+    /// READ_ONLY_MODE override; when set it wins over the admin setting
+    pub read_only_override: Option<bool>,
+    /// Transactional mail API endpoint; emails are only logged when unset
+    pub mail_api_url: Option<String>,
+    pub mail_api_key: Option<String>,
+    pub mail_from: String,
+    pub body_limits: BodyLimits,
+    /// LOG_MESSAGE_CONTENT; chat message bodies are only logged when on.
+    /// Defaults to on in debug builds and off in release builds.
+    pub log_message_content: bool,
+    /// STALE_SYNC_INTERVAL_SECS; how often stale bookings become NoShow/Overstay
+    pub stale_sync_interval_secs: u64,
+    /// SESSION_IDLE_TIMEOUT_MINUTES, with SESSION_IDLE_TIMEOUT_ADMIN_MINUTES and
+    /// SESSION_IDLE_TIMEOUT_GUEST_MINUTES overriding it for those roles
+    pub session_idle_limits: IdleLimits,
+    /// ACCESS_TOKEN_LIFETIME_MINUTES, or JWT_EXPIRY_HOURS when that is not
+    /// set; how long an access token is valid before it has to be refreshed
+    pub access_token_lifetime: chrono::Duration,
+    /// JWT_ISSUER, stamped on tokens as `iss` and `aud` and required back.
+    /// Give each deployment its own so tokens do not cross between them;
+    /// only debug builds fall back to a default. JWT_LEGACY_TOKEN_GRACE_FROM,
+    /// the deploy time that introduced them, accepts tokens issued before it
+    /// without them for 24 hours after it. Unset, they are refused.
+    pub token_issuer: TokenIssuer,
+    /// PASSWORD_HASH_MEMORY_KIB, PASSWORD_HASH_ITERATIONS and
+    /// PASSWORD_HASH_PARALLELISM; Argon2id cost of new password hashes.
+    /// Weaker stored hashes are upgraded when their owner logs in.
+    pub password_hashing: PasswordHashing,
+    /// LOGIN_LOCKOUT_THRESHOLD failed logins lock a username or IP for
+    /// LOGIN_LOCKOUT_MINUTES
+    pub login_lockout: LockoutPolicy,
+    /// TRUST_PROXY_HEADERS; take the client IP from X-Forwarded-For. Only
+    /// turn it on behind a reverse proxy that sets the header, or clients
+    /// can pick their own IP.
+    pub trust_proxy_headers: bool,
+    /// CHAT_RATE_LIMIT_MESSAGES and CHAT_RATE_LIMIT_ASSISTANT_MESSAGES per
+    /// CHAT_RATE_LIMIT_WINDOW_SECS; going over mutes a user for
+    /// CHAT_RATE_LIMIT_MUTE_SECS
+    pub chat_limits: ChatLimits,
 }
 
 impl Config {
@@ -37,7 +143,7 @@ impl Config {
         let get_env = |key: &str| {
             let val = env::var(key);
             match &val {
-                Ok(v) => tracing::info!("Env var {} loaded: {}", key, v),
+                Ok(v) => tracing::info!("Env var {} loaded: {}", key, env_value_for_log(key, v)),
                 Err(_) => tracing::warn!("Env var {} not found in system or .env", key),
             }
             val
@@ -56,6 +162,111 @@ impl Config {
             std::process::exit(1);
         }
 
+        let idle_minutes = |key: &str, default: i64| -> i64 {
+            match get_env(key).ok().filter(|v| !v.trim().is_empty()) {
+                Some(value) => value.trim().parse().ok().filter(|minutes| *minutes > 0).unwrap_or_else(|| {
+                    eprintln!("ERROR: {} must be a positive whole number of minutes!", key);
+                    std::process::exit(1);
+                }),
+                None => default,
+            }
+        };
+        let idle_default = idle_minutes("SESSION_IDLE_TIMEOUT_MINUTES", DEFAULT_IDLE_TIMEOUT_MINUTES);
+        let session_idle_limits = IdleLimits {
+            default: chrono::Duration::minutes(idle_default),
+            admin: chrono::Duration::minutes(idle_minutes("SESSION_IDLE_TIMEOUT_ADMIN_MINUTES", idle_default)),
+            guest: chrono::Duration::minutes(idle_minutes("SESSION_IDLE_TIMEOUT_GUEST_MINUTES", idle_default)),
+        };
+        let expiry_hours = idle_minutes("JWT_EXPIRY_HOURS", DEFAULT_ACCESS_TOKEN_LIFETIME.num_hours());
+        let access_token_lifetime = chrono::Duration::minutes(idle_minutes(
+            "ACCESS_TOKEN_LIFETIME_MINUTES",
+            chrono::Duration::hours(expiry_hours).num_minutes(),
+        ));
+        let token_issuer = TokenIssuer::new(
+            match get_env("JWT_ISSUER")
+                .ok()
+                .map(|issuer| issuer.trim().to_string())
+                .filter(|issuer| !issuer.is_empty())
+            {
+                Some(issuer) => issuer,
+                None if cfg!(debug_assertions) => {
+                    tracing::info!("JWT_ISSUER not set, using default: {}", DEFAULT_JWT_ISSUER);
+                    DEFAULT_JWT_ISSUER.to_string()
+                }
+                None => {
+                    eprintln!("ERROR: JWT_ISSUER environment variable is not set!");
+                    eprintln!("Please give each deployment its own JWT_ISSUER so tokens do not cross between them.");
+                    std::process::exit(1);
+                }
+            },
+        );
+        let token_issuer = match get_env("JWT_LEGACY_TOKEN_GRACE_FROM")
+            .ok()
+            .filter(|v| !v.trim().is_empty())
+        {
+            Some(value) => token_issuer.with_legacy_grace(
+                chrono::DateTime::parse_from_rfc3339(value.trim())
+                    .map(|deployed_at| deployed_at.with_timezone(&chrono::Utc))
+                    .unwrap_or_else(|_| {
+                        eprintln!("ERROR: JWT_LEGACY_TOKEN_GRACE_FROM must be an RFC 3339 timestamp!");
+                        std::process::exit(1);
+                    }),
+            ),
+            None => token_issuer,
+        };
+        let hashing_cost = |key: &str, default: u32| -> u32 {
+            match get_env(key).ok().filter(|v| !v.trim().is_empty()) {
+                Some(value) => value.trim().parse().ok().filter(|cost| *cost > 0).unwrap_or_else(|| {
+                    eprintln!("ERROR: {} must be a positive whole number!", key);
+                    std::process::exit(1);
+                }),
+                None => default,
+            }
+        };
+        let default_hashing = PasswordHashing::default();
+        let password_hashing = PasswordHashing {
+            memory_kib: hashing_cost("PASSWORD_HASH_MEMORY_KIB", default_hashing.memory_kib),
+            iterations: hashing_cost("PASSWORD_HASH_ITERATIONS", default_hashing.iterations),
+            parallelism: hashing_cost("PASSWORD_HASH_PARALLELISM", default_hashing.parallelism),
+        };
+        if let Err(e) = password_hashing.params() {
+            eprintln!("ERROR: {}", e);
+            std::process::exit(1);
+        }
+        let chat_limit = |key: &str, default: u64| -> u64 {
+            match get_env(key).ok().filter(|v| !v.trim().is_empty()) {
+                Some(value) => value.trim().parse().ok().filter(|limit| *limit > 0).unwrap_or_else(|| {
+                    eprintln!("ERROR: {} must be a positive whole number!", key);
+                    std::process::exit(1);
+                }),
+                None => default,
+            }
+        };
+        let chat_limits = ChatLimits {
+            messages: chat_limit("CHAT_RATE_LIMIT_MESSAGES", DEFAULT_CHAT_MESSAGES as u64) as u32,
+            assistant_messages: chat_limit(
+                "CHAT_RATE_LIMIT_ASSISTANT_MESSAGES",
+                DEFAULT_CHAT_ASSISTANT_MESSAGES as u64,
+            ) as u32,
+            window: std::time::Duration::from_secs(chat_limit(
+                "CHAT_RATE_LIMIT_WINDOW_SECS",
+                DEFAULT_CHAT_RATE_WINDOW_SECS,
+            )),
+            mute: std::time::Duration::from_secs(chat_limit("CHAT_RATE_LIMIT_MUTE_SECS", DEFAULT_CHAT_MUTE_SECS)),
+        };
+        let login_lockout = LockoutPolicy {
+            threshold: match get_env("LOGIN_LOCKOUT_THRESHOLD").ok().filter(|v| !v.trim().is_empty()) {
+                Some(value) => value.trim().parse().ok().filter(|failures| *failures > 0).unwrap_or_else(|| {
+                    eprintln!("ERROR: LOGIN_LOCKOUT_THRESHOLD must be a positive whole number of failures!");
+                    std::process::exit(1);
+                }),
+                None => DEFAULT_LOGIN_LOCKOUT_THRESHOLD,
+            },
+            window: std::time::Duration::from_secs(
+                idle_minutes("LOGIN_LOCKOUT_MINUTES", DEFAULT_LOGIN_LOCKOUT_MINUTES as i64) as u64 * 60,
+            ),
+        };
+
         Self {
             database_url: get_env("DATABASE_URL")
                 .unwrap_or_else(|_| {
@@ -101,16 +312,116 @@ impl Config {
             minio_root_user: get_env("MINIO_ROOT_USER")
                 .unwrap_or_else(|_| {
                     let default = "minioadmin".to_string();
-                    tracing::info!("MINIO_ROOT_USER not set, using default: {}", default);
+                    tracing::info!("MINIO_ROOT_USER not set, using the default");
                     default
                 }),
             minio_root_password: get_env("MINIO_ROOT_PASSWORD")
                 .unwrap_or_else(|_| {
                     let default = "minioadmin".to_string();
-                    tracing::info!("MINIO_ROOT_PASSWORD not set, using default: {}", default);
+                    tracing::info!("MINIO_ROOT_PASSWORD not set, using the default");
                     default
                 }),
+            startup_wait_secs: get_env("STARTUP_WAIT_SECS")
+                .unwrap_or_else(|_| {
+                    let default = "60".to_string();
+                    tracing::info!("STARTUP_WAIT_SECS not set, using default: {}", default);
+                    default
+                })
+                .parse()
+                .unwrap_or_else(|_| {
+                    eprintln!("ERROR: STARTUP_WAIT_SECS must be a whole number of seconds!");
+                    std::process::exit(1);
+                }),
+            read_only_override: parse_env_override(env::var("READ_ONLY_MODE").ok().as_deref())
+                .unwrap_or_else(|e| {
+                    eprintln!("ERROR: {}", e);
+                    std::process::exit(1);
+                }),
+            mail_api_url: env::var("MAIL_API_URL").ok().filter(|v| !v.trim().is_empty()),
+            // Not logged: it is a credential
+            mail_api_key: env::var("MAIL_API_KEY").ok().filter(|v| !v.trim().is_empty()),
+            mail_from: get_env("MAIL_FROM")
+                .unwrap_or_else(|_| {
+                    let default = "reports@pupinn.local".to_string();
+                    tracing::info!("MAIL_FROM not set, using default: {}", default);
+                    default
+                }),
+            log_message_content: parse_env_override(env::var("LOG_MESSAGE_CONTENT").ok().as_deref())
+                .unwrap_or_else(|e| {
+                    eprintln!("ERROR: LOG_MESSAGE_CONTENT: {}", e);
+                    std::process::exit(1);
+                })
+                .unwrap_or(cfg!(debug_assertions)),
+            stale_sync_interval_secs: get_env("STALE_SYNC_INTERVAL_SECS")
+                .unwrap_or_else(|_| {
+                    let default = DEFAULT_STALE_SYNC_INTERVAL_SECS.to_string();
+                    tracing::info!("STALE_SYNC_INTERVAL_SECS not set, using default: {}", default);
+                    default
+                })
+                .parse()
+                .ok()
+                .filter(|secs| *secs > 0)
+                .unwrap_or_else(|| {
+                    eprintln!("ERROR: STALE_SYNC_INTERVAL_SECS must be a positive whole number of seconds!");
+                    std::process::exit(1);
+                }),
+            session_idle_limits,
+            access_token_lifetime,
+            token_issuer,
+            password_hashing,
+            login_lockout,
+            chat_limits,
+            trust_proxy_headers: parse_env_override(env::var("TRUST_PROXY_HEADERS").ok().as_deref())
+                .unwrap_or_else(|e| {
+                    eprintln!("ERROR: TRUST_PROXY_HEADERS: {}", e);
+                    std::process::exit(1);
+                })
+                .unwrap_or(false),
+            body_limits: BodyLimits {
+                default_bytes: get_env("MAX_BODY_BYTES")
+                    .unwrap_or_else(|_| {
+                        let default = DEFAULT_BODY_LIMIT_BYTES.to_string();
+                        tracing::info!("MAX_BODY_BYTES not set, using default: {}", default);
+                        default
+                    })
+                    .parse()
+                    .unwrap_or_else(|_| {
+                        eprintln!("ERROR: MAX_BODY_BYTES must be a whole number of bytes!");
+                        std::process::exit(1);
+                    }),
+                upload_bytes: get_env("MAX_UPLOAD_BYTES")
+                    .unwrap_or_else(|_| {
+                        let default = MAX_UPLOAD_BYTES.to_string();
+                        tracing::info!("MAX_UPLOAD_BYTES not set, using default: {}", default);
+                        default
+                    })
+                    .parse::<usize>()
+                    .map(|bytes| {
+                        if bytes > MAX_UPLOAD_BYTES {
+                            tracing::warn!(
+                                "MAX_UPLOAD_BYTES {} exceeds the storage limit, using {}",
+                                bytes,
+                                MAX_UPLOAD_BYTES
+                            );
+                        }
+                        bytes.min(MAX_UPLOAD_BYTES)
+                    })
+                    .unwrap_or_else(|_| {
+                        eprintln!("ERROR: MAX_UPLOAD_BYTES must be a whole number of bytes!");
+                        std::process::exit(1);
+                    }),
+            },
         }
     }
-}
 
+    /// Log the effective storage and database settings without credentials
+    pub fn log_summary(&self) {
+        tracing::info!("Database: {}", redact_url(&self.database_url));
+        tracing::info!("MinIO endpoint: {}", redact_url(&self.minio_url));
+        tracing::info!(
+            "MinIO credentials present: {}",
+            !self.minio_root_user.is_empty() && !self.minio_root_password.is_empty()
+        );
+        tracing::info!("Chat message content logging: {}", self.log_message_content);
+    }
+}
@@ -19,10 +19,8 @@ pub type DbConn = PooledConnection<ConnectionManager<PgConnection>>;
 /// * `database_url` - PostgreSQL connection string
 ///
 /// # Returns
-/// A configured connection pool
-///
-/// # Panics
-/// Panics if the pool cannot be created
+/// A configured connection pool. Connections are established lazily, so the
+/// database does not need to be reachable yet (see `startup::probe_database`).
 pub fn create_pool(database_url: &str) -> DbPool {
     let manager = ConnectionManager::<PgConnection>::new(database_url);
 
@@ -31,8 +29,7 @@ pub fn create_pool(database_url: &str) -> DbPool {
         .min_idle(Some(2))
         .connection_timeout(Duration::from_secs(30))
         .idle_timeout(Some(Duration::from_secs(300)))
-        .build(manager)
-        .expect("Failed to create database pool")
+        .build_unchecked(manager)
 }
 
 /// Try to apply DB fixes needed for enum normalization and stale statuses.
@@ -1,9 +1,10 @@
 use axum::{
-    http::StatusCode,
+    http::{header::RETRY_AFTER, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
 use serde::Serialize;
+use std::collections::BTreeMap;
 
 /// Unified error type for the application
 #[derive(Debug, thiserror::Error)]
@@ -11,18 +12,34 @@ pub enum AppError {
     #[error("Validation error: {0}")]
     ValidationError(String),
 
+    /// Validation failures keyed by the offending field
+    #[error("Validation error: {}", format_field_errors(.0))]
+    FieldErrors(BTreeMap<String, String>),
+
     #[error("Bad request: {0}")]
     BadRequest(String),
 
     #[error("Unauthorized: {0}")]
     Unauthorized(String),
 
+    /// The session was idle past its timeout; the client must log in again
+    #[error("Session expired: {0}")]
+    SessionExpired(String),
+
     #[error("Forbidden: {0}")]
     Forbidden(String),
 
+    /// The user must change the password an admin set before doing anything else
+    #[error("Password change required: {0}")]
+    PasswordChangeRequired(String),
+
     #[error("Not found: {0}")]
     NotFound(String),
 
+    /// The guest has no checked-in or overstaying booking
+    #[error("Not in house: {0}")]
+    NotInHouse(String),
+
     #[error("Room unavailable: {0}")]
     RoomUnavailable(String),
 
@@ -35,6 +52,17 @@ pub enum AppError {
     #[error("Conflict: {0}")]
     Conflict(String),
 
+    /// Conflict with a concurrent change; carries the record as it now is
+    #[error("Conflict: {0}")]
+    ConflictWith(String, serde_json::Value),
+
+    #[error("Payload too large: {0}")]
+    PayloadTooLarge(String),
+
+    /// Too many requests; the client may retry after the given delay
+    #[error("Rate limited: {0}")]
+    RateLimited(String, std::time::Duration),
+
     #[error("Database error: {0}")]
     DatabaseError(String),
 
@@ -42,11 +70,25 @@ pub enum AppError {
     InternalError(String),
 }
 
+fn format_field_errors(fields: &BTreeMap<String, String>) -> String {
+    fields
+        .iter()
+        .map(|(field, msg)| format!("{}: {}", field, msg))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
 /// Error response body sent to clients
 #[derive(Debug, Serialize)]
 pub struct ErrorResponse {
     pub code: String,
     pub message: String,
+    /// Per-field messages, only present for field validation errors
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fields: Option<BTreeMap<String, String>>,
+    /// Current state of the record, only present for concurrent edit conflicts
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub current: Option<serde_json::Value>,
 }
 
 impl IntoResponse for AppError {
@@ -55,12 +97,24 @@ impl IntoResponse for AppError {
             AppError::ValidationError(msg) => {
                 (StatusCode::BAD_REQUEST, "VALIDATION_ERROR", msg.clone())
             }
+            AppError::FieldErrors(fields) => (
+                StatusCode::BAD_REQUEST,
+                "VALIDATION_ERROR",
+                format_field_errors(fields),
+            ),
             AppError::BadRequest(msg) => {
                 (StatusCode::BAD_REQUEST, "BAD_REQUEST", msg.clone())
             }
             AppError::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, "UNAUTHORIZED", msg.clone()),
+            AppError::SessionExpired(msg) => {
+                (StatusCode::UNAUTHORIZED, "SESSION_EXPIRED", msg.clone())
+            }
             AppError::Forbidden(msg) => (StatusCode::FORBIDDEN, "FORBIDDEN", msg.clone()),
+            AppError::PasswordChangeRequired(msg) => {
+                (StatusCode::FORBIDDEN, "PASSWORD_CHANGE_REQUIRED", msg.clone())
+            }
             AppError::NotFound(msg) => (StatusCode::NOT_FOUND, "NOT_FOUND", msg.clone()),
+            AppError::NotInHouse(msg) => (StatusCode::NOT_FOUND, "NOT_IN_HOUSE", msg.clone()),
             AppError::RoomUnavailable(msg) => {
                 (StatusCode::CONFLICT, "ROOM_UNAVAILABLE", msg.clone())
             }
@@ -70,7 +124,15 @@ impl IntoResponse for AppError {
                 "INVALID_STATUS_TRANSITION",
                 msg.clone(),
             ),
-            AppError::Conflict(msg) => (StatusCode::CONFLICT, "CONFLICT", msg.clone()),
+            AppError::Conflict(msg) | AppError::ConflictWith(msg, _) => {
+                (StatusCode::CONFLICT, "CONFLICT", msg.clone())
+            }
+            AppError::PayloadTooLarge(msg) => {
+                (StatusCode::PAYLOAD_TOO_LARGE, "PAYLOAD_TOO_LARGE", msg.clone())
+            }
+            AppError::RateLimited(msg, _) => {
+                (StatusCode::TOO_MANY_REQUESTS, "RATE_LIMITED", msg.clone())
+            }
             AppError::DatabaseError(msg) => {
                 tracing::error!("Database error: {}", msg);
                 (
@@ -89,12 +151,28 @@ impl IntoResponse for AppError {
             }
         };
 
+        let retry_after = match &self {
+            AppError::RateLimited(_, retry_after) => Some(retry_after.as_secs().max(1)),
+            _ => None,
+        };
+
+        let (fields, current) = match self {
+            AppError::FieldErrors(fields) => (Some(fields), None),
+            AppError::ConflictWith(_, current) => (None, Some(current)),
+            _ => (None, None),
+        };
+
         let body = Json(ErrorResponse {
             code: code.to_string(),
             message,
+            fields,
+            current,
         });
 
-        (status, body).into_response()
+        match retry_after {
+            Some(secs) => (status, [(RETRY_AFTER, secs.to_string())], body).into_response(),
+            None => (status, body).into_response(),
+        }
     }
 }
 
@@ -114,7 +192,7 @@ impl From<diesel::result::Error> for AppError {
                 if let Some(rest) = msg.strip_prefix("Validation error: ") {
                     return AppError::ValidationError(rest.to_string());
                 }
-                if let Some(rest) = msg.strip_prefix("Invalid status-+ transition: ") {
+                if let Some(rest) = msg.strip_prefix("Invalid status transition: ") {
                     return AppError::InvalidStatusTransition(rest.to_string());
                 }
                 if let Some(rest) = msg.strip_prefix("Room unavailable: ") {
@@ -3,7 +3,10 @@ pub mod config;
 pub mod db;
 pub mod errors;
 pub mod models;
+pub mod scheduler;
 pub mod schema;
 pub mod services;
+pub mod settings;
+pub mod startup;
 pub mod utils;
 
@@ -1,12 +1,3 @@
-mod api;
-mod config;
-mod db;
-mod errors;
-mod models;
-mod schema;
-mod services;
-mod utils;
-
 use std::net::SocketAddr;
 
 use axum::http::{header, Method};
@@ -15,9 +6,18 @@ use tower_http::cors::CorsLayer;
 use tower_http::trace::TraceLayer;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
-use crate::api::{create_router, AppState};
-use crate::config::Config;
-use crate::db::create_pool;
+use hotel_management_backend::api::{self, create_router, AppState};
+use hotel_management_backend::config::Config;
+use hotel_management_backend::db::{self, create_pool};
+use hotel_management_backend::scheduler;
+use hotel_management_backend::services::mailer::Mailer;
+use hotel_management_backend::services::{
+    storage_service, AuthService, MaintenanceService, ReadOnlyMode, RevocationCache, SessionTracker,
+};
+use hotel_management_backend::services::rate_limit_service::InMemoryLoginAttempts;
+use hotel_management_backend::services::room_photo_service::ROOM_PHOTOS_BUCKET;
+use hotel_management_backend::startup::{self, Backoff};
+use hotel_management_backend::utils::redact;
 
 #[tokio::main]
 async fn main() {
@@ -63,16 +63,23 @@ async fn main() {
     // Create database pool
     let pool = create_pool(&config.database_url);
     tracing::info!("Database connection pool created");
-    // Attempt to apply DB fixes for enum normalization / stale statuses
-    crate::db::apply_stale_statuses_fix(&pool);
 
-    tracing::info!("Final MinIO Config Check:");
-    tracing::info!("  MINIO_URL: {}", config.minio_url);
-    tracing::info!("  MINIO_ROOT_USER: {}", config.minio_root_user);
-    // Do not log password for security, but log its length/presence
-    tracing::info!("  MINIO_ROOT_PASSWORD: [SET, length={}]", config.minio_root_password.len());
-    
-    tracing::info!("Initializing S3 client for MinIO at {}", config.minio_url);
+    config.log_summary();
+    redact::set_log_message_content(config.log_message_content);
+
+    // Time one hash at the configured cost, for sizing it to the machine
+    AuthService::configure_password_hashing(config.password_hashing);
+    let hash_started = std::time::Instant::now();
+    match AuthService::hash_password("password-hashing-benchmark") {
+        Ok(_) => tracing::info!(
+            "Password hashing {:?} takes {} ms per hash",
+            config.password_hashing,
+            hash_started.elapsed().as_millis()
+        ),
+        Err(e) => tracing::warn!("Password hashing check failed: {}", e),
+    }
+
+    tracing::info!("Initializing S3 client for MinIO");
     
     let s3_config = aws_sdk_s3::config::Builder::new()
         .endpoint_url(&config.minio_url)
@@ -91,12 +98,73 @@ async fn main() {
     let s3_client = aws_sdk_s3::Client::from_conf(s3_config);
     tracing::info!("S3 client initialized successfully");
 
+    // Wait for Postgres and MinIO before touching them
+    let startup_deadline = std::time::Duration::from_secs(config.startup_wait_secs);
+    tracing::info!("Waiting up to {}s for dependencies to become ready", config.startup_wait_secs);
+
+    let db_probe = startup::wait_for("Postgres", startup_deadline, Backoff::default(), || {
+        startup::probe_database(config.database_url.clone())
+    });
+    let s3_probe = startup::wait_for("MinIO", startup_deadline, Backoff::default(), || {
+        startup::probe_s3(s3_client.clone())
+    });
+    let (db_ready, s3_ready) = tokio::join!(db_probe, s3_probe);
+    for result in [db_ready, s3_ready] {
+        if let Err(e) = result {
+            eprintln!("ERROR: {}", e);
+            std::process::exit(1);
+        }
+    }
+
+    for bucket in ["chat-images", ROOM_PHOTOS_BUCKET] {
+        if let Err(e) = storage_service::ensure_bucket(&s3_client, bucket).await {
+            tracing::warn!("Could not bootstrap '{}' bucket: {}", bucket, e);
+        }
+    }
+
+    // Attempt to apply DB fixes for enum normalization / stale statuses
+    db::apply_stale_statuses_fix(&pool);
+
+    // Load read-only mode (env override wins over the admin setting)
+    let read_only = std::sync::Arc::new(ReadOnlyMode::new(config.read_only_override));
+    match MaintenanceService::new(pool.clone()).load_read_only_setting() {
+        Ok(enabled) => read_only.set_setting(enabled),
+        Err(e) => tracing::warn!("Could not load read-only setting: {}", e),
+    }
+    if read_only.is_enabled() {
+        tracing::warn!("Starting in read-only mode: mutating requests will be refused");
+    }
+
+    // Background jobs (stale booking sweep, report emails, read-only refresh)
+    let jobs = std::sync::Arc::new(scheduler::JobBoard::default());
+    let mailer = Mailer::from_config(config.mail_api_url.clone(), config.mail_api_key.clone(), config.mail_from.clone());
+    if matches!(mailer, Mailer::Log) {
+        tracing::info!("MAIL_API_URL not set: report emails will be logged, not sent");
+    }
+    scheduler::spawn(pool.clone(), read_only.clone(), jobs.clone(), mailer);
+    scheduler::spawn_stale_sync(
+        pool.clone(),
+        read_only.clone(),
+        jobs.clone(),
+        std::time::Duration::from_secs(config.stale_sync_interval_secs),
+    );
+
     // Create application state
     let state = AppState {
         pool,
         jwt_secret: config.jwt_secret,
-        chat_state: std::sync::Arc::new(crate::api::chat::ChatState::default()),
+        chat_state: std::sync::Arc::new(api::chat::ChatState::new(config.chat_limits)),
         s3_client,
+        read_only,
+        public_lookup: std::sync::Arc::new(api::public_bookings::PublicLookupLimits::default()),
+        jobs,
+        body_limits: config.body_limits,
+        sessions: std::sync::Arc::new(SessionTracker::new(config.session_idle_limits)),
+        access_token_lifetime: config.access_token_lifetime,
+        login_attempts: std::sync::Arc::new(InMemoryLoginAttempts::new(config.login_lockout)),
+        revocations: std::sync::Arc::new(RevocationCache::default()),
+        trust_proxy_headers: config.trust_proxy_headers,
+        token_issuer: config.token_issuer,
     };
 
     // Configure CORS
@@ -150,7 +218,7 @@ async fn main() {
     tracing::info!("Server listening on {}, waiting for connections...", addr);
     let _ = std::io::stdout().flush();
 
-    if let Err(e) = axum::serve(listener, app)
+    if let Err(e) = axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
         .with_graceful_shutdown(shutdown_signal())
         .await
     {
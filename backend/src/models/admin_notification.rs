@@ -0,0 +1,31 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::schema::admin_notifications;
+
+/// Alert shown to every admin until one of them marks it read
+#[derive(Debug, Clone, Queryable, Identifiable, Selectable, Serialize)]
+#[diesel(table_name = admin_notifications)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct AdminNotification {
+    pub id: Uuid,
+    pub kind: String,
+    pub message: String,
+    pub entity_type: Option<String>,
+    pub entity_id: Option<String>,
+    pub read_at: Option<DateTime<Utc>>,
+    pub read_by_user_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// New admin notification for insertion
+#[derive(Debug, Insertable)]
+#[diesel(table_name = admin_notifications)]
+pub struct NewAdminNotification<'a> {
+    pub kind: &'a str,
+    pub message: &'a str,
+    pub entity_type: Option<&'a str>,
+    pub entity_id: Option<&'a str>,
+}
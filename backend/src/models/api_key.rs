@@ -0,0 +1,40 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::models::UserRole;
+use crate::schema::api_keys;
+
+/// Key for a machine-to-machine integration. The key itself is only shown
+/// once, when it is created.
+#[derive(Debug, Clone, Queryable, Identifiable, Selectable, Serialize)]
+#[diesel(table_name = api_keys)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct ApiKey {
+    pub id: Uuid,
+    pub label: String,
+    /// Hex SHA-256 of the key
+    #[serde(skip_serializing)]
+    pub key_hash: String,
+    /// Start of the key, to tell keys apart
+    pub key_prefix: String,
+    pub role: UserRole,
+    /// Admin on whose behalf the key acts
+    pub created_by: Uuid,
+    pub created_at: DateTime<Utc>,
+    /// Updated at most once a minute
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+/// New API key for insertion
+#[derive(Debug, Insertable)]
+#[diesel(table_name = api_keys)]
+pub struct NewApiKey<'a> {
+    pub label: &'a str,
+    pub key_hash: String,
+    pub key_prefix: String,
+    pub role: UserRole,
+    pub created_by: Uuid,
+}
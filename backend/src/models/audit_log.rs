@@ -0,0 +1,31 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::schema::audit_logs;
+
+/// Audit log entry for a sensitive admin action
+#[derive(Debug, Clone, Queryable, Identifiable, Selectable, Serialize)]
+#[diesel(table_name = audit_logs)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct AuditLog {
+    pub id: Uuid,
+    pub actor_user_id: Option<Uuid>,
+    pub action: String,
+    pub entity_type: String,
+    pub entity_id: Option<String>,
+    pub details: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// New audit log entry for insertion
+#[derive(Debug, Insertable)]
+#[diesel(table_name = audit_logs)]
+pub struct NewAuditLog<'a> {
+    pub actor_user_id: Option<Uuid>,
+    pub action: &'a str,
+    pub entity_type: &'a str,
+    pub entity_id: Option<&'a str>,
+    pub details: Option<&'a str>,
+}
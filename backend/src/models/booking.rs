@@ -1,13 +1,27 @@
-use chrono::{DateTime, NaiveDate, Utc};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use chrono_tz::Tz;
 use diesel::prelude::*;
 use diesel_derive_enum::DbEnum;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Serialize, Serializer};
 use uuid::Uuid;
 
-use bigdecimal::BigDecimal;
+use bigdecimal::{BigDecimal, Zero};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::errors::AppError;
 use crate::schema::bookings;
+use crate::utils::clock;
+
+use super::{BookingNote, Room};
 
-use super::Room;
+/// Values of `Booking::creation_source`: made by staff or through the guest portal
+pub const CREATION_SOURCES: [&str; 2] = ["staff", "guest"];
+
+/// Check a creation source filter, listing the valid values when it is unknown
+pub fn parse_creation_source(value: &str) -> Result<&'static str, AppError> {
+    super::parse_wire_value(value.trim(), &CREATION_SOURCES, "creation source")
+}
 
 /// Booking status enum matching PostgreSQL booking_status type
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, DbEnum)]
@@ -20,6 +34,7 @@ pub enum BookingStatus {
     CheckedOut,
     Cancelled,
     Overstay,
+    NoShow,
 }
 
 /// Booking model representing a guest reservation
@@ -43,10 +58,27 @@ pub struct Booking {
     pub creation_source: String,
     /// Booking price/revenue
     pub price: BigDecimal,
+    /// Shared by bookings created together as a group
+    pub group_reference: Option<String>,
+    /// When the guest checked in; set by check-in only, never by an update
+    pub checked_in_at: Option<DateTime<Utc>>,
+    /// When the guest checked out; set by check-out only, never by an update
+    pub checked_out_at: Option<DateTime<Utc>>,
+}
+
+impl Booking {
+    /// Date the nights stayed run to. Once the guest has left this is the
+    /// hotel date of `checked_out_at`, so an overstay is billed to the day
+    /// the guest actually left; before that it is the check-out date.
+    pub fn stay_end_date(&self, tz: Tz) -> NaiveDate {
+        self.checked_out_at.map_or(self.check_out_date, |left| {
+            clock::local_date(left, tz).max(self.check_in_date + Duration::days(1))
+        })
+    }
 }
 
 /// New booking for insertion
-#[derive(Debug, Insertable)]
+#[derive(Debug, Clone, Insertable)]
 #[diesel(table_name = bookings)]
 pub struct NewBooking<'a> {
     pub reference: &'a str,
@@ -57,6 +89,7 @@ pub struct NewBooking<'a> {
     pub created_by_user_id: Option<Uuid>,
     pub creation_source: &'a str,
     pub price: BigDecimal,
+    pub group_reference: Option<&'a str>,
 }
 
 /// Booking update changeset
@@ -70,12 +103,66 @@ pub struct UpdateBooking {
     pub price: Option<BigDecimal>,
 }
 
-/// Booking with room details for API responses
-#[derive(Debug, Clone, Serialize)]
+/// Booking with room details for API responses. Serialized with the derived
+/// `nights` and `total_price`, so every screen shows the same numbers.
+#[derive(Debug, Clone)]
 pub struct BookingWithRoom {
-    #[serde(flatten)]
     pub booking: Booking,
     pub room: Option<Room>,
+    /// Number of recorded edits, so heavily modified bookings stand out
+    pub modification_count: i64,
+    /// Staff notes, only loaded for the single-booking detail view
+    pub notes: Option<Vec<BookingNote>>,
+    /// Username of the staff member who took the booking; None for guest
+    /// portal bookings and in guest-facing views
+    pub created_by: Option<String>,
+}
+
+impl BookingWithRoom {
+    /// Nights between check-in and check-out
+    pub fn nights(&self) -> i64 {
+        (self.booking.check_out_date - self.booking.check_in_date).num_days()
+    }
+
+    /// Stored booking price, or the room's nightly price times the nights for
+    /// bookings stored before prices were recorded (price 0). None when
+    /// neither is known.
+    pub fn total_price(&self) -> Option<BigDecimal> {
+        if self.booking.price > BigDecimal::zero() {
+            return Some(self.booking.price.clone());
+        }
+        self.room
+            .as_ref()
+            .map(|room| &room.price * BigDecimal::from(self.nights()))
+    }
+}
+
+impl Serialize for BookingWithRoom {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(Serialize)]
+        struct Wire<'a> {
+            #[serde(flatten)]
+            booking: &'a Booking,
+            room: &'a Option<Room>,
+            modification_count: i64,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            notes: &'a Option<Vec<BookingNote>>,
+            created_by: &'a Option<String>,
+            nights: i64,
+            total_price: Option<String>,
+        }
+
+        Wire {
+            booking: &self.booking,
+            room: &self.room,
+            modification_count: self.modification_count,
+            notes: &self.notes,
+            created_by: &self.created_by,
+            nights: self.nights(),
+            total_price: self.total_price().map(|price| price.to_string()),
+        }
+        .serialize(serializer)
+    }
 }
 
 /// Booking with room and payment summary for API responses
@@ -89,6 +176,28 @@ pub struct BookingWithPayments {
 }
 
 impl BookingStatus {
+    /// All variants, in lifecycle order
+    pub const ALL: [BookingStatus; 6] = [
+        BookingStatus::Upcoming,
+        BookingStatus::CheckedIn,
+        BookingStatus::CheckedOut,
+        BookingStatus::Cancelled,
+        BookingStatus::Overstay,
+        BookingStatus::NoShow,
+    ];
+
+    /// Canonical snake_case value, identical to the serde and database representation
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BookingStatus::Upcoming => "upcoming",
+            BookingStatus::CheckedIn => "checked_in",
+            BookingStatus::CheckedOut => "checked_out",
+            BookingStatus::Cancelled => "cancelled",
+            BookingStatus::Overstay => "overstay",
+            BookingStatus::NoShow => "no_show",
+        }
+    }
+
     /// Check if transition to new status is valid
     pub fn can_transition_to(&self, new_status: BookingStatus) -> bool {
         match (self, new_status) {
@@ -100,6 +209,9 @@ impl BookingStatus {
             (BookingStatus::CheckedIn, BookingStatus::Overstay) => true, // Automatic via handle_stale_bookings
             // Overstay can be checked out
             (BookingStatus::Overstay, BookingStatus::CheckedOut) => true,
+            (BookingStatus::Upcoming, BookingStatus::NoShow) => true, // Automatic via handle_stale_bookings
+            // Late arrival: a no-show guest can still be checked in
+            (BookingStatus::NoShow, BookingStatus::CheckedIn) => true,
             // CheckedOut and Cancelled are terminal states
             (BookingStatus::CheckedOut, _) => false,
             (BookingStatus::Cancelled, _) => false,
@@ -133,3 +245,17 @@ impl BookingStatus {
         )
     }
 }
+
+impl fmt::Display for BookingStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for BookingStatus {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        super::parse_wire_value(s, &Self::ALL, "booking status")
+    }
+}
@@ -0,0 +1,59 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel_derive_enum::DbEnum;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::schema::booking_emails;
+
+use super::Booking;
+
+/// Which booking email was sent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, DbEnum)]
+#[ExistingTypePath = "crate::schema::sql_types::BookingEmailKind"]
+#[serde(rename_all = "snake_case")]
+#[DbValueStyle = "snake_case"]
+pub enum BookingEmailKind {
+    /// Sent after a guest books online
+    Confirmation,
+    /// Sent after a guest cancels online
+    Cancellation,
+}
+
+/// Delivery state of one booking email
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, DbEnum)]
+#[ExistingTypePath = "crate::schema::sql_types::BookingEmailStatus"]
+#[serde(rename_all = "snake_case")]
+#[DbValueStyle = "snake_case"]
+pub enum BookingEmailStatus {
+    /// Queued, the send has not finished yet
+    Pending,
+    Sent,
+    Failed,
+}
+
+/// Log entry for one email sent (or attempted) to a guest about a booking
+#[derive(Debug, Clone, Queryable, Identifiable, Associations, Selectable, Serialize)]
+#[diesel(table_name = booking_emails)]
+#[diesel(belongs_to(Booking, foreign_key = booking_id))]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct BookingEmail {
+    pub id: Uuid,
+    pub booking_id: Uuid,
+    pub kind: BookingEmailKind,
+    pub recipient_email: String,
+    pub status: BookingEmailStatus,
+    pub last_error: Option<String>,
+    pub sent_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// New booking email for insertion
+#[derive(Debug, Insertable)]
+#[diesel(table_name = booking_emails)]
+pub struct NewBookingEmail<'a> {
+    pub booking_id: Uuid,
+    pub kind: BookingEmailKind,
+    pub recipient_email: &'a str,
+}
@@ -0,0 +1,112 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::schema::booking_events;
+
+use super::{Booking, BookingStatus};
+
+/// Event type for a booking status transition
+pub const EVENT_STATUS_CHANGE: &str = "status_change";
+/// Event type for a change to the stay dates
+pub const EVENT_DATE_CHANGE: &str = "date_change";
+/// Event type for moving a booking to another room
+pub const EVENT_ROOM_MOVE: &str = "room_move";
+/// Event type for an admin booking over a conflicting stay
+pub const EVENT_CONFLICT_OVERRIDE: &str = "conflict_override";
+/// Details of a transition made by a background job rather than a user
+pub const SYSTEM_ACTOR_NOTE: &str = "system";
+
+/// Recorded change to a booking
+#[derive(Debug, Clone, Queryable, Identifiable, Associations, Selectable, Serialize)]
+#[diesel(table_name = booking_events)]
+#[diesel(belongs_to(Booking, foreign_key = booking_id))]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct BookingEvent {
+    pub id: Uuid,
+    pub booking_id: Uuid,
+    /// "status_change", "date_change", "room_move" or "conflict_override"
+    pub event_type: String,
+    pub from_status: Option<BookingStatus>,
+    pub to_status: Option<BookingStatus>,
+    /// None for system jobs and unauthenticated staff routes
+    pub actor_user_id: Option<Uuid>,
+    pub details: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// New booking event for insertion
+#[derive(Debug, Insertable)]
+#[diesel(table_name = booking_events)]
+pub struct NewBookingEvent {
+    pub booking_id: Uuid,
+    pub event_type: &'static str,
+    pub from_status: Option<BookingStatus>,
+    pub to_status: Option<BookingStatus>,
+    pub actor_user_id: Option<Uuid>,
+    pub details: Option<String>,
+}
+
+impl NewBookingEvent {
+    /// Status transition event
+    pub fn status_change(
+        booking_id: Uuid,
+        from: BookingStatus,
+        to: BookingStatus,
+        actor_user_id: Option<Uuid>,
+    ) -> Self {
+        Self {
+            booking_id,
+            event_type: EVENT_STATUS_CHANGE,
+            from_status: Some(from),
+            to_status: Some(to),
+            actor_user_id,
+            details: None,
+        }
+    }
+
+    /// Status transition made by a background job: no actor, noted as "system"
+    pub fn system_status_change(booking_id: Uuid, from: BookingStatus, to: BookingStatus) -> Self {
+        Self {
+            details: Some(SYSTEM_ACTOR_NOTE.to_string()),
+            ..Self::status_change(booking_id, from, to, None)
+        }
+    }
+
+    /// Date change event; `details` describes the old and new dates
+    pub fn date_change(booking_id: Uuid, details: String, actor_user_id: Option<Uuid>) -> Self {
+        Self {
+            booking_id,
+            event_type: EVENT_DATE_CHANGE,
+            from_status: None,
+            to_status: None,
+            actor_user_id,
+            details: Some(details),
+        }
+    }
+
+    /// Room move event; `details` names the old and new rooms
+    pub fn room_move(booking_id: Uuid, details: String, actor_user_id: Option<Uuid>) -> Self {
+        Self {
+            booking_id,
+            event_type: EVENT_ROOM_MOVE,
+            from_status: None,
+            to_status: None,
+            actor_user_id,
+            details: Some(details),
+        }
+    }
+
+    /// Conflict override event; `details` warns which stays were overlapped
+    pub fn conflict_override(booking_id: Uuid, details: String, actor_user_id: Option<Uuid>) -> Self {
+        Self {
+            booking_id,
+            event_type: EVENT_CONFLICT_OVERRIDE,
+            from_status: None,
+            to_status: None,
+            actor_user_id,
+            details: Some(details),
+        }
+    }
+}
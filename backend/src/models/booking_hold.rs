@@ -0,0 +1,31 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use diesel::prelude::*;
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::schema::booking_holds;
+
+/// Room held for a guest for a few minutes while they complete payment
+#[derive(Debug, Clone, PartialEq, Queryable, Identifiable, Selectable, Serialize)]
+#[diesel(table_name = booking_holds)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct BookingHold {
+    pub id: Uuid,
+    pub room_id: Uuid,
+    pub user_id: Uuid,
+    pub check_in_date: NaiveDate,
+    pub check_out_date: NaiveDate,
+    pub expires_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// New booking hold for insertion
+#[derive(Debug, Insertable)]
+#[diesel(table_name = booking_holds)]
+pub struct NewBookingHold {
+    pub room_id: Uuid,
+    pub user_id: Uuid,
+    pub check_in_date: NaiveDate,
+    pub check_out_date: NaiveDate,
+    pub expires_at: DateTime<Utc>,
+}
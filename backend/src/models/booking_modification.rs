@@ -0,0 +1,87 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Serialize, Serializer};
+use uuid::Uuid;
+
+use crate::schema::booking_modifications;
+
+use super::Booking;
+
+/// Source for edits made through PATCH /bookings/:id
+pub const SOURCE_STAFF_UPDATE: &str = "staff_update";
+/// Source for moves through POST /bookings/:id/move-room
+pub const SOURCE_ROOM_MOVE: &str = "room_move";
+
+/// Recorded edit to a booking with the fields it changed
+#[derive(Debug, Clone, Queryable, Identifiable, Associations, Selectable, Serialize)]
+#[diesel(table_name = booking_modifications)]
+#[diesel(belongs_to(Booking, foreign_key = booking_id))]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct BookingModification {
+    pub id: Uuid,
+    pub booking_id: Uuid,
+    /// None for system jobs and unauthenticated staff routes
+    pub actor_user_id: Option<Uuid>,
+    /// What made the change, e.g. "staff_update"
+    pub source: String,
+    /// Field name -> before/after, stored as JSON text and returned as an object
+    #[serde(serialize_with = "serialize_changes")]
+    pub changes: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// New booking modification for insertion
+#[derive(Debug, Insertable)]
+#[diesel(table_name = booking_modifications)]
+pub struct NewBookingModification<'a> {
+    pub booking_id: Uuid,
+    pub actor_user_id: Option<Uuid>,
+    pub source: &'a str,
+    pub changes: String,
+}
+
+/// Old and new value of one booking field
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldChange {
+    pub before: serde_json::Value,
+    pub after: serde_json::Value,
+}
+
+/// Changed fields keyed by name; empty when nothing changed
+pub type BookingChanges = BTreeMap<&'static str, FieldChange>;
+
+fn serialize_changes<S: Serializer>(changes: &str, serializer: S) -> Result<S::Ok, S::Error> {
+    match serde_json::from_str::<serde_json::Value>(changes) {
+        Ok(value) => value.serialize(serializer),
+        Err(_) => serializer.serialize_str(changes),
+    }
+}
+
+/// Compare the editable fields of a booking before and after a change
+pub fn diff_bookings(before: &Booking, after: &Booking) -> BookingChanges {
+    let mut changes = BookingChanges::new();
+
+    let mut push = |field: &'static str, old: serde_json::Value, new: serde_json::Value| {
+        if old != new {
+            changes.insert(field, FieldChange { before: old, after: new });
+        }
+    };
+
+    push("guest_name", before.guest_name.clone().into(), after.guest_name.clone().into());
+    push("room_id", before.room_id.to_string().into(), after.room_id.to_string().into());
+    push(
+        "check_in_date",
+        before.check_in_date.to_string().into(),
+        after.check_in_date.to_string().into(),
+    );
+    push(
+        "check_out_date",
+        before.check_out_date.to_string().into(),
+        after.check_out_date.to_string().into(),
+    );
+    push("price", before.price.to_string().into(), after.price.to_string().into());
+
+    changes
+}
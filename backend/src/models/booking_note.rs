@@ -0,0 +1,31 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::schema::booking_notes;
+
+use super::Booking;
+
+/// Staff note attached to a single booking
+#[derive(Debug, Clone, PartialEq, Queryable, Identifiable, Associations, Selectable, Serialize)]
+#[diesel(table_name = booking_notes)]
+#[diesel(belongs_to(Booking, foreign_key = booking_id))]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct BookingNote {
+    pub id: Uuid,
+    pub booking_id: Uuid,
+    pub author_user_id: Uuid,
+    pub note: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// New booking note for insertion
+#[derive(Debug, Insertable)]
+#[diesel(table_name = booking_notes)]
+pub struct NewBookingNote<'a> {
+    pub booking_id: Uuid,
+    pub author_user_id: Uuid,
+    pub note: &'a str,
+}
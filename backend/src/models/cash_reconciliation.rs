@@ -0,0 +1,94 @@
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, NaiveDate, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::schema::cash_reconciliations;
+
+/// Front desk shift in hotel-local time
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Shift {
+    /// 06:00 - 14:00
+    Morning,
+    /// 14:00 - 22:00
+    Afternoon,
+    /// 22:00 - 06:00 the next day
+    Night,
+}
+
+impl Shift {
+    /// All variants
+    pub const ALL: [Shift; 3] = [Shift::Morning, Shift::Afternoon, Shift::Night];
+
+    /// Canonical snake_case value, identical to the serde and database representation
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Shift::Morning => "morning",
+            Shift::Afternoon => "afternoon",
+            Shift::Night => "night",
+        }
+    }
+
+    /// Local hour the shift starts on its business date
+    pub fn start_hour(&self) -> u32 {
+        match self {
+            Shift::Morning => 6,
+            Shift::Afternoon => 14,
+            Shift::Night => 22,
+        }
+    }
+
+    /// Length of the shift in hours
+    pub fn hours(&self) -> i64 {
+        8
+    }
+}
+
+impl std::fmt::Display for Shift {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for Shift {
+    type Err = crate::errors::AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        super::parse_wire_value(s, &Self::ALL, "shift")
+    }
+}
+
+/// Recorded cash drawer count (immutable once written)
+#[derive(Debug, Clone, Queryable, Identifiable, Selectable, Serialize)]
+#[diesel(table_name = cash_reconciliations)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct CashReconciliation {
+    pub id: Uuid,
+    pub business_date: NaiveDate,
+    pub shift: Option<String>,
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    pub expected_cash: BigDecimal,
+    pub counted_cash: BigDecimal,
+    pub discrepancy: BigDecimal,
+    pub note: Option<String>,
+    pub recorded_by_user_id: Uuid,
+    pub created_at: DateTime<Utc>,
+}
+
+/// New cash reconciliation for insertion
+#[derive(Debug, Insertable)]
+#[diesel(table_name = cash_reconciliations)]
+pub struct NewCashReconciliation<'a> {
+    pub business_date: NaiveDate,
+    pub shift: Option<&'a str>,
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    pub expected_cash: BigDecimal,
+    pub counted_cash: BigDecimal,
+    pub discrepancy: BigDecimal,
+    pub note: Option<&'a str>,
+    pub recorded_by_user_id: Uuid,
+}
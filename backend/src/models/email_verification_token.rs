@@ -0,0 +1,31 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use crate::schema::email_verification_tokens;
+
+/// Stored email verification token; the token itself is only in the emailed link
+#[derive(Debug, Clone, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = email_verification_tokens)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct EmailVerificationToken {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    /// Hex SHA-256 of the token
+    pub token_hash: String,
+    pub expires_at: DateTime<Utc>,
+    /// Set once the token is used or replaced
+    pub used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// New email verification token for insertion
+#[derive(Debug, Insertable)]
+#[diesel(table_name = email_verification_tokens)]
+pub struct NewEmailVerificationToken {
+    pub user_id: Uuid,
+    pub token_hash: String,
+    pub expires_at: DateTime<Utc>,
+    /// When it was sent; resending is limited from here
+    pub created_at: DateTime<Utc>,
+}
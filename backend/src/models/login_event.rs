@@ -0,0 +1,29 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::schema::login_events;
+
+/// Login attempt against an account
+#[derive(Debug, Clone, Queryable, Identifiable, Selectable, Serialize)]
+#[diesel(table_name = login_events)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct LoginEvent {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub success: bool,
+    pub ip: String,
+    pub user_agent: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// New login event for insertion
+#[derive(Debug, Insertable)]
+#[diesel(table_name = login_events)]
+pub struct NewLoginEvent<'a> {
+    pub user_id: Uuid,
+    pub success: bool,
+    pub ip: &'a str,
+    pub user_agent: Option<&'a str>,
+}
@@ -0,0 +1,77 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel_derive_enum::DbEnum;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::schema::maintenance_tickets;
+
+/// How badly a maintenance problem affects the room
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, DbEnum)]
+#[ExistingTypePath = "crate::schema::sql_types::TicketSeverity"]
+#[serde(rename_all = "snake_case")]
+#[DbValueStyle = "snake_case"]
+pub enum TicketSeverity {
+    Low,
+    #[default]
+    Medium,
+    High,
+    Critical,
+}
+
+/// Progress of a maintenance ticket
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, DbEnum)]
+#[ExistingTypePath = "crate::schema::sql_types::TicketStatus"]
+#[serde(rename_all = "snake_case")]
+#[DbValueStyle = "snake_case"]
+pub enum TicketStatus {
+    Open,
+    InProgress,
+    Resolved,
+}
+
+impl TicketStatus {
+    /// Statuses of tickets still keeping a room under maintenance
+    pub const UNRESOLVED: [TicketStatus; 2] = [TicketStatus::Open, TicketStatus::InProgress];
+}
+
+/// Problem reported against a room
+#[derive(Debug, Clone, Queryable, Identifiable, Selectable, Serialize)]
+#[diesel(table_name = maintenance_tickets)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct MaintenanceTicket {
+    pub id: Uuid,
+    pub room_id: Uuid,
+    pub title: String,
+    pub description: Option<String>,
+    pub severity: TicketSeverity,
+    pub status: TicketStatus,
+    /// None once the reporter's account is deleted
+    pub reported_by: Option<Uuid>,
+    /// Set while the ticket is resolved
+    pub resolved_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// New maintenance ticket for insertion
+#[derive(Debug, Insertable)]
+#[diesel(table_name = maintenance_tickets)]
+pub struct NewMaintenanceTicket {
+    pub room_id: Uuid,
+    pub title: String,
+    pub description: Option<String>,
+    pub severity: TicketSeverity,
+    pub reported_by: Option<Uuid>,
+}
+
+/// Maintenance ticket changeset
+#[derive(Debug, AsChangeset, Default)]
+#[diesel(table_name = maintenance_tickets)]
+pub struct UpdateMaintenanceTicket {
+    pub title: Option<String>,
+    pub description: Option<Option<String>>,
+    pub severity: Option<TicketSeverity>,
+    pub status: Option<TicketStatus>,
+    pub resolved_at: Option<Option<DateTime<Utc>>>,
+}
@@ -4,6 +4,9 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use crate::schema::messages;
 
+/// Content left in place of a message the guest deleted
+pub const DELETED_MESSAGE_CONTENT: &str = "[message deleted]";
+
 #[derive(Debug, Queryable, Selectable, Serialize, Deserialize, Clone)]
 #[diesel(table_name = messages)]
 pub struct Message {
@@ -15,6 +18,10 @@ pub struct Message {
     pub is_read: bool,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// Last time the sender changed the content
+    pub edited_at: Option<DateTime<Utc>>,
+    /// The sender deleted it; content and image are gone
+    pub deleted_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Insertable, Deserialize)]
@@ -1,17 +1,87 @@
+pub mod admin_notification;
+pub mod api_key;
+pub mod audit_log;
 pub mod booking;
+pub mod booking_email;
+pub mod booking_event;
+pub mod booking_hold;
+pub mod booking_modification;
+pub mod booking_note;
+pub mod cash_reconciliation;
+pub mod email_verification_token;
 pub mod guest_note;
+pub mod password_reset_token;
 pub mod payment;
+pub mod pricing_rule;
+pub mod refresh_token;
+pub mod report_subscription;
+pub mod revoked_token;
 pub mod room;
+pub mod room_block;
+pub mod room_photo;
+pub mod room_price_change;
+pub mod room_status_event;
+pub mod room_type;
 pub mod user;
 pub mod inventory;
+pub mod login_event;
+pub mod maintenance_ticket;
 pub mod message;
+pub mod no_show_charge;
 pub mod setting;
 
+pub use admin_notification::*;
+pub use api_key::*;
+pub use audit_log::*;
 pub use booking::*;
+pub use booking_email::*;
+pub use booking_event::*;
+pub use booking_hold::*;
+pub use booking_modification::*;
+pub use booking_note::*;
+pub use cash_reconciliation::*;
+pub use email_verification_token::*;
 pub use guest_note::*;
+pub use password_reset_token::*;
 pub use payment::*;
+pub use pricing_rule::*;
+pub use refresh_token::*;
+pub use report_subscription::*;
+pub use revoked_token::*;
 pub use room::*;
+pub use room_block::*;
+pub use room_photo::*;
+pub use room_price_change::*;
+pub use room_status_event::*;
+pub use room_type::*;
 pub use user::*;
 pub use inventory::*;
+pub use login_event::*;
+pub use maintenance_ticket::*;
+pub use no_show_charge::*;
 pub use setting::*;
 
+
+use crate::errors::AppError;
+
+/// Parse a snake_case wire value into one of `variants`, listing the valid
+/// values in the error so typos are rejected instead of silently ignored
+pub(crate) fn parse_wire_value<T: Copy + std::fmt::Display>(
+    value: &str,
+    variants: &[T],
+    kind: &str,
+) -> Result<T, AppError> {
+    variants
+        .iter()
+        .copied()
+        .find(|v| v.to_string() == value)
+        .ok_or_else(|| {
+            let valid: Vec<String> = variants.iter().map(|v| v.to_string()).collect();
+            AppError::ValidationError(format!(
+                "Invalid {} '{}'. Valid values: {}",
+                kind,
+                value,
+                valid.join(", ")
+            ))
+        })
+}
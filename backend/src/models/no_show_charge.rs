@@ -0,0 +1,63 @@
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel_derive_enum::DbEnum;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::schema::no_show_charges;
+
+use super::Booking;
+
+/// Review state of a no-show charge
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, DbEnum)]
+#[ExistingTypePath = "crate::schema::sql_types::NoShowChargeStatus"]
+#[serde(rename_all = "snake_case")]
+#[DbValueStyle = "snake_case"]
+pub enum NoShowChargeStatus {
+    /// Raised by the stale-booking job, waiting for admin review
+    Pending,
+    /// Approved by an admin and recorded as a payment
+    Confirmed,
+    /// Dismissed by an admin
+    Waived,
+    /// Cancelled automatically because the guest arrived late
+    Voided,
+}
+
+/// Charge raised when a booking is marked as no-show
+#[derive(Debug, Clone, Queryable, Identifiable, Associations, Selectable, Serialize)]
+#[diesel(table_name = no_show_charges)]
+#[diesel(belongs_to(Booking, foreign_key = booking_id))]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct NoShowCharge {
+    pub id: Uuid,
+    pub booking_id: Uuid,
+    pub amount: BigDecimal,
+    /// "one_night" or "deposit"
+    pub basis: String,
+    pub status: NoShowChargeStatus,
+    pub reason: Option<String>,
+    pub reviewed_by_user_id: Option<Uuid>,
+    pub reviewed_at: Option<DateTime<Utc>>,
+    pub payment_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// New no-show charge for insertion
+#[derive(Debug, Insertable)]
+#[diesel(table_name = no_show_charges)]
+pub struct NewNoShowCharge<'a> {
+    pub booking_id: Uuid,
+    pub amount: BigDecimal,
+    pub basis: &'a str,
+}
+
+/// No-show charge with its booking for the admin review queue
+#[derive(Debug, Clone, Serialize)]
+pub struct NoShowChargeWithBooking {
+    #[serde(flatten)]
+    pub charge: NoShowCharge,
+    pub booking: Booking,
+}
@@ -0,0 +1,29 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use crate::schema::password_reset_tokens;
+
+/// Stored password reset token; the token itself is only in the emailed link
+#[derive(Debug, Clone, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = password_reset_tokens)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct PasswordResetToken {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    /// Hex SHA-256 of the token
+    pub token_hash: String,
+    pub expires_at: DateTime<Utc>,
+    /// Set once the token is used, replaced, or the guest logs in
+    pub used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// New password reset token for insertion
+#[derive(Debug, Insertable)]
+#[diesel(table_name = password_reset_tokens)]
+pub struct NewPasswordResetToken {
+    pub user_id: Uuid,
+    pub token_hash: String,
+    pub expires_at: DateTime<Utc>,
+}
@@ -0,0 +1,46 @@
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, NaiveDate, Utc};
+use diesel::prelude::*;
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::schema::pricing_rules;
+
+/// Nightly price for a date range, overriding the room's own price
+#[derive(Debug, Clone, Queryable, Identifiable, Selectable, Serialize)]
+#[diesel(table_name = pricing_rules)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct PricingRule {
+    pub id: Uuid,
+    pub name: String,
+    /// One room only
+    pub room_id: Option<Uuid>,
+    pub start_date: NaiveDate,
+    /// Last night covered, inclusive
+    pub end_date: NaiveDate,
+    /// Fixed nightly price in VND
+    pub price: Option<BigDecimal>,
+    /// Percentage added to the room's price; negative for a discount
+    pub percentage: Option<BigDecimal>,
+    /// Higher wins when several rules cover the same night
+    pub priority: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// Rooms of this type; None together with `room_id` covers every room
+    pub room_type_id: Option<Uuid>,
+}
+
+/// Pricing rule for insertion or replacing an existing one
+#[derive(Debug, Clone, Insertable, AsChangeset)]
+#[diesel(table_name = pricing_rules)]
+#[diesel(treat_none_as_null = true)]
+pub struct NewPricingRule {
+    pub name: String,
+    pub room_type_id: Option<Uuid>,
+    pub room_id: Option<Uuid>,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub price: Option<BigDecimal>,
+    pub percentage: Option<BigDecimal>,
+    pub priority: i32,
+}
@@ -0,0 +1,35 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use crate::schema::refresh_tokens;
+
+/// Stored refresh token; the token itself is only known to the client
+#[derive(Debug, Clone, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = refresh_tokens)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct RefreshToken {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    /// Shared by the tokens rotated from one login
+    pub family_id: Uuid,
+    /// Session carried by the access tokens issued for this family
+    pub session_id: Uuid,
+    /// Hex SHA-256 of the token
+    pub token_hash: String,
+    pub expires_at: DateTime<Utc>,
+    /// Set once the token is exchanged, logged out or its family revoked
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// New refresh token for insertion
+#[derive(Debug, Insertable)]
+#[diesel(table_name = refresh_tokens)]
+pub struct NewRefreshToken {
+    pub user_id: Uuid,
+    pub family_id: Uuid,
+    pub session_id: Uuid,
+    pub token_hash: String,
+    pub expires_at: DateTime<Utc>,
+}
@@ -0,0 +1,96 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use diesel::prelude::*;
+use diesel_derive_enum::DbEnum;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::schema::{report_deliveries, report_subscriptions};
+
+/// How often a summary report is emailed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, DbEnum)]
+#[ExistingTypePath = "crate::schema::sql_types::ReportType"]
+#[serde(rename_all = "snake_case")]
+#[DbValueStyle = "snake_case"]
+pub enum ReportType {
+    /// Previous day, sent every morning
+    Daily,
+    /// Previous Monday–Sunday, sent on Mondays
+    Weekly,
+    /// Previous calendar month, sent on the 1st
+    Monthly,
+}
+
+/// Delivery state of one report email
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, DbEnum)]
+#[ExistingTypePath = "crate::schema::sql_types::ReportDeliveryStatus"]
+#[serde(rename_all = "snake_case")]
+#[DbValueStyle = "snake_case"]
+pub enum ReportDeliveryStatus {
+    /// Not sent yet, or waiting for a retry
+    Pending,
+    Sent,
+    /// Gave up after the maximum number of attempts
+    Failed,
+}
+
+/// Admin-managed subscription to a scheduled report
+#[derive(Debug, Clone, Queryable, Identifiable, Selectable, Serialize)]
+#[diesel(table_name = report_subscriptions)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct ReportSubscription {
+    pub id: Uuid,
+    pub recipient_email: String,
+    pub report_type: ReportType,
+    pub enabled: bool,
+    pub created_by_user_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// New report subscription for insertion
+#[derive(Debug, Insertable)]
+#[diesel(table_name = report_subscriptions)]
+pub struct NewReportSubscription {
+    pub recipient_email: String,
+    pub report_type: ReportType,
+    pub enabled: bool,
+    pub created_by_user_id: Option<Uuid>,
+}
+
+/// Report subscription update changeset
+#[derive(Debug, Default, AsChangeset, Deserialize)]
+#[diesel(table_name = report_subscriptions)]
+pub struct UpdateReportSubscription {
+    pub recipient_email: Option<String>,
+    pub report_type: Option<ReportType>,
+    pub enabled: Option<bool>,
+}
+
+/// Log entry for one report period sent (or attempted) to a subscription
+#[derive(Debug, Clone, Queryable, Identifiable, Associations, Selectable, Serialize)]
+#[diesel(table_name = report_deliveries)]
+#[diesel(belongs_to(ReportSubscription, foreign_key = subscription_id))]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct ReportDelivery {
+    pub id: Uuid,
+    pub subscription_id: Uuid,
+    pub period_start: NaiveDate,
+    pub period_end: NaiveDate,
+    pub status: ReportDeliveryStatus,
+    pub attempts: i32,
+    pub last_error: Option<String>,
+    pub next_attempt_at: Option<DateTime<Utc>>,
+    pub sent_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// New report delivery for insertion
+#[derive(Debug, Insertable)]
+#[diesel(table_name = report_deliveries)]
+pub struct NewReportDelivery {
+    pub subscription_id: Uuid,
+    pub period_start: NaiveDate,
+    pub period_end: NaiveDate,
+    pub next_attempt_at: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,15 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use crate::schema::revoked_tokens;
+
+/// Access token revoked before its expiry, by JWT id
+#[derive(Debug, Insertable)]
+#[diesel(table_name = revoked_tokens)]
+pub struct NewRevokedToken {
+    pub jti: Uuid,
+    pub user_id: Uuid,
+    /// When the token would have expired; the row is not needed after that
+    pub expires_at: DateTime<Utc>,
+}
@@ -1,23 +1,28 @@
 use chrono::{DateTime, Utc};
+use diesel::deserialize::{self, FromSql, FromSqlRow};
+use diesel::expression::AsExpression;
+use diesel::pg::{Pg, PgValue};
 use diesel::prelude::*;
+use diesel::serialize::{self, Output, ToSql};
+use diesel::sql_types::Varchar;
 use diesel_derive_enum::DbEnum;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use uuid::Uuid;
 use bigdecimal::BigDecimal;
+use std::borrow::Cow;
+use std::fmt;
+use std::str::FromStr;
 
+use crate::errors::AppError;
 use crate::schema::rooms;
 use crate::models::UserRole;
 
-/// Room type enum matching PostgreSQL room_type type
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, DbEnum)]
-#[ExistingTypePath = "crate::schema::sql_types::RoomType"]
-#[serde(rename_all = "snake_case")]
-#[DbValueStyle = "snake_case"]
-pub enum RoomType {
-    Single,
-    Double,
-    Suite,
-}
+/// Code of a room type, e.g. "double". Room types are configured in the
+/// `room_types` table; the original single, double and suite types keep
+/// their codes, so values sent by older clients still parse.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, AsExpression, FromSqlRow)]
+#[diesel(sql_type = Varchar)]
+pub struct RoomType(Cow<'static, str>);
 
 /// Room status enum matching PostgreSQL room_status type
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, DbEnum)]
@@ -45,6 +50,17 @@ pub struct Room {
     pub updated_at: DateTime<Utc>,
     pub price: BigDecimal,
     pub assigned_cleaner_id: Option<Uuid>,
+    pub description: Option<String>,
+    pub amenities: Vec<String>,
+    pub photo_urls: Vec<String>,
+    /// When the room was taken out of service; it stays for booking history
+    pub decommissioned_at: Option<DateTime<Utc>>,
+    /// Row of the room's type in `room_types`; `room_type` is its code
+    #[serde(skip)]
+    pub room_type_id: Uuid,
+    pub floor: i32,
+    /// None for the main building
+    pub building: Option<String>,
 }
 
 /// New room for insertion
@@ -53,7 +69,10 @@ pub struct Room {
 pub struct NewRoom<'a> {
     pub number: &'a str,
     pub room_type: RoomType,
+    pub room_type_id: Uuid,
     pub price: BigDecimal,
+    pub floor: i32,
+    pub building: Option<String>,
 }
 
 /// Room update changeset
@@ -61,12 +80,137 @@ pub struct NewRoom<'a> {
 #[diesel(table_name = rooms)]
 pub struct UpdateRoom {
     pub room_type: Option<RoomType>,
+    pub room_type_id: Option<Uuid>,
     pub status: Option<RoomStatus>,
     pub price: Option<BigDecimal>,
     pub assigned_cleaner_id: Option<Option<Uuid>>,
+    pub description: Option<Option<String>>,
+    pub amenities: Option<Vec<String>>,
+    pub photo_urls: Option<Vec<String>>,
+    pub floor: Option<i32>,
+    /// Some(None) moves the room to the main building
+    pub building: Option<Option<String>>,
+}
+
+impl UpdateRoom {
+    /// Whether the changeset leaves every column as it is
+    pub fn is_empty(&self) -> bool {
+        self.room_type.is_none()
+            && self.status.is_none()
+            && self.price.is_none()
+            && self.assigned_cleaner_id.is_none()
+            && self.description.is_none()
+            && self.amenities.is_none()
+            && self.photo_urls.is_none()
+            && self.floor.is_none()
+            && self.building.is_none()
+    }
+}
+
+/// Guest-facing details of a room; `None` fields are left unchanged
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct RoomDetailsUpdate {
+    /// An empty description clears it
+    pub description: Option<String>,
+    pub amenities: Option<Vec<String>>,
+    pub photo_urls: Option<Vec<String>>,
+}
+
+impl RoomType {
+    pub const SINGLE: RoomType = RoomType(Cow::Borrowed("single"));
+    pub const DOUBLE: RoomType = RoomType(Cow::Borrowed("double"));
+    pub const SUITE: RoomType = RoomType(Cow::Borrowed("suite"));
+
+    /// The types that existed before room types were configurable
+    pub const LEGACY: [RoomType; 3] = [RoomType::SINGLE, RoomType::DOUBLE, RoomType::SUITE];
+
+    /// Longest room type code
+    pub const MAX_LEN: usize = 30;
+
+    /// Parse a code: lower-cased, then a letter followed by letters, digits
+    /// or underscores. Whether the type exists is for the caller to check.
+    pub fn new(code: &str) -> Result<Self, AppError> {
+        let code = code.trim().to_ascii_lowercase();
+        let well_formed = code.len() <= Self::MAX_LEN
+            && code.starts_with(|c: char| c.is_ascii_lowercase())
+            && code.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+        if !well_formed {
+            return Err(AppError::ValidationError(format!(
+                "Invalid room type '{}'. Codes are up to {} lowercase letters, digits or underscores, starting with a letter",
+                code,
+                Self::MAX_LEN
+            )));
+        }
+        Ok(Self(Cow::Owned(code)))
+    }
+
+    /// The code, identical to the serde and database representation
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for RoomType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for RoomType {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s)
+    }
+}
+
+impl Serialize for RoomType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for RoomType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let code = String::deserialize(deserializer)?;
+        Self::new(&code).map_err(serde::de::Error::custom)
+    }
+}
+
+impl ToSql<Varchar, Pg> for RoomType {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        <str as ToSql<Varchar, Pg>>::to_sql(self.as_str(), out)
+    }
+}
+
+impl FromSql<Varchar, Pg> for RoomType {
+    fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+        let code = <String as FromSql<Varchar, Pg>>::from_sql(bytes)?;
+        Ok(Self(Cow::Owned(code)))
+    }
 }
 
 impl RoomStatus {
+    /// All variants
+    pub const ALL: [RoomStatus; 5] = [
+        RoomStatus::Available,
+        RoomStatus::Occupied,
+        RoomStatus::Maintenance,
+        RoomStatus::Dirty,
+        RoomStatus::Cleaning,
+    ];
+
+    /// Canonical snake_case value, identical to the serde and database representation
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RoomStatus::Available => "available",
+            RoomStatus::Occupied => "occupied",
+            RoomStatus::Maintenance => "maintenance",
+            RoomStatus::Dirty => "dirty",
+            RoomStatus::Cleaning => "cleaning",
+        }
+    }
+
     /// Check if transition to new status is valid
     pub fn can_transition_to(&self, new_status: RoomStatus) -> bool {
         match (self, new_status) {
@@ -96,12 +240,28 @@ impl RoomStatus {
     /// Check if a role is allowed to set this status
     /// Returns true if the role can set this status, false otherwise
     pub fn is_allowed_for_role(&self, role: UserRole) -> bool {
-        match (self, role) {
-            // Cleaners cannot set rooms to Occupied or Maintenance
-            (RoomStatus::Occupied, UserRole::Cleaner) => false,
-            (RoomStatus::Maintenance, UserRole::Cleaner) => false,
-            // All other statuses are allowed for cleaners
-            _ => true,
+        match role {
+            // Cleaners only move rooms through the cleaning flow; a new status
+            // stays closed to them until it is listed here
+            UserRole::Cleaner => matches!(
+                self,
+                RoomStatus::Dirty | RoomStatus::Cleaning | RoomStatus::Available
+            ),
+            UserRole::Admin | UserRole::Receptionist | UserRole::Guest | UserRole::Bot => true,
         }
     }
 }
+
+impl fmt::Display for RoomStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for RoomStatus {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        super::parse_wire_value(s, &Self::ALL, "room status")
+    }
+}
@@ -0,0 +1,44 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use diesel::prelude::*;
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::schema::room_blocks;
+
+/// Nights a room is kept off sale without a booking or maintenance
+#[derive(Debug, Clone, Queryable, Identifiable, Selectable, Serialize)]
+#[diesel(table_name = room_blocks)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct RoomBlock {
+    pub id: Uuid,
+    pub room_id: Uuid,
+    pub start_date: NaiveDate,
+    /// Last night blocked, inclusive
+    pub end_date: NaiveDate,
+    pub reason: Option<String>,
+    /// None once the creator's account is deleted
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// New room block for insertion
+#[derive(Debug, Insertable)]
+#[diesel(table_name = room_blocks)]
+pub struct NewRoomBlock {
+    pub room_id: Uuid,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub reason: Option<String>,
+    pub created_by: Option<Uuid>,
+}
+
+/// Replacement dates and reason of a room block
+#[derive(Debug, AsChangeset)]
+#[diesel(table_name = room_blocks)]
+#[diesel(treat_none_as_null = true)]
+pub struct RoomBlockChanges {
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub reason: Option<String>,
+}
@@ -0,0 +1,31 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::schema::room_photos;
+
+/// Photo of a room kept in object storage
+#[derive(Debug, Clone, Queryable, Identifiable, Selectable, Serialize)]
+#[diesel(table_name = room_photos)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct RoomPhoto {
+    pub id: Uuid,
+    pub room_id: Uuid,
+    /// Key of the object in the room-photos bucket
+    pub object_key: String,
+    pub caption: Option<String>,
+    /// Display position, lowest first
+    pub sort_order: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+/// New room photo for insertion
+#[derive(Debug, Insertable)]
+#[diesel(table_name = room_photos)]
+pub struct NewRoomPhoto {
+    pub room_id: Uuid,
+    pub object_key: String,
+    pub caption: Option<String>,
+    pub sort_order: i32,
+}
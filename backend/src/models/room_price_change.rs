@@ -0,0 +1,32 @@
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::schema::room_price_history;
+
+/// Recorded change to a room's nightly price
+#[derive(Debug, Clone, Queryable, Identifiable, Selectable, Serialize)]
+#[diesel(table_name = room_price_history)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct RoomPriceChange {
+    pub id: Uuid,
+    pub room_id: Uuid,
+    pub old_price: BigDecimal,
+    pub new_price: BigDecimal,
+    /// None once the admin's account is deleted
+    pub changed_by: Option<Uuid>,
+    /// When the new price took effect
+    pub changed_at: DateTime<Utc>,
+}
+
+/// New room price change for insertion
+#[derive(Debug, Insertable)]
+#[diesel(table_name = room_price_history)]
+pub struct NewRoomPriceChange {
+    pub room_id: Uuid,
+    pub old_price: BigDecimal,
+    pub new_price: BigDecimal,
+    pub changed_by: Option<Uuid>,
+}
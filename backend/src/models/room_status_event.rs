@@ -0,0 +1,31 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::models::RoomStatus;
+use crate::schema::room_status_events;
+
+/// Recorded change of a room's status
+#[derive(Debug, Clone, Queryable, Identifiable, Selectable, Serialize)]
+#[diesel(table_name = room_status_events)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct RoomStatusEvent {
+    pub id: Uuid,
+    pub room_id: Uuid,
+    pub from_status: RoomStatus,
+    pub to_status: RoomStatus,
+    /// None for system transitions or once the account is deleted
+    pub actor_user_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// New room status event for insertion
+#[derive(Debug, Insertable)]
+#[diesel(table_name = room_status_events)]
+pub struct NewRoomStatusEvent {
+    pub room_id: Uuid,
+    pub from_status: RoomStatus,
+    pub to_status: RoomStatus,
+    pub actor_user_id: Option<Uuid>,
+}
@@ -0,0 +1,57 @@
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::models::RoomType;
+use crate::schema::room_types;
+
+/// Configured room type with the defaults new rooms of the type start from
+#[derive(Debug, Clone, Queryable, Identifiable, Selectable, Serialize)]
+#[diesel(table_name = room_types)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct RoomTypeConfig {
+    pub id: Uuid,
+    pub code: RoomType,
+    pub display_name: String,
+    /// Nightly price in VND given to new rooms of this type
+    pub default_price: BigDecimal,
+    /// Most guests a room of this type sleeps
+    pub default_capacity: i32,
+    /// Inactive types keep their rooms but are not offered for new ones
+    pub active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// New room type for insertion
+#[derive(Debug, Insertable)]
+#[diesel(table_name = room_types)]
+pub struct NewRoomTypeConfig {
+    pub code: RoomType,
+    pub display_name: String,
+    pub default_price: BigDecimal,
+    pub default_capacity: i32,
+    pub active: bool,
+}
+
+/// Room type changeset; the code cannot change
+#[derive(Debug, AsChangeset, Default)]
+#[diesel(table_name = room_types)]
+pub struct UpdateRoomTypeConfig {
+    pub display_name: Option<String>,
+    pub default_price: Option<BigDecimal>,
+    pub default_capacity: Option<i32>,
+    pub active: Option<bool>,
+}
+
+impl UpdateRoomTypeConfig {
+    /// Whether the changeset leaves every column as it is
+    pub fn is_empty(&self) -> bool {
+        self.display_name.is_none()
+            && self.default_price.is_none()
+            && self.default_capacity.is_none()
+            && self.active.is_none()
+    }
+}
@@ -2,8 +2,11 @@ use chrono::{DateTime, Utc};
 use diesel::prelude::*;
 use diesel_derive_enum::DbEnum;
 use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
 use uuid::Uuid;
 
+use crate::errors::AppError;
 use crate::schema::users;
 
 /// User role enum matching PostgreSQL user_role type
@@ -24,6 +27,42 @@ pub enum UserRole {
     Bot,
 }
 
+impl UserRole {
+    /// All roles
+    pub const ALL: [UserRole; 5] = [
+        UserRole::Admin,
+        UserRole::Receptionist,
+        UserRole::Guest,
+        UserRole::Cleaner,
+        UserRole::Bot,
+    ];
+
+    /// Canonical snake_case value, identical to the serde and database representation
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UserRole::Admin => "admin",
+            UserRole::Receptionist => "receptionist",
+            UserRole::Guest => "guest",
+            UserRole::Cleaner => "cleaner",
+            UserRole::Bot => "bot",
+        }
+    }
+}
+
+impl fmt::Display for UserRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for UserRole {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        super::parse_wire_value(s, &Self::ALL, "user role")
+    }
+}
+
 /// User model representing a staff member or guest
 #[derive(Debug, Clone, Queryable, Identifiable, Selectable, Serialize)]
 #[diesel(table_name = users)]
@@ -47,6 +86,16 @@ pub struct User {
     pub id_number: Option<String>,
     /// Soft delete timestamp for employee accounts (NULL = active)
     pub deactivated_at: Option<DateTime<Utc>>,
+    /// Access tokens issued at or before this time are revoked
+    #[serde(skip_serializing)]
+    pub tokens_not_before: Option<DateTime<Utc>>,
+    /// Last successful login
+    pub last_login_at: Option<DateTime<Utc>>,
+    /// The password was chosen by an admin and must be changed before
+    /// anything else
+    pub must_change_password: bool,
+    /// When a guest confirmed their email; staff do not need to
+    pub email_verified_at: Option<DateTime<Utc>>,
 }
 
 /// New staff user for insertion (username required)
@@ -60,6 +109,7 @@ pub struct NewUser<'a> {
     pub full_name: Option<&'a str>,
     pub phone: Option<&'a str>,
     pub id_number: Option<&'a str>,
+    pub must_change_password: bool,
 }
 
 /// User info without sensitive data (for API responses) - for staff users
@@ -90,6 +140,40 @@ impl From<&User> for UserInfo {
     }
 }
 
+/// The signed-in account's own profile. Staff see their username and
+/// guests their identification number; the password hash never leaves.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProfileResponse {
+    pub id: Uuid,
+    pub role: UserRole,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    pub email: Option<String>,
+    pub full_name: Option<String>,
+    pub phone: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id_number: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub deactivated: bool,
+}
+
+impl From<User> for ProfileResponse {
+    fn from(user: User) -> Self {
+        let is_guest = user.role == UserRole::Guest;
+        Self {
+            id: user.id,
+            role: user.role,
+            username: user.username.filter(|_| !is_guest),
+            email: user.email,
+            full_name: user.full_name,
+            phone: user.phone,
+            id_number: user.id_number.filter(|_| is_guest),
+            created_at: user.created_at,
+            deactivated: user.deactivated_at.is_some(),
+        }
+    }
+}
+
 /// Guest user info for API responses
 #[derive(Debug, Clone, Serialize)]
 pub struct GuestInfo {
@@ -97,6 +181,8 @@ pub struct GuestInfo {
     pub email: String,
     pub full_name: String,
     pub role: UserRole,
+    /// Guests cannot book until they verify their email
+    pub email_verified_at: Option<DateTime<Utc>>,
 }
 
 impl TryFrom<User> for GuestInfo {
@@ -111,6 +197,7 @@ impl TryFrom<User> for GuestInfo {
             email: user.email.ok_or("Guest must have email")?,
             full_name: user.full_name.ok_or("Guest must have full_name")?,
             role: user.role,
+            email_verified_at: user.email_verified_at,
         })
     }
 }
@@ -127,6 +214,7 @@ impl TryFrom<&User> for GuestInfo {
             email: user.email.clone().ok_or("Guest must have email")?,
             full_name: user.full_name.clone().ok_or("Guest must have full_name")?,
             role: user.role,
+            email_verified_at: user.email_verified_at,
         })
     }
 }
@@ -0,0 +1,344 @@
+//! Background jobs that run on a fixed interval alongside the HTTP server.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::services::booking_service::StaleBookingSweep;
+use crate::services::hold_service::HoldPurge;
+use crate::services::mailer::Mailer;
+use crate::services::report_service::render_report_email;
+use crate::services::{BookingService, HoldService, MaintenanceService, ReadOnlyMode, ReportService};
+
+/// How often the scheduler wakes up
+pub const TICK_INTERVAL: Duration = Duration::from_secs(60);
+/// Default interval of the stale-booking sweep (STALE_SYNC_INTERVAL_SECS)
+pub const DEFAULT_STALE_SYNC_INTERVAL_SECS: u64 = 15 * 60;
+/// Queue and send report emails every N ticks
+pub const REPORT_EMAILS_EVERY_TICKS: u64 = 5;
+/// Purge expired booking holds past their retention every N ticks
+pub const HOLD_CLEANUP_EVERY_TICKS: u64 = 60;
+
+/// Job name of the stale-booking sweep
+pub const JOB_STALE_BOOKINGS: &str = "stale_booking_sweep";
+/// Job name of the scheduled report emails
+pub const JOB_REPORT_EMAILS: &str = "report_emails";
+/// Job name of the expired booking hold cleanup
+pub const JOB_HOLD_CLEANUP: &str = "hold_cleanup";
+
+/// Last known state of a background job, shown on the jobs admin endpoint
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct JobStatus {
+    pub name: &'static str,
+    pub runs: u64,
+    pub last_started_at: Option<DateTime<Utc>>,
+    pub last_finished_at: Option<DateTime<Utc>>,
+    /// Error of the most recent run, cleared by the next successful run
+    pub last_error: Option<String>,
+    /// Set when the last due run was skipped because of read-only mode
+    pub paused_at: Option<DateTime<Utc>>,
+    /// A run is in progress right now
+    pub running: bool,
+}
+
+/// In-memory record of background job runs
+#[derive(Debug, Default)]
+pub struct JobBoard {
+    jobs: Mutex<BTreeMap<&'static str, JobStatus>>,
+    running: Mutex<BTreeSet<&'static str>>,
+}
+
+/// Marks a job as running until dropped
+#[derive(Debug)]
+pub struct RunningJob {
+    board: Arc<JobBoard>,
+    name: &'static str,
+}
+
+impl Drop for RunningJob {
+    fn drop(&mut self) {
+        self.board.running.lock().unwrap().remove(self.name);
+    }
+}
+
+impl JobBoard {
+    /// Claim a job so only one run executes at a time
+    ///
+    /// # Returns
+    /// * `None` - The job is already running
+    pub fn try_start(self: &Arc<Self>, name: &'static str) -> Option<RunningJob> {
+        if !self.running.lock().unwrap().insert(name) {
+            return None;
+        }
+        Some(RunningJob {
+            board: self.clone(),
+            name,
+        })
+    }
+
+    /// Record a finished run
+    pub fn record_run(&self, name: &'static str, started_at: DateTime<Utc>, result: Result<(), String>) {
+        let mut jobs = self.jobs.lock().unwrap();
+        let job = jobs.entry(name).or_insert_with(|| JobStatus {
+            name,
+            ..Default::default()
+        });
+        job.runs += 1;
+        job.last_started_at = Some(started_at);
+        job.last_finished_at = Some(Utc::now());
+        job.last_error = result.err();
+        job.paused_at = None;
+    }
+
+    /// Record a due run skipped because read-only mode is on
+    pub fn record_paused(&self, name: &'static str) {
+        let mut jobs = self.jobs.lock().unwrap();
+        let job = jobs.entry(name).or_insert_with(|| JobStatus {
+            name,
+            ..Default::default()
+        });
+        job.paused_at = Some(Utc::now());
+    }
+
+    /// Current state of every job that has run or been paused
+    pub fn snapshot(&self) -> Vec<JobStatus> {
+        let running = self.running.lock().unwrap();
+        self.jobs
+            .lock()
+            .unwrap()
+            .values()
+            .cloned()
+            .map(|mut job| {
+                job.running = running.contains(job.name);
+                job
+            })
+            .collect()
+    }
+}
+
+/// Spawn the background scheduler.
+///
+/// Every tick refreshes the read-only setting so all instances follow an admin
+/// toggle. Mutating jobs are skipped while read-only mode is on and resume on
+/// the next due tick once it is turned off. The stale-booking sweep has its
+/// own task, see [`spawn_stale_sync`].
+pub fn spawn(
+    pool: DbPool,
+    read_only: Arc<ReadOnlyMode>,
+    jobs: Arc<JobBoard>,
+    mailer: Mailer,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(TICK_INTERVAL);
+        let mut tick: u64 = 0;
+
+        loop {
+            interval.tick().await;
+            tick += 1;
+
+            refresh_read_only(&pool, &read_only).await;
+
+            let reports_due = tick % REPORT_EMAILS_EVERY_TICKS == 1;
+            let hold_cleanup_due = tick % HOLD_CLEANUP_EVERY_TICKS == 1;
+
+            if read_only.is_enabled() {
+                if reports_due {
+                    jobs.record_paused(JOB_REPORT_EMAILS);
+                }
+                if hold_cleanup_due {
+                    jobs.record_paused(JOB_HOLD_CLEANUP);
+                }
+                continue;
+            }
+
+            if reports_due {
+                let started_at = Utc::now();
+                let result = run_report_emails(&pool, &mailer).await;
+                jobs.record_run(JOB_REPORT_EMAILS, started_at, result);
+            }
+            if hold_cleanup_due && purge_expired_holds(&pool, &jobs, None).await.is_none() {
+                tracing::info!("Hold cleanup still running, skipping this tick");
+            }
+        }
+    })
+}
+
+/// Reload the read-only system setting into the in-memory flag
+async fn refresh_read_only(pool: &DbPool, read_only: &ReadOnlyMode) {
+    let service = MaintenanceService::new(pool.clone());
+    match tokio::task::spawn_blocking(move || service.load_read_only_setting()).await {
+        Ok(Ok(enabled)) => read_only.set_setting(enabled),
+        Ok(Err(e)) => tracing::warn!("Could not refresh read-only setting: {}", e),
+        Err(e) => tracing::warn!("Read-only refresh task failed: {}", e),
+    }
+}
+
+/// Spawn the stale-booking sweep, run every `every` starting right away.
+/// Runs are skipped while read-only mode is on.
+pub fn spawn_stale_sync(
+    pool: DbPool,
+    read_only: Arc<ReadOnlyMode>,
+    jobs: Arc<JobBoard>,
+    every: Duration,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(every);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        loop {
+            interval.tick().await;
+
+            if read_only.is_enabled() {
+                tracing::info!("Read-only mode is on, skipping stale booking sweep");
+                jobs.record_paused(JOB_STALE_BOOKINGS);
+                continue;
+            }
+
+            if sync_stale_bookings(&pool, &jobs).await.is_none() {
+                tracing::info!("Stale booking sweep still running, skipping this interval");
+            }
+        }
+    })
+}
+
+/// Run the stale-booking sweep unless one is already in progress, recording
+/// the run on the job board. Shared by the scheduled task and the manual
+/// trigger.
+///
+/// # Returns
+/// * `None` - Another sweep was running, nothing was done
+/// * `Some(Ok(sweep))` - Bookings and overstay rooms updated by this run
+pub async fn sync_stale_bookings(
+    pool: &DbPool,
+    jobs: &Arc<JobBoard>,
+) -> Option<Result<StaleBookingSweep, String>> {
+    let _running = jobs.try_start(JOB_STALE_BOOKINGS)?;
+    let started_at = Utc::now();
+    let result = run_stale_booking_sweep(pool).await;
+    jobs.record_run(JOB_STALE_BOOKINGS, started_at, result.as_ref().map(|_| ()).map_err(String::clone));
+    Some(result)
+}
+
+/// Flip stale Upcoming/CheckedIn bookings to NoShow/Overstay
+async fn run_stale_booking_sweep(pool: &DbPool) -> Result<StaleBookingSweep, String> {
+    let pool = pool.clone();
+    let sweep = tokio::task::spawn_blocking(move || {
+        let mut conn = pool.get().map_err(|e| e.to_string())?;
+        BookingService::new(pool.clone())
+            .handle_stale_bookings(&mut conn)
+            .map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    if sweep.no_shows + sweep.overstays > 0 {
+        tracing::info!(
+            "Stale booking sweep marked {} no-show(s) and {} overstay(s) in {} room(s)",
+            sweep.no_shows,
+            sweep.overstays,
+            sweep.overstay_room_ids.len()
+        );
+    } else {
+        tracing::debug!("Stale booking sweep found nothing to update");
+    }
+    Ok(sweep)
+}
+
+/// Purge expired booking holds past their retention unless a purge is
+/// already in progress, recording the run on the job board. Shared by the
+/// scheduled task and the manual purge.
+///
+/// # Returns
+/// * `None` - Another purge was running, nothing was done
+/// * `Some(Ok(purge))` - Holds deleted by this run
+pub async fn purge_expired_holds(
+    pool: &DbPool,
+    jobs: &Arc<JobBoard>,
+    actor_user_id: Option<Uuid>,
+) -> Option<Result<HoldPurge, String>> {
+    let _running = jobs.try_start(JOB_HOLD_CLEANUP)?;
+    let started_at = Utc::now();
+    let pool = pool.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        HoldService::new(pool)
+            .purge_expired(Utc::now(), false, actor_user_id)
+            .map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())
+    .and_then(|purge| purge);
+    jobs.record_run(JOB_HOLD_CLEANUP, started_at, result.as_ref().map(|_| ()).map_err(String::clone));
+
+    if let Ok(purge) = &result {
+        if purge.holds > 0 {
+            tracing::info!("Hold cleanup deleted {} expired hold(s) in {} batch(es)", purge.holds, purge.batches);
+        }
+    }
+    Some(result)
+}
+
+/// Queue due report periods, then send pending deliveries. Each failed send is
+/// retried with backoff by the report service; the job itself only fails when
+/// the database is unreachable.
+async fn run_report_emails(pool: &DbPool, mailer: &Mailer) -> Result<(), String> {
+    let service_pool = pool.clone();
+    let due = tokio::task::spawn_blocking(move || {
+        let service = ReportService::new(service_pool);
+        let now = Utc::now();
+        service.enqueue_due_deliveries(now)?;
+        service.due_deliveries(now)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())?;
+
+    for (delivery, subscription) in due {
+        let summary_pool = pool.clone();
+        let (report_type, start, end) = (subscription.report_type, delivery.period_start, delivery.period_end);
+        let summary = tokio::task::spawn_blocking(move || {
+            ReportService::new(summary_pool).build_summary(report_type, start, end)
+        })
+        .await
+        .map_err(|e| e.to_string())?;
+
+        let result = match summary {
+            Ok(summary) => {
+                mailer
+                    .send(&render_report_email(&subscription.recipient_email, &summary))
+                    .await
+            }
+            Err(e) => Err(e.to_string()),
+        };
+
+        match &result {
+            Ok(()) => tracing::info!(
+                "Sent {:?} report for {} to {}",
+                subscription.report_type,
+                delivery.period_start,
+                subscription.recipient_email
+            ),
+            Err(e) => tracing::warn!(
+                "Report delivery {} to {} failed: {}",
+                delivery.id,
+                subscription.recipient_email,
+                e
+            ),
+        }
+
+        let record_pool = pool.clone();
+        tokio::task::spawn_blocking(move || {
+            ReportService::new(record_pool).record_attempt(&delivery, result, Utc::now())
+        })
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())?;
+    }
+
+    Ok(())
+}
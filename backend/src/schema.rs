@@ -9,10 +9,6 @@ pub mod sql_types {
     #[diesel(postgres_type(name = "room_status"))]
     pub struct RoomStatus;
 
-    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
-    #[diesel(postgres_type(name = "room_type"))]
-    pub struct RoomType;
-
     #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
     #[diesel(postgres_type(name = "user_role"))]
     pub struct UserRole;
@@ -24,6 +20,34 @@ pub mod sql_types {
     #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
     #[diesel(postgres_type(name = "inventory_status"))]
     pub struct InventoryStatus;
+
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "no_show_charge_status"))]
+    pub struct NoShowChargeStatus;
+
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "report_type"))]
+    pub struct ReportType;
+
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "report_delivery_status"))]
+    pub struct ReportDeliveryStatus;
+
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "booking_email_kind"))]
+    pub struct BookingEmailKind;
+
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "booking_email_status"))]
+    pub struct BookingEmailStatus;
+
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "ticket_severity"))]
+    pub struct TicketSeverity;
+
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "ticket_status"))]
+    pub struct TicketStatus;
 }
 
 diesel::table! {
@@ -46,6 +70,10 @@ diesel::table! {
         #[max_length = 10]
         creation_source -> Varchar,
         price -> Numeric,
+        #[max_length = 20]
+        group_reference -> Nullable<Varchar>,
+        checked_in_at -> Nullable<Timestamptz>,
+        checked_out_at -> Nullable<Timestamptz>,
     }
 }
 
@@ -62,19 +90,42 @@ diesel::table! {
 
 diesel::table! {
     use diesel::sql_types::*;
-    use super::sql_types::RoomType;
     use super::sql_types::RoomStatus;
 
     rooms (id) {
         id -> Uuid,
         #[max_length = 10]
         number -> Varchar,
-        room_type -> RoomType,
+        #[max_length = 30]
+        room_type -> Varchar,
         status -> RoomStatus,
         created_at -> Timestamptz,
         updated_at -> Timestamptz,
         price -> Numeric,
         assigned_cleaner_id -> Nullable<Uuid>,
+        description -> Nullable<Text>,
+        amenities -> Array<Text>,
+        photo_urls -> Array<Text>,
+        decommissioned_at -> Nullable<Timestamptz>,
+        room_type_id -> Uuid,
+        floor -> Int4,
+        #[max_length = 50]
+        building -> Nullable<Varchar>,
+    }
+}
+
+diesel::table! {
+    room_types (id) {
+        id -> Uuid,
+        #[max_length = 30]
+        code -> Varchar,
+        #[max_length = 100]
+        display_name -> Varchar,
+        default_price -> Numeric,
+        default_capacity -> Int4,
+        active -> Bool,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
     }
 }
 
@@ -100,6 +151,10 @@ diesel::table! {
         #[max_length = 50]
         id_number -> Nullable<Varchar>,
         deactivated_at -> Nullable<Timestamptz>,
+        tokens_not_before -> Nullable<Timestamptz>,
+        last_login_at -> Nullable<Timestamptz>,
+        must_change_password -> Bool,
+        email_verified_at -> Nullable<Timestamptz>,
     }
 }
 
@@ -149,6 +204,8 @@ diesel::table! {
         is_read -> Bool,
         created_at -> Timestamptz,
         updated_at -> Timestamptz,
+        edited_at -> Nullable<Timestamptz>,
+        deleted_at -> Nullable<Timestamptz>,
     }
 }
 
@@ -162,19 +219,428 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::NoShowChargeStatus;
+
+    no_show_charges (id) {
+        id -> Uuid,
+        booking_id -> Uuid,
+        amount -> Numeric,
+        #[max_length = 20]
+        basis -> Varchar,
+        status -> NoShowChargeStatus,
+        reason -> Nullable<Text>,
+        reviewed_by_user_id -> Nullable<Uuid>,
+        reviewed_at -> Nullable<Timestamptz>,
+        payment_id -> Nullable<Uuid>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    audit_logs (id) {
+        id -> Uuid,
+        actor_user_id -> Nullable<Uuid>,
+        #[max_length = 100]
+        action -> Varchar,
+        #[max_length = 50]
+        entity_type -> Varchar,
+        #[max_length = 100]
+        entity_id -> Nullable<Varchar>,
+        details -> Nullable<Text>,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::BookingStatus;
+
+    booking_events (id) {
+        id -> Uuid,
+        booking_id -> Uuid,
+        #[max_length = 30]
+        event_type -> Varchar,
+        from_status -> Nullable<BookingStatus>,
+        to_status -> Nullable<BookingStatus>,
+        actor_user_id -> Nullable<Uuid>,
+        details -> Nullable<Text>,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::ReportType;
+
+    report_subscriptions (id) {
+        id -> Uuid,
+        #[max_length = 255]
+        recipient_email -> Varchar,
+        report_type -> ReportType,
+        enabled -> Bool,
+        created_by_user_id -> Nullable<Uuid>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::ReportDeliveryStatus;
+
+    report_deliveries (id) {
+        id -> Uuid,
+        subscription_id -> Uuid,
+        period_start -> Date,
+        period_end -> Date,
+        status -> ReportDeliveryStatus,
+        attempts -> Int4,
+        last_error -> Nullable<Text>,
+        next_attempt_at -> Nullable<Timestamptz>,
+        sent_at -> Nullable<Timestamptz>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+diesel::joinable!(audit_logs -> users (actor_user_id));
+diesel::joinable!(booking_events -> bookings (booking_id));
+diesel::joinable!(booking_events -> users (actor_user_id));
+diesel::table! {
+    cash_reconciliations (id) {
+        id -> Uuid,
+        business_date -> Date,
+        #[max_length = 20]
+        shift -> Nullable<Varchar>,
+        period_start -> Timestamptz,
+        period_end -> Timestamptz,
+        expected_cash -> Numeric,
+        counted_cash -> Numeric,
+        discrepancy -> Numeric,
+        note -> Nullable<Text>,
+        recorded_by_user_id -> Uuid,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    admin_notifications (id) {
+        id -> Uuid,
+        #[max_length = 50]
+        kind -> Varchar,
+        message -> Text,
+        #[max_length = 50]
+        entity_type -> Nullable<Varchar>,
+        #[max_length = 100]
+        entity_id -> Nullable<Varchar>,
+        read_at -> Nullable<Timestamptz>,
+        read_by_user_id -> Nullable<Uuid>,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    booking_holds (id) {
+        id -> Uuid,
+        room_id -> Uuid,
+        user_id -> Uuid,
+        check_in_date -> Date,
+        check_out_date -> Date,
+        expires_at -> Timestamptz,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    room_price_history (id) {
+        id -> Uuid,
+        room_id -> Uuid,
+        old_price -> Numeric,
+        new_price -> Numeric,
+        changed_by -> Nullable<Uuid>,
+        changed_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::RoomStatus;
+
+    room_status_events (id) {
+        id -> Uuid,
+        room_id -> Uuid,
+        from_status -> RoomStatus,
+        to_status -> RoomStatus,
+        actor_user_id -> Nullable<Uuid>,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    login_events (id) {
+        id -> Uuid,
+        user_id -> Uuid,
+        success -> Bool,
+        #[max_length = 45]
+        ip -> Varchar,
+        #[max_length = 512]
+        user_agent -> Nullable<Varchar>,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::TicketSeverity;
+    use super::sql_types::TicketStatus;
+
+    maintenance_tickets (id) {
+        id -> Uuid,
+        room_id -> Uuid,
+        #[max_length = 200]
+        title -> Varchar,
+        description -> Nullable<Text>,
+        severity -> TicketSeverity,
+        status -> TicketStatus,
+        reported_by -> Nullable<Uuid>,
+        resolved_at -> Nullable<Timestamptz>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    email_verification_tokens (id) {
+        id -> Uuid,
+        user_id -> Uuid,
+        #[max_length = 64]
+        token_hash -> Varchar,
+        expires_at -> Timestamptz,
+        used_at -> Nullable<Timestamptz>,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::UserRole;
+
+    api_keys (id) {
+        id -> Uuid,
+        #[max_length = 100]
+        label -> Varchar,
+        #[max_length = 64]
+        key_hash -> Varchar,
+        #[max_length = 16]
+        key_prefix -> Varchar,
+        role -> UserRole,
+        created_by -> Uuid,
+        created_at -> Timestamptz,
+        last_used_at -> Nullable<Timestamptz>,
+        revoked_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    password_reset_tokens (id) {
+        id -> Uuid,
+        user_id -> Uuid,
+        #[max_length = 64]
+        token_hash -> Varchar,
+        expires_at -> Timestamptz,
+        used_at -> Nullable<Timestamptz>,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    revoked_tokens (jti) {
+        jti -> Uuid,
+        user_id -> Uuid,
+        revoked_at -> Timestamptz,
+        expires_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    refresh_tokens (id) {
+        id -> Uuid,
+        user_id -> Uuid,
+        family_id -> Uuid,
+        session_id -> Uuid,
+        #[max_length = 64]
+        token_hash -> Varchar,
+        expires_at -> Timestamptz,
+        revoked_at -> Nullable<Timestamptz>,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    room_blocks (id) {
+        id -> Uuid,
+        room_id -> Uuid,
+        start_date -> Date,
+        end_date -> Date,
+        #[max_length = 200]
+        reason -> Nullable<Varchar>,
+        created_by -> Nullable<Uuid>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    pricing_rules (id) {
+        id -> Uuid,
+        #[max_length = 100]
+        name -> Varchar,
+        room_id -> Nullable<Uuid>,
+        start_date -> Date,
+        end_date -> Date,
+        price -> Nullable<Numeric>,
+        percentage -> Nullable<Numeric>,
+        priority -> Int4,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+        room_type_id -> Nullable<Uuid>,
+    }
+}
+
+diesel::table! {
+    room_photos (id) {
+        id -> Uuid,
+        room_id -> Uuid,
+        object_key -> Text,
+        caption -> Nullable<Text>,
+        sort_order -> Int4,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    booking_notes (id) {
+        id -> Uuid,
+        booking_id -> Uuid,
+        author_user_id -> Uuid,
+        note -> Text,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    booking_modifications (id) {
+        id -> Uuid,
+        booking_id -> Uuid,
+        actor_user_id -> Nullable<Uuid>,
+        #[max_length = 30]
+        source -> Varchar,
+        changes -> Text,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    user_sessions (id) {
+        id -> Uuid,
+        user_id -> Uuid,
+        created_at -> Timestamptz,
+        last_seen_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::{BookingEmailKind, BookingEmailStatus};
+
+    booking_emails (id) {
+        id -> Uuid,
+        booking_id -> Uuid,
+        kind -> BookingEmailKind,
+        #[max_length = 255]
+        recipient_email -> Varchar,
+        status -> BookingEmailStatus,
+        last_error -> Nullable<Text>,
+        sent_at -> Nullable<Timestamptz>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+diesel::joinable!(admin_notifications -> users (read_by_user_id));
+diesel::joinable!(booking_emails -> bookings (booking_id));
+diesel::joinable!(booking_modifications -> bookings (booking_id));
+diesel::joinable!(booking_modifications -> users (actor_user_id));
+diesel::joinable!(booking_holds -> rooms (room_id));
+diesel::joinable!(booking_holds -> users (user_id));
+diesel::joinable!(booking_notes -> bookings (booking_id));
+diesel::joinable!(booking_notes -> users (author_user_id));
 diesel::joinable!(bookings -> rooms (room_id));
 diesel::joinable!(bookings -> users (created_by_user_id));
+diesel::joinable!(cash_reconciliations -> users (recorded_by_user_id));
+diesel::joinable!(no_show_charges -> bookings (booking_id));
+diesel::joinable!(no_show_charges -> payments (payment_id));
 diesel::joinable!(payments -> bookings (booking_id));
 diesel::joinable!(payments -> users (created_by_user_id));
+diesel::joinable!(report_deliveries -> report_subscriptions (subscription_id));
+diesel::joinable!(report_subscriptions -> users (created_by_user_id));
+diesel::joinable!(room_price_history -> rooms (room_id));
+diesel::joinable!(room_price_history -> users (changed_by));
+diesel::joinable!(login_events -> users (user_id));
+diesel::joinable!(maintenance_tickets -> rooms (room_id));
+diesel::joinable!(maintenance_tickets -> users (reported_by));
+diesel::joinable!(pricing_rules -> rooms (room_id));
+diesel::joinable!(pricing_rules -> room_types (room_type_id));
+diesel::joinable!(api_keys -> users (created_by));
+diesel::joinable!(email_verification_tokens -> users (user_id));
+diesel::joinable!(password_reset_tokens -> users (user_id));
+diesel::joinable!(refresh_tokens -> users (user_id));
+diesel::joinable!(revoked_tokens -> users (user_id));
+diesel::joinable!(room_blocks -> rooms (room_id));
+diesel::joinable!(room_blocks -> users (created_by));
+diesel::joinable!(room_photos -> rooms (room_id));
+diesel::joinable!(room_status_events -> rooms (room_id));
+diesel::joinable!(room_status_events -> users (actor_user_id));
+diesel::joinable!(rooms -> room_types (room_type_id));
 diesel::joinable!(rooms -> users (assigned_cleaner_id));
+diesel::joinable!(user_sessions -> users (user_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
+    admin_notifications,
+    api_keys,
+    audit_logs,
+    booking_emails,
+    booking_events,
+    booking_modifications,
+    booking_holds,
+    booking_notes,
     bookings,
+    cash_reconciliations,
+    email_verification_tokens,
     guest_interaction_notes,
     inventory_items,
+    login_events,
     messages,
+    no_show_charges,
     payments,
+    report_deliveries,
+    report_subscriptions,
+    maintenance_tickets,
+    pricing_rules,
+    room_price_history,
+    password_reset_tokens,
+    refresh_tokens,
+    revoked_tokens,
+    room_blocks,
+    room_photos,
+    room_status_events,
+    room_types,
     rooms,
     users,
+    user_sessions,
     system_settings,
 );
@@ -1,4 +1,3 @@
-use std::collections::HashMap;
 use diesel::prelude::*;
 use rig::{
     completion::{Prompt, ToolDefinition},
@@ -9,14 +8,16 @@ use rig::client::CompletionClient;
 use tracing::{error, info};
 use serde::{Deserialize, Serialize};
 use chrono::NaiveDate;
-use bigdecimal::BigDecimal;
 use thiserror::Error;
 
 use crate::{
     db::DbPool,
-    schema::{system_settings, messages},
-    models::message::Message,
-    services::{BookingService, RoomService},
+    errors::AppError,
+    schema::messages,
+    settings::{self, Settings},
+    models::{message::{Message, DELETED_MESSAGE_CONTENT}, Room},
+    services::{room_service::RoomFilter, BookingService, EmailVerificationService, PricingService, RoomService},
+    utils::money::format_vnd,
 };
 use uuid::Uuid;
 
@@ -35,6 +36,92 @@ pub struct AiService {
     pool: DbPool,
 }
 
+/// Longest tool output handed back to the model, in characters
+pub const MAX_TOOL_OUTPUT_CHARS: usize = 3000;
+/// Description characters included per room in search results
+pub const SEARCH_DESCRIPTION_CHARS: usize = 160;
+/// Line prefix marking a room photo the chat should render as an image
+pub const ROOM_PHOTO_PREFIX: &str = "ROOM_PHOTO:";
+
+/// Cut `text` to at most `max` characters, marking the cut
+pub fn cap_tool_output(text: String, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text;
+    }
+    const MARKER: &str = "\n[truncated]";
+    let keep = max.saturating_sub(MARKER.chars().count());
+    let mut capped: String = text.chars().take(keep).collect();
+    capped.push_str(MARKER);
+    capped
+}
+
+fn shorten(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        text.to_string()
+    } else {
+        format!("{}...", text.chars().take(max).collect::<String>().trim_end())
+    }
+}
+
+/// One search result line: number, type, price, id, plus a short description and amenities
+pub fn room_summary_line(room: &Room) -> String {
+    let mut line = format!(
+        "Room {}: {} room, Price: {} per night, Room ID: {}",
+        room.number, room.room_type, format_vnd(&room.price), room.id
+    );
+    if let Some(description) = room.description.as_deref() {
+        line.push_str(&format!(", Description: {}", shorten(description, SEARCH_DESCRIPTION_CHARS)));
+    }
+    if !room.amenities.is_empty() {
+        line.push_str(&format!(", Amenities: {}", room.amenities.join(", ")));
+    }
+    line
+}
+
+/// Full room details for the `get_room_details` tool. Photos are listed one
+/// per line with `ROOM_PHOTO_PREFIX` so they can be shared verbatim.
+pub fn room_details_text(room: &Room) -> String {
+    let mut lines = vec![
+        format!("Room {} ({} room), Room ID: {}", room.number, room.room_type, room.id),
+        format!("Price: {} per night", format_vnd(&room.price)),
+        format!(
+            "Description: {}",
+            room.description.as_deref().unwrap_or("No description on file")
+        ),
+    ];
+    if room.amenities.is_empty() {
+        lines.push("Amenities: none listed".to_string());
+    } else {
+        lines.push(format!("Amenities: {}", room.amenities.join(", ")));
+    }
+    if room.photo_urls.is_empty() {
+        lines.push("Photos: none".to_string());
+    } else {
+        lines.push("Photos:".to_string());
+        lines.extend(room.photo_urls.iter().map(|url| format!("{}{}", ROOM_PHOTO_PREFIX, url)));
+    }
+    lines.join("\n")
+}
+
+/// Pull `ROOM_PHOTO:` lines out of a bot reply. Returns the remaining text and
+/// the first photo URL, which the chat attaches as the message image.
+pub fn extract_room_photo(reply: &str) -> (String, Option<String>) {
+    let mut photo = None;
+    let mut kept = Vec::new();
+    for line in reply.lines() {
+        match line.trim().strip_prefix(ROOM_PHOTO_PREFIX) {
+            Some(url) => {
+                let url = url.trim();
+                if photo.is_none() && (url.starts_with("https://") || url.starts_with("http://")) {
+                    photo = Some(url.to_string());
+                }
+            }
+            None => kept.push(line),
+        }
+    }
+    (kept.join("\n").trim().to_string(), photo)
+}
+
 /// Tool input for searching available rooms
 #[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
 struct SearchRoomsInput {
@@ -42,10 +129,17 @@ struct SearchRoomsInput {
     check_in_date: String,
     #[schemars(description = "Check-out date in YYYY-MM-DD format (e.g., 2026-02-25)")]
     check_out_date: String,
-    #[schemars(description = "Optional filter for room type: single, double, or suite")]
+    #[schemars(description = "Optional room type code to filter by, e.g. single, double or suite")]
     room_type: Option<String>,
 }
 
+/// Tool input for fetching one room's details
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+struct GetRoomDetailsInput {
+    #[schemars(description = "UUID of the room (obtained from search_available_rooms)")]
+    room_id: String,
+}
+
 /// Tool input for creating a booking proposal
 #[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
 struct CreateBookingProposalInput {
@@ -74,7 +168,7 @@ impl Tool for SearchRoomsTool {
         let parameters = serde_json::to_value(schemars::schema_for!(SearchRoomsInput)).unwrap();
         ToolDefinition {
             name: Self::NAME.to_string(),
-            description: "Search for available rooms based on check-in and check-out dates. Returns a list of available rooms with their details including room type, number, and price per night.".to_string(),
+            description: "Search for available rooms based on check-in and check-out dates. Returns a list of available rooms with their details including room type, number, price per night, a short description and amenities.".to_string(),
             parameters,
         }
     }
@@ -87,20 +181,19 @@ impl Tool for SearchRoomsTool {
             .map_err(|e| ToolError::InvalidInput(format!("Invalid check-out date format: {}", e)))?;
 
         // Parse room type if provided
-        let room_type = args.room_type.as_ref().and_then(|rt| {
-            match rt.to_lowercase().as_str() {
-                "single" => Some(crate::models::RoomType::Single),
-                "double" => Some(crate::models::RoomType::Double),
-                "suite" => Some(crate::models::RoomType::Suite),
-                _ => None,
-            }
-        });
+        let room_type = args
+            .room_type
+            .as_deref()
+            .and_then(|rt| crate::models::RoomType::new(rt).ok());
 
         let room_service = RoomService::new(self.pool.clone());
         let booking_service = BookingService::new(self.pool.clone());
 
         // Get all rooms (optionally filtered by type)
-        let rooms = room_service.list_rooms(None, room_type)
+        let rooms = room_service.list_rooms(&RoomFilter {
+                room_type,
+                ..RoomFilter::default()
+            })
             .map_err(|e| ToolError::Database(format!("Failed to list rooms: {}", e)))?;
 
         // Check availability for each room
@@ -115,31 +208,65 @@ impl Tool for SearchRoomsTool {
                 check_in,
                 check_out,
                 None,
+                None,
             ).map_err(|e| ToolError::Database(format!("Failed to check availability: {}", e)))?;
 
             if is_available {
-                available_rooms.push(format!(
-                    "Room {}: {:?} room, Price: {} VND per night, Room ID: {}",
-                    room.number,
-                    room.room_type,
-                    room.price,
-                    room.id
-                ));
+                available_rooms.push(room_summary_line(&room));
             }
         }
 
         if available_rooms.is_empty() {
             Ok("No rooms are available for the selected dates. Please try different dates or contact the front desk for assistance.".to_string())
         } else {
-            Ok(format!("Available rooms:\n{}", available_rooms.join("\n")))
+            Ok(cap_tool_output(
+                format!("Available rooms:\n{}", available_rooms.join("\n")),
+                MAX_TOOL_OUTPUT_CHARS,
+            ))
+        }
+    }
+}
+
+/// Tool for fetching a room's full description, amenities and photos
+#[derive(Debug, Clone)]
+struct GetRoomDetailsTool {
+    pool: DbPool,
+}
+
+impl Tool for GetRoomDetailsTool {
+    const NAME: &'static str = "get_room_details";
+
+    type Error = ToolError;
+    type Args = GetRoomDetailsInput;
+    type Output = String;
+
+    async fn definition(&self, _prompt: String) -> ToolDefinition {
+        let parameters = serde_json::to_value(schemars::schema_for!(GetRoomDetailsInput)).unwrap();
+        ToolDefinition {
+            name: Self::NAME.to_string(),
+            description: "Get the full details of a specific room: description, amenities, price and photo links. Use this to answer questions about what a room has or looks like.".to_string(),
+            parameters,
         }
     }
+
+    async fn call(&self, args: Self::Args) -> Result<Self::Output, Self::Error> {
+        let room_id = Uuid::parse_str(&args.room_id)
+            .map_err(|e| ToolError::InvalidInput(format!("Invalid room ID: {}", e)))?;
+
+        let room = RoomService::new(self.pool.clone())
+            .get_room_by_id(room_id)
+            .map_err(|e| ToolError::NotFound(format!("Failed to get room details: {}", e)))?;
+
+        Ok(cap_tool_output(room_details_text(&room), MAX_TOOL_OUTPUT_CHARS))
+    }
 }
 
 /// Tool for creating a booking proposal
 #[derive(Debug, Clone)]
 struct CreateBookingProposalTool {
     pool: DbPool,
+    /// The guest in the conversation, who would confirm the booking
+    user_id: Uuid,
 }
 
 impl Tool for CreateBookingProposalTool {
@@ -159,6 +286,14 @@ impl Tool for CreateBookingProposalTool {
     }
 
     async fn call(&self, args: Self::Args) -> Result<Self::Output, Self::Error> {
+        // A proposal the guest cannot confirm is no use to them
+        EmailVerificationService::new(self.pool.clone())
+            .ensure_verified(self.user_id)
+            .map_err(|e| match e {
+                AppError::ValidationError(msg) => ToolError::InvalidInput(msg),
+                other => ToolError::Database(other.to_string()),
+            })?;
+
         // Parse room ID
         let room_id = Uuid::parse_str(&args.room_id)
             .map_err(|e| ToolError::InvalidInput(format!("Invalid room ID: {}", e)))?;
@@ -180,13 +315,24 @@ impl Tool for CreateBookingProposalTool {
             return Err(ToolError::InvalidInput("Check-out date must be after check-in date".to_string()));
         }
 
-        let total_price = &room.price * BigDecimal::from(nights);
+        // Same date rules as a booking made at the desk, including stay length
+        BookingService::new(self.pool.clone())
+            .validate_dates(check_in, check_out)
+            .map_err(|e| match e {
+                AppError::ValidationError(msg) => ToolError::InvalidInput(msg),
+                other => ToolError::Database(other.to_string()),
+            })?;
+
+        // Each night at its own price, so seasonal rules are included
+        let total_price = PricingService::new(self.pool.clone())
+            .price_for_stay(&room, check_in, check_out)
+            .map_err(|e| ToolError::Database(e.to_string()))?;
 
         // Create booking proposal JSON
         let proposal = serde_json::json!({
             "room_id": room.id.to_string(),
             "room_number": room.number,
-            "room_type": format!("{:?}", room.room_type).to_lowercase(),
+            "room_type": room.room_type.to_string(),
             "check_in_date": args.check_in_date,
             "check_out_date": args.check_out_date,
             "total_price": total_price.to_string(),
@@ -204,40 +350,52 @@ impl AiService {
         Self { pool }
     }
 
-    /// Load settings from DB
-    fn get_settings(&self) -> HashMap<String, String> {
-        let mut conn = self.pool.get().expect("Failed to get DB connection");
-        let settings = system_settings::table
-            .load::<crate::models::setting::SystemSetting>(&mut conn)
-            .unwrap_or_default();
-        
-        settings.into_iter().map(|s| (s.key, s.value)).collect()
+    /// Load settings from DB; `None` when no connection is available
+    fn get_settings(&self) -> Option<Settings> {
+        let mut conn = match self.pool.get() {
+            Ok(conn) => conn,
+            Err(e) => {
+                error!("Failed to get DB connection for AI settings: {}", e);
+                return None;
+            }
+        };
+        Some(Settings::load(&mut conn).unwrap_or_default())
     }
 
     pub async fn generate_reply(&self, user_id: Uuid, user_name: &str, user_message: &str) -> Option<String> {
-        let settings = self.get_settings();
+        // Without the database there is no reply rather than a panic
+        let settings = self.get_settings()?;
 
         // Check if AI is enabled
-        if settings.get("ai_enabled").map(|s| s.as_str()) != Some("true") {
+        if !settings.bool(settings::AI_ENABLED) {
             return None;
         }
 
-        let api_key = settings.get("ai_api_key").cloned().unwrap_or_default();
+        let api_key = settings.text(settings::AI_API_KEY);
         if api_key.is_empty() {
             error!("AI is enabled but API key is missing");
             return Some("I'm having trouble connecting to my brain (API Key missing).".to_string());
         }
 
-        let provider = settings.get("ai_provider").map(|s| s.as_str()).unwrap_or("openai");
-        let model_name = settings.get("ai_model").cloned().unwrap_or_else(|| "gpt-3.5-turbo".to_string());
+        let provider = settings.text(settings::AI_PROVIDER);
+        let model_name = settings.text(settings::AI_MODEL);
         
         // Fetch recent chat history
-        let mut conn = self.pool.get().expect("Failed to get DB connection");
+        let mut conn = match self.pool.get() {
+            Ok(conn) => conn,
+            Err(e) => {
+                error!("Failed to get DB connection for chat history: {}", e);
+                return None;
+            }
+        };
         let history = messages::table
             .filter(
                 (messages::sender_id.eq(user_id).and(messages::receiver_id.eq(crate::api::chat::PUPINN_ID)))
                 .or(messages::sender_id.eq(crate::api::chat::PUPINN_ID).and(messages::receiver_id.eq(user_id)))
             )
+            // Deleted messages must not be fed back to the model
+            .filter(messages::content.ne(DELETED_MESSAGE_CONTENT))
+            .filter(messages::deleted_at.is_null())
             .order(messages::created_at.desc())
             .limit(10)
             .load::<Message>(&mut conn)
@@ -263,7 +421,8 @@ impl AiService {
             YOUR CAPABILITIES: \
             You have access to the following tools: \
             1. search_available_rooms: Search for available rooms by date range and optional room type \
-            2. create_booking_proposal: Create a booking proposal that the user can confirm or cancel \
+            2. get_room_details: Get a room's description, amenities and photos by room_id \
+            3. create_booking_proposal: Create a booking proposal that the user can confirm or cancel \
             
             BOOKING WORKFLOW: \
             1. When a user wants to book a room, gather the following information through conversation: \
@@ -285,6 +444,9 @@ impl AiService {
             - If no rooms are available, suggest alternative dates \
             - After creating a booking proposal, include the tool's BOOKING_PROPOSAL output in your response, then add a friendly message \
             - If user cancels a proposal, ask why and offer alternatives \
+            - For questions about what a room has (balcony, view, bathtub, etc.), use get_room_details and answer only from its output. \
+              Facts from tools override the general hotel information above; if a detail is not listed, say you are not sure rather than guessing \
+            - To show a photo, copy one ROOM_PHOTO: line from get_room_details exactly, on its own line \
             
             Here is the recent conversation history:\n\
             {}\n\
@@ -296,9 +458,13 @@ impl AiService {
 
         // Create tools
         let search_tool = SearchRoomsTool { pool: self.pool.clone() };
-        let booking_tool = CreateBookingProposalTool { pool: self.pool.clone() };
+        let details_tool = GetRoomDetailsTool { pool: self.pool.clone() };
+        let booking_tool = CreateBookingProposalTool {
+            pool: self.pool.clone(),
+            user_id,
+        };
 
-        let result = match provider {
+        let result = match provider.as_str() {
             "gemini" => {
                 let client = match gemini::Client::new(&api_key) {
                     Ok(c) => c,
@@ -308,6 +474,7 @@ impl AiService {
                     .agent(&model_name)
                     .preamble(&preamble)
                     .tool(search_tool)
+                    .tool(details_tool)
                     .tool(booking_tool)
                     .build();
                 
@@ -315,8 +482,8 @@ impl AiService {
             },
             _ => {
                 // Default to OpenAI or compatible
-                let base_url = settings.get("ai_base_url").map(|s| s.as_str()).unwrap_or("https://api.openai.com/v1");
-                
+                let base_url = settings.text(settings::AI_BASE_URL);
+
                 if base_url != "https://api.openai.com/v1" {
                     tracing::warn!("Custom AI Base URL '{}' found but temporarily ignored due to library limitation. Please set OPENAI_API_BASE env var if possible.", base_url);
                 }
@@ -330,6 +497,7 @@ impl AiService {
                     .agent(&model_name)
                     .preamble(&preamble)
                     .tool(search_tool)
+                    .tool(details_tool)
                     .tool(booking_tool)
                     .build();
 
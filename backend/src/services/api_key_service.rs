@@ -0,0 +1,136 @@
+use chrono::{DateTime, Duration, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::errors::{AppError, AppResult};
+use crate::models::{ApiKey, NewApiKey, UserRole};
+use crate::schema::{api_keys, users};
+use crate::services::AuthService;
+
+/// Marks a string as a Pupinn API key
+pub const API_KEY_PREFIX: &str = "pk_";
+
+/// Roles an API key may have. Keys cannot manage users or other keys.
+pub const API_KEY_ROLES: &[UserRole] = &[UserRole::Receptionist, UserRole::Cleaner];
+
+/// `last_used_at` is written at most this often, so a polling integration
+/// does not write on every request
+pub const LAST_USED_INTERVAL: Duration = Duration::minutes(1);
+
+/// Refusal for a key that is unknown or revoked
+pub const INVALID_API_KEY: &str = "Invalid or revoked API key";
+
+/// Create API key request payload
+#[derive(Debug, Deserialize)]
+pub struct CreateApiKeyRequest {
+    pub label: String,
+    pub role: UserRole,
+}
+
+/// A new API key with its plaintext, which is not shown again
+#[derive(Debug, Serialize)]
+pub struct CreatedApiKey {
+    #[serde(flatten)]
+    pub api_key: ApiKey,
+    pub key: String,
+}
+
+/// API keys for machine-to-machine integrations
+pub struct ApiKeyService {
+    pool: DbPool,
+}
+
+impl ApiKeyService {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    fn conn(&self) -> AppResult<crate::db::DbConn> {
+        self.pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
+    /// Create a key acting with `role` on behalf of `created_by`
+    pub fn create(&self, request: &CreateApiKeyRequest, created_by: Uuid) -> AppResult<CreatedApiKey> {
+        let label = request.label.trim();
+        if label.is_empty() {
+            return Err(AppError::ValidationError("Label is required".to_string()));
+        }
+        if label.len() > 100 {
+            return Err(AppError::ValidationError(
+                "Label must be 100 characters or less".to_string(),
+            ));
+        }
+        if !API_KEY_ROLES.contains(&request.role) {
+            return Err(AppError::ValidationError(
+                "API keys may only have the receptionist or cleaner role".to_string(),
+            ));
+        }
+
+        let key = format!("{}{}", API_KEY_PREFIX, AuthService::random_token());
+        let mut conn = self.conn()?;
+        let api_key = diesel::insert_into(api_keys::table)
+            .values(&NewApiKey {
+                label,
+                key_hash: AuthService::hash_token(&key),
+                key_prefix: key[..API_KEY_PREFIX.len() + 8].to_string(),
+                role: request.role,
+                created_by,
+            })
+            .get_result(&mut conn)?;
+        Ok(CreatedApiKey { api_key, key })
+    }
+
+    /// All keys, newest first, including revoked ones
+    pub fn list(&self) -> AppResult<Vec<ApiKey>> {
+        let mut conn = self.conn()?;
+        Ok(api_keys::table
+            .order(api_keys::created_at.desc())
+            .load(&mut conn)?)
+    }
+
+    /// Revoke a key; revoking it again keeps the first revocation time
+    pub fn revoke(&self, id: Uuid) -> AppResult<ApiKey> {
+        let mut conn = self.conn()?;
+        diesel::update(api_keys::table.find(id).filter(api_keys::revoked_at.is_null()))
+            .set(api_keys::revoked_at.eq(Utc::now()))
+            .execute(&mut conn)?;
+        api_keys::table
+            .find(id)
+            .first(&mut conn)
+            .optional()?
+            .ok_or_else(|| AppError::NotFound("API key not found".to_string()))
+    }
+
+    /// Find the key a request presents. Keys stop working when revoked or
+    /// when the admin who created them is deactivated.
+    pub fn authenticate(&self, key: &str, now: DateTime<Utc>) -> AppResult<ApiKey> {
+        let mut conn = self.conn()?;
+        let api_key: ApiKey = api_keys::table
+            .inner_join(users::table)
+            .filter(api_keys::key_hash.eq(AuthService::hash_token(key)))
+            .filter(api_keys::revoked_at.is_null())
+            .filter(users::deactivated_at.is_null())
+            .select(ApiKey::as_select())
+            .first(&mut conn)
+            .optional()?
+            .ok_or_else(|| AppError::Unauthorized(INVALID_API_KEY.to_string()))?;
+
+        if api_key
+            .last_used_at
+            .map_or(true, |last_used| now - last_used >= LAST_USED_INTERVAL)
+        {
+            // A failed write only makes the key look less recently used
+            if let Err(e) = diesel::update(api_keys::table.find(api_key.id))
+                .set(api_keys::last_used_at.eq(now))
+                .execute(&mut conn)
+            {
+                tracing::warn!("Could not record API key use: {}", e);
+            }
+        }
+        Ok(api_key)
+    }
+}
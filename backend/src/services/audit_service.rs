@@ -0,0 +1,41 @@
+use diesel::prelude::*;
+use diesel::result::QueryResult;
+use uuid::Uuid;
+
+use crate::models::NewAuditLog;
+use crate::schema::audit_logs;
+
+/// Audit service for recording sensitive admin actions
+pub struct AuditService;
+
+impl AuditService {
+    /// Record an audit entry on the caller's connection, so it commits or
+    /// rolls back together with the action it describes
+    ///
+    /// # Arguments
+    /// * `actor_user_id` - User who performed the action (None for system jobs)
+    /// * `action` - Dotted action name, e.g. "read_only_mode.enabled"
+    /// * `entity_type` - Kind of entity affected
+    /// * `entity_id` - Identifier of the affected entity, if any
+    /// * `details` - Free-form context such as the admin's reason
+    pub fn record(
+        conn: &mut PgConnection,
+        actor_user_id: Option<Uuid>,
+        action: &str,
+        entity_type: &str,
+        entity_id: Option<&str>,
+        details: Option<&str>,
+    ) -> QueryResult<()> {
+        diesel::insert_into(audit_logs::table)
+            .values(&NewAuditLog {
+                actor_user_id,
+                action,
+                entity_type,
+                entity_id,
+                details,
+            })
+            .execute(conn)?;
+
+        Ok(())
+    }
+}
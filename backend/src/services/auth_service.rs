@@ -1,18 +1,167 @@
 use argon2::{
     password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
-    Argon2,
+    Algorithm, Argon2, Params, Version,
 };
+use std::sync::{Arc, OnceLock};
+
 use chrono::{DateTime, Duration, Utc};
 use diesel::prelude::*;
+use diesel::sql_types::{Nullable, Text};
+use jsonwebtoken::errors::ErrorKind;
 use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use rand::rngs::OsRng as RandOsRng;
+use rand::RngCore;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use uuid::Uuid;
 
 use crate::db::DbPool;
 use crate::errors::{AppError, AppResult};
-use crate::models::{GuestInfo, NewGuestUser, NewUser, UpdateUser, User, UserInfo, UserRole};
+use crate::models::{
+    GuestInfo, NewGuestUser, NewRefreshToken, NewUser, RefreshToken, UpdateUser, User, UserInfo, UserRole,
+};
 // We import the users module, but NOT dsl::* to avoid variable name conflicts
-use crate::schema::users;
+use crate::schema::{refresh_tokens, users};
+use crate::services::session_service::{SESSION_EXPIRED_MESSAGE, SESSION_MAX_AGE_HOURS};
+use crate::services::token_revocation_service::{RevocationCache, TokenRevocationService};
+use crate::services::{PasswordResetService, SessionService};
+use crate::utils::validate_phone;
+
+/// Access token lifetime used when ACCESS_TOKEN_LIFETIME_MINUTES is not set
+/// (one shift)
+pub const DEFAULT_ACCESS_TOKEN_LIFETIME: Duration = Duration::hours(8);
+
+/// A login can be kept alive by refreshing for as long as its session may
+/// last; rotated tokens keep the expiry of the first one
+pub const REFRESH_TOKEN_LIFETIME: Duration = Duration::hours(SESSION_MAX_AGE_HOURS);
+
+/// The only error a failed guest login gets, so it does not tell whether an
+/// account exists for the email
+pub const GUEST_LOGIN_FAILED: &str = "Invalid email or password";
+
+/// Refusal for a user who must change the password an admin set
+pub const PASSWORD_CHANGE_REQUIRED_MESSAGE: &str = "You must change your password before continuing";
+
+/// Refusal for creating, promoting or reactivating a second admin
+pub const SINGLE_ADMIN_MESSAGE: &str =
+    "Only one admin account is allowed in the system. An admin account already exists.";
+
+/// Refusal for deactivating or demoting the only admin
+pub const LAST_ADMIN_MESSAGE: &str =
+    "Cannot delete the last admin account. The system must have at least one active admin account.";
+
+/// Issuer and audience of tokens when JWT_ISSUER is not set
+pub const DEFAULT_JWT_ISSUER: &str = "pupinn";
+
+/// How long after a deploy tokens issued without `iss` and `aud` are still
+/// accepted, while the grace is on
+pub const LEGACY_TOKEN_GRACE: Duration = Duration::hours(24);
+
+define_sql_function!(fn lower(x: Nullable<Text>) -> Nullable<Text>);
+
+/// Argon2id cost of new password hashes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PasswordHashing {
+    /// Memory per hash in KiB
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
+
+impl Default for PasswordHashing {
+    /// The argon2 crate's defaults, which produced the existing hashes
+    fn default() -> Self {
+        Self {
+            memory_kib: Params::DEFAULT_M_COST,
+            iterations: Params::DEFAULT_T_COST,
+            parallelism: Params::DEFAULT_P_COST,
+        }
+    }
+}
+
+impl PasswordHashing {
+    /// Argon2 parameters, refusing a combination argon2 does not support
+    pub fn params(&self) -> AppResult<Params> {
+        Params::new(self.memory_kib, self.iterations, self.parallelism, None)
+            .map_err(|e| AppError::InternalError(format!("Invalid password hashing parameters: {}", e)))
+    }
+
+    /// Whether a stored hash is weaker than this cost in any parameter, or
+    /// not Argon2id at all, and should be replaced on the next login.
+    /// Hashes that cannot be parsed are left alone; they never verify.
+    pub fn needs_rehash(&self, hash: &str) -> bool {
+        let Ok(parsed) = PasswordHash::new(hash) else {
+            return false;
+        };
+        if parsed.algorithm != Algorithm::Argon2id.ident() || parsed.version != Some(Version::V0x13.into()) {
+            return true;
+        }
+        match Params::try_from(&parsed) {
+            Ok(stored) => {
+                stored.m_cost() < self.memory_kib
+                    || stored.t_cost() < self.iterations
+                    || stored.p_cost() < self.parallelism
+            }
+            Err(_) => true,
+        }
+    }
+}
+
+/// Cost of new password hashes, set once at startup
+static PASSWORD_HASHING: OnceLock<PasswordHashing> = OnceLock::new();
+
+/// The deployment tokens are issued by and for. Tokens carry it as both
+/// `iss` and `aud`, so another deployment sharing the secret but not the
+/// issuer (staging, say) cannot mint tokens this one accepts.
+#[derive(Debug, Clone)]
+pub struct TokenIssuer {
+    pub issuer: String,
+    /// Start of the legacy grace: tokens issued before then without `iss`
+    /// and `aud` are accepted for `LEGACY_TOKEN_GRACE` after it. `None`
+    /// refuses them.
+    pub legacy_grace_from: Option<DateTime<Utc>>,
+}
+
+impl TokenIssuer {
+    /// Issuer refusing tokens without `iss` and `aud`
+    pub fn new(issuer: impl Into<String>) -> Self {
+        Self {
+            issuer: issuer.into(),
+            legacy_grace_from: None,
+        }
+    }
+
+    /// Accept tokens issued before `deployed_at` without `iss` and `aud`
+    /// until `LEGACY_TOKEN_GRACE` after it
+    pub fn with_legacy_grace(mut self, deployed_at: DateTime<Utc>) -> Self {
+        self.legacy_grace_from = Some(deployed_at);
+        self
+    }
+
+    /// Validation requiring this issuer and audience
+    fn validation(&self) -> Validation {
+        let mut validation = Validation::default();
+        validation.set_issuer(&[&self.issuer]);
+        validation.set_audience(&[&self.issuer]);
+        validation.set_required_spec_claims(&["exp", "iss", "aud"]);
+        validation
+    }
+
+    /// Whether a token without `iss` and `aud` still falls in the grace
+    fn accepts_legacy(&self, claims: &Claims, now: DateTime<Utc>) -> bool {
+        claims.iss.is_none()
+            && claims.aud.is_none()
+            && self
+                .legacy_grace_from
+                .is_some_and(|deployed_at| claims.iat <= deployed_at.timestamp() && now < deployed_at + LEGACY_TOKEN_GRACE)
+    }
+}
+
+impl Default for TokenIssuer {
+    fn default() -> Self {
+        Self::new(DEFAULT_JWT_ISSUER)
+    }
+}
 
 /// JWT claims structure
 #[derive(Debug, Serialize, Deserialize)]
@@ -21,6 +170,25 @@ pub struct Claims {
     pub role: UserRole,   // User role
     pub exp: i64,         // Expiration timestamp
     pub iat: i64,         // Issued at timestamp
+    /// Session id for the idle timeout; absent on tokens issued before
+    /// sessions were tracked
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sid: Option<Uuid>,
+    /// Token id, for revoking this token alone; absent on tokens issued
+    /// before single tokens could be revoked
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jti: Option<Uuid>,
+    /// The user must change their password before using anything else;
+    /// absent on tokens issued before the flag existed
+    #[serde(default)]
+    pub must_change_password: bool,
+    /// Issuing deployment; absent on tokens issued before it was checked
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iss: Option<String>,
+    /// Deployment the token is for; absent on tokens issued before it was
+    /// checked
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aud: Option<String>,
 }
 
 /// Login request payload
@@ -34,7 +202,25 @@ pub struct LoginRequest {
 #[derive(Debug, Serialize)]
 pub struct LoginResponse {
     pub token: String,
+    /// Exchanged at /auth/refresh for the next access token
+    pub refresh_token: String,
     pub user: UserInfo,
+    /// Only changing the password is allowed until it is done
+    pub must_change_password: bool,
+}
+
+/// Refresh and logout request payload
+#[derive(Debug, Deserialize)]
+pub struct RefreshRequest {
+    pub refresh_token: String,
+}
+
+/// Refresh response payload: a new access token and the refresh token that
+/// replaces the one sent
+#[derive(Debug, Serialize)]
+pub struct RefreshResponse {
+    pub token: String,
+    pub refresh_token: String,
 }
 
 /// Create user request payload
@@ -43,6 +229,9 @@ pub struct CreateUserRequest {
     pub username: String,
     pub password: String,
     pub role: UserRole,
+    /// Make the user choose their own password on first login
+    #[serde(default)]
+    pub must_change_password: bool,
 }
 
 /// Guest registration request payload
@@ -74,34 +263,111 @@ pub struct ChangePasswordRequest {
     pub new_password: String,
 }
 
+/// Changes a user may make to their own profile. Guests may set both
+/// fields; staff may only rename themselves.
+#[derive(Debug, Default, Deserialize)]
+pub struct UpdateProfileRequest {
+    pub full_name: Option<String>,
+    /// An empty phone number clears it
+    pub phone: Option<String>,
+}
 
 /// Authentication service for user management and JWT operations
 pub struct AuthService {
     pool: DbPool,
     jwt_secret: String,
-    token_expiry_hours: i64,
+    token_lifetime: Duration,
+    issuer: TokenIssuer,
+    revocations: Arc<RevocationCache>,
 }
 
 impl AuthService {
     /// Create a new AuthService instance
     pub fn new(pool: DbPool, jwt_secret: String) -> Self {
+        Self::with_token_lifetime(pool, jwt_secret, DEFAULT_ACCESS_TOKEN_LIFETIME)
+    }
+
+    /// Create an AuthService issuing access tokens valid for `token_lifetime`
+    pub fn with_token_lifetime(pool: DbPool, jwt_secret: String, token_lifetime: Duration) -> Self {
         Self {
             pool,
             jwt_secret,
-            token_expiry_hours: 8, // 8-hour token expiry (single shift)
+            token_lifetime,
+            issuer: TokenIssuer::default(),
+            revocations: Arc::new(RevocationCache::default()),
         }
     }
 
-    /// Hash a password using Argon2id
+    /// Issue and accept tokens of this deployment
+    pub fn with_issuer(mut self, issuer: TokenIssuer) -> Self {
+        self.issuer = issuer;
+        self
+    }
+
+    /// Check token revocations through a cache shared across requests
+    pub fn with_revocations(mut self, revocations: Arc<RevocationCache>) -> Self {
+        self.revocations = revocations;
+        self
+    }
+
+    /// Revocation of this service's users' access tokens
+    pub fn revocation_service(&self) -> TokenRevocationService {
+        TokenRevocationService::new(self.pool.clone(), self.revocations.clone())
+    }
+
+    /// Hash new passwords at `hashing` cost from now on. Only the first call
+    /// counts, so make it at startup before anything is hashed.
+    pub fn configure_password_hashing(hashing: PasswordHashing) {
+        if PASSWORD_HASHING.set(hashing).is_err() {
+            tracing::warn!("Password hashing was already configured; keeping {:?}", Self::password_hashing());
+        }
+    }
+
+    /// Cost of new password hashes
+    pub fn password_hashing() -> PasswordHashing {
+        *PASSWORD_HASHING.get_or_init(PasswordHashing::default)
+    }
+
+    /// Hash a password using Argon2id at the configured cost
     pub fn hash_password(password: &str) -> AppResult<String> {
+        Self::hash_password_with(password, &Self::password_hashing())
+    }
+
+    /// Hash a password using Argon2id at `hashing` cost
+    pub fn hash_password_with(password: &str, hashing: &PasswordHashing) -> AppResult<String> {
         let salt = SaltString::generate(&mut OsRng);
-        let argon2 = Argon2::default();
+        let argon2 = Argon2::new(Algorithm::Argon2id, Version::V0x13, hashing.params()?);
         let hash = argon2
             .hash_password(password.as_bytes(), &salt)
             .map_err(|e| AppError::InternalError(format!("Password hashing failed: {}", e)))?;
         Ok(hash.to_string())
     }
 
+    /// Replace a just-verified password's stored hash when it is weaker than
+    /// the configured cost. The login has already succeeded, so a failure is
+    /// only logged.
+    fn upgrade_password_hash_on(conn: &mut PgConnection, user: &User, password: &str) {
+        let hashing = Self::password_hashing();
+        if !hashing.needs_rehash(&user.password_hash) {
+            return;
+        }
+
+        let upgraded = Self::hash_password_with(password, &hashing).and_then(|hash| {
+            // Only the hash that was verified is replaced, not one changed since
+            diesel::update(
+                users::table
+                    .find(user.id)
+                    .filter(users::password_hash.eq(&user.password_hash)),
+            )
+            .set(users::password_hash.eq(hash))
+            .execute(conn)?;
+            Ok(())
+        });
+        if let Err(e) = upgraded {
+            tracing::warn!("Could not upgrade the password hash of user {}: {}", user.id, e);
+        }
+    }
+
     /// Verify a password against a hash
     pub fn verify_password(password: &str, hash: &str) -> AppResult<bool> {
         let parsed_hash = PasswordHash::new(hash)
@@ -111,16 +377,27 @@ impl AuthService {
             .is_ok())
     }
 
-    /// Generate a JWT token for a user
+    /// Generate a JWT token for a user, starting a new session
     pub fn generate_token(&self, user: &User) -> AppResult<String> {
+        let session_id = SessionService::new(self.pool.clone()).start(user.id)?;
+        self.session_token(user, session_id)
+    }
+
+    /// JWT token for a user in an existing session
+    fn session_token(&self, user: &User, session_id: Uuid) -> AppResult<String> {
         let now_utc = Utc::now();
-        let exp_time = now_utc + Duration::hours(self.token_expiry_hours);
+        let exp_time = now_utc + self.token_lifetime;
 
         let claims = Claims {
             sub: user.id,
             role: user.role,
             exp: exp_time.timestamp(),
             iat: now_utc.timestamp(),
+            sid: Some(session_id),
+            jti: Some(Uuid::new_v4()),
+            must_change_password: user.must_change_password,
+            iss: Some(self.issuer.issuer.clone()),
+            aud: Some(self.issuer.issuer.clone()),
         };
 
         encode(
@@ -131,14 +408,25 @@ impl AuthService {
         .map_err(|e| AppError::InternalError(format!("Token generation failed: {}", e)))
     }
 
-    /// Validate and decode a JWT token
+    /// Validate and decode a JWT token of this deployment, refusing revoked
+    /// ones. Tokens without `iss` and `aud` only pass during the legacy grace.
     pub fn validate_token(&self, token: &str) -> AppResult<Claims> {
-        let token_data = decode::<Claims>(
-            token,
-            &DecodingKey::from_secret(self.jwt_secret.as_bytes()),
-            &Validation::default(),
-        )?;
-        Ok(token_data.claims)
+        let key = DecodingKey::from_secret(self.jwt_secret.as_bytes());
+        let claims = match decode::<Claims>(token, &key, &self.issuer.validation()) {
+            Ok(token_data) => token_data.claims,
+            Err(e) if matches!(e.kind(), ErrorKind::MissingRequiredClaim(_)) => {
+                let mut legacy = Validation::default();
+                legacy.validate_aud = false;
+                let claims = decode::<Claims>(token, &key, &legacy)?.claims;
+                if !self.issuer.accepts_legacy(&claims, Utc::now()) {
+                    return Err(e.into());
+                }
+                claims
+            }
+            Err(e) => return Err(e.into()),
+        };
+        self.revocation_service().check(&claims)?;
+        Ok(claims)
     }
 
     /// Login a user with username and password
@@ -152,9 +440,10 @@ impl AuthService {
         let username_input = request.username.trim();
         tracing::debug!("Login attempt for username: '{}'", username_input);
 
-        // First, try to find the user
+        // First, try to find the user; guests sign in by email only
         let user_opt: Option<User> = users::table
             .filter(users::username.eq(&username_input))
+            .filter(users::role.ne(UserRole::Guest))
             .first(&mut conn)
             .optional()
             .map_err(|e| {
@@ -195,14 +484,181 @@ impl AuthService {
         
         tracing::debug!("Login successful for user '{}' (role: {:?})", username_input, user.role);
 
-        let token = self.generate_token(&user)?;
+        Self::upgrade_password_hash_on(&mut conn, &user, &request.password);
+        Self::record_login_on(&mut conn, user.id)?;
+        let session_id = SessionService::new(self.pool.clone()).start(user.id)?;
+        let token = self.session_token(&user, session_id)?;
+        let refresh_token = Self::insert_refresh_token(
+            &mut conn,
+            user.id,
+            Uuid::new_v4(),
+            session_id,
+            Utc::now() + REFRESH_TOKEN_LIFETIME,
+        )?;
 
         Ok(LoginResponse {
             token,
+            refresh_token,
+            must_change_password: user.must_change_password,
             user: user.into(),
         })
     }
 
+    /// Exchange a refresh token for a new access token in the same session
+    /// and the next refresh token of its family. Sending a token that was
+    /// already exchanged or logged out revokes the whole family and ends the
+    /// session, so a stolen token stops working for the thief and the owner
+    /// alike.
+    pub fn refresh(&self, refresh_token: &str) -> AppResult<RefreshResponse> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        let token_hash = Self::hash_token(refresh_token);
+
+        // Revoking the token and issuing its successor commit together; the
+        // conditional update lets only one of two concurrent exchanges win
+        let rotated = conn.transaction::<_, AppError, _>(|conn| {
+            let now = Utc::now();
+            let Some(current) = diesel::update(
+                refresh_tokens::table
+                    .filter(refresh_tokens::token_hash.eq(&token_hash))
+                    .filter(refresh_tokens::revoked_at.is_null())
+                    .filter(refresh_tokens::expires_at.gt(now)),
+            )
+            .set(refresh_tokens::revoked_at.eq(now))
+            .get_result::<RefreshToken>(conn)
+            .optional()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?
+            else {
+                return Ok(None);
+            };
+
+            let next = Self::insert_refresh_token(
+                conn,
+                current.user_id,
+                current.family_id,
+                current.session_id,
+                current.expires_at,
+            )?;
+            Ok(Some((current, next)))
+        })?;
+
+        let Some((current, refresh_token)) = rotated else {
+            return Err(self.refresh_rejection(&mut conn, &token_hash)?);
+        };
+
+        let user = self.get_user_by_id(current.user_id)?;
+        if user.deactivated_at.is_some() {
+            self.revoke_family(&mut conn, &current)?;
+            return Err(AppError::Unauthorized("Account is deactivated".to_string()));
+        }
+
+        // The session may have ended on its idle timeout meanwhile
+        if SessionService::new(self.pool.clone()).last_seen(current.session_id)?.is_none() {
+            self.revoke_family(&mut conn, &current)?;
+            return Err(AppError::SessionExpired(SESSION_EXPIRED_MESSAGE.to_string()));
+        }
+
+        Ok(RefreshResponse {
+            token: self.session_token(&user, current.session_id)?,
+            refresh_token,
+        })
+    }
+
+    /// Log out the login behind a refresh token: its family is revoked and
+    /// its session ended, so neither the refresh token nor the access tokens
+    /// issued with it work any longer. Unknown tokens are ignored.
+    pub fn logout(&self, refresh_token: &str) -> AppResult<()> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let token: Option<RefreshToken> = refresh_tokens::table
+            .filter(refresh_tokens::token_hash.eq(Self::hash_token(refresh_token)))
+            .first(&mut conn)
+            .optional()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        match token {
+            Some(token) => self.revoke_family(&mut conn, &token),
+            None => Ok(()),
+        }
+    }
+
+    /// Why a refresh token that could not be exchanged was refused. A token
+    /// already revoked is being reused, so its family is revoked as well.
+    fn refresh_rejection(&self, conn: &mut PgConnection, token_hash: &str) -> AppResult<AppError> {
+        let token: Option<RefreshToken> = refresh_tokens::table
+            .filter(refresh_tokens::token_hash.eq(token_hash))
+            .first(conn)
+            .optional()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(match token {
+            Some(token) if token.revoked_at.is_some() => {
+                tracing::warn!(
+                    "Revoked refresh token reused for user {}; revoking its family",
+                    token.user_id
+                );
+                self.revoke_family(conn, &token)?;
+                AppError::Unauthorized("Refresh token has been revoked".to_string())
+            }
+            Some(_) => AppError::Unauthorized("Refresh token has expired".to_string()),
+            None => AppError::Unauthorized("Invalid refresh token".to_string()),
+        })
+    }
+
+    /// Revoke every refresh token of a token's family and end its session
+    fn revoke_family(&self, conn: &mut PgConnection, token: &RefreshToken) -> AppResult<()> {
+        diesel::update(
+            refresh_tokens::table
+                .filter(refresh_tokens::family_id.eq(token.family_id))
+                .filter(refresh_tokens::revoked_at.is_null()),
+        )
+        .set(refresh_tokens::revoked_at.eq(Utc::now()))
+        .execute(conn)
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        SessionService::new(self.pool.clone()).end(token.session_id)
+    }
+
+    /// Store a new refresh token of a family and return the token itself
+    fn insert_refresh_token(
+        conn: &mut PgConnection,
+        user_id: Uuid,
+        family_id: Uuid,
+        session_id: Uuid,
+        expires_at: DateTime<Utc>,
+    ) -> AppResult<String> {
+        let token = Self::random_token();
+
+        diesel::insert_into(refresh_tokens::table)
+            .values(&NewRefreshToken {
+                user_id,
+                family_id,
+                session_id,
+                token_hash: Self::hash_token(&token),
+                expires_at,
+            })
+            .execute(conn)
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        Ok(token)
+    }
+
+    /// Opaque token of 32 random bytes, hex-encoded
+    pub(crate) fn random_token() -> String {
+        let mut bytes = [0u8; 32];
+        RandOsRng.fill_bytes(&mut bytes);
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    /// Hex SHA-256 of a refresh or password reset token, as stored
+    pub fn hash_token(token: &str) -> String {
+        format!("{:x}", Sha256::digest(token.trim().as_bytes()))
+    }
+
     /// Get user by ID
     pub fn get_user_by_id(&self, user_id: Uuid) -> AppResult<User> {
         let mut conn = self
@@ -216,6 +672,27 @@ impl AuthService {
             .map_err(|_| AppError::NotFound("User not found".to_string()))
     }
 
+    /// Refuse a token whose account was deactivated or deleted after it was
+    /// issued. Returns whether the account must change its password first.
+    pub fn ensure_active(&self, user_id: Uuid) -> AppResult<bool> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let (deactivated_at, must_change_password): (Option<DateTime<Utc>>, bool) = users::table
+            .find(user_id)
+            .select((users::deactivated_at, users::must_change_password))
+            .first(&mut conn)
+            .optional()?
+            .ok_or_else(|| AppError::Unauthorized("Account no longer exists".to_string()))?;
+
+        if deactivated_at.is_some() {
+            return Err(AppError::Forbidden("Account is deactivated".to_string()));
+        }
+        Ok(must_change_password)
+    }
+
     /// Check the count of active admin users
     pub fn check_admin_count(&self) -> AppResult<u64> {
         let mut conn = self
@@ -233,44 +710,40 @@ impl AuthService {
         Ok(count as u64)
     }
 
-    /// Validate single admin constraint
-    pub fn validate_single_admin_constraint(
-        &self,
-        new_role: UserRole,
-        existing_user_id: Option<Uuid>,
-    ) -> AppResult<()> {
-        // Only check constraint if trying to create/assign admin role
+    /// Lock the active admin rows and return their ids. Every change to who
+    /// is admin takes this lock first, so such changes apply one at a time.
+    /// The ids are read again once the lock is held: a transfer that
+    /// committed while this waited moved the role to a row the locking read
+    /// did not see.
+    fn lock_admins_on(conn: &mut PgConnection) -> AppResult<Vec<Uuid>> {
+        let active_admins = users::table
+            .filter(users::role.eq(UserRole::Admin))
+            .filter(users::deactivated_at.is_null());
+        active_admins.clone().select(users::id).for_update().load::<Uuid>(conn)?;
+        Ok(active_admins.select(users::id).load(conn)?)
+    }
+
+    /// Refuse making a second active admin, whether a new account
+    /// (`user_id` of `None`) or an existing one. The caller's transaction
+    /// holds the admin lock until it commits.
+    fn check_single_admin_on(conn: &mut PgConnection, new_role: UserRole, user_id: Option<Uuid>) -> AppResult<()> {
         if new_role != UserRole::Admin {
             return Ok(());
         }
 
-        let admin_count = self.check_admin_count()?;
-
-        // If updating an existing user, check if they're already an admin
-        if let Some(user_id) = existing_user_id {
-            let mut conn = self
-                .pool
-                .get()
-                .map_err(|e| AppError::DatabaseError(e.to_string()))?;
-
-            let existing: User = users::table
-                .find(user_id)
-                .first(&mut conn)
-                .map_err(|_| AppError::NotFound("User not found".to_string()))?;
-
-            // If user is already an admin, allow the update (no constraint violation)
-            if existing.role == UserRole::Admin {
-                return Ok(());
-            }
+        let admins = Self::lock_admins_on(conn)?;
+        if admins.iter().any(|admin| Some(*admin) != user_id) {
+            return Err(AppError::Conflict(SINGLE_ADMIN_MESSAGE.to_string()));
         }
+        Ok(())
+    }
 
-        // If there's already an admin, prevent creating/assigning another one
-        if admin_count > 0 {
-            return Err(AppError::ValidationError(
-                "Only one admin account is allowed in the system. An admin account already exists.".to_string(),
-            ));
+    /// Refuse deactivating or demoting the last active admin
+    fn check_not_last_admin_on(conn: &mut PgConnection, user_id: Uuid) -> AppResult<()> {
+        let admins = Self::lock_admins_on(conn)?;
+        if admins.contains(&user_id) && admins.len() <= 1 {
+            return Err(AppError::Forbidden(LAST_ADMIN_MESSAGE.to_string()));
         }
-
         Ok(())
     }
 
@@ -292,9 +765,6 @@ impl AuthService {
             ));
         }
 
-        // Validate single admin constraint
-        self.validate_single_admin_constraint(request.role, None)?;
-
         let mut conn = self
             .pool
             .get()
@@ -323,12 +793,14 @@ impl AuthService {
             full_name: None,
             phone: None,
             id_number: None,
+            must_change_password: request.must_change_password,
         };
 
-        let user: User = diesel::insert_into(users::table)
-            .values(&new_user)
-            .get_result(&mut conn)
-            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        // The admin check holds its lock until the new account is in
+        let user: User = conn.transaction(|conn| {
+            Self::check_single_admin_on(conn, request.role, None)?;
+            Ok::<_, AppError>(diesel::insert_into(users::table).values(&new_user).get_result(conn)?)
+        })?;
 
         // Ensure the user is active (deactivated_at should be NULL)
         if user.deactivated_at.is_some() {
@@ -489,31 +961,17 @@ impl AuthService {
             .get()
             .map_err(|e| AppError::DatabaseError(e.to_string()))?;
 
-        // Look up user by email
-        let email_lower = request.email.trim().to_lowercase();
-        let user: User = users::table
-            .filter(users::email.eq(&email_lower))
-            .first(&mut conn)
-            .map_err(|_| AppError::Unauthorized("Invalid email or password".to_string()))?;
-
-        // Check if account is deactivated
-        if user.deactivated_at.is_some() {
-            return Err(AppError::Unauthorized("Account is deactivated".to_string()));
-        }
+        let user = Self::guest_by_email(&mut conn, &request.email)?
+            .ok_or_else(|| AppError::Unauthorized(GUEST_LOGIN_FAILED.to_string()))?;
 
-        // Verify password
-        if !Self::verify_password(&request.password, &user.password_hash)? {
-            return Err(AppError::Unauthorized(
-                "Invalid email or password".to_string(),
-            ));
+        if !Self::verify_password(&request.password, &user.password_hash)? || user.deactivated_at.is_some() {
+            return Err(AppError::Unauthorized(GUEST_LOGIN_FAILED.to_string()));
         }
 
-        // Ensure user has guest role
-        if user.role != UserRole::Guest {
-            return Err(AppError::Unauthorized(
-                "Invalid email or password".to_string(),
-            ));
-        }
+        // A guest who remembered their password no longer needs a reset link
+        PasswordResetService::invalidate_on(&mut conn, user.id)?;
+        Self::upgrade_password_hash_on(&mut conn, &user, &request.password);
+        Self::record_login_on(&mut conn, user.id)?;
 
         // Generate JWT token
         let token = self.generate_token(&user)?;
@@ -528,10 +986,33 @@ impl AuthService {
         })
     }
 
+    /// Note a successful login on the account
+    fn record_login_on(conn: &mut PgConnection, user_id: Uuid) -> AppResult<()> {
+        diesel::update(users::table.find(user_id))
+            .set(users::last_login_at.eq(Some(Utc::now())))
+            .execute(conn)?;
+        Ok(())
+    }
+
+    /// Guest account with `email`, matched case-insensitively; stored emails
+    /// may not be lowercase if they were edited through guest management
+    pub(crate) fn guest_by_email(conn: &mut PgConnection, email: &str) -> AppResult<Option<User>> {
+        users::table
+            .filter(lower(users::email).eq(email.trim().to_lowercase()))
+            .filter(users::role.eq(UserRole::Guest))
+            .first(conn)
+            .optional()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
     /// Get guest user by ID
     pub fn get_guest_by_id(&self, user_id: Uuid) -> AppResult<GuestInfo> {
         let user = self.get_user_by_id(user_id)?;
 
+        if user.deactivated_at.is_some() {
+            return Err(AppError::Forbidden("Account is deactivated".to_string()));
+        }
+
         // Verify the user is a guest
         if user.role != UserRole::Guest {
             return Err(AppError::Forbidden(
@@ -660,49 +1141,20 @@ impl AuthService {
             }
         }
 
-        // Validate single admin constraint if role is being changed to admin
-        if let Some(new_role) = update.role {
-            self.validate_single_admin_constraint(new_role, Some(employee_id))?;
-        }
-
-        // Update user
-        let updated_user: User = diesel::update(users::table.find(employee_id))
-            .set(&update)
-            .get_result(&mut conn)
-            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        // Promoting a second admin or demoting the only one is refused; the
+        // admin role moves with `transfer_admin`
+        let updated_user: User = conn.transaction(|conn| {
+            match update.role {
+                Some(UserRole::Admin) => Self::check_single_admin_on(conn, UserRole::Admin, Some(employee_id))?,
+                Some(_) if existing.role == UserRole::Admin => Self::check_not_last_admin_on(conn, employee_id)?,
+                _ => {}
+            }
+            Ok::<_, AppError>(diesel::update(users::table.find(employee_id)).set(&update).get_result(conn)?)
+        })?;
 
         Ok(updated_user.into())
     }
 
-    /// Check if an admin can be deleted
-    pub fn check_can_delete_admin(&self, admin_id: Uuid) -> AppResult<()> {
-        let mut conn = self
-            .pool
-            .get()
-            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
-
-        // Verify user exists and is an admin
-        let user: User = users::table
-            .find(admin_id)
-            .first(&mut conn)
-            .map_err(|_| AppError::NotFound("Employee not found".to_string()))?;
-
-        if user.role != UserRole::Admin {
-            return Ok(());
-        }
-
-        // Check if this is the only active admin
-        let admin_count = self.check_admin_count()?;
-
-        if admin_count <= 1 {
-            return Err(AppError::Forbidden(
-                "Cannot delete the last admin account. The system must have at least one active admin account.".to_string(),
-            ));
-        }
-
-        Ok(())
-    }
-
     /// Delete (soft delete) an employee account
     pub fn delete_employee(&self, employee_id: Uuid) -> AppResult<()> {
         let mut conn = self
@@ -722,17 +1174,22 @@ impl AuthService {
             ));
         }
 
-        if employee.role == UserRole::Admin {
-            self.check_can_delete_admin(employee_id)?;
-        }
-
         println!(">>> FORCE DELETING USER: {:?}", employee_id);
 
 
-        let rows_affected = diesel::update(users::table.find(employee_id))
-                                    .set(users::deactivated_at.eq(Some(Utc::now())))
-                                    .execute(&mut conn)
-                                    .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        // The employee's outstanding tokens are revoked along with the account
+        let rows_affected = conn.transaction(|conn| {
+            if employee.role == UserRole::Admin {
+                Self::check_not_last_admin_on(conn, employee_id)?;
+            }
+            let now = Utc::now();
+            let rows_affected = diesel::update(users::table.find(employee_id))
+                .set(users::deactivated_at.eq(Some(now)))
+                .execute(conn)?;
+            TokenRevocationService::revoke_user_on(conn, employee_id, now)?;
+            Ok::<_, AppError>(rows_affected)
+        })?;
+        self.revocations.forget(employee_id);
 
         println!(">>> ROWS AFFECTED: {}", rows_affected);
 
@@ -769,16 +1226,16 @@ impl AuthService {
             ));
         }
 
-        // Validate single admin constraint if reactivating an admin
-        if employee.role == UserRole::Admin {
-            self.validate_single_admin_constraint(UserRole::Admin, None)?;
-        }
-
-        // Reactivate by setting deactivated_at to None
-        let rows_affected = diesel::update(users::table.find(employee_id))
-            .set(users::deactivated_at.eq(None::<DateTime<Utc>>))
-            .execute(&mut conn)
-            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        // Reactivate by setting deactivated_at to None; an admin only comes
+        // back while no other admin is active
+        let rows_affected = conn.transaction(|conn| {
+            Self::check_single_admin_on(conn, employee.role, None)?;
+            Ok::<_, AppError>(
+                diesel::update(users::table.find(employee_id))
+                    .set(users::deactivated_at.eq(None::<DateTime<Utc>>))
+                    .execute(conn)?,
+            )
+        })?;
 
         if rows_affected == 0 {
             return Err(AppError::NotFound("Update failed - ID not found".to_string()));
@@ -787,6 +1244,72 @@ impl AuthService {
         Ok(())
     }
 
+    /// Hand the admin role from the active admin to another active employee
+    /// in one transaction: the admin becomes a receptionist and the employee
+    /// the only admin. Both accounts' tokens are revoked, so their next login
+    /// carries the new roles.
+    ///
+    /// # Returns
+    /// The previous admin and the new admin, in that order
+    pub fn transfer_admin(&self, admin_id: Uuid, employee_id: Uuid) -> AppResult<(User, User)> {
+        if admin_id == employee_id {
+            return Err(AppError::ValidationError("You are already the admin".to_string()));
+        }
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let transferred = conn.transaction(|conn| {
+            let admins = Self::lock_admins_on(conn)?;
+            if !admins.contains(&admin_id) {
+                return Err(AppError::Forbidden(
+                    "Only the active admin can transfer the admin role".to_string(),
+                ));
+            }
+
+            let employee: User = users::table
+                .find(employee_id)
+                .for_update()
+                .first(conn)
+                .optional()?
+                .ok_or_else(|| AppError::NotFound("Employee not found".to_string()))?;
+            if employee.role == UserRole::Guest {
+                return Err(AppError::ValidationError(
+                    "The admin role can only be transferred to an employee".to_string(),
+                ));
+            }
+            if employee.deactivated_at.is_some() {
+                return Err(AppError::ValidationError(
+                    "Cannot transfer the admin role to a deactivated account".to_string(),
+                ));
+            }
+
+            // Demote first: the database refuses a second active admin
+            let now = Utc::now();
+            let previous: User = diesel::update(users::table.find(admin_id))
+                .set(users::role.eq(UserRole::Receptionist))
+                .get_result(conn)?;
+            let admin: User = diesel::update(users::table.find(employee_id))
+                .set(users::role.eq(UserRole::Admin))
+                .get_result(conn)?;
+            TokenRevocationService::revoke_user_on(conn, admin_id, now)?;
+            TokenRevocationService::revoke_user_on(conn, employee_id, now)?;
+            Ok((previous, admin))
+        })?;
+        self.revocations.forget(admin_id);
+        self.revocations.forget(employee_id);
+
+        Ok(transferred)
+    }
+
+    /// Revoke every access and refresh token an employee holds, signing them
+    /// out everywhere
+    pub fn revoke_employee_sessions(&self, employee_id: Uuid) -> AppResult<()> {
+        let employee = self.get_employee_by_id(employee_id)?;
+        self.revocation_service().revoke_user(employee.id)
+    }
+
     /// Reset an employee's password
     pub fn reset_password(&self, employee_id: Uuid, new_password: String) -> AppResult<()> {
         // Validate password length
@@ -817,9 +1340,12 @@ impl AuthService {
         // Hash new password
         let hashed_password = Self::hash_password(&new_password)?;
 
-        // Update password
+        // Update password; the employee must replace it with their own
         diesel::update(users::table.find(employee_id))
-            .set(users::password_hash.eq(&hashed_password))
+            .set((
+                users::password_hash.eq(&hashed_password),
+                users::must_change_password.eq(true),
+            ))
             .execute(&mut conn)
             .map_err(|e| AppError::DatabaseError(e.to_string()))?;
 
@@ -866,17 +1392,84 @@ impl AuthService {
             }
         }
 
+        if Self::verify_password(&request.new_password, &user.password_hash)? {
+            return Err(AppError::ValidationError(
+                "New password must be different from the current password".to_string(),
+            ));
+        }
+
         // Hash new password
         let hashed_password = Self::hash_password(&request.new_password)?;
 
-        // Update password
-        diesel::update(users::table.find(user_id))
-            .set(users::password_hash.eq(&hashed_password))
-            .execute(&mut conn)
-            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        // Update password; logins elsewhere can no longer be refreshed
+        conn.transaction(|conn| {
+            diesel::update(users::table.find(user_id))
+                .set((
+                    users::password_hash.eq(&hashed_password),
+                    users::must_change_password.eq(false),
+                ))
+                .execute(conn)?;
+            diesel::update(
+                refresh_tokens::table
+                    .filter(refresh_tokens::user_id.eq(user_id))
+                    .filter(refresh_tokens::revoked_at.is_null()),
+            )
+            .set(refresh_tokens::revoked_at.eq(Utc::now()))
+            .execute(conn)
+        })
+        .map_err(|e: diesel::result::Error| AppError::DatabaseError(e.to_string()))?;
 
         Ok(())
     }
+
+    /// Update the caller's own profile. Role, email and username stay with
+    /// the admin's employee management.
+    pub fn update_profile(&self, user_id: Uuid, request: &UpdateProfileRequest) -> AppResult<User> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let user: User = users::table
+            .find(user_id)
+            .first(&mut conn)
+            .map_err(|_| AppError::NotFound("User not found".to_string()))?;
+
+        if request.phone.is_some() && user.role != UserRole::Guest {
+            return Err(AppError::Forbidden(
+                "Staff may only update their full name".to_string(),
+            ));
+        }
+
+        let full_name = match request.full_name.as_deref().map(str::trim) {
+            Some("") => {
+                return Err(AppError::ValidationError(
+                    "Full name is required".to_string(),
+                ))
+            }
+            Some(name) if name.len() > 100 => {
+                return Err(AppError::ValidationError(
+                    "Full name must be 100 characters or less".to_string(),
+                ))
+            }
+            Some(name) => Some(name.to_string()),
+            None => user.full_name,
+        };
+
+        let phone = match request.phone.as_deref().map(str::trim) {
+            Some("") => None,
+            Some(phone) => {
+                validate_phone(phone)?;
+                Some(phone.to_string())
+            }
+            None => user.phone,
+        };
+
+        diesel::update(users::table.find(user_id))
+            .set((users::full_name.eq(full_name), users::phone.eq(phone)))
+            .get_result(&mut conn)
+            .map_err(AppError::from)
+    }
 }
 
 
@@ -900,6 +1493,47 @@ mod tests {
         assert!(hash.starts_with("$argon2"));
     }
 
+    #[test]
+    fn test_hashes_carry_the_configured_parameters() {
+        let hashing = PasswordHashing {
+            memory_kib: 8 * 1024,
+            iterations: 3,
+            parallelism: 2,
+        };
+        let hash = AuthService::hash_password_with("test_password_123", &hashing).unwrap();
+        assert!(hash.starts_with("$argon2id$v=19$m=8192,t=3,p=2$"), "{}", hash);
+        assert!(AuthService::verify_password("test_password_123", &hash).unwrap());
+        assert!(!hashing.needs_rehash(&hash));
+
+        let default_hash = AuthService::hash_password_with("test_password_123", &PasswordHashing::default()).unwrap();
+        assert!(default_hash.starts_with("$argon2id$v=19$m=19456,t=2,p=1$"), "{}", default_hash);
+    }
+
+    #[test]
+    fn test_weaker_hashes_need_rehashing() {
+        // Seed data hash with the crate defaults: m=19456, t=2, p=1
+        let seed = "$argon2id$v=19$m=19456,t=2,p=1$Q7qpjUxx/KIS14QRgxPttw$ZIljgEut2REPXKiphJsLmDMneXDCxizpxoH0bJxiBl8";
+        assert!(!PasswordHashing::default().needs_rehash(seed));
+
+        let stronger = |memory_kib, iterations, parallelism| PasswordHashing {
+            memory_kib,
+            iterations,
+            parallelism,
+        };
+        assert!(stronger(65536, 2, 1).needs_rehash(seed));
+        assert!(stronger(19456, 3, 1).needs_rehash(seed));
+        assert!(stronger(19456, 2, 2).needs_rehash(seed));
+        // A cheaper configuration does not downgrade existing hashes
+        assert!(!stronger(8192, 1, 1).needs_rehash(seed));
+
+        let argon2i = Argon2::new(Algorithm::Argon2i, Version::V0x13, Params::default())
+            .hash_password(b"admin123", &SaltString::generate(&mut OsRng))
+            .unwrap()
+            .to_string();
+        assert!(PasswordHashing::default().needs_rehash(&argon2i));
+        assert!(!PasswordHashing::default().needs_rehash("not a hash"));
+    }
+
     #[test]
     fn test_seed_data_password_hashes() {
         // Test password hashes from seed data (01-seed-users.sql)
@@ -0,0 +1,351 @@
+use bigdecimal::BigDecimal;
+use chrono::{Duration, NaiveDate, Utc};
+use diesel::prelude::*;
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::errors::{AppError, AppResult};
+use crate::models::{BookingStatus, Room, RoomStatus, RoomType, RoomTypeConfig};
+use crate::schema::{bookings, rooms};
+use crate::settings::{self, Settings};
+use crate::services::{HoldService, RoomBlockService, RoomTypeService};
+use crate::utils::clock;
+
+/// Longest range the availability calendar accepts, in days
+pub const MAX_CALENDAR_DAYS: i64 = 185;
+/// Candidate rooms suggested per room type by the quick availability check
+pub const QUICK_CANDIDATES_PER_TYPE: usize = 3;
+
+/// Free room count for one room type on one date
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RoomTypeAvailability {
+    pub room_type: RoomType,
+    pub available: i64,
+}
+
+/// Availability of every requested room type for one night
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AvailabilityCalendarDay {
+    pub date: NaiveDate,
+    pub room_types: Vec<RoomTypeAvailability>,
+}
+
+/// Body for the staff booking endpoint, minus the guest name
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct QuickBookingPayload {
+    pub room_id: Uuid,
+    pub check_in_date: NaiveDate,
+    pub check_out_date: NaiveDate,
+}
+
+/// Room suggested for a phone enquiry
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct QuickCandidate {
+    pub room_id: Uuid,
+    pub number: String,
+    pub status: RoomStatus,
+    pub price: BigDecimal,
+    pub booking: QuickBookingPayload,
+}
+
+/// Free rooms of one type for a stay and party size
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct QuickRoomTypeAvailability {
+    pub room_type: RoomType,
+    pub max_guests: u32,
+    pub available: usize,
+    pub lowest_rate: Option<BigDecimal>,
+    pub candidates: Vec<QuickCandidate>,
+}
+
+/// Room night occupied by a blocking booking: (room_id, check_in_date, check_out_date, status)
+pub type BlockingStay = (Uuid, NaiveDate, NaiveDate, BookingStatus);
+
+/// Availability service for aggregated (room-type level) availability
+pub struct AvailabilityService {
+    pool: DbPool,
+}
+
+impl AvailabilityService {
+    /// Create a new AvailabilityService instance
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    /// Validate a calendar range (inclusive, at most `MAX_CALENDAR_DAYS` days)
+    pub fn validate_calendar_range(start_date: NaiveDate, end_date: NaiveDate) -> AppResult<()> {
+        if end_date < start_date {
+            return Err(AppError::ValidationError(
+                "end_date must be on or after start_date".to_string(),
+            ));
+        }
+
+        if (end_date - start_date).num_days() + 1 > MAX_CALENDAR_DAYS {
+            return Err(AppError::ValidationError(format!(
+                "Calendar range cannot exceed {} days",
+                MAX_CALENDAR_DAYS
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Build the per-date free room counts from rooms and the blocking stays overlapping the range
+    ///
+    /// A room is free on a date when it is not under maintenance and no stay covers that
+    /// night (check_in_date <= date < check_out_date). Overstaying guests keep their room
+    /// at least through `today`. Room types are listed in the order of
+    /// `room_types`, leaving out those without rooms.
+    pub fn build_calendar(
+        room_types: &[RoomTypeConfig],
+        rooms: &[Room],
+        stays: &[BlockingStay],
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+        today: NaiveDate,
+    ) -> Vec<AvailabilityCalendarDay> {
+        let room_types: Vec<&RoomTypeConfig> = room_types
+            .iter()
+            .filter(|t| rooms.iter().any(|r| r.room_type_id == t.id))
+            .collect();
+
+        let mut days = Vec::new();
+        let mut date = start_date;
+        while date <= end_date {
+            let room_types = room_types
+                .iter()
+                .map(|room_type| {
+                    let available = rooms
+                        .iter()
+                        .filter(|r| r.room_type_id == room_type.id)
+                        .filter(|r| r.status != RoomStatus::Maintenance)
+                        .filter(|r| {
+                            !stays.iter().any(|(room_id, check_in, check_out, status)| {
+                                let effective_out = if *status == BookingStatus::Overstay {
+                                    (*check_out).max(today + Duration::days(1))
+                                } else {
+                                    *check_out
+                                };
+                                *room_id == r.id && *check_in <= date && date < effective_out
+                            })
+                        })
+                        .count() as i64;
+
+                    RoomTypeAvailability {
+                        room_type: room_type.code.clone(),
+                        available,
+                    }
+                })
+                .collect();
+
+            days.push(AvailabilityCalendarDay { date, room_types });
+            date += Duration::days(1);
+        }
+
+        days
+    }
+
+    /// Get free room counts per room type for every date in the range
+    ///
+    /// # Arguments
+    /// * `room_type` - Optional room type filter
+    /// * `start_date` - First night (inclusive)
+    /// * `end_date` - Last night (inclusive)
+    pub fn get_calendar(
+        &self,
+        room_type: Option<RoomType>,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> AppResult<Vec<AvailabilityCalendarDay>> {
+        Self::validate_calendar_range(start_date, end_date)?;
+
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let mut room_query = rooms::table
+            .filter(rooms::decommissioned_at.is_null())
+            .into_boxed();
+        if let Some(room_type) = room_type {
+            room_query = room_query.filter(rooms::room_type.eq(room_type));
+        }
+        let room_list: Vec<Room> = room_query
+            .load(&mut conn)
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let stays = Self::load_blocking_stays(&mut conn, &room_list, start_date, end_date)?;
+        let room_types = RoomTypeService::list_on(&mut conn, true)?;
+
+        Ok(Self::build_calendar(
+            &room_types,
+            &room_list,
+            &stays,
+            start_date,
+            end_date,
+            clock::local_date(Utc::now(), Settings::load(&mut conn)?.time_zone(settings::HOTEL_TIMEZONE)),
+        ))
+    }
+
+    /// Blocking stays of the given rooms touching any night from `start_date`
+    /// to `end_date` (inclusive). Overstays are included regardless of their
+    /// (already passed) check-out date, and room blocks and unexpired holds
+    /// count as stays over their nights, as in `check_availability_on`.
+    fn load_blocking_stays(
+        conn: &mut PgConnection,
+        room_list: &[Room],
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> AppResult<Vec<BlockingStay>> {
+        let room_ids: Vec<Uuid> = room_list.iter().map(|r| r.id).collect();
+        let blocking_statuses: Vec<BookingStatus> = BookingStatus::ALL
+            .into_iter()
+            .filter(|s| s.blocks_availability())
+            .collect();
+
+        let mut stays: Vec<BlockingStay> = bookings::table
+            .filter(bookings::room_id.eq_any(&room_ids))
+            .filter(bookings::status.eq_any(&blocking_statuses))
+            .filter(bookings::check_in_date.le(end_date))
+            .filter(
+                bookings::check_out_date
+                    .gt(start_date)
+                    .or(bookings::status.eq(BookingStatus::Overstay)),
+            )
+            .select((
+                bookings::room_id,
+                bookings::check_in_date,
+                bookings::check_out_date,
+                bookings::status,
+            ))
+            .load(conn)
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let blocks = RoomBlockService::load_blocks_on(conn, &room_ids, start_date, end_date)?;
+        stays.extend(blocks.into_iter().map(|(room_id, first_night, last_night)| {
+            (room_id, first_night, last_night + Duration::days(1), BookingStatus::Upcoming)
+        }));
+        let holds = HoldService::load_active_on(conn, &room_ids, start_date, end_date, Utc::now())?;
+        stays.extend(holds.into_iter().map(|(room_id, check_in_date, check_out_date)| {
+            (room_id, check_in_date, check_out_date, BookingStatus::Upcoming)
+        }));
+        Ok(stays)
+    }
+
+    /// Summarize free rooms per room type for a stay and party size
+    ///
+    /// A room qualifies when its type sleeps `guests`, it is not under
+    /// maintenance, no stay covers any night of the request and, for an
+    /// arrival today, it is not occupied. Candidates prefer rooms ready now
+    /// (Available), then the most recently updated (e.g. just cleaned), then
+    /// the lower rate. Each type sleeps its configured default capacity.
+    pub fn build_quick_availability(
+        room_types: &[RoomTypeConfig],
+        rooms: &[Room],
+        stays: &[BlockingStay],
+        check_in_date: NaiveDate,
+        check_out_date: NaiveDate,
+        guests: u32,
+        today: NaiveDate,
+    ) -> Vec<QuickRoomTypeAvailability> {
+        let last_night = check_out_date - Duration::days(1);
+
+        room_types
+            .iter()
+            .filter(|t| t.default_capacity as u32 >= guests)
+            .filter(|t| rooms.iter().any(|r| r.room_type_id == t.id))
+            .map(|room_type| {
+                let mut free: Vec<&Room> = rooms
+                    .iter()
+                    .filter(|r| r.room_type_id == room_type.id)
+                    .filter(|r| r.status != RoomStatus::Maintenance)
+                    .filter(|r| !(check_in_date == today && r.status == RoomStatus::Occupied))
+                    .filter(|r| {
+                        !stays.iter().any(|(room_id, check_in, check_out, status)| {
+                            let effective_out = if *status == BookingStatus::Overstay {
+                                (*check_out).max(today + Duration::days(1))
+                            } else {
+                                *check_out
+                            };
+                            *room_id == r.id && *check_in <= last_night && check_in_date < effective_out
+                        })
+                    })
+                    .collect();
+
+                free.sort_by(|a, b| {
+                    (b.status == RoomStatus::Available)
+                        .cmp(&(a.status == RoomStatus::Available))
+                        .then(b.updated_at.cmp(&a.updated_at))
+                        .then(a.price.cmp(&b.price))
+                });
+
+                QuickRoomTypeAvailability {
+                    room_type: room_type.code.clone(),
+                    max_guests: room_type.default_capacity as u32,
+                    available: free.len(),
+                    lowest_rate: free.iter().map(|r| &r.price).min().cloned(),
+                    candidates: free
+                        .iter()
+                        .take(QUICK_CANDIDATES_PER_TYPE)
+                        .map(|r| QuickCandidate {
+                            room_id: r.id,
+                            number: r.number.clone(),
+                            status: r.status,
+                            price: r.price.clone(),
+                            booking: QuickBookingPayload {
+                                room_id: r.id,
+                                check_in_date,
+                                check_out_date,
+                            },
+                        })
+                        .collect(),
+                }
+            })
+            .collect()
+    }
+
+    /// Quick availability for a phone enquiry: free rooms per room type that
+    /// sleep `guests` for the whole stay
+    pub fn quick_availability(
+        &self,
+        check_in_date: NaiveDate,
+        check_out_date: NaiveDate,
+        guests: u32,
+    ) -> AppResult<Vec<QuickRoomTypeAvailability>> {
+        if check_out_date <= check_in_date {
+            return Err(AppError::ValidationError(
+                "check_out_date must be after check_in_date".to_string(),
+            ));
+        }
+        if guests == 0 {
+            return Err(AppError::ValidationError(
+                "guests must be at least 1".to_string(),
+            ));
+        }
+        let last_night = check_out_date - Duration::days(1);
+        Self::validate_calendar_range(check_in_date, last_night)?;
+
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let room_list: Vec<Room> = rooms::table
+            .filter(rooms::decommissioned_at.is_null())
+            .load(&mut conn)
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        let stays = Self::load_blocking_stays(&mut conn, &room_list, check_in_date, last_night)?;
+        let room_types = RoomTypeService::list_on(&mut conn, true)?;
+
+        Ok(Self::build_quick_availability(
+            &room_types,
+            &room_list,
+            &stays,
+            check_in_date,
+            check_out_date,
+            guests,
+            clock::local_date(Utc::now(), Settings::load(&mut conn)?.time_zone(settings::HOTEL_TIMEZONE)),
+        ))
+    }
+}
@@ -0,0 +1,227 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::errors::{AppError, AppResult};
+use crate::models::{BookingEmail, BookingEmailKind, BookingEmailStatus, BookingWithRoom, NewBookingEmail};
+use crate::schema::{booking_emails, bookings, users};
+use crate::services::mailer::{Mailer, OutgoingEmail};
+use crate::services::BookingService;
+use crate::settings::Settings;
+use crate::utils::money::format_vnd;
+
+/// Booking emails returned per admin listing
+pub const BOOKING_EMAIL_LIST_LIMIT: i64 = 200;
+
+/// Escape text for interpolation into an HTML email body
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Reference, room type, dates and total price as table rows
+fn booking_rows_html(booking: &BookingWithRoom) -> String {
+    let room_type = booking
+        .room
+        .as_ref()
+        .map_or("-".to_string(), |room| room.room_type.to_string());
+    let total = booking.total_price().map_or("-".to_string(), |price| format_vnd(&price));
+
+    [
+        ("Reference", booking.booking.reference.clone()),
+        ("Room type", room_type),
+        ("Check-in", booking.booking.check_in_date.to_string()),
+        ("Check-out", booking.booking.check_out_date.to_string()),
+        ("Nights", booking.nights().to_string()),
+        ("Total price", total),
+    ]
+    .iter()
+    .map(|(name, value)| format!("<tr><td>{}</td><td>{}</td></tr>", name, escape_html(value)))
+    .collect()
+}
+
+/// Render the email confirming a new booking
+pub fn render_confirmation_email(to: &str, booking: &BookingWithRoom) -> OutgoingEmail {
+    OutgoingEmail {
+        to: to.to_string(),
+        subject: format!("Your Pupinn booking {} is confirmed", booking.booking.reference),
+        html: format!(
+            "<html><body><p>Dear {},</p><p>Thank you for booking with us. Your reservation is confirmed.</p>\
+             <table cellpadding=\"4\">{}</table></body></html>",
+            escape_html(&booking.booking.guest_name),
+            booking_rows_html(booking)
+        ),
+    }
+}
+
+/// Render the email acknowledging a cancelled booking
+pub fn render_cancellation_email(to: &str, booking: &BookingWithRoom) -> OutgoingEmail {
+    OutgoingEmail {
+        to: to.to_string(),
+        subject: format!("Your Pupinn booking {} has been cancelled", booking.booking.reference),
+        html: format!(
+            "<html><body><p>Dear {},</p><p>Your reservation has been cancelled as requested.</p>\
+             <table cellpadding=\"4\">{}</table></body></html>",
+            escape_html(&booking.booking.guest_name),
+            booking_rows_html(booking)
+        ),
+    }
+}
+
+/// Booking email service for guest confirmations and cancellations
+pub struct BookingEmailService {
+    pool: DbPool,
+}
+
+impl BookingEmailService {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    fn conn(&self) -> AppResult<crate::db::DbConn> {
+        self.pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
+    /// Record a pending email to the guest who made the booking and render it
+    ///
+    /// # Returns
+    /// * `Ok(None)` - The booking was not made by a guest account with an email address
+    pub fn prepare(
+        &self,
+        booking_id: Uuid,
+        kind: BookingEmailKind,
+    ) -> AppResult<Option<(BookingEmail, OutgoingEmail)>> {
+        let booking = BookingService::new(self.pool.clone()).get_booking_with_room(booking_id)?;
+        let mut conn = self.conn()?;
+
+        let recipient: Option<String> = bookings::table
+            .inner_join(users::table.on(bookings::created_by_user_id.eq(users::id.nullable())))
+            .filter(bookings::id.eq(booking_id))
+            .filter(bookings::creation_source.eq("guest"))
+            .select(users::email)
+            .first::<Option<String>>(&mut conn)
+            .optional()?
+            .flatten();
+        let Some(recipient) = recipient else {
+            return Ok(None);
+        };
+
+        let record = diesel::insert_into(booking_emails::table)
+            .values(&NewBookingEmail {
+                booking_id,
+                kind,
+                recipient_email: &recipient,
+            })
+            .get_result::<BookingEmail>(&mut conn)?;
+
+        let email = match kind {
+            BookingEmailKind::Confirmation => render_confirmation_email(&recipient, &booking),
+            BookingEmailKind::Cancellation => render_cancellation_email(&recipient, &booking),
+        };
+        Ok(Some((record, email)))
+    }
+
+    /// Mail transport configured in the system settings
+    pub fn mailer(&self) -> AppResult<Mailer> {
+        let mut conn = self.conn()?;
+        let settings = Settings::load(&mut conn)?;
+        Mailer::from_smtp_settings(&settings).map_err(AppError::InternalError)
+    }
+
+    /// Mark an email sent or failed
+    pub fn record_result(&self, email_id: Uuid, result: Result<(), String>, now: DateTime<Utc>) -> AppResult<()> {
+        let mut conn = self.conn()?;
+        let (status, sent_at, last_error) = match result {
+            Ok(()) => (BookingEmailStatus::Sent, Some(now), None),
+            Err(e) => (BookingEmailStatus::Failed, None, Some(e)),
+        };
+
+        diesel::update(booking_emails::table.find(email_id))
+            .set((
+                booking_emails::status.eq(status),
+                booking_emails::sent_at.eq(sent_at),
+                booking_emails::last_error.eq(last_error),
+            ))
+            .execute(&mut conn)?;
+
+        Ok(())
+    }
+
+    /// Most recent booking emails, newest first
+    pub fn list_emails(
+        &self,
+        booking_id: Option<Uuid>,
+        status: Option<BookingEmailStatus>,
+    ) -> AppResult<Vec<BookingEmail>> {
+        let mut conn = self.conn()?;
+        let mut query = booking_emails::table.into_boxed();
+        if let Some(booking_id) = booking_id {
+            query = query.filter(booking_emails::booking_id.eq(booking_id));
+        }
+        if let Some(status) = status {
+            query = query.filter(booking_emails::status.eq(status));
+        }
+
+        Ok(query
+            .order(booking_emails::created_at.desc())
+            .limit(BOOKING_EMAIL_LIST_LIMIT)
+            .load(&mut conn)?)
+    }
+}
+
+/// Send a booking email on a background task. The booking request has already
+/// succeeded, so failures are only logged and recorded on the email row.
+pub fn spawn_booking_email(pool: DbPool, booking_id: Uuid, kind: BookingEmailKind) {
+    tokio::spawn(async move {
+        if let Err(e) = send_booking_email(pool, booking_id, kind).await {
+            tracing::warn!("Could not send {:?} email for booking {}: {}", kind, booking_id, e);
+        }
+    });
+}
+
+async fn send_booking_email(pool: DbPool, booking_id: Uuid, kind: BookingEmailKind) -> Result<(), String> {
+    let prepare_pool = pool.clone();
+    let prepared = tokio::task::spawn_blocking(move || {
+        let service = BookingEmailService::new(prepare_pool);
+        // A misconfigured transport still leaves a failed row behind
+        let prepared = service.prepare(booking_id, kind)?;
+        AppResult::Ok(prepared.map(|p| (p, service.mailer().map_err(|e| e.to_string()))))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())?;
+
+    let Some(((record, email), mailer)) = prepared else {
+        tracing::debug!("Booking {} has no guest email address; skipping {:?} email", booking_id, kind);
+        return Ok(());
+    };
+
+    let result = match mailer {
+        Ok(mailer) => mailer.send(&email).await,
+        Err(e) => Err(e),
+    };
+    match &result {
+        Ok(()) => tracing::info!("Sent {:?} email for booking {} to {}", kind, booking_id, email.to),
+        Err(e) => tracing::warn!("{:?} email for booking {} to {} failed: {}", kind, booking_id, email.to, e),
+    }
+
+    tokio::task::spawn_blocking(move || {
+        BookingEmailService::new(pool).record_result(record.id, result, Utc::now())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())
+}
@@ -1,11 +1,13 @@
-use chrono::{NaiveDate, Utc, Duration};
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc, Duration};
 use diesel::prelude::*;
-use diesel::dsl::{count, sum, avg};
-use diesel::result::{QueryResult, DatabaseErrorInformation};
+use diesel::dsl::{count, count_star, sum, avg};
+use diesel::result::{DatabaseErrorInformation, DatabaseErrorKind, QueryResult};
+use rand::rngs::OsRng;
 use rand::Rng;
 use bigdecimal::BigDecimal;
-use std::str::FromStr;
-use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 /// Simple error wrapper for database errors
@@ -41,16 +43,39 @@ impl DatabaseErrorInformation for StringError {
     }
 }
 
+/// Carry an AppError out of a `diesel::result::Error` transaction so it rolls
+/// back; `From<diesel::result::Error> for AppError` restores the variant
+pub fn app_error_to_diesel(e: AppError) -> diesel::result::Error {
+    diesel::result::Error::DatabaseError(
+        DatabaseErrorKind::CheckViolation,
+        Box::new(StringError(e.to_string())) as Box<dyn DatabaseErrorInformation + Send + Sync>,
+    )
+}
+
 use crate::db::DbPool;
 use crate::errors::{AppError, AppResult};
 use crate::models::{
-    Booking, BookingStatus, BookingWithRoom, BookingWithPayments, NewBooking, Room, RoomStatus, RoomType, UpdateBooking,
+    diff_bookings, message::Message, parse_creation_source, Booking, BookingChanges, BookingEvent, BookingModification, BookingNote, BookingStatus,
+    BookingWithRoom, BookingWithPayments, NewBooking, NewBookingEvent, NewBookingModification, NewBookingNote, EVENT_CONFLICT_OVERRIDE, EVENT_ROOM_MOVE, SOURCE_ROOM_MOVE, SOURCE_STAFF_UPDATE, NoShowCharge, NoShowChargeStatus, Payment, PaymentType, Room, RoomStatus, RoomType, UpdateBooking, UserRole,
+};
+use crate::schema::{
+    booking_events, booking_modifications, booking_notes, bookings, messages, no_show_charges, payments, rooms, users,
+};
+use crate::services::room_service::RoomFilter;
+use crate::services::{
+    EmailVerificationService, GuestService, HoldService, NoShowService, PricingService, RoomBlockService, RoomService, RoomTypeService,
 };
-use crate::schema::{bookings, rooms};
+use crate::settings::{self, Settings};
+use crate::utils::clock::{self, Clock};
+use crate::utils::csv::csv_field;
+use crate::utils::ics::{self, IcsEvent};
+use crate::utils::validate_search_query;
+use crate::utils::money;
 
 /// Booking service for managing reservations
 pub struct BookingService {
     pool: DbPool,
+    clock: Arc<dyn Clock>,
 }
 
 /// Financial metrics for a room
@@ -63,57 +88,500 @@ pub struct RoomFinancials {
     pub occupancy_rate: f64,
 }
 
+/// Booking details shown to an unauthenticated guest who proved the
+/// reference and surname; no ids or other personal data
+#[derive(Debug, Clone, Serialize)]
+pub struct PublicBookingView {
+    pub reference: String,
+    pub room_type: RoomType,
+    pub check_in_date: NaiveDate,
+    pub check_out_date: NaiveDate,
+    pub status: BookingStatus,
+    pub nights: i64,
+    pub total: BigDecimal,
+}
+
+/// Reservation status shown to anyone holding the reference, e.g. from a
+/// confirmation email; carries nothing that identifies the guest
+#[derive(Debug, Clone, Serialize)]
+pub struct PublicBookingStatus {
+    pub reference: String,
+    pub room_number: String,
+    pub check_in_date: NaiveDate,
+    pub check_out_date: NaiveDate,
+    pub status: BookingStatus,
+}
+
+/// Availability of one room type for the public search widget; counts and a
+/// price only, never room ids, numbers or statuses
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PublicRoomTypeAvailability {
+    pub room_type: RoomType,
+    pub available_rooms: usize,
+    /// Lowest nightly price among the available rooms; None when sold out
+    pub nightly_price: Option<BigDecimal>,
+}
+
+/// The guest's in-progress stay as shown on the guest portal home screen
+#[derive(Debug, Clone, Serialize)]
+pub struct CurrentStay {
+    #[serde(flatten)]
+    pub booking: Booking,
+    pub room: Option<Room>,
+    /// Nights left before the scheduled check-out; 0 on departure day or when overstaying
+    pub remaining_nights: i64,
+    pub total_paid: BigDecimal,
+    pub outstanding_balance: BigDecimal,
+}
+
+/// Unique constraint on `bookings.reference`
+pub const REFERENCE_CONSTRAINT: &str = "bookings_reference_key";
+/// Fresh references tried before a booking insert gives up
+pub const MAX_REFERENCE_ATTEMPTS: usize = 10;
+/// Layout of a booking reference; each X is a `REFERENCE_ALPHABET` symbol
+pub const REFERENCE_FORMAT: &str = "BK-YYYYMMDD-XXXX";
+/// Reference suffix symbols: digits and capitals without the look-alikes 0/O
+/// and 1/I, so references read out over the phone are unambiguous
+pub const REFERENCE_ALPHABET: &[u8; 32] = b"23456789ABCDEFGHJKLMNPQRSTUVWXYZ";
+/// Random bits behind a reference suffix, five per symbol
+pub const REFERENCE_SUFFIX_BITS: u32 = 20;
+
+/// Most rooms a single group booking may hold
+pub const MAX_GROUP_ROOMS: usize = 30;
+
+/// Start of the error returned for an unconfirmed early check-out
+pub const EARLY_CHECK_OUT_MESSAGE: &str = "Guest is checking out before the scheduled check-out date";
+
+/// Bookings timeline page size when none is requested
+pub const DEFAULT_TIMELINE_PAGE_SIZE: u64 = 50;
+/// Largest timeline page a client may request
+pub const MAX_TIMELINE_PAGE_SIZE: u64 = 200;
+/// Upper bound on rows loaded from each timeline source
+pub const MAX_TIMELINE_SOURCE_ROWS: i64 = 500;
+
+/// Room booking history page size when none is requested
+pub const DEFAULT_ROOM_HISTORY_PAGE_SIZE: u64 = 20;
+/// Largest room booking history page a client may request
+pub const MAX_ROOM_HISTORY_PAGE_SIZE: u64 = 100;
+/// Most bookings a staff search returns
+pub const BOOKING_SEARCH_LIMIT: i64 = 25;
+
+/// Kind of timeline entry, so the frontend can pick an icon
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimelineEntryKind {
+    Created,
+    StatusChange,
+    DateChange,
+    RoomMove,
+    ConflictOverride,
+    Payment,
+    NoShowCharge,
+    ChatProposal,
+}
+
+/// One entry in a booking timeline
+#[derive(Debug, Clone, Serialize)]
+pub struct TimelineEntry {
+    pub occurred_at: DateTime<Utc>,
+    pub kind: TimelineEntryKind,
+    pub summary: String,
+    /// Staff only
+    pub actor_user_id: Option<Uuid>,
+    /// Staff only (payment notes, review reasons, proposal payload)
+    pub details: Option<String>,
+}
+
+/// Page of a booking timeline, oldest entry first
+#[derive(Debug, Clone, Serialize)]
+pub struct BookingTimelinePage {
+    pub booking_id: Uuid,
+    pub entries: Vec<TimelineEntry>,
+    pub total: u64,
+    pub page: u64,
+    pub per_page: u64,
+}
+
+/// Filters for a room's booking history
+#[derive(Debug, Clone, Default)]
+pub struct RoomHistoryFilter {
+    /// Stays checking out on or after this date
+    pub from: Option<NaiveDate>,
+    /// Stays checking in on or before this date
+    pub to: Option<NaiveDate>,
+    pub status: Option<BookingStatus>,
+}
+
+/// Stay in a room's booking history. Prices are only filled in for admins.
+#[derive(Debug, Clone, Serialize)]
+pub struct RoomHistoryEntry {
+    pub id: Uuid,
+    pub reference: String,
+    pub guest_name: String,
+    pub check_in_date: NaiveDate,
+    pub check_out_date: NaiveDate,
+    pub status: BookingStatus,
+    pub creation_source: String,
+    pub group_reference: Option<String>,
+    pub created_by: Option<String>,
+    pub nights: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_price: Option<String>,
+}
+
+impl RoomHistoryEntry {
+    pub fn new(booking: BookingWithRoom, include_prices: bool) -> Self {
+        let nights = booking.nights();
+        let total_price = include_prices
+            .then(|| booking.total_price())
+            .flatten()
+            .map(|price| price.to_string());
+        let BookingWithRoom { booking, created_by, .. } = booking;
+
+        Self {
+            id: booking.id,
+            reference: booking.reference,
+            guest_name: booking.guest_name,
+            check_in_date: booking.check_in_date,
+            check_out_date: booking.check_out_date,
+            status: booking.status,
+            creation_source: booking.creation_source,
+            group_reference: booking.group_reference,
+            created_by,
+            nights,
+            total_price,
+        }
+    }
+}
+
+/// Page of a room's booking history, latest check-in first
+#[derive(Debug, Clone, Serialize)]
+pub struct RoomHistoryPage {
+    pub room_id: Uuid,
+    pub bookings: Vec<RoomHistoryEntry>,
+    pub total: u64,
+    pub page: u64,
+    pub per_page: u64,
+}
+
+/// Raw rows the timeline is merged from
+#[derive(Debug, Clone)]
+pub struct TimelineSources {
+    pub booking: Booking,
+    pub events: Vec<BookingEvent>,
+    pub payments: Vec<Payment>,
+    pub no_show_charges: Vec<NoShowCharge>,
+    /// Chat proposal the guest accepted to create the booking
+    pub proposal: Option<Message>,
+}
+
+/// Shortest and longest bookable stay, in nights
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StayLimits {
+    pub min_nights: i64,
+    pub max_nights: i64,
+}
+
+impl StayLimits {
+    /// Limits from `booking_min_nights` / `booking_max_nights`, or their
+    /// defaults when unset
+    pub fn from_settings(settings: &Settings) -> Self {
+        Self {
+            min_nights: settings.integer(settings::BOOKING_MIN_NIGHTS),
+            max_nights: settings.integer(settings::BOOKING_MAX_NIGHTS),
+        }
+    }
+}
+
+impl Default for StayLimits {
+    fn default() -> Self {
+        Self::from_settings(&Settings::default())
+    }
+}
+
+/// Bookings changed by one stale-booking sweep
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StaleBookingSweep {
+    pub no_shows: usize,
+    pub overstays: usize,
+    /// Rooms whose guest became an overstay in this sweep, sorted
+    pub overstay_room_ids: Vec<Uuid>,
+}
+
+/// Room still held by a guest past their scheduled check-out
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OverstayRoom {
+    pub room_id: Uuid,
+    pub room_number: String,
+    pub booking_id: Uuid,
+    pub reference: String,
+    pub guest_name: String,
+    pub scheduled_check_out: NaiveDate,
+    pub days_over: i64,
+}
+
+/// Event note on bookings cancelled because their room goes into maintenance
+pub const ROOM_MAINTENANCE_NOTE: &str = "room maintenance";
+
+/// Bookings of a room going into maintenance, by what happened to them
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct MaintenanceCancellation {
+    /// References of the cancelled bookings, so staff can notify the guests
+    pub cancelled: Vec<String>,
+    /// References of guests in the room (checked in or overstaying), left as is
+    pub skipped_checked_in: Vec<String>,
+}
+
+/// Two blocking bookings that hold the same room on the same nights
+#[derive(Debug, Clone, Serialize)]
+pub struct BookingConflict {
+    pub room: Room,
+    /// The pair is ordered by booking id, so each conflict appears once
+    pub first: Booking,
+    pub second: Booking,
+    /// First night both bookings hold
+    pub overlap_from: NaiveDate,
+    /// Night after the last shared night
+    pub overlap_to: NaiveDate,
+}
+
+/// Longest range the per-room calendar accepts, in days
+pub const MAX_ROOM_CALENDAR_DAYS: i64 = 92;
+
+/// Longest range the booking statistics accept, in days
+pub const MAX_BOOKING_STATS_DAYS: i64 = 366;
+
+/// Bookings created per source on one day, keyed by source ("guest", "staff")
+pub type CreatedPerDay = (NaiveDate, String, i64);
+
+/// Bookings created and cancelled on one day (hotel time)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BookingStatsDay {
+    pub date: NaiveDate,
+    pub created: i64,
+    pub created_by_guests: i64,
+    pub created_by_staff: i64,
+    /// Cancellations recorded on this day, whenever the booking was made
+    pub cancelled: i64,
+}
+
+/// Booking activity over a range of days, for the admin dashboard
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BookingStats {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    /// Bookings created in the range
+    pub total_bookings: i64,
+    pub created_by_guests: i64,
+    pub created_by_staff: i64,
+    /// Share of the range's bookings made through the guest portal (0 to 1)
+    pub guest_share: f64,
+    /// Bookings created in the range that are now cancelled
+    pub cancelled_bookings: i64,
+    /// `cancelled_bookings` over `total_bookings` (0 to 1)
+    pub cancellation_rate: f64,
+    /// Nights booked by the range's bookings
+    pub total_nights: i64,
+    pub average_nights: f64,
+    /// Every day of the range, including days without activity
+    pub days: Vec<BookingStatsDay>,
+}
+
+/// Booking occupying a room on one night
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RoomNightBooking {
+    pub reference: String,
+    pub status: BookingStatus,
+}
+
+/// One night of a room's calendar; `booking` is None when the room is free
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RoomCalendarNight {
+    pub date: NaiveDate,
+    pub booking: Option<RoomNightBooking>,
+}
+
+/// Stay shown on a room calendar: (room_id, reference, check_in_date, check_out_date, status)
+pub type CalendarStay = (Uuid, String, NaiveDate, NaiveDate, BookingStatus);
+
+/// Staff booking request payload, for phone and desk bookings and walk-ins
+#[derive(Debug, Deserialize)]
+pub struct StaffBookingRequest {
+    pub guest_name: String,
+    pub room_id: Uuid,
+    pub check_in_date: NaiveDate,
+    pub check_out_date: NaiveDate,
+    #[serde(default)]
+    pub price: Option<BigDecimal>,
+    /// Book even if the same guest already has an overlapping stay
+    #[serde(default)]
+    pub allow_duplicate: bool,
+    /// Admins only: book even though the room is taken, e.g. by an overstay
+    /// guest who promised to leave by noon. Not accepted for walk-ins.
+    #[serde(default)]
+    pub override_conflict: bool,
+}
+
+/// Booking created by staff, with the stays it was booked over when the
+/// availability conflict was overridden
+#[derive(Debug, Serialize)]
+pub struct StaffBookingCreated {
+    #[serde(flatten)]
+    pub booking: Booking,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub overridden_booking_ids: Vec<Uuid>,
+}
+
+/// Rows fetched per query when exporting bookings
+pub const EXPORT_CHUNK_SIZE: i64 = 500;
+
+/// Header row of the bookings CSV export
+pub const BOOKING_EXPORT_HEADER: &str = "reference,guest_name,room_number,room_type,check_in_date,check_out_date,nights,status,total_price,creation_source,created_at\n";
+
+/// Filters for the staff booking list; every filter is optional and they
+/// combine with AND. Dates bound the check-in date.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BookingListFilter<'a> {
+    pub status: Option<BookingStatus>,
+    pub guest_name: Option<&'a str>,
+    pub from_date: Option<NaiveDate>,
+    pub to_date: Option<NaiveDate>,
+    pub group_reference: Option<&'a str>,
+    pub room_id: Option<Uuid>,
+    /// "staff" or "guest"; anything else is rejected
+    pub creation_source: Option<&'a str>,
+}
+
+/// Filters for the bookings export; dates bound the check-in date like
+/// `list_bookings`
+#[derive(Debug, Clone, Default)]
+pub struct BookingExportFilter {
+    pub from_date: Option<NaiveDate>,
+    pub to_date: Option<NaiveDate>,
+    pub status: Option<BookingStatus>,
+}
+
+/// One line of the bookings export
+#[derive(Debug, Clone, Queryable)]
+pub struct BookingExportRow {
+    pub id: Uuid,
+    pub reference: String,
+    pub guest_name: String,
+    pub room_number: String,
+    pub room_type: RoomType,
+    pub check_in_date: NaiveDate,
+    pub check_out_date: NaiveDate,
+    pub status: BookingStatus,
+    pub price: BigDecimal,
+    pub creation_source: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl BookingExportRow {
+    /// Render the row as a CSV line, quoting free-text fields
+    pub fn to_csv_line(&self) -> String {
+        format!(
+            "{},{},{},{},{},{},{},{},{},{},{}\n",
+            csv_field(&self.reference),
+            csv_field(&self.guest_name),
+            csv_field(&self.room_number),
+            self.room_type,
+            self.check_in_date,
+            self.check_out_date,
+            (self.check_out_date - self.check_in_date).num_days(),
+            self.status,
+            self.price,
+            csv_field(&self.creation_source),
+            self.created_at.to_rfc3339(),
+        )
+    }
+}
+
 impl BookingService {
     /// Create a new BookingService instance
     pub fn new(pool: DbPool) -> Self {
-        Self { pool }
+        Self::with_clock(pool, clock::system_clock())
     }
 
-    /// Get default price for a room type
-    #[allow(dead_code)]
-    fn get_default_price_for_room_type(room_type: RoomType) -> BigDecimal {
-        match room_type {
-            // Default prices in VND
-            RoomType::Single => BigDecimal::from_str("1000000").unwrap(),
-            RoomType::Double => BigDecimal::from_str("1500000").unwrap(),
-            RoomType::Suite => BigDecimal::from_str("2500000").unwrap(),
-        }
+    /// Booking service reading the current time from `clock`
+    pub fn with_clock(pool: DbPool, clock: Arc<dyn Clock>) -> Self {
+        Self { pool, clock }
+    }
+
+    /// Today's date in the hotel time zone
+    fn hotel_today(&self, conn: &mut PgConnection) -> QueryResult<NaiveDate> {
+        let tz = Settings::load(conn)?.time_zone(settings::HOTEL_TIMEZONE);
+        Ok(clock::local_date(self.clock.now(), tz))
     }
 
-    /// Generate a unique booking reference in format BK-YYYYMMDD-XXXX
-    pub fn generate_reference(&self) -> AppResult<String> {
+    /// Today's date in the hotel time zone, as the booking rules see it
+    pub fn today(&self) -> AppResult<NaiveDate> {
         let mut conn = self
             .pool
             .get()
             .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        Ok(self.hotel_today(&mut conn)?)
+    }
+
+    /// Generate a candidate booking reference in format BK-YYYYMMDD-XXXX.
+    /// Uniqueness is enforced by the database; see `insert_with_fresh_reference`.
+    pub fn generate_reference() -> String {
+        Self::dated_reference("BK")
+    }
+
+    /// Generate a candidate group reference in format GRP-YYYYMMDD-XXXX
+    pub fn generate_group_reference() -> String {
+        Self::dated_reference("GRP")
+    }
 
+    fn dated_reference(prefix: &str) -> String {
         let today = Utc::now().format("%Y%m%d").to_string();
-        let mut rng = rand::thread_rng();
-
-        // Try up to 10 times to generate a unique reference
-        for _ in 0..10 {
-            let suffix: String = (0..4)
-                .map(|_| {
-                    let idx = rng.gen_range(0..36);
-                    if idx < 10 {
-                        (b'0' + idx) as char
-                    } else {
-                        (b'A' + idx - 10) as char
-                    }
-                })
-                .collect();
+        let value = OsRng.gen_range(0..1u32 << REFERENCE_SUFFIX_BITS);
 
-            let reference = format!("BK-{}-{}", today, suffix);
+        format!("{}-{}-{}", prefix, today, Self::reference_suffix(value))
+    }
 
-            // Check if reference already exists
-            let existing: Option<Booking> = bookings::table
-                .filter(bookings::reference.eq(&reference))
-                .first(&mut conn)
-                .optional()
-                .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+    /// Encode the low `REFERENCE_SUFFIX_BITS` bits of `value` as four
+    /// `REFERENCE_ALPHABET` symbols, most significant first
+    pub fn reference_suffix(value: u32) -> String {
+        (0..REFERENCE_SUFFIX_BITS / 5)
+            .rev()
+            .map(|i| REFERENCE_ALPHABET[((value >> (5 * i)) & 0x1f) as usize] as char)
+            .collect()
+    }
+
+    /// Whether an insert failed because the booking reference is already taken
+    pub fn is_reference_collision(error: &diesel::result::Error) -> bool {
+        matches!(
+            error,
+            diesel::result::Error::DatabaseError(DatabaseErrorKind::UniqueViolation, info)
+                if info.constraint_name() == Some(REFERENCE_CONSTRAINT)
+        )
+    }
 
-            if existing.is_none() {
-                return Ok(reference);
+    /// Insert with a fresh reference, regenerating it when the insert hits the
+    /// unique constraint on `bookings.reference`. Concurrent bookings can draw
+    /// the same suffix, so the database, not a pre-check, decides.
+    ///
+    /// # Arguments
+    /// * `next_reference` - Produces candidate references
+    /// * `try_insert` - Performs the insert with the given reference
+    ///
+    /// # Errors
+    /// * `InternalError` - Every attempt collided
+    /// * `DatabaseError` - Any other insert failure
+    pub fn insert_with_fresh_reference<T>(
+        mut next_reference: impl FnMut() -> String,
+        mut try_insert: impl FnMut(&str) -> QueryResult<T>,
+    ) -> AppResult<T> {
+        for _ in 0..MAX_REFERENCE_ATTEMPTS {
+            let reference = next_reference();
+            match try_insert(&reference) {
+                Ok(inserted) => return Ok(inserted),
+                Err(e) if Self::is_reference_collision(&e) => {
+                    tracing::debug!("Booking reference {} already taken, regenerating", reference);
+                }
+                Err(e) => return Err(AppError::DatabaseError(e.to_string())),
             }
         }
 
@@ -122,14 +590,40 @@ impl BookingService {
         ))
     }
 
-    /// Validate booking dates
+    /// Insert a booking under a fresh reference; `new_booking.reference` is ignored
+    fn insert_booking(conn: &mut PgConnection, new_booking: &NewBooking) -> AppResult<Booking> {
+        Self::insert_with_fresh_reference(Self::generate_reference, |reference| {
+            // Savepoint, so a reference collision does not abort the transaction
+            conn.transaction(|conn| {
+                diesel::insert_into(bookings::table)
+                    .values(&NewBooking { reference, ..new_booking.clone() })
+                    .get_result(conn)
+            })
+        })
+    }
+
+    /// Validate booking dates against the hotel's today and the stay limits
     pub fn validate_dates(
         &self,
         check_in_date: NaiveDate,
         check_out_date: NaiveDate,
     ) -> AppResult<()> {
-        let today = Utc::now().date_naive();
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        Self::validate_date_order(check_in_date, check_out_date, self.hotel_today(&mut conn)?)?;
+
+        let limits = StayLimits::from_settings(&Settings::load(&mut conn)?);
+        Self::validate_stay_length(check_in_date, check_out_date, limits)
+    }
 
+    /// Check-in must be `today` (hotel time) or later and check-out after it
+    pub fn validate_date_order(
+        check_in_date: NaiveDate,
+        check_out_date: NaiveDate,
+        today: NaiveDate,
+    ) -> AppResult<()> {
         // Check-in date must be today or in the future
         if check_in_date < today {
             return Err(AppError::ValidationError(
@@ -147,134 +641,920 @@ impl BookingService {
         Ok(())
     }
 
-    /// Check if a room is available for the given date range
+    /// Check the number of nights against the configured stay limits, so a
+    /// typo in the year does not book half a year
+    pub fn validate_stay_length(
+        check_in_date: NaiveDate,
+        check_out_date: NaiveDate,
+        limits: StayLimits,
+    ) -> AppResult<()> {
+        let nights = (check_out_date - check_in_date).num_days();
+
+        if nights < limits.min_nights {
+            return Err(AppError::ValidationError(format!(
+                "Stays must be at least {} night(s); {} night(s) requested",
+                limits.min_nights, nights
+            )));
+        }
+        if nights > limits.max_nights {
+            return Err(AppError::ValidationError(format!(
+                "Stays can be at most {} nights; {} nights requested",
+                limits.max_nights, nights
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Check if a room is available for the given date range. Unexpired holds
+    /// count as conflicts unless they belong to `holder_user_id`.
     pub fn check_availability(
         &self,
         room_id: Uuid,
         check_in_date: NaiveDate,
         check_out_date: NaiveDate,
         exclude_booking_id: Option<Uuid>,
+        holder_user_id: Option<Uuid>,
     ) -> AppResult<bool> {
         let mut conn = self
             .pool
             .get()
             .map_err(|e| AppError::DatabaseError(e.to_string()))?;
 
-        // Find overlapping bookings that block availability
+        self.check_availability_on(
+            &mut conn,
+            room_id,
+            check_in_date,
+            check_out_date,
+            exclude_booking_id,
+            holder_user_id,
+        )
+    }
+
+    /// Bookings of the room that overlap the stay and block availability
+    pub fn find_conflicting_bookings(
+        conn: &mut PgConnection,
+        room_id: Uuid,
+        check_in_date: NaiveDate,
+        check_out_date: NaiveDate,
+        exclude_booking_id: Option<Uuid>,
+    ) -> AppResult<Vec<Booking>> {
+        let blocking_statuses: Vec<BookingStatus> = BookingStatus::ALL
+            .into_iter()
+            .filter(|s| s.blocks_availability())
+            .collect();
         let mut query = bookings::table
             .filter(bookings::room_id.eq(room_id))
-            .filter(bookings::status.ne(BookingStatus::Cancelled))
-            .filter(bookings::status.ne(BookingStatus::CheckedOut))
+            .filter(bookings::status.eq_any(blocking_statuses))
             .filter(bookings::check_in_date.lt(check_out_date))
             .filter(bookings::check_out_date.gt(check_in_date))
+            .order(bookings::check_in_date.asc())
             .into_boxed();
 
         if let Some(booking_id) = exclude_booking_id {
             query = query.filter(bookings::id.ne(booking_id));
         }
 
-        let conflicting: Vec<Booking> = query
-            .load(&mut conn)
-            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        query
+            .load(conn)
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
+    /// Check availability on the caller's connection. Inside a transaction that
+    /// holds `lock_room`, the answer stays true until the transaction ends.
+    pub fn check_availability_on(
+        &self,
+        conn: &mut PgConnection,
+        room_id: Uuid,
+        check_in_date: NaiveDate,
+        check_out_date: NaiveDate,
+        exclude_booking_id: Option<Uuid>,
+        holder_user_id: Option<Uuid>,
+    ) -> AppResult<bool> {
+        let conflicting =
+            Self::find_conflicting_bookings(conn, room_id, check_in_date, check_out_date, exclude_booking_id)?;
 
         // Check room status only for immediate bookings (check-in today)
         // Future bookings can be made on Dirty/Cleaning/Occupied rooms since
         // they will be available by the check-in date.
         // Maintenance rooms are always blocked as maintenance duration is unpredictable.
-        let today = Utc::now().date_naive();
         let room_rec: Room = rooms::table
             .find(room_id)
-            .first(&mut conn)
+            .first(conn)
             .map_err(|e| AppError::DatabaseError(e.to_string()))?;
 
-        // Maintenance always blocks bookings
-        if room_rec.status == RoomStatus::Maintenance {
+        // Maintenance always blocks bookings, and decommissioned rooms take no more
+        if room_rec.status == RoomStatus::Maintenance || room_rec.decommissioned_at.is_some() {
             return Ok(false);
         }
 
-        // For same-day check-in, room must be Available or at least cleanable
-        if check_in_date == today {
-            // Room must be Available, Dirty, or Cleaning for same-day check-in
-            // Occupied rooms cannot accept same-day bookings
-            if room_rec.status == RoomStatus::Occupied {
-                return Ok(false);
-            }
+        if !conflicting.is_empty() {
+            return Ok(false);
+        }
+
+        // The owner keeps blocked nights off sale
+        if RoomBlockService::is_blocked_on(conn, room_id, check_in_date, check_out_date)? {
+            return Ok(false);
+        }
+
+        // A guest completing payment keeps the room to themselves
+        let now = self.clock.now();
+        if HoldService::held_by_others(conn, room_id, check_in_date, check_out_date, holder_user_id, now)? {
+            return Ok(false);
         }
 
-        Ok(conflicting.is_empty())
+        // Whole-day overlap lets a stay ending today share the date with a new
+        // arrival, so same-day check-in also looks at today's departure
+        let hotel_settings = Settings::load(conn)?;
+        let now = now.with_timezone(&hotel_settings.time_zone(settings::HOTEL_TIMEZONE));
+        if check_in_date == now.date_naive() {
+            let departure = Self::departure_on(conn, room_id, check_in_date, exclude_booking_id)?;
+            return Ok(Self::ready_for_same_day_arrival(
+                room_rec.status,
+                departure,
+                now.time(),
+                hotel_settings.time_of_day(settings::CHECK_IN_TIME),
+            ));
+        }
+
+        Ok(true)
     }
 
-    /// Create a new booking
-    pub fn create_booking(
-        &self,
-        guest_name: &str,
+    /// Status of the stay leaving a room on `date`: a guest still in house
+    /// (CheckedIn or Overstay ending on or before `date`) wins over one who
+    /// already checked out that day
+    fn departure_on(
+        conn: &mut PgConnection,
         room_id: Uuid,
+        date: NaiveDate,
+        exclude_booking_id: Option<Uuid>,
+    ) -> AppResult<Option<BookingStatus>> {
+        let mut query = bookings::table
+            .filter(bookings::room_id.eq(room_id))
+            .filter(
+                bookings::status
+                    .eq_any([BookingStatus::CheckedIn, BookingStatus::Overstay])
+                    .and(bookings::check_out_date.le(date))
+                    .or(bookings::status
+                        .eq(BookingStatus::CheckedOut)
+                        .and(bookings::check_out_date.eq(date))),
+            )
+            .select(bookings::status)
+            .into_boxed();
+
+        if let Some(booking_id) = exclude_booking_id {
+            query = query.filter(bookings::id.ne(booking_id));
+        }
+
+        let statuses: Vec<BookingStatus> = query.load(conn)?;
+        Ok(statuses
+            .iter()
+            .find(|s| **s != BookingStatus::CheckedOut)
+            .or(statuses.first())
+            .copied())
+    }
+
+    /// Whether a room with no overlapping booking can take a guest arriving today
+    ///
+    /// - An Occupied room, or one whose departing guest is still in house, cannot.
+    /// - After a departure today the room must have been cleaned (Available).
+    /// - Otherwise a Dirty or Cleaning room can still be turned before the
+    ///   check-in time, but not once arrivals are due.
+    pub fn ready_for_same_day_arrival(
+        room_status: RoomStatus,
+        departure: Option<BookingStatus>,
+        now: NaiveTime,
+        check_in_time: NaiveTime,
+    ) -> bool {
+        match (room_status, departure) {
+            (RoomStatus::Maintenance | RoomStatus::Occupied, _) => false,
+            (_, Some(BookingStatus::CheckedIn | BookingStatus::Overstay)) => false,
+            (status, Some(_)) => status == RoomStatus::Available,
+            (RoomStatus::Available, None) => true,
+            (RoomStatus::Dirty | RoomStatus::Cleaning, None) => now < check_in_time,
+        }
+    }
+
+    /// Lock a room row until the end of the current transaction so concurrent
+    /// bookings of the same room run their overlap check one at a time
+    pub fn lock_room(conn: &mut PgConnection, room_id: Uuid) -> AppResult<Room> {
+        rooms::table
+            .find(room_id)
+            .for_update()
+            .first(conn)
+            .optional()?
+            .ok_or_else(|| AppError::NotFound(format!("Room with ID '{}' not found", room_id)))
+    }
+
+    /// Normalize a guest name for duplicate detection: trimmed, lowercase,
+    /// inner whitespace collapsed
+    pub fn normalize_guest_name(name: &str) -> String {
+        name.split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase()
+    }
+
+    /// Trimmed guest name, refused when empty or over 100 characters
+    pub fn validate_guest_name(guest_name: &str) -> AppResult<&str> {
+        let guest_name = guest_name.trim();
+        if guest_name.is_empty() {
+            return Err(AppError::ValidationError(
+                "Guest name is required".to_string(),
+            ));
+        }
+        if guest_name.chars().count() > 100 {
+            return Err(AppError::ValidationError(
+                "Guest name must be 100 characters or less".to_string(),
+            ));
+        }
+        Ok(guest_name)
+    }
+
+    /// Non-cancelled bookings in any room overlapping the requested stay that
+    /// belong to the same guest, by normalized name (as `normalize_guest_name`
+    /// does it) or by linked guest account
+    pub fn find_guest_stays(
+        conn: &mut PgConnection,
         check_in_date: NaiveDate,
         check_out_date: NaiveDate,
-        price: Option<BigDecimal>,
+        guest_name: &str,
+        guest_user_id: Option<Uuid>,
+    ) -> AppResult<Vec<Booking>> {
+        use diesel::dsl::sql;
+        use diesel::sql_types::{Bool, Text};
+
+        let same_name = sql::<Bool>("lower(regexp_replace(btrim(bookings.guest_name), '\\s+', ' ', 'g')) = ")
+            .bind::<Text, _>(Self::normalize_guest_name(guest_name));
+        let mut query = bookings::table
+            .filter(bookings::status.ne(BookingStatus::Cancelled))
+            .filter(bookings::check_in_date.lt(check_out_date))
+            .filter(bookings::check_out_date.gt(check_in_date))
+            .into_boxed();
+        query = match guest_user_id {
+            Some(user_id) => query.filter(same_name.or(bookings::created_by_user_id.eq(user_id).assume_not_null())),
+            None => query.filter(same_name),
+        };
+        query
+            .order(bookings::check_in_date.asc())
+            .load(conn)
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
+    /// Pairs of bookings that block the same room on shared nights, where the
+    /// shared nights touch `from..=to`. One self-join, each pair listed once.
+    pub fn find_overlapping_bookings(
+        &self,
+        from: NaiveDate,
+        to: NaiveDate,
+        room_id: Option<Uuid>,
+    ) -> AppResult<Vec<BookingConflict>> {
+        if from > to {
+            return Err(AppError::ValidationError(
+                "from must not be after to".to_string(),
+            ));
+        }
+
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let other = diesel::alias!(bookings as other);
+        let blocking: Vec<BookingStatus> = BookingStatus::ALL
+            .into_iter()
+            .filter(|s| s.blocks_availability())
+            .collect();
+
+        let mut query = bookings::table
+            .inner_join(rooms::table)
+            .inner_join(
+                other.on(other
+                    .field(bookings::room_id)
+                    .eq(bookings::room_id)
+                    .and(other.field(bookings::id).gt(bookings::id))
+                    .and(other.field(bookings::check_in_date).lt(bookings::check_out_date))
+                    .and(other.field(bookings::check_out_date).gt(bookings::check_in_date))),
+            )
+            .filter(bookings::status.eq_any(blocking.clone()))
+            .filter(other.field(bookings::status).eq_any(blocking))
+            .filter(bookings::check_in_date.le(to))
+            .filter(other.field(bookings::check_in_date).le(to))
+            .filter(bookings::check_out_date.gt(from))
+            .filter(other.field(bookings::check_out_date).gt(from))
+            .into_boxed();
+
+        if let Some(room_id) = room_id {
+            query = query.filter(bookings::room_id.eq(room_id));
+        }
+
+        let pairs: Vec<(Booking, Room, Booking)> = query
+            .order((rooms::number.asc(), bookings::check_in_date.asc()))
+            .select((bookings::all_columns, rooms::all_columns, other.fields(bookings::all_columns)))
+            .load(&mut conn)?;
+
+        Ok(pairs
+            .into_iter()
+            .map(|(first, room, second)| BookingConflict {
+                overlap_from: first.check_in_date.max(second.check_in_date),
+                overlap_to: first.check_out_date.min(second.check_out_date),
+                room,
+                first,
+                second,
+            })
+            .collect())
+    }
+
+    /// Refuse a booking that duplicates an existing stay unless overridden
+    ///
+    /// # Errors
+    /// * `Conflict` - Listing the references of the overlapping bookings
+    pub fn check_duplicate_stays(duplicates: &[Booking], allow_duplicate: bool) -> AppResult<()> {
+        if duplicates.is_empty() || allow_duplicate {
+            return Ok(());
+        }
+
+        let references: Vec<&str> = duplicates.iter().map(|b| b.reference.as_str()).collect();
+        Err(AppError::Conflict(format!(
+            "Guest already has an overlapping booking: {}",
+            references.join(", ")
+        )))
+    }
+
+    /// Create a new booking taken by staff
+    ///
+    /// `request.allow_duplicate` skips the check for another stay by the same
+    /// guest on overlapping dates. `actor_user_id` is the receptionist or
+    /// admin taking the booking, recorded as its creator.
+    pub fn create_booking(
+        &self,
+        request: &StaffBookingRequest,
+        actor_user_id: Option<Uuid>,
     ) -> AppResult<Booking> {
+        self.create_staff_booking(request, actor_user_id)
+            .map(|created| created.booking)
+    }
+
+    /// Create a staff booking. With `override_conflict` a room that is only
+    /// unavailable for a same-day turnover (see `overridable_departures_on`)
+    /// is booked anyway and the guests due out are recorded as a warning in
+    /// the booking's audit trail; callers must check the actor is an admin.
+    ///
+    /// # Errors
+    /// * `RoomUnavailable` - The room is under maintenance, or taken and the
+    ///   conflict is not overridden or not a same-day turnover
+    pub fn create_staff_booking(
+        &self,
+        request: &StaffBookingRequest,
+        actor_user_id: Option<Uuid>,
+    ) -> AppResult<StaffBookingCreated> {
+        let (guest_name, room_id, check_in_date, check_out_date) = (
+            request.guest_name.as_str(),
+            request.room_id,
+            request.check_in_date,
+            request.check_out_date,
+        );
         self.validate_dates(check_in_date, check_out_date)?;
+        let guest_name = Self::validate_guest_name(guest_name)?;
 
         let mut conn = self
             .pool
             .get()
             .map_err(|e| AppError::DatabaseError(e.to_string()))?;
 
-        let room: Room = rooms::table
-            .find(room_id)
-            .first(&mut conn)
-            .map_err(|_| AppError::NotFound(format!("Room with ID '{}' not found", room_id)))?;
+        // The room lock makes the availability check and insert atomic with
+        // respect to other bookings of this room
+        conn.transaction::<_, AppError, _>(|conn| {
+            let room = Self::lock_room(conn, room_id)?;
 
-        // Maintenance rooms are always blocked
-        if room.status == RoomStatus::Maintenance {
+            // Maintenance rooms are always blocked
+            if room.status == RoomStatus::Maintenance {
+                return Err(AppError::RoomUnavailable(format!(
+                    "Room {} is under maintenance",
+                    room.number
+                )));
+            }
+            // Overriding a conflict never reopens a decommissioned room
+            if room.decommissioned_at.is_some() {
+                return Err(AppError::RoomUnavailable(format!(
+                    "Room {} has been decommissioned",
+                    room.number
+                )));
+            }
+
+            // Nor does it book over nights the owner has blocked
+            RoomBlockService::check_not_blocked_on(conn, &room, check_in_date, check_out_date)?;
+
+            // check_availability handles both booking conflicts and room status checks
+            let available =
+                self.check_availability_on(conn, room_id, check_in_date, check_out_date, None, None)?;
+            if !available && !request.override_conflict {
+                return Err(AppError::RoomUnavailable(format!(
+                    "Room {} is not available for the selected dates",
+                    room.number
+                )));
+            }
+            let departures = if available {
+                Vec::new()
+            } else {
+                self.overridable_departures_on(conn, &room, check_in_date, check_out_date)?
+            };
+
+            // Phone bookings often duplicate an online one; staff may override
+            Self::check_duplicate_stays(
+                &Self::find_guest_stays(conn, check_in_date, check_out_date, guest_name, None)?,
+                request.allow_duplicate,
+            )?;
+
+            let booking_price = match &request.price {
+                Some(price) => money::vnd_field("price", price)?,
+                None => PricingService::price_for_stay_on(conn, &room, check_in_date, check_out_date)?,
+            };
+
+            let new_booking = NewBooking {
+                reference: "",
+                guest_name,
+                room_id,
+                check_in_date,
+                check_out_date,
+                created_by_user_id: actor_user_id,
+                creation_source: "staff",
+                price: booking_price,
+                group_reference: None,
+            };
+
+            let booking = Self::insert_booking(conn, &new_booking)?;
+            if available {
+                return Ok(StaffBookingCreated {
+                    booking,
+                    overridden_booking_ids: Vec::new(),
+                });
+            }
+
+            let details = if departures.is_empty() {
+                format!("Warning: room {} was not ready; availability overridden", room.number)
+            } else {
+                let references: Vec<&str> = departures.iter().map(|b| b.reference.as_str()).collect();
+                format!(
+                    "Warning: room {} booked over {}; availability overridden",
+                    room.number,
+                    references.join(", ")
+                )
+            };
+            Self::record_event(
+                conn,
+                &NewBookingEvent::conflict_override(booking.id, details, actor_user_id),
+            )?;
+
+            Ok(StaffBookingCreated {
+                booking,
+                overridden_booking_ids: departures.iter().map(|b| b.id).collect(),
+            })
+        })
+    }
+
+    /// Guests an admin may book over: those still in house (CheckedIn or
+    /// Overstay) who are due out by `check_in_date`, such as an overstay
+    /// guest leaving by noon. The override covers only this same-day
+    /// turnover, so a stay overlapping the new one or another guest's hold
+    /// refuses it.
+    ///
+    /// # Errors
+    /// * `RoomUnavailable` - The room is taken for more than a turnover
+    fn overridable_departures_on(
+        &self,
+        conn: &mut PgConnection,
+        room: &Room,
+        check_in_date: NaiveDate,
+        check_out_date: NaiveDate,
+    ) -> AppResult<Vec<Booking>> {
+        let overlapping = Self::find_conflicting_bookings(conn, room.id, check_in_date, check_out_date, None)?;
+        if !overlapping.is_empty()
+            || HoldService::held_by_others(conn, room.id, check_in_date, check_out_date, None, self.clock.now())?
+        {
             return Err(AppError::RoomUnavailable(format!(
-                "Room {} is under maintenance",
+                "Room {} is taken for the selected dates; only a same-day turnover can be overridden",
                 room.number
             )));
         }
 
-        // check_availability handles both booking conflicts and room status checks
-        if !self.check_availability(room_id, check_in_date, check_out_date, None)? {
-            return Err(AppError::RoomUnavailable(format!(
-                "Room {} is not available for the selected dates",
-                room.number
+        bookings::table
+            .filter(bookings::room_id.eq(room.id))
+            .filter(bookings::status.eq_any([BookingStatus::CheckedIn, BookingStatus::Overstay]))
+            .filter(bookings::check_out_date.le(check_in_date))
+            .order(bookings::check_in_date.asc())
+            .load(conn)
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
+    /// Validate a room calendar range (inclusive, at most `MAX_ROOM_CALENDAR_DAYS` days)
+    pub fn validate_room_calendar_range(from: NaiveDate, to: NaiveDate) -> AppResult<()> {
+        if to < from {
+            return Err(AppError::ValidationError(
+                "to must be on or after from".to_string(),
+            ));
+        }
+
+        let days = (to - from).num_days() + 1;
+        if days > MAX_ROOM_CALENDAR_DAYS {
+            return Err(AppError::ValidationError(format!(
+                "Calendar range cannot exceed {} days (requested {})",
+                MAX_ROOM_CALENDAR_DAYS, days
             )));
         }
 
-        let reference = self.generate_reference()?;
+        Ok(())
+    }
+
+    /// Expand one room's stays into a per-night calendar from `from` to `to`
+    /// (inclusive)
+    ///
+    /// A stay covers the nights check_in_date <= date < check_out_date;
+    /// overstaying guests keep the room at least through `today`. Where stays
+    /// overlap, the guest in house wins over an upcoming arrival, which wins
+    /// over a guest who already left.
+    pub fn build_room_calendar(
+        stays: &[CalendarStay],
+        from: NaiveDate,
+        to: NaiveDate,
+        today: NaiveDate,
+    ) -> Vec<RoomCalendarNight> {
+        let rank = |status: BookingStatus| match status {
+            BookingStatus::CheckedIn | BookingStatus::Overstay => 0,
+            BookingStatus::Upcoming => 1,
+            _ => 2,
+        };
+
+        let mut nights = Vec::new();
+        let mut date = from;
+        while date <= to {
+            let booking = stays
+                .iter()
+                .filter(|(_, _, check_in, check_out, status)| {
+                    let effective_out = if *status == BookingStatus::Overstay {
+                        (*check_out).max(today + Duration::days(1))
+                    } else {
+                        *check_out
+                    };
+                    *check_in <= date && date < effective_out
+                })
+                .min_by_key(|(_, _, _, _, status)| rank(*status))
+                .map(|(_, reference, _, _, status)| RoomNightBooking {
+                    reference: reference.clone(),
+                    status: *status,
+                });
+
+            nights.push(RoomCalendarNight { date, booking });
+            date += Duration::days(1);
+        }
+
+        nights
+    }
+
+    /// Stays of the given rooms (all rooms when None) touching any night
+    /// from `from` to `to`, in one query. Cancelled bookings and no-shows
+    /// never occupied their nights and are left out.
+    fn load_calendar_stays(
+        conn: &mut PgConnection,
+        room_id: Option<Uuid>,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> AppResult<Vec<CalendarStay>> {
+        let mut query = bookings::table
+            .filter(bookings::status.ne_all([BookingStatus::Cancelled, BookingStatus::NoShow]))
+            .filter(bookings::check_in_date.le(to))
+            .filter(
+                bookings::check_out_date
+                    .gt(from)
+                    .or(bookings::status.eq(BookingStatus::Overstay)),
+            )
+            .into_boxed();
+
+        if let Some(room_id) = room_id {
+            query = query.filter(bookings::room_id.eq(room_id));
+        }
+
+        Ok(query
+            .select((
+                bookings::room_id,
+                bookings::reference,
+                bookings::check_in_date,
+                bookings::check_out_date,
+                bookings::status,
+            ))
+            .load(conn)?)
+    }
+
+    /// Per-night occupancy of one room from `from` to `to` (inclusive)
+    pub fn get_room_calendar(
+        &self,
+        room_id: Uuid,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> AppResult<Vec<RoomCalendarNight>> {
+        Self::validate_room_calendar_range(from, to)?;
+
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let exists: bool = diesel::select(diesel::dsl::exists(rooms::table.find(room_id)))
+            .get_result(&mut conn)?;
+        if !exists {
+            return Err(AppError::NotFound(format!("Room with ID '{}' not found", room_id)));
+        }
+
+        let stays = Self::load_calendar_stays(&mut conn, Some(room_id), from, to)?;
+        Ok(Self::build_room_calendar(&stays, from, to, self.hotel_today(&mut conn)?))
+    }
+
+    /// Per-night occupancy of every room from `from` to `to` (inclusive),
+    /// keyed by room id
+    pub fn get_rooms_calendar(
+        &self,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> AppResult<HashMap<Uuid, Vec<RoomCalendarNight>>> {
+        Self::validate_room_calendar_range(from, to)?;
 
-        if guest_name.trim().is_empty() {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let room_ids: Vec<Uuid> = rooms::table.select(rooms::id).load(&mut conn)?;
+        let mut stays_by_room: HashMap<Uuid, Vec<CalendarStay>> = HashMap::new();
+        for stay in Self::load_calendar_stays(&mut conn, None, from, to)? {
+            stays_by_room.entry(stay.0).or_default().push(stay);
+        }
+
+        let today = self.hotel_today(&mut conn)?;
+        Ok(room_ids
+            .into_iter()
+            .map(|room_id| {
+                let stays = stays_by_room.remove(&room_id).unwrap_or_default();
+                (room_id, Self::build_room_calendar(&stays, from, to, today))
+            })
+            .collect())
+    }
+
+    /// Check that a room can take a walk-in guest right now. Only an
+    /// Available room can; Dirty and Cleaning rooms are still with
+    /// housekeeping.
+    pub fn check_walk_in_room(room: &Room) -> AppResult<()> {
+        match room.status {
+            RoomStatus::Available => Ok(()),
+            RoomStatus::Dirty | RoomStatus::Cleaning => Err(AppError::RoomUnavailable(format!(
+                "Room {} is {} and has not been released by housekeeping yet",
+                room.number,
+                room.status.as_str()
+            ))),
+            RoomStatus::Occupied => Err(AppError::RoomUnavailable(format!(
+                "Room {} is occupied",
+                room.number
+            ))),
+            RoomStatus::Maintenance => Err(AppError::RoomUnavailable(format!(
+                "Room {} is under maintenance",
+                room.number
+            ))),
+        }
+    }
+
+    /// Create a booking for a guest standing at the desk and check them in
+    /// straight away: the booking starts CheckedIn and the room becomes
+    /// Occupied in the same transaction, so a failure leaves nothing behind.
+    ///
+    /// The stay must start today and the room must be Available now.
+    pub fn create_walk_in(
+        &self,
+        request: &StaffBookingRequest,
+        actor_user_id: Uuid,
+    ) -> AppResult<BookingWithRoom> {
+        let (room_id, check_in_date, check_out_date) =
+            (request.room_id, request.check_in_date, request.check_out_date);
+        self.validate_dates(check_in_date, check_out_date)?;
+
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        // A walk-in moves in at once, so there is no turnover to wait for
+        if request.override_conflict {
             return Err(AppError::ValidationError(
-                "Guest name is required".to_string(),
+                "Availability conflicts cannot be overridden for walk-ins".to_string(),
             ));
         }
 
-        if guest_name.len() > 100 {
+        let today = self.hotel_today(&mut conn)?;
+        if check_in_date != today {
+            return Err(AppError::ValidationError(format!(
+                "Walk-in bookings must check in today ({})",
+                today
+            )));
+        }
+
+        let guest_name = Self::validate_guest_name(&request.guest_name)?;
+
+        conn.transaction::<_, AppError, _>(|conn| {
+            let room = Self::lock_room(conn, room_id)?;
+            Self::check_walk_in_room(&room)?;
+            RoomBlockService::check_not_blocked_on(conn, &room, check_in_date, check_out_date)?;
+
+            // An arrival already booked into this room for tonight keeps it
+            if !self.check_availability_on(conn, room_id, check_in_date, check_out_date, None, None)? {
+                return Err(AppError::RoomUnavailable(format!(
+                    "Room {} is not available for the selected dates",
+                    room.number
+                )));
+            }
+
+            Self::check_duplicate_stays(
+                &Self::find_guest_stays(conn, check_in_date, check_out_date, guest_name, None)?,
+                request.allow_duplicate,
+            )?;
+
+            let booking_price = match &request.price {
+                Some(price) => money::vnd_field("price", price)?,
+                None => PricingService::price_for_stay_on(conn, &room, check_in_date, check_out_date)?,
+            };
+
+            let created = Self::insert_booking(
+                conn,
+                &NewBooking {
+                    reference: "",
+                    guest_name,
+                    room_id,
+                    check_in_date,
+                    check_out_date,
+                    created_by_user_id: Some(actor_user_id),
+                    creation_source: "staff",
+                    price: booking_price,
+                    group_reference: None,
+                },
+            )?;
+
+            let booking: Booking = diesel::update(bookings::table.find(created.id))
+                .set((
+                    bookings::status.eq(BookingStatus::CheckedIn),
+                    bookings::checked_in_at.eq(Some(self.clock.now())),
+                ))
+                .get_result(conn)?;
+            let room = RoomService::update_room_status_on(conn, room_id, RoomStatus::Occupied, Some(actor_user_id))?;
+
+            Self::record_event(
+                conn,
+                &NewBookingEvent {
+                    details: Some("Walk-in".to_string()),
+                    ..NewBookingEvent::status_change(
+                        booking.id,
+                        created.status,
+                        BookingStatus::CheckedIn,
+                        Some(actor_user_id),
+                    )
+                },
+            )?;
+
+            Ok(BookingWithRoom {
+                booking,
+                room: Some(room),
+                modification_count: 0,
+                notes: None,
+                created_by: Self::staff_username(conn, actor_user_id)?,
+            })
+        })
+    }
+
+    /// Check the rooms of a group booking: at least one, none twice, and no
+    /// more than `MAX_GROUP_ROOMS`
+    pub fn validate_group_rooms(room_ids: &[Uuid]) -> AppResult<()> {
+        if room_ids.is_empty() {
             return Err(AppError::ValidationError(
-                "Guest name must be 100 characters or less".to_string(),
+                "A group booking needs at least one room".to_string(),
             ));
         }
+        if room_ids.len() > MAX_GROUP_ROOMS {
+            return Err(AppError::ValidationError(format!(
+                "A group booking can hold at most {} rooms",
+                MAX_GROUP_ROOMS
+            )));
+        }
 
-        let booking_price = price.unwrap_or_else(|| {
-            let nights = (check_out_date - check_in_date).num_days();
-            &room.price * BigDecimal::from(nights.max(1))
-        });
+        let mut seen = HashSet::new();
+        if let Some(repeated) = room_ids.iter().find(|id| !seen.insert(**id)) {
+            return Err(AppError::ValidationError(format!(
+                "Room '{}' is listed more than once",
+                repeated
+            )));
+        }
 
-        let new_booking = NewBooking {
-            reference: &reference,
-            guest_name: guest_name.trim(),
-            room_id,
-            check_in_date,
-            check_out_date,
-            created_by_user_id: None,
-            creation_source: "staff",
-            price: booking_price,
-        };
+        Ok(())
+    }
 
-        diesel::insert_into(bookings::table)
-            .values(&new_booking)
-            .get_result(&mut conn)
-            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    /// Create one booking per room for the same guest and dates, all sharing
+    /// a fresh `group_reference`. Either every room is booked or none is.
+    ///
+    /// The same-guest duplicate check of `create_booking` is skipped, since
+    /// every booking of the group carries the same name. `actor_user_id` is
+    /// recorded as the creator of every booking.
+    ///
+    /// # Errors
+    /// * `RoomUnavailable` - Listing the numbers of every room that cannot
+    ///   be booked for the dates
+    /// * `NotFound` - A room does not exist
+    pub fn create_group_booking(
+        &self,
+        guest_name: &str,
+        room_ids: Vec<Uuid>,
+        check_in_date: NaiveDate,
+        check_out_date: NaiveDate,
+        actor_user_id: Uuid,
+    ) -> AppResult<Vec<BookingWithRoom>> {
+        self.validate_dates(check_in_date, check_out_date)?;
+        Self::validate_group_rooms(&room_ids)?;
+
+        let guest_name = Self::validate_guest_name(guest_name)?;
+
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        conn.transaction::<_, AppError, _>(|conn| {
+            // Lock in id order so two overlapping group bookings cannot deadlock
+            let mut lock_order = room_ids.clone();
+            lock_order.sort();
+            let mut locked = HashMap::new();
+            for room_id in lock_order {
+                locked.insert(room_id, Self::lock_room(conn, room_id)?);
+            }
+
+            let mut unavailable = Vec::new();
+            for room in locked.values() {
+                if !self.check_availability_on(conn, room.id, check_in_date, check_out_date, None, None)? {
+                    unavailable.push(room.number.clone());
+                }
+            }
+            if !unavailable.is_empty() {
+                unavailable.sort();
+                return Err(AppError::RoomUnavailable(format!(
+                    "Rooms not available for the selected dates: {}",
+                    unavailable.join(", ")
+                )));
+            }
+
+            let group_reference = Self::fresh_group_reference(conn)?;
+            let created_by = Self::staff_username(conn, actor_user_id)?;
+
+            let mut created = Vec::with_capacity(room_ids.len());
+            for room_id in &room_ids {
+                let room = locked[room_id].clone();
+                let price = PricingService::price_for_stay_on(conn, &room, check_in_date, check_out_date)?;
+                let new_booking = NewBooking {
+                    reference: "",
+                    guest_name,
+                    room_id: *room_id,
+                    check_in_date,
+                    check_out_date,
+                    created_by_user_id: Some(actor_user_id),
+                    creation_source: "staff",
+                    price,
+                    group_reference: Some(&group_reference),
+                };
+
+                let booking = Self::insert_booking(conn, &new_booking)?;
+
+                created.push(BookingWithRoom {
+                    booking,
+                    room: Some(room),
+                    modification_count: 0,
+                    notes: None,
+                    created_by: created_by.clone(),
+                });
+            }
+
+            Ok(created)
+        })
+    }
+
+    /// A group reference no existing booking uses. Group references are not
+    /// unique per row, so freshness is checked rather than left to a constraint.
+    fn fresh_group_reference(conn: &mut PgConnection) -> AppResult<String> {
+        for _ in 0..MAX_REFERENCE_ATTEMPTS {
+            let candidate = Self::generate_group_reference();
+            let taken: bool = diesel::select(diesel::dsl::exists(
+                bookings::table.filter(bookings::group_reference.eq(&candidate)),
+            ))
+            .get_result(conn)?;
+            if !taken {
+                return Ok(candidate);
+            }
+        }
+
+        Err(AppError::InternalError(
+            "Failed to generate unique group reference".to_string(),
+        ))
     }
 
     /// Get a booking by ID
@@ -305,6 +1585,124 @@ impl BookingService {
             .map_err(|_| AppError::NotFound(format!("Booking '{}' not found", reference)))
     }
 
+    /// Whether `last_name` matches the guest's family name, case-insensitively.
+    /// Both the first and the last word of the name count, since Vietnamese
+    /// names put the family name first.
+    pub fn surname_matches(guest_name: &str, last_name: &str) -> bool {
+        let last_name = Self::normalize_guest_name(last_name);
+        if last_name.is_empty() {
+            return false;
+        }
+
+        let name = Self::normalize_guest_name(guest_name);
+        let words: Vec<&str> = name.split(' ').collect();
+        words.first() == Some(&last_name.as_str()) || words.last() == Some(&last_name.as_str())
+    }
+
+    /// Look up a booking for the public, unauthenticated endpoint
+    ///
+    /// # Returns
+    /// * `None` when the reference is unknown or the surname does not match,
+    ///   so callers cannot tell the two apart
+    pub fn public_lookup(&self, reference: &str, last_name: &str) -> AppResult<Option<PublicBookingView>> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let found: Option<(Booking, RoomType)> = bookings::table
+            .inner_join(rooms::table)
+            .filter(bookings::reference.eq(reference.trim().to_uppercase()))
+            .select((Booking::as_select(), rooms::room_type))
+            .first(&mut conn)
+            .optional()?;
+
+        Ok(found
+            .filter(|(booking, _)| Self::surname_matches(&booking.guest_name, last_name))
+            .map(|(booking, room_type)| PublicBookingView {
+                nights: (booking.check_out_date - booking.check_in_date).num_days(),
+                reference: booking.reference,
+                room_type,
+                check_in_date: booking.check_in_date,
+                check_out_date: booking.check_out_date,
+                status: booking.status,
+                total: booking.price,
+            }))
+    }
+
+    /// Status of a booking by reference alone, for the public status page
+    ///
+    /// # Returns
+    /// * `None` when the reference is unknown
+    pub fn public_status(&self, reference: &str) -> AppResult<Option<PublicBookingStatus>> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let found: Option<(String, String, NaiveDate, NaiveDate, BookingStatus)> = bookings::table
+            .inner_join(rooms::table)
+            .filter(bookings::reference.eq(reference.trim().to_uppercase()))
+            .select((
+                bookings::reference,
+                rooms::number,
+                bookings::check_in_date,
+                bookings::check_out_date,
+                bookings::status,
+            ))
+            .first(&mut conn)
+            .optional()?;
+
+        Ok(found.map(
+            |(reference, room_number, check_in_date, check_out_date, status)| PublicBookingStatus {
+                reference,
+                room_number,
+                check_in_date,
+                check_out_date,
+                status,
+            },
+        ))
+    }
+
+    /// Free rooms and the nightly price per room type for a stay, for the
+    /// unauthenticated availability search. Types without rooms are left out.
+    pub fn public_availability(
+        &self,
+        check_in_date: NaiveDate,
+        check_out_date: NaiveDate,
+        room_type: Option<RoomType>,
+    ) -> AppResult<Vec<PublicRoomTypeAvailability>> {
+        self.validate_dates(check_in_date, check_out_date)?;
+
+        let rooms = RoomService::new(self.pool.clone()).list_rooms(&RoomFilter {
+            room_type,
+            ..RoomFilter::default()
+        })?;
+        let room_types = RoomTypeService::new(self.pool.clone()).list_room_types(true)?;
+
+        let mut result = Vec::new();
+        for room_type in room_types {
+            let of_type: Vec<&Room> = rooms.iter().filter(|r| r.room_type_id == room_type.id).collect();
+            if of_type.is_empty() {
+                continue;
+            }
+
+            let mut available = Vec::new();
+            for room in of_type {
+                if self.check_availability(room.id, check_in_date, check_out_date, None, None)? {
+                    available.push(room);
+                }
+            }
+            result.push(PublicRoomTypeAvailability {
+                room_type: room_type.code,
+                available_rooms: available.len(),
+                nightly_price: available.iter().map(|r| r.price.clone()).min(),
+            });
+        }
+
+        Ok(result)
+    }
+
     /// Get a booking with room details
     pub fn get_booking_with_room(&self, booking_id: Uuid) -> AppResult<BookingWithRoom> {
         let mut conn = self
@@ -312,9 +1710,12 @@ impl BookingService {
             .get()
             .map_err(|e| AppError::DatabaseError(e.to_string()))?;
 
-        let booking: Booking = bookings::table.find(booking_id).first(&mut conn).map_err(
-            |_| AppError::NotFound(format!("Booking with ID '{}' not found", booking_id)),
-        )?;
+        let (booking, created_by): (Booking, Option<String>) = bookings::table
+            .left_join(users::table.on(Self::staff_creator_join()))
+            .filter(bookings::id.eq(booking_id))
+            .select((Booking::as_select(), users::username.nullable()))
+            .first(&mut conn)
+            .map_err(|_| AppError::NotFound(format!("Booking with ID '{}' not found", booking_id)))?;
 
         let room: Option<Room> = rooms::table
             .find(booking.room_id)
@@ -322,7 +1723,14 @@ impl BookingService {
             .optional()
             .map_err(|e| AppError::DatabaseError(e.to_string()))?;
 
-        Ok(BookingWithRoom { booking, room })
+        let modification_count = Self::modification_counts(&mut conn, &[booking.id])
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?
+            .remove(&booking.id)
+            .unwrap_or(0);
+
+        let notes = Self::load_notes(&mut conn, booking.id)?;
+
+        Ok(BookingWithRoom { booking, room, modification_count, notes: Some(notes), created_by })
     }
 
     /// Get a booking with room and payment summary
@@ -341,58 +1749,238 @@ impl BookingService {
         })
     }
 
-    /// List bookings with optional filters
-    pub fn list_bookings(
+    /// List bookings with optional filters
+    pub fn list_bookings(&self, filter: &BookingListFilter) -> AppResult<Vec<BookingWithRoom>> {
+        let creation_source = filter.creation_source.map(parse_creation_source).transpose()?;
+
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let mut query = bookings::table
+            .left_join(users::table.on(Self::staff_creator_join()))
+            .select((Booking::as_select(), users::username.nullable()))
+            .into_boxed();
+
+        if let Some(group) = filter.group_reference {
+            query = query.filter(bookings::group_reference.eq(group.trim().to_uppercase()));
+        }
+
+        if let Some(status) = filter.status {
+            query = query.filter(bookings::status.eq(status));
+        }
+
+        if let Some(name) = filter.guest_name {
+            let pattern = format!("%{}%", name);
+            query = query.filter(bookings::guest_name.ilike(pattern));
+        }
+
+        if let Some(from) = filter.from_date {
+            query = query.filter(bookings::check_in_date.ge(from));
+        }
+
+        if let Some(to) = filter.to_date {
+            query = query.filter(bookings::check_in_date.le(to));
+        }
+
+        if let Some(room_id) = filter.room_id {
+            query = query.filter(bookings::room_id.eq(room_id));
+        }
+
+        if let Some(source) = creation_source {
+            query = query.filter(bookings::creation_source.eq(source));
+        }
+
+        let booking_list: Vec<(Booking, Option<String>)> = query
+            .order(bookings::check_in_date.asc())
+            .load(&mut conn)
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let room_ids: Vec<Uuid> = booking_list.iter().map(|(b, _)| b.room_id).collect();
+        let rooms_list: Vec<Room> = rooms::table
+            .filter(rooms::id.eq_any(&room_ids))
+            .load(&mut conn)
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let booking_ids: Vec<Uuid> = booking_list.iter().map(|(b, _)| b.id).collect();
+        let counts = Self::modification_counts(&mut conn, &booking_ids)
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let result: Vec<BookingWithRoom> = booking_list
+            .into_iter()
+            .map(|(booking, created_by)| {
+                let room = rooms_list.iter().find(|r| r.id == booking.room_id).cloned();
+                let modification_count = counts.get(&booking.id).copied().unwrap_or(0);
+                BookingWithRoom { booking, room, modification_count, notes: None, created_by }
+            })
+            .collect();
+
+        Ok(result)
+    }
+
+    /// Search bookings from partial details given over the phone: reference
+    /// prefix, part of the guest name, part of the booking guest's phone, or
+    /// the exact room number. Exact reference matches come first, then the
+    /// latest check-in; at most `BOOKING_SEARCH_LIMIT` results.
+    pub fn search_bookings(&self, q: &str) -> AppResult<Vec<BookingWithRoom>> {
+        validate_search_query(q)?;
+        let q = q.trim();
+        let reference = q.to_uppercase();
+        let contains = format!("%{}%", q);
+
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        // Guest portal bookings are created by the guest, so their account
+        // holds the phone number
+        let guest = diesel::alias!(users as guest);
+        let found: Vec<(Booking, Option<Room>, Option<String>)> = bookings::table
+            .left_join(rooms::table)
+            .left_join(users::table.on(Self::staff_creator_join()))
+            .left_join(
+                guest.on(bookings::created_by_user_id
+                    .eq(guest.field(users::id).nullable())
+                    .and(guest.field(users::role).eq(UserRole::Guest))),
+            )
+            .filter(
+                bookings::reference
+                    .ilike(format!("{}%", q))
+                    .or(bookings::guest_name.ilike(&contains))
+                    .or(guest.field(users::phone).ilike(&contains).assume_not_null())
+                    .or(rooms::number.eq(q).assume_not_null()),
+            )
+            .select((
+                Booking::as_select(),
+                rooms::all_columns.nullable(),
+                users::username.nullable(),
+            ))
+            .order((
+                bookings::reference.eq(reference).desc(),
+                bookings::check_in_date.desc(),
+                bookings::reference.asc(),
+            ))
+            .limit(BOOKING_SEARCH_LIMIT)
+            .load(&mut conn)
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let booking_ids: Vec<Uuid> = found.iter().map(|(b, _, _)| b.id).collect();
+        let counts = Self::modification_counts(&mut conn, &booking_ids)
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(found
+            .into_iter()
+            .map(|(booking, room, created_by)| {
+                let modification_count = counts.get(&booking.id).copied().unwrap_or(0);
+                BookingWithRoom { booking, room, modification_count, notes: None, created_by }
+            })
+            .collect())
+    }
+
+    /// Join condition from a booking to the staff member who took it. Guest
+    /// portal bookings join nothing, so their creator stays None.
+    fn staff_creator_join() -> diesel::dsl::And<
+        diesel::dsl::Eq<bookings::created_by_user_id, diesel::dsl::Nullable<users::id>>,
+        diesel::dsl::Eq<bookings::creation_source, &'static str>,
+    > {
+        bookings::created_by_user_id
+            .eq(users::id.nullable())
+            .and(bookings::creation_source.eq("staff"))
+    }
+
+    /// Username of a staff member, for responses that create bookings
+    fn staff_username(conn: &mut PgConnection, user_id: Uuid) -> AppResult<Option<String>> {
+        Ok(users::table
+            .find(user_id)
+            .select(users::username)
+            .first::<Option<String>>(conn)
+            .optional()?
+            .flatten())
+    }
+
+    /// Export bookings matching `filter`, ordered by check-in date, handing
+    /// them to `sink` one chunk at a time. Each chunk is a keyset-paginated
+    /// query on its own pooled connection, so a slow consumer neither holds
+    /// a connection nor makes the whole result sit in memory.
+    ///
+    /// `sink` returns false to stop early, e.g. when the client went away.
+    ///
+    /// # Returns
+    /// The number of rows handed to `sink`
+    pub fn export_bookings(
         &self,
-        status_filter: Option<BookingStatus>,
-        guest_name_filter: Option<&str>,
-        from_date: Option<NaiveDate>,
-        to_date: Option<NaiveDate>,
-    ) -> AppResult<Vec<BookingWithRoom>> {
+        filter: &BookingExportFilter,
+        chunk_size: i64,
+        mut sink: impl FnMut(Vec<BookingExportRow>) -> bool,
+    ) -> AppResult<usize> {
+        let mut after = None;
+        let mut exported = 0;
+
+        loop {
+            let chunk = self.export_chunk(filter, after, chunk_size)?;
+            let Some(last) = chunk.last() else { break };
+            after = Some((last.check_in_date, last.id));
+            let full = chunk.len() as i64 == chunk_size;
+
+            exported += chunk.len();
+            if !sink(chunk) || !full {
+                break;
+            }
+        }
+
+        Ok(exported)
+    }
+
+    /// Up to `limit` export rows after the (check-in date, id) key `after`
+    fn export_chunk(
+        &self,
+        filter: &BookingExportFilter,
+        after: Option<(NaiveDate, Uuid)>,
+        limit: i64,
+    ) -> AppResult<Vec<BookingExportRow>> {
         let mut conn = self
             .pool
             .get()
             .map_err(|e| AppError::DatabaseError(e.to_string()))?;
 
-        let mut query = bookings::table.into_boxed();
+        let mut query = bookings::table.inner_join(rooms::table).into_boxed();
 
-        if let Some(status) = status_filter {
+        if let Some(status) = filter.status {
             query = query.filter(bookings::status.eq(status));
         }
-
-        if let Some(name) = guest_name_filter {
-            let pattern = format!("%{}%", name);
-            query = query.filter(bookings::guest_name.ilike(pattern));
-        }
-
-        if let Some(from) = from_date {
+        if let Some(from) = filter.from_date {
             query = query.filter(bookings::check_in_date.ge(from));
         }
-
-        if let Some(to) = to_date {
+        if let Some(to) = filter.to_date {
             query = query.filter(bookings::check_in_date.le(to));
         }
+        if let Some((check_in_date, id)) = after {
+            query = query.filter(
+                bookings::check_in_date
+                    .gt(check_in_date)
+                    .or(bookings::check_in_date.eq(check_in_date).and(bookings::id.gt(id))),
+            );
+        }
 
-        let booking_list: Vec<Booking> = query
-            .order(bookings::check_in_date.asc())
-            .load(&mut conn)
-            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
-
-        let room_ids: Vec<Uuid> = booking_list.iter().map(|b| b.room_id).collect();
-        let rooms_list: Vec<Room> = rooms::table
-            .filter(rooms::id.eq_any(&room_ids))
-            .load(&mut conn)
-            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
-
-        let result: Vec<BookingWithRoom> = booking_list
-            .into_iter()
-            .map(|booking| {
-                let room = rooms_list.iter().find(|r| r.id == booking.room_id).cloned();
-                BookingWithRoom { booking, room }
-            })
-            .collect();
-
-        Ok(result)
+        Ok(query
+            .order((bookings::check_in_date.asc(), bookings::id.asc()))
+            .limit(limit)
+            .select((
+                bookings::id,
+                bookings::reference,
+                bookings::guest_name,
+                rooms::number,
+                rooms::room_type,
+                bookings::check_in_date,
+                bookings::check_out_date,
+                bookings::status,
+                bookings::price,
+                bookings::creation_source,
+                bookings::created_at,
+            ))
+            .load(&mut conn)?)
     }
 
     #[allow(dead_code)]
@@ -424,8 +2012,15 @@ impl BookingService {
             .load::<(Booking, Room)>(&mut conn)
             .map_err(|e| AppError::DatabaseError(e.to_string()))?;
 
+        let booking_ids: Vec<Uuid> = results.iter().map(|(b, _)| b.id).collect();
+        let counts = Self::modification_counts(&mut conn, &booking_ids)
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
         let response = results.into_iter().map(|(booking, room)| {
             BookingWithRoom {
+                modification_count: counts.get(&booking.id).copied().unwrap_or(0),
+                notes: None,
+                created_by: None,
                 booking,
                 room: Some(room)
             }
@@ -445,65 +2040,73 @@ impl BookingService {
         price: Option<BigDecimal>,
     ) -> AppResult<BookingWithRoom> {
         self.validate_dates(check_in_date, check_out_date)?;
+        let guest_name = Self::validate_guest_name(guest_name)?;
 
         let mut conn = self
             .pool
             .get()
             .map_err(|e| AppError::DatabaseError(e.to_string()))?;
 
-        let room: Room = rooms::table
-            .find(room_id)
-            .first(&mut conn)
-            .map_err(|_| AppError::NotFound(format!("Room with ID '{}' not found", room_id)))?;
-
-        // Maintenance rooms are always blocked
-        if room.status == RoomStatus::Maintenance {
-            return Err(AppError::RoomUnavailable(format!(
-                "Room {} is under maintenance",
-                room.number
-            )));
-        }
-
-        // check_availability handles both booking conflicts and room status checks
-        if !self.check_availability(room_id, check_in_date, check_out_date, None)? {
-            return Err(AppError::RoomUnavailable(format!(
-                "Room {} is not available for the selected dates",
-                room.number
-            )));
-        }
-
-        let reference = self.generate_reference()?;
+        // The confirmation email must reach the guest
+        EmailVerificationService::ensure_verified_on(&mut conn, user_id)?;
 
-        if guest_name.trim().is_empty() {
-            return Err(AppError::ValidationError(
-                "Guest name is required".to_string(),
-            ));
-        }
+        // The room lock makes the availability check and insert atomic with
+        // respect to other bookings of this room
+        conn.transaction::<_, AppError, _>(|conn| {
+            let room = Self::lock_room(conn, room_id)?;
 
-        let booking_price = price.unwrap_or_else(|| {
-            let nights = (check_out_date - check_in_date).num_days();
-            &room.price * BigDecimal::from(nights.max(1))
-        });
+            // Maintenance rooms are always blocked
+            if room.status == RoomStatus::Maintenance {
+                return Err(AppError::RoomUnavailable(format!(
+                    "Room {} is under maintenance",
+                    room.number
+                )));
+            }
 
-        let new_booking = NewBooking {
-            reference: &reference,
-            guest_name: guest_name.trim(),
-            room_id,
-            check_in_date,
-            check_out_date,
-            created_by_user_id: Some(user_id),
-            creation_source: "guest",
-            price: booking_price,
-        };
+            RoomBlockService::check_not_blocked_on(conn, &room, check_in_date, check_out_date)?;
 
-        let booking: Booking = diesel::insert_into(bookings::table)
-            .values(&new_booking)
-            .get_result(&mut conn)
-            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+            // check_availability handles both booking conflicts and room status checks
+            if !self.check_availability_on(conn, room_id, check_in_date, check_out_date, None, Some(user_id))? {
+                return Err(AppError::RoomUnavailable(format!(
+                    "Room {} is not available for the selected dates",
+                    room.number
+                )));
+            }
 
-        Ok(BookingWithRoom {
-            booking,
-            room: Some(room),
+            // Guests cannot override the duplicate check
+            Self::check_duplicate_stays(
+                &Self::find_guest_stays(conn, check_in_date, check_out_date, guest_name, Some(user_id))?,
+                false,
+            )?;
+
+            let booking_price = match price {
+                Some(price) => money::vnd_field("price", &price)?,
+                None => PricingService::price_for_stay_on(conn, &room, check_in_date, check_out_date)?,
+            };
+
+            let new_booking = NewBooking {
+                reference: "",
+                guest_name,
+                room_id,
+                check_in_date,
+                check_out_date,
+                created_by_user_id: Some(user_id),
+                creation_source: "guest",
+                price: booking_price,
+                group_reference: None,
+            };
+
+            let booking = Self::insert_booking(conn, &new_booking)?;
+            // The guest's hold on this stay has served its purpose
+            HoldService::release(conn, user_id, room_id, check_in_date, check_out_date)?;
+
+            Ok(BookingWithRoom {
+                booking,
+                room: Some(room),
+                modification_count: 0,
+                notes: None,
+                created_by: None,
+            })
         })
     }
 
@@ -537,11 +2140,16 @@ impl BookingService {
             .load(&mut conn)
             .map_err(|e| AppError::DatabaseError(e.to_string()))?;
 
+        let booking_ids: Vec<Uuid> = booking_list.iter().map(|b| b.id).collect();
+        let counts = Self::modification_counts(&mut conn, &booking_ids)
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
         let result: Vec<BookingWithRoom> = booking_list
             .into_iter()
             .map(|booking| {
                 let room = rooms_list.iter().find(|r| r.id == booking.room_id).cloned();
-                BookingWithRoom { booking, room }
+                let modification_count = counts.get(&booking.id).copied().unwrap_or(0);
+                BookingWithRoom { booking, room, modification_count, notes: None, created_by: None }
             })
             .collect();
 
@@ -560,7 +2168,135 @@ impl BookingService {
             return Err(AppError::NotFound("Booking not found".to_string()));
         }
 
-        Ok(booking_with_room)
+        // Staff notes and who took the booking are internal
+        Ok(BookingWithRoom { notes: None, created_by: None, ..booking_with_room })
+    }
+
+    /// Render a guest's booking as an iCalendar file with the stay as an
+    /// all-day event, ownership-checked like `get_guest_booking`
+    pub fn get_guest_booking_ics(&self, booking_id: Uuid, user_id: Uuid) -> AppResult<String> {
+        let booking_with_room = self.get_guest_booking(booking_id, user_id)?;
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        let hotel_name = Settings::load(&mut conn)?.text(settings::HOTEL_NAME);
+
+        let booking = &booking_with_room.booking;
+        let summary = format!("Stay at {}", hotel_name);
+        let mut description = format!("Booking {} for {}", booking.reference, booking.guest_name);
+        if let Some(room) = &booking_with_room.room {
+            description.push_str(&format!(", {} room", room.room_type));
+        }
+
+        Ok(ics::render_event(&IcsEvent {
+            uid: &booking.reference,
+            summary: &summary,
+            description: Some(&description),
+            start: booking.check_in_date,
+            end: booking.check_out_date,
+            stamp: Utc::now(),
+        }))
+    }
+
+    /// Get the guest's current stay: their checked-in or overstaying booking,
+    /// matched with the same ownership rules as the profile history
+    pub fn current_stay(&self, guest_id: Uuid) -> AppResult<CurrentStay> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let full_name: Option<String> = users::table
+            .find(guest_id)
+            .select(users::full_name)
+            .first(&mut conn)
+            .map_err(|_| AppError::NotFound("Guest not found".to_string()))?;
+
+        let mut query = bookings::table
+            .filter(bookings::status.eq_any([BookingStatus::CheckedIn, BookingStatus::Overstay]))
+            .into_boxed();
+
+        query = match full_name {
+            Some(name) => query.filter(
+                bookings::created_by_user_id.eq(guest_id).or(bookings::creation_source
+                    .eq("staff")
+                    .and(bookings::guest_name.ilike(name))),
+            ),
+            None => query.filter(bookings::created_by_user_id.eq(guest_id)),
+        };
+
+        let booking: Booking = query
+            .order(bookings::check_in_date.desc())
+            .first(&mut conn)
+            .optional()?
+            .ok_or_else(|| {
+                AppError::NotInHouse("You are not currently checked in".to_string())
+            })?;
+
+        let room: Option<Room> = rooms::table
+            .find(booking.room_id)
+            .first(&mut conn)
+            .optional()?;
+
+        let total_paid: BigDecimal = payments::table
+            .filter(payments::booking_id.eq(booking.id))
+            .select(sum(payments::amount))
+            .first::<Option<BigDecimal>>(&mut conn)?
+            .unwrap_or_else(|| BigDecimal::from(0));
+
+        Ok(CurrentStay {
+            remaining_nights: Self::remaining_nights(booking.check_out_date, self.hotel_today(&mut conn)?),
+            outstanding_balance: &booking.price - &total_paid,
+            total_paid,
+            room,
+            booking,
+        })
+    }
+
+    /// Nights left between `today` and the check-out date, never negative
+    pub fn remaining_nights(check_out_date: NaiveDate, today: NaiveDate) -> i64 {
+        (check_out_date - today).num_days().max(0)
+    }
+
+    /// Latest moment a guest may still cancel online: the cutoff before the
+    /// standard check-in time on the arrival date, in hotel local time
+    pub fn cancellation_deadline(check_in_date: NaiveDate, hotel_settings: &Settings) -> DateTime<Utc> {
+        let check_in_at = clock::local_to_utc(
+            hotel_settings.time_zone(settings::HOTEL_TIMEZONE),
+            check_in_date.and_time(hotel_settings.time_of_day(settings::CHECK_IN_TIME)),
+        );
+        check_in_at
+            - Duration::hours(hotel_settings.integer(settings::BOOKING_CANCELLATION_CUTOFF_HOURS))
+    }
+
+    /// Whether `now` is past the online cancellation deadline. Cancelling
+    /// exactly at the deadline is still allowed.
+    pub fn inside_cancellation_cutoff(
+        check_in_date: NaiveDate,
+        now: DateTime<Utc>,
+        hotel_settings: &Settings,
+    ) -> bool {
+        now > Self::cancellation_deadline(check_in_date, hotel_settings)
+    }
+
+    /// Refuse a guest cancellation inside the cutoff window
+    pub fn check_guest_cancellation(
+        check_in_date: NaiveDate,
+        now: DateTime<Utc>,
+        hotel_settings: &Settings,
+    ) -> AppResult<()> {
+        if !Self::inside_cancellation_cutoff(check_in_date, now, hotel_settings) {
+            return Ok(());
+        }
+
+        let deadline = Self::cancellation_deadline(check_in_date, hotel_settings)
+            .with_timezone(&hotel_settings.time_zone(settings::HOTEL_TIMEZONE));
+        Err(AppError::ValidationError(format!(
+            "Bookings can only be cancelled online until {} hours before check-in ({}). Please contact reception to cancel this booking.",
+            hotel_settings.integer(settings::BOOKING_CANCELLATION_CUTOFF_HOURS),
+            deadline.format("%Y-%m-%d %H:%M")
+        )))
     }
 
     /// Cancel a booking for a specific user
@@ -574,46 +2310,78 @@ impl BookingService {
             .get()
             .map_err(|e| AppError::DatabaseError(e.to_string()))?;
 
-        let booking: Booking = bookings::table
-            .find(booking_id)
-            .first(&mut conn)
-            .map_err(|_| AppError::NotFound("Booking not found".to_string()))?;
-
-        if booking.created_by_user_id != Some(user_id) {
-            return Err(AppError::NotFound("Booking not found".to_string()));
-        }
-
-        if booking.status != BookingStatus::Upcoming {
-            return Err(AppError::InvalidStatusTransition(
-                "Only upcoming bookings can be cancelled".to_string(),
-            ));
-        }
-
         let update = UpdateBooking {
             status: Some(BookingStatus::Cancelled),
             ..Default::default()
         };
 
-        diesel::update(bookings::table.find(booking_id))
+        conn.transaction::<_, AppError, _>(|conn| {
+            let booking: Booking = bookings::table
+                .find(booking_id)
+                .first(conn)
+                .map_err(|_| AppError::NotFound("Booking not found".to_string()))?;
+
+            if booking.created_by_user_id != Some(user_id) {
+                return Err(AppError::NotFound("Booking not found".to_string()));
+            }
+
+            if booking.status != BookingStatus::Upcoming {
+                return Err(AppError::InvalidStatusTransition(
+                    "Only upcoming bookings can be cancelled".to_string(),
+                ));
+            }
+
+            Self::check_guest_cancellation(booking.check_in_date, Utc::now(), &Settings::load(conn)?)?;
+
+            let cancelled: Booking = diesel::update(
+                bookings::table
+                    .find(booking_id)
+                    .filter(bookings::status.eq(booking.status)),
+            )
             .set(&update)
-            .get_result(&mut conn)
-            .map_err(|e| AppError::DatabaseError(e.to_string()))
+            .get_result(conn)
+            .optional()?
+            .ok_or_else(|| AppError::Conflict("Booking status was updated by another operation.".to_string()))?;
+
+            Self::record_event(
+                conn,
+                &NewBookingEvent::status_change(booking_id, booking.status, BookingStatus::Cancelled, Some(user_id)),
+            )?;
+
+            Ok(cancelled)
+        })
+    }
+
+    /// Whether `booking` may check in on `today` (hotel time): on its check-in
+    /// date, or for a no-show any day before the booked stay ends
+    pub fn validate_check_in_day(booking: &Booking, today: NaiveDate) -> AppResult<()> {
+        if booking.status == BookingStatus::NoShow {
+            // Late arrival: allowed while the booked stay has not ended
+            if today < booking.check_in_date || today >= booking.check_out_date {
+                return Err(AppError::ValidationError(format!(
+                    "Late check-in is only allowed before the check-out date ({}). Today is {}.",
+                    booking.check_out_date,
+                    today
+                )));
+            }
+        } else if booking.check_in_date != today {
+            // Only allow check-in on the actual check-in date
+            return Err(AppError::ValidationError(format!(
+                "Check-in is only allowed on the check-in date ({}). Today is {}.",
+                booking.check_in_date,
+                today
+            )));
+        }
+        Ok(())
     }
 
     /// Check in a guest
-    pub fn check_in(&self, booking_id: Uuid) -> AppResult<Booking> {
+    pub fn check_in(&self, booking_id: Uuid, actor_user_id: Uuid) -> AppResult<Booking> {
         let mut conn = self
             .pool
             .get()
             .map_err(|e| AppError::DatabaseError(e.to_string()))?;
 
-        fn app_error_to_diesel(e: AppError) -> diesel::result::Error {
-            diesel::result::Error::DatabaseError(
-                diesel::result::DatabaseErrorKind::CheckViolation,
-                Box::new(StringError(e.to_string())) as Box<dyn DatabaseErrorInformation + Send + Sync>,
-            )
-        }
-
         conn.transaction::<_, diesel::result::Error, _>(|conn| {
             let booking: Booking = bookings::table
                 .find(booking_id)
@@ -622,21 +2390,13 @@ impl BookingService {
 
             if !booking.status.can_transition_to(BookingStatus::CheckedIn) {
                 return Err(app_error_to_diesel(AppError::InvalidStatusTransition(format!(
-                    "Cannot check in booking with status {:?}",
+                    "Cannot check in booking with status {}",
                     booking.status
                 ))));
             }
 
-            let today = Utc::now().date_naive();
-            
-            // Only allow check-in on the actual check-in date
-            if booking.check_in_date != today {
-                return Err(app_error_to_diesel(AppError::ValidationError(format!(
-                    "Check-in is only allowed on the check-in date ({}). Today is {}.",
-                    booking.check_in_date,
-                    today
-                ))));
-            }
+            let today = self.hotel_today(conn)?;
+            Self::validate_check_in_day(&booking, today).map_err(app_error_to_diesel)?;
 
             let current_room: Room = rooms::table
                 .find(booking.room_id)
@@ -664,8 +2424,7 @@ impl BookingService {
                         Box::new(StringError(format!("Failed to check room occupancy: {}", e))) as Box<dyn DatabaseErrorInformation + Send + Sync>,
                     ))?;
 
-                if active_booking.is_some() {
-                    let active = active_booking.unwrap();
+                if let Some(active) = active_booking {
                     return Err(app_error_to_diesel(AppError::RoomUnavailable(format!(
                         "Room is currently occupied by another guest until {}",
                         active.check_out_date
@@ -673,13 +2432,16 @@ impl BookingService {
                 }
             }
 
-            // Normal check-in: only update status
+            // Normal check-in: update status and record the arrival
             let rows_updated = diesel::update(
                 bookings::table
                     .find(booking_id)
                     .filter(bookings::status.eq(booking.status)),
             )
-            .set(bookings::status.eq(BookingStatus::CheckedIn))
+            .set((
+                bookings::status.eq(BookingStatus::CheckedIn),
+                bookings::checked_in_at.eq(Some(self.clock.now())),
+            ))
             .execute(conn)?;
 
             if rows_updated == 0 {
@@ -688,33 +2450,66 @@ impl BookingService {
                 )));
             }
 
-            // Set room to Occupied directly (no need to set Available first)
-            diesel::update(rooms::table.find(booking.room_id))
-                .set(rooms::status.eq(RoomStatus::Occupied))
-                .execute(conn)?;
+            // Set room to Occupied directly (no need to set Available first), on the
+            // same connection so a refused transition rolls back the check-in
+            RoomService::update_room_status_on(conn, booking.room_id, RoomStatus::Occupied, Some(actor_user_id))
+                .map_err(app_error_to_diesel)?;
+
+            // The guest showed up after all: drop any pending no-show charge
+            if booking.status == BookingStatus::NoShow {
+                NoShowService::void_pending_charge(conn, booking_id)?;
+            }
+
+            Self::record_event(
+                conn,
+                &NewBookingEvent::status_change(
+                    booking_id,
+                    booking.status,
+                    BookingStatus::CheckedIn,
+                    Some(actor_user_id),
+                ),
+            )?;
 
             bookings::table
                 .find(booking_id)
                 .first(conn)
                 .map_err(|_| diesel::result::Error::NotFound)
         })
-        .map_err(|e| AppError::from(e))
+        .map_err(AppError::from)
+    }
+
+    /// Check-out date to record for a guest leaving on `today`
+    ///
+    /// Leaving before the scheduled date needs `confirm_early`; the stay then
+    /// ends today, but never before the first night. Late departures
+    /// (overstays) end today as well.
+    pub fn actual_check_out_date(
+        booking: &Booking,
+        today: NaiveDate,
+        confirm_early: bool,
+    ) -> AppResult<NaiveDate> {
+        if today < booking.check_out_date && !confirm_early {
+            return Err(AppError::ValidationError(format!(
+                "{} ({}). Confirm the early check-out to release the remaining nights.",
+                EARLY_CHECK_OUT_MESSAGE, booking.check_out_date
+            )));
+        }
+        Ok(today.max(booking.check_in_date + Duration::days(1)))
     }
 
-    /// Check out a guest
-    pub fn check_out(&self, booking_id: Uuid, _confirm_early: bool) -> AppResult<Booking> {
+    /// Check out a guest. Leaving before the scheduled check-out date needs
+    /// `confirm_early`.
+    pub fn check_out(
+        &self,
+        booking_id: Uuid,
+        confirm_early: bool,
+        actor_user_id: Uuid,
+    ) -> AppResult<Booking> {
         let mut conn = self
             .pool
             .get()
             .map_err(|e| AppError::DatabaseError(e.to_string()))?;
 
-        fn app_error_to_diesel(e: AppError) -> diesel::result::Error {
-            diesel::result::Error::DatabaseError(
-                diesel::result::DatabaseErrorKind::CheckViolation,
-                Box::new(StringError(e.to_string())) as Box<dyn DatabaseErrorInformation + Send + Sync>,
-            )
-        }
-
         conn.transaction::<_, diesel::result::Error, _>(|conn| {
             let booking: Booking = bookings::table
                 .find(booking_id)
@@ -723,18 +2518,11 @@ impl BookingService {
 
             if !booking.status.can_transition_to(BookingStatus::CheckedOut) {
                 return Err(app_error_to_diesel(AppError::InvalidStatusTransition(format!(
-                    "Cannot check out booking with status {:?}.",
+                    "Cannot check out booking with status {}.",
                     booking.status
                 ))));
             }
 
-            // Allow staff to perform an early check-out without requiring an explicit
-            // confirmation flag. Previously we prevented check-outs when the
-            // booking's check-out date was in the future unless `confirm_early`
-            // was true — this blocked legitimate early check-outs initiated by
-            // staff or guests. For now, permit early check-outs unconditionally.
-            let _today = Utc::now().date_naive();
-
             let current_room: Room = rooms::table
                 .find(booking.room_id)
                 .first(conn)
@@ -743,17 +2531,23 @@ impl BookingService {
             // Note: can_transition_to already validated that only CheckedIn or Overstay
             // bookings can check out, so no additional status check needed here.
 
-            // Update the booking's check_out_date to today (or at least
-            // `check_in_date + 1`) and adjust the price so financial reports
-            // reflect the actual stay. Database enforces `check_out_date >
-            // check_in_date`, so ensure we respect that constraint.
-            let today = Utc::now().date_naive();
-            let min_checkout = booking.check_in_date + Duration::days(1);
-            let desired_checkout = if today > min_checkout { today } else { min_checkout };
-
-            let nights_i64 = (desired_checkout - booking.check_in_date).num_days().max(1);
-            let nights = BigDecimal::from(nights_i64);
-            let new_price = current_room.price.clone() * nights;
+            // The stay ends on the hotel date of the departure so nights and the
+            // price reflect the actual stay; an early departure releases the
+            // remaining nights and an overstay is billed to the day the guest left
+            let checked_out_at = self.clock.now();
+            let tz = Settings::load(conn)?.time_zone(settings::HOTEL_TIMEZONE);
+            let desired_checkout =
+                Self::actual_check_out_date(&booking, clock::local_date(checked_out_at, tz), confirm_early)
+                    .map_err(app_error_to_diesel)?;
+            let early = desired_checkout < booking.check_out_date;
+
+            // The stored price stands for an on-time departure; early and late
+            // ones pay the booked nightly rate for the nights actually stayed
+            let new_price = Self::rescale_price(
+                &booking.price,
+                (booking.check_out_date - booking.check_in_date).num_days(),
+                (desired_checkout - booking.check_in_date).num_days(),
+            );
 
             // Perform the update and return the updated booking row. Using
             // `get_result` surfaces database errors with better context.
@@ -766,47 +2560,296 @@ impl BookingService {
                 bookings::status.eq(BookingStatus::CheckedOut),
                 bookings::check_out_date.eq(desired_checkout),
                 bookings::price.eq(new_price.clone()),
+                bookings::checked_out_at.eq(Some(checked_out_at)),
             ))
             .get_result(conn)?;
 
-            // Mark the room as dirty after successful check-out
-            diesel::update(rooms::table.find(booking.room_id))
-                .set(rooms::status.eq(RoomStatus::Dirty))
-                .execute(conn)?;
+            // Mark the room as dirty after successful check-out, unless it was
+            // put under maintenance during the stay
+            if current_room.status != RoomStatus::Maintenance {
+                RoomService::update_room_status_on(conn, booking.room_id, RoomStatus::Dirty, Some(actor_user_id))
+                    .map_err(app_error_to_diesel)?;
+            }
+
+            Self::record_event(
+                conn,
+                &NewBookingEvent::status_change(
+                    booking_id,
+                    booking.status,
+                    BookingStatus::CheckedOut,
+                    Some(actor_user_id),
+                ),
+            )?;
+            if desired_checkout != booking.check_out_date {
+                Self::record_event(
+                    conn,
+                    &NewBookingEvent::date_change(
+                        booking_id,
+                        if early {
+                            format!(
+                                "Early check-out: check-out moved from {} to {}, remaining nights released",
+                                booking.check_out_date, desired_checkout
+                            )
+                        } else {
+                            format!(
+                                "Check-out moved from {} to {} at check-out",
+                                booking.check_out_date, desired_checkout
+                            )
+                        },
+                        Some(actor_user_id),
+                    ),
+                )?;
+            }
 
             Ok(updated_booking)
         })
-        .map_err(|e| AppError::from(e))
+        .map_err(AppError::from)
     }
 
     /// Cancel a booking
-    pub fn cancel(&self, booking_id: Uuid) -> AppResult<Booking> {
+    pub fn cancel(&self, booking_id: Uuid, actor_user_id: Uuid) -> AppResult<Booking> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let update = UpdateBooking {
+            status: Some(BookingStatus::Cancelled),
+            ..Default::default()
+        };
+
+        conn.transaction::<_, AppError, _>(|conn| {
+            let booking: Booking = bookings::table
+                .find(booking_id)
+                .first(conn)
+                .map_err(|_| AppError::NotFound(format!("Booking '{}' not found", booking_id)))?;
+
+            if !booking.status.can_transition_to(BookingStatus::Cancelled) {
+                return Err(AppError::InvalidStatusTransition(format!(
+                    "Cannot cancel booking with status {}.",
+                    booking.status
+                )));
+            }
+
+            // Staff may cancel inside the guest cutoff; the trail says they did
+            let hotel_settings = Settings::load(conn)?;
+            let override_note = Self::inside_cancellation_cutoff(booking.check_in_date, Utc::now(), &hotel_settings)
+                .then(|| {
+                    format!(
+                        "Cancelled by staff inside the {}-hour guest cancellation cutoff",
+                        hotel_settings.integer(settings::BOOKING_CANCELLATION_CUTOFF_HOURS)
+                    )
+                });
+
+            let cancelled: Booking = diesel::update(
+                bookings::table
+                    .find(booking_id)
+                    .filter(bookings::status.eq(booking.status)),
+            )
+            .set(&update)
+            .get_result(conn)
+            .optional()?
+            .ok_or_else(|| AppError::Conflict("Booking status was updated by another operation.".to_string()))?;
+
+            Self::record_event(
+                conn,
+                &NewBookingEvent {
+                    details: override_note,
+                    ..NewBookingEvent::status_change(
+                        booking_id,
+                        booking.status,
+                        BookingStatus::Cancelled,
+                        Some(actor_user_id),
+                    )
+                },
+            )?;
+
+            Ok(cancelled)
+        })
+    }
+
+    /// Cancel a room's upcoming bookings before maintenance
+    ///
+    /// Only stays overlapping `from`..=`to` are touched when a range is given.
+    /// Guests already in the room are reported, not cancelled.
+    pub fn cancel_upcoming_for_room(
+        &self,
+        room_id: Uuid,
+        from: Option<NaiveDate>,
+        to: Option<NaiveDate>,
+        actor_user_id: Uuid,
+    ) -> AppResult<MaintenanceCancellation> {
+        if let (Some(from), Some(to)) = (from, to) {
+            if from > to {
+                return Err(AppError::ValidationError(
+                    "'from' must not be after 'to'".to_string(),
+                ));
+            }
+        }
+
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        // The room lock keeps new bookings out while the list is cancelled
+        conn.transaction::<_, AppError, _>(|conn| {
+            Self::lock_room(conn, room_id)?;
+
+            let mut query = bookings::table
+                .filter(bookings::room_id.eq(room_id))
+                .filter(bookings::status.eq_any([
+                    BookingStatus::Upcoming,
+                    BookingStatus::CheckedIn,
+                    BookingStatus::Overstay,
+                ]))
+                .into_boxed();
+            if let Some(from) = from {
+                query = query.filter(bookings::check_out_date.gt(from));
+            }
+            if let Some(to) = to {
+                query = query.filter(bookings::check_in_date.le(to));
+            }
+            let affected: Vec<Booking> = query
+                .order((bookings::check_in_date.asc(), bookings::reference.asc()))
+                .load(conn)?;
+
+            let mut result = MaintenanceCancellation::default();
+            for booking in affected {
+                if booking.status != BookingStatus::Upcoming {
+                    result.skipped_checked_in.push(booking.reference);
+                    continue;
+                }
+
+                // Skip a booking checked in or cancelled since it was loaded
+                let rows_updated = diesel::update(
+                    bookings::table
+                        .find(booking.id)
+                        .filter(bookings::status.eq(BookingStatus::Upcoming)),
+                )
+                .set(bookings::status.eq(BookingStatus::Cancelled))
+                .execute(conn)?;
+                if rows_updated == 0 {
+                    continue;
+                }
+                Self::record_event(
+                    conn,
+                    &NewBookingEvent {
+                        details: Some(ROOM_MAINTENANCE_NOTE.to_string()),
+                        ..NewBookingEvent::status_change(
+                            booking.id,
+                            BookingStatus::Upcoming,
+                            BookingStatus::Cancelled,
+                            Some(actor_user_id),
+                        )
+                    },
+                )?;
+                result.cancelled.push(booking.reference);
+            }
+
+            Ok(result)
+        })
+    }
+
+    /// Nights a room move covers: the whole stay for an upcoming booking, from
+    /// today for a guest already in house
+    pub fn room_move_window(booking: &Booking, today: NaiveDate) -> AppResult<(NaiveDate, NaiveDate)> {
+        if !matches!(booking.status, BookingStatus::Upcoming | BookingStatus::CheckedIn) {
+            return Err(AppError::InvalidStatusTransition(format!(
+                "Cannot move booking with status {}",
+                booking.status
+            )));
+        }
+
+        let from = if booking.status == BookingStatus::CheckedIn {
+            booking.check_in_date.max(today)
+        } else {
+            booking.check_in_date
+        };
+        if from >= booking.check_out_date {
+            return Err(AppError::ValidationError(
+                "No nights left to move; check the guest out instead".to_string(),
+            ));
+        }
+
+        Ok((from, booking.check_out_date))
+    }
+
+    /// Move an upcoming or checked-in booking to another room. For a guest in
+    /// house the old room goes to Dirty and the new one to Occupied.
+    pub fn move_room(
+        &self,
+        booking_id: Uuid,
+        new_room_id: Uuid,
+        actor_user_id: Uuid,
+    ) -> AppResult<BookingWithRoom> {
         let mut conn = self
             .pool
             .get()
             .map_err(|e| AppError::DatabaseError(e.to_string()))?;
 
-        let booking: Booking = bookings::table
-            .find(booking_id)
-            .first(&mut conn)
-            .map_err(|_| AppError::NotFound(format!("Booking '{}' not found", booking_id)))?;
+        conn.transaction::<_, AppError, _>(|conn| {
+            let booking: Booking = bookings::table
+                .find(booking_id)
+                .for_update()
+                .first(conn)
+                .optional()?
+                .ok_or_else(|| AppError::NotFound(format!("Booking '{}' not found", booking_id)))?;
+
+            if booking.room_id == new_room_id {
+                return Err(AppError::ValidationError(
+                    "Booking is already in this room".to_string(),
+                ));
+            }
+            let (from, to) = Self::room_move_window(&booking, self.hotel_today(conn)?)?;
+
+            // Lock in id order so two moves between the same rooms cannot deadlock
+            let (old_room, new_room) = if booking.room_id < new_room_id {
+                let old_room = Self::lock_room(conn, booking.room_id)?;
+                (old_room, Self::lock_room(conn, new_room_id)?)
+            } else {
+                let new_room = Self::lock_room(conn, new_room_id)?;
+                (Self::lock_room(conn, booking.room_id)?, new_room)
+            };
+
+            if new_room.status == RoomStatus::Maintenance {
+                return Err(AppError::RoomUnavailable(format!(
+                    "Room {} is under maintenance",
+                    new_room.number
+                )));
+            }
+            RoomBlockService::check_not_blocked_on(conn, &new_room, from, to)?;
+            if !self.check_availability_on(conn, new_room_id, from, to, Some(booking_id), None)? {
+                return Err(AppError::RoomUnavailable(format!(
+                    "Room {} is not available from {} to {}",
+                    new_room.number, from, to
+                )));
+            }
 
-        if !booking.status.can_transition_to(BookingStatus::Cancelled) {
-            return Err(AppError::InvalidStatusTransition(format!(
-                "Cannot cancel booking with status {:?}.",
-                booking.status
-            )));
-        }
+            let updated: Booking = diesel::update(bookings::table.find(booking_id))
+                .set(bookings::room_id.eq(new_room_id))
+                .get_result(conn)?;
 
-        let update = UpdateBooking {
-            status: Some(BookingStatus::Cancelled),
-            ..Default::default()
-        };
+            if booking.status == BookingStatus::CheckedIn {
+                RoomService::update_room_status_on(conn, old_room.id, RoomStatus::Dirty, Some(actor_user_id))?;
+                RoomService::update_room_status_on(conn, new_room_id, RoomStatus::Occupied, Some(actor_user_id))?;
+            }
 
-        diesel::update(bookings::table.find(booking_id))
-            .set(&update)
-            .get_result(&mut conn)
-            .map_err(|e| AppError::DatabaseError(e.to_string()))
+            let changes = diff_bookings(&booking, &updated);
+            Self::record_modification(conn, booking_id, Some(actor_user_id), SOURCE_ROOM_MOVE, &changes)?;
+            Self::record_event(
+                conn,
+                &NewBookingEvent::room_move(
+                    booking_id,
+                    format!("Moved from room {} to room {}", old_room.number, new_room.number),
+                    Some(actor_user_id),
+                ),
+            )?;
+
+            Ok(())
+        })?;
+
+        self.get_booking_with_room(booking_id)
     }
 
     /// Calculate financial metrics for a room
@@ -883,12 +2926,13 @@ impl BookingService {
                     .filter(bookings::check_out_date.ge(start))
                     .load(&mut conn)
                     .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+                let tz = Settings::load(&mut conn)?.time_zone(settings::HOTEL_TIMEZONE);
 
                 let total_days_occupied: i64 = bookings_in_period
                     .iter()
                     .map(|b| {
                         let booking_start = b.check_in_date.max(start);
-                        let booking_end = b.check_out_date.min(end);
+                        let booking_end = b.stay_end_date(tz).min(end);
                         (booking_end - booking_start).num_days().max(0)
                     })
                     .sum();
@@ -906,7 +2950,7 @@ impl BookingService {
             total_revenue: total_revenue.unwrap_or_else(|| BigDecimal::from(0)),
             booking_count,
             average_revenue,
-            occupancy_rate: occupancy_rate.min(100.0).max(0.0),
+            occupancy_rate: occupancy_rate.clamp(0.0, 100.0),
         })
     }
 
@@ -959,6 +3003,157 @@ impl BookingService {
         Ok(result)
     }
 
+    /// Validate a booking statistics range (inclusive, at most
+    /// `MAX_BOOKING_STATS_DAYS` days)
+    pub fn validate_booking_stats_range(from: NaiveDate, to: NaiveDate) -> AppResult<()> {
+        if to < from {
+            return Err(AppError::ValidationError(
+                "to must be on or after from".to_string(),
+            ));
+        }
+
+        let days = (to - from).num_days() + 1;
+        if days > MAX_BOOKING_STATS_DAYS {
+            return Err(AppError::ValidationError(format!(
+                "Statistics range cannot exceed {} days (requested {})",
+                MAX_BOOKING_STATS_DAYS, days
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Assemble the statistics from the per-day aggregates
+    ///
+    /// # Arguments
+    /// * `created` - Bookings created per day and creation source
+    /// * `cancellations` - Cancellations recorded per day
+    /// * `cancelled_bookings` - Bookings created in the range that are now cancelled
+    /// * `total_nights` - Nights booked by the range's bookings
+    pub fn build_booking_stats(
+        from: NaiveDate,
+        to: NaiveDate,
+        created: &[CreatedPerDay],
+        cancellations: &[(NaiveDate, i64)],
+        cancelled_bookings: i64,
+        total_nights: i64,
+    ) -> BookingStats {
+        let mut days: Vec<BookingStatsDay> = from
+            .iter_days()
+            .take_while(|date| *date <= to)
+            .map(|date| BookingStatsDay {
+                date,
+                created: 0,
+                created_by_guests: 0,
+                created_by_staff: 0,
+                cancelled: 0,
+            })
+            .collect();
+        let index = |date: NaiveDate| usize::try_from((date - from).num_days()).ok();
+
+        for (date, source, count) in created {
+            if let Some(day) = index(*date).and_then(|i| days.get_mut(i)) {
+                day.created += count;
+                match source.as_str() {
+                    "guest" => day.created_by_guests += count,
+                    _ => day.created_by_staff += count,
+                }
+            }
+        }
+        for (date, count) in cancellations {
+            if let Some(day) = index(*date).and_then(|i| days.get_mut(i)) {
+                day.cancelled += count;
+            }
+        }
+
+        let total_bookings: i64 = days.iter().map(|d| d.created).sum();
+        let created_by_guests: i64 = days.iter().map(|d| d.created_by_guests).sum();
+        let ratio = |part: i64| {
+            if total_bookings == 0 {
+                0.0
+            } else {
+                part as f64 / total_bookings as f64
+            }
+        };
+
+        BookingStats {
+            from,
+            to,
+            total_bookings,
+            created_by_guests,
+            created_by_staff: total_bookings - created_by_guests,
+            guest_share: ratio(created_by_guests),
+            cancelled_bookings,
+            cancellation_rate: ratio(cancelled_bookings),
+            total_nights,
+            average_nights: ratio(total_nights),
+            days,
+        }
+    }
+
+    /// Bookings created per day and source, cancellations per day, the
+    /// cancellation rate and nights per booking for `from..=to` in hotel time.
+    /// All aggregation happens in SQL.
+    pub fn get_booking_stats(&self, from: NaiveDate, to: NaiveDate) -> AppResult<BookingStats> {
+        use diesel::dsl::sql;
+        use diesel::sql_types::{BigInt, Date, Nullable, Text};
+
+        Self::validate_booking_stats_range(from, to)?;
+
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        // Days are midnights in hotel time
+        let tz = Settings::load(&mut conn)?.time_zone(settings::HOTEL_TIMEZONE);
+        let local_midnight =
+            |date: NaiveDate| clock::local_to_utc(tz, date.and_hms_opt(0, 0, 0).expect("midnight"));
+        let (start, end) = (local_midnight(from), local_midnight(to + Duration::days(1)));
+        // Zone names come from the tz database, so they are safe to inline
+        let local_day = |column: &str| format!("({} AT TIME ZONE '{}')::date", column, tz.name());
+
+        // Grouping by a computed day needs the grouped columns as SQL too
+        let created_day = local_day("bookings.created_at");
+        let created: Vec<CreatedPerDay> = bookings::table
+            .filter(bookings::created_at.ge(start))
+            .filter(bookings::created_at.lt(end))
+            .group_by(sql::<Text>(&format!("{}, bookings.creation_source", created_day)))
+            .select((
+                sql::<Date>(&created_day),
+                sql::<Text>("bookings.creation_source"),
+                count_star(),
+            ))
+            .load(&mut conn)?;
+
+        let cancelled_day = local_day("booking_events.created_at");
+        let cancellations: Vec<(NaiveDate, i64)> = booking_events::table
+            .filter(booking_events::to_status.eq(BookingStatus::Cancelled))
+            .filter(booking_events::created_at.ge(start))
+            .filter(booking_events::created_at.lt(end))
+            .group_by(sql::<Date>(&cancelled_day))
+            .select((sql::<Date>(&cancelled_day), count_star()))
+            .load(&mut conn)?;
+
+        let (cancelled_bookings, total_nights): (i64, Option<i64>) = bookings::table
+            .filter(bookings::created_at.ge(start))
+            .filter(bookings::created_at.lt(end))
+            .select((
+                sql::<BigInt>("COUNT(*) FILTER (WHERE status = 'cancelled')"),
+                sql::<Nullable<BigInt>>("SUM(check_out_date - check_in_date)"),
+            ))
+            .first(&mut conn)?;
+
+        Ok(Self::build_booking_stats(
+            from,
+            to,
+            &created,
+            &cancellations,
+            cancelled_bookings,
+            total_nights.unwrap_or(0),
+        ))
+    }
+
     /// Get booking history for a specific room
     pub fn get_room_booking_history(
         &self,
@@ -995,9 +3190,16 @@ impl BookingService {
             .optional()
             .map_err(|e| AppError::DatabaseError(e.to_string()))?;
 
+        let booking_ids: Vec<Uuid> = booking_list.iter().map(|b| b.id).collect();
+        let counts = Self::modification_counts(&mut conn, &booking_ids)
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
         let result: Vec<BookingWithRoom> = booking_list
             .into_iter()
             .map(|booking| BookingWithRoom {
+                modification_count: counts.get(&booking.id).copied().unwrap_or(0),
+                notes: None,
+                created_by: None,
                 booking,
                 room: room.clone(),
             })
@@ -1006,20 +3208,653 @@ impl BookingService {
         Ok(result)
     }
 
+    /// Page through every stay in a room, latest check-in first, for staff
+    /// answering "who stayed here" questions
+    ///
+    /// # Arguments
+    /// * `filter` - Stays overlapping `from`..=`to`, optionally in one status
+    /// * `include_prices` - Whether entries carry the booking's total price
+    /// * `page` / `per_page` - 1-based pagination
+    pub fn get_room_history_page(
+        &self,
+        room_id: Uuid,
+        filter: &RoomHistoryFilter,
+        include_prices: bool,
+        page: Option<u64>,
+        per_page: Option<u64>,
+    ) -> AppResult<RoomHistoryPage> {
+        if let (Some(from), Some(to)) = (filter.from, filter.to) {
+            if from > to {
+                return Err(AppError::ValidationError(
+                    "'from' must not be after 'to'".to_string(),
+                ));
+            }
+        }
+        let page = page.unwrap_or(1).max(1);
+        let per_page = per_page
+            .unwrap_or(DEFAULT_ROOM_HISTORY_PAGE_SIZE)
+            .clamp(1, MAX_ROOM_HISTORY_PAGE_SIZE);
+
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let room: Room = rooms::table
+            .find(room_id)
+            .first(&mut conn)
+            .optional()?
+            .ok_or_else(|| AppError::NotFound(format!("Room with ID '{}' not found", room_id)))?;
+
+        let build_query = || {
+            let mut query = bookings::table
+                .left_join(users::table.on(Self::staff_creator_join()))
+                .filter(bookings::room_id.eq(room_id))
+                .into_boxed();
+            if let Some(from) = filter.from {
+                query = query.filter(bookings::check_out_date.ge(from));
+            }
+            if let Some(to) = filter.to {
+                query = query.filter(bookings::check_in_date.le(to));
+            }
+            if let Some(status) = filter.status {
+                query = query.filter(bookings::status.eq(status));
+            }
+            query
+        };
+
+        let total: i64 = build_query().count().get_result(&mut conn)?;
+        let rows: Vec<(Booking, Option<String>)> = build_query()
+            .select((Booking::as_select(), users::username.nullable()))
+            .order((bookings::check_in_date.desc(), bookings::created_at.desc()))
+            .limit(per_page as i64)
+            .offset(((page - 1) * per_page) as i64)
+            .load(&mut conn)?;
+
+        let bookings = rows
+            .into_iter()
+            .map(|(booking, created_by)| {
+                RoomHistoryEntry::new(
+                    BookingWithRoom {
+                        booking,
+                        room: Some(room.clone()),
+                        modification_count: 0,
+                        notes: None,
+                        created_by,
+                    },
+                    include_prices,
+                )
+            })
+            .collect();
+
+        Ok(RoomHistoryPage {
+            room_id,
+            bookings,
+            total: total as u64,
+            page,
+            per_page,
+        })
+    }
+
+    /// Build a booking's timeline from its history, payments, no-show charges and
+    /// the chat proposal that created it
+    ///
+    /// # Arguments
+    /// * `booking_id` - Booking to describe
+    /// * `guest_id` - Set when the owning guest is asking; other guests get NotFound
+    ///   and the guest sees a redacted subset
+    /// * `page` / `per_page` - 1-based pagination over the merged feed
+    pub fn booking_timeline(
+        &self,
+        booking_id: Uuid,
+        guest_id: Option<Uuid>,
+        page: Option<u64>,
+        per_page: Option<u64>,
+    ) -> AppResult<BookingTimelinePage> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let booking: Booking = bookings::table
+            .find(booking_id)
+            .first(&mut conn)
+            .optional()?
+            .ok_or_else(|| AppError::NotFound(format!("Booking '{}' not found", booking_id)))?;
+
+        if guest_id.is_some() && booking.created_by_user_id != guest_id {
+            return Err(AppError::NotFound("Booking not found".to_string()));
+        }
+
+        let events: Vec<BookingEvent> = booking_events::table
+            .filter(booking_events::booking_id.eq(booking_id))
+            .order(booking_events::created_at.asc())
+            .limit(MAX_TIMELINE_SOURCE_ROWS)
+            .load(&mut conn)?;
+
+        let payments: Vec<Payment> = payments::table
+            .filter(payments::booking_id.eq(booking_id))
+            .order(payments::created_at.asc())
+            .limit(MAX_TIMELINE_SOURCE_ROWS)
+            .load(&mut conn)?;
+
+        let no_show_charges: Vec<NoShowCharge> = no_show_charges::table
+            .filter(no_show_charges::booking_id.eq(booking_id))
+            .limit(MAX_TIMELINE_SOURCE_ROWS)
+            .load(&mut conn)?;
+
+        // Guest bookings made from the chat: the last proposal for the same room
+        // and check-in date sent to the guest before the booking was created
+        let proposal: Option<Message> = match booking.created_by_user_id {
+            Some(guest) if booking.creation_source == "guest" => messages::table
+                .filter(messages::receiver_id.eq(guest))
+                .filter(messages::created_at.le(booking.created_at))
+                .filter(messages::content.like("BOOKING_PROPOSAL:%"))
+                .filter(messages::content.like(format!("%\"room_id\":\"{}\"%", booking.room_id)))
+                .filter(messages::content.like(format!("%\"check_in_date\":\"{}\"%", booking.check_in_date)))
+                .order(messages::created_at.desc())
+                .first(&mut conn)
+                .optional()?,
+            _ => None,
+        };
+
+        let entries = Self::build_timeline(
+            &TimelineSources {
+                booking,
+                events,
+                payments,
+                no_show_charges,
+                proposal,
+            },
+            guest_id.is_some(),
+        );
+
+        Ok(Self::paginate_timeline(booking_id, entries, page, per_page))
+    }
+
+    /// Merge timeline sources into one feed, oldest first. The guest view drops
+    /// actors, staff details and the no-show charge review.
+    pub fn build_timeline(sources: &TimelineSources, guest_view: bool) -> Vec<TimelineEntry> {
+        let booking = &sources.booking;
+        let mut entries = vec![TimelineEntry {
+            occurred_at: booking.created_at,
+            kind: TimelineEntryKind::Created,
+            summary: format!(
+                "Booking {} created by {} for {} to {}",
+                booking.reference, booking.creation_source, booking.check_in_date, booking.check_out_date
+            ),
+            actor_user_id: booking.created_by_user_id,
+            details: None,
+        }];
+
+        if let Some(proposal) = &sources.proposal {
+            entries.push(TimelineEntry {
+                occurred_at: proposal.created_at,
+                kind: TimelineEntryKind::ChatProposal,
+                summary: "Booking proposed by the chat assistant".to_string(),
+                actor_user_id: Some(proposal.sender_id),
+                details: Some(proposal.content.clone()),
+            });
+        }
+
+        for event in &sources.events {
+            let (kind, summary, details) = match (event.from_status, event.to_status) {
+                (Some(from), Some(to)) => (
+                    TimelineEntryKind::StatusChange,
+                    format!("Status changed from {} to {}", from, to),
+                    event.details.clone(),
+                ),
+                _ if event.event_type == EVENT_CONFLICT_OVERRIDE => (
+                    TimelineEntryKind::ConflictOverride,
+                    "Booked despite an availability conflict".to_string(),
+                    event.details.clone(),
+                ),
+                _ if event.event_type == EVENT_ROOM_MOVE => (
+                    TimelineEntryKind::RoomMove,
+                    event.details.clone().unwrap_or_else(|| "Moved to another room".to_string()),
+                    None,
+                ),
+                _ => (
+                    TimelineEntryKind::DateChange,
+                    event.details.clone().unwrap_or_else(|| "Stay dates changed".to_string()),
+                    None,
+                ),
+            };
+            entries.push(TimelineEntry {
+                occurred_at: event.created_at,
+                kind,
+                summary,
+                actor_user_id: event.actor_user_id,
+                details,
+            });
+        }
+
+        for payment in &sources.payments {
+            let label = match payment.payment_type {
+                PaymentType::Deposit => "Deposit payment",
+                PaymentType::Partial => "Partial payment",
+                PaymentType::Full => "Full payment",
+                PaymentType::Refund => "Refund",
+            };
+            entries.push(TimelineEntry {
+                occurred_at: payment.created_at,
+                kind: TimelineEntryKind::Payment,
+                summary: format!("{} of {} via {}", label, payment.amount, payment.payment_method),
+                actor_user_id: Some(payment.created_by_user_id),
+                details: payment.notes.clone(),
+            });
+        }
+
+        if !guest_view {
+            for charge in &sources.no_show_charges {
+                entries.push(TimelineEntry {
+                    occurred_at: charge.created_at,
+                    kind: TimelineEntryKind::NoShowCharge,
+                    summary: format!("No-show charge of {} raised ({})", charge.amount, charge.basis),
+                    actor_user_id: None,
+                    details: None,
+                });
+                if let Some(reviewed_at) = charge.reviewed_at {
+                    let outcome = match charge.status {
+                        NoShowChargeStatus::Pending => "pending",
+                        NoShowChargeStatus::Confirmed => "confirmed",
+                        NoShowChargeStatus::Waived => "waived",
+                        NoShowChargeStatus::Voided => "voided",
+                    };
+                    entries.push(TimelineEntry {
+                        occurred_at: reviewed_at,
+                        kind: TimelineEntryKind::NoShowCharge,
+                        summary: format!("No-show charge {}", outcome),
+                        actor_user_id: charge.reviewed_by_user_id,
+                        details: charge.reason.clone(),
+                    });
+                }
+            }
+        }
+
+        if guest_view {
+            for entry in &mut entries {
+                entry.actor_user_id = None;
+                entry.details = None;
+            }
+        }
+
+        // Stable sort keeps same-instant entries in source order (created first)
+        entries.sort_by_key(|entry| entry.occurred_at);
+        entries
+    }
+
+    /// Slice a merged timeline into a page
+    pub fn paginate_timeline(
+        booking_id: Uuid,
+        entries: Vec<TimelineEntry>,
+        page: Option<u64>,
+        per_page: Option<u64>,
+    ) -> BookingTimelinePage {
+        let page = page.unwrap_or(1).max(1);
+        let per_page = per_page
+            .unwrap_or(DEFAULT_TIMELINE_PAGE_SIZE)
+            .clamp(1, MAX_TIMELINE_PAGE_SIZE);
+        let total = entries.len() as u64;
+
+        let entries = entries
+            .into_iter()
+            .skip(((page - 1) * per_page) as usize)
+            .take(per_page as usize)
+            .collect();
+
+        BookingTimelinePage {
+            booking_id,
+            entries,
+            total,
+            page,
+            per_page,
+        }
+    }
+
+    /// Apply a staff edit to an upcoming booking and record what changed
+    ///
+    /// Date changes are re-validated against availability and the price is
+    /// rescaled to the new number of nights at the booking's per-night rate.
+    pub fn update_booking(
+        &self,
+        booking_id: Uuid,
+        guest_name: Option<&str>,
+        check_in_date: Option<NaiveDate>,
+        check_out_date: Option<NaiveDate>,
+        actor_user_id: Option<Uuid>,
+    ) -> AppResult<Booking> {
+        let current = self.get_booking_by_id(booking_id)?;
+
+        if current.status != BookingStatus::Upcoming {
+            return Err(AppError::ValidationError(
+                "Can only update upcoming bookings".to_string(),
+            ));
+        }
+
+        let guest_name = guest_name.map(Self::validate_guest_name).transpose()?;
+
+        let new_check_in = check_in_date.unwrap_or(current.check_in_date);
+        let new_check_out = check_out_date.unwrap_or(current.check_out_date);
+        let dates_changed =
+            new_check_in != current.check_in_date || new_check_out != current.check_out_date;
+
+        let mut update = UpdateBooking {
+            guest_name: guest_name.map(str::to_string),
+            ..Default::default()
+        };
+
+        if dates_changed {
+            self.validate_dates(new_check_in, new_check_out)?;
+
+            update.check_in_date = Some(new_check_in);
+            update.check_out_date = Some(new_check_out);
+            update.price = Some(Self::rescale_price(
+                &current.price,
+                (current.check_out_date - current.check_in_date).num_days(),
+                (new_check_out - new_check_in).num_days(),
+            ));
+        }
+
+        if update.guest_name.is_none() && !dates_changed {
+            return Ok(current);
+        }
+
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        conn.transaction::<_, AppError, _>(|conn| {
+            if dates_changed {
+                let room = Self::lock_room(conn, current.room_id)?;
+                RoomBlockService::check_not_blocked_on(conn, &room, new_check_in, new_check_out)?;
+                if !self.check_availability_on(conn, current.room_id, new_check_in, new_check_out, Some(booking_id), None)? {
+                    return Err(AppError::RoomUnavailable(
+                        "Room is not available for the selected dates".to_string(),
+                    ));
+                }
+            }
+
+            let updated: Booking = diesel::update(
+                bookings::table
+                    .find(booking_id)
+                    .filter(bookings::status.eq(BookingStatus::Upcoming)),
+            )
+            .set(&update)
+            .get_result(conn)
+            .optional()?
+            .ok_or_else(|| {
+                AppError::ValidationError("Can only update upcoming bookings".to_string())
+            })?;
+
+            let changes = diff_bookings(&current, &updated);
+            Self::record_modification(conn, booking_id, actor_user_id, SOURCE_STAFF_UPDATE, &changes)?;
+
+            if dates_changed {
+                Self::record_event(
+                    conn,
+                    &NewBookingEvent::date_change(
+                        booking_id,
+                        format!(
+                            "Stay changed from {} - {} to {} - {}",
+                            current.check_in_date,
+                            current.check_out_date,
+                            updated.check_in_date,
+                            updated.check_out_date
+                        ),
+                        actor_user_id,
+                    ),
+                )?;
+            }
+
+            Ok(updated)
+        })
+    }
+
+    /// Price for a stay of `new_nights` at the per-night rate implied by
+    /// `price` over `old_nights`, rounded to whole currency units
+    pub fn rescale_price(price: &BigDecimal, old_nights: i64, new_nights: i64) -> BigDecimal {
+        if old_nights <= 0 || old_nights == new_nights {
+            return price.clone();
+        }
+        (price * BigDecimal::from(new_nights) / BigDecimal::from(old_nights)).round(0)
+    }
+
+    /// Store the changed fields of a booking edit, on the caller's connection so
+    /// it commits together with the edit. Nothing is written when `changes` is empty.
+    pub fn record_modification(
+        conn: &mut PgConnection,
+        booking_id: Uuid,
+        actor_user_id: Option<Uuid>,
+        source: &str,
+        changes: &BookingChanges,
+    ) -> AppResult<()> {
+        if changes.is_empty() {
+            return Ok(());
+        }
+
+        let changes = serde_json::to_string(changes)
+            .map_err(|e| AppError::InternalError(e.to_string()))?;
+        diesel::insert_into(booking_modifications::table)
+            .values(&NewBookingModification {
+                booking_id,
+                actor_user_id,
+                source,
+                changes,
+            })
+            .execute(conn)?;
+        Ok(())
+    }
+
+    /// Number of recorded modifications per booking; bookings without any are omitted
+    pub fn modification_counts(
+        conn: &mut PgConnection,
+        booking_ids: &[Uuid],
+    ) -> QueryResult<HashMap<Uuid, i64>> {
+        if booking_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let rows: Vec<(Uuid, i64)> = booking_modifications::table
+            .filter(booking_modifications::booking_id.eq_any(booking_ids))
+            .group_by(booking_modifications::booking_id)
+            .select((booking_modifications::booking_id, count(booking_modifications::id)))
+            .load(conn)?;
+
+        Ok(rows.into_iter().collect())
+    }
+
+    /// List a booking's recorded events (status and date changes), oldest first
+    pub fn list_events(&self, booking_id: Uuid) -> AppResult<Vec<BookingEvent>> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        bookings::table
+            .find(booking_id)
+            .select(bookings::id)
+            .first::<Uuid>(&mut conn)
+            .map_err(|_| AppError::NotFound(format!("Booking with ID '{}' not found", booking_id)))?;
+
+        let events = booking_events::table
+            .filter(booking_events::booking_id.eq(booking_id))
+            .order(booking_events::created_at.asc())
+            .select(BookingEvent::as_select())
+            .load(&mut conn)?;
+
+        Ok(events)
+    }
+
+    /// List a booking's modifications, oldest first
+    pub fn list_modifications(&self, booking_id: Uuid) -> AppResult<Vec<BookingModification>> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        bookings::table
+            .find(booking_id)
+            .select(bookings::id)
+            .first::<Uuid>(&mut conn)
+            .map_err(|_| AppError::NotFound(format!("Booking with ID '{}' not found", booking_id)))?;
+
+        let modifications = booking_modifications::table
+            .filter(booking_modifications::booking_id.eq(booking_id))
+            .order(booking_modifications::created_at.asc())
+            .select(BookingModification::as_select())
+            .load(&mut conn)?;
+
+        Ok(modifications)
+    }
+
+    /// Attach a staff note to a booking
+    pub fn add_booking_note(
+        &self,
+        booking_id: Uuid,
+        author_user_id: Uuid,
+        note: &str,
+    ) -> AppResult<BookingNote> {
+        GuestService::validate_note(note)?;
+
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        bookings::table
+            .find(booking_id)
+            .select(bookings::id)
+            .first::<Uuid>(&mut conn)
+            .map_err(|_| AppError::NotFound(format!("Booking with ID '{}' not found", booking_id)))?;
+
+        let created = diesel::insert_into(booking_notes::table)
+            .values(&NewBookingNote {
+                booking_id,
+                author_user_id,
+                note: note.trim(),
+            })
+            .returning(BookingNote::as_returning())
+            .get_result(&mut conn)?;
+
+        Ok(created)
+    }
+
+    /// List a booking's staff notes, newest first
+    pub fn list_booking_notes(&self, booking_id: Uuid) -> AppResult<Vec<BookingNote>> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        bookings::table
+            .find(booking_id)
+            .select(bookings::id)
+            .first::<Uuid>(&mut conn)
+            .map_err(|_| AppError::NotFound(format!("Booking with ID '{}' not found", booking_id)))?;
+
+        Ok(Self::load_notes(&mut conn, booking_id)?)
+    }
+
+    fn load_notes(conn: &mut PgConnection, booking_id: Uuid) -> QueryResult<Vec<BookingNote>> {
+        booking_notes::table
+            .filter(booking_notes::booking_id.eq(booking_id))
+            .order(booking_notes::created_at.desc())
+            .select(BookingNote::as_select())
+            .load(conn)
+    }
+
+    /// Append an entry to a booking's history, on the caller's connection so it
+    /// commits together with the change it describes
+    pub fn record_event(conn: &mut PgConnection, event: &NewBookingEvent) -> QueryResult<()> {
+        diesel::insert_into(booking_events::table)
+            .values(event)
+            .execute(conn)?;
+        Ok(())
+    }
+
     /// Handle stale bookings
-    pub fn handle_stale_bookings(&self, conn: &mut PgConnection) -> QueryResult<(usize, usize)> {
+    ///
+    /// - Upcoming bookings whose check-in date has passed become NoShow and get a
+    ///   pending no-show charge for admin review
+    /// - CheckedIn bookings past their check-out date become Overstay
+    /// - Expired booking holds are deleted
+    pub fn handle_stale_bookings(&self, conn: &mut PgConnection) -> QueryResult<StaleBookingSweep> {
         use crate::schema::bookings::dsl::*;
-        let today = chrono::Utc::now().naive_utc().date();
+        let today = self.hotel_today(conn)?;
+
+        conn.transaction(|conn| {
+            let no_shows: Vec<Booking> = diesel::update(bookings)
+                .filter(status.eq(BookingStatus::Upcoming))
+                .filter(check_in_date.lt(today))
+                .set(status.eq(BookingStatus::NoShow))
+                .get_results(conn)?;
+
+            NoShowService::create_pending_charges(conn, &no_shows)?;
+
+            let overstays: Vec<Booking> = diesel::update(bookings)
+                .filter(status.eq(BookingStatus::CheckedIn))
+                .filter(check_out_date.lt(today))
+                .set(status.eq(BookingStatus::Overstay))
+                .get_results(conn)?;
+
+            for booking in &no_shows {
+                Self::record_event(
+                    conn,
+                    &NewBookingEvent::system_status_change(booking.id, BookingStatus::Upcoming, BookingStatus::NoShow),
+                )?;
+            }
+            for booking in &overstays {
+                Self::record_event(
+                    conn,
+                    &NewBookingEvent::system_status_change(booking.id, BookingStatus::CheckedIn, BookingStatus::Overstay),
+                )?;
+            }
 
-        // No-show handling removed - upcoming bookings past check-in date remain as upcoming
-        let no_show_count = 0;
+            let mut overstay_room_ids: Vec<Uuid> = overstays.iter().map(|b| b.room_id).collect();
+            overstay_room_ids.sort();
+            overstay_room_ids.dedup();
 
-        let overstay_count = diesel::update(bookings)
-            .filter(status.eq(BookingStatus::CheckedIn))
-            .filter(check_out_date.lt(today))
-            .set(status.eq(BookingStatus::Overstay))
-            .execute(conn)?;
+            Ok(StaleBookingSweep {
+                no_shows: no_shows.len(),
+                overstays: overstays.len(),
+                overstay_room_ids,
+            })
+        })
+    }
+
+    /// Days a guest has stayed past the scheduled check-out date
+    pub fn days_over(scheduled_check_out: NaiveDate, today: NaiveDate) -> i64 {
+        (today - scheduled_check_out).num_days().max(0)
+    }
+
+    /// Rooms held by overstaying guests, longest overstay first
+    pub fn list_overstay_rooms(&self) -> AppResult<Vec<OverstayRoom>> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
 
-        Ok((no_show_count, overstay_count))
+        let rows: Vec<(Booking, Room)> = bookings::table
+            .inner_join(rooms::table)
+            .filter(bookings::status.eq(BookingStatus::Overstay))
+            .order((bookings::check_out_date.asc(), rooms::number.asc()))
+            .select((Booking::as_select(), Room::as_select()))
+            .load(&mut conn)?;
+
+        let today = self.hotel_today(&mut conn)?;
+        Ok(rows
+            .into_iter()
+            .map(|(booking, room)| OverstayRoom {
+                room_id: room.id,
+                room_number: room.number,
+                booking_id: booking.id,
+                reference: booking.reference,
+                guest_name: booking.guest_name,
+                scheduled_check_out: booking.check_out_date,
+                days_over: Self::days_over(booking.check_out_date, today),
+            })
+            .collect())
     }
-}
\ No newline at end of file
+}
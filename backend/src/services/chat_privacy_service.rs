@@ -0,0 +1,218 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::api::chat::PUPINN_ID;
+use crate::db::DbPool;
+use crate::errors::{AppError, AppResult};
+use crate::models::message::{Message, DELETED_MESSAGE_CONTENT};
+use crate::models::UserRole;
+use crate::schema::{messages, users};
+use crate::services::AuditService;
+use crate::utils::csv::csv_field;
+
+/// Privacy requests (exports and deletions) allowed per guest per hour
+pub const PRIVACY_REQUESTS_PER_HOUR: u32 = 5;
+
+/// Export file format
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChatExportFormat {
+    #[default]
+    Json,
+    Csv,
+}
+
+impl ChatExportFormat {
+    /// File extension, also used as the format name in the audit log
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ChatExportFormat::Json => "json",
+            ChatExportFormat::Csv => "csv",
+        }
+    }
+}
+
+/// Which conversations a guest asks to delete
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChatHistoryScope {
+    /// Conversations with the Pupinn assistant
+    #[default]
+    Bot,
+    /// Every conversation, including ones with staff
+    All,
+}
+
+/// One message in a guest's chat export. Staff are named only by role.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ChatExportEntry {
+    /// Who the conversation was with, e.g. "Pupinn (assistant)" or "Reception"
+    pub conversation_with: &'static str,
+    /// "sent" by the guest or "received" from the other party
+    pub direction: &'static str,
+    pub content: String,
+    pub image_url: Option<String>,
+    pub sent_at: DateTime<Utc>,
+}
+
+/// Label shown instead of a user's name in exports
+pub fn role_label(role: UserRole) -> &'static str {
+    match role {
+        UserRole::Bot => "Pupinn (assistant)",
+        UserRole::Receptionist => "Reception",
+        UserRole::Admin => "Administrator",
+        UserRole::Cleaner => "Housekeeping",
+        UserRole::Guest => "Guest",
+    }
+}
+
+/// Turn a guest's messages into export entries, oldest first
+///
+/// # Arguments
+/// * `roles` - Role of every other party in the conversations; unknown
+///   parties (e.g. deleted accounts) are labelled as staff
+pub fn export_entries(
+    guest_id: Uuid,
+    messages: &[Message],
+    roles: &HashMap<Uuid, UserRole>,
+) -> Vec<ChatExportEntry> {
+    let mut entries: Vec<ChatExportEntry> = messages
+        .iter()
+        .map(|m| {
+            let sent = m.sender_id == guest_id;
+            let other = if sent { m.receiver_id } else { m.sender_id };
+            ChatExportEntry {
+                conversation_with: roles.get(&other).map_or("Staff", |r| role_label(*r)),
+                direction: if sent { "sent" } else { "received" },
+                content: m.content.clone(),
+                image_url: m.image_url.clone(),
+                sent_at: m.created_at,
+            }
+        })
+        .collect();
+    entries.sort_by_key(|e| e.sent_at);
+    entries
+}
+
+/// Render export entries as CSV with a header row
+pub fn export_csv(entries: &[ChatExportEntry]) -> String {
+    let mut csv = String::from("sent_at,conversation_with,direction,content,image_url\n");
+    for entry in entries {
+        csv.push_str(&format!(
+            "{},{},{},{},{}\n",
+            entry.sent_at.to_rfc3339(),
+            csv_field(entry.conversation_with),
+            entry.direction,
+            csv_field(&entry.content),
+            csv_field(entry.image_url.as_deref().unwrap_or("")),
+        ));
+    }
+    csv
+}
+
+/// Chat privacy service for guest export and deletion requests
+pub struct ChatPrivacyService {
+    pool: DbPool,
+}
+
+impl ChatPrivacyService {
+    /// Create a new ChatPrivacyService instance
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    /// Every message the guest sent or received, apart from ones their
+    /// sender deleted, with staff names replaced by role labels. The export
+    /// is audit-logged.
+    pub fn export(&self, guest_id: Uuid, format: ChatExportFormat) -> AppResult<Vec<ChatExportEntry>> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let message_list: Vec<Message> = messages::table
+            .filter(
+                messages::sender_id
+                    .eq(guest_id)
+                    .or(messages::receiver_id.eq(guest_id)),
+            )
+            .filter(messages::deleted_at.is_null())
+            .order(messages::created_at.asc())
+            .load(&mut conn)?;
+
+        let others: Vec<Uuid> = message_list
+            .iter()
+            .map(|m| if m.sender_id == guest_id { m.receiver_id } else { m.sender_id })
+            .collect();
+        let roles: HashMap<Uuid, UserRole> = users::table
+            .filter(users::id.eq_any(&others))
+            .select((users::id, users::role))
+            .load::<(Uuid, UserRole)>(&mut conn)?
+            .into_iter()
+            .collect();
+
+        let entries = export_entries(guest_id, &message_list, &roles);
+
+        AuditService::record(
+            &mut conn,
+            Some(guest_id),
+            "guest_chat.exported",
+            "user",
+            Some(&guest_id.to_string()),
+            Some(&format!("{} export of {} messages", format.extension(), entries.len())),
+        )?;
+
+        Ok(entries)
+    }
+
+    /// Replace the content of the guest's conversations with the assistant
+    /// by a tombstone, both sides, so the assistant can no longer quote them.
+    /// Staff conversations go through an administrator instead.
+    ///
+    /// # Returns
+    /// Number of messages deleted
+    pub fn delete_history(&self, guest_id: Uuid, scope: ChatHistoryScope) -> AppResult<usize> {
+        if scope == ChatHistoryScope::All {
+            return Err(AppError::Forbidden(
+                "Conversations with staff can only be deleted by an administrator. Please contact the front desk.".to_string(),
+            ));
+        }
+
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        conn.transaction::<_, AppError, _>(|conn| {
+            let deleted = diesel::update(
+                messages::table
+                    .filter(
+                        (messages::sender_id.eq(guest_id).and(messages::receiver_id.eq(PUPINN_ID)))
+                            .or(messages::sender_id.eq(PUPINN_ID).and(messages::receiver_id.eq(guest_id))),
+                    )
+                    .filter(messages::content.ne(DELETED_MESSAGE_CONTENT)),
+            )
+            .set((
+                messages::content.eq(DELETED_MESSAGE_CONTENT),
+                messages::image_url.eq(None::<String>),
+                messages::updated_at.eq(Utc::now()),
+            ))
+            .execute(conn)?;
+
+            AuditService::record(
+                conn,
+                Some(guest_id),
+                "guest_chat.deleted",
+                "user",
+                Some(&guest_id.to_string()),
+                Some(&format!("{} assistant messages deleted", deleted)),
+            )?;
+
+            Ok(deleted)
+        })
+    }
+}
@@ -0,0 +1,238 @@
+use chrono::{DateTime, Duration, Utc};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::errors::{AppError, AppResult};
+use crate::models::{NewEmailVerificationToken, User, UserRole};
+use crate::schema::{email_verification_tokens, users};
+use crate::services::mailer::{Mailer, OutgoingEmail};
+use crate::services::AuthService;
+use crate::settings::{self, Settings};
+
+/// How long an emailed verification link stays valid
+pub const EMAIL_VERIFICATION_TOKEN_LIFETIME: Duration = Duration::hours(24);
+
+/// A guest may have the verification email sent again this often
+pub const RESEND_VERIFICATION_INTERVAL: Duration = Duration::minutes(5);
+
+/// Refusal for a verification token that is unknown, used, replaced or expired
+pub const INVALID_VERIFICATION_TOKEN: &str = "This verification link is invalid or has expired";
+
+/// Refusal for a booking by a guest who has not verified their email
+pub const EMAIL_NOT_VERIFIED: &str =
+    "Please verify your email address before booking; check your inbox for the verification link";
+
+/// Render the email with a guest's verification link
+pub fn render_verification_email(to: &str, link: &str) -> OutgoingEmail {
+    OutgoingEmail {
+        to: to.to_string(),
+        subject: "Verify your Pupinn email address".to_string(),
+        html: format!(
+            "<html><body><p>Welcome to Pupinn! Please confirm this is your email address so we can send \
+             you your booking confirmations.</p><p><a href=\"{0}\">{0}</a></p><p>The link is valid for {1} \
+             hours. If you did not create an account, you can ignore this email.</p></body></html>",
+            link,
+            EMAIL_VERIFICATION_TOKEN_LIFETIME.num_hours()
+        ),
+    }
+}
+
+/// Email verification for guest accounts; staff accounts are exempt
+pub struct EmailVerificationService {
+    pool: DbPool,
+}
+
+impl EmailVerificationService {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    fn conn(&self) -> AppResult<crate::db::DbConn> {
+        self.pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
+    /// Issue a verification token for a guest, replacing any earlier one,
+    /// and render the email carrying it
+    ///
+    /// # Returns
+    /// * `Ok(None)` - The account is staff, deactivated, or already verified
+    pub fn issue(&self, user_id: Uuid, now: DateTime<Utc>) -> AppResult<Option<OutgoingEmail>> {
+        let mut conn = self.conn()?;
+        let user: User = users::table
+            .find(user_id)
+            .first(&mut conn)
+            .optional()?
+            .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+        if user.role != UserRole::Guest || user.deactivated_at.is_some() || user.email_verified_at.is_some() {
+            return Ok(None);
+        }
+        let Some(recipient) = user.email else {
+            return Ok(None);
+        };
+        let portal_url = Settings::load(&mut conn)?.text(settings::GUEST_PORTAL_URL);
+
+        let token = AuthService::random_token();
+        conn.transaction(|conn| {
+            Self::invalidate_on(conn, user.id, now)?;
+            diesel::insert_into(email_verification_tokens::table)
+                .values(&NewEmailVerificationToken {
+                    user_id: user.id,
+                    token_hash: AuthService::hash_token(&token),
+                    expires_at: now + EMAIL_VERIFICATION_TOKEN_LIFETIME,
+                    created_at: now,
+                })
+                .execute(conn)?;
+            Ok::<_, AppError>(())
+        })?;
+
+        let link = format!("{}/verify-email?token={}", portal_url.trim_end_matches('/'), token);
+        Ok(Some(render_verification_email(&recipient, &link)))
+    }
+
+    /// Send the verification email again, at most once per
+    /// `RESEND_VERIFICATION_INTERVAL`
+    pub fn resend(&self, user_id: Uuid, now: DateTime<Utc>) -> AppResult<OutgoingEmail> {
+        let mut conn = self.conn()?;
+        let verified_at: Option<DateTime<Utc>> = users::table
+            .find(user_id)
+            .select(users::email_verified_at)
+            .first(&mut conn)?;
+        if verified_at.is_some() {
+            return Err(AppError::ValidationError("Your email address is already verified".to_string()));
+        }
+
+        let last_sent: Option<DateTime<Utc>> = email_verification_tokens::table
+            .filter(email_verification_tokens::user_id.eq(user_id))
+            .select(email_verification_tokens::created_at)
+            .order(email_verification_tokens::created_at.desc())
+            .first(&mut conn)
+            .optional()?;
+        if let Some(last_sent) = last_sent {
+            let next = last_sent + RESEND_VERIFICATION_INTERVAL;
+            if next > now {
+                let wait = (next - now).to_std().unwrap_or_default();
+                return Err(AppError::RateLimited(
+                    format!(
+                        "A verification email was sent recently; try again in {} minutes",
+                        RESEND_VERIFICATION_INTERVAL.num_minutes()
+                    ),
+                    wait,
+                ));
+            }
+        }
+
+        self.issue(user_id, now)?
+            .ok_or_else(|| AppError::ValidationError("This account does not need email verification".to_string()))
+    }
+
+    /// Mark a guest's email verified with an emailed token, using the token up
+    pub fn verify(&self, token: &str, now: DateTime<Utc>) -> AppResult<()> {
+        let mut conn = self.conn()?;
+
+        conn.transaction(|conn| {
+            // Using the token and checking it happen in one statement, so a
+            // link clicked twice at once only works once
+            let user_id: Uuid = diesel::update(
+                email_verification_tokens::table
+                    .filter(email_verification_tokens::token_hash.eq(AuthService::hash_token(token)))
+                    .filter(email_verification_tokens::used_at.is_null())
+                    .filter(email_verification_tokens::expires_at.gt(now)),
+            )
+            .set(email_verification_tokens::used_at.eq(now))
+            .returning(email_verification_tokens::user_id)
+            .get_result(conn)
+            .optional()?
+            .ok_or_else(|| AppError::ValidationError(INVALID_VERIFICATION_TOKEN.to_string()))?;
+
+            let updated = diesel::update(
+                users::table
+                    .find(user_id)
+                    .filter(users::deactivated_at.is_null()),
+            )
+            .set(users::email_verified_at.eq(now))
+            .execute(conn)?;
+            if updated == 0 {
+                return Err(AppError::ValidationError(INVALID_VERIFICATION_TOKEN.to_string()));
+            }
+            Ok(())
+        })
+    }
+
+    /// Refuse a guest who has not verified their email; staff pass
+    pub fn ensure_verified_on(conn: &mut PgConnection, user_id: Uuid) -> AppResult<()> {
+        let (role, verified_at): (UserRole, Option<DateTime<Utc>>) = users::table
+            .find(user_id)
+            .select((users::role, users::email_verified_at))
+            .first(conn)
+            .optional()?
+            .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+
+        if role == UserRole::Guest && verified_at.is_none() {
+            return Err(AppError::ValidationError(EMAIL_NOT_VERIFIED.to_string()));
+        }
+        Ok(())
+    }
+
+    /// `ensure_verified_on` with a connection from the pool
+    pub fn ensure_verified(&self, user_id: Uuid) -> AppResult<()> {
+        let mut conn = self.conn()?;
+        Self::ensure_verified_on(&mut conn, user_id)
+    }
+
+    /// Use up the outstanding verification tokens of a user
+    fn invalidate_on(conn: &mut PgConnection, user_id: Uuid, now: DateTime<Utc>) -> AppResult<()> {
+        diesel::update(
+            email_verification_tokens::table
+                .filter(email_verification_tokens::user_id.eq(user_id))
+                .filter(email_verification_tokens::used_at.is_null()),
+        )
+        .set(email_verification_tokens::used_at.eq(now))
+        .execute(conn)?;
+        Ok(())
+    }
+
+    /// Mail transport configured in the system settings
+    pub fn mailer(&self) -> AppResult<Mailer> {
+        let mut conn = self.conn()?;
+        let settings = Settings::load(&mut conn)?;
+        Mailer::from_smtp_settings(&settings).map_err(AppError::InternalError)
+    }
+}
+
+/// Issue a verification token for a new guest and email it on a background
+/// task; registration does not wait for it, so failures are only logged
+pub fn spawn_verification_email(pool: DbPool, user_id: Uuid) {
+    tokio::spawn(async move {
+        let prepared = tokio::task::spawn_blocking(move || {
+            let service = EmailVerificationService::new(pool);
+            let Some(outgoing) = service.issue(user_id, Utc::now())? else {
+                return Ok::<_, AppError>(None);
+            };
+            Ok(Some((outgoing, service.mailer()?)))
+        })
+        .await
+        .map_err(|e| e.to_string())
+        .and_then(|prepared| prepared.map_err(|e| e.to_string()));
+
+        let result = match prepared {
+            Ok(Some((outgoing, mailer))) => mailer.send(&outgoing).await,
+            Ok(None) => Ok(()),
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            tracing::warn!("Could not send verification email: {}", e);
+        }
+    });
+}
+
+/// Send an already issued verification email on a background task
+pub fn spawn_send(mailer: Mailer, outgoing: OutgoingEmail) {
+    tokio::spawn(async move {
+        if let Err(e) = mailer.send(&outgoing).await {
+            tracing::warn!("Could not send verification email: {}", e);
+        }
+    });
+}
@@ -1,13 +1,62 @@
+use std::collections::HashMap;
+
+use bigdecimal::BigDecimal;
+use chrono::{NaiveDate, Utc};
+use diesel::dsl::{count_star, sum};
 use diesel::prelude::*;
+use serde::Serialize;
 use uuid::Uuid;
 
+use crate::api::chat::PUPINN_ID;
 use crate::db::DbPool;
 use crate::errors::{AppError, AppResult};
 use crate::models::{
-    Booking, BookingWithRoom, GuestNote, NewGuestNote, UpdateUser, User,
-    UserRole,
+    Booking, BookingStatus, BookingWithRoom, GuestNote, NewGuestNote, Room, RoomType, UpdateUser,
+    User, UserRole,
 };
-use crate::schema::{bookings, guest_interaction_notes, users};
+use crate::schema::{bookings, guest_interaction_notes, messages, payments, rooms, users};
+use crate::services::BookingService;
+use crate::settings::{self, Settings};
+use crate::utils::clock;
+
+/// Booking counts shown next to each guest in list/search results
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct GuestBookingStats {
+    pub total_bookings: i64,
+    /// Check-in date of the most recent stay that actually started
+    pub last_stay_date: Option<NaiveDate>,
+    pub has_upcoming_booking: bool,
+}
+
+/// Contact details from the guest account linked to a booking
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct InHouseContact {
+    pub user_id: Uuid,
+    pub full_name: Option<String>,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+}
+
+/// One checked-in or overstaying stay in the reception directory
+#[derive(Debug, Clone, Serialize)]
+pub struct InHouseGuest {
+    pub booking_id: Uuid,
+    pub reference: String,
+    pub guest_name: String,
+    pub status: BookingStatus,
+    pub room_id: Uuid,
+    pub room_number: String,
+    pub room_type: RoomType,
+    pub check_in_date: NaiveDate,
+    pub check_out_date: NaiveDate,
+    /// Nights left before the scheduled check-out; 0 on departure day or when overstaying
+    pub nights_remaining: i64,
+    pub balance_due: BigDecimal,
+    /// None for walk-in bookings without a guest account
+    pub contact: Option<InHouseContact>,
+    /// Messages from the guest to staff that nobody has read yet
+    pub unread_messages: i64,
+}
 
 /// Guest service for managing guest information and interaction notes
 pub struct GuestService {
@@ -164,7 +213,7 @@ impl GuestService {
         // Also get staff-created bookings that match guest's name (case-insensitive)
         if let Some(ref full_name) = guest.full_name {
             let staff_created_bookings: Vec<Booking> = bookings::table
-                .filter(bookings::created_by_user_id.is_null())
+                .filter(bookings::creation_source.eq("staff"))
                 .filter(bookings::guest_name.ilike(full_name))
                 .order(bookings::created_at.desc())
                 .load(&mut conn)
@@ -179,12 +228,16 @@ impl GuestService {
         }
 
         // Sort by created_at descending
-        all_bookings.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        all_bookings.sort_by_key(|b| std::cmp::Reverse(b.created_at));
 
         // Load room details for each booking
         use crate::models::Room;
         use crate::schema::rooms;
 
+        let booking_ids: Vec<Uuid> = all_bookings.iter().map(|b| b.id).collect();
+        let counts = BookingService::modification_counts(&mut conn, &booking_ids)
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
         let mut bookings_with_rooms = Vec::new();
         for booking in all_bookings {
             let room: Option<Room> = rooms::table
@@ -194,6 +247,9 @@ impl GuestService {
                 .map_err(|e| AppError::DatabaseError(e.to_string()))?;
 
             bookings_with_rooms.push(BookingWithRoom {
+                modification_count: counts.get(&booking.id).copied().unwrap_or(0),
+                notes: None,
+                created_by: None,
                 booking,
                 room,
             });
@@ -202,6 +258,209 @@ impl GuestService {
         Ok(bookings_with_rooms)
     }
 
+    /// Whether a booking belongs to a guest, using the same rules as the profile history:
+    /// owned by the guest, or staff-created with a guest_name matching the full name
+    pub fn booking_matches_guest(booking: &Booking, guest: &User) -> bool {
+        if booking.creation_source == "guest" {
+            return booking.created_by_user_id == Some(guest.id);
+        }
+        guest
+            .full_name
+            .as_deref()
+            .is_some_and(|name| booking.guest_name.to_lowercase() == name.to_lowercase())
+    }
+
+    /// Aggregate booking statistics per guest from an already loaded set of bookings
+    ///
+    /// # Arguments
+    /// * `guests` - Guests to summarize
+    /// * `bookings` - Candidate bookings (may include bookings of other guests)
+    /// * `today` - Reference date for upcoming bookings
+    pub fn summarize_booking_stats(
+        guests: &[User],
+        bookings: &[Booking],
+        today: NaiveDate,
+    ) -> HashMap<Uuid, GuestBookingStats> {
+        guests
+            .iter()
+            .map(|guest| {
+                let mut stats = GuestBookingStats::default();
+                for booking in bookings.iter().filter(|b| Self::booking_matches_guest(b, guest)) {
+                    stats.total_bookings += 1;
+
+                    if matches!(
+                        booking.status,
+                        BookingStatus::CheckedIn | BookingStatus::CheckedOut | BookingStatus::Overstay
+                    ) && stats.last_stay_date.map_or(true, |d| booking.check_in_date > d)
+                    {
+                        stats.last_stay_date = Some(booking.check_in_date);
+                    }
+
+                    if booking.status == BookingStatus::Upcoming && booking.check_in_date >= today {
+                        stats.has_upcoming_booking = true;
+                    }
+                }
+                (guest.id, stats)
+            })
+            .collect()
+    }
+
+    /// Get booking statistics for a page of guests with a single bookings query
+    ///
+    /// # Arguments
+    /// * `guests` - Guests returned by list/search
+    ///
+    /// # Returns
+    /// * `HashMap<Uuid, GuestBookingStats>` - Stats keyed by guest id
+    pub fn get_booking_stats(&self, guests: &[User]) -> AppResult<HashMap<Uuid, GuestBookingStats>> {
+        if guests.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let guest_ids: Vec<Uuid> = guests.iter().map(|g| g.id).collect();
+
+        // Owned bookings plus staff-created bookings matching any guest's name
+        let mut query = bookings::table
+            .into_boxed()
+            .filter(bookings::created_by_user_id.eq_any(guest_ids));
+        for full_name in guests.iter().filter_map(|g| g.full_name.as_deref()) {
+            query = query.or_filter(
+                bookings::creation_source
+                    .eq("staff")
+                    .and(bookings::guest_name.ilike(full_name)),
+            );
+        }
+
+        let candidate_bookings: Vec<Booking> = query
+            .load(&mut conn)
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(Self::summarize_booking_stats(
+            guests,
+            &candidate_bookings,
+            clock::local_date(Utc::now(), Settings::load(&mut conn)?.time_zone(settings::HOTEL_TIMEZONE)),
+        ))
+    }
+
+    /// Assemble the in-house directory from the loaded rows, keeping the
+    /// order of `stays`
+    ///
+    /// # Arguments
+    /// * `contacts` - Guest accounts keyed by user id
+    /// * `paid` - Net payments keyed by booking id
+    /// * `unread` - Unread messages to staff keyed by guest user id
+    pub fn build_in_house_directory(
+        stays: Vec<(Booking, Room)>,
+        contacts: &HashMap<Uuid, User>,
+        paid: &HashMap<Uuid, BigDecimal>,
+        unread: &HashMap<Uuid, i64>,
+        today: NaiveDate,
+    ) -> Vec<InHouseGuest> {
+        stays
+            .into_iter()
+            .map(|(booking, room)| {
+                let contact = booking
+                    .created_by_user_id
+                    .and_then(|id| contacts.get(&id))
+                    .map(|user| InHouseContact {
+                        user_id: user.id,
+                        full_name: user.full_name.clone(),
+                        email: user.email.clone(),
+                        phone: user.phone.clone(),
+                    });
+                let balance_due = match paid.get(&booking.id) {
+                    Some(total_paid) => &booking.price - total_paid,
+                    None => booking.price.clone(),
+                };
+
+                InHouseGuest {
+                    nights_remaining: BookingService::remaining_nights(booking.check_out_date, today),
+                    unread_messages: contact
+                        .as_ref()
+                        .and_then(|c| unread.get(&c.user_id))
+                        .copied()
+                        .unwrap_or(0),
+                    balance_due,
+                    contact,
+                    booking_id: booking.id,
+                    reference: booking.reference,
+                    guest_name: booking.guest_name,
+                    status: booking.status,
+                    room_id: room.id,
+                    room_number: room.number,
+                    room_type: room.room_type,
+                    check_in_date: booking.check_in_date,
+                    check_out_date: booking.check_out_date,
+                }
+            })
+            .collect()
+    }
+
+    /// Everyone checked in or overstaying right now, by room number, with
+    /// contact details, balance and unread chat. Loaded with one query per
+    /// source rather than per booking.
+    ///
+    /// # Arguments
+    /// * `room_number` - Only the stay in this room (exact match)
+    pub fn in_house_directory(&self, room_number: Option<&str>) -> AppResult<Vec<InHouseGuest>> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let mut query = bookings::table
+            .inner_join(rooms::table)
+            .filter(bookings::status.eq_any([BookingStatus::CheckedIn, BookingStatus::Overstay]))
+            .into_boxed();
+        if let Some(number) = room_number {
+            query = query.filter(rooms::number.eq(number));
+        }
+        let stays: Vec<(Booking, Room)> = query
+            .order((rooms::number.asc(), bookings::check_in_date.asc()))
+            .select((Booking::as_select(), Room::as_select()))
+            .load(&mut conn)?;
+
+        let booking_ids: Vec<Uuid> = stays.iter().map(|(b, _)| b.id).collect();
+        let account_ids: Vec<Uuid> = stays.iter().filter_map(|(b, _)| b.created_by_user_id).collect();
+
+        let contacts: HashMap<Uuid, User> = users::table
+            .filter(users::id.eq_any(&account_ids))
+            .filter(users::role.eq(UserRole::Guest))
+            .load::<User>(&mut conn)?
+            .into_iter()
+            .map(|u| (u.id, u))
+            .collect();
+
+        let paid: HashMap<Uuid, BigDecimal> = payments::table
+            .filter(payments::booking_id.eq_any(&booking_ids))
+            .group_by(payments::booking_id)
+            .select((payments::booking_id, sum(payments::amount)))
+            .load::<(Uuid, Option<BigDecimal>)>(&mut conn)?
+            .into_iter()
+            .filter_map(|(id, total)| total.map(|t| (id, t)))
+            .collect();
+
+        let guest_ids: Vec<Uuid> = contacts.keys().copied().collect();
+        let unread: HashMap<Uuid, i64> = messages::table
+            .filter(messages::sender_id.eq_any(&guest_ids))
+            .filter(messages::receiver_id.ne(PUPINN_ID))
+            .filter(messages::is_read.eq(false))
+            .group_by(messages::sender_id)
+            .select((messages::sender_id, count_star()))
+            .load::<(Uuid, i64)>(&mut conn)?
+            .into_iter()
+            .collect();
+
+        let today = clock::local_date(Utc::now(), Settings::load(&mut conn)?.time_zone(settings::HOTEL_TIMEZONE));
+
+        Ok(Self::build_in_house_directory(stays, &contacts, &paid, &unread, today))
+    }
+
     /// Update guest information (PII fields)
     ///
     /// # Arguments
@@ -296,6 +555,24 @@ impl GuestService {
         Ok(notes)
     }
 
+    /// Reject empty notes and notes over 10,000 characters; booking notes
+    /// use the same rules
+    pub fn validate_note(note: &str) -> AppResult<()> {
+        if note.trim().is_empty() {
+            return Err(AppError::ValidationError(
+                "Note cannot be empty".to_string(),
+            ));
+        }
+
+        if note.len() > 10000 {
+            return Err(AppError::ValidationError(
+                "Note must be 10,000 characters or less".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
     /// Add an interaction note for a guest
     ///
     /// # Arguments
@@ -315,18 +592,7 @@ impl GuestService {
         admin_id: Uuid,
         note: &str,
     ) -> AppResult<GuestNote> {
-        // Validate note
-        if note.trim().is_empty() {
-            return Err(AppError::ValidationError(
-                "Note cannot be empty".to_string(),
-            ));
-        }
-
-        if note.len() > 10000 {
-            return Err(AppError::ValidationError(
-                "Note must be 10,000 characters or less".to_string(),
-            ));
-        }
+        Self::validate_note(note)?;
 
         let mut conn = self
             .pool
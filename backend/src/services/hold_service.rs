@@ -0,0 +1,338 @@
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use diesel::prelude::*;
+use diesel::result::QueryResult;
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::errors::{AppError, AppResult};
+use crate::models::{BookingHold, NewBookingHold};
+use crate::schema::{booking_holds, users};
+use crate::services::{AuditService, BookingService, RoomBlockService};
+use crate::settings::{self, Settings};
+
+/// How long a room stays held while the guest completes payment
+pub const HOLD_DURATION_MINUTES: i64 = 15;
+/// Unexpired holds one guest may have at the same time
+pub const MAX_ACTIVE_HOLDS: i64 = 2;
+/// Expired holds deleted per transaction by a purge
+pub const HOLD_PURGE_BATCH: i64 = 500;
+/// Holds listed in the housekeeping overview, most recent first
+pub const HOLD_OVERVIEW_LIMIT: i64 = 100;
+
+/// Holds created within `max_age_secs` (and before the previous bucket)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HoldAgeBucket {
+    pub label: &'static str,
+    /// `None` for the last, open-ended bucket
+    pub max_age_secs: Option<i64>,
+    pub count: i64,
+}
+
+/// Age buckets of the housekeeping overview, by time since creation
+const HOLD_AGE_BUCKETS: [(&str, Option<i64>); 4] = [
+    ("under_1h", Some(3600)),
+    ("1h_to_1d", Some(86_400)),
+    ("1d_to_7d", Some(7 * 86_400)),
+    ("over_7d", None),
+];
+
+/// Booking holds by state and age, for admins cleaning up the table
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HoldOverview {
+    pub total: i64,
+    pub active: i64,
+    pub expired: i64,
+    /// Expired for longer than the retention; the next purge deletes them
+    pub purgeable: i64,
+    pub retention_secs: i64,
+    pub age_buckets: Vec<HoldAgeBucket>,
+    /// Most recent holds first, at most `HOLD_OVERVIEW_LIMIT`
+    pub holds: Vec<BookingHold>,
+}
+
+/// Outcome of purging expired holds
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HoldPurge {
+    pub dry_run: bool,
+    /// Holds that expired at or before this time are purged
+    pub cutoff: DateTime<Utc>,
+    /// Holds deleted, or that would be on a dry run
+    pub holds: usize,
+    /// Transactions the deletes ran in; zero on a dry run
+    pub batches: usize,
+}
+
+/// Booking hold service for reserving a room during payment
+pub struct HoldService {
+    pool: DbPool,
+}
+
+impl HoldService {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    fn conn(&self) -> AppResult<crate::db::DbConn> {
+        self.pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
+    /// Refuse a new hold when the guest already has the maximum unexpired
+    pub fn check_hold_limit(active_holds: i64) -> AppResult<()> {
+        if active_holds >= MAX_ACTIVE_HOLDS {
+            return Err(AppError::Conflict(format!(
+                "You can hold at most {} rooms at a time",
+                MAX_ACTIVE_HOLDS
+            )));
+        }
+        Ok(())
+    }
+
+    /// Hold a room for a guest for `HOLD_DURATION_MINUTES`. Holding the same
+    /// room and dates again renews the hold instead of counting twice.
+    ///
+    /// # Errors
+    /// * `RoomUnavailable` - Booked, held by someone else or under maintenance
+    /// * `Conflict` - The guest already has `MAX_ACTIVE_HOLDS` unexpired holds
+    pub fn create_hold(
+        &self,
+        user_id: Uuid,
+        room_id: Uuid,
+        check_in_date: NaiveDate,
+        check_out_date: NaiveDate,
+    ) -> AppResult<BookingHold> {
+        let bookings = BookingService::new(self.pool.clone());
+        bookings.validate_dates(check_in_date, check_out_date)?;
+
+        let mut conn = self.conn()?;
+        let now = Utc::now();
+
+        // The room lock orders this hold against bookings and other holds of
+        // the room; the user lock keeps concurrent requests under the limit
+        conn.transaction::<_, AppError, _>(|conn| {
+            let room = BookingService::lock_room(conn, room_id)?;
+            users::table
+                .find(user_id)
+                .select(users::id)
+                .for_update()
+                .first::<Uuid>(conn)?;
+
+            Self::release(conn, user_id, room_id, check_in_date, check_out_date)?;
+            let active: i64 = booking_holds::table
+                .filter(booking_holds::user_id.eq(user_id))
+                .filter(booking_holds::expires_at.gt(now))
+                .count()
+                .get_result(conn)?;
+            Self::check_hold_limit(active)?;
+
+            RoomBlockService::check_not_blocked_on(conn, &room, check_in_date, check_out_date)?;
+            if !bookings.check_availability_on(
+                conn,
+                room_id,
+                check_in_date,
+                check_out_date,
+                None,
+                Some(user_id),
+            )? {
+                return Err(AppError::RoomUnavailable(format!(
+                    "Room {} is not available for the selected dates",
+                    room.number
+                )));
+            }
+
+            Ok(diesel::insert_into(booking_holds::table)
+                .values(&NewBookingHold {
+                    room_id,
+                    user_id,
+                    check_in_date,
+                    check_out_date,
+                    expires_at: now + Duration::minutes(HOLD_DURATION_MINUTES),
+                })
+                .get_result(conn)?)
+        })
+    }
+
+    /// Whether another user holds the room on any of the nights, as of `now`
+    pub fn held_by_others(
+        conn: &mut PgConnection,
+        room_id: Uuid,
+        check_in_date: NaiveDate,
+        check_out_date: NaiveDate,
+        holder_user_id: Option<Uuid>,
+        now: DateTime<Utc>,
+    ) -> QueryResult<bool> {
+        let mut query = booking_holds::table
+            .filter(booking_holds::room_id.eq(room_id))
+            .filter(booking_holds::expires_at.gt(now))
+            .filter(booking_holds::check_in_date.lt(check_out_date))
+            .filter(booking_holds::check_out_date.gt(check_in_date))
+            .into_boxed();
+        if let Some(holder) = holder_user_id {
+            query = query.filter(booking_holds::user_id.ne(holder));
+        }
+
+        diesel::select(diesel::dsl::exists(query)).get_result(conn)
+    }
+
+    /// Unexpired holds of the given rooms touching any night from `start_date`
+    /// to `end_date` (inclusive) as (room_id, check_in_date, check_out_date)
+    pub fn load_active_on(
+        conn: &mut PgConnection,
+        room_ids: &[Uuid],
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+        now: DateTime<Utc>,
+    ) -> AppResult<Vec<(Uuid, NaiveDate, NaiveDate)>> {
+        booking_holds::table
+            .filter(booking_holds::room_id.eq_any(room_ids))
+            .filter(booking_holds::expires_at.gt(now))
+            .filter(booking_holds::check_in_date.le(end_date))
+            .filter(booking_holds::check_out_date.gt(start_date))
+            .select((
+                booking_holds::room_id,
+                booking_holds::check_in_date,
+                booking_holds::check_out_date,
+            ))
+            .load(conn)
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
+    /// Delete a guest's hold on exactly this room and these dates, e.g. once
+    /// the booking is made
+    pub fn release(
+        conn: &mut PgConnection,
+        user_id: Uuid,
+        room_id: Uuid,
+        check_in_date: NaiveDate,
+        check_out_date: NaiveDate,
+    ) -> QueryResult<usize> {
+        diesel::delete(
+            booking_holds::table
+                .filter(booking_holds::user_id.eq(user_id))
+                .filter(booking_holds::room_id.eq(room_id))
+                .filter(booking_holds::check_in_date.eq(check_in_date))
+                .filter(booking_holds::check_out_date.eq(check_out_date)),
+        )
+        .execute(conn)
+    }
+
+    /// Counts by state and age of every hold, with the most recent ones
+    pub fn overview(&self, now: DateTime<Utc>) -> AppResult<HoldOverview> {
+        let mut conn = self.conn()?;
+        let retention = Settings::load(&mut conn)?.duration(settings::HOLD_RETENTION);
+
+        let total: i64 = booking_holds::table.count().get_result(&mut conn)?;
+        let active: i64 = booking_holds::table
+            .filter(booking_holds::expires_at.gt(now))
+            .count()
+            .get_result(&mut conn)?;
+        let purgeable: i64 = booking_holds::table
+            .filter(booking_holds::expires_at.le(now - retention))
+            .count()
+            .get_result(&mut conn)?;
+
+        let mut age_buckets = Vec::with_capacity(HOLD_AGE_BUCKETS.len());
+        let mut newer_than: Option<i64> = None;
+        for (label, max_age_secs) in HOLD_AGE_BUCKETS {
+            let mut query = booking_holds::table.into_boxed();
+            if let Some(secs) = max_age_secs {
+                query = query.filter(booking_holds::created_at.gt(now - Duration::seconds(secs)));
+            }
+            if let Some(secs) = newer_than {
+                query = query.filter(booking_holds::created_at.le(now - Duration::seconds(secs)));
+            }
+            age_buckets.push(HoldAgeBucket {
+                label,
+                max_age_secs,
+                count: query.count().get_result(&mut conn)?,
+            });
+            newer_than = max_age_secs;
+        }
+
+        let holds = booking_holds::table
+            .order(booking_holds::created_at.desc())
+            .limit(HOLD_OVERVIEW_LIMIT)
+            .load(&mut conn)?;
+
+        Ok(HoldOverview {
+            total,
+            active,
+            expired: total - active,
+            purgeable,
+            retention_secs: retention.num_seconds(),
+            age_buckets,
+            holds,
+        })
+    }
+
+    /// Delete holds that expired longer than the retention setting ago, in
+    /// batches of `HOLD_PURGE_BATCH`, each audit-logged with its row count.
+    /// A dry run only counts them.
+    ///
+    /// # Arguments
+    /// * `actor_user_id` - Admin purging by hand; `None` for the cleanup job
+    pub fn purge_expired(
+        &self,
+        now: DateTime<Utc>,
+        dry_run: bool,
+        actor_user_id: Option<Uuid>,
+    ) -> AppResult<HoldPurge> {
+        let mut conn = self.conn()?;
+        let cutoff = now - Settings::load(&mut conn)?.duration(settings::HOLD_RETENTION);
+
+        if dry_run {
+            let holds: i64 = booking_holds::table
+                .filter(booking_holds::expires_at.le(cutoff))
+                .count()
+                .get_result(&mut conn)?;
+            return Ok(HoldPurge {
+                dry_run,
+                cutoff,
+                holds: holds as usize,
+                batches: 0,
+            });
+        }
+
+        let mut purge = HoldPurge {
+            dry_run,
+            cutoff,
+            holds: 0,
+            batches: 0,
+        };
+        loop {
+            let deleted = conn.transaction::<_, AppError, _>(|conn| {
+                let ids: Vec<Uuid> = booking_holds::table
+                    .filter(booking_holds::expires_at.le(cutoff))
+                    .select(booking_holds::id)
+                    .limit(HOLD_PURGE_BATCH)
+                    .for_update()
+                    .skip_locked()
+                    .load(conn)?;
+                let deleted = diesel::delete(booking_holds::table.filter(booking_holds::id.eq_any(&ids)))
+                    .execute(conn)?;
+                if deleted > 0 {
+                    AuditService::record(
+                        conn,
+                        actor_user_id,
+                        "booking_holds.purged",
+                        "booking_hold",
+                        None,
+                        Some(&format!("{} holds expired at or before {} deleted", deleted, cutoff.to_rfc3339())),
+                    )?;
+                }
+                Ok(deleted)
+            })?;
+            if deleted == 0 {
+                break;
+            }
+            purge.holds += deleted;
+            purge.batches += 1;
+            if (deleted as i64) < HOLD_PURGE_BATCH {
+                break;
+            }
+        }
+        Ok(purge)
+    }
+}
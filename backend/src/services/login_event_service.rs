@@ -0,0 +1,115 @@
+use diesel::prelude::*;
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::errors::{AppError, AppResult};
+use crate::models::{LoginEvent, NewLoginEvent, UserRole};
+use crate::schema::{login_events, users};
+use crate::services::AuthService;
+
+/// Longest user agent kept; longer ones are cut
+pub const MAX_USER_AGENT_CHARS: usize = 512;
+
+/// Where a login attempt came from
+#[derive(Debug, Clone)]
+pub struct LoginOrigin {
+    pub ip: String,
+    pub user_agent: Option<String>,
+}
+
+/// One page of an account's login history, newest first
+#[derive(Debug, Serialize)]
+pub struct LoginHistoryPage {
+    pub events: Vec<LoginEvent>,
+    pub total: i64,
+    pub page: i64,
+    pub per_page: i64,
+}
+
+/// Login audit log: successful and failed logins per account
+pub struct LoginEventService {
+    pool: DbPool,
+}
+
+impl LoginEventService {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    fn conn(&self) -> AppResult<crate::db::DbConn> {
+        self.pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
+    /// Record a login attempt against an account
+    pub fn record(&self, user_id: Uuid, success: bool, origin: &LoginOrigin) -> AppResult<()> {
+        let mut conn = self.conn()?;
+        let user_agent: Option<String> = origin
+            .user_agent
+            .as_deref()
+            .map(|agent| agent.chars().take(MAX_USER_AGENT_CHARS).collect());
+
+        diesel::insert_into(login_events::table)
+            .values(&NewLoginEvent {
+                user_id,
+                success,
+                ip: &origin.ip,
+                user_agent: user_agent.as_deref(),
+            })
+            .execute(&mut conn)?;
+        Ok(())
+    }
+
+    /// Record a failed staff login against the account with `username`, if any
+    pub fn record_staff_failure(&self, username: &str, origin: &LoginOrigin) -> AppResult<()> {
+        let mut conn = self.conn()?;
+        let user_id: Option<Uuid> = users::table
+            .filter(users::username.eq(username.trim()))
+            .filter(users::role.ne(UserRole::Guest))
+            .select(users::id)
+            .first(&mut conn)
+            .optional()?;
+
+        match user_id {
+            Some(user_id) => self.record(user_id, false, origin),
+            None => Ok(()),
+        }
+    }
+
+    /// Record a failed guest login against the account with `email`, if any
+    pub fn record_guest_failure(&self, email: &str, origin: &LoginOrigin) -> AppResult<()> {
+        let mut conn = self.conn()?;
+        match AuthService::guest_by_email(&mut conn, email)? {
+            Some(user) => self.record(user.id, false, origin),
+            None => Ok(()),
+        }
+    }
+
+    /// Page through an account's login attempts, newest first
+    pub fn history(&self, user_id: Uuid, page: Option<i64>, per_page: Option<i64>) -> AppResult<LoginHistoryPage> {
+        let mut conn = self.conn()?;
+        let page = page.unwrap_or(1).max(1);
+        let per_page = per_page.unwrap_or(20).clamp(1, 100);
+
+        let total: i64 = login_events::table
+            .filter(login_events::user_id.eq(user_id))
+            .count()
+            .get_result(&mut conn)?;
+        let events = login_events::table
+            .filter(login_events::user_id.eq(user_id))
+            .order((login_events::created_at.desc(), login_events::id.desc()))
+            .limit(per_page)
+            .offset((page - 1) * per_page)
+            .select(LoginEvent::as_select())
+            .load(&mut conn)?;
+
+        Ok(LoginHistoryPage {
+            events,
+            total,
+            page,
+            per_page,
+        })
+    }
+}
@@ -0,0 +1,141 @@
+use lettre::message::header::ContentType;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use serde::Serialize;
+
+use crate::settings::{self, Settings};
+
+/// Email ready to hand to a transport
+#[derive(Debug, Clone, Serialize)]
+pub struct OutgoingEmail {
+    pub to: String,
+    pub subject: String,
+    pub html: String,
+}
+
+/// Email transport. Without MAIL_API_URL configured, emails are only logged,
+/// which keeps development and test setups from sending real mail.
+#[derive(Debug, Clone)]
+pub enum Mailer {
+    /// Log the email instead of sending it
+    Log,
+    /// POST the email as JSON to a transactional mail API
+    Http {
+        client: reqwest::Client,
+        url: String,
+        api_key: Option<String>,
+        from: String,
+    },
+    /// Send through an SMTP relay with STARTTLS
+    Smtp {
+        transport: AsyncSmtpTransport<Tokio1Executor>,
+        from: String,
+    },
+}
+
+/// JSON body sent to the mail API
+#[derive(Serialize)]
+struct MailApiRequest<'a> {
+    from: &'a str,
+    to: &'a str,
+    subject: &'a str,
+    html: &'a str,
+}
+
+impl Mailer {
+    /// Build the transport from configuration
+    ///
+    /// # Arguments
+    /// * `api_url` - Mail API endpoint; `None` logs instead of sending
+    /// * `api_key` - Optional bearer token for the mail API
+    /// * `from` - Sender address
+    pub fn from_config(api_url: Option<String>, api_key: Option<String>, from: String) -> Self {
+        match api_url {
+            Some(url) => Mailer::Http {
+                client: reqwest::Client::new(),
+                url,
+                api_key,
+                from,
+            },
+            None => Mailer::Log,
+        }
+    }
+
+    /// Build the SMTP transport from the `smtp_*` system settings. Without a
+    /// host configured, emails are only logged.
+    ///
+    /// # Returns
+    /// * `Err(message)` - The host cannot be used as a STARTTLS relay
+    pub fn from_smtp_settings(settings: &Settings) -> Result<Self, String> {
+        let host = settings.text(settings::SMTP_HOST);
+        if host.is_empty() {
+            return Ok(Mailer::Log);
+        }
+
+        let port = u16::try_from(settings.integer(settings::SMTP_PORT)).map_err(|e| e.to_string())?;
+        let mut builder = AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&host)
+            .map_err(|e| e.to_string())?
+            .port(port);
+        let username = settings.text(settings::SMTP_USERNAME);
+        if !username.is_empty() {
+            builder = builder.credentials(Credentials::new(username, settings.text(settings::SMTP_PASSWORD)));
+        }
+
+        Ok(Mailer::Smtp {
+            transport: builder.build(),
+            from: settings.text(settings::SMTP_FROM),
+        })
+    }
+
+    /// Send an email
+    ///
+    /// # Returns
+    /// * `Err(message)` - The transport rejected or could not reach the mail API
+    pub async fn send(&self, email: &OutgoingEmail) -> Result<(), String> {
+        match self {
+            Mailer::Log => {
+                tracing::info!(
+                    "Mail transport not configured; would send '{}' to {} ({} bytes)",
+                    email.subject,
+                    email.to,
+                    email.html.len()
+                );
+                Ok(())
+            }
+            Mailer::Http {
+                client,
+                url,
+                api_key,
+                from,
+            } => {
+                let mut request = client.post(url).json(&MailApiRequest {
+                    from,
+                    to: &email.to,
+                    subject: &email.subject,
+                    html: &email.html,
+                });
+                if let Some(key) = api_key {
+                    request = request.bearer_auth(key);
+                }
+
+                let response = request.send().await.map_err(|e| e.to_string())?;
+                if response.status().is_success() {
+                    Ok(())
+                } else {
+                    Err(format!("Mail API responded with {}", response.status()))
+                }
+            }
+            Mailer::Smtp { transport, from } => {
+                let message = Message::builder()
+                    .from(from.parse().map_err(|e| format!("Invalid sender address: {}", e))?)
+                    .to(email.to.parse().map_err(|e| format!("Invalid recipient address: {}", e))?)
+                    .subject(email.subject.as_str())
+                    .header(ContentType::TEXT_HTML)
+                    .body(email.html.clone())
+                    .map_err(|e| e.to_string())?;
+
+                transport.send(message).await.map(|_| ()).map_err(|e| e.to_string())
+            }
+        }
+    }
+}
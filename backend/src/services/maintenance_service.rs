@@ -0,0 +1,167 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use axum::http::Method;
+use diesel::prelude::*;
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::errors::{AppError, AppResult};
+use crate::services::audit_service::AuditService;
+use crate::settings::{self, Settings};
+
+/// System setting key holding the read-only switch
+pub const READ_ONLY_SETTING_KEY: &str = settings::READ_ONLY_MODE;
+/// Seconds clients are told to wait before retrying a refused write
+pub const READ_ONLY_RETRY_AFTER_SECS: u64 = 300;
+/// Message returned to clients whose writes are refused
+pub const READ_ONLY_MESSAGE: &str =
+    "The system is in read-only mode for maintenance. Please try again shortly.";
+
+/// Where the effective read-only state comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReadOnlySource {
+    /// READ_ONLY_MODE environment variable, which wins over the setting
+    Env,
+    /// `read_only_mode` system setting toggled by an admin
+    Setting,
+}
+
+/// Snapshot of the read-only mode reported by the health and admin endpoints
+#[derive(Debug, Clone, Serialize)]
+pub struct ReadOnlyStatus {
+    pub read_only: bool,
+    pub source: ReadOnlySource,
+    /// Value of the system setting, which may be shadowed by the env override
+    pub setting_enabled: bool,
+}
+
+/// Process-wide read-only flag, checked on every request without a database
+/// round-trip. The setting half is refreshed from the database by the scheduler
+/// so every instance converges on an admin toggle.
+#[derive(Debug, Default)]
+pub struct ReadOnlyMode {
+    env_override: Option<bool>,
+    setting: AtomicBool,
+}
+
+impl ReadOnlyMode {
+    /// Create the flag with an optional env override
+    pub fn new(env_override: Option<bool>) -> Self {
+        Self {
+            env_override,
+            setting: AtomicBool::new(false),
+        }
+    }
+
+    /// Whether writes are currently refused
+    pub fn is_enabled(&self) -> bool {
+        self.env_override
+            .unwrap_or_else(|| self.setting.load(Ordering::Relaxed))
+    }
+
+    /// Record the latest value of the system setting
+    pub fn set_setting(&self, enabled: bool) {
+        self.setting.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Current state with its source
+    pub fn status(&self) -> ReadOnlyStatus {
+        ReadOnlyStatus {
+            read_only: self.is_enabled(),
+            source: if self.env_override.is_some() {
+                ReadOnlySource::Env
+            } else {
+                ReadOnlySource::Setting
+            },
+            setting_enabled: self.setting.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Whether a request may proceed while read-only mode is on.
+    /// Reads (including the public booking lookup), auth endpoints and the
+    /// toggle itself stay available.
+    ///
+    /// # Arguments
+    /// * `method` - HTTP method
+    /// * `path` - Path relative to the API root, e.g. "/bookings"
+    pub fn allows_request(method: &Method, path: &str) -> bool {
+        matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS)
+            || path.starts_with("/auth/")
+            || path == "/public/bookings/lookup"
+            || path == "/admin/read-only"
+    }
+}
+
+/// Parse the READ_ONLY_MODE env value. Unset or empty means no override.
+pub fn parse_env_override(value: Option<&str>) -> Result<Option<bool>, String> {
+    match value.map(str::trim) {
+        None | Some("") => Ok(None),
+        Some(v) if v.eq_ignore_ascii_case("true") || v == "1" => Ok(Some(true)),
+        Some(v) if v.eq_ignore_ascii_case("false") || v == "0" => Ok(Some(false)),
+        Some(v) => Err(format!("READ_ONLY_MODE must be true or false, got '{}'", v)),
+    }
+}
+
+/// Maintenance service for loading and toggling read-only mode
+pub struct MaintenanceService {
+    pool: DbPool,
+}
+
+impl MaintenanceService {
+    /// Create a new MaintenanceService instance
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    /// Load the read-only system setting (missing row means off)
+    pub fn load_read_only_setting(&self) -> AppResult<bool> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(Settings::load(&mut conn)?.bool(READ_ONLY_SETTING_KEY))
+    }
+
+    /// Toggle the read-only system setting and audit-log the change
+    ///
+    /// # Arguments
+    /// * `enabled` - New value of the setting
+    /// * `admin_id` - Admin performing the toggle
+    /// * `reason` - Optional reason stored with the audit entry
+    pub fn set_read_only_setting(
+        &self,
+        enabled: bool,
+        admin_id: Uuid,
+        reason: Option<&str>,
+    ) -> AppResult<()> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let value = if enabled { "true" } else { "false" };
+        let action = if enabled {
+            "read_only_mode.enabled"
+        } else {
+            "read_only_mode.disabled"
+        };
+
+        conn.transaction::<_, AppError, _>(|conn| {
+            settings::save(conn, &[(READ_ONLY_SETTING_KEY, value.to_string())])?;
+
+            AuditService::record(
+                conn,
+                Some(admin_id),
+                action,
+                "system_setting",
+                Some(READ_ONLY_SETTING_KEY),
+                reason,
+            )?;
+
+            Ok(())
+        })
+    }
+}
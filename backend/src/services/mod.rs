@@ -1,18 +1,62 @@
+pub mod api_key_service;
+pub mod audit_service;
 pub mod auth_service;
+pub mod availability_service;
 pub mod booking_service;
+pub mod booking_email_service;
+pub mod email_verification_service;
 pub mod guest_service;
+pub mod hold_service;
+pub mod password_reset_service;
 pub mod payment_service;
+pub mod pricing_service;
 pub mod room_service;
+pub mod room_block_service;
+pub mod room_photo_service;
+pub mod room_type_service;
 pub mod inventory_service;
+pub mod login_event_service;
 pub mod storage_service;
 pub mod ai_service;
+pub mod no_show_service;
+pub mod maintenance_service;
+pub mod rate_limit_service;
+pub mod mailer;
+pub mod report_service;
+pub mod notification_service;
+pub mod reconciliation_service;
+pub mod chat_privacy_service;
+pub mod session_service;
+pub mod ticket_service;
+pub mod token_revocation_service;
 
+pub use api_key_service::ApiKeyService;
+pub use audit_service::AuditService;
 pub use auth_service::{
     AuthService, ChangePasswordRequest, CreateUserRequest, GuestAuthResponse, GuestLoginRequest,
-    GuestRegisterRequest, LoginRequest,
+    GuestRegisterRequest, LoginRequest, RefreshRequest, TokenIssuer, UpdateProfileRequest,
 };
-pub use booking_service::{BookingService, RoomFinancials};
-pub use guest_service::GuestService;
+pub use availability_service::{AvailabilityCalendarDay, AvailabilityService};
+pub use booking_email_service::BookingEmailService;
+pub use email_verification_service::EmailVerificationService;
+pub use booking_service::{BookingService, BookingTimelinePage, PublicBookingView, RoomFinancials};
+pub use guest_service::{GuestBookingStats, GuestService, InHouseGuest};
+pub use hold_service::HoldService;
+pub use password_reset_service::PasswordResetService;
 pub use payment_service::PaymentService;
+pub use pricing_service::PricingService;
 pub use room_service::RoomService;
+pub use room_block_service::RoomBlockService;
+pub use room_photo_service::RoomPhotoService;
+pub use room_type_service::RoomTypeService;
 pub use inventory_service::InventoryService;
+pub use login_event_service::{LoginEventService, LoginOrigin};
+pub use no_show_service::NoShowService;
+pub use maintenance_service::{MaintenanceService, ReadOnlyMode, ReadOnlyStatus};
+pub use report_service::ReportService;
+pub use notification_service::NotificationService;
+pub use reconciliation_service::ReconciliationService;
+pub use chat_privacy_service::ChatPrivacyService;
+pub use session_service::{SessionService, SessionTracker};
+pub use ticket_service::TicketService;
+pub use token_revocation_service::{RevocationCache, TokenRevocationService};
@@ -0,0 +1,271 @@
+use bigdecimal::{BigDecimal, RoundingMode, Zero};
+use chrono::Utc;
+use diesel::dsl::sum;
+use diesel::prelude::*;
+use diesel::result::QueryResult;
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::errors::{AppError, AppResult};
+use crate::models::{
+    Booking, BookingStatus, NewNoShowCharge, NewPayment, NoShowCharge, NoShowChargeStatus,
+    NoShowChargeWithBooking, Payment, PaymentType,
+};
+use crate::schema::{bookings, no_show_charges, payments};
+use crate::services::payment_service::VALID_PAYMENT_METHODS;
+
+/// Charge basis when the guest paid a deposit: the deposit is forfeited
+pub const BASIS_DEPOSIT: &str = "deposit";
+/// Charge basis without a deposit: one night at the booked rate
+pub const BASIS_ONE_NIGHT: &str = "one_night";
+
+/// No-show service for the no-show charge review queue
+pub struct NoShowService {
+    pool: DbPool,
+}
+
+impl NoShowService {
+    /// Create a new NoShowService instance
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    /// Calculate the no-show charge for a booking
+    ///
+    /// # Arguments
+    /// * `booking` - The booking marked as no-show
+    /// * `deposit_total` - Sum of deposit payments already collected for the booking
+    ///
+    /// # Returns
+    /// * `(amount, basis)` - The deposit when one exists, otherwise one night at the booked rate
+    pub fn calculate_charge(booking: &Booking, deposit_total: &BigDecimal) -> (BigDecimal, &'static str) {
+        if *deposit_total > BigDecimal::zero() {
+            return (deposit_total.clone(), BASIS_DEPOSIT);
+        }
+
+        let nights = (booking.check_out_date - booking.check_in_date).num_days().max(1);
+        let one_night = (&booking.price / BigDecimal::from(nights)).with_scale_round(2, RoundingMode::HalfUp);
+
+        (one_night, BASIS_ONE_NIGHT)
+    }
+
+    /// Raise pending charges for bookings that were just marked as no-show.
+    /// Bookings that already have a charge are skipped, so re-running is safe.
+    ///
+    /// # Returns
+    /// * Number of charges created
+    pub fn create_pending_charges(conn: &mut PgConnection, no_shows: &[Booking]) -> QueryResult<usize> {
+        let mut created = 0;
+
+        for booking in no_shows {
+            let deposit_total: Option<BigDecimal> = payments::table
+                .filter(payments::booking_id.eq(booking.id))
+                .filter(payments::payment_type.eq(PaymentType::Deposit))
+                .select(sum(payments::amount))
+                .first(conn)?;
+
+            let (amount, basis) =
+                Self::calculate_charge(booking, &deposit_total.unwrap_or_else(BigDecimal::zero));
+
+            created += diesel::insert_into(no_show_charges::table)
+                .values(&NewNoShowCharge {
+                    booking_id: booking.id,
+                    amount,
+                    basis,
+                })
+                .on_conflict(no_show_charges::booking_id)
+                .do_nothing()
+                .execute(conn)?;
+        }
+
+        Ok(created)
+    }
+
+    /// Void the pending charge of a booking whose guest arrived late
+    ///
+    /// # Returns
+    /// * Number of charges voided (0 or 1)
+    pub fn void_pending_charge(conn: &mut PgConnection, booking_id: Uuid) -> QueryResult<usize> {
+        diesel::update(
+            no_show_charges::table
+                .filter(no_show_charges::booking_id.eq(booking_id))
+                .filter(no_show_charges::status.eq(NoShowChargeStatus::Pending)),
+        )
+        .set((
+            no_show_charges::status.eq(NoShowChargeStatus::Voided),
+            no_show_charges::reason.eq(Some("Guest arrived late and was checked in")),
+            no_show_charges::reviewed_at.eq(Some(Utc::now())),
+        ))
+        .execute(conn)
+    }
+
+    /// List no-show charges with their bookings
+    ///
+    /// # Arguments
+    /// * `status` - Optional status filter
+    pub fn list_charges(
+        &self,
+        status: Option<NoShowChargeStatus>,
+    ) -> AppResult<Vec<NoShowChargeWithBooking>> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let mut query = no_show_charges::table
+            .inner_join(bookings::table)
+            .into_boxed();
+
+        if let Some(status) = status {
+            query = query.filter(no_show_charges::status.eq(status));
+        }
+
+        let rows: Vec<(NoShowCharge, Booking)> = query
+            .order(no_show_charges::created_at.asc())
+            .select((NoShowCharge::as_select(), Booking::as_select()))
+            .load(&mut conn)
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(charge, booking)| NoShowChargeWithBooking { charge, booking })
+            .collect())
+    }
+
+    /// Amount owed for a booking while it is a no-show: the confirmed charge,
+    /// or nothing once waived. `None` while the charge is unreviewed or the
+    /// guest has arrived, when the booked price applies.
+    pub fn amount_due_on(conn: &mut PgConnection, booking: &Booking) -> QueryResult<Option<BigDecimal>> {
+        if booking.status != BookingStatus::NoShow {
+            return Ok(None);
+        }
+
+        let charge: Option<NoShowCharge> = no_show_charges::table
+            .filter(no_show_charges::booking_id.eq(booking.id))
+            .first(conn)
+            .optional()?;
+
+        Ok(charge.and_then(|charge| match charge.status {
+            NoShowChargeStatus::Confirmed => Some(charge.amount),
+            NoShowChargeStatus::Waived => Some(BigDecimal::zero()),
+            NoShowChargeStatus::Pending | NoShowChargeStatus::Voided => None,
+        }))
+    }
+
+    /// Confirm a pending charge. One-night charges are recorded as a payment;
+    /// deposit forfeitures reuse the deposit already collected. The booked
+    /// price is kept for a late check-in; the payment summary owes the charge
+    /// instead (see `amount_due_on`).
+    ///
+    /// # Errors
+    /// * `NotFound` - Charge not found
+    /// * `InvalidStatusTransition` - Charge was already reviewed
+    /// * `ValidationError` - Unknown payment method
+    pub fn confirm_charge(
+        &self,
+        charge_id: Uuid,
+        admin_id: Uuid,
+        reason: Option<String>,
+        payment_method: Option<String>,
+    ) -> AppResult<NoShowCharge> {
+        let payment_method = payment_method.unwrap_or_else(|| "card".to_string());
+        if !VALID_PAYMENT_METHODS.contains(&payment_method.as_str()) {
+            return Err(AppError::ValidationError(format!(
+                "Invalid payment method. Must be one of: {}",
+                VALID_PAYMENT_METHODS.join(", ")
+            )));
+        }
+
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        conn.transaction::<_, AppError, _>(|conn| {
+            let charge = Self::load_pending_charge(conn, charge_id)?;
+
+            let payment_id = if charge.basis == BASIS_ONE_NIGHT && charge.amount > BigDecimal::zero() {
+                let payment: Payment = diesel::insert_into(payments::table)
+                    .values(&NewPayment {
+                        booking_id: charge.booking_id,
+                        amount: charge.amount.clone(),
+                        payment_type: PaymentType::Full,
+                        payment_method,
+                        notes: Some("No-show charge (one night)".to_string()),
+                        created_by_user_id: admin_id,
+                    })
+                    .get_result(conn)?;
+                Some(payment.id)
+            } else {
+                None
+            };
+
+            let updated = diesel::update(no_show_charges::table.find(charge_id))
+                .set((
+                    no_show_charges::status.eq(NoShowChargeStatus::Confirmed),
+                    no_show_charges::reason.eq(reason),
+                    no_show_charges::reviewed_by_user_id.eq(Some(admin_id)),
+                    no_show_charges::reviewed_at.eq(Some(Utc::now())),
+                    no_show_charges::payment_id.eq(payment_id),
+                ))
+                .get_result(conn)?;
+
+            Ok(updated)
+        })
+    }
+
+    /// Waive a pending charge. Nothing is owed while the booking stays a
+    /// no-show; a collected deposit then shows as a balance to refund.
+    ///
+    /// # Errors
+    /// * `ValidationError` - Reason is empty
+    /// * `NotFound` - Charge not found
+    /// * `InvalidStatusTransition` - Charge was already reviewed
+    pub fn waive_charge(&self, charge_id: Uuid, admin_id: Uuid, reason: &str) -> AppResult<NoShowCharge> {
+        let reason = reason.trim();
+        if reason.is_empty() {
+            return Err(AppError::ValidationError(
+                "A reason is required to waive a no-show charge".to_string(),
+            ));
+        }
+
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        conn.transaction::<_, AppError, _>(|conn| {
+            // Locks the row and refuses a charge that was already reviewed
+            Self::load_pending_charge(conn, charge_id)?;
+
+            let updated = diesel::update(no_show_charges::table.find(charge_id))
+                .set((
+                    no_show_charges::status.eq(NoShowChargeStatus::Waived),
+                    no_show_charges::reason.eq(Some(reason)),
+                    no_show_charges::reviewed_by_user_id.eq(Some(admin_id)),
+                    no_show_charges::reviewed_at.eq(Some(Utc::now())),
+                ))
+                .get_result(conn)?;
+
+            Ok(updated)
+        })
+    }
+
+    /// Load a charge for review, locking the row and requiring it to be pending
+    fn load_pending_charge(conn: &mut PgConnection, charge_id: Uuid) -> AppResult<NoShowCharge> {
+        let charge: NoShowCharge = no_show_charges::table
+            .find(charge_id)
+            .for_update()
+            .first(conn)
+            .optional()?
+            .ok_or_else(|| AppError::NotFound(format!("No-show charge '{}' not found", charge_id)))?;
+
+        if charge.status != NoShowChargeStatus::Pending {
+            return Err(AppError::InvalidStatusTransition(
+                "No-show charge has already been reviewed".to_string(),
+            ));
+        }
+
+        Ok(charge)
+    }
+}
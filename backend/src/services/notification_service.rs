@@ -0,0 +1,93 @@
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel::result::QueryResult;
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::errors::{AppError, AppResult};
+use crate::models::{AdminNotification, NewAdminNotification};
+use crate::schema::admin_notifications;
+
+/// Notifications returned per listing
+const MAX_NOTIFICATIONS: i64 = 100;
+
+/// Notification service for alerts raised to admins
+pub struct NotificationService {
+    pool: DbPool,
+}
+
+impl NotificationService {
+    /// Create a new NotificationService instance
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    /// Raise an admin notification on the caller's connection, so it commits
+    /// or rolls back together with the event it reports
+    ///
+    /// # Arguments
+    /// * `kind` - Short machine-readable kind, e.g. "cash_discrepancy"
+    /// * `message` - Human-readable text shown to admins
+    /// * `entity_type` / `entity_id` - The record the notification is about
+    pub fn notify_admins(
+        conn: &mut PgConnection,
+        kind: &str,
+        message: &str,
+        entity_type: Option<&str>,
+        entity_id: Option<&str>,
+    ) -> QueryResult<AdminNotification> {
+        diesel::insert_into(admin_notifications::table)
+            .values(&NewAdminNotification {
+                kind,
+                message,
+                entity_type,
+                entity_id,
+            })
+            .get_result(conn)
+    }
+
+    /// List the most recent notifications, newest first
+    pub fn list(&self, unread_only: bool) -> AppResult<Vec<AdminNotification>> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let mut query = admin_notifications::table.into_boxed();
+        if unread_only {
+            query = query.filter(admin_notifications::read_at.is_null());
+        }
+
+        query
+            .order(admin_notifications::created_at.desc())
+            .limit(MAX_NOTIFICATIONS)
+            .load(&mut conn)
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
+    /// Mark a notification read. Already-read notifications keep their
+    /// original reader and time.
+    pub fn mark_read(&self, id: Uuid, admin_id: Uuid) -> AppResult<AdminNotification> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let notification: AdminNotification = admin_notifications::table
+            .find(id)
+            .first(&mut conn)
+            .map_err(|_| AppError::NotFound(format!("Notification with ID '{}' not found", id)))?;
+
+        if notification.read_at.is_some() {
+            return Ok(notification);
+        }
+
+        diesel::update(admin_notifications::table.find(id))
+            .set((
+                admin_notifications::read_at.eq(Some(Utc::now())),
+                admin_notifications::read_by_user_id.eq(Some(admin_id)),
+            ))
+            .get_result(&mut conn)
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+}
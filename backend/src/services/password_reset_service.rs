@@ -0,0 +1,167 @@
+use chrono::{DateTime, Duration, Utc};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::errors::{AppError, AppResult};
+use crate::models::NewPasswordResetToken;
+use crate::schema::{password_reset_tokens, users};
+use crate::services::mailer::{Mailer, OutgoingEmail};
+use crate::services::AuthService;
+use crate::settings::{self, Settings};
+
+/// How long an emailed reset link stays valid
+pub const PASSWORD_RESET_TOKEN_LIFETIME: Duration = Duration::minutes(30);
+
+/// Refusal for a reset token that is unknown, used, replaced or expired
+pub const INVALID_RESET_TOKEN: &str = "This password reset link is invalid or has expired";
+
+/// Render the email with a guest's password reset link
+pub fn render_password_reset_email(to: &str, link: &str) -> OutgoingEmail {
+    OutgoingEmail {
+        to: to.to_string(),
+        subject: "Reset your Pupinn password".to_string(),
+        html: format!(
+            "<html><body><p>We received a request to reset the password of your Pupinn account.</p>\
+             <p><a href=\"{0}\">{0}</a></p><p>The link can be used once within {1} minutes. If you did not \
+             ask for it, you can ignore this email.</p></body></html>",
+            link,
+            PASSWORD_RESET_TOKEN_LIFETIME.num_minutes()
+        ),
+    }
+}
+
+/// Password reset service for guests who forgot their password
+pub struct PasswordResetService {
+    pool: DbPool,
+}
+
+impl PasswordResetService {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    fn conn(&self) -> AppResult<crate::db::DbConn> {
+        self.pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
+    /// Issue a reset token for the guest with `email`, replacing any earlier
+    /// one, and render the email carrying it
+    ///
+    /// # Returns
+    /// * `Ok(None)` - No active guest account uses the email
+    pub fn request_reset(&self, email: &str, now: DateTime<Utc>) -> AppResult<Option<OutgoingEmail>> {
+        let mut conn = self.conn()?;
+        let Some(user) = AuthService::guest_by_email(&mut conn, email)? else {
+            return Ok(None);
+        };
+        if user.deactivated_at.is_some() {
+            return Ok(None);
+        }
+        let Some(recipient) = user.email else {
+            return Ok(None);
+        };
+        let portal_url = Settings::load(&mut conn)?.text(settings::GUEST_PORTAL_URL);
+
+        let token = AuthService::random_token();
+        conn.transaction(|conn| {
+            Self::invalidate_on(conn, user.id)?;
+            diesel::insert_into(password_reset_tokens::table)
+                .values(&NewPasswordResetToken {
+                    user_id: user.id,
+                    token_hash: AuthService::hash_token(&token),
+                    expires_at: now + PASSWORD_RESET_TOKEN_LIFETIME,
+                })
+                .execute(conn)?;
+            Ok::<_, AppError>(())
+        })?;
+
+        let link = format!("{}/reset-password?token={}", portal_url.trim_end_matches('/'), token);
+        Ok(Some(render_password_reset_email(&recipient, &link)))
+    }
+
+    /// Set a new password with an emailed token, using the token up
+    pub fn reset_password(&self, token: &str, new_password: &str, now: DateTime<Utc>) -> AppResult<()> {
+        AuthService::validate_guest_password(new_password)?;
+        let password_hash = AuthService::hash_password(new_password)?;
+        let mut conn = self.conn()?;
+
+        conn.transaction(|conn| {
+            // Using the token and checking it happen in one statement, so a
+            // link clicked twice at once only works once
+            let user_id: Uuid = diesel::update(
+                password_reset_tokens::table
+                    .filter(password_reset_tokens::token_hash.eq(AuthService::hash_token(token)))
+                    .filter(password_reset_tokens::used_at.is_null())
+                    .filter(password_reset_tokens::expires_at.gt(now)),
+            )
+            .set(password_reset_tokens::used_at.eq(now))
+            .returning(password_reset_tokens::user_id)
+            .get_result(conn)
+            .optional()?
+            .ok_or_else(|| AppError::ValidationError(INVALID_RESET_TOKEN.to_string()))?;
+
+            let updated = diesel::update(
+                users::table
+                    .find(user_id)
+                    .filter(users::deactivated_at.is_null()),
+            )
+            .set(users::password_hash.eq(&password_hash))
+            .execute(conn)?;
+            if updated == 0 {
+                return Err(AppError::ValidationError(INVALID_RESET_TOKEN.to_string()));
+            }
+            Ok(())
+        })
+    }
+
+    /// Use up the outstanding reset tokens of a user, after a newer one is
+    /// issued or they log in
+    pub fn invalidate_on(conn: &mut PgConnection, user_id: Uuid) -> AppResult<()> {
+        diesel::update(
+            password_reset_tokens::table
+                .filter(password_reset_tokens::user_id.eq(user_id))
+                .filter(password_reset_tokens::used_at.is_null()),
+        )
+        .set(password_reset_tokens::used_at.eq(Utc::now()))
+        .execute(conn)?;
+        Ok(())
+    }
+
+    /// Mail transport configured in the system settings
+    pub fn mailer(&self) -> AppResult<Mailer> {
+        let mut conn = self.conn()?;
+        let settings = Settings::load(&mut conn)?;
+        Mailer::from_smtp_settings(&settings).map_err(AppError::InternalError)
+    }
+}
+
+/// Issue a reset token and email it on a background task. The request always
+/// answers the same way, so failures and unknown emails are only logged.
+pub fn spawn_password_reset_email(pool: DbPool, email: String) {
+    tokio::spawn(async move {
+        if let Err(e) = send_password_reset_email(pool, email).await {
+            tracing::warn!("Could not send password reset email: {}", e);
+        }
+    });
+}
+
+async fn send_password_reset_email(pool: DbPool, email: String) -> Result<(), String> {
+    let prepared = tokio::task::spawn_blocking(move || {
+        let service = PasswordResetService::new(pool);
+        let Some(outgoing) = service.request_reset(&email, Utc::now())? else {
+            return Ok::<_, AppError>(None);
+        };
+        Ok(Some((outgoing, service.mailer()?)))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())?;
+
+    match prepared {
+        Some((outgoing, mailer)) => mailer.send(&outgoing).await,
+        None => Ok(()),
+    }
+}
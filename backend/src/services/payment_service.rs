@@ -1,6 +1,6 @@
 use diesel::prelude::*;
 use diesel::dsl::{count, sum};
-use bigdecimal::BigDecimal;
+use bigdecimal::{BigDecimal, Zero};
 use uuid::Uuid;
 
 use crate::db::DbPool;
@@ -9,6 +9,14 @@ use crate::models::{
     Booking, Payment, PaymentSummary, PaymentType, NewPayment, UpdatePayment,
 };
 use crate::schema::{bookings, payments};
+use crate::services::{AuditService, NoShowService};
+use crate::utils::money;
+
+/// Accepted values for `payments.payment_method`
+pub const VALID_PAYMENT_METHODS: [&str; 4] = ["cash", "card", "bank_transfer", "other"];
+
+/// Audit action recorded when a payment is deleted
+pub const PAYMENT_VOIDED_ACTION: &str = "payment.voided";
 
 /// Payment service for managing payment transactions
 pub struct PaymentService {
@@ -43,31 +51,31 @@ impl PaymentService {
             .map_err(|_| AppError::NotFound(format!("Booking with ID '{}' not found", booking_id)))?;
 
         // Validate amount
-        if amount == BigDecimal::from(0) {
+        let amount = money::signed_vnd_field("amount", &amount)?;
+        if amount == BigDecimal::zero() {
             return Err(AppError::ValidationError(
                 "Payment amount cannot be zero".to_string(),
             ));
         }
 
         // Validate refund amount (must be negative)
-        if payment_type == PaymentType::Refund && amount > BigDecimal::from(0) {
+        if payment_type == PaymentType::Refund && amount > BigDecimal::zero() {
             return Err(AppError::ValidationError(
                 "Refund amount must be negative".to_string(),
             ));
         }
 
         // Validate non-refund amount (must be positive)
-        if payment_type != PaymentType::Refund && amount < BigDecimal::from(0) {
+        if payment_type != PaymentType::Refund && amount < BigDecimal::zero() {
             return Err(AppError::ValidationError(
                 "Payment amount must be positive (use refund type for negative amounts)".to_string(),
             ));
         }
 
         // Validate payment method
-        let valid_methods = vec!["cash", "card", "bank_transfer", "other"];
-        if !valid_methods.contains(&payment_method.as_str()) {
+        if !VALID_PAYMENT_METHODS.contains(&payment_method.as_str()) {
             return Err(AppError::ValidationError(
-                format!("Invalid payment method. Must be one of: {}", valid_methods.join(", "))
+                format!("Invalid payment method. Must be one of: {}", VALID_PAYMENT_METHODS.join(", "))
             ));
         }
 
@@ -125,7 +133,7 @@ impl PaymentService {
     pub fn update_payment(
         &self,
         payment_id: Uuid,
-        update: UpdatePayment,
+        mut update: UpdatePayment,
     ) -> AppResult<Payment> {
         let mut conn = self
             .pool
@@ -139,8 +147,13 @@ impl PaymentService {
             .map_err(|_| AppError::NotFound(format!("Payment with ID '{}' not found", payment_id)))?;
 
         // Validate amount if provided
+        update.amount = update
+            .amount
+            .as_ref()
+            .map(|a| money::signed_vnd_field("amount", a))
+            .transpose()?;
         if let Some(ref amount) = update.amount {
-            if *amount == BigDecimal::from(0) {
+            if *amount == BigDecimal::zero() {
                 return Err(AppError::ValidationError(
                     "Payment amount cannot be zero".to_string(),
                 ));
@@ -148,13 +161,13 @@ impl PaymentService {
 
             // Validate refund amount
             let payment_type = update.payment_type.unwrap_or(existing.payment_type);
-            if payment_type == PaymentType::Refund && *amount > BigDecimal::from(0) {
+            if payment_type == PaymentType::Refund && *amount > BigDecimal::zero() {
                 return Err(AppError::ValidationError(
                     "Refund amount must be negative".to_string(),
                 ));
             }
 
-            if payment_type != PaymentType::Refund && *amount < BigDecimal::from(0) {
+            if payment_type != PaymentType::Refund && *amount < BigDecimal::zero() {
                 return Err(AppError::ValidationError(
                     "Payment amount must be positive".to_string(),
                 ));
@@ -163,10 +176,9 @@ impl PaymentService {
 
         // Validate payment method if provided
         if let Some(ref method) = update.payment_method {
-            let valid_methods = vec!["cash", "card", "bank_transfer", "other"];
-            if !valid_methods.contains(&method.as_str()) {
+            if !VALID_PAYMENT_METHODS.contains(&method.as_str()) {
                 return Err(AppError::ValidationError(
-                    format!("Invalid payment method. Must be one of: {}", valid_methods.join(", "))
+                    format!("Invalid payment method. Must be one of: {}", VALID_PAYMENT_METHODS.join(", "))
                 ));
             }
         }
@@ -177,24 +189,41 @@ impl PaymentService {
             .map_err(|e| AppError::DatabaseError(e.to_string()))
     }
 
-    /// Delete a payment
-    pub fn delete_payment(&self, payment_id: Uuid) -> AppResult<()> {
+    /// Delete (void) a payment. The removed amount and method are kept in the
+    /// audit log so cash summaries can still show what was voided.
+    pub fn delete_payment(&self, payment_id: Uuid, voided_by_user_id: Uuid) -> AppResult<()> {
         let mut conn = self
             .pool
             .get()
             .map_err(|e| AppError::DatabaseError(e.to_string()))?;
 
         // Verify payment exists
-        payments::table
+        let payment = payments::table
             .find(payment_id)
             .first::<Payment>(&mut conn)
             .map_err(|_| AppError::NotFound(format!("Payment with ID '{}' not found", payment_id)))?;
 
-        diesel::delete(payments::table.find(payment_id))
-            .execute(&mut conn)
-            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
-
-        Ok(())
+        let details = serde_json::json!({
+            "booking_id": payment.booking_id,
+            "amount": payment.amount.to_string(),
+            "payment_type": payment.payment_type,
+            "payment_method": payment.payment_method,
+            "recorded_at": payment.created_at,
+        })
+        .to_string();
+
+        conn.transaction::<_, AppError, _>(|conn| {
+            diesel::delete(payments::table.find(payment_id)).execute(conn)?;
+            AuditService::record(
+                conn,
+                Some(voided_by_user_id),
+                PAYMENT_VOIDED_ACTION,
+                "payment",
+                Some(&payment_id.to_string()),
+                Some(&details),
+            )?;
+            Ok(())
+        })
     }
 
     /// Calculate payment summary for a booking
@@ -224,12 +253,17 @@ impl PaymentService {
             .first(&mut conn)
             .map_err(|e| AppError::DatabaseError(e.to_string()))?;
 
+        // A reviewed no-show owes its charge rather than the booked price
+        let total_price = NoShowService::amount_due_on(&mut conn, &booking)
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?
+            .unwrap_or(booking.price);
+
         let total_paid = total_paid.unwrap_or_else(|| BigDecimal::from(0));
-        let remaining_balance = &booking.price - &total_paid;
+        let remaining_balance = &total_price - &total_paid;
 
         Ok(PaymentSummary {
             booking_id,
-            total_price: booking.price,
+            total_price,
             total_paid,
             remaining_balance,
             payment_count,
@@ -0,0 +1,354 @@
+use bigdecimal::{BigDecimal, RoundingMode};
+use chrono::{Days, NaiveDate};
+use diesel::prelude::*;
+use diesel::PgExpressionMethods;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::errors::{AppError, AppResult};
+use crate::models::{NewPricingRule, PricingRule, Room};
+use crate::schema::{pricing_rules, room_types, rooms};
+use crate::services::RoomService;
+use crate::utils::money::field_error;
+
+/// Longest pricing rule name
+pub const MAX_RULE_NAME_LEN: usize = 100;
+/// Largest percentage a rule may add to a room's price
+pub const MAX_RULE_PERCENTAGE: i64 = 1000;
+
+/// Pricing rule sent by an admin, for creating or replacing a rule
+#[derive(Debug, Clone, Deserialize)]
+pub struct PricingRuleRequest {
+    pub name: String,
+    pub room_type_id: Option<Uuid>,
+    pub room_id: Option<Uuid>,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub price: Option<BigDecimal>,
+    pub percentage: Option<BigDecimal>,
+    #[serde(default)]
+    pub priority: i32,
+}
+
+/// Service for date-based room prices
+pub struct PricingService {
+    pool: DbPool,
+}
+
+impl PricingService {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    /// Check a rule request: one scope, an ordered date range and exactly one
+    /// of a fixed price or a percentage
+    pub fn validate_rule(request: &PricingRuleRequest) -> AppResult<NewPricingRule> {
+        let name = request.name.trim();
+        if name.is_empty() || name.chars().count() > MAX_RULE_NAME_LEN {
+            return Err(field_error(
+                "name",
+                format!("must be 1 to {} characters", MAX_RULE_NAME_LEN),
+            ));
+        }
+        if request.room_type_id.is_some() && request.room_id.is_some() {
+            return Err(AppError::ValidationError(
+                "A pricing rule covers a room type or a single room, not both".to_string(),
+            ));
+        }
+        if request.end_date < request.start_date {
+            return Err(field_error("end_date", "must not be before start_date".to_string()));
+        }
+
+        let (price, percentage) = match (&request.price, &request.percentage) {
+            (Some(price), None) => (Some(RoomService::validate_price(price)?), None),
+            (None, Some(percentage)) => (None, Some(Self::validate_percentage(percentage)?)),
+            _ => {
+                return Err(AppError::ValidationError(
+                    "A pricing rule needs either a price or a percentage".to_string(),
+                ))
+            }
+        };
+
+        Ok(NewPricingRule {
+            name: name.to_string(),
+            room_type_id: request.room_type_id,
+            room_id: request.room_id,
+            start_date: request.start_date,
+            end_date: request.end_date,
+            price,
+            percentage,
+            priority: request.priority,
+        })
+    }
+
+    fn validate_percentage(percentage: &BigDecimal) -> AppResult<BigDecimal> {
+        if percentage.with_scale(2) != *percentage {
+            return Err(field_error("percentage", "must have at most 2 decimal places".to_string()));
+        }
+        let (lowest, highest) = (BigDecimal::from(-100), BigDecimal::from(MAX_RULE_PERCENTAGE));
+        if *percentage <= lowest || *percentage > highest {
+            return Err(field_error(
+                "percentage",
+                format!("must be above -100 and at most {}", MAX_RULE_PERCENTAGE),
+            ));
+        }
+        Ok(percentage.with_scale(2))
+    }
+
+    /// Whether a rule covers this room on this night
+    pub fn rule_applies(rule: &PricingRule, room: &Room, night: NaiveDate) -> bool {
+        let in_scope = match (rule.room_id, rule.room_type_id) {
+            (Some(room_id), _) => room_id == room.id,
+            (None, Some(room_type_id)) => room_type_id == room.room_type_id,
+            (None, None) => true,
+        };
+        in_scope && rule.start_date <= night && night <= rule.end_date
+    }
+
+    /// The rule pricing a night: the highest priority among those covering
+    /// it, then the narrowest scope (room, then room type, then all rooms),
+    /// then the newest
+    pub fn resolve_rule<'a>(rules: &'a [PricingRule], room: &Room, night: NaiveDate) -> Option<&'a PricingRule> {
+        rules
+            .iter()
+            .filter(|rule| Self::rule_applies(rule, room, night))
+            .max_by_key(|rule| (rule.priority, Self::specificity(rule), rule.created_at))
+    }
+
+    fn specificity(rule: &PricingRule) -> u8 {
+        match (rule.room_id, rule.room_type_id) {
+            (Some(_), _) => 2,
+            (None, Some(_)) => 1,
+            (None, None) => 0,
+        }
+    }
+
+    /// Price of one night under `rules`, or the room's own price when none applies
+    pub fn nightly_price(rules: &[PricingRule], room: &Room, night: NaiveDate) -> BigDecimal {
+        match Self::resolve_rule(rules, room, night) {
+            Some(PricingRule { price: Some(price), .. }) => price.clone(),
+            Some(PricingRule {
+                percentage: Some(percentage),
+                ..
+            }) => (&room.price * (BigDecimal::from(100) + percentage) / BigDecimal::from(100))
+                .with_scale_round(0, RoundingMode::HalfUp),
+            _ => room.price.clone(),
+        }
+    }
+
+    /// Total for the nights `check_in..check_out` under `rules`. A stay that
+    /// ends on its arrival day is charged one night.
+    pub fn stay_price(rules: &[PricingRule], room: &Room, check_in: NaiveDate, check_out: NaiveDate) -> BigDecimal {
+        let last_night = Self::last_night(check_in, check_out);
+        check_in
+            .iter_days()
+            .take_while(|night| *night <= last_night)
+            .map(|night| Self::nightly_price(rules, room, night))
+            .sum()
+    }
+
+    fn last_night(check_in: NaiveDate, check_out: NaiveDate) -> NaiveDate {
+        check_out.checked_sub_days(Days::new(1)).unwrap_or(check_out).max(check_in)
+    }
+
+    /// Price of one night in a room
+    pub fn price_for_night(&self, room: &Room, night: NaiveDate) -> AppResult<BigDecimal> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let rules = Self::rules_for_room_on(&mut conn, room, night, night)?;
+        Ok(Self::nightly_price(&rules, room, night))
+    }
+
+    /// Total for a stay, each night priced on its own
+    pub fn price_for_stay(&self, room: &Room, check_in: NaiveDate, check_out: NaiveDate) -> AppResult<BigDecimal> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Self::price_for_stay_on(&mut conn, room, check_in, check_out)
+    }
+
+    /// [`Self::price_for_stay`] on the caller's connection
+    pub fn price_for_stay_on(
+        conn: &mut PgConnection,
+        room: &Room,
+        check_in: NaiveDate,
+        check_out: NaiveDate,
+    ) -> AppResult<BigDecimal> {
+        let rules = Self::rules_for_room_on(conn, room, check_in, Self::last_night(check_in, check_out))?;
+        Ok(Self::stay_price(&rules, room, check_in, check_out))
+    }
+
+    /// Rules that may cover the room on some night from `first` to `last`
+    fn rules_for_room_on(
+        conn: &mut PgConnection,
+        room: &Room,
+        first: NaiveDate,
+        last: NaiveDate,
+    ) -> AppResult<Vec<PricingRule>> {
+        pricing_rules::table
+            .filter(pricing_rules::start_date.le(last))
+            .filter(pricing_rules::end_date.ge(first))
+            .filter(
+                pricing_rules::room_id.eq(room.id).or(pricing_rules::room_id
+                    .is_null()
+                    .and(
+                        pricing_rules::room_type_id
+                            .eq(room.room_type_id)
+                            .or(pricing_rules::room_type_id.is_null()),
+                    )),
+            )
+            .load(conn)
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
+    /// Every rule, soonest first
+    pub fn list_rules(&self) -> AppResult<Vec<PricingRule>> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        pricing_rules::table
+            .order((pricing_rules::start_date.asc(), pricing_rules::priority.desc()))
+            .load(&mut conn)
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
+    /// One pricing rule
+    pub fn get_rule(&self, rule_id: Uuid) -> AppResult<PricingRule> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Self::find_rule(&mut conn, rule_id)
+    }
+
+    /// Add a rule
+    ///
+    /// # Errors
+    /// * `Conflict` - Another rule with the same scope and priority covers
+    ///   some of the same nights, so neither would clearly win
+    pub fn create_rule(&self, request: &PricingRuleRequest) -> AppResult<PricingRule> {
+        let rule = Self::validate_rule(request)?;
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        conn.transaction::<_, AppError, _>(|conn| {
+            Self::check_room(conn, rule.room_id)?;
+            Self::check_room_type(conn, rule.room_type_id)?;
+            Self::check_overlap(conn, &rule, None)?;
+            diesel::insert_into(pricing_rules::table)
+                .values(&rule)
+                .get_result(conn)
+                .map_err(|e| AppError::DatabaseError(e.to_string()))
+        })
+    }
+
+    /// Replace a rule with a new definition; same checks as [`Self::create_rule`]
+    pub fn update_rule(&self, rule_id: Uuid, request: &PricingRuleRequest) -> AppResult<PricingRule> {
+        let rule = Self::validate_rule(request)?;
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        conn.transaction::<_, AppError, _>(|conn| {
+            Self::find_rule(conn, rule_id)?;
+            Self::check_room(conn, rule.room_id)?;
+            Self::check_room_type(conn, rule.room_type_id)?;
+            Self::check_overlap(conn, &rule, Some(rule_id))?;
+            diesel::update(pricing_rules::table.find(rule_id))
+                .set(&rule)
+                .get_result(conn)
+                .map_err(|e| AppError::DatabaseError(e.to_string()))
+        })
+    }
+
+    /// Remove a rule; prices already charged to bookings stay as they are
+    pub fn delete_rule(&self, rule_id: Uuid) -> AppResult<()> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let deleted = diesel::delete(pricing_rules::table.find(rule_id))
+            .execute(&mut conn)
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        if deleted == 0 {
+            return Err(AppError::NotFound(format!("Pricing rule '{}' not found", rule_id)));
+        }
+        Ok(())
+    }
+
+    fn find_rule(conn: &mut PgConnection, rule_id: Uuid) -> AppResult<PricingRule> {
+        pricing_rules::table
+            .find(rule_id)
+            .first(conn)
+            .optional()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?
+            .ok_or_else(|| AppError::NotFound(format!("Pricing rule '{}' not found", rule_id)))
+    }
+
+    fn check_room(conn: &mut PgConnection, room_id: Option<Uuid>) -> AppResult<()> {
+        let Some(room_id) = room_id else { return Ok(()) };
+        rooms::table
+            .find(room_id)
+            .select(rooms::id)
+            .first::<Uuid>(conn)
+            .optional()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?
+            .map(|_| ())
+            .ok_or_else(|| AppError::NotFound(format!("Room with ID '{}' not found", room_id)))
+    }
+
+    fn check_room_type(conn: &mut PgConnection, room_type_id: Option<Uuid>) -> AppResult<()> {
+        let Some(room_type_id) = room_type_id else { return Ok(()) };
+        room_types::table
+            .find(room_type_id)
+            .select(room_types::id)
+            .first::<Uuid>(conn)
+            .optional()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?
+            .map(|_| ())
+            .ok_or_else(|| AppError::NotFound(format!("Room type '{}' not found", room_type_id)))
+    }
+
+    fn check_overlap(conn: &mut PgConnection, rule: &NewPricingRule, except: Option<Uuid>) -> AppResult<()> {
+        // Serialize rule edits so two overlapping rules cannot both pass
+        diesel::sql_query("LOCK TABLE pricing_rules IN SHARE ROW EXCLUSIVE MODE")
+            .execute(conn)
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let mut query = pricing_rules::table
+            .filter(pricing_rules::room_id.is_not_distinct_from(rule.room_id))
+            .filter(pricing_rules::room_type_id.is_not_distinct_from(rule.room_type_id))
+            .filter(pricing_rules::priority.eq(rule.priority))
+            .filter(pricing_rules::start_date.le(rule.end_date))
+            .filter(pricing_rules::end_date.ge(rule.start_date))
+            .into_boxed();
+        if let Some(except) = except {
+            query = query.filter(pricing_rules::id.ne(except));
+        }
+        let clash: Option<PricingRule> = query
+            .first(conn)
+            .optional()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        match clash {
+            Some(other) => Err(AppError::Conflict(format!(
+                "Pricing rule '{}' covers the same rooms on overlapping dates ({} to {}) with the same priority",
+                other.name, other.start_date, other.end_date
+            ))),
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,282 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Entries kept before stale ones are pruned
+const MAX_TRACKED_KEYS: usize = 10_000;
+
+/// In-memory fixed-window rate limiter keyed by an arbitrary string (e.g. client IP)
+#[derive(Debug)]
+pub struct FixedWindowLimiter {
+    limit: u32,
+    window: Duration,
+    hits: Mutex<HashMap<String, (Instant, u32)>>,
+}
+
+impl FixedWindowLimiter {
+    /// Allow `limit` hits per key in every `window`
+    pub fn new(limit: u32, window: Duration) -> Self {
+        Self {
+            limit,
+            window,
+            hits: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Count a hit for `key`
+    ///
+    /// # Returns
+    /// * `Err(retry_after)` - The key is over its limit for the current window
+    pub fn check(&self, key: &str, now: Instant) -> Result<(), Duration> {
+        let mut hits = self.hits.lock().unwrap();
+
+        if hits.len() >= MAX_TRACKED_KEYS {
+            hits.retain(|_, (start, _)| now.duration_since(*start) < self.window);
+        }
+
+        let entry = hits.entry(key.to_string()).or_insert((now, 0));
+        if now.duration_since(entry.0) >= self.window {
+            *entry = (now, 0);
+        }
+
+        if entry.1 >= self.limit {
+            return Err(self.window - now.duration_since(entry.0));
+        }
+
+        entry.1 += 1;
+        Ok(())
+    }
+}
+
+/// In-memory token bucket keyed like `FixedWindowLimiter`, for bursty
+/// traffic. A key may spend `capacity` hits at once and regains them
+/// evenly over `refill`; finding the bucket empty mutes the key for `mute`.
+#[derive(Debug)]
+pub struct TokenBucketLimiter {
+    capacity: u32,
+    refill: Duration,
+    mute: Duration,
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    counted_at: Instant,
+    muted_until: Option<Instant>,
+}
+
+impl TokenBucketLimiter {
+    pub fn new(capacity: u32, refill: Duration, mute: Duration) -> Self {
+        Self {
+            capacity,
+            refill,
+            mute,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Spend a token for `key`
+    ///
+    /// # Returns
+    /// * `Err(retry_after)` - The key is muted, or its bucket was empty and
+    ///   this hit muted it
+    pub fn check(&self, key: &str, now: Instant) -> Result<(), Duration> {
+        let mut buckets = self.buckets.lock().unwrap();
+        let capacity = self.capacity as f64;
+
+        if buckets.len() >= MAX_TRACKED_KEYS {
+            // A refilled, unmuted bucket is the same as a new one
+            buckets.retain(|_, bucket| {
+                bucket.muted_until.is_some_and(|until| now < until)
+                    || now.duration_since(bucket.counted_at) < self.refill
+            });
+        }
+
+        let bucket = buckets.entry(key.to_string()).or_insert(Bucket {
+            tokens: capacity,
+            counted_at: now,
+            muted_until: None,
+        });
+        if let Some(until) = bucket.muted_until {
+            if now < until {
+                return Err(until - now);
+            }
+            bucket.muted_until = None;
+        }
+
+        let regained = now.duration_since(bucket.counted_at).as_secs_f64() / self.refill.as_secs_f64() * capacity;
+        bucket.tokens = (bucket.tokens + regained).min(capacity);
+        bucket.counted_at = now;
+
+        if bucket.tokens < 1.0 {
+            bucket.muted_until = Some(now + self.mute);
+            return Err(self.mute);
+        }
+        bucket.tokens -= 1.0;
+        Ok(())
+    }
+}
+
+/// Exponential backoff after repeated failures for the same key
+/// (e.g. wrong guesses against one booking reference)
+#[derive(Debug)]
+pub struct FailureBackoff {
+    free_attempts: u32,
+    base: Duration,
+    max: Duration,
+    failures: Mutex<HashMap<String, (u32, Instant)>>,
+}
+
+impl FailureBackoff {
+    /// After `free_attempts` failures, each further failure locks the key for
+    /// `base` doubled per extra failure, capped at `max`
+    pub fn new(free_attempts: u32, base: Duration, max: Duration) -> Self {
+        Self {
+            free_attempts,
+            base,
+            max,
+            failures: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Lock duration after `failures` consecutive failures
+    pub fn lockout_for(&self, failures: u32) -> Duration {
+        if failures < self.free_attempts {
+            return Duration::ZERO;
+        }
+        let exponent = (failures - self.free_attempts).min(16);
+        self.base.saturating_mul(1 << exponent).min(self.max)
+    }
+
+    /// Whether `key` may be attempted now
+    ///
+    /// # Returns
+    /// * `Err(retry_after)` - The key is locked out
+    pub fn check(&self, key: &str, now: Instant) -> Result<(), Duration> {
+        let failures = self.failures.lock().unwrap();
+        match failures.get(key) {
+            Some((count, last)) => {
+                let unlock_at = *last + self.lockout_for(*count);
+                if now < unlock_at {
+                    Err(unlock_at - now)
+                } else {
+                    Ok(())
+                }
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Record a failed attempt for `key`
+    ///
+    /// # Returns
+    /// * Consecutive failures so far
+    pub fn record_failure(&self, key: &str, now: Instant) -> u32 {
+        let mut failures = self.failures.lock().unwrap();
+
+        if failures.len() >= MAX_TRACKED_KEYS {
+            failures.retain(|_, (_, last)| now.duration_since(*last) < self.max);
+        }
+
+        let entry = failures.entry(key.to_string()).or_insert((0, now));
+        entry.0 += 1;
+        entry.1 = now;
+        entry.0
+    }
+
+    /// Forget failures for `key` after a success
+    pub fn reset(&self, key: &str) {
+        self.failures.lock().unwrap().remove(key);
+    }
+}
+
+/// Consecutive failed logins before a username or IP is locked
+pub const DEFAULT_LOGIN_LOCKOUT_THRESHOLD: u32 = 5;
+/// How long a login lockout lasts, in minutes
+pub const DEFAULT_LOGIN_LOCKOUT_MINUTES: u64 = 15;
+
+/// When repeated login failures lock a key, and for how long
+#[derive(Debug, Clone, Copy)]
+pub struct LockoutPolicy {
+    pub threshold: u32,
+    pub window: Duration,
+}
+
+impl Default for LockoutPolicy {
+    fn default() -> Self {
+        Self {
+            threshold: DEFAULT_LOGIN_LOCKOUT_THRESHOLD,
+            window: Duration::from_secs(DEFAULT_LOGIN_LOCKOUT_MINUTES * 60),
+        }
+    }
+}
+
+/// Failed login tracking keyed by username or client IP. A trait so a
+/// shared store can replace the in-memory one when running several servers.
+pub trait LoginAttemptStore: Send + Sync + std::fmt::Debug {
+    /// Time left on the lock for `key`, if it is locked
+    fn locked_for(&self, key: &str, now: Instant) -> Option<Duration>;
+
+    /// Record a failed login for `key`
+    ///
+    /// # Returns
+    /// * `Some(lock)` - This failure reached the threshold and locked the key
+    fn record_failure(&self, key: &str, now: Instant) -> Option<Duration>;
+
+    /// Forget failures for `key` after a successful login
+    fn reset(&self, key: &str);
+}
+
+/// In-memory login attempt store for a single server
+#[derive(Debug, Default)]
+pub struct InMemoryLoginAttempts {
+    policy: LockoutPolicy,
+    /// Consecutive failures and the end of the current lock
+    attempts: Mutex<HashMap<String, (u32, Option<Instant>)>>,
+}
+
+impl InMemoryLoginAttempts {
+    pub fn new(policy: LockoutPolicy) -> Self {
+        Self {
+            policy,
+            attempts: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl LoginAttemptStore for InMemoryLoginAttempts {
+    fn locked_for(&self, key: &str, now: Instant) -> Option<Duration> {
+        let attempts = self.attempts.lock().unwrap();
+        attempts
+            .get(key)
+            .and_then(|(_, locked_until)| *locked_until)
+            .filter(|until| now < *until)
+            .map(|until| until - now)
+    }
+
+    fn record_failure(&self, key: &str, now: Instant) -> Option<Duration> {
+        let mut attempts = self.attempts.lock().unwrap();
+
+        if attempts.len() >= MAX_TRACKED_KEYS {
+            attempts.retain(|_, (_, locked_until)| locked_until.is_some_and(|until| now < until));
+        }
+
+        let entry = attempts.entry(key.to_string()).or_insert((0, None));
+        // An expired lock starts the count over
+        if entry.1.is_some_and(|until| now >= until) {
+            *entry = (0, None);
+        }
+
+        entry.0 += 1;
+        if entry.0 < self.policy.threshold {
+            return None;
+        }
+        *entry = (0, Some(now + self.policy.window));
+        Some(self.policy.window)
+    }
+
+    fn reset(&self, key: &str) {
+        self.attempts.lock().unwrap().remove(key);
+    }
+}
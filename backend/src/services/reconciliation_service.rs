@@ -0,0 +1,319 @@
+use bigdecimal::{BigDecimal, Zero};
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, Utc};
+use chrono_tz::Tz;
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::errors::{AppError, AppResult};
+use crate::models::{CashReconciliation, NewCashReconciliation, PaymentType, Shift};
+use crate::schema::{audit_logs, cash_reconciliations, payments};
+use crate::services::payment_service::{PAYMENT_VOIDED_ACTION, VALID_PAYMENT_METHODS};
+use crate::services::{NotificationService, ReportService};
+use crate::settings::{self, Settings};
+use crate::utils::clock;
+use crate::utils::money;
+
+/// Notification kind raised for a large cash discrepancy
+pub const CASH_DISCREPANCY_NOTIFICATION: &str = "cash_discrepancy";
+/// Longest discrepancy note accepted
+pub const MAX_NOTE_LEN: usize = 1000;
+/// Reconciliations returned per listing
+const MAX_RECONCILIATIONS: i64 = 200;
+
+/// Totals for one payment method within a period
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MethodTotals {
+    pub method: String,
+    pub payment_count: i64,
+    pub payments_total: BigDecimal,
+    pub refund_count: i64,
+    /// Sum of refunds, as a negative amount
+    pub refunds_total: BigDecimal,
+    /// Payments plus refunds
+    pub net_total: BigDecimal,
+}
+
+/// Payments of one method deleted within a period
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct VoidTotals {
+    pub method: String,
+    pub void_count: i64,
+    pub voided_total: BigDecimal,
+}
+
+/// A deleted payment, as recorded in the audit log
+#[derive(Debug, Clone, Deserialize)]
+pub struct VoidedPayment {
+    pub payment_method: String,
+    #[serde(deserialize_with = "deserialize_amount")]
+    pub amount: BigDecimal,
+}
+
+fn deserialize_amount<'de, D: serde::Deserializer<'de>>(d: D) -> Result<BigDecimal, D::Error> {
+    let raw = String::deserialize(d)?;
+    BigDecimal::from_str(&raw).map_err(serde::de::Error::custom)
+}
+
+/// Payments grouped by method for a business date or shift
+#[derive(Debug, Clone, Serialize)]
+pub struct PaymentMethodSummary {
+    pub business_date: NaiveDate,
+    pub shift: Option<Shift>,
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    pub methods: Vec<MethodTotals>,
+    pub voids: Vec<VoidTotals>,
+    /// Net cash the drawer should hold for the period
+    pub expected_cash: BigDecimal,
+}
+
+/// A saved reconciliation and whether it raised an admin notification
+#[derive(Debug, Clone, Serialize)]
+pub struct RecordedReconciliation {
+    #[serde(flatten)]
+    pub reconciliation: CashReconciliation,
+    pub admins_notified: bool,
+}
+
+/// UTC bounds of a hotel-local business date, or of one shift on it.
+/// The night shift runs past midnight into the next calendar day.
+pub fn shift_window(
+    date: NaiveDate,
+    shift: Option<Shift>,
+    tz: Tz,
+) -> (DateTime<Utc>, DateTime<Utc>) {
+    let (start_hour, hours) = match shift {
+        Some(shift) => (shift.start_hour(), shift.hours()),
+        None => (0, 24),
+    };
+    let local_start = date.and_time(NaiveTime::from_hms_opt(start_hour, 0, 0).expect("valid hour"));
+    let start = clock::local_to_utc(tz, local_start);
+    (start, start + Duration::hours(hours))
+}
+
+/// Group payments and voids by method. Every accepted method is listed, even
+/// with no activity, followed by any legacy method found in the data.
+pub fn summarize_payments(
+    payments: &[(String, PaymentType, BigDecimal)],
+    voids: &[VoidedPayment],
+) -> (Vec<MethodTotals>, Vec<VoidTotals>) {
+    let mut methods: Vec<String> = VALID_PAYMENT_METHODS.iter().map(|m| m.to_string()).collect();
+    for method in payments.iter().map(|p| &p.0).chain(voids.iter().map(|v| &v.payment_method)) {
+        if !methods.contains(method) {
+            methods.push(method.clone());
+        }
+    }
+
+    let totals = methods
+        .iter()
+        .map(|method| {
+            let mut t = MethodTotals {
+                method: method.clone(),
+                payment_count: 0,
+                payments_total: BigDecimal::zero(),
+                refund_count: 0,
+                refunds_total: BigDecimal::zero(),
+                net_total: BigDecimal::zero(),
+            };
+            for (_, payment_type, amount) in payments.iter().filter(|p| &p.0 == method) {
+                if *payment_type == PaymentType::Refund {
+                    t.refund_count += 1;
+                    t.refunds_total += amount;
+                } else {
+                    t.payment_count += 1;
+                    t.payments_total += amount;
+                }
+            }
+            t.net_total = &t.payments_total + &t.refunds_total;
+            t
+        })
+        .collect();
+
+    let void_totals = methods
+        .iter()
+        .filter_map(|method| {
+            let matching: Vec<&VoidedPayment> =
+                voids.iter().filter(|v| &v.payment_method == method).collect();
+            if matching.is_empty() {
+                return None;
+            }
+            Some(VoidTotals {
+                method: method.clone(),
+                void_count: matching.len() as i64,
+                voided_total: matching.iter().map(|v| &v.amount).sum(),
+            })
+        })
+        .collect();
+
+    (totals, void_totals)
+}
+
+/// Whether a discrepancy (either direction) is large enough to alert admins
+pub fn exceeds_threshold(discrepancy: &BigDecimal, threshold: &BigDecimal) -> bool {
+    discrepancy.abs() > *threshold
+}
+
+/// Reconciliation service for end-of-shift cash counts
+pub struct ReconciliationService {
+    pool: DbPool,
+}
+
+impl ReconciliationService {
+    /// Create a new ReconciliationService instance
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    fn conn(&self) -> AppResult<diesel::r2d2::PooledConnection<diesel::r2d2::ConnectionManager<PgConnection>>> {
+        self.pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
+    /// Payments received within a business date (or one shift of it), grouped by
+    /// method. `date` defaults to today in hotel time.
+    pub fn payment_method_summary(
+        &self,
+        date: Option<NaiveDate>,
+        shift: Option<Shift>,
+    ) -> AppResult<PaymentMethodSummary> {
+        let tz = ReportService::new(self.pool.clone()).hotel_time_zone()?;
+        let business_date = date.unwrap_or_else(|| clock::local_date(Utc::now(), tz));
+        let (period_start, period_end) = shift_window(business_date, shift, tz);
+
+        let mut conn = self.conn()?;
+        let rows: Vec<(String, PaymentType, BigDecimal)> = payments::table
+            .filter(payments::created_at.ge(period_start))
+            .filter(payments::created_at.lt(period_end))
+            .select((payments::payment_method, payments::payment_type, payments::amount))
+            .load(&mut conn)?;
+
+        let void_details: Vec<Option<String>> = audit_logs::table
+            .filter(audit_logs::action.eq(PAYMENT_VOIDED_ACTION))
+            .filter(audit_logs::created_at.ge(period_start))
+            .filter(audit_logs::created_at.lt(period_end))
+            .select(audit_logs::details)
+            .load(&mut conn)?;
+        let voids: Vec<VoidedPayment> = void_details
+            .iter()
+            .flatten()
+            .filter_map(|d| serde_json::from_str(d).ok())
+            .collect();
+
+        let (methods, voids) = summarize_payments(&rows, &voids);
+        let expected_cash = methods
+            .iter()
+            .find(|m| m.method == "cash")
+            .map(|m| m.net_total.clone())
+            .unwrap_or_else(BigDecimal::zero);
+
+        Ok(PaymentMethodSummary {
+            business_date,
+            shift,
+            period_start,
+            period_end,
+            methods,
+            voids,
+            expected_cash,
+        })
+    }
+
+    /// Record a cash count against the system's expected cash for the period.
+    /// Admins are notified when the difference exceeds the configured threshold.
+    ///
+    /// # Errors
+    /// * `FieldErrors` - Negative, fractional or out-of-range count
+    /// * `ValidationError` - Future date or note too long
+    pub fn record_reconciliation(
+        &self,
+        date: NaiveDate,
+        shift: Option<Shift>,
+        counted_cash: BigDecimal,
+        note: Option<&str>,
+        recorded_by_user_id: Uuid,
+    ) -> AppResult<RecordedReconciliation> {
+        let counted_cash = money::vnd_field("counted_cash", &counted_cash)?;
+        let note = note.map(str::trim).filter(|n| !n.is_empty());
+        if note.is_some_and(|n| n.chars().count() > MAX_NOTE_LEN) {
+            return Err(AppError::ValidationError(format!(
+                "Note must be at most {} characters",
+                MAX_NOTE_LEN
+            )));
+        }
+
+        let summary = self.payment_method_summary(Some(date), shift)?;
+        if summary.period_start > Utc::now() {
+            return Err(AppError::ValidationError(
+                "Cannot reconcile a period that has not started".to_string(),
+            ));
+        }
+        let discrepancy = &counted_cash - &summary.expected_cash;
+
+        let mut conn = self.conn()?;
+        conn.transaction::<_, AppError, _>(|conn| {
+            let reconciliation: CashReconciliation = diesel::insert_into(cash_reconciliations::table)
+                .values(&NewCashReconciliation {
+                    business_date: date,
+                    shift: shift.map(|s| s.as_str()),
+                    period_start: summary.period_start,
+                    period_end: summary.period_end,
+                    expected_cash: summary.expected_cash.clone(),
+                    counted_cash,
+                    discrepancy: discrepancy.clone(),
+                    note,
+                    recorded_by_user_id,
+                })
+                .get_result(conn)?;
+
+            let threshold = Settings::load(conn)?.decimal(settings::CASH_DISCREPANCY_THRESHOLD);
+            let admins_notified = exceeds_threshold(&discrepancy, &threshold);
+            if admins_notified {
+                let message = format!(
+                    "Cash count for {}{} is off by {} (expected {}, counted {}){}",
+                    date,
+                    shift.map(|s| format!(" ({} shift)", s)).unwrap_or_default(),
+                    money::format_vnd(&discrepancy),
+                    money::format_vnd(&reconciliation.expected_cash),
+                    money::format_vnd(&reconciliation.counted_cash),
+                    note.map(|n| format!(": {}", n)).unwrap_or_default(),
+                );
+                NotificationService::notify_admins(
+                    conn,
+                    CASH_DISCREPANCY_NOTIFICATION,
+                    &message,
+                    Some("cash_reconciliation"),
+                    Some(&reconciliation.id.to_string()),
+                )?;
+            }
+
+            Ok(RecordedReconciliation {
+                reconciliation,
+                admins_notified,
+            })
+        })
+    }
+
+    /// List reconciliations, newest first, optionally limited to a date range
+    pub fn list_reconciliations(
+        &self,
+        from_date: Option<NaiveDate>,
+        to_date: Option<NaiveDate>,
+    ) -> AppResult<Vec<CashReconciliation>> {
+        let mut conn = self.conn()?;
+        let mut query = cash_reconciliations::table.into_boxed();
+        if let Some(from) = from_date {
+            query = query.filter(cash_reconciliations::business_date.ge(from));
+        }
+        if let Some(to) = to_date {
+            query = query.filter(cash_reconciliations::business_date.le(to));
+        }
+
+        Ok(query
+            .order(cash_reconciliations::created_at.desc())
+            .limit(MAX_RECONCILIATIONS)
+            .load(&mut conn)?)
+    }
+}
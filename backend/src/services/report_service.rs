@@ -0,0 +1,404 @@
+use bigdecimal::{BigDecimal, Zero};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, Timelike, Utc};
+use chrono_tz::Tz;
+use diesel::dsl::{count_star, sum};
+use diesel::prelude::*;
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::errors::{AppError, AppResult};
+use crate::models::{
+    Booking, BookingStatus, NewReportDelivery, NewReportSubscription, PaymentType, ReportDelivery,
+    ReportDeliveryStatus, ReportSubscription, ReportType, UpdateReportSubscription,
+};
+use crate::schema::{bookings, payments, report_deliveries, report_subscriptions, rooms};
+use crate::services::mailer::OutgoingEmail;
+use crate::services::BookingService;
+use crate::settings::{self, Settings};
+use crate::utils::clock;
+use crate::utils::money::format_vnd;
+use crate::utils::validate_email;
+
+pub use crate::settings::parse_utc_offset;
+
+/// Local hour (hotel time) after which a finished period's report is sent
+pub const REPORT_SEND_HOUR: u32 = 7;
+/// Attempts before a delivery is marked failed
+pub const MAX_DELIVERY_ATTEMPTS: i32 = 5;
+/// Delay before the first retry; doubles per attempt
+pub const RETRY_BASE_MINUTES: i64 = 5;
+/// Longest delay between retries
+pub const RETRY_MAX_MINUTES: i64 = 6 * 60;
+/// Deliveries processed per scheduler run
+const DELIVERY_BATCH_SIZE: i64 = 50;
+
+/// Occupancy and revenue summary for one reporting period
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportSummary {
+    pub report_type: ReportType,
+    pub period_start: NaiveDate,
+    pub period_end: NaiveDate,
+    pub room_count: i64,
+    pub room_nights_available: i64,
+    pub room_nights_sold: i64,
+    pub occupancy_rate: f64,
+    /// Revenue of stays checked out in the period (same basis as the financial reports)
+    pub revenue: BigDecimal,
+    /// Payments received in the period, net of refunds
+    pub payments_collected: BigDecimal,
+    pub new_bookings: i64,
+}
+
+/// Latest finished period whose report is due at `local_now` (hotel time).
+/// A period becomes due at REPORT_SEND_HOUR on the day after it ends: daily
+/// reports cover yesterday, weekly reports the previous Monday–Sunday and
+/// monthly reports the previous calendar month.
+pub fn latest_due_period(report_type: ReportType, local_now: NaiveDateTime) -> (NaiveDate, NaiveDate) {
+    let today = local_now.date();
+    let effective = if local_now.hour() >= REPORT_SEND_HOUR {
+        today
+    } else {
+        today - Duration::days(1)
+    };
+
+    match report_type {
+        ReportType::Daily => {
+            let day = effective - Duration::days(1);
+            (day, day)
+        }
+        ReportType::Weekly => {
+            let this_monday = effective - Duration::days(effective.weekday().num_days_from_monday() as i64);
+            (this_monday - Duration::days(7), this_monday - Duration::days(1))
+        }
+        ReportType::Monthly => {
+            let first_of_month = effective.with_day(1).expect("day 1 always exists");
+            let period_end = first_of_month - Duration::days(1);
+            (period_end.with_day(1).expect("day 1 always exists"), period_end)
+        }
+    }
+}
+
+/// Delay before the next attempt after `attempts` failed sends
+pub fn retry_delay(attempts: i32) -> Duration {
+    let exponent = (attempts.max(1) - 1).min(16) as u32;
+    Duration::minutes((RETRY_BASE_MINUTES * 2_i64.pow(exponent)).min(RETRY_MAX_MINUTES))
+}
+
+/// Room-nights of `stays` that fall inside `start..=end`
+pub fn room_nights_within(stays: &[(NaiveDate, NaiveDate)], start: NaiveDate, end: NaiveDate) -> i64 {
+    let period_end = end + Duration::days(1);
+    stays
+        .iter()
+        .map(|(check_in, check_out)| {
+            let from = (*check_in).max(start);
+            let to = (*check_out).min(period_end);
+            (to - from).num_days().max(0)
+        })
+        .sum()
+}
+
+/// Render a summary as the report email
+pub fn render_report_email(to: &str, summary: &ReportSummary) -> OutgoingEmail {
+    let label = match summary.report_type {
+        ReportType::Daily => "Daily",
+        ReportType::Weekly => "Weekly",
+        ReportType::Monthly => "Monthly",
+    };
+    let period = if summary.period_start == summary.period_end {
+        summary.period_start.to_string()
+    } else {
+        format!("{} to {}", summary.period_start, summary.period_end)
+    };
+
+    let rows = [
+        ("Occupancy", format!("{:.1}%", summary.occupancy_rate * 100.0)),
+        (
+            "Room-nights sold",
+            format!("{} of {}", summary.room_nights_sold, summary.room_nights_available),
+        ),
+        ("Revenue", format_vnd(&summary.revenue)),
+        ("Payments collected", format_vnd(&summary.payments_collected)),
+        ("New bookings", summary.new_bookings.to_string()),
+    ];
+    let rows_html: String = rows
+        .iter()
+        .map(|(name, value)| format!("<tr><td>{}</td><td align=\"right\">{}</td></tr>", name, value))
+        .collect();
+
+    OutgoingEmail {
+        to: to.to_string(),
+        subject: format!("Pupinn {} report: {}", label.to_lowercase(), period),
+        html: format!(
+            "<html><body><h2>{} report</h2><p>{}</p><table cellpadding=\"4\">{}</table></body></html>",
+            label, period, rows_html
+        ),
+    }
+}
+
+/// Report service for scheduled summary emails
+pub struct ReportService {
+    pool: DbPool,
+}
+
+impl ReportService {
+    /// Create a new ReportService instance
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    fn conn(&self) -> AppResult<crate::db::DbConn> {
+        self.pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
+    /// List all report subscriptions
+    pub fn list_subscriptions(&self) -> AppResult<Vec<ReportSubscription>> {
+        let mut conn = self.conn()?;
+        Ok(report_subscriptions::table
+            .order(report_subscriptions::created_at.asc())
+            .load(&mut conn)?)
+    }
+
+    /// Create a report subscription
+    ///
+    /// # Errors
+    /// * `ValidationError` - Invalid recipient email
+    pub fn create_subscription(
+        &self,
+        recipient_email: &str,
+        report_type: ReportType,
+        enabled: bool,
+        admin_id: Uuid,
+    ) -> AppResult<ReportSubscription> {
+        validate_email(recipient_email)?;
+        let mut conn = self.conn()?;
+
+        Ok(diesel::insert_into(report_subscriptions::table)
+            .values(&NewReportSubscription {
+                recipient_email: recipient_email.trim().to_string(),
+                report_type,
+                enabled,
+                created_by_user_id: Some(admin_id),
+            })
+            .get_result(&mut conn)?)
+    }
+
+    /// Update a report subscription
+    ///
+    /// # Errors
+    /// * `ValidationError` - Invalid recipient email or nothing to update
+    /// * `NotFound` - Subscription not found
+    pub fn update_subscription(
+        &self,
+        id: Uuid,
+        mut changes: UpdateReportSubscription,
+    ) -> AppResult<ReportSubscription> {
+        if let Some(email) = &changes.recipient_email {
+            validate_email(email)?;
+            changes.recipient_email = Some(email.trim().to_string());
+        }
+        if changes.recipient_email.is_none() && changes.report_type.is_none() && changes.enabled.is_none() {
+            return Err(AppError::ValidationError("No changes provided".to_string()));
+        }
+
+        let mut conn = self.conn()?;
+        diesel::update(report_subscriptions::table.find(id))
+            .set(&changes)
+            .get_result(&mut conn)
+            .optional()?
+            .ok_or_else(|| AppError::NotFound(format!("Report subscription '{}' not found", id)))
+    }
+
+    /// Delete a report subscription and its delivery log
+    pub fn delete_subscription(&self, id: Uuid) -> AppResult<()> {
+        let mut conn = self.conn()?;
+        let deleted = diesel::delete(report_subscriptions::table.find(id)).execute(&mut conn)?;
+        if deleted == 0 {
+            return Err(AppError::NotFound(format!("Report subscription '{}' not found", id)));
+        }
+        Ok(())
+    }
+
+    /// Delivery log of a subscription, newest first
+    pub fn list_deliveries(&self, subscription_id: Uuid) -> AppResult<Vec<ReportDelivery>> {
+        let mut conn = self.conn()?;
+        Ok(report_deliveries::table
+            .filter(report_deliveries::subscription_id.eq(subscription_id))
+            .order(report_deliveries::period_start.desc())
+            .limit(100)
+            .load(&mut conn)?)
+    }
+
+    /// Deliveries that failed or are retrying after an error, newest first
+    pub fn troubled_deliveries(&self, limit: i64) -> AppResult<Vec<ReportDelivery>> {
+        let mut conn = self.conn()?;
+        Ok(report_deliveries::table
+            .filter(report_deliveries::status.ne(ReportDeliveryStatus::Sent))
+            .filter(report_deliveries::last_error.is_not_null())
+            .order(report_deliveries::updated_at.desc())
+            .limit(limit)
+            .load(&mut conn)?)
+    }
+
+    /// Hotel time zone from the system setting
+    pub fn hotel_time_zone(&self) -> AppResult<Tz> {
+        let mut conn = self.conn()?;
+        Ok(Settings::load(&mut conn)?.time_zone(settings::HOTEL_TIMEZONE))
+    }
+
+    /// Queue the latest due period for every enabled subscription. Periods that
+    /// already have a delivery row are left alone, so this is safe to re-run.
+    ///
+    /// # Returns
+    /// * Number of deliveries queued
+    pub fn enqueue_due_deliveries(&self, now: DateTime<Utc>) -> AppResult<usize> {
+        let local_now = now.with_timezone(&self.hotel_time_zone()?).naive_local();
+        let mut conn = self.conn()?;
+
+        let subscriptions: Vec<ReportSubscription> = report_subscriptions::table
+            .filter(report_subscriptions::enabled.eq(true))
+            .load(&mut conn)?;
+
+        let mut queued = 0;
+        for subscription in subscriptions {
+            let (period_start, period_end) = latest_due_period(subscription.report_type, local_now);
+            queued += diesel::insert_into(report_deliveries::table)
+                .values(&NewReportDelivery {
+                    subscription_id: subscription.id,
+                    period_start,
+                    period_end,
+                    next_attempt_at: Some(now),
+                })
+                .on_conflict((report_deliveries::subscription_id, report_deliveries::period_start))
+                .do_nothing()
+                .execute(&mut conn)?;
+        }
+
+        Ok(queued)
+    }
+
+    /// Pending deliveries whose next attempt is due, with their subscriptions
+    pub fn due_deliveries(&self, now: DateTime<Utc>) -> AppResult<Vec<(ReportDelivery, ReportSubscription)>> {
+        let mut conn = self.conn()?;
+        Ok(report_deliveries::table
+            .inner_join(report_subscriptions::table)
+            .filter(report_deliveries::status.eq(ReportDeliveryStatus::Pending))
+            .filter(report_deliveries::next_attempt_at.le(now))
+            .filter(report_subscriptions::enabled.eq(true))
+            .order(report_deliveries::next_attempt_at.asc())
+            .limit(DELIVERY_BATCH_SIZE)
+            .select((ReportDelivery::as_select(), ReportSubscription::as_select()))
+            .load(&mut conn)?)
+    }
+
+    /// Record the outcome of a send attempt, scheduling a retry with backoff
+    /// or giving up after MAX_DELIVERY_ATTEMPTS
+    pub fn record_attempt(
+        &self,
+        delivery: &ReportDelivery,
+        result: Result<(), String>,
+        now: DateTime<Utc>,
+    ) -> AppResult<()> {
+        let mut conn = self.conn()?;
+        let attempts = delivery.attempts + 1;
+
+        let (status, next_attempt_at, sent_at, last_error) = match result {
+            Ok(()) => (ReportDeliveryStatus::Sent, None, Some(now), None),
+            Err(e) if attempts >= MAX_DELIVERY_ATTEMPTS => (ReportDeliveryStatus::Failed, None, None, Some(e)),
+            Err(e) => (ReportDeliveryStatus::Pending, Some(now + retry_delay(attempts)), None, Some(e)),
+        };
+
+        diesel::update(report_deliveries::table.find(delivery.id))
+            .set((
+                report_deliveries::status.eq(status),
+                report_deliveries::attempts.eq(attempts),
+                report_deliveries::next_attempt_at.eq(next_attempt_at),
+                report_deliveries::sent_at.eq(sent_at),
+                report_deliveries::last_error.eq(last_error),
+            ))
+            .execute(&mut conn)?;
+
+        Ok(())
+    }
+
+    /// Build the occupancy and revenue summary for a period
+    pub fn build_summary(
+        &self,
+        report_type: ReportType,
+        period_start: NaiveDate,
+        period_end: NaiveDate,
+    ) -> AppResult<ReportSummary> {
+        let revenue = BookingService::new(self.pool.clone())
+            .get_revenue_time_series(None, Some(period_start), Some(period_end))?
+            .into_iter()
+            .fold(BigDecimal::zero(), |total, (_, amount)| total + amount);
+
+        let mut conn = self.conn()?;
+
+        let room_count: i64 = rooms::table.select(count_star()).first(&mut conn)?;
+
+        // Period boundaries are midnights in hotel time
+        let tz = self.hotel_time_zone()?;
+
+        let stays: Vec<(NaiveDate, NaiveDate)> = bookings::table
+            .filter(bookings::status.eq_any([
+                BookingStatus::CheckedIn,
+                BookingStatus::CheckedOut,
+                BookingStatus::Overstay,
+            ]))
+            .filter(bookings::check_in_date.le(period_end))
+            .filter(bookings::check_out_date.gt(period_start))
+            .select(Booking::as_select())
+            .load(&mut conn)?
+            .into_iter()
+            .map(|booking: Booking| (booking.check_in_date, booking.stay_end_date(tz)))
+            .collect();
+
+        let local_midnight =
+            |date: NaiveDate| clock::local_to_utc(tz, date.and_hms_opt(0, 0, 0).expect("midnight"));
+        let period_start_utc = local_midnight(period_start);
+        let period_end_utc = local_midnight(period_end + Duration::days(1));
+
+        let received: Option<BigDecimal> = payments::table
+            .filter(payments::payment_type.ne(PaymentType::Refund))
+            .filter(payments::created_at.ge(period_start_utc))
+            .filter(payments::created_at.lt(period_end_utc))
+            .select(sum(payments::amount))
+            .first(&mut conn)?;
+        let refunded: Option<BigDecimal> = payments::table
+            .filter(payments::payment_type.eq(PaymentType::Refund))
+            .filter(payments::created_at.ge(period_start_utc))
+            .filter(payments::created_at.lt(period_end_utc))
+            .select(sum(payments::amount))
+            .first(&mut conn)?;
+
+        let new_bookings: i64 = bookings::table
+            .filter(bookings::created_at.ge(period_start_utc))
+            .filter(bookings::created_at.lt(period_end_utc))
+            .select(count_star())
+            .first(&mut conn)?;
+
+        let days = (period_end - period_start).num_days() + 1;
+        let room_nights_available = room_count * days;
+        let room_nights_sold = room_nights_within(&stays, period_start, period_end);
+
+        Ok(ReportSummary {
+            report_type,
+            period_start,
+            period_end,
+            room_count,
+            room_nights_available,
+            room_nights_sold,
+            occupancy_rate: if room_nights_available > 0 {
+                room_nights_sold as f64 / room_nights_available as f64
+            } else {
+                0.0
+            },
+            revenue,
+            payments_collected: received.unwrap_or_else(BigDecimal::zero) - refunded.unwrap_or_else(BigDecimal::zero),
+            new_bookings,
+        })
+    }
+}
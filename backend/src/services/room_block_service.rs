@@ -0,0 +1,244 @@
+use chrono::NaiveDate;
+use diesel::prelude::*;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::errors::{AppError, AppResult};
+use crate::models::{BookingStatus, NewRoomBlock, Room, RoomBlock, RoomBlockChanges};
+use crate::schema::{bookings, room_blocks, rooms};
+use crate::services::BookingService;
+use crate::utils::money::field_error;
+
+/// Longest block reason; reasons are stored as VARCHAR(200)
+pub const MAX_BLOCK_REASON_LEN: usize = 200;
+
+/// Request to block a room, or to replace an existing block
+#[derive(Debug, Clone, Deserialize)]
+pub struct RoomBlockRequest {
+    pub start_date: NaiveDate,
+    /// Last night blocked, inclusive
+    pub end_date: NaiveDate,
+    pub reason: Option<String>,
+}
+
+/// Service for dates a room is kept off sale by the owner
+pub struct RoomBlockService {
+    pool: DbPool,
+}
+
+impl RoomBlockService {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    /// Check a block's dates and trim its reason; a blank reason is dropped
+    pub fn validate_block(request: &RoomBlockRequest) -> AppResult<RoomBlockChanges> {
+        if request.end_date < request.start_date {
+            return Err(field_error("end_date", "must not be before start_date".to_string()));
+        }
+        let reason = request.reason.as_deref().map(str::trim).filter(|r| !r.is_empty());
+        if reason.is_some_and(|r| r.chars().count() > MAX_BLOCK_REASON_LEN) {
+            return Err(field_error(
+                "reason",
+                format!("must be at most {} characters", MAX_BLOCK_REASON_LEN),
+            ));
+        }
+        Ok(RoomBlockChanges {
+            start_date: request.start_date,
+            end_date: request.end_date,
+            reason: reason.map(str::to_string),
+        })
+    }
+
+    /// Blocks of a room, earliest first
+    pub fn list_room_blocks(&self, room_id: Uuid) -> AppResult<Vec<RoomBlock>> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        rooms::table
+            .find(room_id)
+            .select(rooms::id)
+            .first::<Uuid>(&mut conn)
+            .optional()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?
+            .ok_or_else(|| AppError::NotFound(format!("Room with ID '{}' not found", room_id)))?;
+
+        room_blocks::table
+            .filter(room_blocks::room_id.eq(room_id))
+            .order((room_blocks::start_date.asc(), room_blocks::created_at.asc()))
+            .load(&mut conn)
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
+    /// One block of a room
+    pub fn get_block(&self, room_id: Uuid, block_id: Uuid) -> AppResult<RoomBlock> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Self::find_block(&mut conn, room_id, block_id)
+    }
+
+    /// Block a room for some nights. Other blocks may overlap, bookings may not.
+    ///
+    /// # Errors
+    /// * `Conflict` - A booking that is not cancelled covers one of the nights
+    pub fn create_block(
+        &self,
+        room_id: Uuid,
+        request: &RoomBlockRequest,
+        created_by: Option<Uuid>,
+    ) -> AppResult<RoomBlock> {
+        let block = Self::validate_block(request)?;
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        // The room lock orders the check against bookings being made
+        conn.transaction::<_, AppError, _>(|conn| {
+            let room = BookingService::lock_room(conn, room_id)?;
+            Self::check_no_bookings(conn, &room, &block)?;
+            diesel::insert_into(room_blocks::table)
+                .values(&NewRoomBlock {
+                    room_id,
+                    start_date: block.start_date,
+                    end_date: block.end_date,
+                    reason: block.reason,
+                    created_by,
+                })
+                .get_result(conn)
+                .map_err(|e| AppError::DatabaseError(e.to_string()))
+        })
+    }
+
+    /// Replace a block's dates and reason; same checks as [`Self::create_block`]
+    pub fn update_block(&self, room_id: Uuid, block_id: Uuid, request: &RoomBlockRequest) -> AppResult<RoomBlock> {
+        let block = Self::validate_block(request)?;
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        conn.transaction::<_, AppError, _>(|conn| {
+            let room = BookingService::lock_room(conn, room_id)?;
+            Self::find_block(conn, room_id, block_id)?;
+            Self::check_no_bookings(conn, &room, &block)?;
+            diesel::update(room_blocks::table.find(block_id))
+                .set(&block)
+                .get_result(conn)
+                .map_err(|e| AppError::DatabaseError(e.to_string()))
+        })
+    }
+
+    /// Lift a block
+    pub fn delete_block(&self, room_id: Uuid, block_id: Uuid) -> AppResult<()> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let deleted = diesel::delete(
+            room_blocks::table
+                .find(block_id)
+                .filter(room_blocks::room_id.eq(room_id)),
+        )
+        .execute(&mut conn)
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        if deleted == 0 {
+            return Err(AppError::NotFound(format!("Block '{}' not found for this room", block_id)));
+        }
+        Ok(())
+    }
+
+    /// Whether a block covers any night of a stay from `check_in_date` to
+    /// `check_out_date` (not included)
+    pub fn is_blocked_on(
+        conn: &mut PgConnection,
+        room_id: Uuid,
+        check_in_date: NaiveDate,
+        check_out_date: NaiveDate,
+    ) -> AppResult<bool> {
+        diesel::select(diesel::dsl::exists(
+            room_blocks::table
+                .filter(room_blocks::room_id.eq(room_id))
+                .filter(room_blocks::start_date.lt(check_out_date))
+                .filter(room_blocks::end_date.ge(check_in_date)),
+        ))
+        .get_result(conn)
+        .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
+    /// Refuse a stay in a room that is blocked for any of its nights
+    pub fn check_not_blocked_on(
+        conn: &mut PgConnection,
+        room: &Room,
+        check_in_date: NaiveDate,
+        check_out_date: NaiveDate,
+    ) -> AppResult<()> {
+        if Self::is_blocked_on(conn, room.id, check_in_date, check_out_date)? {
+            return Err(AppError::RoomUnavailable(format!(
+                "Room {} is blocked for the selected dates",
+                room.number
+            )));
+        }
+        Ok(())
+    }
+
+    /// Blocked nights of the given rooms touching any night from `start_date`
+    /// to `end_date` (inclusive) as (room_id, first night, last night)
+    pub fn load_blocks_on(
+        conn: &mut PgConnection,
+        room_ids: &[Uuid],
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> AppResult<Vec<(Uuid, NaiveDate, NaiveDate)>> {
+        room_blocks::table
+            .filter(room_blocks::room_id.eq_any(room_ids))
+            .filter(room_blocks::start_date.le(end_date))
+            .filter(room_blocks::end_date.ge(start_date))
+            .select((room_blocks::room_id, room_blocks::start_date, room_blocks::end_date))
+            .load(conn)
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
+    /// Refuse a block over a night booked by a stay that is not cancelled;
+    /// no-shows have given the room back, overstaying guests keep it
+    fn check_no_bookings(conn: &mut PgConnection, room: &Room, block: &RoomBlockChanges) -> AppResult<()> {
+        let booked: Vec<String> = bookings::table
+            .filter(bookings::room_id.eq(room.id))
+            .filter(bookings::status.ne_all([BookingStatus::Cancelled, BookingStatus::NoShow]))
+            .filter(bookings::check_in_date.le(block.end_date))
+            .filter(
+                bookings::check_out_date
+                    .gt(block.start_date)
+                    .or(bookings::status.eq(BookingStatus::Overstay)),
+            )
+            .order(bookings::check_in_date.asc())
+            .select(bookings::reference)
+            .load(conn)
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        if !booked.is_empty() {
+            return Err(AppError::Conflict(format!(
+                "Room {} is booked on these dates: {}",
+                room.number,
+                booked.join(", ")
+            )));
+        }
+        Ok(())
+    }
+
+    fn find_block(conn: &mut PgConnection, room_id: Uuid, block_id: Uuid) -> AppResult<RoomBlock> {
+        room_blocks::table
+            .find(block_id)
+            .filter(room_blocks::room_id.eq(room_id))
+            .first(conn)
+            .optional()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?
+            .ok_or_else(|| AppError::NotFound(format!("Block '{}' not found for this room", block_id)))
+    }
+}
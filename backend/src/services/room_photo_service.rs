@@ -0,0 +1,179 @@
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::errors::{AppError, AppResult};
+use crate::models::{NewRoomPhoto, RoomPhoto};
+use crate::schema::{room_photos, rooms};
+use crate::services::room_service::MAX_PHOTOS;
+use crate::services::storage_service;
+use crate::services::BookingService;
+
+/// Bucket holding room photos; it must allow anonymous reads
+pub const ROOM_PHOTOS_BUCKET: &str = "room-photos";
+/// Largest room photo accepted
+pub const MAX_ROOM_PHOTO_BYTES: usize = 5 * 1024 * 1024;
+/// Longest photo caption
+pub const MAX_CAPTION_LEN: usize = 200;
+
+/// Service for the photos of a room. Objects are uploaded and removed by the
+/// caller; this keeps the rows and the limits.
+pub struct RoomPhotoService {
+    pool: DbPool,
+}
+
+impl RoomPhotoService {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    /// Check an uploaded photo's size and type. The type comes from the
+    /// file's magic bytes, never its name. Returns the file extension to
+    /// store it under.
+    pub fn validate_photo(data: &[u8]) -> AppResult<&'static str> {
+        if data.len() > MAX_ROOM_PHOTO_BYTES {
+            return Err(AppError::PayloadTooLarge(format!(
+                "Room photos must be at most {} MB",
+                MAX_ROOM_PHOTO_BYTES / (1024 * 1024)
+            )));
+        }
+        match storage_service::image_content_type(data) {
+            Some("image/jpeg") => Ok("jpg"),
+            Some("image/png") => Ok("png"),
+            Some("image/webp") => Ok("webp"),
+            _ => Err(AppError::ValidationError(
+                "Room photos must be JPEG, PNG or WebP images".to_string(),
+            )),
+        }
+    }
+
+    /// Trim a caption; blank captions are dropped
+    pub fn validate_caption(caption: Option<&str>) -> AppResult<Option<String>> {
+        let caption = caption.map(str::trim).filter(|c| !c.is_empty());
+        if caption.is_some_and(|c| c.chars().count() > MAX_CAPTION_LEN) {
+            return Err(AppError::ValidationError(format!(
+                "Caption must be at most {} characters",
+                MAX_CAPTION_LEN
+            )));
+        }
+        Ok(caption.map(str::to_string))
+    }
+
+    /// Refuse another photo for a full room before anything is uploaded
+    pub fn ensure_room_has_space(&self, room_id: Uuid) -> AppResult<()> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Self::find_room(&mut conn, room_id)?;
+        Self::check_space(&mut conn, room_id)
+    }
+
+    /// Record an uploaded photo after the room's other photos
+    pub fn add_photo(&self, room_id: Uuid, object_key: &str, caption: Option<String>) -> AppResult<RoomPhoto> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        // The room lock keeps concurrent uploads from passing the limit
+        conn.transaction::<_, AppError, _>(|conn| {
+            BookingService::lock_room(conn, room_id)?;
+            Self::check_space(conn, room_id)?;
+
+            let last: Option<i32> = room_photos::table
+                .filter(room_photos::room_id.eq(room_id))
+                .select(diesel::dsl::max(room_photos::sort_order))
+                .first(conn)
+                .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+            diesel::insert_into(room_photos::table)
+                .values(&NewRoomPhoto {
+                    room_id,
+                    object_key: object_key.to_string(),
+                    caption,
+                    sort_order: last.map_or(0, |n| n + 1),
+                })
+                .get_result(conn)
+                .map_err(|e| AppError::DatabaseError(e.to_string()))
+        })
+    }
+
+    /// Photos of a room in display order
+    pub fn list_photos(&self, room_id: Uuid) -> AppResult<Vec<RoomPhoto>> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Self::find_room(&mut conn, room_id)?;
+        room_photos::table
+            .filter(room_photos::room_id.eq(room_id))
+            .order((room_photos::sort_order.asc(), room_photos::created_at.asc()))
+            .load(&mut conn)
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
+    /// One photo of a room
+    pub fn get_photo(&self, room_id: Uuid, photo_id: Uuid) -> AppResult<RoomPhoto> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        room_photos::table
+            .find(photo_id)
+            .filter(room_photos::room_id.eq(room_id))
+            .first(&mut conn)
+            .optional()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?
+            .ok_or_else(|| AppError::NotFound(format!("Photo '{}' not found for this room", photo_id)))
+    }
+
+    /// Remove a photo's row once its object is gone
+    pub fn delete_photo(&self, room_id: Uuid, photo_id: Uuid) -> AppResult<()> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let deleted = diesel::delete(
+            room_photos::table
+                .find(photo_id)
+                .filter(room_photos::room_id.eq(room_id)),
+        )
+        .execute(&mut conn)
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        if deleted == 0 {
+            return Err(AppError::NotFound(format!("Photo '{}' not found for this room", photo_id)));
+        }
+        Ok(())
+    }
+
+    fn find_room(conn: &mut PgConnection, room_id: Uuid) -> AppResult<()> {
+        rooms::table
+            .find(room_id)
+            .select(rooms::id)
+            .first::<Uuid>(conn)
+            .optional()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?
+            .map(|_| ())
+            .ok_or_else(|| AppError::NotFound(format!("Room with ID '{}' not found", room_id)))
+    }
+
+    fn check_space(conn: &mut PgConnection, room_id: Uuid) -> AppResult<()> {
+        let count: i64 = room_photos::table
+            .filter(room_photos::room_id.eq(room_id))
+            .count()
+            .get_result(conn)
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        if count >= MAX_PHOTOS as i64 {
+            return Err(AppError::Conflict(format!(
+                "A room can have at most {} photos",
+                MAX_PHOTOS
+            )));
+        }
+        Ok(())
+    }
+}
@@ -1,12 +1,220 @@
 use diesel::prelude::*;
-use bigdecimal::BigDecimal;
-use std::str::FromStr;
+use bigdecimal::{BigDecimal, Zero};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use uuid::Uuid;
 
 use crate::db::DbPool;
 use crate::errors::{AppError, AppResult};
-use crate::models::{NewRoom, Room, RoomStatus, RoomType, UpdateRoom};
-use crate::schema::rooms;
+use crate::models::{
+    BookingStatus, NewMaintenanceTicket, NewRoom, NewRoomPriceChange, NewRoomStatusEvent, Room,
+    RoomDetailsUpdate, RoomPriceChange, RoomStatus, RoomStatusEvent, RoomType, RoomTypeConfig, TicketSeverity, UpdateRoom,
+};
+use crate::schema::{bookings, room_price_history, room_status_events, rooms, users};
+use crate::services::{RoomTypeService, TicketService};
+use crate::settings::{self, Settings};
+use crate::utils::clock;
+use crate::utils::money::{self, field_error};
+use crate::utils::validate_search_query;
+
+/// Longest room description accepted
+pub const MAX_DESCRIPTION_LEN: usize = 2000;
+/// Most amenities a room can list
+pub const MAX_AMENITIES: usize = 30;
+/// Longest single amenity label
+pub const MAX_AMENITY_LEN: usize = 60;
+/// Most photos a room can have
+pub const MAX_PHOTOS: usize = 10;
+/// Highest nightly room price; prices are stored as DECIMAL(12, 0)
+pub const MAX_ROOM_PRICE: i64 = 999_999_999_999;
+/// Longest room number; numbers are stored as VARCHAR(10)
+pub const MAX_ROOM_NUMBER_LEN: usize = 10;
+/// Most rooms one bulk request may create
+pub const MAX_BULK_ROOMS: usize = 200;
+/// Lowest floor a room may be on; basements are negative
+pub const MIN_FLOOR: i32 = -10;
+/// Highest floor a room may be on
+pub const MAX_FLOOR: i32 = 200;
+/// Longest building name; names are stored as VARCHAR(50)
+pub const MAX_BUILDING_LEN: usize = 50;
+/// Most rooms a number search suggests
+pub const ROOM_SEARCH_LIMIT: i64 = 20;
+
+/// Where a new room is. Without a floor, rooms numbered floor then room
+/// (305, 1204) go on that floor and others on floor 1; without a building
+/// they are in the main building.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct RoomLocation {
+    pub floor: Option<i32>,
+    pub building: Option<String>,
+}
+
+/// Filters for listing rooms; None matches every room
+#[derive(Debug, Clone, Default)]
+pub struct RoomFilter {
+    pub status: Option<RoomStatus>,
+    pub room_type: Option<RoomType>,
+    pub floor: Option<i32>,
+    /// An empty name matches rooms in the main building
+    pub building: Option<String>,
+    pub include_decommissioned: bool,
+}
+
+/// Rooms on one floor of a building, by number
+#[derive(Debug, Clone, Serialize)]
+pub struct FloorRooms {
+    pub floor: i32,
+    pub rooms: Vec<Room>,
+}
+
+/// Floors of one building, lowest first; `building` is None for the main
+/// building
+#[derive(Debug, Clone, Serialize)]
+pub struct BuildingRooms {
+    pub building: Option<String>,
+    pub floors: Vec<FloorRooms>,
+}
+
+/// A room on the cleaners' list
+#[derive(Debug, Clone, Serialize)]
+pub struct CleanerRoom {
+    #[serde(flatten)]
+    pub room: Room,
+    /// When the room entered its current status
+    pub dirty_since: DateTime<Utc>,
+    /// A guest checks into the room today
+    pub priority: bool,
+}
+
+/// One room of a bulk creation request
+#[derive(Debug, Clone, Deserialize)]
+pub struct BulkRoomSpec {
+    pub number: String,
+    pub room_type: RoomType,
+    /// Nightly price in VND; the room type's default when absent
+    pub price: Option<BigDecimal>,
+    #[serde(flatten)]
+    pub location: RoomLocation,
+}
+
+/// Rooms to create at once: an explicit list, or rooms `from`..=`to` on a
+/// floor, numbered floor then two-digit room (floor 2, 1 to 20 is 201-220)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum BulkRoomRequest {
+    Rooms(Vec<BulkRoomSpec>),
+    Range {
+        floor: u32,
+        from: u32,
+        to: u32,
+        room_type: RoomType,
+        price: Option<BigDecimal>,
+        building: Option<String>,
+    },
+}
+
+/// Room number left out of a bulk creation and why
+#[derive(Debug, Clone, Serialize)]
+pub struct SkippedRoom {
+    pub number: String,
+    pub reason: String,
+}
+
+/// Outcome of a bulk room creation
+#[derive(Debug, Serialize)]
+pub struct BulkRoomsResult {
+    pub created: Vec<Room>,
+    pub skipped: Vec<SkippedRoom>,
+}
+
+/// Changes to a room; fields left as None stay as they are
+#[derive(Debug, Clone, Default)]
+pub struct RoomEdit {
+    pub room_type: Option<RoomType>,
+    pub status: Option<RoomStatus>,
+    /// Some(None) unassigns the cleaner
+    pub assigned_cleaner_id: Option<Option<Uuid>>,
+    pub details: RoomDetailsUpdate,
+    /// New nightly price in VND; recorded in the price history
+    pub price: Option<BigDecimal>,
+    /// Opens a maintenance ticket when the room is put under maintenance
+    /// without one
+    pub maintenance_reason: Option<String>,
+    pub floor: Option<i32>,
+    /// An empty name moves the room to the main building
+    pub building: Option<String>,
+    /// `updated_at` of the room the edit was made against; the edit is
+    /// refused if the room has changed since
+    pub expected_updated_at: Option<DateTime<Utc>>,
+}
+
+/// Status history entries per page unless the caller asks otherwise
+pub const DEFAULT_STATUS_HISTORY_PAGE_SIZE: u64 = 20;
+/// Most status history entries one page may hold
+pub const MAX_STATUS_HISTORY_PAGE_SIZE: u64 = 100;
+
+/// Room status change with the username of whoever made it
+#[derive(Debug, Clone, Serialize)]
+pub struct RoomStatusHistoryEntry {
+    #[serde(flatten)]
+    pub event: RoomStatusEvent,
+    /// None for system transitions
+    pub actor_username: Option<String>,
+}
+
+/// Page of a room's status history, newest change first
+#[derive(Debug, Clone, Serialize)]
+pub struct RoomStatusHistoryPage {
+    pub room_id: Uuid,
+    pub events: Vec<RoomStatusHistoryEntry>,
+    pub total: u64,
+    pub page: u64,
+    pub per_page: u64,
+}
+
+/// Booking shown alongside a room: who and when
+#[derive(Debug, Clone, PartialEq, Eq, Queryable, Selectable, Serialize)]
+#[diesel(table_name = bookings)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct RoomOverviewBooking {
+    pub id: Uuid,
+    pub reference: String,
+    pub guest_name: String,
+    pub check_in_date: NaiveDate,
+    pub check_out_date: NaiveDate,
+    pub status: BookingStatus,
+}
+
+/// A room suggested by a number search, with the guest staying in it
+#[derive(Debug, Clone, Queryable, Serialize)]
+pub struct RoomSearchResult {
+    pub id: Uuid,
+    pub number: String,
+    pub room_type: RoomType,
+    pub status: RoomStatus,
+    /// Guest of the checked-in or overstaying booking
+    pub guest_name: Option<String>,
+}
+
+/// A room with the guest staying in it and the next arrival
+#[derive(Debug, Clone, Serialize)]
+pub struct RoomOverview {
+    #[serde(flatten)]
+    pub room: Room,
+    pub current_booking: Option<RoomOverviewBooking>,
+    pub next_booking: Option<RoomOverviewBooking>,
+}
+
+/// Room counts for the dashboard tiles; decommissioned rooms are left out
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RoomStatusSummary {
+    pub total: i64,
+    /// Every status, zero when no room has it
+    pub by_status: BTreeMap<&'static str, i64>,
+    /// Room types with at least one room
+    pub by_room_type: BTreeMap<RoomType, i64>,
+}
 
 /// Room service for managing hotel rooms
 pub struct RoomService {
@@ -19,8 +227,14 @@ impl RoomService {
         Self { pool }
     }
 
-    /// Create a new room
+    /// Create a new room where its number puts it
     pub fn create_room(&self, number: &str, room_type: RoomType) -> AppResult<Room> {
+        self.create_room_at(number, room_type, &RoomLocation::default())
+    }
+
+    /// Create a new room on a given floor and building
+    pub fn create_room_at(&self, number: &str, room_type: RoomType, location: &RoomLocation) -> AppResult<Room> {
+        let (floor, building) = Self::resolve_location(number, location)?;
         let mut conn = self
             .pool
             .get()
@@ -40,22 +254,211 @@ impl RoomService {
             )));
         }
 
-        // Set default price based on room type
-        // Default prices in VND (no fractional VND amounts)
-        let price = match room_type {
-            RoomType::Single => BigDecimal::from_str("1000000").unwrap(),
-            RoomType::Double => BigDecimal::from_str("1500000").unwrap(),
-            RoomType::Suite => BigDecimal::from_str("2500000").unwrap(),
+        let room_type = RoomTypeService::find_active_on(&mut conn, &room_type)?;
+        let new_room = NewRoom {
+            number,
+            room_type: room_type.code,
+            room_type_id: room_type.id,
+            price: room_type.default_price,
+            floor,
+            building,
         };
 
-        let new_room = NewRoom { number, room_type, price };
-
         diesel::insert_into(rooms::table)
             .values(&new_room)
             .get_result(&mut conn)
             .map_err(|e| AppError::DatabaseError(e.to_string()))
     }
 
+    /// Expand a bulk request into the rooms it names
+    pub fn expand_bulk_request(request: BulkRoomRequest) -> AppResult<Vec<BulkRoomSpec>> {
+        let specs = match request {
+            BulkRoomRequest::Rooms(specs) => specs,
+            BulkRoomRequest::Range {
+                floor,
+                from,
+                to,
+                room_type,
+                price,
+                building,
+            } => {
+                if from == 0 || from > to || to > 99 {
+                    return Err(AppError::ValidationError(
+                        "Room range must run from 1 up to at most 99, with from <= to".to_string(),
+                    ));
+                }
+                let floor = i32::try_from(floor).unwrap_or(i32::MAX);
+                Self::validate_floor(floor)?;
+                (from..=to)
+                    .map(|n| BulkRoomSpec {
+                        number: format!("{}{:02}", floor, n),
+                        room_type: room_type.clone(),
+                        price: price.clone(),
+                        location: RoomLocation {
+                            floor: Some(floor),
+                            building: building.clone(),
+                        },
+                    })
+                    .collect()
+            }
+        };
+
+        if specs.is_empty() {
+            return Err(AppError::ValidationError("No rooms to create".to_string()));
+        }
+        if specs.len() > MAX_BULK_ROOMS {
+            return Err(AppError::ValidationError(format!(
+                "At most {} rooms can be created at once",
+                MAX_BULK_ROOMS
+            )));
+        }
+        Ok(specs)
+    }
+
+    /// Create many rooms at once. Every number is checked first, against the
+    /// rest of the request and the existing rooms; rooms that fail a check
+    /// are skipped with the reason and the rest are inserted in a single
+    /// transaction.
+    pub fn create_rooms_bulk(&self, request: BulkRoomRequest) -> AppResult<BulkRoomsResult> {
+        let specs = Self::expand_bulk_request(request)?;
+
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let numbers: Vec<String> = specs.iter().map(|s| s.number.trim().to_string()).collect();
+        let existing: HashSet<String> = rooms::table
+            .filter(rooms::number.eq_any(&numbers))
+            .select(rooms::number)
+            .load::<String>(&mut conn)
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?
+            .into_iter()
+            .collect();
+        let mut occurrences: HashMap<&str, usize> = HashMap::new();
+        for number in &numbers {
+            *occurrences.entry(number.as_str()).or_default() += 1;
+        }
+        let mut room_types: HashMap<RoomType, Result<RoomTypeConfig, String>> = HashMap::new();
+        for spec in &specs {
+            if !room_types.contains_key(&spec.room_type) {
+                let found = match RoomTypeService::find_active_on(&mut conn, &spec.room_type) {
+                    Err(AppError::DatabaseError(e)) => return Err(AppError::DatabaseError(e)),
+                    found => found.map_err(Self::bulk_reason),
+                };
+                room_types.insert(spec.room_type.clone(), found);
+            }
+        }
+
+        let mut skipped = Vec::new();
+        let mut accepted = Vec::new();
+        for (spec, number) in specs.iter().zip(&numbers) {
+            let checked = if number.is_empty() || number.chars().count() > MAX_ROOM_NUMBER_LEN {
+                Err(format!("Room number must be 1 to {} characters", MAX_ROOM_NUMBER_LEN))
+            } else if occurrences[number.as_str()] > 1 {
+                Err("Room number appears more than once in the request".to_string())
+            } else if existing.contains(number) {
+                Err("Room number already exists".to_string())
+            } else {
+                room_types[&spec.room_type].clone().and_then(|room_type| {
+                    let price = match &spec.price {
+                        None => room_type.default_price.clone(),
+                        Some(price) => Self::validate_price(price).map_err(Self::bulk_reason)?,
+                    };
+                    let location = Self::resolve_location(number, &spec.location).map_err(Self::bulk_reason)?;
+                    Ok((room_type, price, location))
+                })
+            };
+
+            match checked {
+                Ok((room_type, price, (floor, building))) => accepted.push(NewRoom {
+                    number,
+                    room_type: room_type.code,
+                    room_type_id: room_type.id,
+                    price,
+                    floor,
+                    building,
+                }),
+                Err(reason) => skipped.push(SkippedRoom {
+                    number: number.clone(),
+                    reason,
+                }),
+            }
+        }
+
+        if accepted.is_empty() {
+            return Ok(BulkRoomsResult {
+                created: Vec::new(),
+                skipped,
+            });
+        }
+
+        let created = conn.transaction::<_, AppError, _>(|conn| {
+            diesel::insert_into(rooms::table)
+                .values(&accepted)
+                .get_results::<Room>(conn)
+                .map_err(|e| match e {
+                    diesel::result::Error::DatabaseError(
+                        diesel::result::DatabaseErrorKind::UniqueViolation,
+                        _,
+                    ) => AppError::DuplicateRoom(
+                        "Another request created one of these room numbers; nothing was created".to_string(),
+                    ),
+                    e => AppError::DatabaseError(e.to_string()),
+                })
+        })?;
+
+        Ok(BulkRoomsResult { created, skipped })
+    }
+
+    /// Refuse a status change that contradicts a guest staying in the room:
+    /// while a booking is checked in or overstaying, the room can become
+    /// neither available nor under maintenance
+    pub fn check_no_guest_on(conn: &mut PgConnection, room: &Room, status: RoomStatus) -> AppResult<()> {
+        if room.status == status || !matches!(status, RoomStatus::Available | RoomStatus::Maintenance) {
+            return Ok(());
+        }
+
+        let staying: Option<String> = bookings::table
+            .filter(bookings::room_id.eq(room.id))
+            .filter(bookings::status.eq_any([BookingStatus::CheckedIn, BookingStatus::Overstay]))
+            .select(bookings::reference)
+            .first(conn)
+            .optional()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        match staying {
+            Some(reference) => Err(AppError::Conflict(format!(
+                "Room {} cannot be set to {} while booking {} is checked in",
+                room.number, status, reference
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// Conflict for an edit made against an outdated copy of the room
+    fn stale_room(current: &Room) -> AppError {
+        AppError::ConflictWith(
+            format!(
+                "Room {} was changed by someone else; review the current details and try again",
+                current.number
+            ),
+            serde_json::to_value(current).unwrap_or(serde_json::Value::Null),
+        )
+    }
+
+    /// Reason a bulk room was skipped for a validation error
+    fn bulk_reason(error: AppError) -> String {
+        match error {
+            AppError::FieldErrors(fields) => fields
+                .into_iter()
+                .map(|(field, message)| format!("{} {}", field, message))
+                .collect::<Vec<_>>()
+                .join(", "),
+            other => other.to_string(),
+        }
+    }
+
     /// Get a room by ID
     pub fn get_room_by_id(&self, room_id: Uuid) -> AppResult<Room> {
         let mut conn = self
@@ -69,6 +472,95 @@ impl RoomService {
             .map_err(|_| AppError::NotFound(format!("Room with ID '{}' not found", room_id)))
     }
 
+    /// How many rooms are in each status and of each type. Dashboards poll
+    /// this, so it is a single grouped count without loading any rooms.
+    pub fn status_summary(&self) -> AppResult<RoomStatusSummary> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let counts: Vec<(RoomStatus, RoomType, i64)> = rooms::table
+            .filter(rooms::decommissioned_at.is_null())
+            .group_by((rooms::status, rooms::room_type))
+            .select((rooms::status, rooms::room_type, diesel::dsl::count_star()))
+            .load(&mut conn)
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(Self::build_status_summary(&counts))
+    }
+
+    /// Fold room counts per (status, room type) into the summary
+    pub fn build_status_summary(counts: &[(RoomStatus, RoomType, i64)]) -> RoomStatusSummary {
+        let mut summary = RoomStatusSummary {
+            total: 0,
+            by_status: RoomStatus::ALL.iter().map(|s| (s.as_str(), 0)).collect(),
+            by_room_type: BTreeMap::new(),
+        };
+        for (status, room_type, count) in counts {
+            summary.total += count;
+            *summary.by_status.entry(status.as_str()).or_default() += count;
+            *summary.by_room_type.entry(room_type.clone()).or_default() += count;
+        }
+        summary
+    }
+
+    /// A room with its current stay and next arrival: the room and its
+    /// active bookings come from one query, the hotel date from the settings
+    pub fn get_room_overview(&self, room_id: Uuid) -> AppResult<RoomOverview> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let today = clock::local_date(Utc::now(), Settings::load(&mut conn)?.time_zone(settings::HOTEL_TIMEZONE));
+        let rows: Vec<(Room, Option<RoomOverviewBooking>)> = rooms::table
+            .left_join(
+                bookings::table.on(bookings::room_id.eq(rooms::id).and(
+                    bookings::status
+                        .eq_any([BookingStatus::CheckedIn, BookingStatus::Overstay])
+                        .or(bookings::status
+                            .eq(BookingStatus::Upcoming)
+                            .and(bookings::check_in_date.ge(today))),
+                )),
+            )
+            .filter(rooms::id.eq(room_id))
+            .select((Room::as_select(), Option::<RoomOverviewBooking>::as_select()))
+            .load(&mut conn)
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let Some(room) = rows.first().map(|(room, _)| room.clone()) else {
+            return Err(AppError::NotFound(format!("Room with ID '{}' not found", room_id)));
+        };
+        let bookings: Vec<RoomOverviewBooking> = rows.into_iter().filter_map(|(_, booking)| booking).collect();
+        Ok(Self::build_overview(room, &bookings, today))
+    }
+
+    /// Pick the current and next booking of a room from its active bookings.
+    /// The current one is the checked-in or overstaying guest who arrived by
+    /// `today`, even when their check-out date has passed and the overstay
+    /// sweep has not caught up; the next one is the earliest upcoming arrival
+    /// from `today` on.
+    pub fn build_overview(room: Room, bookings: &[RoomOverviewBooking], today: NaiveDate) -> RoomOverview {
+        let current_booking = bookings
+            .iter()
+            .filter(|b| matches!(b.status, BookingStatus::CheckedIn | BookingStatus::Overstay))
+            .filter(|b| b.check_in_date <= today)
+            .min_by_key(|b| b.check_in_date)
+            .cloned();
+        let next_booking = bookings
+            .iter()
+            .filter(|b| b.status == BookingStatus::Upcoming && b.check_in_date >= today)
+            .min_by_key(|b| (b.check_in_date, b.check_out_date))
+            .cloned();
+
+        RoomOverview {
+            room,
+            current_booking,
+            next_booking,
+        }
+    }
+
     /// Get a room by number
     #[allow(dead_code)]
     pub fn get_room_by_number(&self, number: &str) -> AppResult<Room> {
@@ -83,133 +575,681 @@ impl RoomService {
             .map_err(|_| AppError::NotFound(format!("Room '{}' not found", number)))
     }
 
-    /// List all rooms with optional filters
-    pub fn list_rooms(
-        &self,
-        status_filter: Option<RoomStatus>,
-        type_filter: Option<RoomType>,
-    ) -> AppResult<Vec<Room>> {
+    /// List all rooms matching the filter, by number. Decommissioned rooms
+    /// are left out unless `include_decommissioned` is set.
+    pub fn list_rooms(&self, filter: &RoomFilter) -> AppResult<Vec<Room>> {
         let mut conn = self
             .pool
             .get()
             .map_err(|e| AppError::DatabaseError(e.to_string()))?;
 
+        Self::filtered_rooms(filter)
+            .order(rooms::number.asc())
+            .load(&mut conn)
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
+    /// Rooms matching the filter, in no particular order
+    fn filtered_rooms(filter: &RoomFilter) -> rooms::BoxedQuery<'static, diesel::pg::Pg> {
         let mut query = rooms::table.into_boxed();
 
-        if let Some(status) = status_filter {
+        if !filter.include_decommissioned {
+            query = query.filter(rooms::decommissioned_at.is_null());
+        }
+
+        if let Some(status) = filter.status {
             query = query.filter(rooms::status.eq(status));
         }
 
-        if let Some(room_type) = type_filter {
-            query = query.filter(rooms::room_type.eq(room_type));
+        if let Some(room_type) = &filter.room_type {
+            query = query.filter(rooms::room_type.eq(room_type.clone()));
+        }
+
+        if let Some(floor) = filter.floor {
+            query = query.filter(rooms::floor.eq(floor));
+        }
+
+        if let Some(building) = &filter.building {
+            let building = building.trim();
+            if building.is_empty() {
+                query = query.filter(rooms::building.is_null());
+            } else {
+                query = query.filter(rooms::building.eq(building.to_string()));
+            }
         }
 
         query
+    }
+
+    /// Rooms for the cleaners' list, matching the filter. Rooms with a guest
+    /// arriving today come first, then the ones that have waited longest in
+    /// their current status. The wait is taken from the latest status event
+    /// into that status; rooms that never changed status count from their
+    /// creation.
+    pub fn list_cleaner_rooms(&self, filter: &RoomFilter) -> AppResult<Vec<CleanerRoom>> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let today = clock::local_date(Utc::now(), Settings::load(&mut conn)?.time_zone(settings::HOTEL_TIMEZONE));
+        let entered_status = room_status_events::table
+            .filter(room_status_events::room_id.eq(rooms::id))
+            .filter(room_status_events::to_status.eq(rooms::status))
+            .select(diesel::dsl::max(room_status_events::created_at))
+            .single_value();
+        let arrival_today = diesel::dsl::exists(
+            bookings::table
+                .filter(bookings::room_id.eq(rooms::id))
+                .filter(bookings::status.eq(BookingStatus::Upcoming))
+                .filter(bookings::check_in_date.eq(today)),
+        );
+
+        let rows: Vec<(Room, Option<DateTime<Utc>>, bool)> = Self::filtered_rooms(filter)
+            .select((Room::as_select(), entered_status, arrival_today))
+            .load(&mut conn)
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let rooms = rows
+            .into_iter()
+            .map(|(room, entered, priority)| CleanerRoom {
+                dirty_since: entered.unwrap_or(room.created_at),
+                room,
+                priority,
+            })
+            .collect();
+        Ok(Self::order_cleaner_rooms(rooms))
+    }
+
+    /// Order the cleaners' list: priority rooms first, then longest waiting,
+    /// then by number
+    pub fn order_cleaner_rooms(mut rooms: Vec<CleanerRoom>) -> Vec<CleanerRoom> {
+        rooms.sort_by(|a, b| {
+            b.priority
+                .cmp(&a.priority)
+                .then(a.dirty_since.cmp(&b.dirty_since))
+                .then_with(|| a.room.number.cmp(&b.room.number))
+        });
+        rooms
+    }
+
+    /// Rooms whose number starts with `q`, by number, with the guest staying
+    /// in each; at most `ROOM_SEARCH_LIMIT` results. Decommissioned rooms are
+    /// left out.
+    pub fn search_rooms(&self, q: &str) -> AppResult<Vec<RoomSearchResult>> {
+        validate_search_query(q)?;
+        let q = q.trim();
+
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        rooms::table
+            .left_join(
+                bookings::table.on(bookings::room_id
+                    .eq(rooms::id)
+                    .and(bookings::status.eq_any([BookingStatus::CheckedIn, BookingStatus::Overstay]))),
+            )
+            .filter(rooms::decommissioned_at.is_null())
+            .filter(rooms::number.ilike(format!("{}%", q)))
+            .select((
+                rooms::id,
+                rooms::number,
+                rooms::room_type,
+                rooms::status,
+                bookings::guest_name.nullable(),
+            ))
             .order(rooms::number.asc())
+            .limit(ROOM_SEARCH_LIMIT)
             .load(&mut conn)
             .map_err(|e| AppError::DatabaseError(e.to_string()))
     }
 
+    /// Rooms matching the filter grouped by building, then floor
+    pub fn list_rooms_grouped(&self, filter: &RoomFilter) -> AppResult<Vec<BuildingRooms>> {
+        Ok(Self::group_rooms(self.list_rooms(filter)?))
+    }
+
+    /// Group rooms by building (main building first, then by name) and
+    /// floor (lowest first); rooms keep their order within a floor
+    pub fn group_rooms(rooms: Vec<Room>) -> Vec<BuildingRooms> {
+        let mut buildings: BTreeMap<Option<String>, BTreeMap<i32, Vec<Room>>> = BTreeMap::new();
+        for room in rooms {
+            buildings
+                .entry(room.building.clone())
+                .or_default()
+                .entry(room.floor)
+                .or_default()
+                .push(room);
+        }
+        buildings
+            .into_iter()
+            .map(|(building, floors)| BuildingRooms {
+                building,
+                floors: floors
+                    .into_iter()
+                    .map(|(floor, rooms)| FloorRooms { floor, rooms })
+                    .collect(),
+            })
+            .collect()
+    }
+
     /// Update a room
     pub fn update_room(
         &self,
         room_id: Uuid,
-        room_type: Option<RoomType>,
-        status: Option<RoomStatus>,
-        assigned_cleaner_id: Option<Option<Uuid>>,
+        edit: RoomEdit,
+        actor_user_id: Option<Uuid>,
     ) -> AppResult<Room> {
+        let RoomEdit {
+            room_type,
+            status,
+            assigned_cleaner_id,
+            details,
+            price,
+            maintenance_reason,
+            floor,
+            building,
+            expected_updated_at,
+        } = edit;
+        let details = Self::validate_details(details)?;
+        let new_price = price.as_ref().map(Self::validate_price).transpose()?;
+        let floor = floor.map(Self::validate_floor).transpose()?;
+        let building = building.as_deref().map(Self::validate_building).transpose()?;
+        let maintenance_reason = maintenance_reason
+            .as_deref()
+            .filter(|r| !r.trim().is_empty())
+            .map(TicketService::validate_title)
+            .transpose()?;
+
         let mut conn = self
             .pool
             .get()
             .map_err(|e| AppError::DatabaseError(e.to_string()))?;
 
-        // Get current room
-        let current: Room = rooms::table
-            .find(room_id)
-            .first(&mut conn)
-            .map_err(|_| AppError::NotFound(format!("Room with ID '{}' not found", room_id)))?;
+        conn.transaction::<_, AppError, _>(|conn| {
+            // Get current room; the lock keeps concurrent price changes in order
+            let current: Room = rooms::table
+                .find(room_id)
+                .for_update()
+                .first(conn)
+                .map_err(|_| AppError::NotFound(format!("Room with ID '{}' not found", room_id)))?;
 
-        // Validate status transition if status is being changed
-        if let Some(new_status) = status {
-            // Special business rule: Occupied -> Available must still go through the
-            // controlled check-out flow, not a direct room edit.
-            if current.status == RoomStatus::Occupied && new_status == RoomStatus::Available {
-                return Err(AppError::InvalidStatusTransition(
-                    "Occupied rooms can only be set to available via guest check-out.".into(),
-                ));
+            // Validate status transition if status is being changed
+            if let Some(new_status) = status {
+                // Special business rule: Occupied -> Available must still go through the
+                // controlled check-out flow, not a direct room edit.
+                if current.status == RoomStatus::Occupied && new_status == RoomStatus::Available {
+                    return Err(AppError::InvalidStatusTransition(
+                        "Occupied rooms can only be set to available via guest check-out.".into(),
+                    ));
+                }
+
+                // Marking a room dirty is covered by the matrix (Available,
+                // Occupied and Cleaning rooms); a room under maintenance must
+                // be released to Available first
+                Self::check_status_transition(current.status, new_status)?;
+                Self::check_no_guest_on(conn, &current, new_status)?;
+
+                // Every stretch under maintenance is backed by a ticket
+                if new_status == RoomStatus::Maintenance
+                    && current.status != RoomStatus::Maintenance
+                    && !TicketService::has_unresolved_on(conn, room_id)?
+                {
+                    let Some(title) = maintenance_reason else {
+                        return Err(AppError::ValidationError(
+                            "A room needs an open maintenance ticket or a reason to go under maintenance"
+                                .to_string(),
+                        ));
+                    };
+                    TicketService::open_ticket_on(
+                        conn,
+                        &NewMaintenanceTicket {
+                            room_id,
+                            title,
+                            description: None,
+                            severity: TicketSeverity::default(),
+                            reported_by: actor_user_id,
+                        },
+                    )?;
+                }
             }
 
-            // Admin override: allow setting a room to Dirty from any status.
-            // This lets staff mark a room as dirty even if it's currently maintenance,
-            // occupied, cleaning, etc.
-            if new_status != RoomStatus::Dirty {
-                // For all other statuses, fall back to normal transition rules.
-                if !current.status.can_transition_to(new_status) {
-                    return Err(AppError::InvalidStatusTransition(format!(
-                        "Cannot transition room from {:?} to {:?}",
-                        current.status, new_status
-                    )));
+            // Setting the same price again is not a change worth recording
+            let new_price = new_price.filter(|p| *p != current.price);
+
+            // Retyping a room needs the type to be on offer; its price stays
+            let room_type = match room_type {
+                Some(code) if code != current.room_type => Some(RoomTypeService::find_active_on(conn, &code)?),
+                _ => None,
+            };
+
+            let mut update = UpdateRoom {
+                room_type_id: room_type.as_ref().map(|t| t.id),
+                room_type: room_type.map(|t| t.code),
+                status,
+                price: new_price.clone(),
+                assigned_cleaner_id,
+                description: details.description.map(|d| Some(d).filter(|d| !d.is_empty())),
+                amenities: details.amenities,
+                photo_urls: details.photo_urls,
+                floor: floor.filter(|f| *f != current.floor),
+                building: building.filter(|b| *b != current.building),
+            };
+
+            // Auto-clear assignment if becoming available
+            if status == Some(RoomStatus::Available) {
+                update.assigned_cleaner_id = Some(None);
+            }
+
+            // Diesel cannot build an update without columns, e.g. when only
+            // the unchanged price was sent
+            if update.is_empty() {
+                if expected_updated_at.is_some_and(|at| at != current.updated_at) {
+                    return Err(Self::stale_room(&current));
                 }
+                return Ok(current);
+            }
+
+            // Without an expected version the locked row always matches
+            let version = expected_updated_at.unwrap_or(current.updated_at);
+            let room: Room = diesel::update(rooms::table.find(room_id).filter(rooms::updated_at.eq(version)))
+                .set(&update)
+                .get_result(conn)
+                .optional()
+                .map_err(|e| AppError::DatabaseError(e.to_string()))?
+                .ok_or_else(|| Self::stale_room(&current))?;
+
+            if let Some(new_price) = new_price {
+                diesel::insert_into(room_price_history::table)
+                    .values(&NewRoomPriceChange {
+                        room_id,
+                        old_price: current.price,
+                        new_price,
+                        changed_by: actor_user_id,
+                    })
+                    .execute(conn)
+                    .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+            }
+            Self::record_status_change(conn, room_id, current.status, room.status, actor_user_id)?;
+
+            Ok(room)
+        })
+    }
+
+    /// Take a room out of service for good. The room and its bookings stay
+    /// so history still resolves it; it just drops out of room lists and
+    /// cannot be booked. Refused while any guest is booked into or staying
+    /// in the room. Decommissioning an already decommissioned room returns
+    /// it unchanged.
+    pub fn decommission_room(&self, room_id: Uuid) -> AppResult<Room> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        conn.transaction::<_, AppError, _>(|conn| {
+            // The lock keeps new bookings out until the room is decommissioned
+            let room: Room = rooms::table
+                .find(room_id)
+                .for_update()
+                .first(conn)
+                .optional()?
+                .ok_or_else(|| AppError::NotFound(format!("Room with ID '{}' not found", room_id)))?;
+
+            if room.decommissioned_at.is_some() {
+                return Ok(room);
+            }
+
+            let active: Vec<String> = bookings::table
+                .filter(bookings::room_id.eq(room_id))
+                .filter(bookings::status.eq_any([
+                    BookingStatus::Upcoming,
+                    BookingStatus::CheckedIn,
+                    BookingStatus::Overstay,
+                ]))
+                .order(bookings::check_in_date.asc())
+                .select(bookings::reference)
+                .load(conn)
+                .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+            if !active.is_empty() {
+                return Err(AppError::Conflict(format!(
+                    "Room {} still has active bookings: {}",
+                    room.number,
+                    active.join(", ")
+                )));
             }
+
+            diesel::update(rooms::table.find(room_id))
+                .set(rooms::decommissioned_at.eq(Some(Utc::now())))
+                .get_result(conn)
+                .map_err(|e| AppError::DatabaseError(e.to_string()))
+        })
+    }
+
+    /// Check a floor is within [`MIN_FLOOR`]..=[`MAX_FLOOR`]
+    pub fn validate_floor(floor: i32) -> AppResult<i32> {
+        if !(MIN_FLOOR..=MAX_FLOOR).contains(&floor) {
+            return Err(field_error(
+                "floor",
+                format!("must be {} to {}", MIN_FLOOR, MAX_FLOOR),
+            ));
+        }
+        Ok(floor)
+    }
+
+    /// Trim a building name; a blank name means the main building (None)
+    pub fn validate_building(building: &str) -> AppResult<Option<String>> {
+        let building = building.trim();
+        if building.chars().count() > MAX_BUILDING_LEN {
+            return Err(field_error(
+                "building",
+                format!("must be at most {} characters", MAX_BUILDING_LEN),
+            ));
         }
+        Ok(Some(building.to_string()).filter(|b| !b.is_empty()))
+    }
 
-        let mut update = UpdateRoom {
-            room_type,
-            status,
-            price: None,
-            assigned_cleaner_id,
+    /// Floor a room number implies: 305 is on floor 3 and 1204 on floor 12.
+    /// Numbers not made of three or four digits go on floor 1.
+    pub fn floor_from_number(number: &str) -> i32 {
+        let number = number.trim();
+        if (3..=4).contains(&number.len()) && number.bytes().all(|b| b.is_ascii_digit()) {
+            number.parse::<i32>().map_or(1, |n| n / 100)
+        } else {
+            1
+        }
+    }
+
+    /// Floor and building a new room goes in
+    fn resolve_location(number: &str, location: &RoomLocation) -> AppResult<(i32, Option<String>)> {
+        let floor = Self::validate_floor(location.floor.unwrap_or_else(|| Self::floor_from_number(number)))?;
+        let building = match &location.building {
+            Some(building) => Self::validate_building(building)?,
+            None => None,
         };
-        
-        // Auto-clear assignment if becoming available
-        if status == Some(RoomStatus::Available) {
-            update.assigned_cleaner_id = Some(None);
+        Ok((floor, building))
+    }
+
+    /// Check a nightly room price: a whole, positive VND amount of at most
+    /// 12 digits. Returns the price at scale 0.
+    pub fn validate_price(price: &BigDecimal) -> AppResult<BigDecimal> {
+        let price = money::validate_vnd_amount(price, &BigDecimal::from(MAX_ROOM_PRICE))
+            .map_err(|message| field_error("price", message))?;
+        if price.is_zero() {
+            return Err(field_error("price", "must be positive".to_string()));
         }
+        Ok(price)
+    }
 
-        diesel::update(rooms::table.find(room_id))
-            .set(&update)
-            .get_result(&mut conn)
+    /// Price changes of a room, newest first
+    pub fn get_price_history(&self, room_id: Uuid) -> AppResult<Vec<RoomPriceChange>> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        rooms::table
+            .find(room_id)
+            .select(rooms::id)
+            .first::<Uuid>(&mut conn)
+            .optional()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?
+            .ok_or_else(|| AppError::NotFound(format!("Room with ID '{}' not found", room_id)))?;
+
+        room_price_history::table
+            .filter(room_price_history::room_id.eq(room_id))
+            .order((room_price_history::changed_at.desc(), room_price_history::id.desc()))
+            .load(&mut conn)
             .map_err(|e| AppError::DatabaseError(e.to_string()))
     }
 
+    /// Trim and validate guest-facing room details.
+    ///
+    /// Blank amenities are dropped, duplicates removed (first occurrence kept),
+    /// and photos must be absolute http(s) URLs.
+    pub fn validate_details(details: RoomDetailsUpdate) -> AppResult<RoomDetailsUpdate> {
+        let description = details.description.map(|d| d.trim().to_string());
+        if let Some(d) = &description {
+            if d.chars().count() > MAX_DESCRIPTION_LEN {
+                return Err(AppError::ValidationError(format!(
+                    "Description must be at most {} characters",
+                    MAX_DESCRIPTION_LEN
+                )));
+            }
+        }
+
+        let amenities = match details.amenities {
+            Some(list) => {
+                let mut cleaned: Vec<String> = Vec::new();
+                for amenity in list.iter().map(|a| a.trim()).filter(|a| !a.is_empty()) {
+                    if amenity.chars().count() > MAX_AMENITY_LEN {
+                        return Err(AppError::ValidationError(format!(
+                            "Amenity '{}' is longer than {} characters",
+                            amenity, MAX_AMENITY_LEN
+                        )));
+                    }
+                    if !cleaned.iter().any(|a| a.eq_ignore_ascii_case(amenity)) {
+                        cleaned.push(amenity.to_string());
+                    }
+                }
+                if cleaned.len() > MAX_AMENITIES {
+                    return Err(AppError::ValidationError(format!(
+                        "A room can list at most {} amenities",
+                        MAX_AMENITIES
+                    )));
+                }
+                Some(cleaned)
+            }
+            None => None,
+        };
+
+        let photo_urls = match details.photo_urls {
+            Some(list) => {
+                let cleaned: Vec<String> = list
+                    .iter()
+                    .map(|u| u.trim())
+                    .filter(|u| !u.is_empty())
+                    .map(str::to_string)
+                    .collect();
+                if cleaned.len() > MAX_PHOTOS {
+                    return Err(AppError::ValidationError(format!(
+                        "A room can have at most {} photos",
+                        MAX_PHOTOS
+                    )));
+                }
+                if let Some(bad) = cleaned
+                    .iter()
+                    .find(|u| !(u.starts_with("https://") || u.starts_with("http://")))
+                {
+                    return Err(AppError::ValidationError(format!(
+                        "Photo URL '{}' must start with http:// or https://",
+                        bad
+                    )));
+                }
+                Some(cleaned)
+            }
+            None => None,
+        };
+
+        Ok(RoomDetailsUpdate {
+            description,
+            amenities,
+            photo_urls,
+        })
+    }
+
     /// Update room status (internal use for check-in/out)
     ///
     /// This bypasses the UI restriction that prevents editing an occupied room
     /// directly to available; that transition is allowed here as part of the
     /// controlled check-out flow.
     #[allow(dead_code)]
-    pub fn update_room_status(&self, room_id: Uuid, status: RoomStatus) -> AppResult<Room> {
+    pub fn update_room_status(
+        &self,
+        room_id: Uuid,
+        status: RoomStatus,
+        actor_user_id: Option<Uuid>,
+    ) -> AppResult<Room> {
         let mut conn = self
             .pool
             .get()
             .map_err(|e| AppError::DatabaseError(e.to_string()))?;
 
+        conn.transaction(|conn| Self::update_room_status_on(conn, room_id, status, actor_user_id))
+    }
+
+    /// Update room status on the caller's connection, so it commits or rolls
+    /// back together with the booking change that caused it. The change is
+    /// recorded in the status history with `actor_user_id`, None for system
+    /// transitions.
+    pub fn update_room_status_on(
+        conn: &mut PgConnection,
+        room_id: Uuid,
+        status: RoomStatus,
+        actor_user_id: Option<Uuid>,
+    ) -> AppResult<Room> {
         let current: Room = rooms::table
             .find(room_id)
-            .first(&mut conn)
+            .first(conn)
             .map_err(|_| AppError::NotFound(format!("Room with ID '{}' not found", room_id)))?;
 
-        if !current.status.can_transition_to(status) {
-            return Err(AppError::InvalidStatusTransition(format!(
-                "Cannot transition room from {:?} to {:?}",
-                current.status, status
-            )));
-        }
-        
+        Self::check_status_transition(current.status, status)?;
+
         let mut update = UpdateRoom {
             room_type: None,
+            room_type_id: None,
             status: Some(status),
             price: None,
             assigned_cleaner_id: None,
+            ..Default::default()
         };
-        
+
         // Auto-clear assignment if becoming available
         if status == RoomStatus::Available {
             update.assigned_cleaner_id = Some(None);
         }
 
-        diesel::update(rooms::table.find(room_id))
+        let room: Room = diesel::update(rooms::table.find(room_id))
             .set(&update)
-            .get_result(&mut conn)
-            .map_err(|e| AppError::DatabaseError(e.to_string()))
+            .get_result(conn)
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        Self::record_status_change(conn, room_id, current.status, room.status, actor_user_id)?;
+        Ok(room)
+    }
+
+    /// Add a status change to the room's history; a status set to itself is
+    /// not a change
+    pub fn record_status_change(
+        conn: &mut PgConnection,
+        room_id: Uuid,
+        from_status: RoomStatus,
+        to_status: RoomStatus,
+        actor_user_id: Option<Uuid>,
+    ) -> AppResult<()> {
+        if from_status == to_status {
+            return Ok(());
+        }
+        diesel::insert_into(room_status_events::table)
+            .values(&NewRoomStatusEvent {
+                room_id,
+                from_status,
+                to_status,
+                actor_user_id,
+            })
+            .execute(conn)
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Page through a room's status changes, newest first, with the username
+    /// of whoever made each one
+    ///
+    /// # Arguments
+    /// * `from` / `to` - Changes made on these days in hotel time, inclusive
+    /// * `page` / `per_page` - 1-based pagination
+    pub fn get_status_history(
+        &self,
+        room_id: Uuid,
+        from: Option<NaiveDate>,
+        to: Option<NaiveDate>,
+        page: Option<u64>,
+        per_page: Option<u64>,
+    ) -> AppResult<RoomStatusHistoryPage> {
+        if let (Some(from), Some(to)) = (from, to) {
+            if from > to {
+                return Err(AppError::ValidationError(
+                    "'from' must not be after 'to'".to_string(),
+                ));
+            }
+        }
+        let page = page.unwrap_or(1).max(1);
+        let per_page = per_page
+            .unwrap_or(DEFAULT_STATUS_HISTORY_PAGE_SIZE)
+            .clamp(1, MAX_STATUS_HISTORY_PAGE_SIZE);
+
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        rooms::table
+            .find(room_id)
+            .select(rooms::id)
+            .first::<Uuid>(&mut conn)
+            .optional()?
+            .ok_or_else(|| AppError::NotFound(format!("Room with ID '{}' not found", room_id)))?;
+
+        // Days are midnights in hotel time
+        let tz = Settings::load(&mut conn)?.time_zone(settings::HOTEL_TIMEZONE);
+        let local_midnight =
+            |date: NaiveDate| clock::local_to_utc(tz, date.and_hms_opt(0, 0, 0).expect("midnight"));
+        let start = from.map(local_midnight);
+        let end = to.map(|to| local_midnight(to + Duration::days(1)));
+
+        let build_query = || {
+            let mut query = room_status_events::table
+                .left_join(users::table)
+                .filter(room_status_events::room_id.eq(room_id))
+                .into_boxed();
+            if let Some(start) = start {
+                query = query.filter(room_status_events::created_at.ge(start));
+            }
+            if let Some(end) = end {
+                query = query.filter(room_status_events::created_at.lt(end));
+            }
+            query
+        };
+
+        let total: i64 = build_query().count().get_result(&mut conn)?;
+        let rows: Vec<(RoomStatusEvent, Option<String>)> = build_query()
+            .select((RoomStatusEvent::as_select(), users::username.nullable()))
+            .order((room_status_events::created_at.desc(), room_status_events::id.desc()))
+            .limit(per_page as i64)
+            .offset(((page - 1) * per_page) as i64)
+            .load(&mut conn)?;
+
+        Ok(RoomStatusHistoryPage {
+            room_id,
+            events: rows
+                .into_iter()
+                .map(|(event, actor_username)| RoomStatusHistoryEntry { event, actor_username })
+                .collect(),
+            total: total as u64,
+            page,
+            per_page,
+        })
+    }
+
+    /// Refuse room status changes the room lifecycle does not allow
+    pub fn check_status_transition(from: RoomStatus, to: RoomStatus) -> AppResult<()> {
+        if !from.can_transition_to(to) {
+            return Err(AppError::InvalidStatusTransition(format!(
+                "Cannot transition room from {} to {}",
+                from, to
+            )));
+        }
+        Ok(())
     }
 }
@@ -0,0 +1,233 @@
+use bigdecimal::BigDecimal;
+use diesel::prelude::*;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::errors::{AppError, AppResult};
+use crate::models::{NewRoomTypeConfig, RoomType, RoomTypeConfig, UpdateRoomTypeConfig};
+use crate::schema::{room_types, rooms};
+use crate::services::RoomService;
+use crate::utils::money::field_error;
+
+/// Longest room type display name
+pub const MAX_DISPLAY_NAME_LEN: usize = 100;
+/// Most guests a room type may be configured to sleep
+pub const MAX_CAPACITY: i32 = 50;
+
+/// Request to add a room type
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateRoomTypeRequest {
+    pub code: RoomType,
+    pub display_name: String,
+    pub default_price: BigDecimal,
+    pub default_capacity: i32,
+    #[serde(default = "default_active")]
+    pub active: bool,
+}
+
+fn default_active() -> bool {
+    true
+}
+
+/// Changes to a room type; fields left as None stay as they are
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct UpdateRoomTypeRequest {
+    pub display_name: Option<String>,
+    pub default_price: Option<BigDecimal>,
+    pub default_capacity: Option<i32>,
+    pub active: Option<bool>,
+}
+
+/// Service for the configurable room types
+pub struct RoomTypeService {
+    pool: DbPool,
+}
+
+impl RoomTypeService {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    fn validate_display_name(name: &str) -> AppResult<String> {
+        let name = name.trim();
+        if name.is_empty() || name.chars().count() > MAX_DISPLAY_NAME_LEN {
+            return Err(field_error(
+                "display_name",
+                format!("must be 1 to {} characters", MAX_DISPLAY_NAME_LEN),
+            ));
+        }
+        Ok(name.to_string())
+    }
+
+    fn validate_capacity(capacity: i32) -> AppResult<i32> {
+        if !(1..=MAX_CAPACITY).contains(&capacity) {
+            return Err(field_error(
+                "default_capacity",
+                format!("must be 1 to {}", MAX_CAPACITY),
+            ));
+        }
+        Ok(capacity)
+    }
+
+    /// Room types cheapest first; inactive ones only when asked for
+    pub fn list_room_types(&self, include_inactive: bool) -> AppResult<Vec<RoomTypeConfig>> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Self::list_on(&mut conn, include_inactive)
+    }
+
+    /// [`Self::list_room_types`] on the caller's connection
+    pub fn list_on(conn: &mut PgConnection, include_inactive: bool) -> AppResult<Vec<RoomTypeConfig>> {
+        let mut query = room_types::table.into_boxed();
+        if !include_inactive {
+            query = query.filter(room_types::active.eq(true));
+        }
+        query
+            .order((room_types::default_price.asc(), room_types::code.asc()))
+            .load(conn)
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
+    /// One room type
+    pub fn get_room_type(&self, id: Uuid) -> AppResult<RoomTypeConfig> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        room_types::table
+            .find(id)
+            .first(&mut conn)
+            .optional()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?
+            .ok_or_else(|| AppError::NotFound(format!("Room type '{}' not found", id)))
+    }
+
+    /// The room type with this code, active or not
+    pub fn find_by_code_on(conn: &mut PgConnection, code: &RoomType) -> AppResult<RoomTypeConfig> {
+        room_types::table
+            .filter(room_types::code.eq(code))
+            .first(conn)
+            .optional()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?
+            .ok_or_else(|| field_error("room_type", format!("unknown room type '{}'", code)))
+    }
+
+    /// The room type new rooms of this code are created with; it must be active
+    pub fn find_active_on(conn: &mut PgConnection, code: &RoomType) -> AppResult<RoomTypeConfig> {
+        let room_type = Self::find_by_code_on(conn, code)?;
+        if !room_type.active {
+            return Err(field_error(
+                "room_type",
+                format!("room type '{}' is no longer offered", code),
+            ));
+        }
+        Ok(room_type)
+    }
+
+    /// Add a room type
+    ///
+    /// # Errors
+    /// * `Conflict` - The code is taken
+    pub fn create_room_type(&self, request: &CreateRoomTypeRequest) -> AppResult<RoomTypeConfig> {
+        let new_type = NewRoomTypeConfig {
+            code: request.code.clone(),
+            display_name: Self::validate_display_name(&request.display_name)?,
+            default_price: RoomService::validate_price(&request.default_price)?,
+            default_capacity: Self::validate_capacity(request.default_capacity)?,
+            active: request.active,
+        };
+
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        diesel::insert_into(room_types::table)
+            .values(&new_type)
+            .get_result(&mut conn)
+            .map_err(|e| match e {
+                diesel::result::Error::DatabaseError(diesel::result::DatabaseErrorKind::UniqueViolation, _) => {
+                    AppError::Conflict(format!("Room type '{}' already exists", request.code))
+                }
+                other => AppError::DatabaseError(other.to_string()),
+            })
+    }
+
+    /// Change a room type's name, defaults or whether it is offered. Existing
+    /// rooms keep their own price.
+    pub fn update_room_type(&self, id: Uuid, request: &UpdateRoomTypeRequest) -> AppResult<RoomTypeConfig> {
+        let changes = UpdateRoomTypeConfig {
+            display_name: request
+                .display_name
+                .as_deref()
+                .map(Self::validate_display_name)
+                .transpose()?,
+            default_price: request
+                .default_price
+                .as_ref()
+                .map(RoomService::validate_price)
+                .transpose()?,
+            default_capacity: request
+                .default_capacity
+                .map(Self::validate_capacity)
+                .transpose()?,
+            active: request.active,
+        };
+        if changes.is_empty() {
+            return self.get_room_type(id);
+        }
+
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        diesel::update(room_types::table.find(id))
+            .set(&changes)
+            .get_result(&mut conn)
+            .optional()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?
+            .ok_or_else(|| AppError::NotFound(format!("Room type '{}' not found", id)))
+    }
+
+    /// Delete a room type no room uses; types with rooms can only be
+    /// deactivated. Its pricing rules go with it.
+    pub fn delete_room_type(&self, id: Uuid) -> AppResult<()> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        conn.transaction::<_, AppError, _>(|conn| {
+            let room_type: RoomTypeConfig = room_types::table
+                .find(id)
+                .for_update()
+                .first(conn)
+                .optional()
+                .map_err(|e| AppError::DatabaseError(e.to_string()))?
+                .ok_or_else(|| AppError::NotFound(format!("Room type '{}' not found", id)))?;
+
+            let room_count: i64 = rooms::table
+                .filter(rooms::room_type_id.eq(id))
+                .count()
+                .get_result(conn)
+                .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+            if room_count > 0 {
+                return Err(AppError::Conflict(format!(
+                    "Room type '{}' has {} room(s); deactivate it instead",
+                    room_type.code, room_count
+                )));
+            }
+
+            diesel::delete(room_types::table.find(id))
+                .execute(conn)
+                .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,235 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use chrono::{DateTime, Duration, Utc};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::errors::{AppError, AppResult};
+use crate::models::UserRole;
+use crate::schema::user_sessions;
+
+/// Idle timeout used when SESSION_IDLE_TIMEOUT_MINUTES is not set
+pub const DEFAULT_IDLE_TIMEOUT_MINUTES: i64 = 30;
+
+/// Activity is written to the database at most this often per session
+pub const LAST_SEEN_FLUSH_SECS: i64 = 60;
+
+/// Message sent with the SESSION_EXPIRED code
+pub const SESSION_EXPIRED_MESSAGE: &str = "You were logged out due to inactivity";
+
+/// Sessions kept in memory before idle ones are pruned
+const MAX_TRACKED_SESSIONS: usize = 10_000;
+
+/// Sessions older than this can no longer hold a valid token
+pub const SESSION_MAX_AGE_HOURS: i64 = 24;
+
+/// How long a session may sit idle, per role. Receptionists and cleaners use
+/// the default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IdleLimits {
+    pub default: Duration,
+    pub admin: Duration,
+    pub guest: Duration,
+}
+
+impl IdleLimits {
+    /// The same limit for every role
+    pub fn uniform(limit: Duration) -> Self {
+        Self {
+            default: limit,
+            admin: limit,
+            guest: limit,
+        }
+    }
+
+    /// Idle limit for a role
+    pub fn for_role(&self, role: UserRole) -> Duration {
+        match role {
+            UserRole::Admin => self.admin,
+            UserRole::Guest => self.guest,
+            _ => self.default,
+        }
+    }
+
+    fn longest(&self) -> Duration {
+        self.default.max(self.admin).max(self.guest)
+    }
+}
+
+impl Default for IdleLimits {
+    fn default() -> Self {
+        Self::uniform(Duration::minutes(DEFAULT_IDLE_TIMEOUT_MINUTES))
+    }
+}
+
+/// Outcome of recording activity on a session
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Activity {
+    /// Within the idle limit; `flush` asks the caller to persist `last_seen_at`
+    Active { flush: bool },
+    /// Idle past the limit for the role
+    Expired,
+    /// Not tracked by this process yet; load `last_seen_at` and call `resume`
+    Unknown,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Seen {
+    last_seen: DateTime<Utc>,
+    flushed_at: DateTime<Utc>,
+}
+
+/// In-memory last-seen times per session, written behind to the database so
+/// authenticated requests do not each cost an UPDATE
+#[derive(Debug, Default)]
+pub struct SessionTracker {
+    limits: IdleLimits,
+    seen: Mutex<HashMap<Uuid, Seen>>,
+}
+
+impl SessionTracker {
+    /// Track sessions against the given idle limits
+    pub fn new(limits: IdleLimits) -> Self {
+        Self {
+            limits,
+            seen: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Record activity on a session tracked by this process
+    pub fn touch(&self, session_id: Uuid, role: UserRole, now: DateTime<Utc>) -> Activity {
+        let mut seen = self.seen.lock().unwrap();
+        if !seen.contains_key(&session_id) {
+            return Activity::Unknown;
+        }
+        self.advance(&mut seen, session_id, role, now)
+    }
+
+    /// Start tracking a session from its stored `last_seen_at` (after a
+    /// restart or on another instance) and record the current activity
+    pub fn resume(
+        &self,
+        session_id: Uuid,
+        role: UserRole,
+        last_seen: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> Activity {
+        let mut seen = self.seen.lock().unwrap();
+
+        if seen.len() >= MAX_TRACKED_SESSIONS {
+            let longest = self.limits.longest();
+            seen.retain(|_, s| now - s.last_seen <= longest);
+        }
+
+        seen.entry(session_id).or_insert(Seen {
+            last_seen,
+            flushed_at: last_seen,
+        });
+        self.advance(&mut seen, session_id, role, now)
+    }
+
+    fn advance(
+        &self,
+        seen: &mut HashMap<Uuid, Seen>,
+        session_id: Uuid,
+        role: UserRole,
+        now: DateTime<Utc>,
+    ) -> Activity {
+        let Some(entry) = seen.get_mut(&session_id) else {
+            return Activity::Unknown;
+        };
+
+        if now - entry.last_seen > self.limits.for_role(role) {
+            seen.remove(&session_id);
+            return Activity::Expired;
+        }
+
+        entry.last_seen = entry.last_seen.max(now);
+        let flush = now - entry.flushed_at >= Duration::seconds(LAST_SEEN_FLUSH_SECS);
+        if flush {
+            entry.flushed_at = now;
+        }
+        Activity::Active { flush }
+    }
+}
+
+/// Session service for the `user_sessions` table
+pub struct SessionService {
+    pool: DbPool,
+}
+
+impl SessionService {
+    /// Create a new SessionService instance
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    /// Start a session for a user who just logged in. The user's sessions
+    /// too old to back a valid token are dropped at the same time.
+    ///
+    /// # Returns
+    /// The new session id, carried in the token as `sid`
+    pub fn start(&self, user_id: Uuid) -> AppResult<Uuid> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        conn.transaction::<_, AppError, _>(|conn| {
+            diesel::delete(
+                user_sessions::table
+                    .filter(user_sessions::user_id.eq(user_id))
+                    .filter(
+                        user_sessions::created_at
+                            .lt(Utc::now() - Duration::hours(SESSION_MAX_AGE_HOURS)),
+                    ),
+            )
+            .execute(conn)?;
+
+            Ok(diesel::insert_into(user_sessions::table)
+                .values(user_sessions::user_id.eq(user_id))
+                .returning(user_sessions::id)
+                .get_result(conn)?)
+        })
+    }
+
+    /// Stored last activity of a session, or None when it has ended
+    pub fn last_seen(&self, session_id: Uuid) -> AppResult<Option<DateTime<Utc>>> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(user_sessions::table
+            .find(session_id)
+            .select(user_sessions::last_seen_at)
+            .first(&mut conn)
+            .optional()?)
+    }
+
+    /// Persist the last activity of a session
+    pub fn record_seen(&self, session_id: Uuid, at: DateTime<Utc>) -> AppResult<()> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        diesel::update(user_sessions::table.find(session_id))
+            .set(user_sessions::last_seen_at.eq(at))
+            .execute(&mut conn)?;
+        Ok(())
+    }
+
+    /// End a session; tokens carrying it are refused from now on
+    pub fn end(&self, session_id: Uuid) -> AppResult<()> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        diesel::delete(user_sessions::table.find(session_id)).execute(&mut conn)?;
+        Ok(())
+    }
+}
@@ -1,15 +1,31 @@
 use aws_sdk_s3::{Client};
 use std::env;
 
-pub async fn upload_image(
-    client: &Client,
-    bucket: &str,
-    file_name: &str,
-    data: Vec<u8>
-) -> Result<String, Box<dyn std::error::Error>> {
-    tracing::info!("Starting upload to MinIO: bucket={}, file={}, size={} bytes", bucket, file_name, data.len());
-    
-    // Check if bucket exists, create if not
+/// Largest object the storage bucket accepts from an upload
+pub const MAX_UPLOAD_BYTES: usize = 10 * 1024 * 1024;
+
+/// Content type of a JPEG, PNG or WebP image, read from its magic bytes
+pub fn image_content_type(data: &[u8]) -> Option<&'static str> {
+    if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("image/jpeg")
+    } else if data.starts_with(&[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A]) {
+        Some("image/png")
+    } else if data.len() >= 12 && data.starts_with(b"RIFF") && &data[8..12] == b"WEBP" {
+        Some("image/webp")
+    } else {
+        None
+    }
+}
+
+/// Browser-facing URL of an object in a public bucket
+pub fn public_url(bucket: &str, key: &str) -> String {
+    let minio_public_url = env::var("MINIO_PUBLIC_URL")
+        .unwrap_or_else(|_| "http://localhost:9000".to_string());
+    format!("{}/{}/{}", minio_public_url, bucket, key)
+}
+
+/// Create the bucket if it does not exist yet
+pub async fn ensure_bucket(client: &Client, bucket: &str) -> Result<(), Box<dyn std::error::Error>> {
     match client.head_bucket().bucket(bucket).send().await {
         Ok(_) => {
             tracing::debug!("Bucket '{}' exists", bucket);
@@ -29,18 +45,34 @@ pub async fn upload_image(
             }
         }
     }
+
+    Ok(())
+}
+
+pub async fn upload_image(
+    client: &Client,
+    bucket: &str,
+    file_name: &str,
+    data: Vec<u8>
+) -> Result<String, Box<dyn std::error::Error>> {
+    // Object names embed user ids, so only the bucket and size are logged
+    tracing::info!("Starting upload to MinIO: bucket={}, size={} bytes", bucket, data.len());
+    
+    // Check if bucket exists, create if not
+    ensure_bucket(client, bucket).await?;
     
     tracing::debug!("Uploading object to MinIO...");
+    let content_type = image_content_type(&data).unwrap_or("image/jpeg");
     match client
         .put_object()
         .bucket(bucket)
         .key(file_name)
         .body(data.into())
-        .content_type("image/jpeg")
+        .content_type(content_type)
         .send()
         .await {
         Ok(_) => {
-            tracing::info!("Successfully uploaded object to MinIO: {}/{}", bucket, file_name);
+            tracing::info!("Successfully uploaded object to MinIO bucket '{}'", bucket);
         }
         Err(e) => {
             tracing::error!("Failed to upload object to MinIO: {:?}", e);
@@ -55,6 +87,25 @@ pub async fn upload_image(
         })?;
     
     let result_url = format!("{}/{}/{}", minio_url, bucket, file_name);
-    tracing::info!("Upload complete, returning URL: {}", result_url);
     Ok(result_url)
-}
\ No newline at end of file
+}
+
+/// Remove an object; removing one that is already gone succeeds
+pub async fn delete_object(
+    client: &Client,
+    bucket: &str,
+    key: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    client
+        .delete_object()
+        .bucket(bucket)
+        .key(key)
+        .send()
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to delete object from MinIO: {:?}", e);
+            format!("Failed to delete from MinIO: {}", e)
+        })?;
+    tracing::info!("Deleted object from MinIO bucket '{}'", bucket);
+    Ok(())
+}
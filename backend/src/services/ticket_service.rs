@@ -0,0 +1,307 @@
+use chrono::Utc;
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::errors::{AppError, AppResult};
+use crate::models::{
+    MaintenanceTicket, NewMaintenanceTicket, Room, RoomStatus, TicketSeverity, TicketStatus,
+    UpdateMaintenanceTicket,
+};
+use crate::schema::{maintenance_tickets, rooms};
+use crate::services::{BookingService, RoomService};
+
+/// Longest ticket title; titles are stored as VARCHAR(200)
+pub const MAX_TICKET_TITLE_LEN: usize = 200;
+/// Longest ticket description
+pub const MAX_TICKET_DESCRIPTION_LEN: usize = 2000;
+
+/// Request to report a problem with a room
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateTicketRequest {
+    pub title: String,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub severity: TicketSeverity,
+}
+
+/// Changes to a ticket; fields left as None stay as they are
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct UpdateTicketRequest {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub severity: Option<TicketSeverity>,
+    pub status: Option<TicketStatus>,
+    /// Return the room to Available when this resolves its last open ticket
+    #[serde(default)]
+    pub set_available: bool,
+}
+
+/// Ticket after an update, with its room
+#[derive(Debug, Serialize)]
+pub struct TicketUpdateResult {
+    pub ticket: MaintenanceTicket,
+    pub room: Room,
+    /// The room is still under maintenance but has no unresolved tickets
+    /// left, so it may be returned to Available
+    pub can_set_available: bool,
+}
+
+/// Ticket with the number of its room, for the cross-room list
+#[derive(Debug, Serialize)]
+pub struct TicketWithRoom {
+    #[serde(flatten)]
+    pub ticket: MaintenanceTicket,
+    pub room_number: String,
+}
+
+/// Service for maintenance tickets raised against rooms
+pub struct TicketService {
+    pool: DbPool,
+}
+
+impl TicketService {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    /// Trim a ticket title and check its length
+    pub fn validate_title(title: &str) -> AppResult<String> {
+        let title = title.trim();
+        if title.is_empty() || title.chars().count() > MAX_TICKET_TITLE_LEN {
+            return Err(AppError::ValidationError(format!(
+                "Title must be 1 to {} characters",
+                MAX_TICKET_TITLE_LEN
+            )));
+        }
+        Ok(title.to_string())
+    }
+
+    /// Trim a ticket description; a blank description is dropped
+    pub fn validate_description(description: Option<&str>) -> AppResult<Option<String>> {
+        let description = description.map(str::trim).filter(|d| !d.is_empty());
+        if description.is_some_and(|d| d.chars().count() > MAX_TICKET_DESCRIPTION_LEN) {
+            return Err(AppError::ValidationError(format!(
+                "Description must be at most {} characters",
+                MAX_TICKET_DESCRIPTION_LEN
+            )));
+        }
+        Ok(description.map(str::to_string))
+    }
+
+    /// Report a problem with a room
+    pub fn create_ticket(
+        &self,
+        room_id: Uuid,
+        request: &CreateTicketRequest,
+        reported_by: Option<Uuid>,
+    ) -> AppResult<MaintenanceTicket> {
+        let ticket = NewMaintenanceTicket {
+            room_id,
+            title: Self::validate_title(&request.title)?,
+            description: Self::validate_description(request.description.as_deref())?,
+            severity: request.severity,
+            reported_by,
+        };
+
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Self::find_room(&mut conn, room_id)?;
+        Self::open_ticket_on(&mut conn, &ticket)
+    }
+
+    /// Insert a ticket on the caller's connection
+    pub fn open_ticket_on(conn: &mut PgConnection, ticket: &NewMaintenanceTicket) -> AppResult<MaintenanceTicket> {
+        diesel::insert_into(maintenance_tickets::table)
+            .values(ticket)
+            .get_result(conn)
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
+    /// Whether a room has a ticket that is open or in progress
+    pub fn has_unresolved_on(conn: &mut PgConnection, room_id: Uuid) -> AppResult<bool> {
+        diesel::select(diesel::dsl::exists(
+            maintenance_tickets::table
+                .filter(maintenance_tickets::room_id.eq(room_id))
+                .filter(maintenance_tickets::status.eq_any(TicketStatus::UNRESOLVED)),
+        ))
+        .get_result(conn)
+        .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
+    /// Tickets of one room, newest first
+    pub fn list_room_tickets(
+        &self,
+        room_id: Uuid,
+        status: Option<TicketStatus>,
+    ) -> AppResult<Vec<MaintenanceTicket>> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Self::find_room(&mut conn, room_id)?;
+        let mut query = maintenance_tickets::table
+            .filter(maintenance_tickets::room_id.eq(room_id))
+            .into_boxed();
+        if let Some(status) = status {
+            query = query.filter(maintenance_tickets::status.eq(status));
+        }
+        query
+            .order((maintenance_tickets::created_at.desc(), maintenance_tickets::id.desc()))
+            .load(&mut conn)
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
+    /// Tickets across all rooms, most severe first, then oldest first
+    pub fn list_tickets(&self, status: Option<TicketStatus>) -> AppResult<Vec<TicketWithRoom>> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let mut query = maintenance_tickets::table.inner_join(rooms::table).into_boxed();
+        if let Some(status) = status {
+            query = query.filter(maintenance_tickets::status.eq(status));
+        }
+        let rows: Vec<(MaintenanceTicket, String)> = query
+            .select((MaintenanceTicket::as_select(), rooms::number))
+            .order((maintenance_tickets::severity.desc(), maintenance_tickets::created_at.asc()))
+            .load(&mut conn)
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(ticket, room_number)| TicketWithRoom { ticket, room_number })
+            .collect())
+    }
+
+    /// One ticket of a room
+    pub fn get_ticket(&self, room_id: Uuid, ticket_id: Uuid) -> AppResult<MaintenanceTicket> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Self::find_ticket(&mut conn, room_id, ticket_id)
+    }
+
+    /// Change a ticket. Resolving the room's last unresolved ticket offers to
+    /// return the room to Available, which happens only with `set_available`.
+    pub fn update_ticket(
+        &self,
+        room_id: Uuid,
+        ticket_id: Uuid,
+        request: &UpdateTicketRequest,
+        actor_user_id: Option<Uuid>,
+    ) -> AppResult<TicketUpdateResult> {
+        let title = request.title.as_deref().map(Self::validate_title).transpose()?;
+        let description = request
+            .description
+            .as_deref()
+            .map(|d| Self::validate_description(Some(d)))
+            .transpose()?;
+
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        // The room lock keeps two resolutions from both missing the other
+        conn.transaction::<_, AppError, _>(|conn| {
+            let room = BookingService::lock_room(conn, room_id)?;
+            let current = Self::find_ticket(conn, room_id, ticket_id)?;
+
+            let status = request.status.filter(|s| *s != current.status);
+            let resolved_at = status.map(|s| (s == TicketStatus::Resolved).then(Utc::now));
+            let changes = UpdateMaintenanceTicket {
+                title,
+                description,
+                severity: request.severity,
+                status,
+                resolved_at,
+            };
+
+            let ticket = if changes.title.is_none()
+                && changes.description.is_none()
+                && changes.severity.is_none()
+                && changes.status.is_none()
+            {
+                current
+            } else {
+                diesel::update(maintenance_tickets::table.find(ticket_id))
+                    .set(&changes)
+                    .get_result(conn)
+                    .map_err(|e| AppError::DatabaseError(e.to_string()))?
+            };
+
+            let can_set_available =
+                room.status == RoomStatus::Maintenance && !Self::has_unresolved_on(conn, room_id)?;
+            if !request.set_available {
+                return Ok(TicketUpdateResult {
+                    ticket,
+                    room,
+                    can_set_available,
+                });
+            }
+            if !can_set_available {
+                return Err(AppError::ValidationError(
+                    "The room can only return to available once its last open ticket is resolved"
+                        .to_string(),
+                ));
+            }
+
+            let room = RoomService::update_room_status_on(conn, room_id, RoomStatus::Available, actor_user_id)?;
+            Ok(TicketUpdateResult {
+                ticket,
+                room,
+                can_set_available: false,
+            })
+        })
+    }
+
+    /// Delete a ticket raised by mistake
+    pub fn delete_ticket(&self, room_id: Uuid, ticket_id: Uuid) -> AppResult<()> {
+        let mut conn = self
+            .pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let deleted = diesel::delete(
+            maintenance_tickets::table
+                .find(ticket_id)
+                .filter(maintenance_tickets::room_id.eq(room_id)),
+        )
+        .execute(&mut conn)
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        if deleted == 0 {
+            return Err(AppError::NotFound(format!("Ticket '{}' not found for this room", ticket_id)));
+        }
+        Ok(())
+    }
+
+    fn find_room(conn: &mut PgConnection, room_id: Uuid) -> AppResult<()> {
+        rooms::table
+            .find(room_id)
+            .select(rooms::id)
+            .first::<Uuid>(conn)
+            .optional()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?
+            .map(|_| ())
+            .ok_or_else(|| AppError::NotFound(format!("Room with ID '{}' not found", room_id)))
+    }
+
+    fn find_ticket(conn: &mut PgConnection, room_id: Uuid, ticket_id: Uuid) -> AppResult<MaintenanceTicket> {
+        maintenance_tickets::table
+            .find(ticket_id)
+            .filter(maintenance_tickets::room_id.eq(room_id))
+            .first(conn)
+            .optional()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?
+            .ok_or_else(|| AppError::NotFound(format!("Ticket '{}' not found for this room", ticket_id)))
+    }
+}
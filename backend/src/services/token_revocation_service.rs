@@ -0,0 +1,190 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::errors::{AppError, AppResult};
+use crate::models::NewRevokedToken;
+use crate::schema::{refresh_tokens, revoked_tokens, users};
+use crate::services::auth_service::Claims;
+
+/// How long a user's revocations are trusted before they are read again. A
+/// revocation made by another server takes at most this long to apply here.
+pub const REVOCATION_CACHE_TTL: Duration = Duration::from_secs(30);
+
+/// Refusal for an access token revoked before its expiry
+pub const TOKEN_REVOKED_MESSAGE: &str = "Token has been revoked";
+
+/// Users cached before stale entries are pruned
+const MAX_CACHED_USERS: usize = 10_000;
+
+/// Revocations of one user's access tokens
+#[derive(Debug, Default)]
+struct UserRevocations {
+    /// Tokens issued at or before this Unix time are revoked; JWTs only
+    /// carry whole seconds, so a token from the same second is refused too
+    not_before: Option<i64>,
+    jtis: HashSet<Uuid>,
+}
+
+impl UserRevocations {
+    fn revokes(&self, claims: &Claims) -> bool {
+        self.not_before.is_some_and(|not_before| claims.iat <= not_before)
+            || claims.jti.is_some_and(|jti| self.jtis.contains(&jti))
+    }
+}
+
+/// In-memory TTL cache of each user's revocations, so validating a token
+/// does not read the database on every request
+#[derive(Debug)]
+pub struct RevocationCache {
+    ttl: Duration,
+    users: Mutex<HashMap<Uuid, (Instant, Arc<UserRevocations>)>>,
+}
+
+impl Default for RevocationCache {
+    fn default() -> Self {
+        Self::new(REVOCATION_CACHE_TTL)
+    }
+}
+
+impl RevocationCache {
+    /// Cache revocations read from the database for `ttl`
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            users: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn get(&self, user_id: Uuid, now: Instant) -> Option<Arc<UserRevocations>> {
+        let users = self.users.lock().unwrap();
+        users
+            .get(&user_id)
+            .filter(|(loaded, _)| now.duration_since(*loaded) < self.ttl)
+            .map(|(_, revocations)| revocations.clone())
+    }
+
+    fn insert(&self, user_id: Uuid, revocations: Arc<UserRevocations>, now: Instant) {
+        let mut users = self.users.lock().unwrap();
+        if users.len() >= MAX_CACHED_USERS {
+            users.retain(|_, (loaded, _)| now.duration_since(*loaded) < self.ttl);
+        }
+        users.insert(user_id, (now, revocations));
+    }
+
+    /// Drop a user's entry, so their next token is checked against the database
+    pub fn forget(&self, user_id: Uuid) {
+        self.users.lock().unwrap().remove(&user_id);
+    }
+}
+
+/// Server-side revocation of access tokens before they expire, one at a time
+/// by JWT id or all of a user's at once
+pub struct TokenRevocationService {
+    pool: DbPool,
+    cache: Arc<RevocationCache>,
+}
+
+impl TokenRevocationService {
+    pub fn new(pool: DbPool, cache: Arc<RevocationCache>) -> Self {
+        Self { pool, cache }
+    }
+
+    fn conn(&self) -> AppResult<crate::db::DbConn> {
+        self.pool
+            .get()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
+    /// Refuse the claims of a revoked token
+    pub fn check(&self, claims: &Claims) -> AppResult<()> {
+        let now = Instant::now();
+        let revocations = match self.cache.get(claims.sub, now) {
+            Some(revocations) => revocations,
+            None => {
+                let revocations = Arc::new(self.load(claims.sub, Utc::now())?);
+                self.cache.insert(claims.sub, revocations.clone(), now);
+                revocations
+            }
+        };
+
+        if revocations.revokes(claims) {
+            return Err(AppError::Unauthorized(TOKEN_REVOKED_MESSAGE.to_string()));
+        }
+        Ok(())
+    }
+
+    fn load(&self, user_id: Uuid, now: DateTime<Utc>) -> AppResult<UserRevocations> {
+        let mut conn = self.conn()?;
+
+        let not_before: Option<DateTime<Utc>> = users::table
+            .find(user_id)
+            .select(users::tokens_not_before)
+            .first(&mut conn)
+            .optional()?
+            .flatten();
+        let jtis: Vec<Uuid> = revoked_tokens::table
+            .filter(revoked_tokens::user_id.eq(user_id))
+            .filter(revoked_tokens::expires_at.gt(now))
+            .select(revoked_tokens::jti)
+            .load(&mut conn)?;
+
+        Ok(UserRevocations {
+            not_before: not_before.map(|at| at.timestamp()),
+            jtis: jtis.into_iter().collect(),
+        })
+    }
+
+    /// Revoke one access token until it expires. A token without a JWT id
+    /// can only be revoked along with every token issued to its user so far.
+    pub fn revoke_token(&self, claims: &Claims) -> AppResult<()> {
+        let mut conn = self.conn()?;
+        let Some(jti) = claims.jti else {
+            diesel::update(users::table.find(claims.sub))
+                .set(users::tokens_not_before.eq(Some(Utc::now())))
+                .execute(&mut conn)?;
+            self.cache.forget(claims.sub);
+            return Ok(());
+        };
+        diesel::insert_into(revoked_tokens::table)
+            .values(&NewRevokedToken {
+                jti,
+                user_id: claims.sub,
+                expires_at: DateTime::from_timestamp(claims.exp, 0).unwrap_or_else(Utc::now),
+            })
+            .on_conflict_do_nothing()
+            .execute(&mut conn)?;
+        self.cache.forget(claims.sub);
+        Ok(())
+    }
+
+    /// Revoke every access token issued to a user so far, and their refresh
+    /// tokens so no new ones are issued from an old login
+    pub fn revoke_user(&self, user_id: Uuid) -> AppResult<()> {
+        let mut conn = self.conn()?;
+        conn.transaction(|conn| Self::revoke_user_on(conn, user_id, Utc::now()))?;
+        self.cache.forget(user_id);
+        Ok(())
+    }
+
+    /// `revoke_user` on the caller's connection; the caller forgets the
+    /// user's cache entry once the transaction commits
+    pub fn revoke_user_on(conn: &mut PgConnection, user_id: Uuid, now: DateTime<Utc>) -> AppResult<()> {
+        diesel::update(users::table.find(user_id))
+            .set(users::tokens_not_before.eq(Some(now)))
+            .execute(conn)?;
+        diesel::update(
+            refresh_tokens::table
+                .filter(refresh_tokens::user_id.eq(user_id))
+                .filter(refresh_tokens::revoked_at.is_null()),
+        )
+        .set(refresh_tokens::revoked_at.eq(now))
+        .execute(conn)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,597 @@
+//! Registry of known `system_settings` keys.
+//!
+//! Every setting is declared here with its type, validation and default.
+//! Writes are validated against the registry and reads go through the typed
+//! accessors on [`Settings`], so a bad stored value falls back to the default
+//! with a warning instead of being silently misread.
+
+use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
+
+use bigdecimal::BigDecimal;
+use chrono::{Duration, FixedOffset, NaiveTime, Utc};
+use chrono_tz::Tz;
+use diesel::prelude::*;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::errors::AppError;
+use crate::schema::system_settings;
+
+pub const AI_ENABLED: &str = "ai_enabled";
+pub const AI_PROVIDER: &str = "ai_provider";
+pub const AI_API_KEY: &str = "ai_api_key";
+pub const AI_MODEL: &str = "ai_model";
+pub const AI_BASE_URL: &str = "ai_base_url";
+pub const READ_ONLY_MODE: &str = "read_only_mode";
+pub const HOTEL_TIMEZONE: &str = "hotel_timezone";
+pub const HOTEL_NAME: &str = "hotel_name";
+pub const CASH_DISCREPANCY_THRESHOLD: &str = "cash_discrepancy_threshold";
+pub const CHECK_IN_TIME: &str = "check_in_time";
+pub const CHECK_OUT_TIME: &str = "check_out_time";
+pub const BOOKING_MIN_NIGHTS: &str = "booking_min_nights";
+pub const BOOKING_MAX_NIGHTS: &str = "booking_max_nights";
+pub const BOOKING_CANCELLATION_CUTOFF_HOURS: &str = "booking_cancellation_cutoff_hours";
+pub const SMTP_HOST: &str = "smtp_host";
+pub const SMTP_PORT: &str = "smtp_port";
+pub const SMTP_USERNAME: &str = "smtp_username";
+pub const SMTP_PASSWORD: &str = "smtp_password";
+pub const SMTP_FROM: &str = "smtp_from";
+pub const GUEST_PORTAL_URL: &str = "guest_portal_url";
+pub const HOLD_RETENTION: &str = "hold_retention";
+
+/// Value type of a setting and its constraints
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SettingType {
+    /// "true" or "false"
+    Bool,
+    /// Decimal number within optional bounds
+    Decimal { min: Option<i64>, max: Option<i64> },
+    /// Whole number within bounds
+    Integer { min: i64, max: i64 },
+    /// One of a fixed set of strings
+    Enum(&'static [&'static str]),
+    /// Duration such as "90s", "15m", "2h" or "1d"
+    Duration { min_secs: i64, max_secs: i64 },
+    /// IANA time zone such as "Asia/Ho_Chi_Minh"
+    TimeZone,
+    /// Local time of day such as "14:00"
+    TimeOfDay,
+    /// Free text up to a length
+    Text { max_len: usize },
+}
+
+/// Declaration of one known setting
+#[derive(Debug, Clone, Copy)]
+pub struct SettingDef {
+    pub key: &'static str,
+    pub setting_type: SettingType,
+    pub default: &'static str,
+    pub description: &'static str,
+    /// Value is never echoed back in listings
+    pub secret: bool,
+    /// False when the setting is managed by a dedicated endpoint
+    pub writable: bool,
+}
+
+/// All known settings
+pub const REGISTRY: &[SettingDef] = &[
+    SettingDef {
+        key: AI_ENABLED,
+        setting_type: SettingType::Bool,
+        default: "false",
+        description: "Master switch for AI chatbot features",
+        secret: false,
+        writable: true,
+    },
+    SettingDef {
+        key: AI_PROVIDER,
+        setting_type: SettingType::Enum(&["openai", "gemini"]),
+        default: "openai",
+        description: "AI provider",
+        secret: false,
+        writable: true,
+    },
+    SettingDef {
+        key: AI_API_KEY,
+        setting_type: SettingType::Text { max_len: 500 },
+        default: "",
+        description: "API key for the AI provider",
+        secret: true,
+        writable: true,
+    },
+    SettingDef {
+        key: AI_MODEL,
+        setting_type: SettingType::Text { max_len: 100 },
+        default: "gpt-3.5-turbo",
+        description: "Model identifier to use",
+        secret: false,
+        writable: true,
+    },
+    SettingDef {
+        key: AI_BASE_URL,
+        setting_type: SettingType::Text { max_len: 255 },
+        default: "https://api.openai.com/v1",
+        description: "Base URL of an OpenAI-compatible API",
+        secret: false,
+        writable: true,
+    },
+    SettingDef {
+        key: READ_ONLY_MODE,
+        setting_type: SettingType::Bool,
+        default: "false",
+        description: "Refuse writes while database or storage maintenance is in progress",
+        secret: false,
+        writable: false,
+    },
+    SettingDef {
+        key: HOTEL_TIMEZONE,
+        setting_type: SettingType::TimeZone,
+        default: "Asia/Ho_Chi_Minh",
+        description: "Hotel time zone as an IANA name, e.g. Asia/Ho_Chi_Minh",
+        secret: false,
+        writable: true,
+    },
+    SettingDef {
+        key: HOTEL_NAME,
+        setting_type: SettingType::Text { max_len: 100 },
+        default: "Pupinn Hotel",
+        description: "Hotel name shown to guests, e.g. in calendar files",
+        secret: false,
+        writable: true,
+    },
+    SettingDef {
+        key: CASH_DISCREPANCY_THRESHOLD,
+        setting_type: SettingType::Decimal { min: Some(0), max: None },
+        default: "100000",
+        description: "Notify admins when a cash count differs from the expected amount by more than this (VND)",
+        secret: false,
+        writable: true,
+    },
+    SettingDef {
+        key: CHECK_IN_TIME,
+        setting_type: SettingType::TimeOfDay,
+        default: "14:00",
+        description: "Standard check-in time (hotel local time)",
+        secret: false,
+        writable: true,
+    },
+    SettingDef {
+        key: CHECK_OUT_TIME,
+        setting_type: SettingType::TimeOfDay,
+        default: "12:00",
+        description: "Standard check-out time (hotel local time)",
+        secret: false,
+        writable: true,
+    },
+    SettingDef {
+        key: BOOKING_MIN_NIGHTS,
+        setting_type: SettingType::Integer { min: 1, max: 365 },
+        default: "1",
+        description: "Shortest stay that can be booked, in nights",
+        secret: false,
+        writable: true,
+    },
+    SettingDef {
+        key: BOOKING_MAX_NIGHTS,
+        setting_type: SettingType::Integer { min: 1, max: 365 },
+        default: "30",
+        description: "Longest stay that can be booked, in nights",
+        secret: false,
+        writable: true,
+    },
+    SettingDef {
+        key: BOOKING_CANCELLATION_CUTOFF_HOURS,
+        setting_type: SettingType::Integer { min: 0, max: 720 },
+        default: "24",
+        description: "Guests cannot cancel online within this many hours of the check-in time",
+        secret: false,
+        writable: true,
+    },
+    SettingDef {
+        key: SMTP_HOST,
+        setting_type: SettingType::Text { max_len: 255 },
+        default: "",
+        description: "SMTP server for guest booking emails; emails are only logged when empty",
+        secret: false,
+        writable: true,
+    },
+    SettingDef {
+        key: SMTP_PORT,
+        setting_type: SettingType::Integer { min: 1, max: 65535 },
+        default: "587",
+        description: "SMTP server port (STARTTLS)",
+        secret: false,
+        writable: true,
+    },
+    SettingDef {
+        key: SMTP_USERNAME,
+        setting_type: SettingType::Text { max_len: 255 },
+        default: "",
+        description: "SMTP login; leave empty for an unauthenticated relay",
+        secret: false,
+        writable: true,
+    },
+    SettingDef {
+        key: SMTP_PASSWORD,
+        setting_type: SettingType::Text { max_len: 255 },
+        default: "",
+        description: "SMTP password",
+        secret: true,
+        writable: true,
+    },
+    SettingDef {
+        key: SMTP_FROM,
+        setting_type: SettingType::Text { max_len: 255 },
+        default: "Pupinn Hotel <no-reply@pupinn.local>",
+        description: "Sender address of guest booking emails",
+        secret: false,
+        writable: true,
+    },
+    SettingDef {
+        key: GUEST_PORTAL_URL,
+        setting_type: SettingType::Text { max_len: 255 },
+        default: "http://localhost:3000",
+        description: "Address of the guest portal, used for links in guest emails",
+        secret: false,
+        writable: true,
+    },
+    SettingDef {
+        key: HOLD_RETENTION,
+        setting_type: SettingType::Duration { min_secs: 0, max_secs: 30 * 86_400 },
+        default: "1d",
+        description: "Expired booking holds are kept this long for review before the cleanup job deletes them",
+        secret: false,
+        writable: true,
+    },
+];
+
+/// Look up a setting declaration
+pub fn definition(key: &str) -> Option<&'static SettingDef> {
+    REGISTRY.iter().find(|d| d.key == key)
+}
+
+fn registered(key: &str) -> &'static SettingDef {
+    definition(key).unwrap_or_else(|| panic!("setting '{}' is not registered", key))
+}
+
+/// Parse a duration such as "90s", "15m", "2h", "1d" or a bare number of seconds
+pub fn parse_duration(value: &str) -> Option<Duration> {
+    let value = value.trim().to_ascii_lowercase();
+    let (digits, unit_secs) = match value.chars().last()? {
+        's' => (&value[..value.len() - 1], 1),
+        'm' => (&value[..value.len() - 1], 60),
+        'h' => (&value[..value.len() - 1], 3600),
+        'd' => (&value[..value.len() - 1], 86_400),
+        c if c.is_ascii_digit() => (value.as_str(), 1),
+        _ => return None,
+    };
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let amount: i64 = digits.parse().ok()?;
+    Some(Duration::seconds(amount.checked_mul(unit_secs)?))
+}
+
+/// Parse a UTC offset such as "+07:00", "-05:30" or "UTC"
+pub fn parse_utc_offset(value: &str) -> Option<FixedOffset> {
+    let value = value.trim();
+    if value.eq_ignore_ascii_case("utc") || value == "Z" {
+        return FixedOffset::east_opt(0);
+    }
+
+    let (sign, rest) = match value.as_bytes().first()? {
+        b'+' => (1, &value[1..]),
+        b'-' => (-1, &value[1..]),
+        _ => return None,
+    };
+    let (hours, minutes) = match rest.split_once(':') {
+        Some((h, m)) => (h.parse::<i32>().ok()?, m.parse::<i32>().ok()?),
+        None => (rest.parse::<i32>().ok()?, 0),
+    };
+    if hours > 14 || minutes >= 60 {
+        return None;
+    }
+
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
+
+/// Parse an IANA time zone name such as "Asia/Ho_Chi_Minh" (any case). A
+/// whole-hour UTC offset such as "+07:00", as stored before time zone names
+/// were supported, maps to the matching fixed "Etc/GMT" zone.
+pub fn parse_time_zone(value: &str) -> Option<Tz> {
+    let value = value.trim();
+    if let Ok(tz) = Tz::from_str_insensitive(value) {
+        return Some(tz);
+    }
+
+    let seconds = parse_utc_offset(value)?.local_minus_utc();
+    if seconds % 3600 != 0 {
+        return None;
+    }
+    match seconds / 3600 {
+        0 => Some(Tz::UTC),
+        // The Etc/GMT names count hours west of Greenwich, so the sign flips
+        hours => format!("Etc/GMT{:+}", -hours).parse().ok(),
+    }
+}
+
+/// Parse a time of day such as "14:00" or "09:30"
+pub fn parse_time_of_day(value: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(value.trim(), "%H:%M").ok()
+}
+
+/// Check a raw value against a setting's type, returning the normalized
+/// string to store or a message describing the problem
+pub fn validate_value(def: &SettingDef, value: &Value) -> Result<String, String> {
+    let text = match value {
+        Value::String(s) => s.trim().to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => n.to_string(),
+        _ => return Err("must be a string, number or boolean".to_string()),
+    };
+
+    match def.setting_type {
+        SettingType::Bool => match text.to_ascii_lowercase().as_str() {
+            "true" => Ok("true".to_string()),
+            "false" => Ok("false".to_string()),
+            _ => Err("must be true or false".to_string()),
+        },
+        SettingType::Decimal { min, max } => {
+            let number = BigDecimal::from_str(&text).map_err(|_| "must be a number".to_string())?;
+            if let Some(min) = min {
+                if number < min {
+                    return Err(format!("must be at least {}", min));
+                }
+            }
+            if let Some(max) = max {
+                if number > max {
+                    return Err(format!("must be at most {}", max));
+                }
+            }
+            Ok(number.to_string())
+        }
+        SettingType::Integer { min, max } => {
+            let number: i64 = text.parse().map_err(|_| "must be a whole number".to_string())?;
+            if number < min || number > max {
+                return Err(format!("must be between {} and {}", min, max));
+            }
+            Ok(number.to_string())
+        }
+        SettingType::Enum(options) => {
+            let lowered = text.to_ascii_lowercase();
+            options
+                .iter()
+                .find(|o| **o == lowered)
+                .map(|o| o.to_string())
+                .ok_or_else(|| format!("must be one of: {}", options.join(", ")))
+        }
+        SettingType::Duration { min_secs, max_secs } => {
+            let duration = parse_duration(&text)
+                .ok_or_else(|| "must be a duration such as 90s, 15m, 2h or 1d".to_string())?;
+            if duration.num_seconds() < min_secs || duration.num_seconds() > max_secs {
+                return Err(format!(
+                    "must be between {} and {} seconds",
+                    min_secs, max_secs
+                ));
+            }
+            Ok(text.to_ascii_lowercase())
+        }
+        SettingType::TimeZone => parse_time_zone(&text)
+            .map(|tz| tz.name().to_string())
+            .ok_or_else(|| "must be a time zone name such as Asia/Ho_Chi_Minh".to_string()),
+        SettingType::TimeOfDay => parse_time_of_day(&text)
+            .map(|time| time.format("%H:%M").to_string())
+            .ok_or_else(|| "must be a time of day such as 14:00".to_string()),
+        SettingType::Text { max_len } => {
+            if text.chars().count() > max_len {
+                Err(format!("must be at most {} characters", max_len))
+            } else {
+                Ok(text)
+            }
+        }
+    }
+}
+
+/// Validate a batch of setting updates. Unknown keys, settings managed
+/// elsewhere and type errors are all reported together, keyed by setting.
+pub fn validate_updates(
+    updates: &serde_json::Map<String, Value>,
+) -> Result<Vec<(&'static str, String)>, AppError> {
+    let mut valid = Vec::new();
+    let mut errors = BTreeMap::new();
+
+    for (key, value) in updates {
+        let Some(def) = definition(key) else {
+            errors.insert(key.clone(), "unknown setting".to_string());
+            continue;
+        };
+        if !def.writable {
+            errors.insert(key.clone(), "is managed by its own endpoint".to_string());
+            continue;
+        }
+        match validate_value(def, value) {
+            Ok(normalized) => valid.push((def.key, normalized)),
+            Err(msg) => {
+                errors.insert(key.clone(), msg);
+            }
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(valid)
+    } else {
+        Err(AppError::FieldErrors(errors))
+    }
+}
+
+/// Upsert validated settings
+pub fn save(conn: &mut PgConnection, values: &[(&'static str, String)]) -> QueryResult<()> {
+    for (key, value) in values {
+        let description = definition(key).map(|d| d.description);
+        diesel::insert_into(system_settings::table)
+            .values((
+                system_settings::key.eq(key),
+                system_settings::value.eq(value),
+                system_settings::description.eq(description),
+                system_settings::updated_at.eq(Utc::now()),
+            ))
+            .on_conflict(system_settings::key)
+            .do_update()
+            .set((
+                system_settings::value.eq(value),
+                system_settings::updated_at.eq(Utc::now()),
+            ))
+            .execute(conn)?;
+    }
+    Ok(())
+}
+
+/// Form description of a setting for the admin UI
+#[derive(Debug, Clone, Serialize)]
+pub struct SettingSchema {
+    pub key: &'static str,
+    #[serde(rename = "type")]
+    pub setting_type: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub options: Option<&'static [&'static str]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_length: Option<usize>,
+    pub default: &'static str,
+    pub description: &'static str,
+    pub secret: bool,
+    pub writable: bool,
+}
+
+/// Schema of every registered setting
+pub fn schema() -> Vec<SettingSchema> {
+    REGISTRY
+        .iter()
+        .map(|def| {
+            let mut schema = SettingSchema {
+                key: def.key,
+                setting_type: "string",
+                options: None,
+                min: None,
+                max: None,
+                max_length: None,
+                default: def.default,
+                description: def.description,
+                secret: def.secret,
+                writable: def.writable,
+            };
+            match def.setting_type {
+                SettingType::Bool => schema.setting_type = "bool",
+                SettingType::Decimal { min, max } => {
+                    schema.setting_type = "decimal";
+                    schema.min = min;
+                    schema.max = max;
+                }
+                SettingType::Integer { min, max } => {
+                    schema.setting_type = "integer";
+                    schema.min = Some(min);
+                    schema.max = Some(max);
+                }
+                SettingType::Enum(options) => {
+                    schema.setting_type = "enum";
+                    schema.options = Some(options);
+                }
+                SettingType::Duration { min_secs, max_secs } => {
+                    schema.setting_type = "duration";
+                    schema.min = Some(min_secs);
+                    schema.max = Some(max_secs);
+                }
+                SettingType::TimeZone => schema.setting_type = "time_zone",
+                SettingType::TimeOfDay => schema.setting_type = "time_of_day",
+                SettingType::Text { max_len } => schema.max_length = Some(max_len),
+            }
+            schema
+        })
+        .collect()
+}
+
+/// Snapshot of stored settings with typed, default-aware accessors
+#[derive(Debug, Clone, Default)]
+pub struct Settings {
+    values: HashMap<String, String>,
+}
+
+impl Settings {
+    /// Load every stored setting
+    pub fn load(conn: &mut PgConnection) -> QueryResult<Self> {
+        let rows: Vec<(String, String)> = system_settings::table
+            .select((system_settings::key, system_settings::value))
+            .load(conn)?;
+        Ok(Self::from_values(rows.into_iter().collect()))
+    }
+
+    /// Build a snapshot from raw key/value pairs
+    pub fn from_values(values: HashMap<String, String>) -> Self {
+        Self { values }
+    }
+
+    /// Stored value if it validates against the registry, otherwise the default
+    fn valid_value(&self, key: &str) -> String {
+        let def = registered(key);
+        match self.values.get(key) {
+            Some(raw) => match validate_value(def, &Value::String(raw.clone())) {
+                Ok(value) => value,
+                Err(msg) => {
+                    tracing::warn!(
+                        "Setting '{}' has invalid value '{}' ({}), using default '{}'",
+                        key,
+                        if def.secret { "***" } else { raw },
+                        msg,
+                        def.default
+                    );
+                    def.default.to_string()
+                }
+            },
+            None => def.default.to_string(),
+        }
+    }
+
+    /// Raw value as stored, for listings (no validation or defaults)
+    pub fn raw(&self, key: &str) -> Option<&str> {
+        self.values.get(key).map(String::as_str)
+    }
+
+    /// Value of a bool setting
+    pub fn bool(&self, key: &str) -> bool {
+        self.valid_value(key) == "true"
+    }
+
+    /// Value of a decimal setting
+    pub fn decimal(&self, key: &str) -> BigDecimal {
+        BigDecimal::from_str(&self.valid_value(key)).expect("validated decimal")
+    }
+
+    /// Value of an integer setting
+    pub fn integer(&self, key: &str) -> i64 {
+        self.valid_value(key).parse().expect("validated integer")
+    }
+
+    /// Value of a text or enum setting
+    pub fn text(&self, key: &str) -> String {
+        self.valid_value(key)
+    }
+
+    /// Value of a duration setting
+    pub fn duration(&self, key: &str) -> Duration {
+        parse_duration(&self.valid_value(key)).expect("validated duration")
+    }
+
+    /// Value of a time zone setting
+    pub fn time_zone(&self, key: &str) -> Tz {
+        parse_time_zone(&self.valid_value(key)).expect("validated time zone")
+    }
+
+    /// Value of a time of day setting
+    pub fn time_of_day(&self, key: &str) -> NaiveTime {
+        parse_time_of_day(&self.valid_value(key)).expect("validated time of day")
+    }
+}
@@ -0,0 +1,129 @@
+use std::future::Future;
+use std::time::Duration;
+
+use aws_sdk_s3::Client;
+use diesel::pg::PgConnection;
+use diesel::sql_query;
+use diesel::{Connection, RunQueryDsl};
+use tokio::time::Instant;
+
+/// Longest a single database probe may take, well below the startup deadline
+/// so `wait_for` keeps to its backoff schedule
+pub const DATABASE_PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Exponential backoff schedule used by the startup probes
+#[derive(Debug, Clone, Copy)]
+pub struct Backoff {
+    pub initial: Duration,
+    pub max: Duration,
+    pub factor: u32,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self {
+            initial: Duration::from_millis(500),
+            max: Duration::from_secs(10),
+            factor: 2,
+        }
+    }
+}
+
+impl Backoff {
+    /// Delay to wait after the given (zero-based) failed attempt, capped at `max`
+    pub fn delay_for(&self, attempt: u32) -> Duration {
+        let multiplier = self.factor.max(1).saturating_pow(attempt);
+        self.initial
+            .checked_mul(multiplier)
+            .unwrap_or(self.max)
+            .min(self.max)
+    }
+}
+
+/// Retry `probe` with backoff until it succeeds or `deadline` has elapsed
+///
+/// # Arguments
+/// * `name` - Dependency name used in log messages
+/// * `deadline` - Total time allowed for the dependency to become ready
+/// * `backoff` - Delay schedule between attempts
+/// * `probe` - Async check returning an error message while not ready
+///
+/// # Returns
+/// * `Ok(attempts)` - Number of attempts it took
+/// * `Err(message)` - The last probe error once the deadline is exceeded
+pub async fn wait_for<F, Fut>(
+    name: &str,
+    deadline: Duration,
+    backoff: Backoff,
+    mut probe: F,
+) -> Result<u32, String>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<(), String>>,
+{
+    let started = Instant::now();
+    let mut attempt: u32 = 0;
+
+    loop {
+        attempt += 1;
+        match probe().await {
+            Ok(()) => {
+                tracing::info!("{} is ready (attempt {})", name, attempt);
+                return Ok(attempt);
+            }
+            Err(e) => {
+                let elapsed = started.elapsed();
+                if elapsed >= deadline {
+                    return Err(format!(
+                        "{} not ready after {}s ({} attempts): {}",
+                        name,
+                        elapsed.as_secs(),
+                        attempt,
+                        e
+                    ));
+                }
+
+                let delay = backoff.delay_for(attempt - 1).min(deadline - elapsed);
+                tracing::warn!(
+                    "{} not ready (attempt {}): {}. Retrying in {}ms",
+                    name,
+                    attempt,
+                    e,
+                    delay.as_millis()
+                );
+                tokio::time::sleep(delay).await;
+            }
+        }
+    }
+}
+
+/// Check that Postgres accepts queries (`SELECT 1`) on a connection of its
+/// own. The app pool waits up to 30s for a connection, so the probe gives up
+/// after `DATABASE_PROBE_TIMEOUT` instead.
+pub async fn probe_database(database_url: String) -> Result<(), String> {
+    let probe = tokio::task::spawn_blocking(move || {
+        let mut conn = PgConnection::establish(&database_url).map_err(|e| e.to_string())?;
+        sql_query("SELECT 1")
+            .execute(&mut conn)
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    });
+
+    match tokio::time::timeout(DATABASE_PROBE_TIMEOUT, probe).await {
+        Ok(result) => result.map_err(|e| e.to_string())?,
+        Err(_) => Err(format!(
+            "no answer within {}s",
+            DATABASE_PROBE_TIMEOUT.as_secs()
+        )),
+    }
+}
+
+/// Check that the S3 endpoint (MinIO) is listening and accepts our credentials
+pub async fn probe_s3(client: Client) -> Result<(), String> {
+    client
+        .list_buckets()
+        .send()
+        .await
+        .map(|_| ())
+        .map_err(|e| format!("{:?}", e))
+}
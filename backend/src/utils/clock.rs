@@ -0,0 +1,59 @@
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use chrono::offset::LocalResult;
+use chrono::{DateTime, Duration, FixedOffset, NaiveDate, NaiveDateTime, Offset, TimeZone, Utc};
+use chrono_tz::Tz;
+
+/// Source of the current time, so date rules can be tested at fixed instants
+pub trait Clock: Debug + Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// The real wall clock
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A clock stopped at one instant
+#[derive(Debug, Clone, Copy)]
+pub struct FixedClock(pub DateTime<Utc>);
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.0
+    }
+}
+
+/// Shared handle to the wall clock
+pub fn system_clock() -> Arc<dyn Clock> {
+    Arc::new(SystemClock)
+}
+
+/// Calendar date at `now` in the time zone `tz`
+pub fn local_date(now: DateTime<Utc>, tz: Tz) -> NaiveDate {
+    now.with_timezone(&tz).date_naive()
+}
+
+/// UTC instant of a local date and time in `tz`. An ambiguous time (clocks
+/// turned back) takes the earlier instant; a time skipped when clocks go
+/// forward is read with the offset in force the day before.
+pub fn local_to_utc(tz: Tz, local: NaiveDateTime) -> DateTime<Utc> {
+    match tz.from_local_datetime(&local) {
+        LocalResult::Single(at) | LocalResult::Ambiguous(at, _) => at.with_timezone(&Utc),
+        LocalResult::None => {
+            let offset = tz.offset_from_utc_datetime(&(local - Duration::days(1))).fix();
+            (local - offset).and_utc()
+        }
+    }
+}
+
+/// UTC offset of `tz` at the instant `at`, for code that works with fixed offsets
+pub fn offset_at(tz: Tz, at: DateTime<Utc>) -> FixedOffset {
+    tz.offset_from_utc_datetime(&at.naive_utc()).fix()
+}
@@ -0,0 +1,85 @@
+use chrono::{DateTime, NaiveDate, Utc};
+
+/// Identifies this application as the producer of calendar files
+const PRODUCT_ID: &str = "-//Pupinn//Hotel Management//EN";
+/// Longest content line in octets before it is folded (RFC 5545 §3.1)
+const MAX_LINE_OCTETS: usize = 75;
+
+/// An all-day calendar event, e.g. a stay from check-in to check-out
+#[derive(Debug, Clone)]
+pub struct IcsEvent<'a> {
+    pub uid: &'a str,
+    pub summary: &'a str,
+    pub description: Option<&'a str>,
+    /// First day of the event
+    pub start: NaiveDate,
+    /// Day after the last day of the event (DTEND is exclusive)
+    pub end: NaiveDate,
+    /// When the file was generated
+    pub stamp: DateTime<Utc>,
+}
+
+/// Escape a TEXT value: backslashes, semicolons, commas and line breaks
+pub fn escape_text(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\r' => {
+                if chars.peek() == Some(&'\n') {
+                    chars.next();
+                }
+                escaped.push_str("\\n");
+            }
+            '\n' => escaped.push_str("\\n"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Fold a content line into CRLF-terminated lines of at most 75 octets,
+/// continuation lines starting with a space. Never splits a UTF-8 character.
+pub fn fold_line(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len() + 8);
+    let mut octets = 0;
+    for c in line.chars() {
+        if octets + c.len_utf8() > MAX_LINE_OCTETS {
+            folded.push_str("\r\n ");
+            // The leading space counts towards the continuation line
+            octets = 1;
+        }
+        folded.push(c);
+        octets += c.len_utf8();
+    }
+    folded.push_str("\r\n");
+    folded
+}
+
+/// Render a single all-day event as an iCalendar file
+pub fn render_event(event: &IcsEvent) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        format!("PRODID:{}", PRODUCT_ID),
+        "CALSCALE:GREGORIAN".to_string(),
+        "METHOD:PUBLISH".to_string(),
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:{}", escape_text(event.uid)),
+        format!("DTSTAMP:{}", event.stamp.format("%Y%m%dT%H%M%SZ")),
+        format!("DTSTART;VALUE=DATE:{}", event.start.format("%Y%m%d")),
+        format!("DTEND;VALUE=DATE:{}", event.end.format("%Y%m%d")),
+        format!("SUMMARY:{}", escape_text(event.summary)),
+    ];
+    if let Some(description) = event.description {
+        lines.push(format!("DESCRIPTION:{}", escape_text(description)));
+    }
+    lines.push("TRANSP:OPAQUE".to_string());
+    lines.push("END:VEVENT".to_string());
+    lines.push("END:VCALENDAR".to_string());
+
+    lines.iter().map(|line| fold_line(line)).collect()
+}
@@ -1,3 +1,8 @@
+pub mod clock;
+pub mod csv;
+pub mod ics;
+pub mod money;
+pub mod redact;
 pub mod validation;
 
 pub use validation::*;
@@ -0,0 +1,66 @@
+use std::collections::BTreeMap;
+
+use bigdecimal::{BigDecimal, RoundingMode, Signed};
+
+use crate::errors::{AppError, AppResult};
+
+/// Largest amount accepted from clients, in VND (100 billion)
+pub const MAX_VND_AMOUNT: i64 = 100_000_000_000;
+
+/// Check a VND amount: non-negative, whole dong and at most `max`
+///
+/// # Returns
+/// * `Ok(amount)` - The amount at scale 0, e.g. "1.5E+6" becomes "1500000"
+/// * `Err(message)` - What is wrong, phrased to follow a field name
+pub fn validate_vnd_amount(amount: &BigDecimal, max: &BigDecimal) -> Result<BigDecimal, String> {
+    if amount.is_negative() {
+        return Err("must not be negative".to_string());
+    }
+    let whole = amount.with_scale(0);
+    if whole != *amount {
+        return Err("must be a whole VND amount".to_string());
+    }
+    if whole > *max {
+        return Err(format!("must be at most {}", format_vnd(max)));
+    }
+    Ok(whole)
+}
+
+/// Error reporting `message` against one request field
+pub(crate) fn field_error(field: &str, message: String) -> AppError {
+    AppError::FieldErrors(BTreeMap::from([(field.to_string(), message)]))
+}
+
+/// Validate a client-supplied amount, reporting problems against `field`
+pub fn vnd_field(field: &str, amount: &BigDecimal) -> AppResult<BigDecimal> {
+    validate_vnd_amount(amount, &BigDecimal::from(MAX_VND_AMOUNT))
+        .map_err(|message| field_error(field, message))
+}
+
+/// Validate a signed amount such as a refund: the magnitude is checked like
+/// [`vnd_field`] and the sign is kept
+pub fn signed_vnd_field(field: &str, amount: &BigDecimal) -> AppResult<BigDecimal> {
+    let magnitude = vnd_field(field, &amount.abs())?;
+    Ok(if amount.is_negative() { -magnitude } else { magnitude })
+}
+
+/// Format an amount for people, rounded to whole dong with thousands
+/// separators, e.g. "1,500,000 VND"
+pub fn format_vnd(amount: &BigDecimal) -> String {
+    let whole = amount.with_scale_round(0, RoundingMode::HalfUp);
+    let digits = whole.abs().to_string();
+
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+
+    if whole.is_negative() {
+        format!("-{} VND", grouped)
+    } else {
+        format!("{} VND", grouped)
+    }
+}
@@ -26,7 +26,7 @@ pub fn validate_username(username: &str) -> AppResult<()> {
     }
 
     // Must start with letter or number
-    if !username.chars().next().map_or(false, |c| c.is_alphanumeric()) {
+    if !username.chars().next().is_some_and(|c| c.is_alphanumeric()) {
         return Err(AppError::ValidationError(
             "Username must start with a letter or number".to_string(),
         ));
@@ -121,7 +121,7 @@ pub fn validate_phone(phone: &str) -> AppResult<()> {
     }
 
     // Must start with + or digit
-    if !phone.starts_with('+') && !phone.chars().next().map_or(false, |c| c.is_ascii_digit()) {
+    if !phone.starts_with('+') && !phone.chars().next().is_some_and(|c| c.is_ascii_digit()) {
         return Err(AppError::ValidationError(
             "Phone number must start with a digit or +".to_string(),
         ));
@@ -180,7 +180,8 @@ pub fn validate_date_format(date_str: &str) -> AppResult<()> {
     Ok(())
 }
 
-/// Validate search query
+/// Validate search query. A single digit is enough, so typing the first
+/// digit of a room number already searches.
 pub fn validate_search_query(query: &str) -> AppResult<()> {
     let query = query.trim();
 
@@ -190,7 +191,7 @@ pub fn validate_search_query(query: &str) -> AppResult<()> {
         ));
     }
 
-    if query.len() < 2 {
+    if query.len() < 2 && !query.chars().all(|c| c.is_ascii_digit()) {
         return Err(AppError::ValidationError(
             "Search query must be at least 2 characters".to_string(),
         ));
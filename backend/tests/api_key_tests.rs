@@ -0,0 +1,326 @@
+//! API key tests
+//!
+//! Tests for the keys integrations use instead of a staff login: admins
+//! create, list and revoke them, and `Authorization: ApiKey <key>` acts
+//! with the key's role. They need a migrated PostgreSQL database and only
+//! run when TEST_DATABASE_URL is set.
+
+use std::sync::Arc;
+
+use axum::body::{to_bytes, Body};
+use axum::http::{header::AUTHORIZATION, Method, Request, StatusCode};
+use chrono::{Duration, Utc};
+use diesel::prelude::*;
+use jsonwebtoken::{encode, EncodingKey, Header};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use hotel_management_backend::api::public_bookings::PublicLookupLimits;
+use hotel_management_backend::api::{chat::ChatState, create_router, AppState};
+use hotel_management_backend::config::BodyLimits;
+use hotel_management_backend::db::{create_pool, DbPool};
+use hotel_management_backend::errors::AppError;
+use hotel_management_backend::models::UserRole;
+use hotel_management_backend::scheduler::JobBoard;
+use hotel_management_backend::schema::{api_keys, users};
+use hotel_management_backend::services::api_key_service::{CreateApiKeyRequest, API_KEY_PREFIX};
+use hotel_management_backend::services::auth_service::{
+    Claims, CreateUserRequest, DEFAULT_ACCESS_TOKEN_LIFETIME, DEFAULT_JWT_ISSUER,
+};
+use hotel_management_backend::services::rate_limit_service::InMemoryLoginAttempts;
+use hotel_management_backend::services::{
+    ApiKeyService, AuthService, ReadOnlyMode, RevocationCache, SessionTracker, TokenIssuer,
+};
+
+const JWT_SECRET: &str = "test-secret";
+
+fn test_pool() -> Option<DbPool> {
+    match std::env::var("TEST_DATABASE_URL") {
+        Ok(url) => Some(create_pool(&url)),
+        Err(_) => {
+            eprintln!("TEST_DATABASE_URL not set, skipping database test");
+            None
+        }
+    }
+}
+
+/// Id of a new receptionist to own keys
+fn creator(pool: &DbPool) -> Uuid {
+    AuthService::new(pool.clone(), JWT_SECRET.to_string())
+        .create_user(&CreateUserRequest {
+            username: format!("apikey-{}", &Uuid::new_v4().simple().to_string()[..8]),
+            password: "apikey-password-1".to_string(),
+            role: UserRole::Receptionist,
+            must_change_password: false,
+        })
+        .unwrap()
+        .id
+}
+
+fn request(label: &str, role: UserRole) -> CreateApiKeyRequest {
+    CreateApiKeyRequest {
+        label: label.to_string(),
+        role,
+    }
+}
+
+mod service_tests {
+    use super::*;
+
+    #[test]
+    fn test_created_key_is_only_stored_hashed() {
+        let Some(pool) = test_pool() else { return };
+        let created = ApiKeyService::new(pool.clone())
+            .create(&request(" Channel manager ", UserRole::Receptionist), creator(&pool))
+            .unwrap();
+
+        assert!(created.key.starts_with(API_KEY_PREFIX));
+        assert!(created.key.starts_with(&created.api_key.key_prefix));
+        assert_eq!(created.api_key.label, "Channel manager");
+        assert_eq!(created.api_key.key_hash, AuthService::hash_token(&created.key));
+        assert_ne!(created.api_key.key_hash, created.key);
+
+        let json = serde_json::to_value(&created).unwrap();
+        assert_eq!(json["key"], created.key);
+        assert!(json.get("key_hash").is_none());
+        let listed = ApiKeyService::new(pool.clone()).list().unwrap();
+        assert!(listed.iter().any(|key| key.id == created.api_key.id));
+    }
+
+    #[test]
+    fn test_keys_cannot_be_admins_or_guests() {
+        let Some(pool) = test_pool() else { return };
+        let owner = creator(&pool);
+        for role in [UserRole::Admin, UserRole::Guest, UserRole::Bot] {
+            let err = ApiKeyService::new(pool.clone())
+                .create(&request("Integration", role), owner)
+                .unwrap_err();
+            assert!(matches!(err, AppError::ValidationError(_)), "{:?}", err);
+        }
+        let err = ApiKeyService::new(pool.clone())
+            .create(&request("  ", UserRole::Cleaner), owner)
+            .unwrap_err();
+        assert!(matches!(err, AppError::ValidationError(_)), "{:?}", err);
+    }
+
+    #[test]
+    fn test_last_used_is_written_at_most_once_a_minute() {
+        let Some(pool) = test_pool() else { return };
+        let keys = ApiKeyService::new(pool.clone());
+        let created = keys.create(&request("Poller", UserRole::Receptionist), creator(&pool)).unwrap();
+        let start = Utc::now();
+
+        assert_eq!(keys.authenticate(&created.key, start).unwrap().id, created.api_key.id);
+        keys.authenticate(&created.key, start + Duration::seconds(30)).unwrap();
+        let last_used = keys.list().unwrap().into_iter().find(|key| key.id == created.api_key.id).unwrap().last_used_at;
+        assert_eq!(last_used.map(|at| at.timestamp_micros()), Some(start.timestamp_micros()));
+
+        let later = start + Duration::seconds(61);
+        keys.authenticate(&created.key, later).unwrap();
+        let last_used = keys.list().unwrap().into_iter().find(|key| key.id == created.api_key.id).unwrap().last_used_at;
+        assert_eq!(last_used.map(|at| at.timestamp_micros()), Some(later.timestamp_micros()));
+    }
+
+    #[test]
+    fn test_revoked_keys_and_keys_of_deactivated_creators_stop_working() {
+        let Some(pool) = test_pool() else { return };
+        let keys = ApiKeyService::new(pool.clone());
+        let owner = creator(&pool);
+        let revoked = keys.create(&request("Old integration", UserRole::Cleaner), owner).unwrap();
+        let orphaned = keys.create(&request("Other integration", UserRole::Cleaner), owner).unwrap();
+
+        let first = keys.revoke(revoked.api_key.id).unwrap().revoked_at.unwrap();
+        assert_eq!(keys.revoke(revoked.api_key.id).unwrap().revoked_at, Some(first));
+        let err = keys.authenticate(&revoked.key, Utc::now()).unwrap_err();
+        assert!(matches!(err, AppError::Unauthorized(_)), "{:?}", err);
+
+        diesel::update(users::table.find(owner))
+            .set(users::deactivated_at.eq(Some(Utc::now())))
+            .execute(&mut pool.get().unwrap())
+            .unwrap();
+        let err = keys.authenticate(&orphaned.key, Utc::now()).unwrap_err();
+        assert!(matches!(err, AppError::Unauthorized(_)), "{:?}", err);
+
+        let err = keys.revoke(Uuid::new_v4()).unwrap_err();
+        assert!(matches!(err, AppError::NotFound(_)), "{:?}", err);
+    }
+}
+
+fn router(pool: DbPool) -> axum::Router {
+    let s3_config = aws_sdk_s3::config::Builder::new()
+        .region(aws_sdk_s3::config::Region::new("us-east-1"))
+        .behavior_version_latest()
+        .build();
+
+    create_router(AppState {
+        pool,
+        jwt_secret: JWT_SECRET.to_string(),
+        chat_state: Arc::new(ChatState::default()),
+        s3_client: aws_sdk_s3::Client::from_conf(s3_config),
+        read_only: Arc::new(ReadOnlyMode::new(Some(false))),
+        public_lookup: Arc::new(PublicLookupLimits::default()),
+        jobs: Arc::new(JobBoard::default()),
+        body_limits: BodyLimits::default(),
+        sessions: Arc::new(SessionTracker::default()),
+        access_token_lifetime: DEFAULT_ACCESS_TOKEN_LIFETIME,
+        login_attempts: Arc::new(InMemoryLoginAttempts::default()),
+        revocations: Arc::new(RevocationCache::default()),
+        trust_proxy_headers: false,
+        token_issuer: TokenIssuer::default(),
+    })
+}
+
+/// Token of the active admin, if the database has one
+fn admin_token(pool: &DbPool) -> Option<String> {
+    let admin: Uuid = users::table
+        .filter(users::role.eq(UserRole::Admin))
+        .filter(users::deactivated_at.is_null())
+        .select(users::id)
+        .first(&mut pool.get().unwrap())
+        .optional()
+        .unwrap()?;
+    let now = Utc::now().timestamp();
+    let claims = Claims {
+        sub: admin,
+        role: UserRole::Admin,
+        exp: now + 3600,
+        iat: now,
+        sid: None,
+        jti: Some(Uuid::new_v4()),
+        must_change_password: false,
+        iss: Some(DEFAULT_JWT_ISSUER.to_string()),
+        aud: Some(DEFAULT_JWT_ISSUER.to_string()),
+    };
+    Some(encode(&Header::default(), &claims, &EncodingKey::from_secret(JWT_SECRET.as_bytes())).unwrap())
+}
+
+async fn send(
+    pool: &DbPool,
+    method: Method,
+    uri: &str,
+    authorization: &str,
+    body: Option<serde_json::Value>,
+) -> (StatusCode, serde_json::Value) {
+    send_to(router(pool.clone()), method, uri, authorization, body).await
+}
+
+async fn send_to(
+    app: axum::Router,
+    method: Method,
+    uri: &str,
+    authorization: &str,
+    body: Option<serde_json::Value>,
+) -> (StatusCode, serde_json::Value) {
+    let builder = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(AUTHORIZATION, authorization);
+    let request = match body {
+        Some(body) => builder
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap(),
+        None => builder.body(Body::empty()).unwrap(),
+    };
+    let response = app.oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&bytes).unwrap_or(serde_json::Value::Null))
+}
+
+mod endpoint_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_admin_creates_a_key_an_integration_can_use() {
+        let Some(pool) = test_pool() else { return };
+        let Some(token) = admin_token(&pool) else { return };
+        let admin = format!("Bearer {}", token);
+
+        let body = serde_json::json!({ "label": "Channel manager", "role": "receptionist" });
+        let (status, created) = send(&pool, Method::POST, "/admin/api-keys", &admin, Some(body)).await;
+        assert_eq!(status, StatusCode::CREATED);
+        let key = format!("ApiKey {}", created["key"].as_str().unwrap());
+        let id = created["id"].as_str().unwrap().to_string();
+
+        // Only the creation response carries the key
+        let (status, listed) = send(&pool, Method::GET, "/admin/api-keys", &admin, None).await;
+        assert_eq!(status, StatusCode::OK);
+        let listed = listed.as_array().unwrap().iter().find(|k| k["id"] == id.as_str()).unwrap().clone();
+        assert!(listed.get("key").is_none());
+        assert!(listed.get("key_hash").is_none());
+
+        let (status, _) = send(&pool, Method::GET, "/bookings", &key, None).await;
+        assert_eq!(status, StatusCode::OK);
+        let last_used: Option<chrono::DateTime<Utc>> = api_keys::table
+            .find(Uuid::parse_str(&id).unwrap())
+            .select(api_keys::last_used_at)
+            .first(&mut pool.get().unwrap())
+            .unwrap();
+        assert!(last_used.is_some());
+
+        // A receptionist key cannot manage keys or act as a person
+        let (status, _) = send(&pool, Method::GET, "/admin/api-keys", &key, None).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        let (status, _) = send(&pool, Method::GET, "/auth/me", &key, None).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        let uri = format!("/admin/api-keys/{}/revoke", id);
+        let (status, revoked) = send(&pool, Method::POST, &uri, &admin, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(revoked["revoked_at"].is_string());
+        let (status, _) = send(&pool, Method::GET, "/bookings", &key, None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_account_endpoints_are_refused_under_the_api_prefix() {
+        let Some(pool) = test_pool() else { return };
+        let Some(token) = admin_token(&pool) else { return };
+        let body = serde_json::json!({ "label": "Mounted", "role": "receptionist" });
+        let (status, created) =
+            send(&pool, Method::POST, "/admin/api-keys", &format!("Bearer {}", token), Some(body)).await;
+        assert_eq!(status, StatusCode::CREATED);
+        let key = format!("ApiKey {}", created["key"].as_str().unwrap());
+        // Mounted as main.rs serves it
+        let api = || axum::Router::new().nest("/api", router(pool.clone()));
+
+        let (status, _) = send_to(api(), Method::GET, "/api/bookings", &key, None).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, body) = send_to(api(), Method::GET, "/api/auth/me", &key, None).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["code"], "FORBIDDEN");
+    }
+
+    #[tokio::test]
+    async fn test_keys_cannot_read_the_creators_chat() {
+        let Some(pool) = test_pool() else { return };
+        let Some(token) = admin_token(&pool) else { return };
+        let body = serde_json::json!({ "label": "Chat reader", "role": "receptionist" });
+        let (status, created) =
+            send(&pool, Method::POST, "/admin/api-keys", &format!("Bearer {}", token), Some(body)).await;
+        assert_eq!(status, StatusCode::CREATED);
+        let key = format!("ApiKey {}", created["key"].as_str().unwrap());
+
+        let other = Uuid::new_v4();
+        for uri in [
+            "/chat/contacts".to_string(),
+            format!("/chat/history?other_user_id={}", other),
+            "/chat/messages/unsynced".to_string(),
+            "/guest/current-stay".to_string(),
+        ] {
+            let (status, body) = send(&pool, Method::GET, &uri, &key, None).await;
+            assert_eq!(status, StatusCode::FORBIDDEN, "{}", uri);
+            assert_eq!(body["code"], "FORBIDDEN");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_unknown_keys_are_refused() {
+        let Some(pool) = test_pool() else { return };
+        let bogus = format!("ApiKey {}{}", API_KEY_PREFIX, "0".repeat(64));
+        let (status, body) = send(&pool, Method::GET, "/bookings", &bogus, None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body["code"], "UNAUTHORIZED");
+    }
+}
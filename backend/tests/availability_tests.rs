@@ -0,0 +1,287 @@
+//! Availability calendar tests
+//!
+//! Tests for the per-room-type availability calendar aggregation, the
+//! same-day turnover rule for arrivals today and the front desk quick check.
+
+use bigdecimal::BigDecimal;
+use chrono::{Duration, NaiveDate, NaiveTime, Utc};
+use uuid::Uuid;
+
+use hotel_management_backend::models::{BookingStatus, Room, RoomStatus, RoomType, RoomTypeConfig};
+use hotel_management_backend::services::availability_service::{BlockingStay, MAX_CALENDAR_DAYS};
+use hotel_management_backend::services::{AvailabilityService, BookingService};
+
+fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(y, m, d).unwrap()
+}
+
+/// The seeded room types, cheapest first, sleeping 2, 4 and 6
+fn types() -> Vec<RoomTypeConfig> {
+    RoomType::LEGACY
+        .into_iter()
+        .zip(1..)
+        .map(|(code, n)| RoomTypeConfig {
+            id: Uuid::from_u128(n),
+            display_name: code.to_string(),
+            code,
+            default_price: BigDecimal::from(500000 * (n as i64 + 1)),
+            default_capacity: 2 * n as i32,
+            active: true,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        })
+        .collect()
+}
+
+fn room(number: &str, room_type: RoomType, status: RoomStatus) -> Room {
+    let room_type_id = types().into_iter().find(|t| t.code == room_type).unwrap().id;
+    Room {
+        id: Uuid::new_v4(),
+        number: number.to_string(),
+        room_type,
+        status,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+        price: BigDecimal::from(1000000),
+        assigned_cleaner_id: None,
+        description: None,
+        amenities: Vec::new(),
+        photo_urls: Vec::new(),
+        decommissioned_at: None,
+        floor: 1,
+        building: None,
+        room_type_id,
+    }
+}
+
+fn available_on(
+    days: &[hotel_management_backend::services::AvailabilityCalendarDay],
+    on: NaiveDate,
+    room_type: RoomType,
+) -> i64 {
+    days.iter()
+        .find(|d| d.date == on)
+        .and_then(|d| d.room_types.iter().find(|t| t.room_type == room_type))
+        .map(|t| t.available)
+        .unwrap()
+}
+
+mod calendar_tests {
+    use super::*;
+
+    #[test]
+    fn test_counts_free_rooms_per_type_per_night() {
+        let today = date(2025, 6, 1);
+        let single_a = room("101", RoomType::SINGLE, RoomStatus::Available);
+        let single_b = room("102", RoomType::SINGLE, RoomStatus::Available);
+        let suite = room("301", RoomType::SUITE, RoomStatus::Available);
+        let rooms = vec![single_a.clone(), single_b, suite];
+
+        // Single 101 booked for the nights of June 2 and 3
+        let stays: Vec<BlockingStay> = vec![(
+            single_a.id,
+            date(2025, 6, 2),
+            date(2025, 6, 4),
+            BookingStatus::Upcoming,
+        )];
+
+        let days = AvailabilityService::build_calendar(&types(), &rooms, &stays, date(2025, 6, 1), date(2025, 6, 4), today);
+
+        assert_eq!(days.len(), 4);
+        assert_eq!(available_on(&days, date(2025, 6, 1), RoomType::SINGLE), 2);
+        assert_eq!(available_on(&days, date(2025, 6, 2), RoomType::SINGLE), 1);
+        assert_eq!(available_on(&days, date(2025, 6, 3), RoomType::SINGLE), 1);
+        // Check-out day is free again
+        assert_eq!(available_on(&days, date(2025, 6, 4), RoomType::SINGLE), 2);
+        assert_eq!(available_on(&days, date(2025, 6, 2), RoomType::SUITE), 1);
+    }
+
+    #[test]
+    fn test_maintenance_rooms_are_never_free() {
+        let today = date(2025, 6, 1);
+        let rooms = vec![
+            room("201", RoomType::DOUBLE, RoomStatus::Maintenance),
+            room("202", RoomType::DOUBLE, RoomStatus::Dirty),
+        ];
+
+        let days = AvailabilityService::build_calendar(&types(), &rooms, &[], today, today, today);
+
+        assert_eq!(available_on(&days, today, RoomType::DOUBLE), 1);
+    }
+
+    #[test]
+    fn test_overstay_keeps_room_through_today() {
+        let today = date(2025, 6, 5);
+        let double = room("201", RoomType::DOUBLE, RoomStatus::Occupied);
+        let stays: Vec<BlockingStay> = vec![(
+            double.id,
+            date(2025, 6, 1),
+            date(2025, 6, 3),
+            BookingStatus::Overstay,
+        )];
+
+        let days = AvailabilityService::build_calendar(&types(), &[double], &stays, today, date(2025, 6, 6), today);
+
+        assert_eq!(available_on(&days, today, RoomType::DOUBLE), 0);
+        assert_eq!(available_on(&days, date(2025, 6, 6), RoomType::DOUBLE), 1);
+    }
+
+    #[test]
+    fn test_only_existing_room_types_are_listed() {
+        let today = date(2025, 6, 1);
+        let rooms = vec![room("101", RoomType::SINGLE, RoomStatus::Available)];
+
+        let days = AvailabilityService::build_calendar(&types(), &rooms, &[], today, today, today);
+
+        assert_eq!(days[0].room_types.len(), 1);
+        assert_eq!(days[0].room_types[0].room_type, RoomType::SINGLE);
+    }
+}
+
+mod calendar_range_tests {
+    use super::*;
+
+    #[test]
+    fn test_range_up_to_cap_is_accepted() {
+        let start = date(2025, 1, 1);
+        let end = start + chrono::Duration::days(MAX_CALENDAR_DAYS - 1);
+        assert!(AvailabilityService::validate_calendar_range(start, end).is_ok());
+    }
+
+    #[test]
+    fn test_range_over_cap_is_rejected() {
+        let start = date(2025, 1, 1);
+        let end = start + chrono::Duration::days(MAX_CALENDAR_DAYS);
+        assert!(AvailabilityService::validate_calendar_range(start, end).is_err());
+    }
+
+    #[test]
+    fn test_reversed_range_is_rejected() {
+        assert!(AvailabilityService::validate_calendar_range(date(2025, 1, 2), date(2025, 1, 1)).is_err());
+    }
+}
+
+mod same_day_turnover_tests {
+    use super::*;
+
+    fn at(h: u32, m: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(h, m, 0).unwrap()
+    }
+
+    fn ready(status: RoomStatus, departure: Option<BookingStatus>, now: NaiveTime) -> bool {
+        BookingService::ready_for_same_day_arrival(status, departure, now, at(14, 0))
+    }
+
+    #[test]
+    fn test_guest_still_in_house_blocks_same_day_arrival() {
+        // Outgoing guest due out today has not checked out yet at 09:00
+        assert!(!ready(RoomStatus::Available, Some(BookingStatus::CheckedIn), at(9, 0)));
+        assert!(!ready(RoomStatus::Available, Some(BookingStatus::Overstay), at(16, 0)));
+        assert!(!ready(RoomStatus::Occupied, Some(BookingStatus::CheckedIn), at(9, 0)));
+    }
+
+    #[test]
+    fn test_departed_room_must_be_cleaned_first() {
+        assert!(!ready(RoomStatus::Dirty, Some(BookingStatus::CheckedOut), at(9, 0)));
+        assert!(!ready(RoomStatus::Cleaning, Some(BookingStatus::CheckedOut), at(9, 0)));
+        assert!(ready(RoomStatus::Available, Some(BookingStatus::CheckedOut), at(9, 0)));
+    }
+
+    #[test]
+    fn test_dirty_room_without_departure_only_before_check_in_time() {
+        assert!(ready(RoomStatus::Dirty, None, at(13, 59)));
+        assert!(!ready(RoomStatus::Dirty, None, at(14, 0)));
+        assert!(!ready(RoomStatus::Cleaning, None, at(18, 0)));
+        assert!(ready(RoomStatus::Available, None, at(18, 0)));
+    }
+
+    #[test]
+    fn test_occupied_and_maintenance_never_ready() {
+        assert!(!ready(RoomStatus::Occupied, None, at(8, 0)));
+        assert!(!ready(RoomStatus::Maintenance, None, at(8, 0)));
+    }
+}
+
+mod quick_availability_tests {
+    use super::*;
+
+    #[test]
+    fn test_party_size_filters_room_types() {
+        let rooms = vec![
+            room("101", RoomType::SINGLE, RoomStatus::Available),
+            room("201", RoomType::DOUBLE, RoomStatus::Available),
+            room("301", RoomType::SUITE, RoomStatus::Available),
+        ];
+
+        let result = AvailabilityService::build_quick_availability(
+            &types(), &rooms, &[], date(2025, 6, 6), date(2025, 6, 8), 3, date(2025, 6, 1),
+        );
+
+        let types: Vec<RoomType> = result.iter().map(|t| t.room_type.clone()).collect();
+        assert_eq!(types, vec![RoomType::DOUBLE, RoomType::SUITE]);
+        assert_eq!(result[0].max_guests, 4);
+    }
+
+    #[test]
+    fn test_any_booked_night_excludes_room() {
+        let booked = room("201", RoomType::DOUBLE, RoomStatus::Available);
+        let free = room("202", RoomType::DOUBLE, RoomStatus::Available);
+        let rooms = vec![booked.clone(), free.clone()];
+        // 201 is taken for the Saturday night only
+        let stays: Vec<BlockingStay> =
+            vec![(booked.id, date(2025, 6, 7), date(2025, 6, 8), BookingStatus::Upcoming)];
+
+        let result = AvailabilityService::build_quick_availability(
+            &types(), &rooms, &stays, date(2025, 6, 6), date(2025, 6, 8), 2, date(2025, 6, 1),
+        );
+
+        assert_eq!(result[0].available, 1);
+        assert_eq!(result[0].candidates[0].room_id, free.id);
+    }
+
+    #[test]
+    fn test_candidates_prefer_ready_then_recently_cleaned() {
+        let mut stale = room("201", RoomType::DOUBLE, RoomStatus::Available);
+        stale.updated_at = Utc::now() - Duration::days(3);
+        stale.price = BigDecimal::from(800000);
+        let fresh = room("202", RoomType::DOUBLE, RoomStatus::Available);
+        let dirty = room("203", RoomType::DOUBLE, RoomStatus::Dirty);
+        let mut older = room("204", RoomType::DOUBLE, RoomStatus::Available);
+        older.updated_at = Utc::now() - Duration::days(5);
+        let rooms = vec![dirty.clone(), stale.clone(), fresh.clone(), older];
+
+        let result = AvailabilityService::build_quick_availability(
+            &types(), &rooms, &[], date(2025, 6, 6), date(2025, 6, 8), 1, date(2025, 6, 1),
+        );
+        let doubles = result.iter().find(|t| t.room_type == RoomType::DOUBLE).unwrap();
+
+        assert_eq!(doubles.available, 4);
+        assert_eq!(doubles.lowest_rate, Some(BigDecimal::from(800000)));
+        let numbers: Vec<&str> = doubles.candidates.iter().map(|c| c.number.as_str()).collect();
+        assert_eq!(numbers, vec!["202", "201", "204"]);
+
+        let payload = &doubles.candidates[0].booking;
+        assert_eq!(payload.room_id, fresh.id);
+        assert_eq!(payload.check_in_date, date(2025, 6, 6));
+        assert_eq!(payload.check_out_date, date(2025, 6, 8));
+    }
+
+    #[test]
+    fn test_occupied_and_maintenance_rooms_not_offered_today() {
+        let today = date(2025, 6, 1);
+        let occupied = room("201", RoomType::DOUBLE, RoomStatus::Occupied);
+        let rooms = vec![occupied, room("202", RoomType::DOUBLE, RoomStatus::Maintenance)];
+
+        let tonight = AvailabilityService::build_quick_availability(
+            &types(), &rooms, &[], today, date(2025, 6, 2), 2, today,
+        );
+        assert_eq!(tonight[0].available, 0);
+        assert!(tonight[0].lowest_rate.is_none());
+
+        // The occupying guest's stay is not in `stays`, so later dates count it free
+        let later = AvailabilityService::build_quick_availability(
+            &types(), &rooms, &[], date(2025, 6, 5), date(2025, 6, 6), 2, today,
+        );
+        assert_eq!(later[0].available, 1);
+    }
+}
@@ -0,0 +1,186 @@
+//! Request body limit tests
+//!
+//! Tests that oversized bodies are refused with a JSON 413 before handlers
+//! parse them, while upload routes accept larger multipart bodies. The routes
+//! look up the caller's account, so the tests need a migrated PostgreSQL
+//! database and only run when TEST_DATABASE_URL is set.
+
+use std::sync::Arc;
+
+use axum::body::Body;
+use axum::http::{header::AUTHORIZATION, header::CONTENT_TYPE, Method, Request, StatusCode};
+use chrono::Utc;
+use jsonwebtoken::{encode, EncodingKey, Header};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use hotel_management_backend::api::public_bookings::PublicLookupLimits;
+use hotel_management_backend::api::{chat::ChatState, create_router, AppState};
+use hotel_management_backend::config::BodyLimits;
+use hotel_management_backend::db::{create_pool, DbPool};
+use hotel_management_backend::models::UserRole;
+use hotel_management_backend::scheduler::JobBoard;
+use hotel_management_backend::services::auth_service::{Claims, CreateUserRequest, DEFAULT_ACCESS_TOKEN_LIFETIME, DEFAULT_JWT_ISSUER};
+use hotel_management_backend::services::rate_limit_service::InMemoryLoginAttempts;
+use hotel_management_backend::services::{AuthService, ReadOnlyMode, RevocationCache, SessionTracker, TokenIssuer};
+
+const JWT_SECRET: &str = "test-secret";
+const BOUNDARY: &str = "body-limit-test-boundary";
+
+fn limits() -> BodyLimits {
+    BodyLimits {
+        default_bytes: 1024,
+        upload_bytes: 64 * 1024,
+    }
+}
+
+fn test_pool() -> Option<DbPool> {
+    match std::env::var("TEST_DATABASE_URL") {
+        Ok(url) => Some(create_pool(&url)),
+        Err(_) => {
+            eprintln!("TEST_DATABASE_URL not set, skipping database test");
+            None
+        }
+    }
+}
+
+/// Build a router whose S3 client is never contacted
+fn test_router(pool: DbPool) -> axum::Router {
+    let s3_config = aws_sdk_s3::config::Builder::new()
+        .region(aws_sdk_s3::config::Region::new("us-east-1"))
+        .behavior_version_latest()
+        .build();
+
+    create_router(AppState {
+        pool,
+        jwt_secret: JWT_SECRET.to_string(),
+        chat_state: Arc::new(ChatState::default()),
+        s3_client: aws_sdk_s3::Client::from_conf(s3_config),
+        read_only: Arc::new(ReadOnlyMode::new(Some(false))),
+        public_lookup: Arc::new(PublicLookupLimits::default()),
+        jobs: Arc::new(JobBoard::default()),
+        body_limits: limits(),
+        sessions: Arc::new(SessionTracker::default()),
+        access_token_lifetime: DEFAULT_ACCESS_TOKEN_LIFETIME,
+        login_attempts: Arc::new(InMemoryLoginAttempts::default()),
+        revocations: Arc::new(RevocationCache::default()),
+        trust_proxy_headers: false,
+        token_issuer: TokenIssuer::default(),
+    })
+}
+
+/// Token of a new receptionist account
+fn bearer_token(pool: &DbPool) -> String {
+    let receptionist = AuthService::new(pool.clone(), JWT_SECRET.to_string())
+        .create_user(&CreateUserRequest {
+            username: format!("limits-{}", &Uuid::new_v4().simple().to_string()[..8]),
+            password: "desk-password-1".to_string(),
+            role: UserRole::Receptionist,
+            must_change_password: false,
+        })
+        .unwrap();
+    let now = Utc::now().timestamp();
+    let claims = Claims {
+        sub: receptionist.id,
+        role: UserRole::Receptionist,
+        exp: now + 3600,
+        iat: now,
+        sid: None,
+        jti: Some(Uuid::new_v4()),
+        must_change_password: false,
+        iss: Some(DEFAULT_JWT_ISSUER.to_string()),
+        aud: Some(DEFAULT_JWT_ISSUER.to_string()),
+    };
+    let token = encode(&Header::default(), &claims, &EncodingKey::from_secret(JWT_SECRET.as_bytes())).unwrap();
+    format!("Bearer {}", token)
+}
+
+/// Multipart body with a single non-file field of `size` bytes
+fn multipart_body(size: usize) -> String {
+    format!(
+        "--{b}\r\nContent-Disposition: form-data; name=\"note\"\r\n\r\n{data}\r\n--{b}--\r\n",
+        b = BOUNDARY,
+        data = "x".repeat(size)
+    )
+}
+
+async fn upload(pool: DbPool, size: usize) -> axum::response::Response {
+    let token = bearer_token(&pool);
+    test_router(pool)
+        .oneshot(
+            Request::builder()
+                .method(Method::POST)
+                .uri("/chat/upload")
+                .header(AUTHORIZATION, token)
+                .header(CONTENT_TYPE, format!("multipart/form-data; boundary={}", BOUNDARY))
+                .body(Body::from(multipart_body(size)))
+                .unwrap(),
+        )
+        .await
+        .unwrap()
+}
+
+async fn json_body(response: axum::response::Response) -> serde_json::Value {
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    serde_json::from_slice(&body).unwrap()
+}
+
+mod json_limit_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_oversized_json_gets_structured_413() {
+        let Some(pool) = test_pool() else { return };
+        let guest_name = "x".repeat(limits().default_bytes * 2);
+        let payload = serde_json::json!({
+            "guest_name": guest_name,
+            "room_id": Uuid::new_v4(),
+            "check_in_date": "2025-04-01",
+            "check_out_date": "2025-04-03",
+        });
+
+        let token = bearer_token(&pool);
+        let response = test_router(pool)
+            .oneshot(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/bookings")
+                    .header(AUTHORIZATION, token)
+                    .header(CONTENT_TYPE, "application/json")
+                    .body(Body::from(payload.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        let json = json_body(response).await;
+        assert_eq!(json["code"], "PAYLOAD_TOO_LARGE");
+    }
+}
+
+mod upload_limit_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_upload_above_default_limit_is_accepted() {
+        let Some(pool) = test_pool() else { return };
+        // Larger than the default limit but within the upload limit, so the
+        // handler reads it and reports the missing file field
+        let response = upload(pool, limits().default_bytes * 8).await;
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let json = json_body(response).await;
+        assert_eq!(json["code"], "BAD_REQUEST");
+    }
+
+    #[tokio::test]
+    async fn test_upload_above_upload_limit_gets_413() {
+        let Some(pool) = test_pool() else { return };
+        let response = upload(pool, limits().upload_bytes * 2).await;
+
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        let json = json_body(response).await;
+        assert_eq!(json["code"], "PAYLOAD_TOO_LARGE");
+    }
+}
@@ -0,0 +1,143 @@
+//! Booking concurrency tests
+//!
+//! Tests for concurrent bookings and for a cancellation racing another status
+//! change. These need a migrated PostgreSQL database and only run when
+//! TEST_DATABASE_URL is set, e.g.
+//! `TEST_DATABASE_URL=postgres://postgres@localhost/hotel_test cargo test --test booking_concurrency_tests`
+
+use std::sync::{Arc, Barrier};
+
+use chrono::{Duration, Utc};
+use diesel::prelude::*;
+
+use hotel_management_backend::db::{create_pool, DbPool};
+use hotel_management_backend::errors::AppError;
+use hotel_management_backend::models::{BookingStatus, RoomType, UserRole};
+use hotel_management_backend::schema::bookings;
+use hotel_management_backend::services::auth_service::CreateUserRequest;
+use hotel_management_backend::services::booking_service::StaffBookingRequest;
+use hotel_management_backend::services::{AuthService, BookingService, RoomService};
+
+fn test_pool() -> Option<DbPool> {
+    match std::env::var("TEST_DATABASE_URL") {
+        Ok(url) => Some(create_pool(&url)),
+        Err(_) => {
+            eprintln!("TEST_DATABASE_URL not set, skipping database test");
+            None
+        }
+    }
+}
+
+mod concurrent_create_tests {
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_only_one_of_two_concurrent_bookings_succeeds() {
+        let Some(pool) = test_pool() else { return };
+
+        let number = format!("T{}", &uuid::Uuid::new_v4().simple().to_string()[..8]);
+        let room = RoomService::new(pool.clone())
+            .create_room(&number, RoomType::SUITE)
+            .unwrap();
+        let check_in = Utc::now().date_naive() + Duration::days(30);
+        let check_out = check_in + Duration::days(1);
+
+        let barrier = Arc::new(Barrier::new(2));
+        let attempts: Vec<_> = ["Concierge", "Portal"]
+            .into_iter()
+            .map(|source| {
+                let guest_name = format!("{} Guest {}", source, number);
+                let pool = pool.clone();
+                let barrier = barrier.clone();
+                tokio::task::spawn_blocking(move || {
+                    barrier.wait();
+                    BookingService::new(pool).create_booking(
+                        &StaffBookingRequest {
+                            guest_name,
+                            room_id: room.id,
+                            check_in_date: check_in,
+                            check_out_date: check_out,
+                            price: None,
+                            allow_duplicate: false,
+                            override_conflict: false,
+                        },
+                        None,
+                    )
+                })
+            })
+            .collect();
+
+        let mut results = Vec::new();
+        for attempt in attempts {
+            results.push(attempt.await.unwrap());
+        }
+
+        let created = results.iter().filter(|r| r.is_ok()).count();
+        assert_eq!(created, 1, "exactly one booking should be created: {:?}", results);
+        assert!(results
+            .iter()
+            .any(|r| matches!(r, Err(AppError::RoomUnavailable(_)))));
+    }
+}
+
+mod concurrent_cancel_tests {
+    use super::*;
+
+    #[test]
+    fn test_cancel_losing_a_race_is_a_conflict() {
+        let Some(pool) = test_pool() else { return };
+
+        let number = format!("C{}", &uuid::Uuid::new_v4().simple().to_string()[..8]);
+        let room = RoomService::new(pool.clone())
+            .create_room(&number, RoomType::SINGLE)
+            .unwrap();
+        let actor = AuthService::new(pool.clone(), "test-secret".to_string())
+            .create_user(&CreateUserRequest {
+                username: format!("cancel-{}", number),
+                password: "desk-password-1".to_string(),
+                role: UserRole::Receptionist,
+                must_change_password: false,
+            })
+            .unwrap()
+            .id;
+        let check_in = Utc::now().date_naive() + Duration::days(30);
+        let booking = BookingService::new(pool.clone())
+            .create_booking(
+                &StaffBookingRequest {
+                    guest_name: format!("Race Guest {}", number),
+                    room_id: room.id,
+                    check_in_date: check_in,
+                    check_out_date: check_in + Duration::days(1),
+                    price: None,
+                    allow_duplicate: false,
+                    override_conflict: false,
+                },
+                Some(actor),
+            )
+            .unwrap();
+
+        // Another operation moves the booking on and holds its row until the
+        // cancellation has read the old status and is waiting on the update
+        let mut conn = pool.get().unwrap();
+        let cancel = conn
+            .transaction::<_, diesel::result::Error, _>(|conn| {
+                diesel::update(bookings::table.find(booking.id))
+                    .set(bookings::status.eq(BookingStatus::NoShow))
+                    .execute(conn)?;
+                let pool = pool.clone();
+                let cancel = std::thread::spawn(move || BookingService::new(pool).cancel(booking.id, actor));
+                std::thread::sleep(std::time::Duration::from_millis(500));
+                Ok(cancel)
+            })
+            .unwrap();
+
+        let result = cancel.join().unwrap();
+        assert!(matches!(result, Err(AppError::Conflict(_))), "{:?}", result);
+        let status: BookingStatus = bookings::table
+            .find(booking.id)
+            .select(bookings::status)
+            .first(&mut conn)
+            .unwrap();
+        assert_eq!(status, BookingStatus::NoShow);
+    }
+}
@@ -0,0 +1,107 @@
+//! Double-booking report tests
+//!
+//! Tests for finding pairs of bookings that hold the same room on shared
+//! nights. They need a migrated PostgreSQL database and only run when
+//! TEST_DATABASE_URL is set.
+
+use bigdecimal::BigDecimal;
+use chrono::{Duration, NaiveDate, Utc};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use hotel_management_backend::db::{create_pool, DbPool};
+use hotel_management_backend::errors::AppError;
+use hotel_management_backend::models::{Booking, BookingStatus, NewBooking, Room, RoomType};
+use hotel_management_backend::schema::bookings;
+use hotel_management_backend::services::{BookingService, RoomService};
+
+fn test_pool() -> Option<DbPool> {
+    match std::env::var("TEST_DATABASE_URL") {
+        Ok(url) => Some(create_pool(&url)),
+        Err(_) => {
+            eprintln!("TEST_DATABASE_URL not set, skipping database test");
+            None
+        }
+    }
+}
+
+/// Insert a booking directly, skipping the availability check the way the
+/// historical race did
+fn insert(pool: &DbPool, room: &Room, check_in: NaiveDate, nights: i64, status: BookingStatus) -> Booking {
+    let reference = BookingService::generate_reference();
+    let guest_name = format!("Conflict {}", reference);
+    let booking: Booking = diesel::insert_into(bookings::table)
+        .values(&NewBooking {
+            reference: &reference,
+            guest_name: &guest_name,
+            room_id: room.id,
+            check_in_date: check_in,
+            check_out_date: check_in + Duration::days(nights),
+            created_by_user_id: None,
+            creation_source: "staff",
+            price: BigDecimal::from(0),
+            group_reference: None,
+        })
+        .get_result(&mut pool.get().unwrap())
+        .unwrap();
+    diesel::update(bookings::table.find(booking.id))
+        .set(bookings::status.eq(status))
+        .get_result(&mut pool.get().unwrap())
+        .unwrap()
+}
+
+fn room(pool: &DbPool) -> Room {
+    let number = format!("D{}", &Uuid::new_v4().simple().to_string()[..8]);
+    RoomService::new(pool.clone())
+        .create_room(&number, RoomType::DOUBLE)
+        .unwrap()
+}
+
+#[test]
+fn test_overlapping_pair_is_reported_once_with_shared_nights() {
+    let Some(pool) = test_pool() else { return };
+    let service = BookingService::new(pool.clone());
+    let (room, quiet_room) = (room(&pool), room(&pool));
+    let start = Utc::now().date_naive() + Duration::days(60);
+
+    let a = insert(&pool, &room, start, 4, BookingStatus::Upcoming);
+    let b = insert(&pool, &room, start + Duration::days(2), 3, BookingStatus::Upcoming);
+    // Cancelled and back-to-back stays are not conflicts
+    insert(&pool, &room, start, 4, BookingStatus::Cancelled);
+    insert(&pool, &room, start + Duration::days(5), 2, BookingStatus::Upcoming);
+    insert(&pool, &quiet_room, start, 4, BookingStatus::Upcoming);
+
+    let conflicts = service
+        .find_overlapping_bookings(start, start + Duration::days(10), Some(room.id))
+        .unwrap();
+    assert_eq!(conflicts.len(), 1);
+    let conflict = &conflicts[0];
+    assert_eq!(conflict.room.id, room.id);
+    let mut pair = [conflict.first.id, conflict.second.id];
+    pair.sort();
+    let mut expected = [a.id, b.id];
+    expected.sort();
+    assert_eq!(pair, expected);
+    assert_eq!(conflict.overlap_from, start + Duration::days(2));
+    assert_eq!(conflict.overlap_to, start + Duration::days(4));
+
+    assert!(service
+        .find_overlapping_bookings(start, start + Duration::days(10), Some(quiet_room.id))
+        .unwrap()
+        .is_empty());
+    // Outside the requested window
+    assert!(service
+        .find_overlapping_bookings(start + Duration::days(20), start + Duration::days(30), Some(room.id))
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn test_reversed_range_is_rejected() {
+    let Some(pool) = test_pool() else { return };
+    let today = Utc::now().date_naive();
+    assert!(matches!(
+        BookingService::new(pool).find_overlapping_bookings(today, today - Duration::days(1), None),
+        Err(AppError::ValidationError(_))
+    ));
+}
@@ -0,0 +1,243 @@
+//! Booking email tests
+//!
+//! Tests for rendering guest confirmation and cancellation emails, for the
+//! SMTP settings and for the send log. The log tests need a migrated
+//! PostgreSQL database and only run when TEST_DATABASE_URL is set.
+
+use std::collections::HashMap;
+
+use bigdecimal::BigDecimal;
+use chrono::{Duration, NaiveDate, Utc};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use hotel_management_backend::db::{create_pool, DbPool};
+use hotel_management_backend::models::{
+    Booking, BookingEmailKind, BookingEmailStatus, BookingStatus, BookingWithRoom, Room, RoomStatus, RoomType,
+};
+use hotel_management_backend::schema::users;
+use hotel_management_backend::services::booking_service::StaffBookingRequest;
+use hotel_management_backend::services::auth_service::GuestRegisterRequest;
+use hotel_management_backend::services::booking_email_service::{
+    render_cancellation_email, render_confirmation_email,
+};
+use hotel_management_backend::services::mailer::Mailer;
+use hotel_management_backend::services::{AuthService, BookingEmailService, BookingService, RoomService};
+use hotel_management_backend::settings::{self, Settings};
+
+fn booking_with_room(guest_name: &str) -> BookingWithRoom {
+    let room_id = Uuid::new_v4();
+    BookingWithRoom {
+        booking: Booking {
+            id: Uuid::new_v4(),
+            reference: "BK-20250601-AB12".to_string(),
+            guest_name: guest_name.to_string(),
+            room_id,
+            check_in_date: NaiveDate::from_ymd_opt(2025, 6, 1).unwrap(),
+            check_out_date: NaiveDate::from_ymd_opt(2025, 6, 4).unwrap(),
+            status: BookingStatus::Upcoming,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            created_by_user_id: None,
+            creation_source: "guest".to_string(),
+            price: BigDecimal::from(4_500_000),
+            group_reference: None,
+            checked_in_at: None,
+            checked_out_at: None,
+        },
+        room: Some(Room {
+            id: room_id,
+            number: "305".to_string(),
+            room_type: RoomType::SUITE,
+            status: RoomStatus::Available,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            price: BigDecimal::from(1_500_000),
+            assigned_cleaner_id: None,
+            description: None,
+            amenities: vec![],
+            photo_urls: vec![],
+            decommissioned_at: None,
+            floor: 1,
+            building: None,
+            room_type_id: Uuid::new_v4(),
+        }),
+        modification_count: 0,
+        notes: None,
+        created_by: None,
+    }
+}
+
+mod render_tests {
+    use super::*;
+
+    #[test]
+    fn test_confirmation_lists_the_stay() {
+        let email = render_confirmation_email("guest@example.com", &booking_with_room("Tran Thi B"));
+        assert_eq!(email.to, "guest@example.com");
+        assert!(email.subject.contains("BK-20250601-AB12"));
+        assert!(email.subject.contains("confirmed"));
+        for part in ["Tran Thi B", "suite", "2025-06-01", "2025-06-04", "4,500,000 VND"] {
+            assert!(email.html.contains(part), "missing {} in {}", part, email.html);
+        }
+    }
+
+    #[test]
+    fn test_cancellation_names_the_booking() {
+        let email = render_cancellation_email("guest@example.com", &booking_with_room("Tran Thi B"));
+        assert!(email.subject.contains("cancelled"));
+        assert!(email.html.contains("BK-20250601-AB12"));
+    }
+
+    #[test]
+    fn test_guest_name_is_escaped() {
+        let email = render_confirmation_email("guest@example.com", &booking_with_room("<b>Mallory</b> & co"));
+        assert!(email.html.contains("&lt;b&gt;Mallory&lt;/b&gt; &amp; co"));
+        assert!(!email.html.contains("<b>Mallory"));
+    }
+}
+
+mod smtp_settings_tests {
+    use super::*;
+
+    #[test]
+    fn test_no_host_logs_instead_of_sending() {
+        let mailer = Mailer::from_smtp_settings(&Settings::from_values(HashMap::new())).unwrap();
+        assert!(matches!(mailer, Mailer::Log));
+    }
+
+    #[test]
+    fn test_host_builds_smtp_transport() {
+        let settings = Settings::from_values(HashMap::from([
+            (settings::SMTP_HOST.to_string(), "smtp.example.com".to_string()),
+            (settings::SMTP_PORT.to_string(), "2525".to_string()),
+            (settings::SMTP_USERNAME.to_string(), "mailer".to_string()),
+            (settings::SMTP_PASSWORD.to_string(), "secret".to_string()),
+        ]));
+        match Mailer::from_smtp_settings(&settings).unwrap() {
+            Mailer::Smtp { from, .. } => assert!(from.contains("@")),
+            other => panic!("Expected SMTP transport, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_password_is_secret() {
+        assert!(settings::definition(settings::SMTP_PASSWORD).unwrap().secret);
+    }
+}
+
+fn test_pool() -> Option<DbPool> {
+    match std::env::var("TEST_DATABASE_URL") {
+        Ok(url) => Some(create_pool(&url)),
+        Err(_) => {
+            eprintln!("TEST_DATABASE_URL not set, skipping database test");
+            None
+        }
+    }
+}
+
+mod send_log_tests {
+    use super::*;
+
+    /// A guest booking and the guest's email address
+    fn guest_booking(pool: &DbPool) -> (BookingWithRoom, String) {
+        let suffix = &Uuid::new_v4().simple().to_string()[..8];
+        let email = format!("booker-{}@example.com", suffix);
+        let name = format!("Email Guest {}", suffix);
+        let guest = AuthService::new(pool.clone(), "test-secret".to_string())
+            .register_guest(&GuestRegisterRequest {
+                email: email.clone(),
+                password: "guest-password-1".to_string(),
+                full_name: name.clone(),
+            })
+            .unwrap()
+            .user;
+        // Guests must verify their email before booking
+        diesel::update(users::table.find(guest.id))
+            .set(users::email_verified_at.eq(Some(Utc::now())))
+            .execute(&mut pool.get().unwrap())
+            .unwrap();
+        let room = RoomService::new(pool.clone())
+            .create_room(&format!("E{}", suffix), RoomType::DOUBLE)
+            .unwrap();
+        let check_in = Utc::now().date_naive() + Duration::days(30);
+        let booking = BookingService::new(pool.clone())
+            .create_guest_booking(guest.id, &name, room.id, check_in, check_in + Duration::days(2), None)
+            .unwrap();
+        (booking, email)
+    }
+
+    #[test]
+    fn test_prepared_email_is_logged_pending_then_sent() {
+        let Some(pool) = test_pool() else { return };
+        let (booking, address) = guest_booking(&pool);
+        let service = BookingEmailService::new(pool.clone());
+
+        let (record, email) = service
+            .prepare(booking.booking.id, BookingEmailKind::Confirmation)
+            .unwrap()
+            .unwrap();
+        assert_eq!(record.status, BookingEmailStatus::Pending);
+        assert_eq!(record.recipient_email, address);
+        assert_eq!(email.to, address);
+        assert!(email.html.contains(&booking.booking.reference));
+
+        service.record_result(record.id, Ok(()), Utc::now()).unwrap();
+        let logged = service.list_emails(Some(booking.booking.id), None).unwrap();
+        assert_eq!(logged.len(), 1);
+        assert_eq!(logged[0].status, BookingEmailStatus::Sent);
+        assert!(logged[0].sent_at.is_some());
+    }
+
+    #[test]
+    fn test_failed_send_keeps_the_error() {
+        let Some(pool) = test_pool() else { return };
+        let (booking, _) = guest_booking(&pool);
+        let service = BookingEmailService::new(pool.clone());
+
+        let (record, _) = service
+            .prepare(booking.booking.id, BookingEmailKind::Cancellation)
+            .unwrap()
+            .unwrap();
+        service
+            .record_result(record.id, Err("connection refused".to_string()), Utc::now())
+            .unwrap();
+
+        let failed = service
+            .list_emails(Some(booking.booking.id), Some(BookingEmailStatus::Failed))
+            .unwrap();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].kind, BookingEmailKind::Cancellation);
+        assert_eq!(failed[0].last_error.as_deref(), Some("connection refused"));
+        assert!(failed[0].sent_at.is_none());
+    }
+
+    #[test]
+    fn test_staff_booking_without_guest_account_sends_nothing() {
+        let Some(pool) = test_pool() else { return };
+        let number = format!("E{}", &Uuid::new_v4().simple().to_string()[..8]);
+        let room = RoomService::new(pool.clone()).create_room(&number, RoomType::SINGLE).unwrap();
+        let check_in = Utc::now().date_naive() + Duration::days(30);
+        let booking = BookingService::new(pool.clone())
+            .create_booking(
+                &StaffBookingRequest {
+                    guest_name: format!("Phone Guest {}", number),
+                    room_id: room.id,
+                    check_in_date: check_in,
+                    check_out_date: check_in + Duration::days(1),
+                    price: None,
+                    allow_duplicate: false,
+                    override_conflict: false,
+                },
+                None,
+            )
+            .unwrap();
+
+        let service = BookingEmailService::new(pool);
+        assert!(service
+            .prepare(booking.id, BookingEmailKind::Confirmation)
+            .unwrap()
+            .is_none());
+        assert!(service.list_emails(Some(booking.id), None).unwrap().is_empty());
+    }
+}
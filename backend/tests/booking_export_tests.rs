@@ -0,0 +1,129 @@
+//! Booking export tests
+//!
+//! Tests for the CSV lines of the accounting export and for fetching the
+//! export in keyset-paginated chunks. The chunking tests need a migrated
+//! PostgreSQL database and only run when TEST_DATABASE_URL is set.
+
+use bigdecimal::BigDecimal;
+use chrono::{Duration, NaiveDate, TimeZone, Utc};
+use uuid::Uuid;
+
+use hotel_management_backend::db::{create_pool, DbPool};
+use hotel_management_backend::models::{BookingStatus, RoomType};
+use hotel_management_backend::services::booking_service::{BookingExportFilter, BookingExportRow, StaffBookingRequest, BOOKING_EXPORT_HEADER};
+use hotel_management_backend::services::{BookingService, RoomService};
+
+mod csv_line_tests {
+    use super::*;
+
+    fn row(guest_name: &str) -> BookingExportRow {
+        BookingExportRow {
+            id: Uuid::new_v4(),
+            reference: "BK-20250601-AB12".to_string(),
+            guest_name: guest_name.to_string(),
+            room_number: "101".to_string(),
+            room_type: RoomType::DOUBLE,
+            check_in_date: NaiveDate::from_ymd_opt(2025, 6, 1).unwrap(),
+            check_out_date: NaiveDate::from_ymd_opt(2025, 6, 4).unwrap(),
+            status: BookingStatus::CheckedOut,
+            price: BigDecimal::from(4_500_000),
+            creation_source: "staff".to_string(),
+            created_at: Utc.with_ymd_and_hms(2025, 5, 20, 8, 30, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_line_matches_header_columns() {
+        let line = row("Nguyen Van A").to_csv_line();
+        assert_eq!(
+            line,
+            "BK-20250601-AB12,Nguyen Van A,101,double,2025-06-01,2025-06-04,3,checked_out,4500000,staff,2025-05-20T08:30:00+00:00\n"
+        );
+        assert_eq!(line.split(',').count(), BOOKING_EXPORT_HEADER.split(',').count());
+    }
+
+    #[test]
+    fn test_commas_and_quotes_in_names_are_escaped() {
+        let line = row("Smith, John \"Johnny\"").to_csv_line();
+        assert!(line.contains(",\"Smith, John \"\"Johnny\"\"\",101,"));
+    }
+}
+
+fn test_pool() -> Option<DbPool> {
+    match std::env::var("TEST_DATABASE_URL") {
+        Ok(url) => Some(create_pool(&url)),
+        Err(_) => {
+            eprintln!("TEST_DATABASE_URL not set, skipping database test");
+            None
+        }
+    }
+}
+
+mod chunked_export_tests {
+    use super::*;
+
+    #[test]
+    fn test_small_chunks_export_every_row_once_in_order() {
+        let Some(pool) = test_pool() else { return };
+        let service = BookingService::new(pool.clone());
+        let rooms = RoomService::new(pool.clone());
+
+        // A far-future window nothing else books into
+        let offset = 3000 + (Uuid::new_v4().as_u128() % 3000) as i64;
+        let first = Utc::now().date_naive() + Duration::days(offset);
+        let mut references = Vec::new();
+        for day in 0..5 {
+            let number = format!("X{}", &Uuid::new_v4().simple().to_string()[..8]);
+            let room = rooms.create_room(&number, RoomType::SINGLE).unwrap();
+            let check_in = first + Duration::days(day % 3);
+            let booking = service
+                .create_booking(
+                    &StaffBookingRequest {
+                        guest_name: "Export Guest".to_string(),
+                        room_id: room.id,
+                        check_in_date: check_in,
+                        check_out_date: check_in + Duration::days(1),
+                        price: None,
+                        allow_duplicate: true,
+                        override_conflict: false,
+                    },
+                    None,
+                )
+                .unwrap();
+            references.push((check_in, booking.reference));
+        }
+
+        let filter = BookingExportFilter {
+            from_date: Some(first),
+            to_date: Some(first + Duration::days(2)),
+            status: Some(BookingStatus::Upcoming),
+        };
+        let mut chunks = Vec::new();
+        let exported = service
+            .export_bookings(&filter, 2, |rows| {
+                chunks.push(rows);
+                true
+            })
+            .unwrap();
+
+        assert_eq!(exported, 5);
+        assert_eq!(chunks.iter().map(Vec::len).collect::<Vec<_>>(), vec![2, 2, 1]);
+        let rows: Vec<BookingExportRow> = chunks.into_iter().flatten().collect();
+        assert!(rows.windows(2).all(|w| w[0].check_in_date <= w[1].check_in_date));
+        let mut exported_refs: Vec<&str> = rows.iter().map(|r| r.reference.as_str()).collect();
+        let mut expected: Vec<&str> = references.iter().map(|(_, r)| r.as_str()).collect();
+        exported_refs.sort();
+        expected.sort();
+        assert_eq!(exported_refs, expected);
+
+        // Stopping early fetches nothing more
+        let mut calls = 0;
+        service
+            .export_bookings(&filter, 2, |_| {
+                calls += 1;
+                false
+            })
+            .unwrap();
+        assert_eq!(calls, 1);
+    }
+}
@@ -0,0 +1,402 @@
+//! Booking hold tests
+//!
+//! Tests for the per-guest hold limit, for how holds affect availability,
+//! the staff availability views and guest bookings, and for purging expired
+//! holds. The database tests need a migrated PostgreSQL database and only run
+//! when TEST_DATABASE_URL is set.
+
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use hotel_management_backend::db::{create_pool, DbPool};
+use hotel_management_backend::errors::AppError;
+use hotel_management_backend::models::{NewBookingHold, Room, RoomType};
+use hotel_management_backend::scheduler::{self, JobBoard, JOB_HOLD_CLEANUP};
+use hotel_management_backend::schema::{audit_logs, booking_holds, users};
+use hotel_management_backend::services::auth_service::GuestRegisterRequest;
+use hotel_management_backend::services::hold_service::{HOLD_DURATION_MINUTES, HOLD_PURGE_BATCH, MAX_ACTIVE_HOLDS};
+use hotel_management_backend::services::room_type_service::CreateRoomTypeRequest;
+use hotel_management_backend::settings::{self, Settings};
+use hotel_management_backend::services::{
+    AuthService, AvailabilityService, BookingService, HoldService, RoomService, RoomTypeService,
+};
+
+/// Tests that purge expired holds run one at a time, so one purge does not
+/// take the stale holds another is counting
+static PURGES: Mutex<()> = Mutex::new(());
+
+fn purges() -> MutexGuard<'static, ()> {
+    PURGES.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+mod limit_tests {
+    use super::*;
+
+    #[test]
+    fn test_guest_may_hold_up_to_the_limit() {
+        assert!(HoldService::check_hold_limit(0).is_ok());
+        assert!(HoldService::check_hold_limit(MAX_ACTIVE_HOLDS - 1).is_ok());
+        match HoldService::check_hold_limit(MAX_ACTIVE_HOLDS) {
+            Err(AppError::Conflict(msg)) => assert!(msg.contains("at most 2")),
+            other => panic!("Expected Conflict, got {:?}", other),
+        }
+    }
+}
+
+fn test_pool() -> Option<DbPool> {
+    match std::env::var("TEST_DATABASE_URL") {
+        Ok(url) => Some(create_pool(&url)),
+        Err(_) => {
+            eprintln!("TEST_DATABASE_URL not set, skipping database test");
+            None
+        }
+    }
+}
+
+mod hold_service_tests {
+    use super::*;
+
+    fn guest(pool: &DbPool) -> (Uuid, String) {
+        let suffix = &Uuid::new_v4().simple().to_string()[..8];
+        let name = format!("Hold Guest {}", suffix);
+        let user = AuthService::new(pool.clone(), "test-secret".to_string())
+            .register_guest(&GuestRegisterRequest {
+                email: format!("hold-{}@example.com", suffix),
+                password: "guest-password-1".to_string(),
+                full_name: name.clone(),
+            })
+            .unwrap()
+            .user;
+        // Guests must verify their email before booking
+        diesel::update(users::table.find(user.id))
+            .set(users::email_verified_at.eq(Some(Utc::now())))
+            .execute(&mut pool.get().unwrap())
+            .unwrap();
+        (user.id, name)
+    }
+
+    fn room(pool: &DbPool) -> Room {
+        let number = format!("H{}", &Uuid::new_v4().simple().to_string()[..8]);
+        RoomService::new(pool.clone()).create_room(&number, RoomType::DOUBLE).unwrap()
+    }
+
+    /// A room of a new type of its own, so other tests' rooms do not change
+    /// the availability counts of its type
+    fn room_of_own_type(pool: &DbPool) -> Room {
+        let code = format!("hold_{}", &Uuid::new_v4().simple().to_string()[..8]);
+        let room_type = RoomTypeService::new(pool.clone())
+            .create_room_type(&CreateRoomTypeRequest {
+                code: RoomType::new(&code).unwrap(),
+                display_name: "Hold".to_string(),
+                default_price: BigDecimal::from(900_000),
+                default_capacity: 2,
+                active: true,
+            })
+            .unwrap();
+        let number = format!("H{}", &Uuid::new_v4().simple().to_string()[..8]);
+        RoomService::new(pool.clone()).create_room(&number, room_type.code).unwrap()
+    }
+
+    fn stay() -> (NaiveDate, NaiveDate) {
+        let check_in = Utc::now().date_naive() + Duration::days(40);
+        (check_in, check_in + Duration::days(2))
+    }
+
+    #[test]
+    fn test_hold_blocks_everyone_but_the_holder() {
+        let Some(pool) = test_pool() else { return };
+        let (holder, _) = guest(&pool);
+        let (other, _) = guest(&pool);
+        let room = room(&pool);
+        let (check_in, check_out) = stay();
+        let bookings = BookingService::new(pool.clone());
+
+        let hold = HoldService::new(pool.clone())
+            .create_hold(holder, room.id, check_in, check_out)
+            .unwrap();
+        let minutes = (hold.expires_at - hold.created_at).num_minutes();
+        assert!((HOLD_DURATION_MINUTES - 1..=HOLD_DURATION_MINUTES).contains(&minutes));
+
+        assert!(bookings.check_availability(room.id, check_in, check_out, None, Some(holder)).unwrap());
+        assert!(!bookings.check_availability(room.id, check_in, check_out, None, Some(other)).unwrap());
+        assert!(!bookings.check_availability(room.id, check_in, check_out, None, None).unwrap());
+        // The night before the hold is still free
+        assert!(bookings
+            .check_availability(room.id, check_in - Duration::days(1), check_in, None, None)
+            .unwrap());
+
+        assert!(matches!(
+            HoldService::new(pool.clone()).create_hold(other, room.id, check_in, check_out),
+            Err(AppError::RoomUnavailable(_))
+        ));
+    }
+
+    #[test]
+    fn test_quick_availability_does_not_offer_a_held_room() {
+        let Some(pool) = test_pool() else { return };
+        let (holder, _) = guest(&pool);
+        let room = room_of_own_type(&pool);
+        let (check_in, check_out) = stay();
+        let availability = AvailabilityService::new(pool.clone());
+        let free = || {
+            availability
+                .quick_availability(check_in, check_out, 1)
+                .unwrap()
+                .into_iter()
+                .find(|t| t.room_type == room.room_type)
+                .unwrap()
+                .available
+        };
+
+        assert_eq!(free(), 1);
+        HoldService::new(pool.clone())
+            .create_hold(holder, room.id, check_in, check_out)
+            .unwrap();
+        assert_eq!(free(), 0);
+    }
+
+    #[test]
+    fn test_room_type_calendar_counts_a_held_room_as_taken() {
+        let Some(pool) = test_pool() else { return };
+        let (holder, _) = guest(&pool);
+        let room = room_of_own_type(&pool);
+        let (check_in, check_out) = stay();
+        let availability = AvailabilityService::new(pool.clone());
+        // Free rooms of its type each night from check-in through check-out
+        let free = || -> Vec<i64> {
+            availability
+                .get_calendar(Some(room.room_type.clone()), check_in, check_out)
+                .unwrap()
+                .iter()
+                .map(|day| day.room_types.iter().find(|t| t.room_type == room.room_type).unwrap().available)
+                .collect()
+        };
+
+        assert_eq!(free(), vec![1, 1, 1]);
+        HoldService::new(pool.clone())
+            .create_hold(holder, room.id, check_in, check_out)
+            .unwrap();
+        // Held for both nights; free again on the night of check-out
+        assert_eq!(free(), vec![0, 0, 1]);
+    }
+
+    #[test]
+    fn test_booking_the_held_stay_releases_the_hold() {
+        let Some(pool) = test_pool() else { return };
+        let (holder, name) = guest(&pool);
+        let room = room(&pool);
+        let (check_in, check_out) = stay();
+        let bookings = BookingService::new(pool.clone());
+
+        HoldService::new(pool.clone())
+            .create_hold(holder, room.id, check_in, check_out)
+            .unwrap();
+        bookings
+            .create_guest_booking(holder, &name, room.id, check_in, check_out, None)
+            .unwrap();
+
+        let remaining: i64 = booking_holds::table
+            .filter(booking_holds::user_id.eq(holder))
+            .count()
+            .get_result(&mut pool.get().unwrap())
+            .unwrap();
+        assert_eq!(remaining, 0);
+    }
+
+    #[test]
+    fn test_third_hold_is_refused_but_renewing_is_not() {
+        let Some(pool) = test_pool() else { return };
+        let (holder, _) = guest(&pool);
+        let holds = HoldService::new(pool.clone());
+        let (check_in, check_out) = stay();
+        let rooms: Vec<Room> = (0..3).map(|_| room(&pool)).collect();
+
+        holds.create_hold(holder, rooms[0].id, check_in, check_out).unwrap();
+        holds.create_hold(holder, rooms[1].id, check_in, check_out).unwrap();
+        // Holding the same stay again only renews it
+        holds.create_hold(holder, rooms[1].id, check_in, check_out).unwrap();
+
+        assert!(matches!(
+            holds.create_hold(holder, rooms[2].id, check_in, check_out),
+            Err(AppError::Conflict(_))
+        ));
+    }
+
+    #[test]
+    fn test_expired_holds_are_ignored_and_purged() {
+        let Some(pool) = test_pool() else { return };
+        let _purges = purges();
+        let (holder, _) = guest(&pool);
+        let room = room(&pool);
+        let (check_in, check_out) = stay();
+        let mut conn = pool.get().unwrap();
+
+        let expired_id: Uuid = diesel::insert_into(booking_holds::table)
+            .values(&NewBookingHold {
+                room_id: room.id,
+                user_id: holder,
+                check_in_date: check_in,
+                check_out_date: check_out,
+                expires_at: Utc::now() - Duration::days(31),
+            })
+            .returning(booking_holds::id)
+            .get_result(&mut conn)
+            .unwrap();
+
+        assert!(BookingService::new(pool.clone())
+            .check_availability(room.id, check_in, check_out, None, None)
+            .unwrap());
+
+        // Expired longer ago than any retention the setting allows
+        let purge = HoldService::new(pool.clone())
+            .purge_expired(Utc::now(), false, None)
+            .unwrap();
+        assert!(purge.holds >= 1);
+        let left: i64 = booking_holds::table
+            .find(expired_id)
+            .count()
+            .get_result(&mut conn)
+            .unwrap();
+        assert_eq!(left, 0);
+    }
+
+    /// Insert `count` holds of `holder` on `room` expiring at `expires_at`
+    fn insert_holds(
+        conn: &mut PgConnection,
+        holder: Uuid,
+        room: &Room,
+        expires_at: DateTime<Utc>,
+        count: usize,
+    ) -> Vec<Uuid> {
+        let (check_in, check_out) = stay();
+        let holds: Vec<NewBookingHold> = (0..count)
+            .map(|_| NewBookingHold {
+                room_id: room.id,
+                user_id: holder,
+                check_in_date: check_in,
+                check_out_date: check_out,
+                expires_at,
+            })
+            .collect();
+        diesel::insert_into(booking_holds::table)
+            .values(&holds)
+            .returning(booking_holds::id)
+            .get_results(conn)
+            .unwrap()
+    }
+
+    fn exists(conn: &mut PgConnection, hold_id: Uuid) -> bool {
+        diesel::select(diesel::dsl::exists(booking_holds::table.find(hold_id)))
+            .get_result(conn)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_purge_removes_stale_holds_and_keeps_fresh_ones() {
+        let Some(pool) = test_pool() else { return };
+        let _purges = purges();
+        let (holder, _) = guest(&pool);
+        let room = room(&pool);
+        let mut conn = pool.get().unwrap();
+        let now = Utc::now();
+        let retention = Settings::load(&mut conn).unwrap().duration(settings::HOLD_RETENTION);
+
+        let stale = insert_holds(&mut conn, holder, &room, now - retention - Duration::hours(1), 1)[0];
+        let recently_expired = insert_holds(&mut conn, holder, &room, now - retention + Duration::hours(1), 1)[0];
+        let active = insert_holds(&mut conn, holder, &room, now + Duration::minutes(5), 1)[0];
+        let holds = HoldService::new(pool.clone());
+
+        let dry_run = holds.purge_expired(now, true, Some(holder)).unwrap();
+        assert!(dry_run.dry_run);
+        assert!(dry_run.holds >= 1);
+        assert_eq!(dry_run.batches, 0);
+        assert!(exists(&mut conn, stale));
+
+        let purge = holds.purge_expired(now, false, Some(holder)).unwrap();
+        assert_eq!(purge.cutoff, now - retention);
+        assert!(purge.holds >= 1);
+        assert!(!exists(&mut conn, stale));
+        assert!(exists(&mut conn, recently_expired));
+        assert!(exists(&mut conn, active));
+
+        let logged: Vec<Option<String>> = audit_logs::table
+            .filter(audit_logs::actor_user_id.eq(holder))
+            .filter(audit_logs::action.eq("booking_holds.purged"))
+            .select(audit_logs::details)
+            .load(&mut conn)
+            .unwrap();
+        assert_eq!(logged.len(), purge.batches);
+        assert!(logged[0].as_deref().unwrap().contains("holds expired at or before"));
+    }
+
+    #[test]
+    fn test_purge_deletes_in_bounded_batches() {
+        let Some(pool) = test_pool() else { return };
+        let _purges = purges();
+        let (holder, _) = guest(&pool);
+        let room = room(&pool);
+        let mut conn = pool.get().unwrap();
+        let stale = insert_holds(
+            &mut conn,
+            holder,
+            &room,
+            Utc::now() - Duration::days(40),
+            HOLD_PURGE_BATCH as usize + 1,
+        );
+
+        let purge = HoldService::new(pool.clone())
+            .purge_expired(Utc::now(), false, Some(holder))
+            .unwrap();
+        assert!(purge.holds >= stale.len());
+        assert!(purge.batches >= 2);
+        let left: i64 = booking_holds::table
+            .filter(booking_holds::id.eq_any(&stale))
+            .count()
+            .get_result(&mut conn)
+            .unwrap();
+        assert_eq!(left, 0);
+    }
+
+    #[test]
+    fn test_overview_counts_holds_by_state_and_age() {
+        let Some(pool) = test_pool() else { return };
+        let (holder, _) = guest(&pool);
+        let room = room(&pool);
+        let mut conn = pool.get().unwrap();
+        let active = insert_holds(&mut conn, holder, &room, Utc::now() + Duration::minutes(5), 1)[0];
+        insert_holds(&mut conn, holder, &room, Utc::now() - Duration::minutes(5), 1);
+
+        let overview = HoldService::new(pool.clone()).overview(Utc::now()).unwrap();
+        assert!(overview.active >= 1);
+        assert!(overview.expired >= 1);
+        assert_eq!(overview.total, overview.active + overview.expired);
+        assert_eq!(overview.age_buckets.iter().map(|b| b.count).sum::<i64>(), overview.total);
+        assert_eq!(overview.age_buckets[0].label, "under_1h");
+        assert!(overview.age_buckets[0].count >= 2);
+        assert!(overview.holds.iter().any(|h| h.id == active));
+    }
+
+    #[test]
+    fn test_cleanup_job_reports_to_the_job_board() {
+        let Some(pool) = test_pool() else { return };
+        let _purges = purges();
+        let jobs = Arc::new(JobBoard::default());
+
+        tokio::runtime::Runtime::new()
+            .unwrap()
+            .block_on(scheduler::purge_expired_holds(&pool, &jobs, None))
+            .unwrap()
+            .unwrap();
+        let job = jobs
+            .snapshot()
+            .into_iter()
+            .find(|job| job.name == JOB_HOLD_CLEANUP)
+            .unwrap();
+        assert_eq!(job.runs, 1);
+        assert_eq!(job.last_error, None);
+    }
+}
@@ -0,0 +1,241 @@
+//! Booking list filter tests
+//!
+//! Tests for the creation source filter values, for combining the room
+//! filter with status and date filters and for the staff member shown as a
+//! booking's creator. The listing tests need a migrated
+//! PostgreSQL database and only run when TEST_DATABASE_URL is set.
+
+use chrono::{Duration, Utc};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use hotel_management_backend::db::{create_pool, DbPool};
+use hotel_management_backend::errors::AppError;
+use hotel_management_backend::models::{parse_creation_source, BookingStatus, RoomType, UserRole};
+use hotel_management_backend::schema::{bookings, users};
+use hotel_management_backend::services::auth_service::{CreateUserRequest, GuestRegisterRequest};
+use hotel_management_backend::services::booking_service::{BookingListFilter, StaffBookingRequest};
+use hotel_management_backend::services::{AuthService, BookingService, RoomService};
+
+mod creation_source_tests {
+    use super::*;
+
+    #[test]
+    fn test_known_sources_are_accepted() {
+        assert_eq!(parse_creation_source("staff").unwrap(), "staff");
+        assert_eq!(parse_creation_source(" guest ").unwrap(), "guest");
+    }
+
+    #[test]
+    fn test_unknown_source_lists_valid_values() {
+        match parse_creation_source("portal") {
+            Err(AppError::ValidationError(msg)) => {
+                assert!(msg.contains("portal"));
+                assert!(msg.contains("staff, guest"), "{}", msg);
+            }
+            other => panic!("Expected ValidationError, got {:?}", other),
+        }
+    }
+}
+
+fn test_pool() -> Option<DbPool> {
+    match std::env::var("TEST_DATABASE_URL") {
+        Ok(url) => Some(create_pool(&url)),
+        Err(_) => {
+            eprintln!("TEST_DATABASE_URL not set, skipping database test");
+            None
+        }
+    }
+}
+
+mod list_tests {
+    use super::*;
+
+    #[test]
+    fn test_room_filter_combines_with_status_and_dates() {
+        let Some(pool) = test_pool() else { return };
+        let rooms = RoomService::new(pool.clone());
+        let number = format!("F{}", &Uuid::new_v4().simple().to_string()[..8]);
+        let room = rooms.create_room(&number, RoomType::SINGLE).unwrap();
+        let other = rooms
+            .create_room(&format!("{}B", number), RoomType::SINGLE)
+            .unwrap();
+        let service = BookingService::new(pool.clone());
+        let first = Utc::now().date_naive() + Duration::days(50);
+
+        let early = service
+            .create_booking(
+                &StaffBookingRequest {
+                    guest_name: format!("Early {}", number),
+                    room_id: room.id,
+                    check_in_date: first,
+                    check_out_date: first + Duration::days(2),
+                    price: None,
+                    allow_duplicate: false,
+                    override_conflict: false,
+                },
+                None,
+            )
+            .unwrap();
+        let late = service
+            .create_booking(
+                &StaffBookingRequest {
+                    guest_name: format!("Late {}", number),
+                    room_id: room.id,
+                    check_in_date: first + Duration::days(5),
+                    check_out_date: first + Duration::days(7),
+                    price: None,
+                    allow_duplicate: false,
+                    override_conflict: false,
+                },
+                None,
+            )
+            .unwrap();
+        let cancelled = service
+            .create_booking(
+                &StaffBookingRequest {
+                    guest_name: format!("Gone {}", number),
+                    room_id: room.id,
+                    check_in_date: first + Duration::days(10),
+                    check_out_date: first + Duration::days(11),
+                    price: None,
+                    allow_duplicate: false,
+                    override_conflict: false,
+                },
+                None,
+            )
+            .unwrap();
+        diesel::update(bookings::table.find(cancelled.id))
+            .set(bookings::status.eq(BookingStatus::Cancelled))
+            .execute(&mut pool.get().unwrap())
+            .unwrap();
+        service
+            .create_booking(
+                &StaffBookingRequest {
+                    guest_name: format!("Elsewhere {}", number),
+                    room_id: other.id,
+                    check_in_date: first,
+                    check_out_date: first + Duration::days(2),
+                    price: None,
+                    allow_duplicate: false,
+                    override_conflict: false,
+                },
+                None,
+            )
+            .unwrap();
+
+        let references = |filter: BookingListFilter| -> Vec<String> {
+            service
+                .list_bookings(&filter)
+                .unwrap()
+                .into_iter()
+                .map(|b| b.booking.reference)
+                .collect()
+        };
+
+        let by_room = BookingListFilter { room_id: Some(room.id), ..Default::default() };
+        assert_eq!(references(by_room).len(), 3);
+        assert_eq!(
+            references(BookingListFilter { status: Some(BookingStatus::Upcoming), ..by_room }),
+            vec![early.reference.clone(), late.reference.clone()]
+        );
+        assert_eq!(
+            references(BookingListFilter {
+                status: Some(BookingStatus::Upcoming),
+                from_date: Some(first + Duration::days(1)),
+                to_date: Some(first + Duration::days(9)),
+                ..by_room
+            }),
+            vec![late.reference.clone()]
+        );
+        assert_eq!(
+            references(BookingListFilter { creation_source: Some("staff"), ..by_room }).len(),
+            3
+        );
+        assert!(references(BookingListFilter { creation_source: Some("guest"), ..by_room }).is_empty());
+    }
+
+    #[test]
+    fn test_unknown_creation_source_is_rejected() {
+        let Some(pool) = test_pool() else { return };
+        assert!(matches!(
+            BookingService::new(pool).list_bookings(&BookingListFilter {
+                creation_source: Some("kiosk"),
+                ..Default::default()
+            }),
+            Err(AppError::ValidationError(_))
+        ));
+    }
+
+    #[test]
+    fn test_staff_creator_is_listed_by_username() {
+        let Some(pool) = test_pool() else { return };
+        let auth = AuthService::new(pool.clone(), "test-secret".to_string());
+        let suffix = &Uuid::new_v4().simple().to_string()[..8];
+        let receptionist = auth
+            .create_user(&CreateUserRequest {
+                username: format!("desk-{}", suffix),
+                password: "desk-password-1".to_string(),
+                role: UserRole::Receptionist,
+                must_change_password: false,
+            })
+            .unwrap();
+        let guest_name = format!("Portal {}", suffix);
+        let guest = auth
+            .register_guest(&GuestRegisterRequest {
+                email: format!("portal-{}@example.com", suffix),
+                password: "guest-password-1".to_string(),
+                full_name: guest_name.clone(),
+            })
+            .unwrap()
+            .user;
+        // Guests must verify their email before booking
+        diesel::update(users::table.find(guest.id))
+            .set(users::email_verified_at.eq(Some(Utc::now())))
+            .execute(&mut pool.get().unwrap())
+            .unwrap();
+        let room = RoomService::new(pool.clone())
+            .create_room(&format!("C{}", suffix), RoomType::DOUBLE)
+            .unwrap();
+        let service = BookingService::new(pool.clone());
+        let first = Utc::now().date_naive() + Duration::days(50);
+
+        let phoned = service
+            .create_booking(
+                &StaffBookingRequest {
+                    guest_name: format!("Phoned {}", suffix),
+                    room_id: room.id,
+                    check_in_date: first,
+                    check_out_date: first + Duration::days(2),
+                    price: None,
+                    allow_duplicate: false,
+                    override_conflict: false,
+                },
+                Some(receptionist.id),
+            )
+            .unwrap();
+        assert_eq!(phoned.created_by_user_id, Some(receptionist.id));
+        assert_eq!(phoned.creation_source, "staff");
+        service
+            .create_guest_booking(guest.id, &guest_name, room.id, first + Duration::days(3), first + Duration::days(4), None)
+            .unwrap();
+
+        let creators: Vec<(String, Option<String>)> = service
+            .list_bookings(&BookingListFilter { room_id: Some(room.id), ..Default::default() })
+            .unwrap()
+            .into_iter()
+            .map(|b| (b.booking.creation_source, b.created_by))
+            .collect();
+        assert_eq!(
+            creators,
+            vec![
+                ("staff".to_string(), receptionist.username.clone()),
+                ("guest".to_string(), None),
+            ]
+        );
+        assert_eq!(
+            service.get_booking_with_room(phoned.id).unwrap().created_by,
+            receptionist.username
+        );
+    }
+}
@@ -0,0 +1,96 @@
+//! Booking modification tests
+//!
+//! Tests for diffing a booking before and after an edit and for rescaling the
+//! price when the stay dates change.
+
+use bigdecimal::BigDecimal;
+use chrono::{NaiveDate, Utc};
+use uuid::Uuid;
+
+use hotel_management_backend::models::{diff_bookings, Booking, BookingStatus, FieldChange};
+use hotel_management_backend::services::BookingService;
+
+fn booking() -> Booking {
+    Booking {
+        id: Uuid::new_v4(),
+        reference: "BK-20250401-MODS".to_string(),
+        guest_name: "Nguyen Van A".to_string(),
+        room_id: Uuid::new_v4(),
+        check_in_date: NaiveDate::from_ymd_opt(2025, 4, 1).unwrap(),
+        check_out_date: NaiveDate::from_ymd_opt(2025, 4, 3).unwrap(),
+        status: BookingStatus::Upcoming,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+        created_by_user_id: None,
+        creation_source: "staff".to_string(),
+        price: BigDecimal::from(2000000),
+        group_reference: None,
+        checked_in_at: None,
+        checked_out_at: None,
+    }
+}
+
+mod diff_tests {
+    use super::*;
+
+    #[test]
+    fn test_unchanged_booking_has_no_changes() {
+        let before = booking();
+        let mut after = before.clone();
+        after.updated_at = Utc::now();
+        assert!(diff_bookings(&before, &after).is_empty());
+    }
+
+    #[test]
+    fn test_date_change_records_dates_and_price() {
+        let before = booking();
+        let mut after = before.clone();
+        after.check_out_date = NaiveDate::from_ymd_opt(2025, 4, 5).unwrap();
+        after.price = BigDecimal::from(4000000);
+
+        let changes = diff_bookings(&before, &after);
+
+        assert_eq!(changes.keys().copied().collect::<Vec<_>>(), vec!["check_out_date", "price"]);
+        assert_eq!(
+            changes["check_out_date"],
+            FieldChange { before: "2025-04-03".into(), after: "2025-04-05".into() }
+        );
+        assert_eq!(changes["price"].before, "2000000");
+        assert_eq!(changes["price"].after, "4000000");
+    }
+
+    #[test]
+    fn test_changes_serialize_as_before_after_object() {
+        let before = booking();
+        let mut after = before.clone();
+        after.guest_name = "Nguyen Van B".to_string();
+
+        let json = serde_json::to_value(diff_bookings(&before, &after)).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"guest_name": {"before": "Nguyen Van A", "after": "Nguyen Van B"}})
+        );
+    }
+}
+
+mod price_rescale_tests {
+    use super::*;
+
+    #[test]
+    fn test_extending_stay_keeps_nightly_rate() {
+        let price = BookingService::rescale_price(&BigDecimal::from(2000000), 2, 5);
+        assert_eq!(price, BigDecimal::from(5000000));
+    }
+
+    #[test]
+    fn test_uneven_rate_is_rounded() {
+        let price = BookingService::rescale_price(&BigDecimal::from(1000000), 3, 1);
+        assert_eq!(price, BigDecimal::from(333333));
+    }
+
+    #[test]
+    fn test_same_length_keeps_negotiated_price() {
+        let price = BookingService::rescale_price(&BigDecimal::from(1234567), 2, 2);
+        assert_eq!(price, BigDecimal::from(1234567));
+    }
+}
@@ -0,0 +1,107 @@
+//! Booking note tests
+//!
+//! Tests for validating note text and for attaching notes to a booking. The
+//! storage tests need a migrated PostgreSQL database and only run when
+//! TEST_DATABASE_URL is set.
+
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+
+use hotel_management_backend::db::{create_pool, DbPool};
+use hotel_management_backend::errors::AppError;
+use hotel_management_backend::models::{RoomType, UserRole};
+use hotel_management_backend::services::booking_service::StaffBookingRequest;
+use hotel_management_backend::services::auth_service::CreateUserRequest;
+use hotel_management_backend::services::{AuthService, BookingService, GuestService, RoomService};
+
+mod validate_note_tests {
+    use super::*;
+
+    #[test]
+    fn test_blank_and_oversized_notes_are_rejected() {
+        for note in ["   ".to_string(), "x".repeat(10_001)] {
+            assert!(matches!(
+                GuestService::validate_note(&note),
+                Err(AppError::ValidationError(_))
+            ));
+        }
+        assert!(GuestService::validate_note(&"x".repeat(10_000)).is_ok());
+    }
+}
+
+fn test_pool() -> Option<DbPool> {
+    match std::env::var("TEST_DATABASE_URL") {
+        Ok(url) => Some(create_pool(&url)),
+        Err(_) => {
+            eprintln!("TEST_DATABASE_URL not set, skipping database test");
+            None
+        }
+    }
+}
+
+mod storage_tests {
+    use super::*;
+
+    #[test]
+    fn test_notes_are_listed_and_inlined_in_detail() {
+        let Some(pool) = test_pool() else { return };
+        let suffix = &Uuid::new_v4().simple().to_string()[..8];
+        let room = RoomService::new(pool.clone())
+            .create_room(&format!("N{}", suffix), RoomType::SINGLE)
+            .unwrap();
+        let receptionist = AuthService::new(pool.clone(), "test-secret".to_string())
+            .create_user(&CreateUserRequest {
+                username: format!("notes-{}", suffix),
+                password: "desk-password-1".to_string(),
+                role: UserRole::Receptionist,
+                must_change_password: false,
+            })
+            .unwrap();
+        let service = BookingService::new(pool.clone());
+        let check_in = Utc::now().date_naive() + Duration::days(20);
+        let booking = service
+            .create_booking(
+                &StaffBookingRequest {
+                    guest_name: format!("Noted {}", suffix),
+                    room_id: room.id,
+                    check_in_date: check_in,
+                    check_out_date: check_in + Duration::days(1),
+                    price: None,
+                    allow_duplicate: false,
+                    override_conflict: false,
+                },
+                None,
+            )
+            .unwrap();
+
+        let first = service
+            .add_booking_note(booking.id, receptionist.id, "  Arrives after midnight ")
+            .unwrap();
+        assert_eq!(first.note, "Arrives after midnight");
+        assert_eq!(first.author_user_id, receptionist.id);
+        let second = service
+            .add_booking_note(booking.id, receptionist.id, "Allergic to feather pillows")
+            .unwrap();
+
+        let listed = service.list_booking_notes(booking.id).unwrap();
+        assert_eq!(listed.len(), 2);
+        assert!(listed.contains(&first) && listed.contains(&second));
+
+        let detail = service.get_booking_with_room(booking.id).unwrap();
+        assert_eq!(detail.notes, Some(listed));
+    }
+
+    #[test]
+    fn test_unknown_booking_is_not_found() {
+        let Some(pool) = test_pool() else { return };
+        let service = BookingService::new(pool);
+        assert!(matches!(
+            service.list_booking_notes(Uuid::new_v4()),
+            Err(AppError::NotFound(_))
+        ));
+        assert!(matches!(
+            service.add_booking_note(Uuid::new_v4(), Uuid::new_v4(), "Late arrival"),
+            Err(AppError::NotFound(_))
+        ));
+    }
+}
@@ -0,0 +1,190 @@
+//! Booking reference tests
+//!
+//! Tests for generating references and retrying inserts that collide on the
+//! unique reference constraint.
+
+use std::cell::RefCell;
+use std::collections::HashSet;
+
+use diesel::result::{DatabaseErrorInformation, DatabaseErrorKind, Error, QueryResult};
+use regex::Regex;
+
+use hotel_management_backend::errors::AppError;
+use hotel_management_backend::services::booking_service::{
+    MAX_REFERENCE_ATTEMPTS, REFERENCE_ALPHABET, REFERENCE_CONSTRAINT, REFERENCE_FORMAT,
+    REFERENCE_SUFFIX_BITS,
+};
+use hotel_management_backend::services::BookingService;
+
+/// Database error details carrying only a constraint name
+struct ConstraintError(&'static str);
+
+impl DatabaseErrorInformation for ConstraintError {
+    fn message(&self) -> &str {
+        "duplicate key value violates unique constraint"
+    }
+    fn details(&self) -> Option<&str> {
+        None
+    }
+    fn hint(&self) -> Option<&str> {
+        None
+    }
+    fn table_name(&self) -> Option<&str> {
+        Some("bookings")
+    }
+    fn column_name(&self) -> Option<&str> {
+        None
+    }
+    fn constraint_name(&self) -> Option<&str> {
+        Some(self.0)
+    }
+    fn statement_position(&self) -> Option<i32> {
+        None
+    }
+}
+
+fn unique_violation(constraint: &'static str) -> Error {
+    Error::DatabaseError(DatabaseErrorKind::UniqueViolation, Box::new(ConstraintError(constraint)))
+}
+
+/// Simulated bookings table that rejects references already taken
+fn insert_into(taken: &RefCell<HashSet<String>>, reference: &str) -> QueryResult<String> {
+    if !taken.borrow_mut().insert(reference.to_string()) {
+        return Err(unique_violation(REFERENCE_CONSTRAINT));
+    }
+    Ok(reference.to_string())
+}
+
+mod reference_format_tests {
+    use super::*;
+
+    #[test]
+    fn test_reference_format() {
+        let reference = BookingService::generate_reference();
+        let parts: Vec<&str> = reference.split('-').collect();
+
+        assert_eq!(parts.len(), 3);
+        assert_eq!(parts[0], "BK");
+        assert_eq!(parts[1].len(), 8);
+        assert!(parts[1].chars().all(|c| c.is_ascii_digit()));
+        assert_eq!(parts[2].len(), 4);
+        assert!(parts[2].chars().all(|c| c.is_ascii_digit() || c.is_ascii_uppercase()));
+    }
+
+    #[test]
+    fn test_generated_references_match_the_documented_pattern() {
+        // Same pattern as booking_tests, so existing references stay valid
+        let pattern = Regex::new(r"^BK-\d{8}-[A-Z0-9]{4}$").unwrap();
+        assert_eq!(REFERENCE_FORMAT.len(), BookingService::generate_reference().len());
+
+        for _ in 0..200 {
+            let reference = BookingService::generate_reference();
+            assert!(pattern.is_match(&reference), "{}", reference);
+            let suffix = &reference[reference.len() - 4..];
+            assert!(suffix.bytes().all(|b| REFERENCE_ALPHABET.contains(&b)), "{}", reference);
+        }
+    }
+
+    #[test]
+    fn test_alphabet_has_no_look_alikes() {
+        let symbols: HashSet<u8> = REFERENCE_ALPHABET.iter().copied().collect();
+        assert_eq!(symbols.len(), 1 << 5);
+        for ambiguous in [b'0', b'O', b'1', b'I'] {
+            assert!(!symbols.contains(&ambiguous));
+        }
+    }
+
+    #[test]
+    fn test_suffix_encoding_is_deterministic() {
+        let max = (1u32 << REFERENCE_SUFFIX_BITS) - 1;
+        assert_eq!(BookingService::reference_suffix(0), "2222");
+        assert_eq!(BookingService::reference_suffix(max), "ZZZZ");
+        assert_eq!(BookingService::reference_suffix(1), "2223");
+        assert_eq!(BookingService::reference_suffix(32), "2232");
+        // Bits above the suffix width are ignored
+        assert_eq!(BookingService::reference_suffix(max + 1), "2222");
+
+        let pattern = Regex::new(r"^[A-Z0-9]{4}$").unwrap();
+        let suffixes: HashSet<String> = (0..4096).map(|v| BookingService::reference_suffix(v * 255)).collect();
+        assert_eq!(suffixes.len(), 4096);
+        assert!(suffixes.iter().all(|s| pattern.is_match(s)));
+    }
+}
+
+mod collision_retry_tests {
+    use super::*;
+
+    #[test]
+    fn test_collision_is_detected_by_constraint_name() {
+        assert!(BookingService::is_reference_collision(&unique_violation(REFERENCE_CONSTRAINT)));
+        assert!(!BookingService::is_reference_collision(&unique_violation("users_username_key")));
+        assert!(!BookingService::is_reference_collision(&Error::NotFound));
+    }
+
+    #[test]
+    fn test_colliding_reference_is_regenerated() {
+        let taken = RefCell::new(HashSet::from(["BK-20250401-AAAA".to_string()]));
+        let mut candidates = vec!["BK-20250401-BBBB", "BK-20250401-AAAA"];
+
+        let inserted = BookingService::insert_with_fresh_reference(
+            || candidates.pop().unwrap().to_string(),
+            |reference| insert_into(&taken, reference),
+        )
+        .unwrap();
+
+        assert_eq!(inserted, "BK-20250401-BBBB");
+        assert_eq!(taken.borrow().len(), 2);
+    }
+
+    #[test]
+    fn test_concurrent_inserts_get_distinct_references() {
+        // Both writers draw the same first suffix; the loser retries
+        let taken = RefCell::new(HashSet::new());
+        let mut first = vec!["BK-20250401-CCCC", "BK-20250401-SAME"];
+        let mut second = vec!["BK-20250401-DDDD", "BK-20250401-SAME"];
+
+        let a = BookingService::insert_with_fresh_reference(
+            || first.pop().unwrap().to_string(),
+            |reference| insert_into(&taken, reference),
+        )
+        .unwrap();
+        let b = BookingService::insert_with_fresh_reference(
+            || second.pop().unwrap().to_string(),
+            |reference| insert_into(&taken, reference),
+        )
+        .unwrap();
+
+        assert_eq!(a, "BK-20250401-SAME");
+        assert_eq!(b, "BK-20250401-DDDD");
+    }
+
+    #[test]
+    fn test_gives_up_after_max_attempts() {
+        let mut attempts = 0;
+        let result: Result<(), AppError> = BookingService::insert_with_fresh_reference(
+            || "BK-20250401-AAAA".to_string(),
+            |_| {
+                attempts += 1;
+                Err(unique_violation(REFERENCE_CONSTRAINT))
+            },
+        );
+
+        assert!(matches!(result, Err(AppError::InternalError(_))));
+        assert_eq!(attempts, MAX_REFERENCE_ATTEMPTS);
+    }
+
+    #[test]
+    fn test_other_errors_are_not_retried() {
+        let mut attempts = 0;
+        let result: Result<(), AppError> = BookingService::insert_with_fresh_reference(
+            BookingService::generate_reference,
+            |_| {
+                attempts += 1;
+                Err(unique_violation("bookings_other_key"))
+            },
+        );
+
+        assert!(matches!(result, Err(AppError::DatabaseError(_))));
+        assert_eq!(attempts, 1);
+    }
+}
@@ -0,0 +1,145 @@
+//! Booking search tests
+//!
+//! Tests for the staff booking search by reference prefix, guest name, guest
+//! phone and room number. They need a migrated PostgreSQL database and only
+//! run when TEST_DATABASE_URL is set.
+
+use chrono::{Duration, Utc};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use hotel_management_backend::db::{create_pool, DbPool};
+use hotel_management_backend::errors::AppError;
+use hotel_management_backend::models::{Booking, Room, RoomType};
+use hotel_management_backend::schema::users;
+use hotel_management_backend::services::auth_service::GuestRegisterRequest;
+use hotel_management_backend::services::booking_service::{StaffBookingRequest, BOOKING_SEARCH_LIMIT};
+use hotel_management_backend::services::{AuthService, BookingService, RoomService};
+
+fn test_pool() -> Option<DbPool> {
+    match std::env::var("TEST_DATABASE_URL") {
+        Ok(url) => Some(create_pool(&url)),
+        Err(_) => {
+            eprintln!("TEST_DATABASE_URL not set, skipping database test");
+            None
+        }
+    }
+}
+
+mod search_tests {
+    use super::*;
+
+    fn room(pool: &DbPool) -> Room {
+        let number = format!("S{}", &Uuid::new_v4().simple().to_string()[..8]);
+        RoomService::new(pool.clone()).create_room(&number, RoomType::DOUBLE).unwrap()
+    }
+
+    /// Book `room` for a guest whose name is suffixed so reruns against the
+    /// same database do not trip the duplicate-stay warning
+    fn book(pool: &DbPool, room: &Room, guest_name: &str, days_ahead: i64) -> Booking {
+        let check_in_date = Utc::now().date_naive() + Duration::days(days_ahead);
+        BookingService::new(pool.clone())
+            .create_booking(
+                &StaffBookingRequest {
+                    guest_name: format!("{} {}", guest_name, &Uuid::new_v4().simple().to_string()[..8]),
+                    room_id: room.id,
+                    check_in_date,
+                    check_out_date: check_in_date + Duration::days(1),
+                    price: None,
+                    allow_duplicate: false,
+                    override_conflict: false,
+                },
+                None,
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn test_room_number_matches_exactly() {
+        let Some(pool) = test_pool() else { return };
+        let room = room(&pool);
+        let early = book(&pool, &room, "Room Search Early", 20);
+        let late = book(&pool, &room, "Room Search Late", 25);
+        let service = BookingService::new(pool);
+
+        let found = service.search_bookings(&room.number).unwrap();
+        let ids: Vec<Uuid> = found.iter().map(|b| b.booking.id).collect();
+        // Latest check-in first
+        assert_eq!(ids, vec![late.id, early.id]);
+        assert_eq!(found[0].room.as_ref().unwrap().number, room.number);
+
+        // Part of a room number is not a match
+        assert!(service.search_bookings(&room.number[..5]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_exact_reference_ranks_first() {
+        let Some(pool) = test_pool() else { return };
+        let room = room(&pool);
+        let booking = book(&pool, &room, "Reference Search", 20);
+        let service = BookingService::new(pool);
+
+        let found = service.search_bookings(&booking.reference.to_lowercase()).unwrap();
+        assert_eq!(found[0].booking.id, booking.id);
+
+        let prefix = &booking.reference[..11];
+        let found = service.search_bookings(prefix).unwrap();
+        assert!(!found.is_empty());
+        assert!(found.len() as i64 <= BOOKING_SEARCH_LIMIT);
+        assert!(found.iter().all(|b| b.booking.reference.starts_with(prefix)));
+    }
+
+    #[test]
+    fn test_guest_name_and_phone_match_substrings() {
+        let Some(pool) = test_pool() else { return };
+        let suffix = Uuid::new_v4().simple().to_string();
+        // Unlike the staff booking, the guest's name does not contain the first part of the suffix
+        let guest_name = format!("Phone Search {}", &suffix[24..]);
+        let room = room(&pool);
+        let staff_booking = book(&pool, &room, &format!("Nguyen {} Van", &suffix[..8]), 20);
+
+        let guest = AuthService::new(pool.clone(), "test-secret".to_string())
+            .register_guest(&GuestRegisterRequest {
+                email: format!("search-{}@example.com", &suffix[..8]),
+                password: "guest-password-1".to_string(),
+                full_name: guest_name.clone(),
+            })
+            .unwrap()
+            .user;
+        let digits: String = suffix.chars().filter(|c| c.is_ascii_digit()).take(6).collect();
+        let phone = format!("+8490{:0>6}", digits);
+        diesel::update(users::table.find(guest.id))
+            .set((users::phone.eq(&phone), users::email_verified_at.eq(Some(Utc::now()))))
+            .execute(&mut pool.get().unwrap())
+            .unwrap();
+        let check_in = Utc::now().date_naive() + Duration::days(30);
+        let guest_booking = BookingService::new(pool.clone())
+            .create_guest_booking(
+                guest.id,
+                &guest_name,
+                room.id,
+                check_in,
+                check_in + Duration::days(1),
+                None,
+            )
+            .unwrap();
+        let service = BookingService::new(pool);
+
+        let found = service.search_bookings(&suffix[..8].to_uppercase()).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].booking.id, staff_booking.id);
+
+        let found = service.search_bookings(&phone[3..]).unwrap();
+        assert!(found.iter().any(|b| b.booking.id == guest_booking.booking.id));
+        assert!(found.iter().all(|b| b.booking.id != staff_booking.id));
+    }
+
+    #[test]
+    fn test_short_query_is_rejected() {
+        let Some(pool) = test_pool() else { return };
+        let service = BookingService::new(pool);
+
+        assert!(matches!(service.search_bookings(" B "), Err(AppError::ValidationError(_))));
+        assert!(matches!(service.search_bookings(""), Err(AppError::ValidationError(_))));
+    }
+}
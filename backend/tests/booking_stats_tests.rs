@@ -0,0 +1,159 @@
+//! Booking statistics tests
+//!
+//! Tests for assembling the per-day statistics, for the range cap and for the
+//! SQL aggregation. The aggregation test needs a migrated PostgreSQL database
+//! and only runs when TEST_DATABASE_URL is set.
+
+use chrono::{Duration, NaiveDate, Utc};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use hotel_management_backend::db::{create_pool, DbPool};
+use hotel_management_backend::errors::AppError;
+use hotel_management_backend::models::{RoomType, UserRole};
+use hotel_management_backend::schema::users;
+use hotel_management_backend::services::auth_service::{CreateUserRequest, GuestRegisterRequest};
+use hotel_management_backend::services::booking_service::{BookingStats, StaffBookingRequest, MAX_BOOKING_STATS_DAYS};
+use hotel_management_backend::services::{AuthService, BookingService, RoomService};
+
+fn date(day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2025, 6, day).unwrap()
+}
+
+mod build_tests {
+    use super::*;
+
+    #[test]
+    fn test_every_day_is_listed_with_sources_split() {
+        let created = [
+            (date(2), "guest".to_string(), 3),
+            (date(2), "staff".to_string(), 1),
+            (date(4), "staff".to_string(), 4),
+        ];
+        let stats = BookingService::build_booking_stats(date(1), date(4), &created, &[(date(3), 2)], 2, 16);
+
+        assert_eq!(stats.days.len(), 4);
+        assert_eq!(stats.days[0].created, 0);
+        assert_eq!(
+            (stats.days[1].created, stats.days[1].created_by_guests, stats.days[1].created_by_staff),
+            (4, 3, 1)
+        );
+        assert_eq!(stats.days[2].cancelled, 2);
+        assert_eq!(stats.days[3].created_by_staff, 4);
+
+        assert_eq!(stats.total_bookings, 8);
+        assert_eq!((stats.created_by_guests, stats.created_by_staff), (3, 5));
+        assert_eq!(stats.guest_share, 0.375);
+        assert_eq!(stats.cancellation_rate, 0.25);
+        assert_eq!(stats.average_nights, 2.0);
+    }
+
+    #[test]
+    fn test_empty_range_has_zero_rates() {
+        let stats = BookingService::build_booking_stats(date(1), date(1), &[], &[], 0, 0);
+        assert_eq!(stats.total_bookings, 0);
+        assert_eq!(stats.guest_share, 0.0);
+        assert_eq!(stats.cancellation_rate, 0.0);
+        assert_eq!(stats.average_nights, 0.0);
+    }
+
+    #[test]
+    fn test_range_is_capped_at_a_year() {
+        let from = date(1);
+        assert!(BookingService::validate_booking_stats_range(
+            from,
+            from + Duration::days(MAX_BOOKING_STATS_DAYS - 1)
+        )
+        .is_ok());
+
+        match BookingService::validate_booking_stats_range(from, from + Duration::days(MAX_BOOKING_STATS_DAYS)) {
+            Err(AppError::ValidationError(msg)) => assert!(msg.contains("366")),
+            other => panic!("Expected ValidationError, got {:?}", other),
+        }
+        assert!(BookingService::validate_booking_stats_range(date(2), date(1)).is_err());
+    }
+}
+
+fn test_pool() -> Option<DbPool> {
+    match std::env::var("TEST_DATABASE_URL") {
+        Ok(url) => Some(create_pool(&url)),
+        Err(_) => {
+            eprintln!("TEST_DATABASE_URL not set, skipping database test");
+            None
+        }
+    }
+}
+
+mod aggregation_tests {
+    use super::*;
+
+    fn around_today(service: &BookingService) -> BookingStats {
+        let today = Utc::now().date_naive();
+        service
+            .get_booking_stats(today - Duration::days(1), today + Duration::days(1))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_new_bookings_and_cancellations_are_counted() {
+        let Some(pool) = test_pool() else { return };
+        let service = BookingService::new(pool.clone());
+        let auth = AuthService::new(pool.clone(), "test-secret".to_string());
+        let suffix = &Uuid::new_v4().simple().to_string()[..8];
+        let rooms = RoomService::new(pool.clone());
+        let guest_room = rooms.create_room(&format!("S{}", suffix), RoomType::DOUBLE).unwrap();
+        let staff_room = rooms.create_room(&format!("T{}", suffix), RoomType::SINGLE).unwrap();
+        let guest_name = format!("Stats Guest {}", suffix);
+        let guest = auth
+            .register_guest(&GuestRegisterRequest {
+                email: format!("stats-{}@example.com", suffix),
+                password: "guest-password-1".to_string(),
+                full_name: guest_name.clone(),
+            })
+            .unwrap()
+            .user;
+        // Guests must verify their email before booking
+        diesel::update(users::table.find(guest.id))
+            .set(users::email_verified_at.eq(Some(Utc::now())))
+            .execute(&mut pool.get().unwrap())
+            .unwrap();
+        let receptionist = auth
+            .create_user(&CreateUserRequest {
+                username: format!("stats-{}", suffix),
+                password: "desk-password-1".to_string(),
+                role: UserRole::Receptionist,
+                must_change_password: false,
+            })
+            .unwrap();
+
+        let before = around_today(&service);
+        let check_in = Utc::now().date_naive() + Duration::days(60);
+        service
+            .create_guest_booking(guest.id, &guest_name, guest_room.id, check_in, check_in + Duration::days(2), None)
+            .unwrap();
+        let staff_booking = service
+            .create_booking(
+                &StaffBookingRequest {
+                    guest_name: format!("Staff Guest {}", suffix),
+                    room_id: staff_room.id,
+                    check_in_date: check_in,
+                    check_out_date: check_in + Duration::days(3),
+                    price: None,
+                    allow_duplicate: false,
+                    override_conflict: false,
+                },
+                Some(receptionist.id),
+            )
+            .unwrap();
+        service.cancel(staff_booking.id, receptionist.id).unwrap();
+        let after = around_today(&service);
+
+        assert_eq!(after.total_bookings - before.total_bookings, 2);
+        assert_eq!(after.created_by_guests - before.created_by_guests, 1);
+        assert_eq!(after.created_by_staff - before.created_by_staff, 1);
+        assert_eq!(after.cancelled_bookings - before.cancelled_bookings, 1);
+        assert_eq!(after.total_nights - before.total_nights, 5);
+        let cancelled = |stats: &BookingStats| stats.days.iter().map(|d| d.cancelled).sum::<i64>();
+        assert_eq!(cancelled(&after) - cancelled(&before), 1);
+    }
+}
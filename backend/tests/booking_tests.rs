@@ -85,7 +85,7 @@ mod date_validation_tests {
         let check_out = days_from_now(5); // Same day
 
         assert!(
-            !(check_out > check_in),
+            check_out <= check_in,
             "Same-day checkout should be invalid"
         );
     }
@@ -96,7 +96,7 @@ mod date_validation_tests {
         let check_out = days_from_now(5); // Before check-in
 
         assert!(
-            !(check_out > check_in),
+            check_out <= check_in,
             "Check-out before check-in should be invalid"
         );
     }
@@ -250,7 +250,7 @@ mod availability_check_tests {
     fn test_room_available_between_bookings() {
         // Existing bookings: Jan 5-10 and Jan 20-25
         // New: Jan 12-18 (fits in the gap)
-        let bookings = vec![
+        let bookings = [
             BookingPeriod::new(days_from_now(5), days_from_now(10)),
             BookingPeriod::new(days_from_now(20), days_from_now(25)),
         ];
@@ -431,4 +431,219 @@ mod checkout_room_status_tests {
             "Occupied rooms must be allowed to transition to Dirty on checkout"
         );
     }
-}
\ No newline at end of file
+}
+// ============================================================================
+// EARLY CHECK-OUT
+// ============================================================================
+
+mod early_check_out_tests {
+    use super::*;
+    use bigdecimal::BigDecimal;
+    use hotel_management_backend::errors::AppError;
+    use hotel_management_backend::models::Booking;
+    use hotel_management_backend::services::booking_service::EARLY_CHECK_OUT_MESSAGE;
+    use hotel_management_backend::services::BookingService;
+    use uuid::Uuid;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 7, day).unwrap()
+    }
+
+    /// Checked-in stay from July 10 to July 14
+    fn stay() -> Booking {
+        Booking {
+            id: Uuid::new_v4(),
+            reference: "BK-20250710-EARL".to_string(),
+            guest_name: "Le Van C".to_string(),
+            room_id: Uuid::new_v4(),
+            check_in_date: date(10),
+            check_out_date: date(14),
+            status: BookingStatus::CheckedIn,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            created_by_user_id: None,
+            creation_source: "staff".to_string(),
+            price: BigDecimal::from(4_000_000),
+            group_reference: None,
+            checked_in_at: None,
+            checked_out_at: None,
+        }
+    }
+
+    #[test]
+    fn on_time_check_out_keeps_scheduled_date() {
+        assert_eq!(
+            BookingService::actual_check_out_date(&stay(), date(14), false).unwrap(),
+            date(14)
+        );
+    }
+
+    #[test]
+    fn overstay_check_out_ends_today() {
+        assert_eq!(
+            BookingService::actual_check_out_date(&stay(), date(16), false).unwrap(),
+            date(16)
+        );
+    }
+
+    #[test]
+    fn early_check_out_needs_confirmation() {
+        match BookingService::actual_check_out_date(&stay(), date(12), false) {
+            Err(AppError::ValidationError(msg)) => {
+                assert!(msg.starts_with(EARLY_CHECK_OUT_MESSAGE));
+                assert!(msg.contains("2025-07-14"));
+            }
+            other => panic!("Expected ValidationError, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn confirmed_early_check_out_releases_remaining_nights() {
+        assert_eq!(
+            BookingService::actual_check_out_date(&stay(), date(12), true).unwrap(),
+            date(12)
+        );
+    }
+
+    #[test]
+    fn confirmed_check_out_on_arrival_day_keeps_first_night() {
+        assert_eq!(
+            BookingService::actual_check_out_date(&stay(), date(10), true).unwrap(),
+            date(11)
+        );
+    }
+}
+
+mod stay_length_tests {
+    use super::*;
+    use hotel_management_backend::errors::AppError;
+    use hotel_management_backend::services::booking_service::StayLimits;
+    use hotel_management_backend::services::BookingService;
+    use hotel_management_backend::settings::Settings;
+    use std::collections::HashMap;
+
+    fn nights(n: i64) -> (NaiveDate, NaiveDate) {
+        let check_in = NaiveDate::from_ymd_opt(2025, 8, 1).unwrap();
+        (check_in, check_in + chrono::Duration::days(n))
+    }
+
+    fn check(n: i64, limits: StayLimits) -> Result<(), String> {
+        let (check_in, check_out) = nights(n);
+        BookingService::validate_stay_length(check_in, check_out, limits).map_err(|e| match e {
+            AppError::ValidationError(msg) => msg,
+            other => panic!("Expected ValidationError, got {:?}", other),
+        })
+    }
+
+    #[test]
+    fn defaults_allow_one_to_thirty_nights() {
+        assert_eq!(StayLimits::default(), StayLimits { min_nights: 1, max_nights: 30 });
+        assert!(check(1, StayLimits::default()).is_ok());
+        assert!(check(30, StayLimits::default()).is_ok());
+    }
+
+    #[test]
+    fn typo_in_year_is_rejected_with_limit_and_request() {
+        let msg = check(183, StayLimits::default()).unwrap_err();
+        assert!(msg.contains("30"), "{}", msg);
+        assert!(msg.contains("183"), "{}", msg);
+    }
+
+    #[test]
+    fn minimum_stay_states_limit_and_request() {
+        let limits = StayLimits { min_nights: 2, max_nights: 14 };
+        let msg = check(1, limits).unwrap_err();
+        assert!(msg.contains("at least 2"), "{}", msg);
+        assert!(msg.contains("1 night"), "{}", msg);
+        assert!(check(2, limits).is_ok());
+    }
+
+    #[test]
+    fn limits_come_from_settings() {
+        let settings = Settings::from_values(HashMap::from([
+            ("booking_min_nights".to_string(), "3".to_string()),
+            ("booking_max_nights".to_string(), "90".to_string()),
+        ]));
+        assert_eq!(
+            StayLimits::from_settings(&settings),
+            StayLimits { min_nights: 3, max_nights: 90 }
+        );
+    }
+}
+
+// ============================================================================
+// RESPONSE SERIALIZATION
+// ============================================================================
+
+mod response_serialization_tests {
+    use super::*;
+    use bigdecimal::BigDecimal;
+    use hotel_management_backend::models::{Booking, BookingWithRoom, Room, RoomType};
+    use uuid::Uuid;
+
+    fn booking_with_room(price: i64, room_price: Option<i64>) -> BookingWithRoom {
+        let room_id = Uuid::new_v4();
+        BookingWithRoom {
+            booking: Booking {
+                id: Uuid::new_v4(),
+                reference: "BK-20250601-AB12".to_string(),
+                guest_name: "Le Van C".to_string(),
+                room_id,
+                check_in_date: NaiveDate::from_ymd_opt(2025, 6, 1).unwrap(),
+                check_out_date: NaiveDate::from_ymd_opt(2025, 6, 4).unwrap(),
+                status: BookingStatus::Upcoming,
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+                created_by_user_id: None,
+                creation_source: "staff".to_string(),
+                price: BigDecimal::from(price),
+                group_reference: None,
+                checked_in_at: None,
+                checked_out_at: None,
+            },
+            room: room_price.map(|room_price| Room {
+                id: room_id,
+                number: "305".to_string(),
+                room_type: RoomType::DOUBLE,
+                status: RoomStatus::Available,
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+                price: BigDecimal::from(room_price),
+                assigned_cleaner_id: None,
+                description: None,
+                amenities: vec![],
+                photo_urls: vec![],
+                decommissioned_at: None,
+                floor: 1,
+                building: None,
+                room_type_id: Uuid::new_v4(),
+            }),
+            modification_count: 0,
+            notes: None,
+            created_by: None,
+        }
+    }
+
+    #[test]
+    fn stored_price_is_the_total() {
+        let json = serde_json::to_value(booking_with_room(4_200_000, Some(1_500_000))).unwrap();
+        assert_eq!(json["nights"], 3);
+        assert_eq!(json["total_price"], "4200000");
+        assert_eq!(json["reference"], "BK-20250601-AB12");
+        assert!(json.get("notes").is_none());
+    }
+
+    #[test]
+    fn unpriced_booking_falls_back_to_room_price() {
+        let json = serde_json::to_value(booking_with_room(0, Some(1_500_000))).unwrap();
+        assert_eq!(json["total_price"], "4500000");
+    }
+
+    #[test]
+    fn unpriced_booking_with_deleted_room_has_no_total() {
+        let json = serde_json::to_value(booking_with_room(0, None)).unwrap();
+        assert_eq!(json["nights"], 3);
+        assert!(json["room"].is_null());
+        assert!(json["total_price"].is_null());
+    }
+}
@@ -0,0 +1,246 @@
+//! Booking timeline tests
+//!
+//! Tests for merging booking history sources into one feed, guest redaction,
+//! and pagination.
+
+use std::str::FromStr;
+
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+use uuid::Uuid;
+
+use hotel_management_backend::models::message::Message;
+use hotel_management_backend::models::{
+    Booking, BookingEvent, BookingStatus, NewBookingEvent, NoShowCharge, NoShowChargeStatus, Payment,
+    PaymentType, EVENT_DATE_CHANGE, EVENT_STATUS_CHANGE, SYSTEM_ACTOR_NOTE,
+};
+use hotel_management_backend::services::booking_service::{
+    TimelineEntryKind, TimelineSources, MAX_TIMELINE_PAGE_SIZE,
+};
+use hotel_management_backend::services::BookingService;
+
+fn at(hour: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2025, 3, 1, hour, 0, 0).unwrap()
+}
+
+fn sources() -> TimelineSources {
+    let guest_id = Uuid::new_v4();
+    let staff_id = Uuid::new_v4();
+    let booking = Booking {
+        id: Uuid::new_v4(),
+        reference: "BK-20250301-TIME".to_string(),
+        guest_name: "Tran Thi B".to_string(),
+        room_id: Uuid::new_v4(),
+        check_in_date: NaiveDate::from_ymd_opt(2025, 3, 10).unwrap(),
+        check_out_date: NaiveDate::from_ymd_opt(2025, 3, 12).unwrap(),
+        status: BookingStatus::CheckedIn,
+        created_at: at(2),
+        updated_at: at(2),
+        created_by_user_id: Some(guest_id),
+        creation_source: "guest".to_string(),
+        price: BigDecimal::from(2000000),
+        group_reference: None,
+        checked_in_at: None,
+        checked_out_at: None,
+    };
+
+    TimelineSources {
+        events: vec![
+            BookingEvent {
+                id: Uuid::new_v4(),
+                booking_id: booking.id,
+                event_type: EVENT_STATUS_CHANGE.to_string(),
+                from_status: Some(BookingStatus::Upcoming),
+                to_status: Some(BookingStatus::CheckedIn),
+                actor_user_id: Some(staff_id),
+                details: None,
+                created_at: at(9),
+            },
+            BookingEvent {
+                id: Uuid::new_v4(),
+                booking_id: booking.id,
+                event_type: EVENT_DATE_CHANGE.to_string(),
+                from_status: None,
+                to_status: None,
+                actor_user_id: None,
+                details: Some("Check-out moved from 2025-03-12 to 2025-03-11".to_string()),
+                created_at: at(5),
+            },
+        ],
+        payments: vec![Payment {
+            id: Uuid::new_v4(),
+            booking_id: booking.id,
+            amount: BigDecimal::from_str("500000").unwrap(),
+            payment_type: PaymentType::Deposit,
+            payment_method: "card".to_string(),
+            notes: Some("Paid at front desk".to_string()),
+            created_by_user_id: staff_id,
+            created_at: at(4),
+            updated_at: at(4),
+        }],
+        no_show_charges: vec![NoShowCharge {
+            id: Uuid::new_v4(),
+            booking_id: booking.id,
+            amount: BigDecimal::from_str("500000").unwrap(),
+            basis: "deposit".to_string(),
+            status: NoShowChargeStatus::Voided,
+            reason: Some("Guest arrived late and was checked in".to_string()),
+            reviewed_by_user_id: None,
+            reviewed_at: Some(at(9)),
+            payment_id: None,
+            created_at: at(6),
+            updated_at: at(9),
+        }],
+        proposal: Some(Message {
+            id: Uuid::new_v4(),
+            sender_id: Uuid::nil(),
+            receiver_id: guest_id,
+            content: "BOOKING_PROPOSAL:{}".to_string(),
+            image_url: None,
+            is_read: true,
+            created_at: at(1),
+            updated_at: at(1),
+            edited_at: None,
+            deleted_at: None,
+        }),
+        booking,
+    }
+}
+
+mod timeline_merge_tests {
+    use super::*;
+
+    #[test]
+    fn test_entries_are_sorted_oldest_first() {
+        let entries = BookingService::build_timeline(&sources(), false);
+
+        let kinds: Vec<TimelineEntryKind> = entries.iter().map(|e| e.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                TimelineEntryKind::ChatProposal,
+                TimelineEntryKind::Created,
+                TimelineEntryKind::Payment,
+                TimelineEntryKind::DateChange,
+                TimelineEntryKind::NoShowCharge,
+                TimelineEntryKind::StatusChange,
+                TimelineEntryKind::NoShowCharge,
+            ]
+        );
+        assert!(entries.windows(2).all(|w| w[0].occurred_at <= w[1].occurred_at));
+    }
+
+    #[test]
+    fn test_staff_view_keeps_actors_and_details() {
+        let entries = BookingService::build_timeline(&sources(), false);
+
+        let payment = entries.iter().find(|e| e.kind == TimelineEntryKind::Payment).unwrap();
+        assert!(payment.actor_user_id.is_some());
+        assert_eq!(payment.details.as_deref(), Some("Paid at front desk"));
+    }
+
+    #[test]
+    fn test_guest_view_is_redacted() {
+        let entries = BookingService::build_timeline(&sources(), true);
+
+        assert!(entries.iter().all(|e| e.kind != TimelineEntryKind::NoShowCharge));
+        assert!(entries.iter().all(|e| e.actor_user_id.is_none() && e.details.is_none()));
+        assert!(entries.iter().any(|e| e.kind == TimelineEntryKind::ChatProposal));
+    }
+
+    #[test]
+    fn test_system_transition_is_noted_without_actor() {
+        let mut sources = sources();
+        let event = NewBookingEvent::system_status_change(
+            sources.booking.id,
+            BookingStatus::CheckedIn,
+            BookingStatus::Overstay,
+        );
+        assert_eq!(event.actor_user_id, None);
+        assert_eq!(event.details.as_deref(), Some(SYSTEM_ACTOR_NOTE));
+
+        sources.events.push(BookingEvent {
+            id: Uuid::new_v4(),
+            booking_id: event.booking_id,
+            event_type: event.event_type.to_string(),
+            from_status: event.from_status,
+            to_status: event.to_status,
+            actor_user_id: event.actor_user_id,
+            details: event.details,
+            created_at: at(12),
+        });
+
+        let entries = BookingService::build_timeline(&sources, false);
+        let last = entries.last().unwrap();
+        assert_eq!(last.summary, "Status changed from checked_in to overstay");
+        assert_eq!(last.details.as_deref(), Some("system"));
+    }
+
+    #[test]
+    fn test_conflict_override_warning_is_staff_only() {
+        let mut sources = sources();
+        let event = NewBookingEvent::conflict_override(
+            sources.booking.id,
+            "Warning: room 101 booked over BK-20250601-OVR1; availability overridden".to_string(),
+            Some(Uuid::new_v4()),
+        );
+        sources.events.push(BookingEvent {
+            id: Uuid::new_v4(),
+            booking_id: event.booking_id,
+            event_type: event.event_type.to_string(),
+            from_status: event.from_status,
+            to_status: event.to_status,
+            actor_user_id: event.actor_user_id,
+            details: event.details,
+            created_at: at(12),
+        });
+
+        let staff = BookingService::build_timeline(&sources, false);
+        let last = staff.last().unwrap();
+        assert_eq!(last.kind, TimelineEntryKind::ConflictOverride);
+        assert!(last.details.as_deref().unwrap().contains("BK-20250601-OVR1"));
+
+        let guest = BookingService::build_timeline(&sources, true);
+        assert!(!guest.last().unwrap().summary.contains("BK-"));
+        assert!(guest.last().unwrap().details.is_none());
+    }
+
+    #[test]
+    fn test_kind_serialization() {
+        let json = serde_json::to_string(&TimelineEntryKind::StatusChange).unwrap();
+        assert_eq!(json, "\"status_change\"");
+    }
+}
+
+mod timeline_pagination_tests {
+    use super::*;
+
+    #[test]
+    fn test_paginate_slices_and_reports_total() {
+        let entries = BookingService::build_timeline(&sources(), false);
+        let page = BookingService::paginate_timeline(Uuid::new_v4(), entries, Some(2), Some(3));
+
+        assert_eq!(page.total, 7);
+        assert_eq!(page.page, 2);
+        assert_eq!(page.entries.len(), 3);
+    }
+
+    #[test]
+    fn test_page_size_is_capped_and_page_starts_at_one() {
+        let entries = BookingService::build_timeline(&sources(), false);
+        let page = BookingService::paginate_timeline(Uuid::new_v4(), entries, Some(0), Some(10_000));
+
+        assert_eq!(page.page, 1);
+        assert_eq!(page.per_page, MAX_TIMELINE_PAGE_SIZE);
+        assert_eq!(page.entries.len(), 7);
+    }
+
+    #[test]
+    fn test_page_past_end_is_empty() {
+        let entries = BookingService::build_timeline(&sources(), false);
+        let page = BookingService::paginate_timeline(Uuid::new_v4(), entries, Some(5), None);
+
+        assert!(page.entries.is_empty());
+        assert_eq!(page.total, 7);
+    }
+}
@@ -0,0 +1,123 @@
+//! Booking update tests
+//!
+//! Tests for PATCH /bookings/:id applying guest name and date changes. These
+//! need a migrated PostgreSQL database and only run when TEST_DATABASE_URL is
+//! set, e.g.
+//! `TEST_DATABASE_URL=postgres://postgres@localhost/hotel_test cargo test --test booking_update_tests`
+
+use bigdecimal::BigDecimal;
+use chrono::{Duration, NaiveDate, Utc};
+
+use hotel_management_backend::db::{create_pool, DbPool};
+use hotel_management_backend::errors::AppError;
+use hotel_management_backend::models::{Booking, Room, RoomType};
+use hotel_management_backend::services::booking_service::StaffBookingRequest;
+use hotel_management_backend::services::{BookingService, RoomService};
+
+fn test_pool() -> Option<DbPool> {
+    match std::env::var("TEST_DATABASE_URL") {
+        Ok(url) => Some(create_pool(&url)),
+        Err(_) => {
+            eprintln!("TEST_DATABASE_URL not set, skipping database test");
+            None
+        }
+    }
+}
+
+fn new_room(pool: &DbPool) -> Room {
+    let number = format!("U{}", &uuid::Uuid::new_v4().simple().to_string()[..8]);
+    RoomService::new(pool.clone())
+        .create_room(&number, RoomType::DOUBLE)
+        .unwrap()
+}
+
+fn in_days(days: i64) -> NaiveDate {
+    Utc::now().date_naive() + Duration::days(days)
+}
+
+/// Three-night booking starting in 40 days at 1,000,000 VND a night
+fn booked(service: &BookingService, room: &Room, guest: &str) -> Booking {
+    service
+        .create_booking(
+            &StaffBookingRequest {
+                guest_name: format!("{} {}", guest, room.number),
+                room_id: room.id,
+                check_in_date: in_days(40),
+                check_out_date: in_days(43),
+                price: Some(BigDecimal::from(3000000)),
+                allow_duplicate: false,
+                override_conflict: false,
+            },
+            None,
+        )
+        .unwrap()
+}
+
+mod update_booking_tests {
+    use super::*;
+
+    #[test]
+    fn test_shortening_stay_updates_dates_and_price() {
+        let Some(pool) = test_pool() else { return };
+        let room = new_room(&pool);
+        let service = BookingService::new(pool);
+        let booking = booked(&service, &room, "Shorter");
+
+        service
+            .update_booking(booking.id, None, None, Some(in_days(41)), None)
+            .unwrap();
+
+        let updated = service.get_booking_with_room(booking.id).unwrap();
+        assert_eq!(updated.booking.check_in_date, in_days(40));
+        assert_eq!(updated.booking.check_out_date, in_days(41));
+        assert_eq!(updated.booking.price, BigDecimal::from(1000000));
+        assert_eq!(updated.room.unwrap().id, room.id);
+        assert_eq!(updated.modification_count, 1);
+    }
+
+    #[test]
+    fn test_extending_into_another_booking_is_rejected() {
+        let Some(pool) = test_pool() else { return };
+        let room = new_room(&pool);
+        let service = BookingService::new(pool);
+        let booking = booked(&service, &room, "Extender");
+        service
+            .create_booking(
+                &StaffBookingRequest {
+                    guest_name: format!("Next Guest {}", room.number),
+                    room_id: room.id,
+                    check_in_date: in_days(43),
+                    check_out_date: in_days(45),
+                    price: None,
+                    allow_duplicate: false,
+                    override_conflict: false,
+                },
+                None,
+            )
+            .unwrap();
+
+        let result = service.update_booking(booking.id, None, None, Some(in_days(44)), None);
+        assert!(matches!(result, Err(AppError::RoomUnavailable(_))));
+
+        let unchanged = service.get_booking_by_id(booking.id).unwrap();
+        assert_eq!(unchanged.check_out_date, in_days(43));
+    }
+
+    #[test]
+    fn test_guest_name_only_change_keeps_dates_and_price() {
+        let Some(pool) = test_pool() else { return };
+        let room = new_room(&pool);
+        let service = BookingService::new(pool);
+        let booking = booked(&service, &room, "Misspeled");
+
+        let renamed = format!("Misspelled {}", room.number);
+        let updated = service
+            .update_booking(booking.id, Some(&format!("  {}  ", renamed)), None, None, None)
+            .unwrap();
+
+        assert_eq!(updated.guest_name, renamed);
+        assert_eq!(updated.check_in_date, booking.check_in_date);
+        assert_eq!(updated.check_out_date, booking.check_out_date);
+        assert_eq!(updated.price, booking.price);
+    }
+}
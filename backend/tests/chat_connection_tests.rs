@@ -0,0 +1,759 @@
+//! Chat connection tests
+//!
+//! Tests for users with the chat open on several devices at once: every
+//! socket receives the user's messages, and closing one leaves the others
+//! connected. Also for catching up on messages sent while a user was
+//! offline, read receipts, typing indicators, presence, sockets staying
+//! up while the database is unavailable, message rate and length limits,
+//! and senders editing or deleting their messages. They need a
+//! migrated PostgreSQL database and only run when TEST_DATABASE_URL is set.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::body::{to_bytes, Body};
+use axum::http::{header::{AUTHORIZATION, CONTENT_TYPE}, Method, Request, StatusCode};
+use chrono::{Duration as ChronoDuration, Utc};
+use diesel::prelude::*;
+use jsonwebtoken::{encode, EncodingKey, Header};
+use diesel::r2d2::{ConnectionManager, Pool};
+use futures::{SinkExt, StreamExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use hotel_management_backend::api::public_bookings::PublicLookupLimits;
+use hotel_management_backend::api::chat::{MAX_MESSAGE_CHARS, PUPINN_ID};
+use hotel_management_backend::api::{chat::ChatState, create_router, AppState};
+use hotel_management_backend::config::{BodyLimits, ChatLimits};
+use hotel_management_backend::db::{create_pool, DbPool};
+use hotel_management_backend::models::message::NewMessage;
+use hotel_management_backend::models::UserRole;
+use hotel_management_backend::scheduler::JobBoard;
+use hotel_management_backend::schema::{messages, users};
+use hotel_management_backend::services::auth_service::{
+    Claims, CreateUserRequest, GuestRegisterRequest, LoginRequest, DEFAULT_ACCESS_TOKEN_LIFETIME,
+    DEFAULT_JWT_ISSUER,
+};
+use hotel_management_backend::services::rate_limit_service::InMemoryLoginAttempts;
+use hotel_management_backend::services::{
+    AuthService, ReadOnlyMode, RevocationCache, SessionTracker, TokenIssuer,
+};
+
+const JWT_SECRET: &str = "test-secret";
+const PASSWORD: &str = "Chat-password-1";
+
+type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+fn test_pool() -> Option<DbPool> {
+    match std::env::var("TEST_DATABASE_URL") {
+        Ok(url) => Some(create_pool(&url)),
+        Err(_) => {
+            eprintln!("TEST_DATABASE_URL not set, skipping database test");
+            None
+        }
+    }
+}
+
+/// A pool of one connection that gives up quickly, so holding that
+/// connection makes the database unavailable to the server
+fn single_connection_pool() -> Option<DbPool> {
+    let url = std::env::var("TEST_DATABASE_URL").ok()?;
+    Some(
+        Pool::builder()
+            .max_size(1)
+            .connection_timeout(Duration::from_millis(300))
+            .build_unchecked(ConnectionManager::new(url)),
+    )
+}
+
+fn auth(pool: &DbPool) -> AuthService {
+    AuthService::new(pool.clone(), JWT_SECRET.to_string())
+}
+
+fn suffix() -> String {
+    Uuid::new_v4().simple().to_string()[..8].to_string()
+}
+
+/// Token and id of a new receptionist
+fn receptionist(pool: &DbPool) -> (String, Uuid) {
+    let username = format!("chat-{}", suffix());
+    let user = auth(pool)
+        .create_user(&CreateUserRequest {
+            username: username.clone(),
+            password: PASSWORD.to_string(),
+            role: UserRole::Receptionist,
+            must_change_password: false,
+        })
+        .unwrap();
+    let token = auth(pool)
+        .login(&LoginRequest {
+            username,
+            password: PASSWORD.to_string(),
+        })
+        .unwrap()
+        .token;
+    (token, user.id)
+}
+
+/// Token and id of a new cleaner
+fn cleaner(pool: &DbPool) -> (String, Uuid) {
+    let username = format!("chat-{}", suffix());
+    let user = auth(pool)
+        .create_user(&CreateUserRequest {
+            username: username.clone(),
+            password: PASSWORD.to_string(),
+            role: UserRole::Cleaner,
+            must_change_password: false,
+        })
+        .unwrap();
+    let token = auth(pool)
+        .login(&LoginRequest {
+            username,
+            password: PASSWORD.to_string(),
+        })
+        .unwrap()
+        .token;
+    (token, user.id)
+}
+
+/// Token and id of a new guest
+fn guest(pool: &DbPool) -> (String, Uuid) {
+    let response = auth(pool)
+        .register_guest(&GuestRegisterRequest {
+            email: format!("chat-{}@example.com", suffix()),
+            password: PASSWORD.to_string(),
+            full_name: format!("Chat Guest {}", suffix()),
+        })
+        .unwrap();
+    (response.token, response.user.id)
+}
+
+fn router(pool: DbPool, chat_state: Arc<ChatState>) -> axum::Router {
+    let s3_config = aws_sdk_s3::config::Builder::new()
+        .region(aws_sdk_s3::config::Region::new("us-east-1"))
+        .behavior_version_latest()
+        .build();
+
+    create_router(AppState {
+        pool,
+        jwt_secret: JWT_SECRET.to_string(),
+        chat_state,
+        s3_client: aws_sdk_s3::Client::from_conf(s3_config),
+        read_only: Arc::new(ReadOnlyMode::new(Some(false))),
+        public_lookup: Arc::new(PublicLookupLimits::default()),
+        jobs: Arc::new(JobBoard::default()),
+        body_limits: BodyLimits::default(),
+        sessions: Arc::new(SessionTracker::default()),
+        access_token_lifetime: DEFAULT_ACCESS_TOKEN_LIFETIME,
+        login_attempts: Arc::new(InMemoryLoginAttempts::default()),
+        revocations: Arc::new(RevocationCache::default()),
+        trust_proxy_headers: false,
+        token_issuer: TokenIssuer::default(),
+    })
+}
+
+/// Serve the API on a free local port
+async fn serve(pool: DbPool, chat_state: Arc<ChatState>) -> String {
+    let router = router(pool, chat_state);
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+    format!("ws://{}/chat/ws", address)
+}
+
+/// Open a socket and return the messages of its opening sync frame
+async fn connect_since(url: &str, token: &str, since: Option<&str>) -> (Socket, Vec<serde_json::Value>) {
+    let since = since.map(|since| format!("&since={}", since)).unwrap_or_default();
+    let mut socket = connect_async(format!("{}?token={}{}", url, token, since)).await.unwrap().0;
+    let sync = recv_frame(&mut socket).await;
+    assert_eq!(sync["type"], "sync");
+    (socket, sync["messages"].as_array().unwrap().clone())
+}
+
+async fn connect(url: &str, token: &str) -> Socket {
+    connect_since(url, token, None).await.0
+}
+
+/// Wait until a user has `expected` open sockets; `None` once their channel is gone
+async fn wait_for_sockets(chat_state: &ChatState, user_id: Uuid, expected: Option<usize>) {
+    for _ in 0..100 {
+        let sockets = chat_state
+            .active_connections
+            .lock()
+            .unwrap()
+            .get(&user_id)
+            .map(|user| user.tx.receiver_count());
+        if sockets == expected {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("user {} never had {:?} sockets", user_id, expected);
+}
+
+async fn send(socket: &mut Socket, receiver_id: Uuid, content: &str) {
+    let message = serde_json::json!({ "receiver_id": receiver_id, "content": content });
+    socket.send(Message::Text(message.to_string())).await.unwrap();
+}
+
+async fn recv_frame(socket: &mut Socket) -> serde_json::Value {
+    let frame = tokio::time::timeout(Duration::from_secs(5), socket.next())
+        .await
+        .expect("no message arrived")
+        .unwrap()
+        .unwrap();
+    serde_json::from_str(frame.to_text().unwrap()).unwrap()
+}
+
+/// Next frame other than a presence update, which arrives whenever a
+/// contact connects or disconnects
+async fn next_frame(socket: &mut Socket) -> serde_json::Value {
+    loop {
+        let frame = recv_frame(socket).await;
+        if frame["type"] != "presence" {
+            return frame;
+        }
+    }
+}
+
+async fn next_presence(socket: &mut Socket) -> serde_json::Value {
+    loop {
+        let frame = recv_frame(socket).await;
+        if frame["type"] == "presence" {
+            return frame;
+        }
+    }
+}
+
+/// Next error frame, skipping anything else (such as Pupinn's replies)
+async fn next_error(socket: &mut Socket) -> serde_json::Value {
+    loop {
+        let frame = recv_frame(socket).await;
+        if frame["type"] == "error" {
+            return frame;
+        }
+    }
+}
+
+/// Chat state allowing three messages, one of them to Pupinn, a minute
+fn strict_chat_state() -> Arc<ChatState> {
+    Arc::new(ChatState::new(ChatLimits {
+        messages: 3,
+        assistant_messages: 1,
+        window: Duration::from_secs(60),
+        mute: Duration::from_secs(60),
+    }))
+}
+
+/// Assert nothing arrives on a socket for a moment
+async fn assert_quiet(socket: &mut Socket) {
+    let frame = tokio::time::timeout(Duration::from_millis(300), socket.next()).await;
+    assert!(frame.is_err(), "unexpected frame {:?}", frame);
+}
+
+async fn next_content(socket: &mut Socket) -> String {
+    next_frame(socket).await["content"].as_str().unwrap().to_string()
+}
+
+fn contents(messages: &[serde_json::Value]) -> Vec<&str> {
+    messages.iter().map(|m| m["content"].as_str().unwrap()).collect()
+}
+
+fn store(pool: &DbPool, sender_id: Uuid, receiver_id: Uuid, content: &str) -> Uuid {
+    diesel::insert_into(messages::table)
+        .values(&NewMessage {
+            sender_id,
+            receiver_id,
+            content: content.to_string(),
+            image_url: None,
+        })
+        .returning(messages::id)
+        .get_result(&mut pool.get().unwrap())
+        .unwrap()
+}
+
+/// Backdate a message's sending time
+fn sent_minutes_ago(pool: &DbPool, message_id: Uuid, minutes: i64) {
+    diesel::update(messages::table.find(message_id))
+        .set(messages::created_at.eq(Utc::now() - ChronoDuration::minutes(minutes)))
+        .execute(&mut pool.get().unwrap())
+        .unwrap();
+}
+
+/// Token and id of the existing admin, if the database has one
+fn admin(pool: &DbPool) -> Option<(String, Uuid)> {
+    let admin: Uuid = users::table
+        .filter(users::role.eq(UserRole::Admin))
+        .filter(users::deactivated_at.is_null())
+        .select(users::id)
+        .first(&mut pool.get().unwrap())
+        .optional()
+        .unwrap()?;
+    let now = Utc::now().timestamp();
+    let claims = Claims {
+        sub: admin,
+        role: UserRole::Admin,
+        exp: now + 3600,
+        iat: now,
+        sid: None,
+        jti: Some(Uuid::new_v4()),
+        must_change_password: false,
+        iss: Some(DEFAULT_JWT_ISSUER.to_string()),
+        aud: Some(DEFAULT_JWT_ISSUER.to_string()),
+    };
+    let token = encode(&Header::default(), &claims, &EncodingKey::from_secret(JWT_SECRET.as_bytes())).unwrap();
+    Some((token, admin))
+}
+
+async fn get(pool: &DbPool, chat_state: &Arc<ChatState>, token: &str, uri: &str) -> (StatusCode, serde_json::Value) {
+    call(pool, chat_state, Method::GET, token, uri, None).await
+}
+
+async fn call(
+    pool: &DbPool,
+    chat_state: &Arc<ChatState>,
+    method: Method,
+    token: &str,
+    uri: &str,
+    body: Option<serde_json::Value>,
+) -> (StatusCode, serde_json::Value) {
+    let request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .header(CONTENT_TYPE, "application/json")
+        .body(body.map(|body| Body::from(body.to_string())).unwrap_or_else(Body::empty))
+        .unwrap();
+    let response = router(pool.clone(), chat_state.clone()).oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&bytes).unwrap_or(serde_json::Value::Null))
+}
+
+mod endpoint_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_every_socket_of_a_user_receives_their_messages() {
+        let Some(pool) = test_pool() else { return };
+        let chat_state = Arc::new(ChatState::default());
+        let url = serve(pool.clone(), chat_state.clone()).await;
+        let (staff_token, _) = receptionist(&pool);
+        let (guest_token, guest_id) = guest(&pool);
+
+        let mut desk = connect(&url, &staff_token).await;
+        let mut phone = connect(&url, &guest_token).await;
+        let mut laptop = connect(&url, &guest_token).await;
+        wait_for_sockets(&chat_state, guest_id, Some(2)).await;
+
+        send(&mut desk, guest_id, "Your room is ready").await;
+        assert_eq!(next_content(&mut phone).await, "Your room is ready");
+        assert_eq!(next_content(&mut laptop).await, "Your room is ready");
+
+        // Closing one device leaves the other connected
+        phone.close(None).await.unwrap();
+        wait_for_sockets(&chat_state, guest_id, Some(1)).await;
+        send(&mut desk, guest_id, "Breakfast is until ten").await;
+        assert_eq!(next_content(&mut laptop).await, "Breakfast is until ten");
+
+        laptop.close(None).await.unwrap();
+        wait_for_sockets(&chat_state, guest_id, None).await;
+    }
+
+    #[tokio::test]
+    async fn test_reconnecting_socket_syncs_missed_messages() {
+        let Some(pool) = test_pool() else { return };
+        let chat_state = Arc::new(ChatState::default());
+        let url = serve(pool.clone(), chat_state.clone()).await;
+        let (staff_token, _) = receptionist(&pool);
+        let (guest_token, guest_id) = guest(&pool);
+
+        // The guest is offline for both messages
+        let mut desk = connect(&url, &staff_token).await;
+        send(&mut desk, guest_id, "Your taxi is booked").await;
+        send(&mut desk, guest_id, "It arrives at nine").await;
+
+        let mut synced = Vec::new();
+        for _ in 0..100 {
+            let (socket, messages) = connect_since(&url, &guest_token, None).await;
+            drop(socket);
+            synced = messages;
+            if synced.len() == 2 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert_eq!(contents(&synced), ["Your taxi is booked", "It arrives at nine"]);
+
+        // Syncing does not read them; a later sync picks up after `since`
+        let (_, unread) = connect_since(&url, &guest_token, None).await;
+        assert_eq!(unread.len(), 2);
+        let since = synced[0]["created_at"].as_str().unwrap().replace('+', "%2B");
+        let (_, after) = connect_since(&url, &guest_token, Some(&since)).await;
+        assert_eq!(contents(&after), ["It arrives at nine"]);
+    }
+
+    #[tokio::test]
+    async fn test_unsynced_messages_are_returned_without_reading_them() {
+        let Some(pool) = test_pool() else { return };
+        let chat_state = Arc::new(ChatState::default());
+        let (_, staff_id) = receptionist(&pool);
+        let (guest_token, guest_id) = guest(&pool);
+        let before = (Utc::now() - ChronoDuration::seconds(1)).format("%Y-%m-%dT%H:%M:%S%.6fZ").to_string();
+        store(&pool, staff_id, guest_id, "Checkout is at noon");
+        store(&pool, guest_id, staff_id, "Thanks!");
+
+        let since_before = format!("/chat/messages/unsynced?since={}", before);
+        let (status, body) = get(&pool, &chat_state, &guest_token, &since_before).await;
+        assert_eq!(status, StatusCode::OK);
+        let messages = body.as_array().unwrap();
+        assert_eq!(contents(messages), ["Checkout is at noon"]);
+        assert_eq!(messages[0]["is_read"], false);
+        let (_, again) = get(&pool, &chat_state, &guest_token, &since_before).await;
+        assert_eq!(again.as_array().unwrap().len(), 1);
+
+        let later = (Utc::now() + ChronoDuration::seconds(1)).format("%Y-%m-%dT%H:%M:%SZ").to_string();
+        let since_later = format!("/chat/messages/unsynced?since={}", later);
+        let (_, none) = get(&pool, &chat_state, &guest_token, &since_later).await;
+        assert!(none.as_array().unwrap().is_empty());
+
+        let (status, _) = get(&pool, &chat_state, &guest_token, "/chat/messages/unsynced?since=yesterday").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_marking_messages_read_sends_the_sender_a_receipt() {
+        let Some(pool) = test_pool() else { return };
+        let chat_state = Arc::new(ChatState::default());
+        let url = serve(pool.clone(), chat_state.clone()).await;
+        let (staff_token, staff_id) = receptionist(&pool);
+        let (guest_token, guest_id) = guest(&pool);
+        let (_, other_guest_id) = guest(&pool);
+        store(&pool, staff_id, other_guest_id, "Not for this guest");
+
+        let mut desk = connect(&url, &staff_token).await;
+        let mut phone = connect(&url, &guest_token).await;
+        wait_for_sockets(&chat_state, guest_id, Some(1)).await;
+        send(&mut desk, guest_id, "Late checkout is fine").await;
+        let delivered = next_frame(&mut phone).await;
+        let id = delivered["id"].as_str().unwrap().to_string();
+
+        // Reading the history no longer marks anything read
+        let history = format!("/chat/history?other_user_id={}", staff_id);
+        let (status, body) = get(&pool, &chat_state, &guest_token, &history).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body[0]["is_read"], false);
+        let (_, contacts) = get(&pool, &chat_state, &guest_token, "/chat/contacts").await;
+        let staff = contacts.as_array().unwrap().iter().find(|c| c["id"] == staff_id.to_string()).unwrap();
+        assert_eq!(staff["unread_count"], 1);
+
+        // Another user's message in the frame is left alone
+        let other: String = messages::table
+            .filter(messages::receiver_id.eq(other_guest_id))
+            .select(messages::id)
+            .first::<Uuid>(&mut pool.get().unwrap())
+            .unwrap()
+            .to_string();
+        let frame = serde_json::json!({ "type": "mark_read", "message_ids": [id, other] });
+        phone.send(Message::Text(frame.to_string())).await.unwrap();
+
+        let receipt = next_frame(&mut desk).await;
+        assert_eq!(receipt["type"], "read");
+        assert_eq!(receipt["by"], guest_id.to_string());
+        assert_eq!(receipt["message_ids"], serde_json::json!([id]));
+
+        let (_, body) = get(&pool, &chat_state, &guest_token, &history).await;
+        assert_eq!(body[0]["is_read"], true);
+        let (_, contacts) = get(&pool, &chat_state, &guest_token, "/chat/contacts").await;
+        let staff = contacts.as_array().unwrap().iter().find(|c| c["id"] == staff_id.to_string()).unwrap();
+        assert_eq!(staff["unread_count"], 0);
+        let other_read: bool = messages::table
+            .filter(messages::receiver_id.eq(other_guest_id))
+            .select(messages::is_read)
+            .first(&mut pool.get().unwrap())
+            .unwrap();
+        assert!(!other_read);
+    }
+
+    #[tokio::test]
+    async fn test_typing_reaches_only_permitted_receivers() {
+        let Some(pool) = test_pool() else { return };
+        let chat_state = Arc::new(ChatState::default());
+        let url = serve(pool.clone(), chat_state.clone()).await;
+        let (staff_token, staff_id) = receptionist(&pool);
+        let (guest_token, guest_id) = guest(&pool);
+        let (other_token, other_id) = guest(&pool);
+
+        let mut desk = connect(&url, &staff_token).await;
+        let mut phone = connect(&url, &guest_token).await;
+        let mut other = connect(&url, &other_token).await;
+        wait_for_sockets(&chat_state, other_id, Some(1)).await;
+
+        let typing = |receiver_id: Uuid| {
+            Message::Text(serde_json::json!({ "type": "typing", "receiver_id": receiver_id }).to_string())
+        };
+        desk.send(typing(guest_id)).await.unwrap();
+        let frame = next_frame(&mut phone).await;
+        assert_eq!(frame["type"], "typing");
+        assert_eq!(frame["sender_id"], staff_id.to_string());
+
+        // Guests may not chat with each other, and unknown users are ignored
+        // the same way, without an error
+        other.send(typing(guest_id)).await.unwrap();
+        other.send(typing(Uuid::new_v4())).await.unwrap();
+        assert_quiet(&mut phone).await;
+        assert_quiet(&mut other).await;
+
+        // Typing is not stored
+        let (_, history) = get(&pool, &chat_state, &guest_token, &format!("/chat/history?other_user_id={}", staff_id)).await;
+        assert!(history.as_array().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_presence_follows_first_and_last_connection() {
+        let Some(pool) = test_pool() else { return };
+        let chat_state = Arc::new(ChatState::default());
+        let url = serve(pool.clone(), chat_state.clone()).await;
+        let (staff_token, _) = receptionist(&pool);
+        let (cleaner_token, _) = cleaner(&pool);
+        let (guest_token, guest_id) = guest(&pool);
+
+        let mut desk = connect(&url, &staff_token).await;
+        let mut housekeeping = connect(&url, &cleaner_token).await;
+        let is_online = |contacts: &serde_json::Value| {
+            contacts.as_array().unwrap().iter().find(|c| c["id"] == guest_id.to_string()).unwrap()["online"].clone()
+        };
+        let (_, contacts) = get(&pool, &chat_state, &staff_token, "/chat/contacts").await;
+        assert_eq!(is_online(&contacts), false);
+
+        let phone = connect(&url, &guest_token).await;
+        let presence = next_presence(&mut desk).await;
+        assert_eq!(presence["user_id"], guest_id.to_string());
+        assert_eq!(presence["online"], true);
+        let (_, contacts) = get(&pool, &chat_state, &staff_token, "/chat/contacts").await;
+        assert_eq!(is_online(&contacts), true);
+
+        // A second device changes nothing; closing the last one goes offline
+        let mut laptop = connect(&url, &guest_token).await;
+        wait_for_sockets(&chat_state, guest_id, Some(2)).await;
+        drop(phone);
+        wait_for_sockets(&chat_state, guest_id, Some(1)).await;
+        laptop.close(None).await.unwrap();
+        let presence = next_presence(&mut desk).await;
+        assert_eq!(presence["user_id"], guest_id.to_string());
+        assert_eq!(presence["online"], false);
+
+        // Cleaners do not chat with guests and never hear about them
+        assert_quiet(&mut housekeeping).await;
+    }
+
+    #[tokio::test]
+    async fn test_socket_survives_database_outage() {
+        let Some(pool) = test_pool() else { return };
+        let Some(server_pool) = single_connection_pool() else { return };
+        let chat_state = Arc::new(ChatState::default());
+        let url = serve(server_pool.clone(), chat_state.clone()).await;
+        let (staff_token, staff_id) = receptionist(&pool);
+        let (guest_token, _) = guest(&pool);
+
+        let mut desk = connect(&url, &staff_token).await;
+        let mut phone = connect(&url, &guest_token).await;
+
+        // Taking the only connection stands in for an exhausted pool
+        let held = server_pool.get().unwrap();
+        send(&mut phone, staff_id, "Lost").await;
+        let frame = next_frame(&mut phone).await;
+        assert_eq!(frame["type"], "error");
+        assert_eq!(frame["code"], "DB_ERROR");
+        drop(held);
+
+        // The same socket carries on once the database is back
+        send(&mut phone, staff_id, "Delivered").await;
+        assert_eq!(next_content(&mut desk).await, "Delivered");
+    }
+
+    #[tokio::test]
+    async fn test_overlong_message_is_refused() {
+        let Some(pool) = test_pool() else { return };
+        let chat_state = Arc::new(ChatState::default());
+        let url = serve(pool.clone(), chat_state.clone()).await;
+        let (staff_token, staff_id) = receptionist(&pool);
+        let (guest_token, _) = guest(&pool);
+
+        let mut desk = connect(&url, &staff_token).await;
+        let mut phone = connect(&url, &guest_token).await;
+
+        send(&mut phone, staff_id, &"a".repeat(MAX_MESSAGE_CHARS + 1)).await;
+        let frame = next_frame(&mut phone).await;
+        assert_eq!(frame["type"], "error");
+        assert_eq!(frame["code"], "VALIDATION_ERROR");
+
+        send(&mut phone, staff_id, &"a".repeat(MAX_MESSAGE_CHARS)).await;
+        assert_eq!(next_content(&mut desk).await.chars().count(), MAX_MESSAGE_CHARS);
+    }
+
+    #[tokio::test]
+    async fn test_flooding_sender_is_muted() {
+        let Some(pool) = test_pool() else { return };
+        let chat_state = strict_chat_state();
+        let url = serve(pool.clone(), chat_state.clone()).await;
+        let (staff_token, staff_id) = receptionist(&pool);
+        let (guest_token, _) = guest(&pool);
+
+        let mut desk = connect(&url, &staff_token).await;
+        let mut phone = connect(&url, &guest_token).await;
+
+        for n in 0..3 {
+            send(&mut phone, staff_id, &format!("Message {}", n)).await;
+            assert_eq!(next_content(&mut desk).await, format!("Message {}", n));
+        }
+        send(&mut phone, staff_id, "One too many").await;
+        let frame = next_frame(&mut phone).await;
+        assert_eq!(frame["code"], "RATE_LIMITED");
+        assert_eq!(frame["retry_after"], 60);
+        assert_quiet(&mut desk).await;
+    }
+
+    #[tokio::test]
+    async fn test_messages_to_pupinn_have_a_stricter_limit() {
+        let Some(pool) = test_pool() else { return };
+        let chat_state = strict_chat_state();
+        let url = serve(pool.clone(), chat_state.clone()).await;
+        let (staff_token, staff_id) = receptionist(&pool);
+        let (guest_token, _) = guest(&pool);
+
+        let mut desk = connect(&url, &staff_token).await;
+        let mut phone = connect(&url, &guest_token).await;
+
+        send(&mut phone, PUPINN_ID, "Any rooms tonight?").await;
+        send(&mut phone, PUPINN_ID, "Hello?").await;
+        assert_eq!(next_error(&mut phone).await["code"], "RATE_LIMITED");
+
+        // Staff can still be reached
+        send(&mut phone, staff_id, "Is anyone there?").await;
+        assert_eq!(next_content(&mut desk).await, "Is anyone there?");
+    }
+
+    #[tokio::test]
+    async fn test_uploads_count_against_the_message_limit() {
+        let Some(pool) = test_pool() else { return };
+        let chat_state = strict_chat_state();
+        let (guest_token, guest_id) = guest(&pool);
+        for _ in 0..3 {
+            chat_state.check_send_rate(guest_id, false, std::time::Instant::now()).unwrap();
+        }
+
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri("/chat/upload")
+            .header(AUTHORIZATION, format!("Bearer {}", guest_token))
+            .header(CONTENT_TYPE, "multipart/form-data; boundary=upload")
+            .body(Body::empty())
+            .unwrap();
+        let response = router(pool.clone(), chat_state).oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(response.headers().contains_key("retry-after"));
+    }
+
+    #[tokio::test]
+    async fn test_sender_edits_a_message_and_the_receiver_sees_it() {
+        let Some(pool) = test_pool() else { return };
+        let chat_state = Arc::new(ChatState::default());
+        let url = serve(pool.clone(), chat_state.clone()).await;
+        let (staff_token, staff_id) = receptionist(&pool);
+        let (guest_token, guest_id) = guest(&pool);
+        let message_id = store(&pool, staff_id, guest_id, "Room 101 is ready");
+
+        let mut phone = connect(&url, &guest_token).await;
+        let uri = format!("/chat/messages/{}", message_id);
+        let edit = serde_json::json!({ "content": "Room 102 is ready" });
+
+        // Only the sender may edit
+        let (status, _) = call(&pool, &chat_state, Method::PATCH, &guest_token, &uri, Some(edit.clone())).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let (status, body) = call(&pool, &chat_state, Method::PATCH, &staff_token, &uri, Some(edit)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["content"], "Room 102 is ready");
+        assert!(body["edited_at"].is_string());
+
+        let frame = next_frame(&mut phone).await;
+        assert_eq!(frame["type"], "message_updated");
+        assert_eq!(frame["message"]["id"], message_id.to_string());
+        assert_eq!(frame["message"]["content"], "Room 102 is ready");
+
+        let (_, page) = get(&pool, &chat_state, &guest_token, &format!("/chat/v2/history?other_user_id={}", staff_id)).await;
+        assert_eq!(page["messages"][0]["content"], "Room 102 is ready");
+    }
+
+    #[tokio::test]
+    async fn test_deleted_message_leaves_a_tombstone() {
+        let Some(pool) = test_pool() else { return };
+        let chat_state = Arc::new(ChatState::default());
+        let url = serve(pool.clone(), chat_state.clone()).await;
+        let (staff_token, staff_id) = receptionist(&pool);
+        let (guest_token, guest_id) = guest(&pool);
+        let message_id = store(&pool, staff_id, guest_id, "Another guest's passport number");
+
+        let mut phone = connect(&url, &guest_token).await;
+        let uri = format!("/chat/messages/{}", message_id);
+
+        let (status, body) = call(&pool, &chat_state, Method::DELETE, &staff_token, &uri, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["deleted"], true);
+
+        let frame = next_frame(&mut phone).await;
+        assert_eq!(frame["type"], "message_deleted");
+        assert_eq!(frame["message"]["id"], message_id.to_string());
+        assert_eq!(frame["message"]["content"], "");
+
+        // The content is gone from the database, not just hidden
+        let stored: String = messages::table
+            .find(message_id)
+            .select(messages::content)
+            .first(&mut pool.get().unwrap())
+            .unwrap();
+        assert_eq!(stored, "");
+
+        let history = format!("/chat/v2/history?other_user_id={}", staff_id);
+        let (_, page) = get(&pool, &chat_state, &guest_token, &history).await;
+        assert_eq!(page["messages"][0]["deleted"], true);
+        assert_eq!(page["messages"][0]["content"], "");
+
+        // Deleting again changes nothing; a deleted message cannot be edited
+        let (status, _) = call(&pool, &chat_state, Method::DELETE, &staff_token, &uri, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_quiet(&mut phone).await;
+        let edit = serde_json::json!({ "content": "Oops" });
+        let (status, _) = call(&pool, &chat_state, Method::PATCH, &staff_token, &uri, Some(edit)).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+    }
+
+    #[tokio::test]
+    async fn test_messages_can_only_be_changed_for_fifteen_minutes() {
+        let Some(pool) = test_pool() else { return };
+        let chat_state = Arc::new(ChatState::default());
+        let (staff_token, staff_id) = receptionist(&pool);
+        let (_, guest_id) = guest(&pool);
+        let message_id = store(&pool, staff_id, guest_id, "Checkout is at noon");
+        sent_minutes_ago(&pool, message_id, 16);
+        let uri = format!("/chat/messages/{}", message_id);
+
+        let (status, _) = call(&pool, &chat_state, Method::DELETE, &staff_token, &uri, None).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        let edit = serde_json::json!({ "content": "Checkout is at 11" });
+        let (status, _) = call(&pool, &chat_state, Method::PATCH, &staff_token, &uri, Some(edit)).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        // Admins are not held to the window
+        let Some((admin_token, admin_id)) = admin(&pool) else { return };
+        let message_id = store(&pool, admin_id, staff_id, "Wrong guest's details");
+        sent_minutes_ago(&pool, message_id, 60);
+        let uri = format!("/chat/messages/{}", message_id);
+        let (status, _) = call(&pool, &chat_state, Method::DELETE, &admin_token, &uri, None).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+}
@@ -0,0 +1,286 @@
+//! Chat history tests
+//!
+//! Tests for paging through a conversation with /chat/v2/history: newest
+//! first, page size limits, cursors that stay stable while new messages
+//! arrive, and the unpaged /chat/history old clients still use. They need
+//! a migrated PostgreSQL database and only run when TEST_DATABASE_URL is
+//! set.
+
+use std::sync::Arc;
+
+use axum::body::{to_bytes, Body};
+use axum::http::{header::AUTHORIZATION, Request, StatusCode};
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use diesel::prelude::*;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use hotel_management_backend::api::chat::HISTORY_PAGE_MAX;
+use hotel_management_backend::api::public_bookings::PublicLookupLimits;
+use hotel_management_backend::api::{chat::ChatState, create_router, AppState};
+use hotel_management_backend::config::BodyLimits;
+use hotel_management_backend::db::{create_pool, DbPool};
+use hotel_management_backend::models::message::{Message, NewMessage};
+use hotel_management_backend::models::UserRole;
+use hotel_management_backend::scheduler::JobBoard;
+use hotel_management_backend::schema::messages;
+use hotel_management_backend::services::auth_service::{
+    CreateUserRequest, GuestRegisterRequest, LoginRequest, DEFAULT_ACCESS_TOKEN_LIFETIME,
+};
+use hotel_management_backend::services::rate_limit_service::InMemoryLoginAttempts;
+use hotel_management_backend::services::{
+    AuthService, ReadOnlyMode, RevocationCache, SessionTracker, TokenIssuer,
+};
+
+const JWT_SECRET: &str = "test-secret";
+const PASSWORD: &str = "History-password-1";
+
+fn test_pool() -> Option<DbPool> {
+    match std::env::var("TEST_DATABASE_URL") {
+        Ok(url) => Some(create_pool(&url)),
+        Err(_) => {
+            eprintln!("TEST_DATABASE_URL not set, skipping database test");
+            None
+        }
+    }
+}
+
+fn auth(pool: &DbPool) -> AuthService {
+    AuthService::new(pool.clone(), JWT_SECRET.to_string())
+}
+
+fn suffix() -> String {
+    Uuid::new_v4().simple().to_string()[..8].to_string()
+}
+
+/// Token and id of a new receptionist
+fn receptionist(pool: &DbPool) -> (String, Uuid) {
+    let username = format!("history-{}", suffix());
+    let user = auth(pool)
+        .create_user(&CreateUserRequest {
+            username: username.clone(),
+            password: PASSWORD.to_string(),
+            role: UserRole::Receptionist,
+            must_change_password: false,
+        })
+        .unwrap();
+    let token = auth(pool)
+        .login(&LoginRequest {
+            username,
+            password: PASSWORD.to_string(),
+        })
+        .unwrap()
+        .token;
+    (token, user.id)
+}
+
+/// Token and id of a new guest
+fn guest(pool: &DbPool) -> (String, Uuid) {
+    let response = auth(pool)
+        .register_guest(&GuestRegisterRequest {
+            email: format!("history-{}@example.com", suffix()),
+            password: PASSWORD.to_string(),
+            full_name: format!("History Guest {}", suffix()),
+        })
+        .unwrap();
+    (response.token, response.user.id)
+}
+
+/// Store a message as sent at `at`
+fn send(pool: &DbPool, sender_id: Uuid, receiver_id: Uuid, content: &str, at: DateTime<Utc>) -> Uuid {
+    let mut conn = pool.get().unwrap();
+    let message: Message = diesel::insert_into(messages::table)
+        .values(&NewMessage {
+            sender_id,
+            receiver_id,
+            content: content.to_string(),
+            image_url: None,
+        })
+        .get_result(&mut conn)
+        .unwrap();
+    diesel::update(messages::table.find(message.id))
+        .set(messages::created_at.eq(at))
+        .execute(&mut conn)
+        .unwrap();
+    message.id
+}
+
+fn minute(n: i64) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2025, 4, 1, 9, 0, 0).unwrap() + Duration::minutes(n)
+}
+
+fn router(pool: DbPool) -> axum::Router {
+    let s3_config = aws_sdk_s3::config::Builder::new()
+        .region(aws_sdk_s3::config::Region::new("us-east-1"))
+        .behavior_version_latest()
+        .build();
+
+    create_router(AppState {
+        pool,
+        jwt_secret: JWT_SECRET.to_string(),
+        chat_state: Arc::new(ChatState::default()),
+        s3_client: aws_sdk_s3::Client::from_conf(s3_config),
+        read_only: Arc::new(ReadOnlyMode::new(Some(false))),
+        public_lookup: Arc::new(PublicLookupLimits::default()),
+        jobs: Arc::new(JobBoard::default()),
+        body_limits: BodyLimits::default(),
+        sessions: Arc::new(SessionTracker::default()),
+        access_token_lifetime: DEFAULT_ACCESS_TOKEN_LIFETIME,
+        login_attempts: Arc::new(InMemoryLoginAttempts::default()),
+        revocations: Arc::new(RevocationCache::default()),
+        trust_proxy_headers: false,
+        token_issuer: TokenIssuer::default(),
+    })
+}
+
+async fn get(pool: &DbPool, token: &str, uri: &str) -> (StatusCode, serde_json::Value) {
+    let request = Request::builder()
+        .uri(uri)
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .body(Body::empty())
+        .unwrap();
+    let response = router(pool.clone()).oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&bytes).unwrap_or(serde_json::Value::Null))
+}
+
+fn contents(page: &serde_json::Value) -> Vec<&str> {
+    page["messages"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|m| m["content"].as_str().unwrap())
+        .collect()
+}
+
+mod endpoint_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_pages_run_newest_first_to_the_first_message() {
+        let Some(pool) = test_pool() else { return };
+        let (guest_token, guest_id) = guest(&pool);
+        let (_, staff_id) = receptionist(&pool);
+        for n in 0..5 {
+            let (from, to) = if n % 2 == 0 { (guest_id, staff_id) } else { (staff_id, guest_id) };
+            send(&pool, from, to, &format!("m{}", n), minute(n));
+        }
+        let base = format!("/chat/v2/history?other_user_id={}", staff_id);
+
+        let (status, first) = get(&pool, &guest_token, &format!("{}&limit=2", base)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(contents(&first), vec!["m4", "m3"]);
+
+        let cursor = first["next_cursor"].as_str().unwrap();
+        let (_, second) = get(&pool, &guest_token, &format!("{}&limit=2&before={}", base, cursor)).await;
+        assert_eq!(contents(&second), vec!["m2", "m1"]);
+
+        let cursor = second["next_cursor"].as_str().unwrap();
+        let (_, last) = get(&pool, &guest_token, &format!("{}&limit=2&before={}", base, cursor)).await;
+        assert_eq!(contents(&last), vec!["m0"]);
+        assert!(last["next_cursor"].is_null());
+
+        // A page that holds the whole conversation has no cursor either
+        let (_, all) = get(&pool, &guest_token, &base).await;
+        assert_eq!(contents(&all).len(), 5);
+        assert!(all["next_cursor"].is_null());
+    }
+
+    #[tokio::test]
+    async fn test_cursor_is_stable_across_inserts_and_ties() {
+        let Some(pool) = test_pool() else { return };
+        let (guest_token, guest_id) = guest(&pool);
+        let (_, staff_id) = receptionist(&pool);
+        send(&pool, guest_id, staff_id, "early", minute(0));
+        // Three messages stored in the same instant
+        for content in ["a", "b", "c"] {
+            send(&pool, staff_id, guest_id, content, minute(1));
+        }
+        send(&pool, guest_id, staff_id, "late", minute(2));
+        let base = format!("/chat/v2/history?other_user_id={}&limit=2", staff_id);
+
+        let mut seen = Vec::new();
+        let (_, mut page) = get(&pool, &guest_token, &base).await;
+        loop {
+            seen.extend(contents(&page).into_iter().map(str::to_string));
+            // New messages keep arriving while the client pages back
+            send(&pool, staff_id, guest_id, "new", Utc::now());
+            let Some(cursor) = page["next_cursor"].as_str() else { break };
+            page = get(&pool, &guest_token, &format!("{}&before={}", base, cursor)).await.1;
+        }
+
+        assert_eq!(seen.len(), 5);
+        assert_eq!(seen[0], "late");
+        assert_eq!(seen[4], "early");
+        let mut tied = seen[1..4].to_vec();
+        tied.sort();
+        assert_eq!(tied, vec!["a", "b", "c"]);
+    }
+
+    #[tokio::test]
+    async fn test_timestamp_cursor_and_limit_bounds() {
+        let Some(pool) = test_pool() else { return };
+        let (guest_token, guest_id) = guest(&pool);
+        let (_, staff_id) = receptionist(&pool);
+        for n in 0..4 {
+            send(&pool, guest_id, staff_id, &format!("m{}", n), minute(n));
+        }
+        let base = format!("/chat/v2/history?other_user_id={}", staff_id);
+
+        let before = minute(2).to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+        let (status, page) = get(&pool, &guest_token, &format!("{}&before={}", base, before)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(contents(&page), vec!["m1", "m0"]);
+
+        // Sizes outside 1..=HISTORY_PAGE_MAX are clamped
+        let (_, page) = get(&pool, &guest_token, &format!("{}&limit=0", base)).await;
+        assert_eq!(contents(&page), vec!["m3"]);
+        let (status, _) = get(&pool, &guest_token, &format!("{}&limit={}", base, HISTORY_PAGE_MAX + 1)).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_cursor_must_belong_to_the_conversation() {
+        let Some(pool) = test_pool() else { return };
+        let (guest_token, _) = guest(&pool);
+        let (_, other_guest_id) = guest(&pool);
+        let (_, staff_id) = receptionist(&pool);
+        let foreign = send(&pool, other_guest_id, staff_id, "not yours", minute(0));
+        let base = format!("/chat/v2/history?other_user_id={}", staff_id);
+
+        let (status, _) = get(&pool, &guest_token, &format!("{}&before={}", base, foreign)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let (status, _) = get(&pool, &guest_token, &format!("{}&before=yesterday", base)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        // Guests cannot page through a conversation with another guest
+        let (status, _) = get(
+            &pool,
+            &guest_token,
+            &format!("/chat/v2/history?other_user_id={}", other_guest_id),
+        )
+        .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_unpaged_history_keeps_its_shape() {
+        let Some(pool) = test_pool() else { return };
+        let (guest_token, guest_id) = guest(&pool);
+        let (_, staff_id) = receptionist(&pool);
+        for n in 0..3 {
+            send(&pool, guest_id, staff_id, &format!("m{}", n), minute(n));
+        }
+
+        let (status, body) = get(&pool, &guest_token, &format!("/chat/history?other_user_id={}", staff_id)).await;
+        assert_eq!(status, StatusCode::OK);
+        let contents: Vec<&str> = body
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|m| m["content"].as_str().unwrap())
+            .collect();
+        assert_eq!(contents, vec!["m0", "m1", "m2"]);
+    }
+}
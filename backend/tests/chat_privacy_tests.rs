@@ -0,0 +1,181 @@
+//! Chat privacy tests
+//!
+//! Tests for the guest chat export (role labels, CSV quoting) and for
+//! deleting conversations with the assistant. The deletion tests need a
+//! migrated PostgreSQL database and only run when TEST_DATABASE_URL is set.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use axum::{http::StatusCode, response::IntoResponse};
+use chrono::{TimeZone, Utc};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use hotel_management_backend::api::chat::PUPINN_ID;
+use hotel_management_backend::db::{create_pool, DbPool};
+use hotel_management_backend::errors::AppError;
+use hotel_management_backend::models::message::{Message, NewMessage, DELETED_MESSAGE_CONTENT};
+use hotel_management_backend::models::UserRole;
+use hotel_management_backend::schema::messages;
+use hotel_management_backend::services::auth_service::{CreateUserRequest, GuestRegisterRequest};
+use hotel_management_backend::services::chat_privacy_service::{
+    export_csv, export_entries, ChatHistoryScope,
+};
+use hotel_management_backend::services::{AuthService, ChatPrivacyService};
+
+fn message(sender_id: Uuid, receiver_id: Uuid, content: &str, minute: u32) -> Message {
+    let at = Utc.with_ymd_and_hms(2025, 4, 1, 9, minute, 0).unwrap();
+    Message {
+        id: Uuid::new_v4(),
+        sender_id,
+        receiver_id,
+        content: content.to_string(),
+        image_url: None,
+        is_read: true,
+        created_at: at,
+        updated_at: at,
+        edited_at: None,
+        deleted_at: None,
+    }
+}
+
+mod export_tests {
+    use super::*;
+
+    #[test]
+    fn test_staff_are_named_by_role() {
+        let guest = Uuid::new_v4();
+        let receptionist = Uuid::new_v4();
+        let roles = HashMap::from([(PUPINN_ID, UserRole::Bot), (receptionist, UserRole::Receptionist)]);
+        let messages = vec![
+            message(receptionist, guest, "Your room is ready", 5),
+            message(guest, PUPINN_ID, "Any rooms tonight?", 1),
+            message(PUPINN_ID, guest, "Room 501 is free", 2),
+        ];
+
+        let entries = export_entries(guest, &messages, &roles);
+
+        let labels: Vec<(&str, &str)> = entries
+            .iter()
+            .map(|e| (e.conversation_with, e.direction))
+            .collect();
+        assert_eq!(
+            labels,
+            vec![
+                ("Pupinn (assistant)", "sent"),
+                ("Pupinn (assistant)", "received"),
+                ("Reception", "received"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unknown_party_is_labelled_staff() {
+        let guest = Uuid::new_v4();
+        let entries = export_entries(guest, &[message(Uuid::new_v4(), guest, "Hi", 0)], &HashMap::new());
+        assert_eq!(entries[0].conversation_with, "Staff");
+    }
+
+    #[test]
+    fn test_csv_quotes_commas_quotes_and_newlines() {
+        let guest = Uuid::new_v4();
+        let roles = HashMap::from([(PUPINN_ID, UserRole::Bot)]);
+        let entries = export_entries(
+            guest,
+            &[message(guest, PUPINN_ID, "Hello, I said \"two\"\nnights", 0)],
+            &roles,
+        );
+
+        let csv = export_csv(&entries);
+        let mut lines = csv.lines();
+        assert_eq!(lines.next().unwrap(), "sent_at,conversation_with,direction,content,image_url");
+        assert!(csv.contains(",Pupinn (assistant),sent,\"Hello, I said \"\"two\"\"\nnights\",\n"));
+    }
+
+    #[test]
+    fn test_rate_limited_error_sets_retry_after() {
+        let response = AppError::RateLimited("slow down".to_string(), Duration::from_secs(90)).into_response();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()["retry-after"], "90");
+    }
+}
+
+fn test_pool() -> Option<DbPool> {
+    match std::env::var("TEST_DATABASE_URL") {
+        Ok(url) => Some(create_pool(&url)),
+        Err(_) => {
+            eprintln!("TEST_DATABASE_URL not set, skipping database test");
+            None
+        }
+    }
+}
+
+mod delete_history_tests {
+    use super::*;
+
+    fn send(pool: &DbPool, sender_id: Uuid, receiver_id: Uuid, content: &str) {
+        let mut conn = pool.get().unwrap();
+        diesel::insert_into(messages::table)
+            .values(&NewMessage {
+                sender_id,
+                receiver_id,
+                content: content.to_string(),
+                image_url: None,
+            })
+            .execute(&mut conn)
+            .unwrap();
+    }
+
+    fn contents(pool: &DbPool, user_id: Uuid, other_id: Uuid) -> Vec<String> {
+        let mut conn = pool.get().unwrap();
+        messages::table
+            .filter(
+                (messages::sender_id.eq(user_id).and(messages::receiver_id.eq(other_id)))
+                    .or(messages::sender_id.eq(other_id).and(messages::receiver_id.eq(user_id))),
+            )
+            .select(messages::content)
+            .load(&mut conn)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_bot_conversation_is_tombstoned_and_staff_kept() {
+        let Some(pool) = test_pool() else { return };
+        let auth = AuthService::new(pool.clone(), "test-secret".to_string());
+        let suffix = &Uuid::new_v4().simple().to_string()[..8];
+        let guest = auth
+            .register_guest(&GuestRegisterRequest {
+                email: format!("privacy-{}@example.com", suffix),
+                password: "guest-password-1".to_string(),
+                full_name: "Privacy Guest".to_string(),
+            })
+            .unwrap()
+            .user;
+        let receptionist = auth
+            .create_user(&CreateUserRequest {
+                username: format!("desk-{}", suffix),
+                password: "desk-password-1".to_string(),
+                role: UserRole::Receptionist,
+                must_change_password: false,
+            })
+            .unwrap();
+
+        send(&pool, guest.id, PUPINN_ID, "My passport number is 123");
+        send(&pool, PUPINN_ID, guest.id, "Thanks, noted passport 123");
+        send(&pool, guest.id, receptionist.id, "Can I check in early?");
+
+        let service = ChatPrivacyService::new(pool.clone());
+        assert!(matches!(
+            service.delete_history(guest.id, ChatHistoryScope::All),
+            Err(AppError::Forbidden(_))
+        ));
+        assert_eq!(service.delete_history(guest.id, ChatHistoryScope::Bot).unwrap(), 2);
+        assert_eq!(service.delete_history(guest.id, ChatHistoryScope::Bot).unwrap(), 0);
+
+        assert!(contents(&pool, guest.id, PUPINN_ID)
+            .iter()
+            .all(|c| c == DELETED_MESSAGE_CONTENT));
+        assert_eq!(contents(&pool, guest.id, receptionist.id), vec!["Can I check in early?"]);
+    }
+}
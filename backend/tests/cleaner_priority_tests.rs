@@ -0,0 +1,161 @@
+//! Cleaner priority tests
+//!
+//! Tests for ordering the cleaners' room list: rooms with a guest arriving
+//! today first, then the rooms that have waited longest in their status. The
+//! database tests need a migrated PostgreSQL database and only run when
+//! TEST_DATABASE_URL is set.
+
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use uuid::Uuid;
+
+use hotel_management_backend::db::{create_pool, DbPool};
+use hotel_management_backend::models::{Room, RoomStatus, RoomType};
+use hotel_management_backend::services::booking_service::StaffBookingRequest;
+use hotel_management_backend::services::room_service::{CleanerRoom, RoomFilter, RoomLocation};
+use hotel_management_backend::services::{BookingService, RoomService};
+use hotel_management_backend::settings::{self, Settings};
+use hotel_management_backend::utils::clock::local_date;
+
+fn at(hour: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2026, 3, 14, hour, 0, 0).unwrap()
+}
+
+fn cleaner_room(number: &str, dirty_since: DateTime<Utc>, priority: bool) -> CleanerRoom {
+    CleanerRoom {
+        room: Room {
+            id: Uuid::new_v4(),
+            number: number.to_string(),
+            room_type: RoomType::DOUBLE,
+            status: RoomStatus::Dirty,
+            created_at: at(0),
+            updated_at: dirty_since,
+            price: BigDecimal::from(1_500_000),
+            assigned_cleaner_id: None,
+            description: None,
+            amenities: Vec::new(),
+            photo_urls: Vec::new(),
+            decommissioned_at: None,
+            floor: 1,
+            building: None,
+            room_type_id: Uuid::new_v4(),
+        },
+        dirty_since,
+        priority,
+    }
+}
+
+fn numbers(rooms: &[CleanerRoom]) -> Vec<&str> {
+    rooms.iter().map(|r| r.room.number.as_str()).collect()
+}
+
+mod ordering_tests {
+    use super::*;
+
+    #[test]
+    fn test_arrivals_first_then_longest_waiting() {
+        let ordered = RoomService::order_cleaner_rooms(vec![
+            cleaner_room("101", at(9), false),
+            cleaner_room("102", at(11), true),
+            cleaner_room("103", at(7), false),
+            cleaner_room("104", at(10), true),
+        ]);
+        assert_eq!(numbers(&ordered), ["104", "102", "103", "101"]);
+    }
+
+    #[test]
+    fn test_same_wait_goes_by_number() {
+        let ordered = RoomService::order_cleaner_rooms(vec![
+            cleaner_room("305", at(8), false),
+            cleaner_room("301", at(8), false),
+            cleaner_room("302", at(8), true),
+        ]);
+        assert_eq!(numbers(&ordered), ["302", "301", "305"]);
+    }
+
+    #[test]
+    fn test_rooms_serialize_flat() {
+        let json = serde_json::to_value(cleaner_room("101", at(9), true)).unwrap();
+        assert_eq!(json["number"], "101");
+        assert_eq!(json["priority"], true);
+        assert_eq!(json["dirty_since"], "2026-03-14T09:00:00Z");
+    }
+}
+
+fn test_pool() -> Option<DbPool> {
+    match std::env::var("TEST_DATABASE_URL") {
+        Ok(url) => Some(create_pool(&url)),
+        Err(_) => {
+            eprintln!("TEST_DATABASE_URL not set, skipping database test");
+            None
+        }
+    }
+}
+
+mod service_tests {
+    use super::*;
+
+    #[test]
+    fn test_list_puts_todays_arrivals_first() {
+        let Some(pool) = test_pool() else { return };
+        let rooms = RoomService::new(pool.clone());
+        let building = format!("Wing {}", &Uuid::new_v4().simple().to_string()[..8]);
+        let location = RoomLocation {
+            floor: Some(2),
+            building: Some(building.clone()),
+        };
+        let suffix = &Uuid::new_v4().simple().to_string()[..6];
+        let [first, second, third] = ["A", "B", "C"]
+            .map(|letter| rooms.create_room_at(&format!("{}{}", letter, suffix), RoomType::SINGLE, &location).unwrap());
+
+        let today = local_date(
+            Utc::now(),
+            Settings::load(&mut pool.get().unwrap())
+                .unwrap()
+                .time_zone(settings::HOTEL_TIMEZONE),
+        );
+        let bookings = BookingService::new(pool.clone());
+        let arrive = |room: &Room, check_in_date| {
+            bookings
+                .create_booking(
+                    &StaffBookingRequest {
+                        guest_name: format!("Arrival {}", room.number),
+                        room_id: room.id,
+                        check_in_date,
+                        check_out_date: check_in_date + Duration::days(1),
+                        price: None,
+                        allow_duplicate: true,
+                        override_conflict: false,
+                    },
+                    None,
+                )
+                .unwrap()
+        };
+        let arriving = arrive(&second, today);
+        // Tomorrow's arrival waits its turn
+        arrive(&first, today + Duration::days(1));
+
+        // Rooms go dirty in a different order than they were created
+        for room in [&third, &first, &second] {
+            rooms.update_room_status(room.id, RoomStatus::Dirty, None).unwrap();
+        }
+        let filter = RoomFilter {
+            status: Some(RoomStatus::Dirty),
+            building: Some(building),
+            ..RoomFilter::default()
+        };
+        let listed = rooms.list_cleaner_rooms(&filter).unwrap();
+        let ids: Vec<Uuid> = listed.iter().map(|r| r.room.id).collect();
+        assert_eq!(ids, [second.id, third.id, first.id]);
+        assert!(listed[0].priority);
+        assert!(!listed[1].priority && !listed[2].priority);
+        assert!(listed.iter().all(|r| r.dirty_since > r.room.created_at));
+
+        // A cancelled arrival is no longer a reason to hurry
+        bookings.cancel(arriving.id, Uuid::nil()).unwrap();
+        let listed = rooms.list_cleaner_rooms(&filter).unwrap();
+        let ids: Vec<Uuid> = listed.iter().map(|r| r.room.id).collect();
+        assert_eq!(ids, [third.id, first.id, second.id]);
+        assert!(listed.iter().all(|r| !r.priority));
+    }
+}
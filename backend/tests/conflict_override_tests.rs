@@ -0,0 +1,283 @@
+//! Availability override tests
+//!
+//! Tests for admins booking a room over a same-day turnover: the audit
+//! warning, the echoed conflict ids, refusing overlapping stays even with the
+//! flag, and refusing the flag for receptionists, walk-ins and the guest
+//! portal. They need a migrated PostgreSQL database and only run
+//! when TEST_DATABASE_URL is set.
+
+use std::sync::Arc;
+
+use axum::body::Body;
+use axum::http::{header::AUTHORIZATION, header::CONTENT_TYPE, Method, Request, StatusCode};
+use chrono::{Duration, NaiveDate, Utc};
+use diesel::prelude::*;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use hotel_management_backend::api::public_bookings::PublicLookupLimits;
+use hotel_management_backend::api::{chat::ChatState, create_router, AppState};
+use hotel_management_backend::config::BodyLimits;
+use hotel_management_backend::db::{create_pool, DbPool};
+use hotel_management_backend::errors::AppError;
+use hotel_management_backend::models::{
+    Booking, BookingEvent, BookingStatus, Room, RoomType, UserRole, EVENT_CONFLICT_OVERRIDE,
+};
+use hotel_management_backend::scheduler::JobBoard;
+use hotel_management_backend::schema::{booking_events, bookings};
+use hotel_management_backend::services::auth_service::{CreateUserRequest, GuestRegisterRequest, LoginRequest, DEFAULT_ACCESS_TOKEN_LIFETIME};
+use hotel_management_backend::services::booking_service::StaffBookingRequest;
+use hotel_management_backend::services::rate_limit_service::InMemoryLoginAttempts;
+use hotel_management_backend::services::{
+    AuthService, BookingService, ReadOnlyMode, RevocationCache, RoomService, SessionTracker, TokenIssuer,
+};
+
+const JWT_SECRET: &str = "test-secret";
+
+fn test_pool() -> Option<DbPool> {
+    match std::env::var("TEST_DATABASE_URL") {
+        Ok(url) => Some(create_pool(&url)),
+        Err(_) => {
+            eprintln!("TEST_DATABASE_URL not set, skipping database test");
+            None
+        }
+    }
+}
+
+fn room(pool: &DbPool) -> Room {
+    let number = format!("O{}", &Uuid::new_v4().simple().to_string()[..8]);
+    RoomService::new(pool.clone()).create_room(&number, RoomType::DOUBLE).unwrap()
+}
+
+fn request(room: &Room, name: &str, check_in_date: NaiveDate, override_conflict: bool) -> StaffBookingRequest {
+    StaffBookingRequest {
+        guest_name: format!("{} {}", name, room.number),
+        room_id: room.id,
+        check_in_date,
+        check_out_date: check_in_date + Duration::days(2),
+        price: None,
+        allow_duplicate: false,
+        override_conflict,
+    }
+}
+
+mod service_tests {
+    use super::*;
+
+    #[test]
+    fn test_override_books_over_the_conflict_and_records_a_warning() {
+        let Some(pool) = test_pool() else { return };
+        let room = room(&pool);
+        let service = BookingService::new(pool.clone());
+        let turnover = service.today().unwrap();
+        // An overstay guest who was due out yesterday and is still in the room
+        let booked = service
+            .create_booking(&request(&room, "Staying", turnover + Duration::days(20), false), None)
+            .unwrap();
+        let existing: Booking = diesel::update(bookings::table.find(booked.id))
+            .set((
+                bookings::check_in_date.eq(turnover - Duration::days(3)),
+                bookings::check_out_date.eq(turnover - Duration::days(1)),
+                bookings::status.eq(BookingStatus::Overstay),
+            ))
+            .get_result(&mut pool.get().unwrap())
+            .unwrap();
+
+        assert!(matches!(
+            service.create_staff_booking(&request(&room, "Arriving", turnover, false), None),
+            Err(AppError::RoomUnavailable(_))
+        ));
+
+        let actor = AuthService::new(pool.clone(), JWT_SECRET.to_string())
+            .create_user(&CreateUserRequest {
+                username: format!("override-{}", &room.number[1..]),
+                password: "desk-password-1".to_string(),
+                role: UserRole::Receptionist,
+                must_change_password: false,
+            })
+            .unwrap()
+            .id;
+        let created = service
+            .create_staff_booking(&request(&room, "Arriving", turnover, true), Some(actor))
+            .unwrap();
+        assert_eq!(created.overridden_booking_ids, vec![existing.id]);
+        let json = serde_json::to_value(&created).unwrap();
+        assert_eq!(json["reference"], created.booking.reference.as_str());
+        assert_eq!(json["overridden_booking_ids"][0], existing.id.to_string());
+
+        let event: BookingEvent = booking_events::table
+            .filter(booking_events::booking_id.eq(created.booking.id))
+            .filter(booking_events::event_type.eq(EVENT_CONFLICT_OVERRIDE))
+            .first(&mut pool.get().unwrap())
+            .unwrap();
+        assert_eq!(event.actor_user_id, Some(actor));
+        let details = event.details.unwrap();
+        assert!(details.starts_with("Warning"), "{}", details);
+        assert!(details.contains(&existing.reference), "{}", details);
+    }
+
+    #[test]
+    fn test_override_refuses_an_overlapping_stay() {
+        let Some(pool) = test_pool() else { return };
+        let room = room(&pool);
+        let service = BookingService::new(pool.clone());
+        let check_in = service.today().unwrap() + Duration::days(20);
+        let fortnight = |name: &str, override_conflict: bool| StaffBookingRequest {
+            check_out_date: check_in + Duration::days(14),
+            ..request(&room, name, check_in, override_conflict)
+        };
+        service.create_booking(&fortnight("Upcoming", false), None).unwrap();
+
+        match service.create_staff_booking(&fortnight("Double", true), None) {
+            Err(AppError::RoomUnavailable(message)) => assert!(message.contains("same-day turnover"), "{}", message),
+            other => panic!("Expected RoomUnavailable, got {:?}", other),
+        }
+        // Nor does a partial overlap with an upcoming stay qualify
+        assert!(matches!(
+            service.create_staff_booking(&request(&room, "Partial", check_in + Duration::days(13), true), None),
+            Err(AppError::RoomUnavailable(_))
+        ));
+    }
+
+    #[test]
+    fn test_override_on_a_free_room_books_normally() {
+        let Some(pool) = test_pool() else { return };
+        let room = room(&pool);
+        let check_in = Utc::now().date_naive() + Duration::days(20);
+
+        let created = BookingService::new(pool.clone())
+            .create_staff_booking(&request(&room, "Free", check_in, true), None)
+            .unwrap();
+        assert!(created.overridden_booking_ids.is_empty());
+        let json = serde_json::to_value(&created).unwrap();
+        assert!(json.get("overridden_booking_ids").is_none());
+
+        let events: i64 = booking_events::table
+            .filter(booking_events::booking_id.eq(created.booking.id))
+            .count()
+            .get_result(&mut pool.get().unwrap())
+            .unwrap();
+        assert_eq!(events, 0);
+    }
+
+    #[test]
+    fn test_walk_in_cannot_override() {
+        let Some(pool) = test_pool() else { return };
+        let room = room(&pool);
+
+        assert!(matches!(
+            BookingService::new(pool.clone()).create_walk_in(
+                &request(&room, "Walk In", Utc::now().date_naive(), true),
+                Uuid::new_v4()
+            ),
+            Err(AppError::ValidationError(_))
+        ));
+    }
+}
+
+fn router(pool: DbPool) -> axum::Router {
+    let s3_config = aws_sdk_s3::config::Builder::new()
+        .region(aws_sdk_s3::config::Region::new("us-east-1"))
+        .behavior_version_latest()
+        .build();
+
+    create_router(AppState {
+        pool,
+        jwt_secret: JWT_SECRET.to_string(),
+        chat_state: Arc::new(ChatState::default()),
+        s3_client: aws_sdk_s3::Client::from_conf(s3_config),
+        read_only: Arc::new(ReadOnlyMode::new(Some(false))),
+        public_lookup: Arc::new(PublicLookupLimits::default()),
+        jobs: Arc::new(JobBoard::default()),
+        body_limits: BodyLimits::default(),
+        sessions: Arc::new(SessionTracker::default()),
+        access_token_lifetime: DEFAULT_ACCESS_TOKEN_LIFETIME,
+        login_attempts: Arc::new(InMemoryLoginAttempts::default()),
+        revocations: Arc::new(RevocationCache::default()),
+        trust_proxy_headers: false,
+        token_issuer: TokenIssuer::default(),
+    })
+}
+
+async fn post(pool: &DbPool, uri: &str, token: &str, body: serde_json::Value) -> StatusCode {
+    let request = Request::builder()
+        .method(Method::POST)
+        .uri(uri)
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .header(CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap();
+    router(pool.clone()).oneshot(request).await.unwrap().status()
+}
+
+mod endpoint_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_receptionist_cannot_override() {
+        let Some(pool) = test_pool() else { return };
+        let auth = AuthService::new(pool.clone(), JWT_SECRET.to_string());
+        let username = format!("override-{}", &Uuid::new_v4().simple().to_string()[..8]);
+        auth.create_user(&CreateUserRequest {
+            username: username.clone(),
+            password: "desk-password-1".to_string(),
+            role: UserRole::Receptionist,
+            must_change_password: false,
+        })
+        .unwrap();
+        let token = auth
+            .login(&LoginRequest {
+                username,
+                password: "desk-password-1".to_string(),
+            })
+            .unwrap()
+            .token;
+        let room = room(&pool);
+        let check_in = Utc::now().date_naive() + Duration::days(20);
+
+        let status = post(
+            &pool,
+            "/bookings",
+            &token,
+            serde_json::json!({
+                "guest_name": "Desk Override",
+                "room_id": room.id,
+                "check_in_date": check_in,
+                "check_out_date": check_in + Duration::days(1),
+                "override_conflict": true,
+            }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_guest_portal_refuses_the_flag() {
+        let Some(pool) = test_pool() else { return };
+        let suffix = &Uuid::new_v4().simple().to_string()[..8];
+        let token = AuthService::new(pool.clone(), JWT_SECRET.to_string())
+            .register_guest(&GuestRegisterRequest {
+                email: format!("override-{}@example.com", suffix),
+                password: "guest-password-1".to_string(),
+                full_name: "Override Guest".to_string(),
+            })
+            .unwrap()
+            .token;
+        let room = room(&pool);
+        let check_in = Utc::now().date_naive() + Duration::days(20);
+
+        let status = post(
+            &pool,
+            "/guest/bookings",
+            &token,
+            serde_json::json!({
+                "room_id": room.id,
+                "check_in_date": check_in,
+                "check_out_date": check_in + Duration::days(1),
+                "override_conflict": false,
+            }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+    }
+}
@@ -0,0 +1,201 @@
+//! Deactivated account tests
+//!
+//! Tests that a token issued before its account was deactivated is refused by
+//! the auth middleware and by guest lookups. They need a migrated PostgreSQL
+//! database and only run when TEST_DATABASE_URL is set.
+
+use std::sync::Arc;
+
+use axum::body::Body;
+use axum::http::{header::AUTHORIZATION, header::CONTENT_TYPE, Method, Request, StatusCode};
+use chrono::{Duration, Utc};
+use diesel::prelude::*;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use hotel_management_backend::api::public_bookings::PublicLookupLimits;
+use hotel_management_backend::api::{chat::ChatState, create_router, AppState};
+use hotel_management_backend::config::BodyLimits;
+use hotel_management_backend::db::{create_pool, DbPool};
+use hotel_management_backend::errors::AppError;
+use hotel_management_backend::models::{RoomType, UserRole};
+use hotel_management_backend::scheduler::JobBoard;
+use hotel_management_backend::schema::users;
+use hotel_management_backend::services::auth_service::{CreateUserRequest, GuestRegisterRequest, LoginRequest, DEFAULT_ACCESS_TOKEN_LIFETIME};
+use hotel_management_backend::services::rate_limit_service::InMemoryLoginAttempts;
+use hotel_management_backend::services::token_revocation_service::TOKEN_REVOKED_MESSAGE;
+use hotel_management_backend::services::{AuthService, ReadOnlyMode, RevocationCache, RoomService, SessionTracker, TokenIssuer};
+
+const JWT_SECRET: &str = "test-secret";
+
+fn test_pool() -> Option<DbPool> {
+    match std::env::var("TEST_DATABASE_URL") {
+        Ok(url) => Some(create_pool(&url)),
+        Err(_) => {
+            eprintln!("TEST_DATABASE_URL not set, skipping database test");
+            None
+        }
+    }
+}
+
+fn router(pool: DbPool) -> axum::Router {
+    let s3_config = aws_sdk_s3::config::Builder::new()
+        .region(aws_sdk_s3::config::Region::new("us-east-1"))
+        .behavior_version_latest()
+        .build();
+
+    create_router(AppState {
+        pool,
+        jwt_secret: JWT_SECRET.to_string(),
+        chat_state: Arc::new(ChatState::default()),
+        s3_client: aws_sdk_s3::Client::from_conf(s3_config),
+        read_only: Arc::new(ReadOnlyMode::new(Some(false))),
+        public_lookup: Arc::new(PublicLookupLimits::default()),
+        jobs: Arc::new(JobBoard::default()),
+        body_limits: BodyLimits::default(),
+        sessions: Arc::new(SessionTracker::default()),
+        access_token_lifetime: DEFAULT_ACCESS_TOKEN_LIFETIME,
+        login_attempts: Arc::new(InMemoryLoginAttempts::default()),
+        revocations: Arc::new(RevocationCache::default()),
+        trust_proxy_headers: false,
+        token_issuer: TokenIssuer::default(),
+    })
+}
+
+fn auth(pool: &DbPool) -> AuthService {
+    AuthService::new(pool.clone(), JWT_SECRET.to_string())
+}
+
+/// Guest id and a token issued while the account was active
+fn register_guest(pool: &DbPool) -> (Uuid, String) {
+    let suffix = &Uuid::new_v4().simple().to_string()[..8];
+    let response = auth(pool)
+        .register_guest(&GuestRegisterRequest {
+            email: format!("deactivated-{}@example.com", suffix),
+            password: "guest-password-1".to_string(),
+            full_name: "Deactivated Guest".to_string(),
+        })
+        .unwrap();
+    (response.user.id, response.token)
+}
+
+fn deactivate(pool: &DbPool, user_id: Uuid) {
+    diesel::update(users::table.find(user_id))
+        .set(users::deactivated_at.eq(Some(Utc::now())))
+        .execute(&mut pool.get().unwrap())
+        .unwrap();
+}
+
+async fn send(
+    pool: &DbPool,
+    method: Method,
+    uri: &str,
+    token: &str,
+    body: Option<serde_json::Value>,
+) -> (StatusCode, serde_json::Value) {
+    let request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .header(CONTENT_TYPE, "application/json");
+    let body = body.map_or(Body::empty(), |json| Body::from(json.to_string()));
+    let response = router(pool.clone())
+        .oneshot(request.body(body).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&bytes).unwrap_or_default())
+}
+
+mod middleware_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_guest_token_is_refused_after_deactivation() {
+        let Some(pool) = test_pool() else { return };
+        let (guest_id, token) = register_guest(&pool);
+
+        let (status, _) = send(&pool, Method::GET, "/auth/guest/me", &token, None).await;
+        assert_eq!(status, StatusCode::OK);
+
+        deactivate(&pool, guest_id);
+        let (status, body) = send(&pool, Method::GET, "/auth/guest/me", &token, None).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["message"], "Account is deactivated");
+    }
+
+    #[tokio::test]
+    async fn test_deactivated_guest_cannot_book() {
+        let Some(pool) = test_pool() else { return };
+        let (guest_id, token) = register_guest(&pool);
+        let number = format!("D{}", &Uuid::new_v4().simple().to_string()[..8]);
+        let room = RoomService::new(pool.clone()).create_room(&number, RoomType::SINGLE).unwrap();
+        deactivate(&pool, guest_id);
+
+        let check_in = Utc::now().date_naive() + Duration::days(15);
+        let (status, _) = send(
+            &pool,
+            Method::POST,
+            "/guest/bookings",
+            &token,
+            Some(serde_json::json!({
+                "room_id": room.id,
+                "check_in_date": check_in,
+                "check_out_date": check_in + Duration::days(1),
+            })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_staff_token_is_refused_after_deactivation() {
+        let Some(pool) = test_pool() else { return };
+        let username = format!("desk-{}", &Uuid::new_v4().simple().to_string()[..8]);
+        let receptionist = auth(&pool)
+            .create_user(&CreateUserRequest {
+                username: username.clone(),
+                password: "desk-password-1".to_string(),
+                role: UserRole::Receptionist,
+                must_change_password: false,
+            })
+            .unwrap();
+        let token = auth(&pool)
+            .login(&LoginRequest {
+                username,
+                password: "desk-password-1".to_string(),
+            })
+            .unwrap()
+            .token;
+
+        let uri = "/bookings/reference/BK-20250101-NONE";
+        let (status, _) = send(&pool, Method::GET, uri, &token, None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        // Deactivation revokes the employee's tokens
+        auth(&pool).delete_employee(receptionist.id).unwrap();
+        let (status, body) = send(&pool, Method::GET, uri, &token, None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body["message"], TOKEN_REVOKED_MESSAGE);
+    }
+}
+
+mod guest_lookup_tests {
+    use super::*;
+
+    #[test]
+    fn test_deactivated_guest_is_forbidden() {
+        let Some(pool) = test_pool() else { return };
+        let (guest_id, _) = register_guest(&pool);
+        assert!(auth(&pool).get_guest_by_id(guest_id).is_ok());
+
+        deactivate(&pool, guest_id);
+        assert!(matches!(auth(&pool).get_guest_by_id(guest_id), Err(AppError::Forbidden(_))));
+        assert!(matches!(auth(&pool).ensure_active(guest_id), Err(AppError::Forbidden(_))));
+        assert!(matches!(
+            auth(&pool).ensure_active(Uuid::new_v4()),
+            Err(AppError::Unauthorized(_))
+        ));
+    }
+}
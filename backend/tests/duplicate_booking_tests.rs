@@ -0,0 +1,213 @@
+//! Duplicate booking tests
+//!
+//! Tests for checking guest names and for detecting a second booking by the
+//! same guest on overlapping dates. The detection tests need a migrated
+//! PostgreSQL database and only run when TEST_DATABASE_URL is set.
+
+use bigdecimal::BigDecimal;
+use chrono::{Duration, NaiveDate, Utc};
+use uuid::Uuid;
+
+use hotel_management_backend::db::{create_pool, DbPool};
+use hotel_management_backend::errors::AppError;
+use hotel_management_backend::models::{Booking, BookingStatus, RoomType, UserRole};
+use hotel_management_backend::services::auth_service::CreateUserRequest;
+use hotel_management_backend::services::booking_service::StaffBookingRequest;
+use hotel_management_backend::services::{AuthService, BookingService, RoomService};
+
+fn booking(reference: &str, guest_name: &str, created_by_user_id: Option<Uuid>) -> Booking {
+    Booking {
+        id: Uuid::new_v4(),
+        reference: reference.to_string(),
+        guest_name: guest_name.to_string(),
+        room_id: Uuid::new_v4(),
+        check_in_date: NaiveDate::from_ymd_opt(2025, 4, 1).unwrap(),
+        check_out_date: NaiveDate::from_ymd_opt(2025, 4, 3).unwrap(),
+        status: BookingStatus::Upcoming,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+        created_by_user_id,
+        creation_source: "guest".to_string(),
+        price: BigDecimal::from(1000000),
+        group_reference: None,
+        checked_in_at: None,
+        checked_out_at: None,
+    }
+}
+
+fn test_pool() -> Option<DbPool> {
+    match std::env::var("TEST_DATABASE_URL") {
+        Ok(url) => Some(create_pool(&url)),
+        Err(_) => {
+            eprintln!("TEST_DATABASE_URL not set, skipping database test");
+            None
+        }
+    }
+}
+
+/// A guest name no other test books, and the stay it is booked for
+struct Stay {
+    name: String,
+    check_in: NaiveDate,
+    check_out: NaiveDate,
+    booking: Booking,
+}
+
+/// Book `Le Van <suffix>` into a new room, taken by `actor`
+fn book_stay(pool: &DbPool, actor: Option<Uuid>) -> Stay {
+    let suffix = &Uuid::new_v4().simple().to_string()[..8];
+    let room = RoomService::new(pool.clone())
+        .create_room(&format!("D{}", suffix), RoomType::SINGLE)
+        .unwrap();
+    let name = format!("Le Van {}", suffix);
+    let check_in = Utc::now().date_naive() + Duration::days(40);
+    let check_out = check_in + Duration::days(2);
+    let booking = BookingService::new(pool.clone())
+        .create_booking(
+            &StaffBookingRequest {
+                guest_name: name.clone(),
+                room_id: room.id,
+                check_in_date: check_in,
+                check_out_date: check_out,
+                price: None,
+                allow_duplicate: false,
+                override_conflict: false,
+            },
+            actor,
+        )
+        .unwrap();
+    Stay {
+        name,
+        check_in,
+        check_out,
+        booking,
+    }
+}
+
+mod duplicate_detection_tests {
+    use super::*;
+
+    #[test]
+    fn test_exact_name_is_a_duplicate() {
+        let Some(pool) = test_pool() else { return };
+        let stay = book_stay(&pool, None);
+        let mut conn = pool.get().unwrap();
+        let matches =
+            BookingService::find_guest_stays(&mut conn, stay.check_in, stay.check_out, &stay.name, None).unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].id, stay.booking.id);
+    }
+
+    #[test]
+    fn test_case_and_spacing_differences_are_a_duplicate() {
+        let Some(pool) = test_pool() else { return };
+        let stay = book_stay(&pool, None);
+        let mut conn = pool.get().unwrap();
+        let spaced = format!("  {}  ", stay.name.to_uppercase().replace(' ', " \t "));
+        let matches =
+            BookingService::find_guest_stays(&mut conn, stay.check_in, stay.check_out, &spaced, None).unwrap();
+        assert_eq!(matches.len(), 1);
+    }
+
+    #[test]
+    fn test_different_name_or_dates_are_not_a_duplicate() {
+        let Some(pool) = test_pool() else { return };
+        let stay = book_stay(&pool, None);
+        let mut conn = pool.get().unwrap();
+        let other = format!("{} Jr", stay.name);
+        assert!(BookingService::find_guest_stays(&mut conn, stay.check_in, stay.check_out, &other, None)
+            .unwrap()
+            .is_empty());
+        let later = stay.check_out + Duration::days(1);
+        assert!(BookingService::find_guest_stays(&mut conn, stay.check_out, later, &stay.name, None)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_linked_guest_account_is_a_duplicate() {
+        let Some(pool) = test_pool() else { return };
+        let user_id = AuthService::new(pool.clone(), "test-secret".to_string())
+            .create_user(&CreateUserRequest {
+                username: format!("duplicate-{}", &Uuid::new_v4().simple().to_string()[..8]),
+                password: "desk-password-1".to_string(),
+                role: UserRole::Receptionist,
+                must_change_password: false,
+            })
+            .unwrap()
+            .id;
+        let stay = book_stay(&pool, Some(user_id));
+        let mut conn = pool.get().unwrap();
+        let matches =
+            BookingService::find_guest_stays(&mut conn, stay.check_in, stay.check_out, "Mr. C", Some(user_id))
+                .unwrap();
+        assert_eq!(matches.len(), 1);
+        assert!(BookingService::find_guest_stays(&mut conn, stay.check_in, stay.check_out, "Mr. C", None)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_normalize_guest_name() {
+        assert_eq!(BookingService::normalize_guest_name("  Nguyen\tVan  A "), "nguyen van a");
+    }
+}
+
+mod guest_name_tests {
+    use super::*;
+
+    #[test]
+    fn test_name_is_trimmed() {
+        assert_eq!(BookingService::validate_guest_name("  Le Van C ").unwrap(), "Le Van C");
+    }
+
+    #[test]
+    fn test_blank_name_is_refused() {
+        assert!(matches!(
+            BookingService::validate_guest_name("   "),
+            Err(AppError::ValidationError(_))
+        ));
+    }
+
+    #[test]
+    fn test_limit_counts_characters_not_bytes() {
+        let name = "Ễ".repeat(100);
+        assert!(name.len() > 100);
+        assert!(BookingService::validate_guest_name(&name).is_ok());
+        assert!(matches!(
+            BookingService::validate_guest_name(&format!("{}a", name)),
+            Err(AppError::ValidationError(_))
+        ));
+    }
+}
+
+mod duplicate_override_tests {
+    use super::*;
+
+    #[test]
+    fn test_duplicate_returns_conflict_listing_references() {
+        let matches = vec![
+            booking("BK-20250401-AAAA", "Le Van C", None),
+            booking("BK-20250401-BBBB", "le van c", None),
+        ];
+
+        match BookingService::check_duplicate_stays(&matches, false) {
+            Err(AppError::Conflict(msg)) => {
+                assert!(msg.contains("BK-20250401-AAAA"));
+                assert!(msg.contains("BK-20250401-BBBB"));
+            }
+            other => panic!("Expected Conflict, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_allow_duplicate_overrides_conflict() {
+        let matches = vec![booking("BK-20250401-AAAA", "Le Van C", None)];
+        assert!(BookingService::check_duplicate_stays(&matches, true).is_ok());
+    }
+
+    #[test]
+    fn test_no_duplicates_is_ok() {
+        assert!(BookingService::check_duplicate_stays(&[], false).is_ok());
+    }
+}
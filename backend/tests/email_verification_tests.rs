@@ -0,0 +1,305 @@
+//! Email verification tests
+//!
+//! Tests for guests verifying the email they registered with: issuing and
+//! using single-use tokens, resending at most every five minutes, refusing
+//! bookings until the email is verified, and the endpoints. They need a
+//! migrated PostgreSQL database and only run when TEST_DATABASE_URL is set.
+
+use std::sync::Arc;
+
+use axum::body::{to_bytes, Body};
+use axum::http::{header::AUTHORIZATION, Method, Request, StatusCode};
+use chrono::{Duration, Utc};
+use diesel::prelude::*;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use hotel_management_backend::api::public_bookings::PublicLookupLimits;
+use hotel_management_backend::api::{chat::ChatState, create_router, AppState};
+use hotel_management_backend::config::BodyLimits;
+use hotel_management_backend::db::{create_pool, DbPool};
+use hotel_management_backend::errors::AppError;
+use hotel_management_backend::models::{RoomType, UserRole};
+use hotel_management_backend::scheduler::JobBoard;
+use hotel_management_backend::schema::{email_verification_tokens, users};
+use hotel_management_backend::services::auth_service::{
+    CreateUserRequest, DEFAULT_ACCESS_TOKEN_LIFETIME,
+};
+use hotel_management_backend::services::email_verification_service::{
+    EMAIL_NOT_VERIFIED, EMAIL_VERIFICATION_TOKEN_LIFETIME, INVALID_VERIFICATION_TOKEN, RESEND_VERIFICATION_INTERVAL,
+};
+use hotel_management_backend::services::mailer::OutgoingEmail;
+use hotel_management_backend::services::rate_limit_service::InMemoryLoginAttempts;
+use hotel_management_backend::services::{
+    AuthService, BookingService, EmailVerificationService, GuestAuthResponse, GuestRegisterRequest, ReadOnlyMode,
+    RevocationCache, RoomService, SessionTracker, TokenIssuer,
+};
+
+const JWT_SECRET: &str = "test-secret";
+const PASSWORD: &str = "guest-password-1";
+
+fn test_pool() -> Option<DbPool> {
+    match std::env::var("TEST_DATABASE_URL") {
+        Ok(url) => Some(create_pool(&url)),
+        Err(_) => {
+            eprintln!("TEST_DATABASE_URL not set, skipping database test");
+            None
+        }
+    }
+}
+
+/// A new guest, registered without going through the endpoint, so no
+/// verification email has been issued yet
+fn guest(pool: &DbPool) -> GuestAuthResponse {
+    AuthService::new(pool.clone(), JWT_SECRET.to_string())
+        .register_guest(&GuestRegisterRequest {
+            email: format!("verify-{}@example.com", &Uuid::new_v4().simple().to_string()[..8]),
+            password: PASSWORD.to_string(),
+            full_name: format!("Verify Guest {}", &Uuid::new_v4().simple().to_string()[..8]),
+        })
+        .unwrap()
+}
+
+/// Token carried by the link in a verification email
+fn token_in(email: &OutgoingEmail) -> String {
+    let start = email.html.find("/verify-email?token=").unwrap() + "/verify-email?token=".len();
+    email.html[start..].chars().take_while(|c| c.is_ascii_hexdigit()).collect()
+}
+
+fn verified_at(pool: &DbPool, user_id: Uuid) -> Option<chrono::DateTime<Utc>> {
+    users::table
+        .find(user_id)
+        .select(users::email_verified_at)
+        .first(&mut pool.get().unwrap())
+        .unwrap()
+}
+
+fn assert_invalid_token(result: Result<(), AppError>) {
+    let err = result.unwrap_err();
+    assert!(matches!(&err, AppError::ValidationError(m) if m == INVALID_VERIFICATION_TOKEN), "{:?}", err);
+}
+
+fn book(pool: &DbPool, guest: &GuestAuthResponse) -> Result<(), AppError> {
+    let room = RoomService::new(pool.clone())
+        .create_room(&format!("V{}", &Uuid::new_v4().simple().to_string()[..8]), RoomType::DOUBLE)
+        .unwrap();
+    let check_in = Utc::now().date_naive() + Duration::days(60);
+    BookingService::new(pool.clone())
+        .create_guest_booking(guest.user.id, &guest.user.full_name, room.id, check_in, check_in + Duration::days(1), None)
+        .map(|_| ())
+}
+
+mod service_tests {
+    use super::*;
+
+    #[test]
+    fn test_token_verifies_the_email_once() {
+        let Some(pool) = test_pool() else { return };
+        let verifications = EmailVerificationService::new(pool.clone());
+        let guest = guest(&pool);
+        assert!(guest.user.email_verified_at.is_none());
+
+        let outgoing = verifications.issue(guest.user.id, Utc::now()).unwrap().unwrap();
+        assert_eq!(outgoing.to, guest.user.email);
+        let token = token_in(&outgoing);
+        assert_eq!(token.len(), 64);
+
+        // Only the hash is stored
+        let hash: String = email_verification_tokens::table
+            .filter(email_verification_tokens::user_id.eq(guest.user.id))
+            .select(email_verification_tokens::token_hash)
+            .first(&mut pool.get().unwrap())
+            .unwrap();
+        assert_ne!(hash, token);
+
+        verifications.verify(&token, Utc::now()).unwrap();
+        assert!(verified_at(&pool, guest.user.id).is_some());
+        assert_invalid_token(verifications.verify(&token, Utc::now()));
+
+        // Nothing more to send
+        assert!(verifications.issue(guest.user.id, Utc::now()).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_replaced_expired_and_unknown_tokens_are_refused() {
+        let Some(pool) = test_pool() else { return };
+        let verifications = EmailVerificationService::new(pool.clone());
+        let guest = guest(&pool);
+
+        let first = token_in(&verifications.issue(guest.user.id, Utc::now()).unwrap().unwrap());
+        let second = token_in(&verifications.issue(guest.user.id, Utc::now()).unwrap().unwrap());
+        assert_invalid_token(verifications.verify(&first, Utc::now()));
+
+        let later = Utc::now() + EMAIL_VERIFICATION_TOKEN_LIFETIME + Duration::minutes(1);
+        assert_invalid_token(verifications.verify(&second, later));
+        assert_invalid_token(verifications.verify("not-a-verification-token", Utc::now()));
+        assert!(verified_at(&pool, guest.user.id).is_none());
+    }
+
+    #[test]
+    fn test_resend_is_limited_to_once_per_interval() {
+        let Some(pool) = test_pool() else { return };
+        let verifications = EmailVerificationService::new(pool.clone());
+        let guest = guest(&pool);
+        let now = Utc::now();
+
+        // The first send is not limited
+        verifications.resend(guest.user.id, now).unwrap();
+        let err = verifications.resend(guest.user.id, now + Duration::minutes(1)).unwrap_err();
+        match err {
+            AppError::RateLimited(_, wait) => assert!(wait.as_secs() > 0 && wait.as_secs() <= 240),
+            other => panic!("Expected RateLimited, got {:?}", other),
+        }
+
+        let outgoing = verifications
+            .resend(guest.user.id, now + RESEND_VERIFICATION_INTERVAL)
+            .unwrap();
+        verifications.verify(&token_in(&outgoing), now + RESEND_VERIFICATION_INTERVAL).unwrap();
+
+        let err = verifications
+            .resend(guest.user.id, now + RESEND_VERIFICATION_INTERVAL * 2)
+            .unwrap_err();
+        assert!(matches!(err, AppError::ValidationError(_)), "{:?}", err);
+    }
+
+    #[test]
+    fn test_unverified_guests_cannot_book_and_staff_are_exempt() {
+        let Some(pool) = test_pool() else { return };
+        let verifications = EmailVerificationService::new(pool.clone());
+        let guest = guest(&pool);
+
+        let err = book(&pool, &guest).unwrap_err();
+        assert!(matches!(&err, AppError::ValidationError(m) if m == EMAIL_NOT_VERIFIED), "{:?}", err);
+
+        let token = token_in(&verifications.issue(guest.user.id, Utc::now()).unwrap().unwrap());
+        verifications.verify(&token, Utc::now()).unwrap();
+        book(&pool, &guest).unwrap();
+
+        let staff = AuthService::new(pool.clone(), JWT_SECRET.to_string())
+            .create_user(&CreateUserRequest {
+                username: format!("verify-{}", &Uuid::new_v4().simple().to_string()[..8]),
+                password: "desk-password-1".to_string(),
+                role: UserRole::Receptionist,
+                must_change_password: false,
+            })
+            .unwrap();
+        verifications.ensure_verified(staff.id).unwrap();
+        assert!(verifications.issue(staff.id, Utc::now()).unwrap().is_none());
+    }
+}
+
+fn router(pool: DbPool) -> axum::Router {
+    let s3_config = aws_sdk_s3::config::Builder::new()
+        .region(aws_sdk_s3::config::Region::new("us-east-1"))
+        .behavior_version_latest()
+        .build();
+
+    create_router(AppState {
+        pool,
+        jwt_secret: JWT_SECRET.to_string(),
+        chat_state: Arc::new(ChatState::default()),
+        s3_client: aws_sdk_s3::Client::from_conf(s3_config),
+        read_only: Arc::new(ReadOnlyMode::new(Some(false))),
+        public_lookup: Arc::new(PublicLookupLimits::default()),
+        jobs: Arc::new(JobBoard::default()),
+        body_limits: BodyLimits::default(),
+        sessions: Arc::new(SessionTracker::default()),
+        access_token_lifetime: DEFAULT_ACCESS_TOKEN_LIFETIME,
+        login_attempts: Arc::new(InMemoryLoginAttempts::default()),
+        revocations: Arc::new(RevocationCache::default()),
+        trust_proxy_headers: false,
+        token_issuer: TokenIssuer::default(),
+    })
+}
+
+async fn post(
+    pool: &DbPool,
+    uri: &str,
+    token: Option<&str>,
+    body: serde_json::Value,
+) -> (StatusCode, serde_json::Value) {
+    let mut builder = Request::builder()
+        .method(Method::POST)
+        .uri(uri)
+        .header("content-type", "application/json");
+    if let Some(token) = token {
+        builder = builder.header(AUTHORIZATION, format!("Bearer {}", token));
+    }
+    let request = builder.body(Body::from(body.to_string())).unwrap();
+    let response = router(pool.clone()).oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&bytes).unwrap_or(serde_json::Value::Null))
+}
+
+mod endpoint_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_registration_sends_a_link_and_resending_waits() {
+        let Some(pool) = test_pool() else { return };
+        let email = format!("verify-{}@example.com", &Uuid::new_v4().simple().to_string()[..8]);
+        let body = serde_json::json!({ "email": email, "password": PASSWORD, "full_name": "Verify Guest" });
+        let (status, registered) = post(&pool, "/auth/register", None, body).await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert!(registered["user"]["email_verified_at"].is_null());
+        let token = registered["token"].as_str().unwrap().to_string();
+        let user_id: Uuid = registered["user"]["id"].as_str().unwrap().parse().unwrap();
+
+        // The link is issued on a background task
+        let mut issued = false;
+        for _ in 0..50 {
+            let count: i64 = email_verification_tokens::table
+                .filter(email_verification_tokens::user_id.eq(user_id))
+                .count()
+                .get_result(&mut pool.get().unwrap())
+                .unwrap();
+            if count > 0 {
+                issued = true;
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        }
+        assert!(issued);
+
+        let (status, body) = post(&pool, "/auth/resend-verification", Some(&token), serde_json::json!({})).await;
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(body["code"], "RATE_LIMITED");
+
+        let (status, _) = post(&pool, "/auth/resend-verification", None, serde_json::json!({})).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_verify_email_and_book() {
+        let Some(pool) = test_pool() else { return };
+        let guest = guest(&pool);
+        let room = RoomService::new(pool.clone())
+            .create_room(&format!("V{}", &Uuid::new_v4().simple().to_string()[..8]), RoomType::DOUBLE)
+            .unwrap();
+        let check_in = Utc::now().date_naive() + Duration::days(70);
+        let stay = serde_json::json!({
+            "room_id": room.id,
+            "check_in_date": check_in,
+            "check_out_date": check_in + Duration::days(2),
+        });
+
+        let (status, body) = post(&pool, "/guest/bookings", Some(&guest.token), stay.clone()).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["message"], EMAIL_NOT_VERIFIED);
+
+        let outgoing = EmailVerificationService::new(pool.clone())
+            .issue(guest.user.id, Utc::now())
+            .unwrap()
+            .unwrap();
+        let verify = serde_json::json!({ "token": token_in(&outgoing) });
+        let (status, _) = post(&pool, "/auth/verify-email", None, verify.clone()).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, body) = post(&pool, "/auth/verify-email", None, verify).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["message"], INVALID_VERIFICATION_TOKEN);
+
+        let (status, _) = post(&pool, "/guest/bookings", Some(&guest.token), stay).await;
+        assert_eq!(status, StatusCode::CREATED);
+    }
+}
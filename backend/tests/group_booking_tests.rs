@@ -0,0 +1,160 @@
+//! Group booking tests
+//!
+//! Tests for validating the rooms of a group booking and for creating one
+//! all-or-nothing. The creation tests need a migrated PostgreSQL database and
+//! only run when TEST_DATABASE_URL is set.
+
+use chrono::{Duration, NaiveDate, Utc};
+use uuid::Uuid;
+
+use hotel_management_backend::db::{create_pool, DbPool};
+use hotel_management_backend::errors::AppError;
+use hotel_management_backend::models::{Room, RoomType, UserRole};
+use hotel_management_backend::services::auth_service::CreateUserRequest;
+use hotel_management_backend::services::booking_service::{BookingListFilter, StaffBookingRequest, MAX_GROUP_ROOMS};
+use hotel_management_backend::services::{AuthService, BookingService, RoomService};
+
+mod validate_rooms_tests {
+    use super::*;
+
+    #[test]
+    fn test_distinct_rooms_are_accepted() {
+        assert!(BookingService::validate_group_rooms(&[Uuid::new_v4(), Uuid::new_v4()]).is_ok());
+    }
+
+    #[test]
+    fn test_empty_repeated_and_oversized_groups_are_rejected() {
+        let room = Uuid::new_v4();
+        let too_many: Vec<Uuid> = (0..=MAX_GROUP_ROOMS).map(|_| Uuid::new_v4()).collect();
+
+        for room_ids in [vec![], vec![room, Uuid::new_v4(), room], too_many] {
+            assert!(matches!(
+                BookingService::validate_group_rooms(&room_ids),
+                Err(AppError::ValidationError(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_group_reference_format() {
+        let reference = BookingService::generate_group_reference();
+        assert!(reference.starts_with("GRP-"));
+        assert_eq!(reference.len(), 17);
+    }
+}
+
+fn test_pool() -> Option<DbPool> {
+    match std::env::var("TEST_DATABASE_URL") {
+        Ok(url) => Some(create_pool(&url)),
+        Err(_) => {
+            eprintln!("TEST_DATABASE_URL not set, skipping database test");
+            None
+        }
+    }
+}
+
+mod create_group_tests {
+    use super::*;
+
+    fn rooms(pool: &DbPool, count: usize) -> Vec<Room> {
+        let rooms = RoomService::new(pool.clone());
+        (0..count)
+            .map(|_| {
+                let number = format!("G{}", &Uuid::new_v4().simple().to_string()[..8]);
+                rooms.create_room(&number, RoomType::DOUBLE).unwrap()
+            })
+            .collect()
+    }
+
+    fn receptionist(pool: &DbPool) -> Uuid {
+        AuthService::new(pool.clone(), "test-secret".to_string())
+            .create_user(&CreateUserRequest {
+                username: format!("group-{}", &Uuid::new_v4().simple().to_string()[..8]),
+                password: "desk-password-1".to_string(),
+                role: UserRole::Receptionist,
+                must_change_password: false,
+            })
+            .unwrap()
+            .id
+    }
+
+    fn stay() -> (NaiveDate, NaiveDate) {
+        let check_in = Utc::now().date_naive() + Duration::days(40);
+        (check_in, check_in + Duration::days(2))
+    }
+
+    #[test]
+    fn test_all_rooms_share_group_reference() {
+        let Some(pool) = test_pool() else { return };
+        let service = BookingService::new(pool.clone());
+        let rooms = rooms(&pool, 3);
+        let (check_in, check_out) = stay();
+
+        let created = service
+            .create_group_booking(
+                "Saigon Tours",
+                rooms.iter().map(|r| r.id).collect(),
+                check_in,
+                check_out,
+                receptionist(&pool),
+            )
+            .unwrap();
+
+        assert_eq!(created.len(), 3);
+        let group = created[0].booking.group_reference.clone().unwrap();
+        assert!(created
+            .iter()
+            .all(|b| b.booking.group_reference.as_deref() == Some(group.as_str())));
+        assert_eq!(created[1].booking.price, &rooms[1].price * bigdecimal::BigDecimal::from(2));
+
+        let listed = service
+            .list_bookings(&BookingListFilter {
+                group_reference: Some(&group.to_lowercase()),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(listed.len(), 3);
+    }
+
+    #[test]
+    fn test_one_unavailable_room_fails_the_whole_group() {
+        let Some(pool) = test_pool() else { return };
+        let service = BookingService::new(pool.clone());
+        let rooms = rooms(&pool, 3);
+        let (check_in, check_out) = stay();
+
+        service
+            .create_booking(
+                &StaffBookingRequest {
+                    guest_name: format!("Guest {}", rooms[2].number),
+                    room_id: rooms[2].id,
+                    check_in_date: check_in,
+                    check_out_date: check_out,
+                    price: None,
+                    allow_duplicate: false,
+                    override_conflict: false,
+                },
+                None,
+            )
+            .unwrap();
+
+        match service.create_group_booking(
+            "Hanoi Travel",
+            rooms.iter().map(|r| r.id).collect(),
+            check_in,
+            check_out,
+            receptionist(&pool),
+        ) {
+            Err(AppError::RoomUnavailable(msg)) => {
+                assert!(msg.contains(&rooms[2].number));
+                assert!(!msg.contains(&rooms[0].number));
+            }
+            other => panic!("Expected RoomUnavailable, got {:?}", other),
+        }
+
+        // Nothing was booked in the free rooms
+        assert!(service
+            .check_availability(rooms[0].id, check_in, check_out, None, None)
+            .unwrap());
+    }
+}
@@ -6,9 +6,6 @@
 use hotel_management_backend::models::UserRole;
 use hotel_management_backend::services::AuthService;
 
-use serde_json;
-use uuid::Uuid;
-
 /// Test helper: Basic email validation (sufficient for unit tests)
 fn is_valid_email(email: &str) -> bool {
     // Basic email validation: contains @ and at least one . after @
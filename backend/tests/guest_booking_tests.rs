@@ -110,9 +110,9 @@ fn test_new_guest_booking_status_is_upcoming_and_transitions() {
 /// Test: RoomType variants are defined and distinct
 #[test]
 fn test_room_types_defined_and_distinct() {
-    let s = RoomType::Single;
-    let d = RoomType::Double;
-    let su = RoomType::Suite;
+    let s = RoomType::SINGLE;
+    let d = RoomType::DOUBLE;
+    let su = RoomType::SUITE;
 
     assert_ne!(s, d);
     assert_ne!(d, su);
@@ -131,11 +131,12 @@ fn test_guest_only_sees_own_bookings_mocked() {
     assert_ne!(guest_a_id, guest_b_id);
 
     struct MockBooking {
+        #[allow(dead_code)]
         id: &'static str,
         created_by_user_id: Option<&'static str>,
     }
 
-    let bookings = vec![
+    let bookings = [
         MockBooking { id: "booking-1", created_by_user_id: Some(guest_a_id) },
         MockBooking { id: "booking-2", created_by_user_id: Some(guest_b_id) },
         MockBooking { id: "booking-3", created_by_user_id: Some(guest_a_id) },
@@ -168,7 +169,7 @@ fn test_guest_cannot_access_other_guests_booking_mocked() {
     assert!(booking_owner == guest_a_id, "Guest A should access their own booking");
 
     // Guest B cannot access
-    assert!(!(booking_owner == guest_b_id), "Guest B should NOT access Guest A's booking");
+    assert!(booking_owner != guest_b_id, "Guest B should NOT access Guest A's booking");
 
     // Design: unauthorized access returns "not_found"
     let expected_error = "not_found";
@@ -180,7 +181,7 @@ fn test_guest_cannot_access_other_guests_booking_mocked() {
 fn test_bookings_sorted_by_checkin_date_mocked() {
     struct MockBooking { check_in_date: u32 }
 
-    let mut bookings = vec![
+    let mut bookings = [
         MockBooking { check_in_date: 15 },
         MockBooking { check_in_date: 20 },
         MockBooking { check_in_date: 10 },
@@ -188,7 +189,7 @@ fn test_bookings_sorted_by_checkin_date_mocked() {
     ];
 
     // Sort descending
-    bookings.sort_by(|a, b| b.check_in_date.cmp(&a.check_in_date));
+    bookings.sort_by_key(|b| std::cmp::Reverse(b.check_in_date));
 
     assert_eq!(bookings[0].check_in_date, 25);
     assert_eq!(bookings[1].check_in_date, 20);
@@ -269,3 +270,107 @@ fn test_guest_cannot_cancel_already_cancelled_booking() {
 // - T065-T067: guest cancellation tests
 // These require database setup and will be tested via API integration tests.
 
+
+// ============================================================================
+// Guest Portal: Current Stay Tests
+// ============================================================================
+
+/// Test: Remaining nights count down to check-out and never go negative
+#[test]
+fn test_current_stay_remaining_nights() {
+    use chrono::NaiveDate;
+    use hotel_management_backend::services::BookingService;
+
+    let check_out = NaiveDate::from_ymd_opt(2025, 4, 5).unwrap();
+
+    let mid_stay = NaiveDate::from_ymd_opt(2025, 4, 2).unwrap();
+    assert_eq!(BookingService::remaining_nights(check_out, mid_stay), 3);
+    assert_eq!(BookingService::remaining_nights(check_out, check_out), 0);
+
+    let overstaying = NaiveDate::from_ymd_opt(2025, 4, 7).unwrap();
+    assert_eq!(BookingService::remaining_nights(check_out, overstaying), 0);
+}
+
+/// Test: A guest who is not in house gets a 404 with a specific code
+#[tokio::test]
+async fn test_not_in_house_response_code() {
+    use axum::http::StatusCode;
+    use axum::response::IntoResponse;
+    use hotel_management_backend::errors::AppError;
+
+    let response = AppError::NotInHouse("You are not currently checked in".to_string()).into_response();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["code"], "NOT_IN_HOUSE");
+}
+
+// ============================================================================
+// Cancellation Cutoff Tests
+// ============================================================================
+
+/// Hotel at +07:00 with 14:00 check-in and a 24-hour cutoff; check-in on
+/// 2025-09-10, so the deadline is 2025-09-09 14:00 local (07:00 UTC)
+fn cutoff_case() -> (
+    chrono::NaiveDate,
+    hotel_management_backend::settings::Settings,
+    chrono::DateTime<chrono::Utc>,
+) {
+    use chrono::{NaiveDate, TimeZone, Utc};
+    use std::collections::HashMap;
+
+    let settings = hotel_management_backend::settings::Settings::from_values(HashMap::from([
+        ("hotel_timezone".to_string(), "+07:00".to_string()),
+        ("check_in_time".to_string(), "14:00".to_string()),
+        ("booking_cancellation_cutoff_hours".to_string(), "24".to_string()),
+    ]));
+    let deadline = Utc.with_ymd_and_hms(2025, 9, 9, 7, 0, 0).unwrap();
+    (NaiveDate::from_ymd_opt(2025, 9, 10).unwrap(), settings, deadline)
+}
+
+/// Test: The deadline is the cutoff before the local check-in time, not midnight
+#[test]
+fn test_cancellation_deadline_uses_check_in_time() {
+    use hotel_management_backend::services::BookingService;
+
+    let (check_in, settings, deadline) = cutoff_case();
+    assert_eq!(BookingService::cancellation_deadline(check_in, &settings), deadline);
+}
+
+/// Test: Cancelling exactly at the deadline is still allowed
+#[test]
+fn test_guest_can_cancel_exactly_at_cutoff() {
+    use hotel_management_backend::services::BookingService;
+
+    let (check_in, settings, deadline) = cutoff_case();
+    assert!(BookingService::check_guest_cancellation(check_in, deadline, &settings).is_ok());
+}
+
+/// Test: Cancelling before the window opens is allowed
+#[test]
+fn test_guest_can_cancel_outside_cutoff() {
+    use hotel_management_backend::services::BookingService;
+
+    let (check_in, settings, deadline) = cutoff_case();
+    let earlier = deadline - chrono::Duration::days(3);
+    assert!(BookingService::check_guest_cancellation(check_in, earlier, &settings).is_ok());
+    assert!(!BookingService::inside_cancellation_cutoff(check_in, earlier, &settings));
+}
+
+/// Test: Cancelling inside the window points the guest to reception
+#[test]
+fn test_guest_cannot_cancel_inside_cutoff() {
+    use hotel_management_backend::errors::AppError;
+    use hotel_management_backend::services::BookingService;
+
+    let (check_in, settings, deadline) = cutoff_case();
+    let late = deadline + chrono::Duration::minutes(1);
+    match BookingService::check_guest_cancellation(check_in, late, &settings) {
+        Err(AppError::ValidationError(msg)) => {
+            assert!(msg.contains("contact reception"), "{}", msg);
+            assert!(msg.contains("2025-09-09 14:00"), "{}", msg);
+        }
+        other => panic!("Expected ValidationError, got {:?}", other),
+    }
+}
@@ -0,0 +1,208 @@
+//! Guest login tests
+//!
+//! Tests for signing guests in by email: case-insensitive matching, one
+//! generic error for every failure, and keeping guests and staff on their
+//! own login. They need a migrated PostgreSQL database and only run when
+//! TEST_DATABASE_URL is set.
+
+use std::sync::Arc;
+
+use axum::body::{to_bytes, Body};
+use axum::http::{Method, Request, StatusCode};
+use chrono::Utc;
+use diesel::prelude::*;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use hotel_management_backend::api::public_bookings::PublicLookupLimits;
+use hotel_management_backend::api::{chat::ChatState, create_router, AppState};
+use hotel_management_backend::config::BodyLimits;
+use hotel_management_backend::db::{create_pool, DbPool};
+use hotel_management_backend::errors::AppError;
+use hotel_management_backend::models::UserRole;
+use hotel_management_backend::scheduler::JobBoard;
+use hotel_management_backend::schema::users;
+use hotel_management_backend::services::auth_service::{
+    CreateUserRequest, GuestLoginRequest, GuestRegisterRequest, LoginRequest, DEFAULT_ACCESS_TOKEN_LIFETIME,
+    GUEST_LOGIN_FAILED,
+};
+use hotel_management_backend::services::rate_limit_service::InMemoryLoginAttempts;
+use hotel_management_backend::services::{AuthService, ReadOnlyMode, RevocationCache, SessionTracker, TokenIssuer};
+
+const JWT_SECRET: &str = "test-secret";
+const PASSWORD: &str = "guest-password-1";
+
+fn test_pool() -> Option<DbPool> {
+    match std::env::var("TEST_DATABASE_URL") {
+        Ok(url) => Some(create_pool(&url)),
+        Err(_) => {
+            eprintln!("TEST_DATABASE_URL not set, skipping database test");
+            None
+        }
+    }
+}
+
+/// Email and id of a new guest
+fn guest(auth: &AuthService) -> (String, Uuid) {
+    let email = format!("guest-{}@example.com", &Uuid::new_v4().simple().to_string()[..8]);
+    let response = auth
+        .register_guest(&GuestRegisterRequest {
+            email: email.clone(),
+            password: PASSWORD.to_string(),
+            full_name: "Login Guest".to_string(),
+        })
+        .unwrap();
+    (email, response.user.id)
+}
+
+fn guest_login(auth: &AuthService, email: &str, password: &str) -> Result<String, AppError> {
+    auth.login_guest(&GuestLoginRequest {
+        email: email.to_string(),
+        password: password.to_string(),
+    })
+    .map(|response| response.token)
+}
+
+fn assert_generic(result: Result<String, AppError>) {
+    let err = result.unwrap_err();
+    assert!(matches!(&err, AppError::Unauthorized(m) if m == GUEST_LOGIN_FAILED), "{:?}", err);
+}
+
+mod service_tests {
+    use super::*;
+
+    #[test]
+    fn test_email_matches_case_insensitively() {
+        let Some(pool) = test_pool() else { return };
+        let auth = AuthService::new(pool.clone(), JWT_SECRET.to_string());
+        let (email, id) = guest(&auth);
+
+        let token = guest_login(&auth, &format!("  {}  ", email.to_uppercase()), PASSWORD).unwrap();
+        assert_eq!(auth.validate_token(&token).unwrap().sub, id);
+
+        // An email saved with capitals through guest management still matches
+        let mut conn = pool.get().unwrap();
+        let mixed = format!("Mixed.{}", email);
+        diesel::update(users::table.find(id))
+            .set(users::email.eq(&mixed))
+            .execute(&mut conn)
+            .unwrap();
+        guest_login(&auth, &mixed.to_lowercase(), PASSWORD).unwrap();
+    }
+
+    #[test]
+    fn test_every_failure_gets_the_same_error() {
+        let Some(pool) = test_pool() else { return };
+        let auth = AuthService::new(pool.clone(), JWT_SECRET.to_string());
+        let (email, id) = guest(&auth);
+
+        assert_generic(guest_login(&auth, &email, "wrong-password-1"));
+        assert_generic(guest_login(&auth, &format!("nobody-{}", email), PASSWORD));
+
+        // A staff member with an email is not a guest
+        let staff = auth
+            .create_user(&CreateUserRequest {
+                username: format!("desk-{}", &Uuid::new_v4().simple().to_string()[..8]),
+                password: PASSWORD.to_string(),
+                role: UserRole::Receptionist,
+                must_change_password: false,
+            })
+            .unwrap();
+        let staff_email = format!("staff-{}", email);
+        let mut conn = pool.get().unwrap();
+        diesel::update(users::table.find(staff.id))
+            .set(users::email.eq(&staff_email))
+            .execute(&mut conn)
+            .unwrap();
+        assert_generic(guest_login(&auth, &staff_email, PASSWORD));
+
+        // Nor does a deactivated guest learn that the password was right
+        diesel::update(users::table.find(id))
+            .set(users::deactivated_at.eq(Some(Utc::now())))
+            .execute(&mut conn)
+            .unwrap();
+        assert_generic(guest_login(&auth, &email, PASSWORD));
+    }
+
+    #[test]
+    fn test_staff_login_refuses_guests() {
+        let Some(pool) = test_pool() else { return };
+        let auth = AuthService::new(pool.clone(), JWT_SECRET.to_string());
+        let (_, id) = guest(&auth);
+
+        // A guest that somehow has a username still cannot use it
+        let username = format!("guest-{}", &Uuid::new_v4().simple().to_string()[..8]);
+        let mut conn = pool.get().unwrap();
+        diesel::update(users::table.find(id))
+            .set(users::username.eq(&username))
+            .execute(&mut conn)
+            .unwrap();
+        let err = auth
+            .login(&LoginRequest {
+                username,
+                password: PASSWORD.to_string(),
+            })
+            .unwrap_err();
+        assert!(matches!(&err, AppError::Unauthorized(m) if m == "Invalid credentials"), "{:?}", err);
+    }
+}
+
+fn router(pool: DbPool) -> axum::Router {
+    let s3_config = aws_sdk_s3::config::Builder::new()
+        .region(aws_sdk_s3::config::Region::new("us-east-1"))
+        .behavior_version_latest()
+        .build();
+
+    create_router(AppState {
+        pool,
+        jwt_secret: JWT_SECRET.to_string(),
+        chat_state: Arc::new(ChatState::default()),
+        s3_client: aws_sdk_s3::Client::from_conf(s3_config),
+        read_only: Arc::new(ReadOnlyMode::new(Some(false))),
+        public_lookup: Arc::new(PublicLookupLimits::default()),
+        jobs: Arc::new(JobBoard::default()),
+        body_limits: BodyLimits::default(),
+        sessions: Arc::new(SessionTracker::default()),
+        access_token_lifetime: DEFAULT_ACCESS_TOKEN_LIFETIME,
+        login_attempts: Arc::new(InMemoryLoginAttempts::default()),
+        revocations: Arc::new(RevocationCache::default()),
+        trust_proxy_headers: false,
+        token_issuer: TokenIssuer::default(),
+    })
+}
+
+async fn post_guest_login(pool: &DbPool, email: &str, password: &str) -> (StatusCode, serde_json::Value) {
+    let request = Request::builder()
+        .method(Method::POST)
+        .uri("/auth/guest/login")
+        .header("content-type", "application/json")
+        .body(Body::from(
+            serde_json::json!({ "email": email, "password": password }).to_string(),
+        ))
+        .unwrap();
+    let response = router(pool.clone()).oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&bytes).unwrap_or(serde_json::Value::Null))
+}
+
+mod endpoint_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_guest_login_over_http() {
+        let Some(pool) = test_pool() else { return };
+        let (email, _) = guest(&AuthService::new(pool.clone(), JWT_SECRET.to_string()));
+
+        let (status, body) = post_guest_login(&pool, &email.to_uppercase(), PASSWORD).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["user"]["email"], email.as_str());
+        assert!(body["token"].is_string());
+
+        let (status, wrong) = post_guest_login(&pool, &email, "wrong-password-1").await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let (status, unknown) = post_guest_login(&pool, &format!("nobody-{}", email), PASSWORD).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(wrong, unknown);
+    }
+}
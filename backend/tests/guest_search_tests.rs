@@ -0,0 +1,231 @@
+//! Guest search tests
+//!
+//! Tests for the booking statistics attached to guest search results and for
+//! the reception in-house directory.
+
+use std::collections::HashMap;
+
+use bigdecimal::BigDecimal;
+use chrono::{NaiveDate, Utc};
+use uuid::Uuid;
+
+use hotel_management_backend::models::{Booking, BookingStatus, Room, RoomStatus, RoomType, User, UserRole};
+use hotel_management_backend::services::GuestService;
+
+fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(y, m, d).unwrap()
+}
+
+fn guest(full_name: &str) -> User {
+    User {
+        id: Uuid::new_v4(),
+        username: None,
+        password_hash: String::new(),
+        role: UserRole::Guest,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+        email: Some(format!("{}@example.com", full_name.to_lowercase().replace(' ', "."))),
+        full_name: Some(full_name.to_string()),
+        phone: None,
+        id_number: None,
+        deactivated_at: None,
+        tokens_not_before: None,
+        last_login_at: None,
+        must_change_password: false,
+        email_verified_at: None,
+    }
+}
+
+fn booking(
+    guest_name: &str,
+    created_by_user_id: Option<Uuid>,
+    check_in_date: NaiveDate,
+    status: BookingStatus,
+) -> Booking {
+    Booking {
+        id: Uuid::new_v4(),
+        reference: "BK-20250101-TEST".to_string(),
+        guest_name: guest_name.to_string(),
+        room_id: Uuid::new_v4(),
+        check_in_date,
+        check_out_date: check_in_date + chrono::Duration::days(2),
+        status,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+        created_by_user_id,
+        creation_source: if created_by_user_id.is_some() { "guest" } else { "staff" }.to_string(),
+        price: BigDecimal::from(100),
+        group_reference: None,
+        checked_in_at: None,
+        checked_out_at: None,
+    }
+}
+
+mod booking_stats_tests {
+    use super::*;
+
+    #[test]
+    fn test_stats_combine_owned_and_name_matched_bookings() {
+        let today = date(2025, 6, 1);
+        let guest_a = guest("Nguyen Van A");
+        let bookings = vec![
+            // Owned by the guest
+            booking("Nguyen Van A", Some(guest_a.id), date(2025, 3, 10), BookingStatus::CheckedOut),
+            booking("Nguyen Van A", Some(guest_a.id), date(2025, 7, 1), BookingStatus::Upcoming),
+            // Staff-created, matched by name (case-insensitive)
+            booking("nguyen van a", None, date(2025, 5, 2), BookingStatus::CheckedOut),
+        ];
+
+        let stats = GuestService::summarize_booking_stats(std::slice::from_ref(&guest_a), &bookings, today);
+        let a = &stats[&guest_a.id];
+
+        assert_eq!(a.total_bookings, 3);
+        assert_eq!(a.last_stay_date, Some(date(2025, 5, 2)));
+        assert!(a.has_upcoming_booking);
+    }
+
+    #[test]
+    fn test_stats_ignore_other_guests_bookings() {
+        let today = date(2025, 6, 1);
+        let guest_a = guest("Nguyen Van A");
+        let guest_b = guest("Tran Thi B");
+        let bookings = vec![
+            booking("Tran Thi B", Some(guest_b.id), date(2025, 2, 1), BookingStatus::CheckedOut),
+            // Same name but owned by another account: not name-matched
+            booking("Nguyen Van A", Some(guest_b.id), date(2025, 4, 1), BookingStatus::CheckedOut),
+        ];
+
+        let stats = GuestService::summarize_booking_stats(
+            &[guest_a.clone(), guest_b.clone()],
+            &bookings,
+            today,
+        );
+
+        assert_eq!(stats[&guest_a.id].total_bookings, 0);
+        assert_eq!(stats[&guest_a.id].last_stay_date, None);
+        assert_eq!(stats[&guest_b.id].total_bookings, 2);
+        assert_eq!(stats[&guest_b.id].last_stay_date, Some(date(2025, 4, 1)));
+    }
+
+    #[test]
+    fn test_cancelled_and_past_upcoming_do_not_count_as_stays() {
+        let today = date(2025, 6, 1);
+        let guest_a = guest("Nguyen Van A");
+        let bookings = vec![
+            booking("Nguyen Van A", Some(guest_a.id), date(2025, 5, 1), BookingStatus::Cancelled),
+            booking("Nguyen Van A", None, date(2025, 5, 20), BookingStatus::Upcoming),
+        ];
+
+        let stats = GuestService::summarize_booking_stats(std::slice::from_ref(&guest_a), &bookings, today);
+        let a = &stats[&guest_a.id];
+
+        assert_eq!(a.total_bookings, 2);
+        assert_eq!(a.last_stay_date, None);
+        assert!(!a.has_upcoming_booking);
+    }
+
+    #[test]
+    fn test_staff_booking_with_recorded_creator_is_name_matched() {
+        let today = date(2025, 6, 1);
+        let guest_a = guest("Nguyen Van A");
+        let mut taken_at_desk = booking("Nguyen Van A", None, date(2025, 5, 2), BookingStatus::CheckedOut);
+        // The receptionist who took the booking, not the guest
+        taken_at_desk.created_by_user_id = Some(Uuid::new_v4());
+
+        let stats = GuestService::summarize_booking_stats(std::slice::from_ref(&guest_a), &[taken_at_desk], today);
+
+        assert_eq!(stats[&guest_a.id].total_bookings, 1);
+        assert_eq!(stats[&guest_a.id].last_stay_date, Some(date(2025, 5, 2)));
+    }
+
+    #[test]
+    fn test_guest_without_full_name_only_counts_owned_bookings() {
+        let today = date(2025, 6, 1);
+        let mut guest_a = guest("Nguyen Van A");
+        guest_a.full_name = None;
+        let bookings = vec![
+            booking("Nguyen Van A", None, date(2025, 5, 1), BookingStatus::CheckedOut),
+            booking("Nguyen Van A", Some(guest_a.id), date(2025, 6, 1), BookingStatus::CheckedIn),
+        ];
+
+        let stats = GuestService::summarize_booking_stats(std::slice::from_ref(&guest_a), &bookings, today);
+
+        assert_eq!(stats[&guest_a.id].total_bookings, 1);
+        assert_eq!(stats[&guest_a.id].last_stay_date, Some(date(2025, 6, 1)));
+    }
+}
+
+mod in_house_directory_tests {
+    use super::*;
+
+    fn room(number: &str) -> Room {
+        Room {
+            id: Uuid::new_v4(),
+            number: number.to_string(),
+            room_type: RoomType::DOUBLE,
+            status: RoomStatus::Occupied,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            price: BigDecimal::from(50),
+            assigned_cleaner_id: None,
+            description: None,
+            amenities: vec![],
+            photo_urls: vec![],
+            decommissioned_at: None,
+            floor: 1,
+            building: None,
+            room_type_id: Uuid::new_v4(),
+        }
+    }
+
+    #[test]
+    fn test_directory_joins_contact_balance_and_unread_chat() {
+        let today = date(2025, 6, 2);
+        let account = guest("Pham Thi D");
+        let owned = booking("Pham Thi D", Some(account.id), date(2025, 6, 1), BookingStatus::CheckedIn);
+        let walk_in = booking("Walk In", None, date(2025, 5, 30), BookingStatus::Overstay);
+
+        let paid = HashMap::from([(owned.id, BigDecimal::from(40))]);
+        let unread = HashMap::from([(account.id, 2)]);
+        let contacts = HashMap::from([(account.id, account.clone())]);
+
+        let directory = GuestService::build_in_house_directory(
+            vec![(owned.clone(), room("204")), (walk_in.clone(), room("305"))],
+            &contacts,
+            &paid,
+            &unread,
+            today,
+        );
+
+        assert_eq!(directory.len(), 2);
+        let first = &directory[0];
+        assert_eq!(first.room_number, "204");
+        assert_eq!(first.nights_remaining, 1);
+        assert_eq!(first.balance_due, BigDecimal::from(60));
+        assert_eq!(first.unread_messages, 2);
+        let contact = first.contact.as_ref().unwrap();
+        assert_eq!(contact.email, account.email);
+
+        let second = &directory[1];
+        assert_eq!(second.room_number, "305");
+        assert_eq!(second.nights_remaining, 0);
+        assert_eq!(second.balance_due, BigDecimal::from(100));
+        assert!(second.contact.is_none());
+        assert_eq!(second.unread_messages, 0);
+    }
+
+    #[test]
+    fn test_staff_created_by_id_is_not_a_guest_contact() {
+        let staff_created = booking("Walk In", Some(Uuid::new_v4()), date(2025, 6, 1), BookingStatus::CheckedIn);
+
+        let directory = GuestService::build_in_house_directory(
+            vec![(staff_created, room("101"))],
+            &HashMap::new(),
+            &HashMap::new(),
+            &HashMap::new(),
+            date(2025, 6, 1),
+        );
+
+        assert!(directory[0].contact.is_none());
+    }
+}
@@ -0,0 +1,201 @@
+//! Hotel date tests
+//!
+//! Tests that "today" is the calendar date in the hotel time zone, using a
+//! fixed clock around the local midnight, for booking dates, check-in and
+//! early check-out. The service tests need a migrated PostgreSQL database and
+//! only run when TEST_DATABASE_URL is set.
+
+use std::sync::Arc;
+
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+use chrono_tz::Tz;
+use uuid::Uuid;
+
+use hotel_management_backend::db::{create_pool, DbPool};
+use hotel_management_backend::errors::AppError;
+use hotel_management_backend::models::{Booking, BookingStatus, RoomType};
+use hotel_management_backend::services::booking_service::StaffBookingRequest;
+use hotel_management_backend::services::{BookingService, RoomService};
+use hotel_management_backend::settings::{self, Settings};
+use hotel_management_backend::utils::clock::{local_date, local_to_utc, Clock, FixedClock};
+
+const HOTEL: Tz = chrono_tz::Asia::Ho_Chi_Minh;
+
+fn date(day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2025, 6, day).unwrap()
+}
+
+/// Hotel date read from a clock stopped at `hour`:`minute` UTC on 2025-06-`day`
+fn hotel_today(day: u32, hour: u32, minute: u32) -> NaiveDate {
+    let clock = FixedClock(Utc.with_ymd_and_hms(2025, 6, day, hour, minute, 0).unwrap());
+    local_date(clock.now(), HOTEL)
+}
+
+fn booking(check_in_date: NaiveDate, check_out_date: NaiveDate, status: BookingStatus) -> Booking {
+    Booking {
+        id: Uuid::new_v4(),
+        reference: "BK-20250610-TZ01".to_string(),
+        guest_name: "Le Van C".to_string(),
+        room_id: Uuid::new_v4(),
+        check_in_date,
+        check_out_date,
+        status,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+        created_by_user_id: None,
+        creation_source: "staff".to_string(),
+        price: BigDecimal::from(1_000_000),
+        group_reference: None,
+        checked_in_at: None,
+        checked_out_at: None,
+    }
+}
+
+mod local_date_tests {
+    use super::*;
+
+    #[test]
+    fn test_hotel_day_starts_at_17_utc() {
+        // 23:59 and 00:00 in Ho Chi Minh City (UTC+7)
+        assert_eq!(hotel_today(9, 16, 59), date(9));
+        assert_eq!(hotel_today(9, 17, 0), date(10));
+    }
+
+    #[test]
+    fn test_early_morning_is_already_the_next_day() {
+        // 06:30 on the 10th in the hotel, still the 9th in UTC
+        assert_eq!(hotel_today(9, 23, 30), date(10));
+    }
+
+    #[test]
+    fn test_skipped_local_time_uses_offset_before_the_jump() {
+        // New York skipped 02:00-03:00 on 2025-03-09 (EST to EDT)
+        let skipped = NaiveDate::from_ymd_opt(2025, 3, 9).unwrap().and_hms_opt(2, 30, 0).unwrap();
+        assert_eq!(
+            local_to_utc(chrono_tz::America::New_York, skipped),
+            Utc.with_ymd_and_hms(2025, 3, 9, 7, 30, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_repeated_local_time_takes_the_first() {
+        // New York ran 01:00-02:00 twice on 2025-11-02; the first was EDT
+        let repeated = NaiveDate::from_ymd_opt(2025, 11, 2).unwrap().and_hms_opt(1, 30, 0).unwrap();
+        assert_eq!(
+            local_to_utc(chrono_tz::America::New_York, repeated),
+            Utc.with_ymd_and_hms(2025, 11, 2, 5, 30, 0).unwrap()
+        );
+    }
+}
+
+mod date_rule_tests {
+    use super::*;
+
+    #[test]
+    fn test_todays_check_in_is_accepted_before_utc_catches_up() {
+        // 06:30 hotel time: today's arrivals must not count as in the past
+        let today = hotel_today(9, 23, 30);
+        assert!(BookingService::validate_date_order(date(10), date(11), today).is_ok());
+        match BookingService::validate_date_order(date(9), date(11), today) {
+            Err(AppError::ValidationError(msg)) => assert!(msg.contains("past")),
+            other => panic!("Expected ValidationError, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_late_night_cannot_check_in_tomorrow() {
+        // 23:30 hotel time on the 9th, already the 9th in UTC too
+        let stay = booking(date(10), date(12), BookingStatus::Upcoming);
+        assert!(BookingService::validate_check_in_day(&stay, hotel_today(9, 16, 30)).is_err());
+        assert!(BookingService::validate_check_in_day(&stay, hotel_today(9, 17, 0)).is_ok());
+    }
+
+    #[test]
+    fn test_late_arrival_window_follows_hotel_date() {
+        let no_show = booking(date(10), date(12), BookingStatus::NoShow);
+        // 23:59 on the 11th and 00:00 on the 12th in the hotel
+        assert!(BookingService::validate_check_in_day(&no_show, hotel_today(11, 16, 59)).is_ok());
+        assert!(BookingService::validate_check_in_day(&no_show, hotel_today(11, 17, 0)).is_err());
+    }
+
+    #[test]
+    fn test_departure_morning_is_not_early() {
+        let stay = booking(date(10), date(12), BookingStatus::CheckedIn);
+        // 07:00 on the 12th in the hotel, still the 11th in UTC
+        assert_eq!(
+            BookingService::actual_check_out_date(&stay, hotel_today(12, 0, 0), false).unwrap(),
+            date(12)
+        );
+        // 23:00 on the 11th in the hotel is a day early
+        assert!(BookingService::actual_check_out_date(&stay, hotel_today(11, 16, 0), false).is_err());
+    }
+}
+
+fn test_pool() -> Option<DbPool> {
+    match std::env::var("TEST_DATABASE_URL") {
+        Ok(url) => Some(create_pool(&url)),
+        Err(_) => {
+            eprintln!("TEST_DATABASE_URL not set, skipping database test");
+            None
+        }
+    }
+}
+
+mod service_clock_tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_dates_reads_the_service_clock() {
+        let Some(pool) = test_pool() else { return };
+        let tz = Settings::load(&mut pool.get().unwrap())
+            .unwrap()
+            .time_zone(settings::HOTEL_TIMEZONE);
+        let now: DateTime<Utc> = Utc::now() + Duration::days(10);
+        let service = BookingService::with_clock(pool, Arc::new(FixedClock(now)));
+        let today = local_date(now, tz);
+
+        assert!(service.validate_dates(today, today + Duration::days(1)).is_ok());
+        // Still in the future by the wall clock, but past for the service
+        assert!(matches!(
+            service.validate_dates(today - Duration::days(1), today + Duration::days(1)),
+            Err(AppError::ValidationError(_))
+        ));
+    }
+
+    #[test]
+    fn test_check_in_records_the_clock_time() {
+        let Some(pool) = test_pool() else { return };
+        let tz = Settings::load(&mut pool.get().unwrap())
+            .unwrap()
+            .time_zone(settings::HOTEL_TIMEZONE);
+        let now = Utc::now();
+        let today = local_date(now, tz);
+        let number = format!("Z{}", &Uuid::new_v4().simple().to_string()[..8]);
+        let room = RoomService::new(pool.clone()).create_room(&number, RoomType::SINGLE).unwrap();
+        let service = BookingService::with_clock(pool, Arc::new(FixedClock(now)));
+        let booking = service
+            .create_booking(
+                &StaffBookingRequest {
+                    guest_name: format!("Arrival {}", number),
+                    room_id: room.id,
+                    check_in_date: today,
+                    check_out_date: today + Duration::days(1),
+                    price: None,
+                    allow_duplicate: false,
+                    override_conflict: false,
+                },
+                None,
+            )
+            .unwrap();
+        assert_eq!(booking.checked_in_at, None);
+
+        let checked_in = service.check_in(booking.id, Uuid::nil()).unwrap();
+        // Postgres keeps microseconds
+        assert_eq!(
+            checked_in.checked_in_at.map(|at| at.timestamp_micros()),
+            Some(now.timestamp_micros())
+        );
+        assert_eq!(checked_in.checked_out_at, None);
+    }
+}
@@ -0,0 +1,177 @@
+//! iCalendar tests
+//!
+//! Tests for escaping and folding iCalendar content lines, for the rendered
+//! stay event and for the ownership check on a guest's calendar file. The
+//! ownership test needs a migrated PostgreSQL database and only runs when
+//! TEST_DATABASE_URL is set.
+
+use chrono::{Duration, NaiveDate, TimeZone, Utc};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use hotel_management_backend::db::{create_pool, DbPool};
+use hotel_management_backend::errors::AppError;
+use hotel_management_backend::models::RoomType;
+use hotel_management_backend::schema::users;
+use hotel_management_backend::services::auth_service::GuestRegisterRequest;
+use hotel_management_backend::services::{AuthService, BookingService, RoomService};
+use hotel_management_backend::utils::ics::{escape_text, fold_line, render_event, IcsEvent};
+
+fn event<'a>(summary: &'a str, description: Option<&'a str>) -> IcsEvent<'a> {
+    IcsEvent {
+        uid: "BK-20250601-AB12",
+        summary,
+        description,
+        start: NaiveDate::from_ymd_opt(2025, 6, 1).unwrap(),
+        end: NaiveDate::from_ymd_opt(2025, 6, 4).unwrap(),
+        stamp: Utc.with_ymd_and_hms(2025, 5, 20, 8, 30, 0).unwrap(),
+    }
+}
+
+mod escape_tests {
+    use super::*;
+
+    #[test]
+    fn test_commas_and_semicolons_are_escaped() {
+        assert_eq!(escape_text("Smith, John; Jr."), "Smith\\, John\\; Jr.");
+    }
+
+    #[test]
+    fn test_backslashes_are_escaped_first() {
+        assert_eq!(escape_text("a\\,b"), "a\\\\\\,b");
+    }
+
+    #[test]
+    fn test_line_breaks_become_literal_n() {
+        assert_eq!(escape_text("one\r\ntwo\nthree"), "one\\ntwo\\nthree");
+    }
+
+    #[test]
+    fn test_plain_text_is_unchanged() {
+        assert_eq!(escape_text("Nguyễn Văn A"), "Nguyễn Văn A");
+    }
+}
+
+mod fold_tests {
+    use super::*;
+
+    #[test]
+    fn test_short_line_is_only_terminated() {
+        assert_eq!(fold_line("SUMMARY:Stay"), "SUMMARY:Stay\r\n");
+    }
+
+    #[test]
+    fn test_long_line_folds_at_75_octets() {
+        let line = format!("DESCRIPTION:{}", "x".repeat(200));
+        let folded = fold_line(&line);
+        let parts: Vec<&str> = folded.trim_end_matches("\r\n").split("\r\n").collect();
+
+        assert!(parts.len() > 1);
+        assert!(parts.iter().all(|p| p.len() <= 75));
+        assert!(parts[1..].iter().all(|p| p.starts_with(' ')));
+        let unfolded: String = parts
+            .iter()
+            .enumerate()
+            .map(|(i, p)| if i == 0 { *p } else { &p[1..] })
+            .collect();
+        assert_eq!(unfolded, line);
+    }
+
+    #[test]
+    fn test_multibyte_characters_are_not_split() {
+        let line = format!("SUMMARY:{}", "ễ".repeat(60));
+        for part in fold_line(&line).split("\r\n") {
+            assert!(part.len() <= 75);
+            assert!(std::str::from_utf8(part.as_bytes()).is_ok());
+        }
+    }
+}
+
+mod render_tests {
+    use super::*;
+
+    #[test]
+    fn test_stay_is_an_all_day_event() {
+        let calendar = render_event(&event("Stay at Pupinn Hotel", None));
+
+        assert!(calendar.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(calendar.ends_with("END:VEVENT\r\nEND:VCALENDAR\r\n"));
+        assert!(calendar.contains("\r\nUID:BK-20250601-AB12\r\n"));
+        assert!(calendar.contains("\r\nDTSTAMP:20250520T083000Z\r\n"));
+        assert!(calendar.contains("\r\nDTSTART;VALUE=DATE:20250601\r\n"));
+        assert!(calendar.contains("\r\nDTEND;VALUE=DATE:20250604\r\n"));
+        assert!(calendar.contains("\r\nSUMMARY:Stay at Pupinn Hotel\r\n"));
+        assert!(!calendar.contains("DESCRIPTION"));
+        // Every line ends in CRLF
+        assert!(!calendar.replace("\r\n", "").contains('\n'));
+    }
+
+    #[test]
+    fn test_hotel_and_guest_names_are_escaped() {
+        let calendar = render_event(&event(
+            "Stay at Sea View; Resort, Spa",
+            Some("Booking BK-20250601-AB12 for Smith, John"),
+        ));
+
+        assert!(calendar.contains("SUMMARY:Stay at Sea View\\; Resort\\, Spa\r\n"));
+        assert!(calendar.contains("DESCRIPTION:Booking BK-20250601-AB12 for Smith\\, John\r\n"));
+    }
+}
+
+fn test_pool() -> Option<DbPool> {
+    match std::env::var("TEST_DATABASE_URL") {
+        Ok(url) => Some(create_pool(&url)),
+        Err(_) => {
+            eprintln!("TEST_DATABASE_URL not set, skipping database test");
+            None
+        }
+    }
+}
+
+mod guest_calendar_tests {
+    use super::*;
+
+    fn register(auth: &AuthService) -> (Uuid, String) {
+        let suffix = &Uuid::new_v4().simple().to_string()[..8];
+        let name = format!("Calendar, Guest {}", suffix);
+        let guest = auth
+            .register_guest(&GuestRegisterRequest {
+                email: format!("ics-{}@example.com", suffix),
+                password: "guest-password-1".to_string(),
+                full_name: name.clone(),
+            })
+            .unwrap()
+            .user;
+        (guest.id, name)
+    }
+
+    #[test]
+    fn test_only_the_owner_gets_the_calendar() {
+        let Some(pool) = test_pool() else { return };
+        let auth = AuthService::new(pool.clone(), "test-secret".to_string());
+        let (owner, name) = register(&auth);
+        // Guests must verify their email before booking
+        diesel::update(users::table.find(owner))
+            .set(users::email_verified_at.eq(Some(Utc::now())))
+            .execute(&mut pool.get().unwrap())
+            .unwrap();
+        let (stranger, _) = register(&auth);
+        let number = format!("I{}", &Uuid::new_v4().simple().to_string()[..8]);
+        let room = RoomService::new(pool.clone()).create_room(&number, RoomType::SUITE).unwrap();
+        let service = BookingService::new(pool.clone());
+        let check_in = Utc::now().date_naive() + Duration::days(20);
+        let booking = service
+            .create_guest_booking(owner, &name, room.id, check_in, check_in + Duration::days(3), None)
+            .unwrap();
+
+        let calendar = service.get_guest_booking_ics(booking.booking.id, owner).unwrap();
+        assert!(calendar.contains(&format!("UID:{}\r\n", booking.booking.reference)));
+        assert!(calendar.contains(&format!("DTSTART;VALUE=DATE:{}", check_in.format("%Y%m%d"))));
+        assert!(calendar.contains("Calendar\\, Guest"));
+
+        assert!(matches!(
+            service.get_guest_booking_ics(booking.booking.id, stranger),
+            Err(AppError::NotFound(_))
+        ));
+    }
+}
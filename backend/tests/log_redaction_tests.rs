@@ -0,0 +1,114 @@
+//! Log redaction tests
+//!
+//! Tests for the redaction helpers and a capture of the startup log output
+//! checking that no configured secret reaches it.
+
+use std::io;
+use std::sync::{Arc, Mutex};
+
+use hotel_management_backend::config::Config;
+use hotel_management_backend::utils::redact::{
+    env_value_for_log, is_sensitive_env, message_content_for_log, redact, redact_url,
+    set_log_message_content, REDACTED,
+};
+
+/// Log sink shared between the subscriber and the test
+#[derive(Clone, Default)]
+struct CapturedLogs(Arc<Mutex<Vec<u8>>>);
+
+impl CapturedLogs {
+    fn contents(&self) -> String {
+        String::from_utf8_lossy(&self.0.lock().unwrap()).into_owned()
+    }
+}
+
+impl io::Write for CapturedLogs {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+mod helper_tests {
+    use super::*;
+
+    #[test]
+    fn test_redact_hides_value() {
+        assert_eq!(redact("hunter2"), REDACTED);
+        assert_eq!(redact(""), "[EMPTY]");
+    }
+
+    #[test]
+    fn test_url_user_info_is_stripped() {
+        assert_eq!(
+            redact_url("postgres://hotel:s3cret@db:5432/hotel"),
+            format!("postgres://{}@db:5432/hotel", REDACTED)
+        );
+        assert_eq!(redact_url("http://minio:9000"), "http://minio:9000");
+        assert_eq!(redact_url("http://minio:9000/a@b"), "http://minio:9000/a@b");
+        assert_eq!(redact_url("not a url"), "not a url");
+    }
+
+    #[test]
+    fn test_sensitive_env_keys() {
+        for key in ["JWT_SECRET", "MINIO_ROOT_PASSWORD", "MINIO_ROOT_USER", "MAIL_API_KEY"] {
+            assert!(is_sensitive_env(key), "{} should be sensitive", key);
+        }
+        assert!(!is_sensitive_env("SERVER_PORT"));
+        assert_eq!(env_value_for_log("SERVER_PORT", "8080"), "8080");
+        assert_eq!(env_value_for_log("JWT_SECRET", "abc"), REDACTED);
+    }
+
+    #[test]
+    fn test_message_content_is_gated() {
+        set_log_message_content(false);
+        assert_eq!(message_content_for_log("room 501 please"), "[15 chars]");
+
+        set_log_message_content(true);
+        assert_eq!(message_content_for_log("room 501 please"), "room 501 please");
+        set_log_message_content(false);
+    }
+}
+
+mod startup_log_tests {
+    use super::*;
+
+    const SECRETS: [&str; 4] = [
+        "jwt-secret-value-1234",
+        "db-password-5678",
+        "minio-user-abcd",
+        "minio-password-efgh",
+    ];
+
+    #[test]
+    fn test_startup_logs_contain_no_secrets() {
+        std::env::set_var("JWT_SECRET", SECRETS[0]);
+        std::env::set_var("DATABASE_URL", format!("postgres://hotel:{}@db:5432/hotel", SECRETS[1]));
+        std::env::set_var("MINIO_ROOT_USER", SECRETS[2]);
+        std::env::set_var("MINIO_ROOT_PASSWORD", SECRETS[3]);
+
+        let logs = CapturedLogs::default();
+        let writer = logs.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_max_level(tracing::Level::TRACE)
+            .with_ansi(false)
+            .with_writer(move || writer.clone())
+            .finish();
+
+        tracing::subscriber::with_default(subscriber, || {
+            let config = Config::from_env();
+            config.log_summary();
+        });
+
+        let output = logs.contents();
+        assert!(output.contains("MinIO credentials present: true"));
+        assert!(output.contains("Env var JWT_SECRET loaded"));
+        for secret in SECRETS {
+            assert!(!output.contains(secret), "log output leaked '{}'", secret);
+        }
+    }
+}
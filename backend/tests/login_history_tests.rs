@@ -0,0 +1,315 @@
+//! Login history tests
+//!
+//! Tests for the login audit log: `last_login_at` on users, login events for
+//! successes and failures, the client IP behind a trusted proxy, and the
+//! paged login-history endpoint. They need a migrated PostgreSQL database
+//! and only run when TEST_DATABASE_URL is set.
+
+use std::sync::Arc;
+
+use axum::body::{to_bytes, Body};
+use axum::http::{
+    header::{AUTHORIZATION, USER_AGENT},
+    Method, Request, StatusCode,
+};
+use chrono::Utc;
+use diesel::prelude::*;
+use jsonwebtoken::{encode, EncodingKey, Header};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use hotel_management_backend::api::public_bookings::PublicLookupLimits;
+use hotel_management_backend::api::{chat::ChatState, create_router, AppState};
+use hotel_management_backend::config::BodyLimits;
+use hotel_management_backend::db::{create_pool, DbPool};
+use hotel_management_backend::models::UserRole;
+use hotel_management_backend::scheduler::JobBoard;
+use hotel_management_backend::schema::users;
+use hotel_management_backend::services::auth_service::{
+    Claims, CreateUserRequest, GuestRegisterRequest, LoginRequest, DEFAULT_ACCESS_TOKEN_LIFETIME, DEFAULT_JWT_ISSUER,
+};
+use hotel_management_backend::services::rate_limit_service::InMemoryLoginAttempts;
+use hotel_management_backend::services::{
+    AuthService, LoginEventService, LoginOrigin, ReadOnlyMode, RevocationCache, SessionTracker, TokenIssuer,
+};
+
+const JWT_SECRET: &str = "test-secret";
+const PASSWORD: &str = "history-password-1";
+
+fn test_pool() -> Option<DbPool> {
+    match std::env::var("TEST_DATABASE_URL") {
+        Ok(url) => Some(create_pool(&url)),
+        Err(_) => {
+            eprintln!("TEST_DATABASE_URL not set, skipping database test");
+            None
+        }
+    }
+}
+
+/// Username and id of a new receptionist
+fn receptionist(pool: &DbPool) -> (String, Uuid) {
+    let username = format!("history-{}", &Uuid::new_v4().simple().to_string()[..8]);
+    let user = AuthService::new(pool.clone(), JWT_SECRET.to_string())
+        .create_user(&CreateUserRequest {
+            username: username.clone(),
+            password: PASSWORD.to_string(),
+            role: UserRole::Receptionist,
+            must_change_password: false,
+        })
+        .unwrap();
+    (username, user.id)
+}
+
+fn origin(ip: &str) -> LoginOrigin {
+    LoginOrigin {
+        ip: ip.to_string(),
+        user_agent: Some("history-test".to_string()),
+    }
+}
+
+fn last_login_at(pool: &DbPool, user_id: Uuid) -> Option<chrono::DateTime<Utc>> {
+    let mut conn = pool.get().unwrap();
+    users::table
+        .find(user_id)
+        .select(users::last_login_at)
+        .first(&mut conn)
+        .unwrap()
+}
+
+mod service_tests {
+    use super::*;
+
+    #[test]
+    fn test_login_sets_last_login_at() {
+        let Some(pool) = test_pool() else { return };
+        let auth = AuthService::new(pool.clone(), JWT_SECRET.to_string());
+        let (username, id) = receptionist(&pool);
+        assert!(last_login_at(&pool, id).is_none());
+
+        let before = Utc::now();
+        auth.login(&LoginRequest {
+            username: username.clone(),
+            password: PASSWORD.to_string(),
+        })
+        .unwrap();
+        let first = last_login_at(&pool, id).unwrap();
+        assert!(first >= before - chrono::Duration::seconds(1));
+
+        // A failed login leaves it alone
+        assert!(auth
+            .login(&LoginRequest {
+                username,
+                password: "wrong-password-1".to_string(),
+            })
+            .is_err());
+        assert_eq!(last_login_at(&pool, id), Some(first));
+    }
+
+    #[test]
+    fn test_history_pages_newest_first() {
+        let Some(pool) = test_pool() else { return };
+        let events = LoginEventService::new(pool.clone());
+        let (username, id) = receptionist(&pool);
+
+        events.record(id, true, &origin("10.0.0.1")).unwrap();
+        events.record_staff_failure(&username, &origin("10.0.0.2")).unwrap();
+        events.record(id, true, &origin("10.0.0.3")).unwrap();
+
+        let first = events.history(id, Some(1), Some(2)).unwrap();
+        assert_eq!(first.total, 3);
+        assert_eq!(first.per_page, 2);
+        let ips: Vec<&str> = first.events.iter().map(|e| e.ip.as_str()).collect();
+        assert_eq!(ips, ["10.0.0.3", "10.0.0.2"]);
+        assert!(!first.events[1].success);
+        assert_eq!(first.events[0].user_agent.as_deref(), Some("history-test"));
+
+        let second = events.history(id, Some(2), Some(2)).unwrap();
+        assert_eq!(second.events.len(), 1);
+        assert_eq!(second.events[0].ip, "10.0.0.1");
+    }
+
+    #[test]
+    fn test_failures_for_unknown_accounts_are_not_recorded() {
+        let Some(pool) = test_pool() else { return };
+        let events = LoginEventService::new(pool.clone());
+
+        events.record_staff_failure("history-nobody", &origin("10.0.0.4")).unwrap();
+        events.record_guest_failure("history-nobody@example.com", &origin("10.0.0.4")).unwrap();
+
+        // Long user agents are cut rather than refused
+        let (_, id) = receptionist(&pool);
+        let long = LoginOrigin {
+            ip: "10.0.0.5".to_string(),
+            user_agent: Some("a".repeat(2000)),
+        };
+        events.record(id, true, &long).unwrap();
+        let page = events.history(id, None, None).unwrap();
+        assert_eq!(page.events[0].user_agent.as_ref().unwrap().len(), 512);
+    }
+}
+
+fn router(pool: DbPool, trust_proxy_headers: bool) -> axum::Router {
+    let s3_config = aws_sdk_s3::config::Builder::new()
+        .region(aws_sdk_s3::config::Region::new("us-east-1"))
+        .behavior_version_latest()
+        .build();
+
+    create_router(AppState {
+        pool,
+        jwt_secret: JWT_SECRET.to_string(),
+        chat_state: Arc::new(ChatState::default()),
+        s3_client: aws_sdk_s3::Client::from_conf(s3_config),
+        read_only: Arc::new(ReadOnlyMode::new(Some(false))),
+        public_lookup: Arc::new(PublicLookupLimits::default()),
+        jobs: Arc::new(JobBoard::default()),
+        body_limits: BodyLimits::default(),
+        sessions: Arc::new(SessionTracker::default()),
+        access_token_lifetime: DEFAULT_ACCESS_TOKEN_LIFETIME,
+        login_attempts: Arc::new(InMemoryLoginAttempts::default()),
+        revocations: Arc::new(RevocationCache::default()),
+        trust_proxy_headers,
+        token_issuer: TokenIssuer::default(),
+    })
+}
+
+/// Token of the active admin, if the database has one; only one admin may exist
+fn admin_token(pool: &DbPool) -> Option<String> {
+    let mut conn = pool.get().unwrap();
+    let admin: Uuid = users::table
+        .filter(users::role.eq(UserRole::Admin))
+        .filter(users::deactivated_at.is_null())
+        .select(users::id)
+        .first(&mut conn)
+        .optional()
+        .unwrap()?;
+    let now = Utc::now().timestamp();
+    let claims = Claims {
+        sub: admin,
+        role: UserRole::Admin,
+        exp: now + 3600,
+        iat: now,
+        sid: None,
+        jti: Some(Uuid::new_v4()),
+        must_change_password: false,
+        iss: Some(DEFAULT_JWT_ISSUER.to_string()),
+        aud: Some(DEFAULT_JWT_ISSUER.to_string()),
+    };
+    Some(encode(&Header::default(), &claims, &EncodingKey::from_secret(JWT_SECRET.as_bytes())).unwrap())
+}
+
+async fn post_login(pool: &DbPool, trust_proxy_headers: bool, uri: &str, body: serde_json::Value) -> StatusCode {
+    let request = Request::builder()
+        .method(Method::POST)
+        .uri(uri)
+        .header("content-type", "application/json")
+        .header("x-forwarded-for", "198.51.100.7, 203.0.113.9")
+        .header(USER_AGENT, "history-browser")
+        .body(Body::from(body.to_string()))
+        .unwrap();
+    router(pool.clone(), trust_proxy_headers)
+        .oneshot(request)
+        .await
+        .unwrap()
+        .status()
+}
+
+async fn get(pool: &DbPool, uri: &str, token: &str) -> (StatusCode, serde_json::Value) {
+    let request = Request::builder()
+        .method(Method::GET)
+        .uri(uri)
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .body(Body::empty())
+        .unwrap();
+    let response = router(pool.clone(), false).oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&bytes).unwrap_or(serde_json::Value::Null))
+}
+
+mod endpoint_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_forwarded_ip_is_only_used_behind_a_trusted_proxy() {
+        let Some(pool) = test_pool() else { return };
+        let (username, id) = receptionist(&pool);
+        let right = serde_json::json!({ "username": username, "password": PASSWORD });
+        let wrong = serde_json::json!({ "username": username, "password": "wrong-password-1" });
+
+        assert_eq!(post_login(&pool, false, "/auth/login", wrong).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(post_login(&pool, true, "/auth/login", right).await, StatusCode::OK);
+
+        let page = LoginEventService::new(pool.clone()).history(id, None, None).unwrap();
+        assert_eq!(page.total, 2);
+        let (trusted, untrusted) = (&page.events[0], &page.events[1]);
+        assert!(trusted.success);
+        assert_eq!(trusted.ip, "203.0.113.9");
+        assert_eq!(trusted.user_agent.as_deref(), Some("history-browser"));
+        assert!(!untrusted.success);
+        assert_ne!(untrusted.ip, "203.0.113.9");
+        assert!(last_login_at(&pool, id).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_guest_logins_are_recorded() {
+        let Some(pool) = test_pool() else { return };
+        let email = format!("history-{}@example.com", &Uuid::new_v4().simple().to_string()[..8]);
+        let id = AuthService::new(pool.clone(), JWT_SECRET.to_string())
+            .register_guest(&GuestRegisterRequest {
+                email: email.clone(),
+                password: PASSWORD.to_string(),
+                full_name: "History Guest".to_string(),
+            })
+            .unwrap()
+            .user
+            .id;
+
+        let wrong = serde_json::json!({ "email": email, "password": "wrong-password-1" });
+        assert_eq!(post_login(&pool, false, "/auth/guest/login", wrong).await, StatusCode::UNAUTHORIZED);
+        let right = serde_json::json!({ "email": email, "password": PASSWORD });
+        assert_eq!(post_login(&pool, false, "/auth/guest/login", right).await, StatusCode::OK);
+
+        let page = LoginEventService::new(pool.clone()).history(id, None, None).unwrap();
+        let outcomes: Vec<bool> = page.events.iter().map(|e| e.success).collect();
+        assert_eq!(outcomes, [true, false]);
+        assert!(last_login_at(&pool, id).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_admin_reads_an_employees_login_history() {
+        let Some(pool) = test_pool() else { return };
+        let Some(admin) = admin_token(&pool) else {
+            eprintln!("No active admin, skipping");
+            return;
+        };
+        let (username, id) = receptionist(&pool);
+        let token = AuthService::new(pool.clone(), JWT_SECRET.to_string())
+            .login(&LoginRequest {
+                username,
+                password: PASSWORD.to_string(),
+            })
+            .unwrap()
+            .token;
+        let events = LoginEventService::new(pool.clone());
+        events.record(id, false, &origin("10.0.1.1")).unwrap();
+        events.record(id, true, &origin("10.0.1.2")).unwrap();
+
+        let uri = format!("/admin/employees/{}/login-history?page=1&per_page=1", id);
+        let (status, _) = get(&pool, &uri, &token).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        let (status, body) = get(&pool, &uri, &admin).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["total"], 2);
+        assert_eq!(body["events"].as_array().unwrap().len(), 1);
+        assert_eq!(body["events"][0]["ip"], "10.0.1.2");
+
+        let (status, body) = get(&pool, &format!("/admin/employees/{}", id), &admin).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body["last_login_at"].is_string());
+
+        let unknown = format!("/admin/employees/{}/login-history", Uuid::new_v4());
+        let (status, _) = get(&pool, &unknown, &admin).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+}
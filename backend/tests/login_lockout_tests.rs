@@ -0,0 +1,203 @@
+//! Login lockout tests
+//!
+//! Tests for locking staff logins after repeated failures: the in-memory
+//! attempt store, and the 429 with Retry-After from `POST /auth/login`. The
+//! endpoint tests need a migrated PostgreSQL database and only run when
+//! TEST_DATABASE_URL is set.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use axum::body::{to_bytes, Body};
+use axum::http::{header::RETRY_AFTER, Method, Request, StatusCode};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use hotel_management_backend::api::public_bookings::PublicLookupLimits;
+use hotel_management_backend::api::{chat::ChatState, create_router, AppState};
+use hotel_management_backend::config::BodyLimits;
+use hotel_management_backend::db::{create_pool, DbPool};
+use hotel_management_backend::models::UserRole;
+use hotel_management_backend::scheduler::JobBoard;
+use hotel_management_backend::services::auth_service::{CreateUserRequest, DEFAULT_ACCESS_TOKEN_LIFETIME};
+use hotel_management_backend::services::rate_limit_service::{
+    InMemoryLoginAttempts, LockoutPolicy, LoginAttemptStore,
+};
+use hotel_management_backend::services::{AuthService, ReadOnlyMode, RevocationCache, SessionTracker, TokenIssuer};
+
+const JWT_SECRET: &str = "test-secret";
+const PASSWORD: &str = "desk-password-1";
+
+fn policy() -> LockoutPolicy {
+    LockoutPolicy {
+        threshold: 3,
+        window: Duration::from_secs(900),
+    }
+}
+
+mod store_tests {
+    use super::*;
+
+    #[test]
+    fn test_threshold_failures_lock_the_key() {
+        let store = InMemoryLoginAttempts::new(policy());
+        let now = Instant::now();
+
+        assert_eq!(store.record_failure("user:desk", now), None);
+        assert_eq!(store.record_failure("user:desk", now), None);
+        assert_eq!(store.locked_for("user:desk", now), None);
+        assert_eq!(store.record_failure("user:desk", now), Some(Duration::from_secs(900)));
+
+        let later = now + Duration::from_secs(600);
+        assert_eq!(store.locked_for("user:desk", later), Some(Duration::from_secs(300)));
+        assert_eq!(store.locked_for("user:other", later), None);
+    }
+
+    #[test]
+    fn test_lock_expires_and_counting_restarts() {
+        let store = InMemoryLoginAttempts::new(policy());
+        let now = Instant::now();
+        for _ in 0..3 {
+            store.record_failure("ip:10.0.0.1", now);
+        }
+
+        let after = now + Duration::from_secs(900);
+        assert_eq!(store.locked_for("ip:10.0.0.1", after), None);
+        assert_eq!(store.record_failure("ip:10.0.0.1", after), None);
+        assert_eq!(store.locked_for("ip:10.0.0.1", after), None);
+    }
+
+    #[test]
+    fn test_success_resets_the_count() {
+        let store = InMemoryLoginAttempts::new(policy());
+        let now = Instant::now();
+        store.record_failure("user:desk", now);
+        store.record_failure("user:desk", now);
+        store.reset("user:desk");
+
+        assert_eq!(store.record_failure("user:desk", now), None);
+        assert_eq!(store.record_failure("user:desk", now), None);
+        assert!(store.record_failure("user:desk", now).is_some());
+    }
+}
+
+fn test_pool() -> Option<DbPool> {
+    match std::env::var("TEST_DATABASE_URL") {
+        Ok(url) => Some(create_pool(&url)),
+        Err(_) => {
+            eprintln!("TEST_DATABASE_URL not set, skipping database test");
+            None
+        }
+    }
+}
+
+fn router(pool: DbPool, login_attempts: Arc<InMemoryLoginAttempts>) -> axum::Router {
+    let s3_config = aws_sdk_s3::config::Builder::new()
+        .region(aws_sdk_s3::config::Region::new("us-east-1"))
+        .behavior_version_latest()
+        .build();
+
+    create_router(AppState {
+        pool,
+        jwt_secret: JWT_SECRET.to_string(),
+        chat_state: Arc::new(ChatState::default()),
+        s3_client: aws_sdk_s3::Client::from_conf(s3_config),
+        read_only: Arc::new(ReadOnlyMode::new(Some(false))),
+        public_lookup: Arc::new(PublicLookupLimits::default()),
+        jobs: Arc::new(JobBoard::default()),
+        body_limits: BodyLimits::default(),
+        sessions: Arc::new(SessionTracker::default()),
+        access_token_lifetime: DEFAULT_ACCESS_TOKEN_LIFETIME,
+        login_attempts,
+        revocations: Arc::new(RevocationCache::default()),
+        trust_proxy_headers: false,
+        token_issuer: TokenIssuer::default(),
+    })
+}
+
+/// Username of a new receptionist
+fn receptionist(pool: &DbPool) -> String {
+    let username = format!("lockout-{}", &Uuid::new_v4().simple().to_string()[..8]);
+    AuthService::new(pool.clone(), JWT_SECRET.to_string())
+        .create_user(&CreateUserRequest {
+            username: username.clone(),
+            password: PASSWORD.to_string(),
+            role: UserRole::Receptionist,
+            must_change_password: false,
+        })
+        .unwrap();
+    username
+}
+
+/// Status, Retry-After and body of a staff login
+async fn login(
+    pool: &DbPool,
+    attempts: &Arc<InMemoryLoginAttempts>,
+    username: &str,
+    password: &str,
+) -> (StatusCode, Option<u64>, serde_json::Value) {
+    let request = Request::builder()
+        .method(Method::POST)
+        .uri("/auth/login")
+        .header("content-type", "application/json")
+        .body(Body::from(
+            serde_json::json!({ "username": username, "password": password }).to_string(),
+        ))
+        .unwrap();
+    let response = router(pool.clone(), attempts.clone()).oneshot(request).await.unwrap();
+    let status = response.status();
+    let retry_after = response
+        .headers()
+        .get(RETRY_AFTER)
+        .map(|value| value.to_str().unwrap().parse().unwrap());
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, retry_after, serde_json::from_slice(&bytes).unwrap_or(serde_json::Value::Null))
+}
+
+mod endpoint_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_locked_login_hides_the_right_password() {
+        let Some(pool) = test_pool() else { return };
+        let attempts = Arc::new(InMemoryLoginAttempts::new(policy()));
+        let username = receptionist(&pool);
+
+        for _ in 0..3 {
+            let (status, _, _) = login(&pool, &attempts, &username, "wrong-password-1").await;
+            assert_eq!(status, StatusCode::UNAUTHORIZED);
+        }
+
+        // The right password gets the same refusal while locked
+        let (status, retry_after, body) = login(&pool, &attempts, &username, PASSWORD).await;
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(body["code"], "RATE_LIMITED");
+        assert!(retry_after.is_some_and(|secs| secs > 0 && secs <= 900), "{:?}", retry_after);
+        let (status, _, wrong) = login(&pool, &attempts, &username, "wrong-password-1").await;
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(wrong, body);
+
+        // The client IP is locked too, so other accounts wait as well
+        let (status, _, _) = login(&pool, &attempts, &receptionist(&pool), PASSWORD).await;
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    #[tokio::test]
+    async fn test_successful_login_resets_failures() {
+        let Some(pool) = test_pool() else { return };
+        let attempts = Arc::new(InMemoryLoginAttempts::new(policy()));
+        let username = receptionist(&pool);
+
+        for _ in 0..2 {
+            login(&pool, &attempts, &username, "wrong-password-1").await;
+        }
+        let (status, _, _) = login(&pool, &attempts, &username, PASSWORD).await;
+        assert_eq!(status, StatusCode::OK);
+
+        for _ in 0..2 {
+            login(&pool, &attempts, &username, "wrong-password-1").await;
+        }
+        let (status, _, _) = login(&pool, &attempts, &username, PASSWORD).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+}
@@ -0,0 +1,303 @@
+//! Maintenance ticket tests
+//!
+//! Tests for reporting room problems, requiring a ticket (or a reason that
+//! opens one) before a room goes under maintenance, and offering to return
+//! the room to available once its last ticket is resolved. The database
+//! tests need a migrated PostgreSQL database and only run when
+//! TEST_DATABASE_URL is set.
+
+use std::sync::Arc;
+
+use axum::body::Body;
+use axum::http::{header::AUTHORIZATION, header::CONTENT_TYPE, Method, Request, StatusCode};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use hotel_management_backend::api::public_bookings::PublicLookupLimits;
+use hotel_management_backend::api::{chat::ChatState, create_router, AppState};
+use hotel_management_backend::config::BodyLimits;
+use hotel_management_backend::db::{create_pool, DbPool};
+use hotel_management_backend::errors::AppError;
+use hotel_management_backend::models::{Room, RoomStatus, RoomType, TicketSeverity, TicketStatus, UserRole};
+use hotel_management_backend::scheduler::JobBoard;
+use hotel_management_backend::services::auth_service::{CreateUserRequest, LoginRequest, DEFAULT_ACCESS_TOKEN_LIFETIME};
+use hotel_management_backend::services::rate_limit_service::InMemoryLoginAttempts;
+use hotel_management_backend::services::room_service::RoomEdit;
+use hotel_management_backend::services::ticket_service::{
+    CreateTicketRequest, UpdateTicketRequest, MAX_TICKET_TITLE_LEN,
+};
+use hotel_management_backend::services::{
+    AuthService, ReadOnlyMode, RevocationCache, RoomService, SessionTracker, TokenIssuer, TicketService,
+};
+
+const JWT_SECRET: &str = "test-secret";
+
+mod validation_tests {
+    use super::*;
+
+    #[test]
+    fn test_titles_are_trimmed_and_limited() {
+        assert_eq!(TicketService::validate_title("  Leaking tap ").unwrap(), "Leaking tap");
+        assert!(TicketService::validate_title("   ").is_err());
+        assert!(TicketService::validate_title(&"a".repeat(MAX_TICKET_TITLE_LEN)).is_ok());
+        assert!(matches!(
+            TicketService::validate_title(&"a".repeat(MAX_TICKET_TITLE_LEN + 1)),
+            Err(AppError::ValidationError(_))
+        ));
+        assert_eq!(TicketService::validate_description(Some("  ")).unwrap(), None);
+    }
+
+    #[test]
+    fn test_requests_deserialize_with_defaults() {
+        let create: CreateTicketRequest =
+            serde_json::from_value(serde_json::json!({ "title": "Broken AC" })).unwrap();
+        assert_eq!(create.severity, TicketSeverity::Medium);
+
+        let update: UpdateTicketRequest =
+            serde_json::from_value(serde_json::json!({ "status": "in_progress" })).unwrap();
+        assert_eq!(update.status, Some(TicketStatus::InProgress));
+        assert!(!update.set_available);
+    }
+}
+
+fn test_pool() -> Option<DbPool> {
+    match std::env::var("TEST_DATABASE_URL") {
+        Ok(url) => Some(create_pool(&url)),
+        Err(_) => {
+            eprintln!("TEST_DATABASE_URL not set, skipping database test");
+            None
+        }
+    }
+}
+
+fn room(pool: &DbPool) -> Room {
+    let number = format!("T{}", &Uuid::new_v4().simple().to_string()[..8]);
+    RoomService::new(pool.clone()).create_room(&number, RoomType::DOUBLE).unwrap()
+}
+
+fn ticket(title: &str) -> CreateTicketRequest {
+    CreateTicketRequest {
+        title: title.to_string(),
+        description: None,
+        severity: TicketSeverity::High,
+    }
+}
+
+fn to_maintenance(reason: Option<&str>) -> RoomEdit {
+    RoomEdit {
+        status: Some(RoomStatus::Maintenance),
+        maintenance_reason: reason.map(str::to_string),
+        ..RoomEdit::default()
+    }
+}
+
+fn resolve(set_available: bool) -> UpdateTicketRequest {
+    UpdateTicketRequest {
+        status: Some(TicketStatus::Resolved),
+        set_available,
+        ..UpdateTicketRequest::default()
+    }
+}
+
+mod service_tests {
+    use super::*;
+
+    #[test]
+    fn test_maintenance_needs_a_ticket_or_a_reason() {
+        let Some(pool) = test_pool() else { return };
+        let rooms = RoomService::new(pool.clone());
+        let tickets = TicketService::new(pool.clone());
+
+        let bare = room(&pool);
+        assert!(matches!(
+            rooms.update_room(bare.id, to_maintenance(None), None),
+            Err(AppError::ValidationError(_))
+        ));
+        assert!(matches!(
+            rooms.update_room(bare.id, to_maintenance(Some("   ")), None),
+            Err(AppError::ValidationError(_))
+        ));
+        assert_eq!(rooms.get_room_by_id(bare.id).unwrap().status, RoomStatus::Available);
+
+        // A reason opens a ticket with it as the title
+        let updated = rooms.update_room(bare.id, to_maintenance(Some(" Burst pipe ")), None).unwrap();
+        assert_eq!(updated.status, RoomStatus::Maintenance);
+        let opened = tickets.list_room_tickets(bare.id, None).unwrap();
+        assert_eq!(opened.len(), 1);
+        assert_eq!(opened[0].title, "Burst pipe");
+        assert_eq!(opened[0].status, TicketStatus::Open);
+
+        // An existing open ticket is enough on its own
+        let reported = room(&pool);
+        tickets.create_ticket(reported.id, &ticket("Mould in bathroom"), None).unwrap();
+        rooms.update_room(reported.id, to_maintenance(None), None).unwrap();
+        assert_eq!(tickets.list_room_tickets(reported.id, None).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_resolving_the_last_ticket_offers_to_free_the_room() {
+        let Some(pool) = test_pool() else { return };
+        let room = room(&pool);
+        let rooms = RoomService::new(pool.clone());
+        let tickets = TicketService::new(pool.clone());
+        let first = tickets.create_ticket(room.id, &ticket("Broken window"), None).unwrap();
+        let second = tickets.create_ticket(room.id, &ticket("No hot water"), None).unwrap();
+        rooms.update_room(room.id, to_maintenance(None), None).unwrap();
+
+        // Another ticket is still open, so the room cannot be freed yet
+        assert!(matches!(
+            tickets.update_ticket(room.id, first.id, &resolve(true), None),
+            Err(AppError::ValidationError(_))
+        ));
+        let result = tickets.update_ticket(room.id, first.id, &resolve(false), None).unwrap();
+        assert_eq!(result.ticket.status, TicketStatus::Resolved);
+        assert!(result.ticket.resolved_at.is_some());
+        assert!(!result.can_set_available);
+
+        // The last one offers to, but leaves the room alone
+        let result = tickets.update_ticket(room.id, second.id, &resolve(false), None).unwrap();
+        assert!(result.can_set_available);
+        assert_eq!(result.room.status, RoomStatus::Maintenance);
+        assert_eq!(rooms.get_room_by_id(room.id).unwrap().status, RoomStatus::Maintenance);
+
+        // Asking for it frees the room
+        let result = tickets
+            .update_ticket(room.id, second.id, &UpdateTicketRequest { set_available: true, ..Default::default() }, None)
+            .unwrap();
+        assert_eq!(result.room.status, RoomStatus::Available);
+        assert!(!result.can_set_available);
+
+        // Reopening clears the resolution time
+        let reopened = tickets
+            .update_ticket(
+                room.id,
+                first.id,
+                &UpdateTicketRequest {
+                    status: Some(TicketStatus::Open),
+                    ..Default::default()
+                },
+                None,
+            )
+            .unwrap();
+        assert_eq!(reopened.ticket.resolved_at, None);
+    }
+
+    #[test]
+    fn test_tickets_belong_to_their_room_and_list_across_rooms() {
+        let Some(pool) = test_pool() else { return };
+        let room = room(&pool);
+        let other = self::room(&pool);
+        let tickets = TicketService::new(pool.clone());
+        let open = tickets.create_ticket(room.id, &ticket("Squeaky door"), None).unwrap();
+        let done = tickets.create_ticket(room.id, &ticket("Dead bulb"), None).unwrap();
+        tickets.update_ticket(room.id, done.id, &resolve(false), None).unwrap();
+
+        assert!(matches!(tickets.get_ticket(other.id, open.id), Err(AppError::NotFound(_))));
+        assert!(matches!(tickets.delete_ticket(other.id, open.id), Err(AppError::NotFound(_))));
+        assert!(matches!(
+            tickets.create_ticket(Uuid::new_v4(), &ticket("Nowhere"), None),
+            Err(AppError::NotFound(_))
+        ));
+
+        let open_list = tickets.list_tickets(Some(TicketStatus::Open)).unwrap();
+        let mine: Vec<_> = open_list.iter().filter(|t| t.ticket.room_id == room.id).collect();
+        assert_eq!(mine.len(), 1);
+        assert_eq!(mine[0].ticket.id, open.id);
+        assert_eq!(mine[0].room_number, room.number);
+
+        tickets.delete_ticket(room.id, open.id).unwrap();
+        assert_eq!(tickets.list_room_tickets(room.id, None).unwrap().len(), 1);
+    }
+}
+
+fn router(pool: DbPool) -> axum::Router {
+    let s3_config = aws_sdk_s3::config::Builder::new()
+        .region(aws_sdk_s3::config::Region::new("us-east-1"))
+        .behavior_version_latest()
+        .build();
+
+    create_router(AppState {
+        pool,
+        jwt_secret: JWT_SECRET.to_string(),
+        chat_state: Arc::new(ChatState::default()),
+        s3_client: aws_sdk_s3::Client::from_conf(s3_config),
+        read_only: Arc::new(ReadOnlyMode::new(Some(false))),
+        public_lookup: Arc::new(PublicLookupLimits::default()),
+        jobs: Arc::new(JobBoard::default()),
+        body_limits: BodyLimits::default(),
+        sessions: Arc::new(SessionTracker::default()),
+        access_token_lifetime: DEFAULT_ACCESS_TOKEN_LIFETIME,
+        login_attempts: Arc::new(InMemoryLoginAttempts::default()),
+        revocations: Arc::new(RevocationCache::default()),
+        trust_proxy_headers: false,
+        token_issuer: TokenIssuer::default(),
+    })
+}
+
+/// Login token of a new receptionist
+fn receptionist_token(pool: &DbPool) -> String {
+    let auth = AuthService::new(pool.clone(), JWT_SECRET.to_string());
+    let username = format!("ticket-{}", &Uuid::new_v4().simple().to_string()[..8]);
+    auth.create_user(&CreateUserRequest {
+        username: username.clone(),
+        password: "desk-password-1".to_string(),
+        role: UserRole::Receptionist,
+        must_change_password: false,
+    })
+    .unwrap();
+    auth.login(&LoginRequest {
+        username,
+        password: "desk-password-1".to_string(),
+    })
+    .unwrap()
+    .token
+}
+
+async fn send(pool: &DbPool, method: Method, uri: &str, token: &str, body: Option<serde_json::Value>) -> StatusCode {
+    let body = body.map_or(Body::empty(), |json| Body::from(json.to_string()));
+    let request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .header(CONTENT_TYPE, "application/json")
+        .body(body)
+        .unwrap();
+    router(pool.clone()).oneshot(request).await.unwrap().status()
+}
+
+mod endpoint_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_receptionist_needs_a_reason_and_cannot_manage_tickets() {
+        let Some(pool) = test_pool() else { return };
+        let room = room(&pool);
+        let token = receptionist_token(&pool);
+        let uri = format!("/rooms/{}", room.id);
+
+        let status = send(&pool, Method::PATCH, &uri, &token, Some(serde_json::json!({ "status": "maintenance" }))).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let status = send(
+            &pool,
+            Method::PATCH,
+            &uri,
+            &token,
+            Some(serde_json::json!({ "status": "maintenance", "reason": "Flooded floor" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let opened = TicketService::new(pool.clone()).list_room_tickets(room.id, None).unwrap();
+        assert_eq!(opened[0].title, "Flooded floor");
+        assert!(opened[0].reported_by.is_some());
+
+        for (method, uri) in [
+            (Method::GET, "/admin/tickets?status=open".to_string()),
+            (Method::GET, format!("/admin/rooms/{}/tickets", room.id)),
+            (Method::POST, format!("/admin/rooms/{}/tickets", room.id)),
+            (Method::PATCH, format!("/admin/rooms/{}/tickets/{}", room.id, opened[0].id)),
+        ] {
+            let status = send(&pool, method, &uri, &token, Some(serde_json::json!({ "title": "x" }))).await;
+            assert_eq!(status, StatusCode::FORBIDDEN);
+        }
+    }
+}
@@ -0,0 +1,104 @@
+//! Money tests
+//!
+//! VND amounts from clients must be non-negative whole dong under the upper
+//! bound, come back normalized to scale 0 and survive a JSON round trip.
+
+use std::str::FromStr;
+
+use bigdecimal::BigDecimal;
+
+use hotel_management_backend::errors::AppError;
+use hotel_management_backend::utils::money::{
+    format_vnd, signed_vnd_field, validate_vnd_amount, vnd_field, MAX_VND_AMOUNT,
+};
+
+fn dec(value: &str) -> BigDecimal {
+    BigDecimal::from_str(value).unwrap()
+}
+
+fn max() -> BigDecimal {
+    BigDecimal::from(MAX_VND_AMOUNT)
+}
+
+mod validate_tests {
+    use super::*;
+
+    #[test]
+    fn test_boundaries() {
+        assert_eq!(validate_vnd_amount(&dec("0"), &max()).unwrap(), dec("0"));
+        assert_eq!(validate_vnd_amount(&max(), &max()).unwrap(), max());
+        assert_eq!(
+            validate_vnd_amount(&(max() + BigDecimal::from(1)), &max()).unwrap_err(),
+            "must be at most 100,000,000,000 VND"
+        );
+        assert_eq!(validate_vnd_amount(&dec("-1"), &max()).unwrap_err(), "must not be negative");
+        assert_eq!(
+            validate_vnd_amount(&dec("0.5"), &max()).unwrap_err(),
+            "must be a whole VND amount"
+        );
+    }
+
+    #[test]
+    fn test_equivalent_spellings_normalize_to_scale_zero() {
+        for input in ["1500000", "1500000.00", "1.5E+6", "15e5"] {
+            let normalized = validate_vnd_amount(&dec(input), &max()).unwrap();
+            assert_eq!(normalized.to_string(), "1500000", "input {}", input);
+        }
+    }
+
+    #[test]
+    fn test_field_errors_name_the_field() {
+        match vnd_field("price", &dec("-5")).unwrap_err() {
+            AppError::FieldErrors(fields) => {
+                assert_eq!(fields.get("price").map(String::as_str), Some("must not be negative"));
+            }
+            other => panic!("Expected FieldErrors, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_signed_amount_keeps_refund_sign() {
+        assert_eq!(signed_vnd_field("amount", &dec("-200000.0")).unwrap().to_string(), "-200000");
+        assert!(signed_vnd_field("amount", &dec("-0.5")).is_err());
+        assert!(signed_vnd_field("amount", &-(max() + BigDecimal::from(1))).is_err());
+    }
+
+    #[test]
+    fn test_normalized_values_round_trip_through_json() {
+        let mut value: i64 = 1;
+        while value <= MAX_VND_AMOUNT {
+            for candidate in [value - 1, value, value * 7 / 3] {
+                let raw = dec(&format!("{}.000", candidate));
+                let Ok(normalized) = validate_vnd_amount(&raw, &max()) else {
+                    assert!(candidate > MAX_VND_AMOUNT);
+                    continue;
+                };
+                let json = serde_json::to_string(&normalized).unwrap();
+                let back: BigDecimal = serde_json::from_str(&json).unwrap();
+                assert_eq!(back, normalized);
+                assert_eq!(back.to_string(), candidate.to_string());
+            }
+            value *= 10;
+        }
+    }
+}
+
+mod format_tests {
+    use super::*;
+
+    #[test]
+    fn test_thousands_separators() {
+        assert_eq!(format_vnd(&dec("0")), "0 VND");
+        assert_eq!(format_vnd(&dec("999")), "999 VND");
+        assert_eq!(format_vnd(&dec("1000")), "1,000 VND");
+        assert_eq!(format_vnd(&dec("1500000")), "1,500,000 VND");
+        assert_eq!(format_vnd(&dec("1.5E+6")), "1,500,000 VND");
+    }
+
+    #[test]
+    fn test_negative_and_fractional_amounts() {
+        assert_eq!(format_vnd(&dec("-250000")), "-250,000 VND");
+        assert_eq!(format_vnd(&dec("33333.50")), "33,334 VND");
+        assert_eq!(format_vnd(&dec("-0.4")), "0 VND");
+    }
+}
@@ -0,0 +1,319 @@
+//! Forced password change tests
+//!
+//! Tests for employees who must change a password an admin chose: the flag
+//! set on creation and by an admin reset, carried in the login response and
+//! the token, the 403 PASSWORD_CHANGE_REQUIRED from every other endpoint,
+//! and clearing it by changing the password. They need a migrated
+//! PostgreSQL database and only run when TEST_DATABASE_URL is set.
+
+use std::sync::Arc;
+
+use axum::body::{to_bytes, Body};
+use axum::http::{header::AUTHORIZATION, Method, Request, StatusCode};
+use chrono::Utc;
+use diesel::prelude::*;
+use jsonwebtoken::{encode, EncodingKey, Header};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use hotel_management_backend::api::public_bookings::PublicLookupLimits;
+use hotel_management_backend::api::{chat::ChatState, create_router, AppState};
+use hotel_management_backend::config::BodyLimits;
+use hotel_management_backend::db::{create_pool, DbPool};
+use hotel_management_backend::models::UserRole;
+use hotel_management_backend::scheduler::JobBoard;
+use hotel_management_backend::schema::users;
+use hotel_management_backend::services::auth_service::{
+    Claims, CreateUserRequest, LoginRequest, LoginResponse, DEFAULT_ACCESS_TOKEN_LIFETIME, DEFAULT_JWT_ISSUER,
+    PASSWORD_CHANGE_REQUIRED_MESSAGE,
+};
+use hotel_management_backend::services::rate_limit_service::InMemoryLoginAttempts;
+use hotel_management_backend::services::{
+    AuthService, ChangePasswordRequest, ReadOnlyMode, RevocationCache, SessionTracker, TokenIssuer,
+};
+
+const JWT_SECRET: &str = "test-secret";
+const TEMPORARY_PASSWORD: &str = "temporary-password-1";
+const OWN_PASSWORD: &str = "own-password-1";
+
+fn test_pool() -> Option<DbPool> {
+    match std::env::var("TEST_DATABASE_URL") {
+        Ok(url) => Some(create_pool(&url)),
+        Err(_) => {
+            eprintln!("TEST_DATABASE_URL not set, skipping database test");
+            None
+        }
+    }
+}
+
+fn auth(pool: &DbPool) -> AuthService {
+    AuthService::new(pool.clone(), JWT_SECRET.to_string())
+}
+
+/// Username of a new receptionist
+fn receptionist(pool: &DbPool, must_change_password: bool) -> String {
+    let username = format!("temp-{}", &Uuid::new_v4().simple().to_string()[..8]);
+    auth(pool)
+        .create_user(&CreateUserRequest {
+            username: username.clone(),
+            password: TEMPORARY_PASSWORD.to_string(),
+            role: UserRole::Receptionist,
+            must_change_password,
+        })
+        .unwrap();
+    username
+}
+
+fn login(pool: &DbPool, username: &str, password: &str) -> LoginResponse {
+    auth(pool)
+        .login(&LoginRequest {
+            username: username.to_string(),
+            password: password.to_string(),
+        })
+        .unwrap()
+}
+
+fn change_to_own(pool: &DbPool, user_id: Uuid) {
+    auth(pool)
+        .change_password(
+            user_id,
+            &ChangePasswordRequest {
+                current_password: TEMPORARY_PASSWORD.to_string(),
+                new_password: OWN_PASSWORD.to_string(),
+            },
+        )
+        .unwrap();
+}
+
+mod service_tests {
+    use super::*;
+
+    #[test]
+    fn test_flag_is_carried_until_the_password_is_changed() {
+        let Some(pool) = test_pool() else { return };
+        let username = receptionist(&pool, true);
+
+        let first = login(&pool, &username, TEMPORARY_PASSWORD);
+        assert!(first.must_change_password);
+        assert!(auth(&pool).validate_token(&first.token).unwrap().must_change_password);
+
+        change_to_own(&pool, first.user.id);
+        let second = login(&pool, &username, OWN_PASSWORD);
+        assert!(!second.must_change_password);
+        assert!(!auth(&pool).validate_token(&second.token).unwrap().must_change_password);
+    }
+
+    #[test]
+    fn test_admin_reset_sets_the_flag() {
+        let Some(pool) = test_pool() else { return };
+        let username = receptionist(&pool, false);
+        let before = login(&pool, &username, TEMPORARY_PASSWORD);
+        assert!(!before.must_change_password);
+
+        auth(&pool)
+            .reset_password(before.user.id, "reset-password-1".to_string())
+            .unwrap();
+        let after = login(&pool, &username, "reset-password-1");
+        assert!(after.must_change_password);
+    }
+
+    #[test]
+    fn test_tokens_without_the_claim_still_decode() {
+        let Some(pool) = test_pool() else { return };
+        let username = receptionist(&pool, false);
+        let user_id = login(&pool, &username, TEMPORARY_PASSWORD).user.id;
+        let now = Utc::now().timestamp();
+        let old = serde_json::json!({
+            "sub": user_id,
+            "role": "receptionist",
+            "exp": now + 3600,
+            "iat": now,
+            "jti": Uuid::new_v4(),
+            "iss": DEFAULT_JWT_ISSUER,
+            "aud": DEFAULT_JWT_ISSUER,
+        });
+        let token = encode(&Header::default(), &old, &EncodingKey::from_secret(JWT_SECRET.as_bytes())).unwrap();
+
+        assert!(!auth(&pool).validate_token(&token).unwrap().must_change_password);
+    }
+}
+
+fn router(pool: DbPool) -> axum::Router {
+    let s3_config = aws_sdk_s3::config::Builder::new()
+        .region(aws_sdk_s3::config::Region::new("us-east-1"))
+        .behavior_version_latest()
+        .build();
+
+    create_router(AppState {
+        pool,
+        jwt_secret: JWT_SECRET.to_string(),
+        chat_state: Arc::new(ChatState::default()),
+        s3_client: aws_sdk_s3::Client::from_conf(s3_config),
+        read_only: Arc::new(ReadOnlyMode::new(Some(false))),
+        public_lookup: Arc::new(PublicLookupLimits::default()),
+        jobs: Arc::new(JobBoard::default()),
+        body_limits: BodyLimits::default(),
+        sessions: Arc::new(SessionTracker::default()),
+        access_token_lifetime: DEFAULT_ACCESS_TOKEN_LIFETIME,
+        login_attempts: Arc::new(InMemoryLoginAttempts::default()),
+        revocations: Arc::new(RevocationCache::default()),
+        trust_proxy_headers: false,
+        token_issuer: TokenIssuer::default(),
+    })
+}
+
+/// Token of the active admin, if the database has one; only one admin may exist
+fn admin_token(pool: &DbPool) -> Option<String> {
+    let mut conn = pool.get().unwrap();
+    let admin: Uuid = users::table
+        .filter(users::role.eq(UserRole::Admin))
+        .filter(users::deactivated_at.is_null())
+        .select(users::id)
+        .first(&mut conn)
+        .optional()
+        .unwrap()?;
+    let now = Utc::now().timestamp();
+    let claims = Claims {
+        sub: admin,
+        role: UserRole::Admin,
+        exp: now + 3600,
+        iat: now,
+        sid: None,
+        jti: Some(Uuid::new_v4()),
+        must_change_password: false,
+        iss: Some(DEFAULT_JWT_ISSUER.to_string()),
+        aud: Some(DEFAULT_JWT_ISSUER.to_string()),
+    };
+    Some(encode(&Header::default(), &claims, &EncodingKey::from_secret(JWT_SECRET.as_bytes())).unwrap())
+}
+
+async fn send(
+    pool: &DbPool,
+    method: Method,
+    uri: &str,
+    token: &str,
+    body: Option<serde_json::Value>,
+) -> (StatusCode, serde_json::Value) {
+    send_to(router(pool.clone()), method, uri, token, body).await
+}
+
+async fn send_to(
+    app: axum::Router,
+    method: Method,
+    uri: &str,
+    token: &str,
+    body: Option<serde_json::Value>,
+) -> (StatusCode, serde_json::Value) {
+    let builder = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(AUTHORIZATION, format!("Bearer {}", token));
+    let request = match body {
+        Some(body) => builder
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap(),
+        None => builder.body(Body::empty()).unwrap(),
+    };
+    let response = app.oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&bytes).unwrap_or(serde_json::Value::Null))
+}
+
+fn assert_change_required((status, body): (StatusCode, serde_json::Value)) {
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body["code"], "PASSWORD_CHANGE_REQUIRED");
+    assert_eq!(body["message"], PASSWORD_CHANGE_REQUIRED_MESSAGE);
+}
+
+mod endpoint_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_only_changing_the_password_is_allowed() {
+        let Some(pool) = test_pool() else { return };
+        let login = login(&pool, &receptionist(&pool, true), TEMPORARY_PASSWORD);
+
+        assert_change_required(send(&pool, Method::GET, "/bookings", &login.token, None).await);
+        assert_change_required(send(&pool, Method::GET, "/admin/employees", &login.token, None).await);
+
+        let (status, body) = send(&pool, Method::GET, "/auth/me", &login.token, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["id"], login.user.id.to_string());
+        let rename = serde_json::json!({ "full_name": "Renamed" });
+        assert_change_required(send(&pool, Method::PATCH, "/auth/me", &login.token, Some(rename)).await);
+
+        let change = serde_json::json!({
+            "current_password": TEMPORARY_PASSWORD,
+            "new_password": OWN_PASSWORD,
+        });
+        let (status, _) = send(&pool, Method::POST, "/auth/change-password", &login.token, Some(change)).await;
+        assert_eq!(status, StatusCode::OK);
+
+        // The account is read on every request, so the same token now works
+        let (status, _) = send(&pool, Method::GET, "/bookings", &login.token, None).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_allowed_routes_match_under_the_api_prefix() {
+        let Some(pool) = test_pool() else { return };
+        let login = login(&pool, &receptionist(&pool, true), TEMPORARY_PASSWORD);
+        // Mounted as main.rs serves it
+        let api = || axum::Router::new().nest("/api", router(pool.clone()));
+
+        let (status, body) = send_to(api(), Method::GET, "/api/auth/me", &login.token, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["id"], login.user.id.to_string());
+        assert_change_required(send_to(api(), Method::GET, "/api/bookings", &login.token, None).await);
+
+        let change = serde_json::json!({
+            "current_password": TEMPORARY_PASSWORD,
+            "new_password": OWN_PASSWORD,
+        });
+        let (status, _) = send_to(api(), Method::POST, "/api/auth/change-password", &login.token, Some(change)).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_admin_reset_locks_existing_tokens() {
+        let Some(pool) = test_pool() else { return };
+        let Some(admin) = admin_token(&pool) else {
+            eprintln!("No active admin, skipping");
+            return;
+        };
+        let login = login(&pool, &receptionist(&pool, false), TEMPORARY_PASSWORD);
+        let (status, _) = send(&pool, Method::GET, "/bookings", &login.token, None).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let uri = format!("/admin/employees/{}/reset-password", login.user.id);
+        let reset = serde_json::json!({ "new_password": "reset-password-1" });
+        let (status, _) = send(&pool, Method::POST, &uri, &admin, Some(reset)).await;
+        assert!(status.is_success(), "{}", status);
+
+        assert_change_required(send(&pool, Method::GET, "/bookings", &login.token, None).await);
+    }
+
+    #[tokio::test]
+    async fn test_admin_creates_an_employee_who_must_change_it() {
+        let Some(pool) = test_pool() else { return };
+        let Some(admin) = admin_token(&pool) else {
+            eprintln!("No active admin, skipping");
+            return;
+        };
+        let username = format!("temp-{}", &Uuid::new_v4().simple().to_string()[..8]);
+        let employee = serde_json::json!({
+            "username": username,
+            "password": TEMPORARY_PASSWORD,
+            "role": "cleaner",
+            "must_change_password": true,
+        });
+        let (status, _) = send(&pool, Method::POST, "/admin/employees", &admin, Some(employee)).await;
+        assert_eq!(status, StatusCode::CREATED);
+
+        let login = login(&pool, &username, TEMPORARY_PASSWORD);
+        assert!(login.must_change_password);
+        assert_change_required(send(&pool, Method::GET, "/cleaner/rooms", &login.token, None).await);
+    }
+}
@@ -0,0 +1,210 @@
+//! No-show tests
+//!
+//! Tests for no-show transitions, the automatic no-show charge amount and
+//! reviewing the charge. The review tests need a migrated PostgreSQL
+//! database and only run when TEST_DATABASE_URL is set.
+
+use std::str::FromStr;
+
+use bigdecimal::BigDecimal;
+use chrono::{Duration, NaiveDate, Utc};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use hotel_management_backend::db::{create_pool, DbPool};
+use hotel_management_backend::models::{Booking, BookingStatus, NoShowCharge, NoShowChargeStatus, RoomType, UserRole};
+use hotel_management_backend::schema::{bookings, no_show_charges};
+use hotel_management_backend::services::auth_service::CreateUserRequest;
+use hotel_management_backend::services::booking_service::StaffBookingRequest;
+use hotel_management_backend::services::no_show_service::{BASIS_DEPOSIT, BASIS_ONE_NIGHT};
+use hotel_management_backend::services::{AuthService, BookingService, NoShowService, PaymentService, RoomService};
+
+fn booking(nights: i64, price: &str) -> Booking {
+    let check_in_date = NaiveDate::from_ymd_opt(2025, 3, 10).unwrap();
+    Booking {
+        id: Uuid::new_v4(),
+        reference: "BK-20250310-NOSH".to_string(),
+        guest_name: "Nguyen Van A".to_string(),
+        room_id: Uuid::new_v4(),
+        check_in_date,
+        check_out_date: check_in_date + chrono::Duration::days(nights),
+        status: BookingStatus::NoShow,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+        created_by_user_id: None,
+        creation_source: "staff".to_string(),
+        price: BigDecimal::from_str(price).unwrap(),
+        group_reference: None,
+        checked_in_at: None,
+        checked_out_at: None,
+    }
+}
+
+mod no_show_transition_tests {
+    use super::*;
+
+    #[test]
+    fn test_upcoming_can_become_no_show() {
+        assert!(BookingStatus::Upcoming.can_transition_to(BookingStatus::NoShow));
+    }
+
+    #[test]
+    fn test_late_arrival_can_check_in() {
+        assert!(BookingStatus::NoShow.can_transition_to(BookingStatus::CheckedIn));
+    }
+
+    #[test]
+    fn test_no_show_cannot_be_cancelled_or_checked_out() {
+        assert!(!BookingStatus::NoShow.can_transition_to(BookingStatus::Cancelled));
+        assert!(!BookingStatus::NoShow.can_transition_to(BookingStatus::CheckedOut));
+    }
+
+    #[test]
+    fn test_no_show_does_not_block_availability() {
+        assert!(!BookingStatus::NoShow.blocks_availability());
+    }
+
+    #[test]
+    fn test_no_show_serialization() {
+        let json = serde_json::to_string(&BookingStatus::NoShow).unwrap();
+        assert_eq!(json, "\"no_show\"");
+        let json = serde_json::to_string(&NoShowChargeStatus::Pending).unwrap();
+        assert_eq!(json, "\"pending\"");
+    }
+}
+
+mod no_show_charge_tests {
+    use super::*;
+
+    #[test]
+    fn test_charge_is_one_night_without_deposit() {
+        let (amount, basis) = NoShowService::calculate_charge(&booking(3, "3000000"), &BigDecimal::from(0));
+        assert_eq!(amount, BigDecimal::from(1000000));
+        assert_eq!(basis, BASIS_ONE_NIGHT);
+    }
+
+    #[test]
+    fn test_one_night_charge_is_rounded_to_cents() {
+        let (amount, _) = NoShowService::calculate_charge(&booking(3, "100"), &BigDecimal::from(0));
+        assert_eq!(amount, BigDecimal::from_str("33.33").unwrap());
+    }
+
+    #[test]
+    fn test_deposit_is_forfeited_when_present() {
+        let deposit = BigDecimal::from(500000);
+        let (amount, basis) = NoShowService::calculate_charge(&booking(3, "3000000"), &deposit);
+        assert_eq!(amount, deposit);
+        assert_eq!(basis, BASIS_DEPOSIT);
+    }
+}
+
+fn test_pool() -> Option<DbPool> {
+    match std::env::var("TEST_DATABASE_URL") {
+        Ok(url) => Some(create_pool(&url)),
+        Err(_) => {
+            eprintln!("TEST_DATABASE_URL not set, skipping database test");
+            None
+        }
+    }
+}
+
+/// Id of a new staff member to review charges and check guests in
+fn reviewer(pool: &DbPool) -> Uuid {
+    AuthService::new(pool.clone(), "test-secret".to_string())
+        .create_user(&CreateUserRequest {
+            username: format!("noshow-{}", &Uuid::new_v4().simple().to_string()[..8]),
+            password: "review-password-1".to_string(),
+            role: UserRole::Receptionist,
+            must_change_password: false,
+        })
+        .unwrap()
+        .id
+}
+
+/// A three-night stay at 3,000,000 that started yesterday and became a
+/// no-show, with its pending charge
+fn no_show(pool: &DbPool) -> (Booking, NoShowCharge) {
+    let number = format!("N{}", &Uuid::new_v4().simple().to_string()[..8]);
+    let room = RoomService::new(pool.clone()).create_room(&number, RoomType::SINGLE).unwrap();
+    let bookings = BookingService::new(pool.clone());
+    let today = bookings.today().unwrap();
+    let booked = bookings
+        .create_booking(
+            &StaffBookingRequest {
+                guest_name: format!("No-show {}", number),
+                room_id: room.id,
+                check_in_date: today + Duration::days(30),
+                check_out_date: today + Duration::days(33),
+                price: Some(BigDecimal::from(3_000_000)),
+                allow_duplicate: false,
+                override_conflict: false,
+            },
+            None,
+        )
+        .unwrap();
+
+    let mut conn = pool.get().unwrap();
+    let booking: Booking = diesel::update(bookings::table.find(booked.id))
+        .set((
+            bookings::check_in_date.eq(today - Duration::days(1)),
+            bookings::check_out_date.eq(today + Duration::days(2)),
+            bookings::status.eq(BookingStatus::NoShow),
+        ))
+        .get_result(&mut conn)
+        .unwrap();
+    NoShowService::create_pending_charges(&mut conn, std::slice::from_ref(&booking)).unwrap();
+    let charge = no_show_charges::table
+        .filter(no_show_charges::booking_id.eq(booking.id))
+        .first(&mut conn)
+        .unwrap();
+    (booking, charge)
+}
+
+mod no_show_review_tests {
+    use super::*;
+
+    #[test]
+    fn test_late_check_in_after_confirmed_charge_keeps_the_booked_price() {
+        let Some(pool) = test_pool() else { return };
+        let (booking, charge) = no_show(&pool);
+        let admin = reviewer(&pool);
+        assert_eq!(charge.amount, BigDecimal::from(1_000_000));
+
+        NoShowService::new(pool.clone())
+            .confirm_charge(charge.id, admin, None, None)
+            .unwrap();
+        let payments = PaymentService::new(pool.clone());
+        let summary = payments.get_payment_summary(booking.id).unwrap();
+        assert_eq!(summary.total_price, BigDecimal::from(1_000_000));
+        assert_eq!(summary.remaining_balance, BigDecimal::from(0));
+
+        let checked_in = BookingService::new(pool.clone()).check_in(booking.id, admin).unwrap();
+        assert_eq!(checked_in.status, BookingStatus::CheckedIn);
+        assert_eq!(checked_in.price, BigDecimal::from(3_000_000));
+        // The collected charge counts towards the stay
+        let summary = payments.get_payment_summary(booking.id).unwrap();
+        assert_eq!(summary.total_price, BigDecimal::from(3_000_000));
+        assert_eq!(summary.total_paid, BigDecimal::from(1_000_000));
+        assert_eq!(summary.remaining_balance, BigDecimal::from(2_000_000));
+    }
+
+    #[test]
+    fn test_waived_charge_owes_nothing_until_the_guest_arrives() {
+        let Some(pool) = test_pool() else { return };
+        let (booking, charge) = no_show(&pool);
+        let admin = reviewer(&pool);
+
+        NoShowService::new(pool.clone())
+            .waive_charge(charge.id, admin, "Flight cancelled")
+            .unwrap();
+        let payments = PaymentService::new(pool.clone());
+        assert_eq!(payments.get_payment_summary(booking.id).unwrap().total_price, BigDecimal::from(0));
+
+        let checked_in = BookingService::new(pool.clone()).check_in(booking.id, admin).unwrap();
+        assert_eq!(checked_in.price, BigDecimal::from(3_000_000));
+        assert_eq!(
+            payments.get_payment_summary(booking.id).unwrap().remaining_balance,
+            BigDecimal::from(3_000_000)
+        );
+    }
+}
@@ -0,0 +1,224 @@
+//! Overstay tests
+//!
+//! Tests for counting days over, for the nights an overstay is billed to and
+//! for flagging the rooms of overstaying guests. The sweep tests need a migrated PostgreSQL database and only run
+//! when TEST_DATABASE_URL is set.
+
+use bigdecimal::BigDecimal;
+use chrono::{Duration, NaiveDate, TimeZone, Utc};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use hotel_management_backend::db::{create_pool, DbPool};
+use hotel_management_backend::models::{Booking, BookingStatus, RoomType, UserRole};
+use hotel_management_backend::schema::{bookings, rooms};
+use hotel_management_backend::services::auth_service::CreateUserRequest;
+use hotel_management_backend::services::booking_service::StaffBookingRequest;
+use hotel_management_backend::services::{AuthService, BookingService, RoomService};
+
+mod days_over_tests {
+    use super::*;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 8, day).unwrap()
+    }
+
+    #[test]
+    fn test_days_past_scheduled_check_out() {
+        assert_eq!(BookingService::days_over(date(10), date(11)), 1);
+        assert_eq!(BookingService::days_over(date(10), date(14)), 4);
+    }
+
+    #[test]
+    fn test_not_yet_due_is_zero() {
+        assert_eq!(BookingService::days_over(date(10), date(10)), 0);
+        assert_eq!(BookingService::days_over(date(10), date(8)), 0);
+    }
+}
+
+mod stay_end_tests {
+    use super::*;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 8, day).unwrap()
+    }
+
+    fn stay(checked_out_at: Option<chrono::DateTime<Utc>>) -> Booking {
+        Booking {
+            id: Uuid::new_v4(),
+            reference: "BK-20250801-OS01".to_string(),
+            guest_name: "Pham Van D".to_string(),
+            room_id: Uuid::new_v4(),
+            check_in_date: date(8),
+            check_out_date: date(10),
+            status: BookingStatus::Overstay,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            created_by_user_id: None,
+            creation_source: "staff".to_string(),
+            price: BigDecimal::from(2_000_000),
+            group_reference: None,
+            checked_in_at: Some(Utc.with_ymd_and_hms(2025, 8, 8, 7, 0, 0).unwrap()),
+            checked_out_at,
+        }
+    }
+
+    #[test]
+    fn test_still_in_house_runs_to_check_out_date() {
+        assert_eq!(stay(None).stay_end_date(chrono_tz::Asia::Ho_Chi_Minh), date(10));
+    }
+
+    #[test]
+    fn test_departure_day_is_read_in_hotel_time() {
+        // 01:00 on the 13th in the hotel, still the 12th in UTC
+        let left = Utc.with_ymd_and_hms(2025, 8, 12, 18, 0, 0).unwrap();
+        assert_eq!(stay(Some(left)).stay_end_date(chrono_tz::Asia::Ho_Chi_Minh), date(13));
+        assert_eq!(stay(Some(left)).stay_end_date(chrono_tz::UTC), date(12));
+    }
+
+    #[test]
+    fn test_same_day_departure_still_counts_one_night() {
+        let left = Utc.with_ymd_and_hms(2025, 8, 8, 5, 0, 0).unwrap();
+        assert_eq!(stay(Some(left)).stay_end_date(chrono_tz::Asia::Ho_Chi_Minh), date(9));
+    }
+}
+
+fn test_pool() -> Option<DbPool> {
+    match std::env::var("TEST_DATABASE_URL") {
+        Ok(url) => Some(create_pool(&url)),
+        Err(_) => {
+            eprintln!("TEST_DATABASE_URL not set, skipping database test");
+            None
+        }
+    }
+}
+
+mod sweep_tests {
+    use super::*;
+
+    #[test]
+    fn test_sweep_flags_room_and_check_out_bills_actual_nights() {
+        let Some(pool) = test_pool() else { return };
+        let suffix = &Uuid::new_v4().simple().to_string()[..8];
+        let room = RoomService::new(pool.clone())
+            .create_room(&format!("O{}", suffix), RoomType::SINGLE)
+            .unwrap();
+        let actor = AuthService::new(pool.clone(), "test-secret".to_string())
+            .create_user(&CreateUserRequest {
+                username: format!("overstay-{}", suffix),
+                password: "desk-password-1".to_string(),
+                role: UserRole::Receptionist,
+                must_change_password: false,
+            })
+            .unwrap()
+            .id;
+        let service = BookingService::new(pool.clone());
+        let today = service.today().unwrap();
+        let stay = service
+            .create_walk_in(
+                &StaffBookingRequest {
+                    guest_name: format!("Overstay {}", suffix),
+                    room_id: room.id,
+                    check_in_date: today,
+                    check_out_date: today + Duration::days(1),
+                    price: None,
+                    allow_duplicate: false,
+                    override_conflict: false,
+                },
+                actor,
+            )
+            .unwrap();
+        assert!(stay.booking.checked_in_at.is_some());
+
+        // The guest arrived three days ago and should have left yesterday
+        let mut conn = pool.get().unwrap();
+        diesel::update(bookings::table.find(stay.booking.id))
+            .set((
+                bookings::check_in_date.eq(today - Duration::days(3)),
+                bookings::check_out_date.eq(today - Duration::days(1)),
+                bookings::price.eq(&room.price * BigDecimal::from(2)),
+            ))
+            .execute(&mut conn)
+            .unwrap();
+
+        let sweep = service.handle_stale_bookings(&mut conn).unwrap();
+        assert!(sweep.overstay_room_ids.contains(&room.id));
+        assert!(sweep.overstays >= 1);
+
+        let overstays = service.list_overstay_rooms().unwrap();
+        let flagged = overstays.iter().find(|o| o.room_id == room.id).unwrap();
+        assert_eq!(flagged.booking_id, stay.booking.id);
+        assert_eq!(flagged.room_number, room.number);
+        assert_eq!(flagged.scheduled_check_out, today - Duration::days(1));
+        assert_eq!(flagged.days_over, 1);
+
+        let checked_out = service.check_out(stay.booking.id, false, actor).unwrap();
+        assert_eq!(checked_out.status, BookingStatus::CheckedOut);
+        assert!(checked_out.checked_out_at.is_some());
+        assert_eq!(checked_out.checked_in_at, stay.booking.checked_in_at);
+        let nights = (checked_out.check_out_date - checked_out.check_in_date).num_days();
+        assert!(nights >= 3);
+        assert_eq!(checked_out.price, &room.price * BigDecimal::from(nights));
+        assert!(!service
+            .list_overstay_rooms()
+            .unwrap()
+            .iter()
+            .any(|o| o.room_id == room.id));
+    }
+
+    #[test]
+    fn test_on_time_check_out_keeps_the_negotiated_price() {
+        let Some(pool) = test_pool() else { return };
+        let suffix = &Uuid::new_v4().simple().to_string()[..8];
+        let room = RoomService::new(pool.clone())
+            .create_room(&format!("N{}", suffix), RoomType::SINGLE)
+            .unwrap();
+        let actor = AuthService::new(pool.clone(), "test-secret".to_string())
+            .create_user(&CreateUserRequest {
+                username: format!("negotiated-{}", suffix),
+                password: "desk-password-1".to_string(),
+                role: UserRole::Receptionist,
+                must_change_password: false,
+            })
+            .unwrap()
+            .id;
+        let service = BookingService::new(pool.clone());
+        let today = service.today().unwrap();
+        let negotiated = BigDecimal::from(123_000);
+        let stay = service
+            .create_walk_in(
+                &StaffBookingRequest {
+                    guest_name: format!("Negotiated {}", suffix),
+                    room_id: room.id,
+                    check_in_date: today,
+                    check_out_date: today + Duration::days(1),
+                    price: Some(negotiated.clone()),
+                    allow_duplicate: false,
+                    override_conflict: false,
+                },
+                actor,
+            )
+            .unwrap();
+        assert_eq!(stay.booking.price, negotiated);
+
+        // The guest arrived yesterday and leaves today as booked, while the
+        // room's list price has gone up in the meantime
+        let mut conn = pool.get().unwrap();
+        diesel::update(bookings::table.find(stay.booking.id))
+            .set((
+                bookings::check_in_date.eq(today - Duration::days(1)),
+                bookings::check_out_date.eq(today),
+            ))
+            .execute(&mut conn)
+            .unwrap();
+        diesel::update(rooms::table.find(room.id))
+            .set(rooms::price.eq(&room.price * BigDecimal::from(3)))
+            .execute(&mut conn)
+            .unwrap();
+
+        let checked_out = service.check_out(stay.booking.id, false, actor).unwrap();
+        assert_eq!(checked_out.status, BookingStatus::CheckedOut);
+        assert_eq!(checked_out.check_out_date, today);
+        assert_eq!(checked_out.price, negotiated);
+    }
+}
@@ -0,0 +1,220 @@
+//! Password change tests
+//!
+//! Tests for users changing their own password: checking the current
+//! password, the creation policy for the new one, refusing the same
+//! password, and revoking refresh tokens afterwards. They need a migrated
+//! PostgreSQL database and only run when TEST_DATABASE_URL is set.
+
+use std::sync::Arc;
+
+use axum::body::{to_bytes, Body};
+use axum::http::{header::AUTHORIZATION, Method, Request, StatusCode};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use hotel_management_backend::api::public_bookings::PublicLookupLimits;
+use hotel_management_backend::api::{chat::ChatState, create_router, AppState};
+use hotel_management_backend::config::BodyLimits;
+use hotel_management_backend::db::{create_pool, DbPool};
+use hotel_management_backend::errors::AppError;
+use hotel_management_backend::models::UserRole;
+use hotel_management_backend::scheduler::JobBoard;
+use hotel_management_backend::services::auth_service::{
+    CreateUserRequest, GuestRegisterRequest, LoginRequest, LoginResponse, DEFAULT_ACCESS_TOKEN_LIFETIME,
+};
+use hotel_management_backend::services::rate_limit_service::InMemoryLoginAttempts;
+use hotel_management_backend::services::{
+    AuthService, ChangePasswordRequest, ReadOnlyMode, RevocationCache, SessionTracker, TokenIssuer,
+};
+
+const JWT_SECRET: &str = "test-secret";
+const PASSWORD: &str = "desk-password-1";
+
+fn test_pool() -> Option<DbPool> {
+    match std::env::var("TEST_DATABASE_URL") {
+        Ok(url) => Some(create_pool(&url)),
+        Err(_) => {
+            eprintln!("TEST_DATABASE_URL not set, skipping database test");
+            None
+        }
+    }
+}
+
+/// Login of a new receptionist
+fn receptionist(auth: &AuthService) -> LoginResponse {
+    let username = format!("password-{}", &Uuid::new_v4().simple().to_string()[..8]);
+    auth.create_user(&CreateUserRequest {
+        username: username.clone(),
+        password: PASSWORD.to_string(),
+        role: UserRole::Receptionist,
+        must_change_password: false,
+    })
+    .unwrap();
+    auth.login(&LoginRequest {
+        username,
+        password: PASSWORD.to_string(),
+    })
+    .unwrap()
+}
+
+fn change(current_password: &str, new_password: &str) -> ChangePasswordRequest {
+    ChangePasswordRequest {
+        current_password: current_password.to_string(),
+        new_password: new_password.to_string(),
+    }
+}
+
+fn assert_validation(result: Result<(), AppError>, message: &str) {
+    let err = result.unwrap_err();
+    assert!(matches!(&err, AppError::ValidationError(m) if m == message), "{:?}", err);
+}
+
+mod service_tests {
+    use super::*;
+
+    #[test]
+    fn test_wrong_current_password_is_refused() {
+        let Some(pool) = test_pool() else { return };
+        let auth = AuthService::new(pool.clone(), JWT_SECRET.to_string());
+        let login = receptionist(&auth);
+
+        let err = auth
+            .change_password(login.user.id, &change("not-my-password-1", "new-password-2"))
+            .unwrap_err();
+        assert!(matches!(&err, AppError::Unauthorized(m) if m == "Current password is incorrect"), "{:?}", err);
+        // Nothing changed
+        auth.refresh(&login.refresh_token).unwrap();
+    }
+
+    #[test]
+    fn test_new_password_follows_the_creation_policy() {
+        let Some(pool) = test_pool() else { return };
+        let auth = AuthService::new(pool.clone(), JWT_SECRET.to_string());
+        let staff = receptionist(&auth);
+
+        assert_validation(
+            auth.change_password(staff.user.id, &change(PASSWORD, "short")),
+            "Password must be at least 8 characters",
+        );
+        assert_validation(
+            auth.change_password(staff.user.id, &change(PASSWORD, PASSWORD)),
+            "New password must be different from the current password",
+        );
+
+        // Guests keep their stricter rules
+        let email = format!("password-{}@example.com", &Uuid::new_v4().simple().to_string()[..8]);
+        let guest = auth
+            .register_guest(&GuestRegisterRequest {
+                email,
+                password: "guest-password-1".to_string(),
+                full_name: "Password Guest".to_string(),
+            })
+            .unwrap();
+        assert_validation(
+            auth.change_password(guest.user.id, &change("guest-password-1", "no-numbers-here")),
+            "Password must contain at least one number",
+        );
+        auth.change_password(guest.user.id, &change("guest-password-1", "guest-password-2"))
+            .unwrap();
+    }
+
+    #[test]
+    fn test_change_revokes_refresh_tokens() {
+        let Some(pool) = test_pool() else { return };
+        let auth = AuthService::new(pool.clone(), JWT_SECRET.to_string());
+        let login = receptionist(&auth);
+        let username = login.user.username.clone().unwrap();
+        let elsewhere = auth
+            .login(&LoginRequest {
+                username: username.clone(),
+                password: PASSWORD.to_string(),
+            })
+            .unwrap();
+
+        auth.change_password(login.user.id, &change(PASSWORD, "new-password-2"))
+            .unwrap();
+
+        for token in [&login.refresh_token, &elsewhere.refresh_token] {
+            let err = auth.refresh(token).unwrap_err();
+            assert!(matches!(err, AppError::Unauthorized(_)), "{:?}", err);
+        }
+        auth.login(&LoginRequest {
+            username,
+            password: "new-password-2".to_string(),
+        })
+        .unwrap();
+    }
+}
+
+fn router(pool: DbPool) -> axum::Router {
+    let s3_config = aws_sdk_s3::config::Builder::new()
+        .region(aws_sdk_s3::config::Region::new("us-east-1"))
+        .behavior_version_latest()
+        .build();
+
+    create_router(AppState {
+        pool,
+        jwt_secret: JWT_SECRET.to_string(),
+        chat_state: Arc::new(ChatState::default()),
+        s3_client: aws_sdk_s3::Client::from_conf(s3_config),
+        read_only: Arc::new(ReadOnlyMode::new(Some(false))),
+        public_lookup: Arc::new(PublicLookupLimits::default()),
+        jobs: Arc::new(JobBoard::default()),
+        body_limits: BodyLimits::default(),
+        sessions: Arc::new(SessionTracker::default()),
+        access_token_lifetime: DEFAULT_ACCESS_TOKEN_LIFETIME,
+        login_attempts: Arc::new(InMemoryLoginAttempts::default()),
+        revocations: Arc::new(RevocationCache::default()),
+        trust_proxy_headers: false,
+        token_issuer: TokenIssuer::default(),
+    })
+}
+
+async fn change_password(pool: &DbPool, token: &str, body: serde_json::Value) -> (StatusCode, serde_json::Value) {
+    let request = Request::builder()
+        .method(Method::POST)
+        .uri("/auth/change-password")
+        .header("content-type", "application/json")
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .body(Body::from(body.to_string()))
+        .unwrap();
+    let response = router(pool.clone()).oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&bytes).unwrap_or(serde_json::Value::Null))
+}
+
+mod endpoint_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_receptionist_changes_own_password() {
+        let Some(pool) = test_pool() else { return };
+        let login = receptionist(&AuthService::new(pool.clone(), JWT_SECRET.to_string()));
+
+        let (status, body) = change_password(
+            &pool,
+            &login.token,
+            serde_json::json!({ "current_password": "not-my-password-1", "new_password": "new-password-2" }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body["message"], "Current password is incorrect");
+
+        let (status, _) = change_password(
+            &pool,
+            &login.token,
+            serde_json::json!({ "current_password": PASSWORD, "new_password": "short" }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (status, _) = change_password(
+            &pool,
+            &login.token,
+            serde_json::json!({ "current_password": PASSWORD, "new_password": "new-password-2" }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+}
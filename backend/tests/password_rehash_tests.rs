@@ -0,0 +1,125 @@
+//! Password rehash tests
+//!
+//! Tests for upgrading stored password hashes that are weaker than the
+//! configured Argon2id cost when their owner logs in. They need a migrated
+//! PostgreSQL database and only run when TEST_DATABASE_URL is set.
+
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use hotel_management_backend::db::{create_pool, DbPool};
+use hotel_management_backend::models::UserRole;
+use hotel_management_backend::schema::users;
+use hotel_management_backend::services::auth_service::{
+    CreateUserRequest, GuestLoginRequest, GuestRegisterRequest, LoginRequest, PasswordHashing,
+};
+use hotel_management_backend::services::AuthService;
+
+const JWT_SECRET: &str = "test-secret";
+const PASSWORD: &str = "rehash-password-1";
+
+fn test_pool() -> Option<DbPool> {
+    match std::env::var("TEST_DATABASE_URL") {
+        Ok(url) => Some(create_pool(&url)),
+        Err(_) => {
+            eprintln!("TEST_DATABASE_URL not set, skipping database test");
+            None
+        }
+    }
+}
+
+fn auth(pool: &DbPool) -> AuthService {
+    AuthService::new(pool.clone(), JWT_SECRET.to_string())
+}
+
+/// Hash the cheapest cost argon2 allows, well below any configuration
+fn weak_hash() -> String {
+    let weakest = PasswordHashing {
+        memory_kib: 8,
+        iterations: 1,
+        parallelism: 1,
+    };
+    AuthService::hash_password_with(PASSWORD, &weakest).unwrap()
+}
+
+fn set_hash(pool: &DbPool, user_id: Uuid, hash: &str) {
+    diesel::update(users::table.find(user_id))
+        .set(users::password_hash.eq(hash))
+        .execute(&mut pool.get().unwrap())
+        .unwrap();
+}
+
+fn stored_hash(pool: &DbPool, user_id: Uuid) -> String {
+    users::table
+        .find(user_id)
+        .select(users::password_hash)
+        .first(&mut pool.get().unwrap())
+        .unwrap()
+}
+
+mod service_tests {
+    use super::*;
+
+    #[test]
+    fn test_staff_login_upgrades_a_weak_hash() {
+        let Some(pool) = test_pool() else { return };
+        let username = format!("rehash-{}", &Uuid::new_v4().simple().to_string()[..8]);
+        let user = auth(&pool)
+            .create_user(&CreateUserRequest {
+                username: username.clone(),
+                password: PASSWORD.to_string(),
+                role: UserRole::Receptionist,
+                must_change_password: false,
+            })
+            .unwrap();
+        let current = stored_hash(&pool, user.id);
+
+        // A hash at the configured cost is kept
+        let login = LoginRequest {
+            username,
+            password: PASSWORD.to_string(),
+        };
+        auth(&pool).login(&login).unwrap();
+        assert_eq!(stored_hash(&pool, user.id), current);
+
+        set_hash(&pool, user.id, &weak_hash());
+        auth(&pool).login(&login).unwrap();
+        let upgraded = stored_hash(&pool, user.id);
+        assert!(!AuthService::password_hashing().needs_rehash(&upgraded), "{}", upgraded);
+        assert!(AuthService::verify_password(PASSWORD, &upgraded).unwrap());
+
+        // A failed login leaves a weak hash alone
+        let weak = weak_hash();
+        set_hash(&pool, user.id, &weak);
+        let wrong = LoginRequest {
+            username: login.username.clone(),
+            password: "wrong-password-1".to_string(),
+        };
+        assert!(auth(&pool).login(&wrong).is_err());
+        assert_eq!(stored_hash(&pool, user.id), weak);
+    }
+
+    #[test]
+    fn test_guest_login_upgrades_a_weak_hash() {
+        let Some(pool) = test_pool() else { return };
+        let email = format!("rehash-{}@example.com", &Uuid::new_v4().simple().to_string()[..8]);
+        let guest = auth(&pool)
+            .register_guest(&GuestRegisterRequest {
+                email: email.clone(),
+                password: PASSWORD.to_string(),
+                full_name: "Rehash Guest".to_string(),
+            })
+            .unwrap();
+        set_hash(&pool, guest.user.id, &weak_hash());
+
+        auth(&pool)
+            .login_guest(&GuestLoginRequest {
+                email,
+                password: PASSWORD.to_string(),
+            })
+            .unwrap();
+        let upgraded = stored_hash(&pool, guest.user.id);
+        assert!(!AuthService::password_hashing().needs_rehash(&upgraded), "{}", upgraded);
+        assert!(AuthService::verify_password(PASSWORD, &upgraded).unwrap());
+    }
+}
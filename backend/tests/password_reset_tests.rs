@@ -0,0 +1,237 @@
+//! Password reset tests
+//!
+//! Tests for guests resetting a forgotten password with an emailed link:
+//! issuing single-use tokens, replacing and expiring them, using them up on
+//! login, and the enumeration-safe endpoints. They need a migrated
+//! PostgreSQL database and only run when TEST_DATABASE_URL is set.
+
+use std::sync::Arc;
+
+use axum::body::{to_bytes, Body};
+use axum::http::{Method, Request, StatusCode};
+use chrono::{Duration, Utc};
+use diesel::prelude::*;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use hotel_management_backend::api::public_bookings::PublicLookupLimits;
+use hotel_management_backend::api::{chat::ChatState, create_router, AppState};
+use hotel_management_backend::config::BodyLimits;
+use hotel_management_backend::db::{create_pool, DbPool};
+use hotel_management_backend::errors::AppError;
+use hotel_management_backend::scheduler::JobBoard;
+use hotel_management_backend::schema::{password_reset_tokens, users};
+use hotel_management_backend::services::auth_service::DEFAULT_ACCESS_TOKEN_LIFETIME;
+use hotel_management_backend::services::mailer::OutgoingEmail;
+use hotel_management_backend::services::password_reset_service::{
+    INVALID_RESET_TOKEN, PASSWORD_RESET_TOKEN_LIFETIME,
+};
+use hotel_management_backend::services::rate_limit_service::InMemoryLoginAttempts;
+use hotel_management_backend::services::{
+    AuthService, GuestLoginRequest, GuestRegisterRequest, PasswordResetService, ReadOnlyMode, RevocationCache, SessionTracker, TokenIssuer,
+};
+
+const JWT_SECRET: &str = "test-secret";
+const PASSWORD: &str = "guest-password-1";
+const NEW_PASSWORD: &str = "guest-password-2";
+
+fn test_pool() -> Option<DbPool> {
+    match std::env::var("TEST_DATABASE_URL") {
+        Ok(url) => Some(create_pool(&url)),
+        Err(_) => {
+            eprintln!("TEST_DATABASE_URL not set, skipping database test");
+            None
+        }
+    }
+}
+
+/// Email and id of a new guest
+fn guest(pool: &DbPool) -> (String, Uuid) {
+    let email = format!("reset-{}@example.com", &Uuid::new_v4().simple().to_string()[..8]);
+    let response = AuthService::new(pool.clone(), JWT_SECRET.to_string())
+        .register_guest(&GuestRegisterRequest {
+            email: email.clone(),
+            password: PASSWORD.to_string(),
+            full_name: "Reset Guest".to_string(),
+        })
+        .unwrap();
+    (email, response.user.id)
+}
+
+/// Token carried by the link in a reset email
+fn token_in(email: &OutgoingEmail) -> String {
+    let start = email.html.find("/reset-password?token=").unwrap() + "/reset-password?token=".len();
+    email.html[start..].chars().take_while(|c| c.is_ascii_hexdigit()).collect()
+}
+
+fn guest_login(pool: &DbPool, email: &str, password: &str) -> Result<(), AppError> {
+    AuthService::new(pool.clone(), JWT_SECRET.to_string())
+        .login_guest(&GuestLoginRequest {
+            email: email.to_string(),
+            password: password.to_string(),
+        })
+        .map(|_| ())
+}
+
+fn assert_invalid_token(result: Result<(), AppError>) {
+    let err = result.unwrap_err();
+    assert!(matches!(&err, AppError::ValidationError(m) if m == INVALID_RESET_TOKEN), "{:?}", err);
+}
+
+mod service_tests {
+    use super::*;
+
+    #[test]
+    fn test_token_resets_the_password_once() {
+        let Some(pool) = test_pool() else { return };
+        let resets = PasswordResetService::new(pool.clone());
+        let (email, id) = guest(&pool);
+
+        let outgoing = resets.request_reset(&email.to_uppercase(), Utc::now()).unwrap().unwrap();
+        assert_eq!(outgoing.to, email);
+        let token = token_in(&outgoing);
+        assert_eq!(token.len(), 64);
+
+        // Only the hash is stored
+        let mut conn = pool.get().unwrap();
+        let (hash, expires_at): (String, chrono::DateTime<Utc>) = password_reset_tokens::table
+            .filter(password_reset_tokens::user_id.eq(id))
+            .select((password_reset_tokens::token_hash, password_reset_tokens::expires_at))
+            .first(&mut conn)
+            .unwrap();
+        assert_ne!(hash, token);
+        assert!(expires_at <= Utc::now() + PASSWORD_RESET_TOKEN_LIFETIME);
+
+        // A weak password does not use the token up
+        let err = resets.reset_password(&token, "weak", Utc::now()).unwrap_err();
+        assert!(matches!(err, AppError::ValidationError(_)), "{:?}", err);
+
+        resets.reset_password(&token, NEW_PASSWORD, Utc::now()).unwrap();
+        guest_login(&pool, &email, NEW_PASSWORD).unwrap();
+        assert!(guest_login(&pool, &email, PASSWORD).is_err());
+        assert_invalid_token(resets.reset_password(&token, "guest-password-3", Utc::now()));
+    }
+
+    #[test]
+    fn test_replaced_expired_and_unknown_tokens_are_refused() {
+        let Some(pool) = test_pool() else { return };
+        let resets = PasswordResetService::new(pool.clone());
+        let (email, _) = guest(&pool);
+
+        let first = token_in(&resets.request_reset(&email, Utc::now()).unwrap().unwrap());
+        let second = token_in(&resets.request_reset(&email, Utc::now()).unwrap().unwrap());
+        assert_invalid_token(resets.reset_password(&first, NEW_PASSWORD, Utc::now()));
+
+        let later = Utc::now() + PASSWORD_RESET_TOKEN_LIFETIME + Duration::minutes(1);
+        assert_invalid_token(resets.reset_password(&second, NEW_PASSWORD, later));
+        assert_invalid_token(resets.reset_password("not-a-reset-token", NEW_PASSWORD, Utc::now()));
+
+        // Nobody to email
+        assert!(resets
+            .request_reset(&format!("nobody-{}", email), Utc::now())
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_login_and_deactivation_void_the_token() {
+        let Some(pool) = test_pool() else { return };
+        let resets = PasswordResetService::new(pool.clone());
+        let (email, id) = guest(&pool);
+
+        // The guest remembered their password after all
+        let token = token_in(&resets.request_reset(&email, Utc::now()).unwrap().unwrap());
+        guest_login(&pool, &email, PASSWORD).unwrap();
+        assert_invalid_token(resets.reset_password(&token, NEW_PASSWORD, Utc::now()));
+
+        let token = token_in(&resets.request_reset(&email, Utc::now()).unwrap().unwrap());
+        let mut conn = pool.get().unwrap();
+        diesel::update(users::table.find(id))
+            .set(users::deactivated_at.eq(Some(Utc::now())))
+            .execute(&mut conn)
+            .unwrap();
+        assert_invalid_token(resets.reset_password(&token, NEW_PASSWORD, Utc::now()));
+        assert!(resets.request_reset(&email, Utc::now()).unwrap().is_none());
+    }
+}
+
+fn router(pool: DbPool) -> axum::Router {
+    let s3_config = aws_sdk_s3::config::Builder::new()
+        .region(aws_sdk_s3::config::Region::new("us-east-1"))
+        .behavior_version_latest()
+        .build();
+
+    create_router(AppState {
+        pool,
+        jwt_secret: JWT_SECRET.to_string(),
+        chat_state: Arc::new(ChatState::default()),
+        s3_client: aws_sdk_s3::Client::from_conf(s3_config),
+        read_only: Arc::new(ReadOnlyMode::new(Some(false))),
+        public_lookup: Arc::new(PublicLookupLimits::default()),
+        jobs: Arc::new(JobBoard::default()),
+        body_limits: BodyLimits::default(),
+        sessions: Arc::new(SessionTracker::default()),
+        access_token_lifetime: DEFAULT_ACCESS_TOKEN_LIFETIME,
+        login_attempts: Arc::new(InMemoryLoginAttempts::default()),
+        revocations: Arc::new(RevocationCache::default()),
+        trust_proxy_headers: false,
+        token_issuer: TokenIssuer::default(),
+    })
+}
+
+async fn post(pool: &DbPool, uri: &str, body: serde_json::Value) -> (StatusCode, String) {
+    let request = Request::builder()
+        .method(Method::POST)
+        .uri(uri)
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap();
+    let response = router(pool.clone()).oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, String::from_utf8(bytes.to_vec()).unwrap())
+}
+
+mod endpoint_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_forgot_password_does_not_reveal_accounts() {
+        let Some(pool) = test_pool() else { return };
+        let (email, _) = guest(&pool);
+
+        let known = post(&pool, "/auth/forgot-password", serde_json::json!({ "email": email })).await;
+        let unknown = post(
+            &pool,
+            "/auth/forgot-password",
+            serde_json::json!({ "email": format!("nobody-{}", email) }),
+        )
+        .await;
+        assert_eq!(known.0, StatusCode::OK);
+        assert_eq!(known, unknown);
+
+        let (status, _) = post(&pool, "/auth/forgot-password", serde_json::json!({ "email": "nobody" })).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_reset_password_over_http() {
+        let Some(pool) = test_pool() else { return };
+        let (email, _) = guest(&pool);
+        let token = token_in(
+            &PasswordResetService::new(pool.clone())
+                .request_reset(&email, Utc::now())
+                .unwrap()
+                .unwrap(),
+        );
+
+        let body = serde_json::json!({ "token": token, "new_password": NEW_PASSWORD });
+        let (status, _) = post(&pool, "/auth/reset-password", body.clone()).await;
+        assert_eq!(status, StatusCode::OK);
+        guest_login(&pool, &email, NEW_PASSWORD).unwrap();
+
+        let (status, message) = post(&pool, "/auth/reset-password", body).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(message.contains(INVALID_RESET_TOKEN), "{}", message);
+    }
+}
@@ -0,0 +1,328 @@
+//! Pricing rule tests
+//!
+//! Tests for resolving which date-based rule prices a night when several
+//! overlap, summing a stay night by night, refusing ambiguous overlapping
+//! rules and pricing new bookings. The database tests need a migrated
+//! PostgreSQL database and only run when TEST_DATABASE_URL is set; they only
+//! create single-room rules so other tests' prices are unaffected.
+
+use std::str::FromStr;
+
+use bigdecimal::BigDecimal;
+use chrono::{Duration, NaiveDate, Utc};
+use uuid::Uuid;
+
+use hotel_management_backend::db::{create_pool, DbPool};
+use hotel_management_backend::errors::AppError;
+use hotel_management_backend::models::{PricingRule, Room, RoomStatus, RoomType};
+use hotel_management_backend::services::booking_service::StaffBookingRequest;
+use hotel_management_backend::services::pricing_service::PricingRuleRequest;
+use hotel_management_backend::services::{BookingService, PricingService, RoomService};
+
+fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(y, m, d).unwrap()
+}
+
+fn vnd(amount: i64) -> BigDecimal {
+    BigDecimal::from(amount)
+}
+
+/// Stand-in id of a seeded room type
+fn type_id(room_type: &RoomType) -> Uuid {
+    let position = RoomType::LEGACY.iter().position(|t| t == room_type).unwrap();
+    Uuid::from_u128(position as u128 + 1)
+}
+
+fn room(room_type: RoomType) -> Room {
+    Room {
+        room_type_id: type_id(&room_type),
+        id: Uuid::new_v4(),
+        number: "101".to_string(),
+        room_type,
+        status: RoomStatus::Available,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+        price: vnd(1_000_000),
+        assigned_cleaner_id: None,
+        description: None,
+        amenities: Vec::new(),
+        photo_urls: Vec::new(),
+        decommissioned_at: None,
+        floor: 1,
+        building: None,
+    }
+}
+
+/// Fixed-price rule for the nights `from..=to`
+fn rule(name: &str, from: NaiveDate, to: NaiveDate, price: i64, priority: i32) -> PricingRule {
+    PricingRule {
+        id: Uuid::new_v4(),
+        name: name.to_string(),
+        room_type_id: None,
+        room_id: None,
+        start_date: from,
+        end_date: to,
+        price: Some(vnd(price)),
+        percentage: None,
+        priority,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+    }
+}
+
+mod resolution_tests {
+    use super::*;
+
+    #[test]
+    fn test_room_price_applies_without_rules() {
+        let room = room(RoomType::DOUBLE);
+        assert_eq!(PricingService::nightly_price(&[], &room, date(2026, 2, 1)), vnd(1_000_000));
+        assert_eq!(
+            PricingService::stay_price(&[], &room, date(2026, 2, 1), date(2026, 2, 4)),
+            vnd(3_000_000)
+        );
+    }
+
+    #[test]
+    fn test_dates_are_inclusive_at_both_ends() {
+        let room = room(RoomType::DOUBLE);
+        let rules = [rule("Tet", date(2026, 2, 16), date(2026, 2, 18), 2_000_000, 0)];
+        assert_eq!(PricingService::nightly_price(&rules, &room, date(2026, 2, 15)), vnd(1_000_000));
+        assert_eq!(PricingService::nightly_price(&rules, &room, date(2026, 2, 16)), vnd(2_000_000));
+        assert_eq!(PricingService::nightly_price(&rules, &room, date(2026, 2, 18)), vnd(2_000_000));
+        assert_eq!(PricingService::nightly_price(&rules, &room, date(2026, 2, 19)), vnd(1_000_000));
+    }
+
+    #[test]
+    fn test_highest_priority_wins_on_overlapping_nights() {
+        let room = room(RoomType::DOUBLE);
+        let rules = [
+            rule("Spring", date(2026, 2, 1), date(2026, 2, 28), 1_200_000, 1),
+            rule("Tet", date(2026, 2, 16), date(2026, 2, 18), 2_000_000, 5),
+            rule("Low", date(2026, 2, 10), date(2026, 2, 20), 900_000, 0),
+        ];
+        let picked = |night| PricingService::resolve_rule(&rules, &room, night).map(|r| r.name.as_str());
+        assert_eq!(picked(date(2026, 2, 5)), Some("Spring"));
+        assert_eq!(picked(date(2026, 2, 12)), Some("Spring"));
+        assert_eq!(picked(date(2026, 2, 17)), Some("Tet"));
+        assert_eq!(picked(date(2026, 3, 1)), None);
+    }
+
+    #[test]
+    fn test_narrower_scope_breaks_a_priority_tie() {
+        let room = room(RoomType::SUITE);
+        let night = date(2026, 4, 30);
+        let all = rule("All rooms", night, night, 1_100_000, 3);
+        let suites = PricingRule {
+            room_type_id: Some(type_id(&RoomType::SUITE)),
+            ..rule("Suites", night, night, 1_300_000, 3)
+        };
+        let this_room = PricingRule {
+            room_id: Some(room.id),
+            ..rule("Room 101", night, night, 1_500_000, 3)
+        };
+        let doubles = PricingRule {
+            room_type_id: Some(type_id(&RoomType::DOUBLE)),
+            ..rule("Doubles", night, night, 5_000_000, 9)
+        };
+        let other_room = PricingRule {
+            room_id: Some(Uuid::new_v4()),
+            ..rule("Room 102", night, night, 5_000_000, 9)
+        };
+
+        let rules = [all.clone(), suites.clone(), doubles.clone(), other_room.clone()];
+        assert_eq!(PricingService::nightly_price(&rules, &room, night), vnd(1_300_000));
+        let rules = [all, suites, this_room, doubles, other_room];
+        assert_eq!(PricingService::nightly_price(&rules, &room, night), vnd(1_500_000));
+    }
+
+    #[test]
+    fn test_percentage_rules_adjust_the_room_price() {
+        let room = room(RoomType::DOUBLE);
+        let night = date(2026, 5, 2);
+        let weekend = PricingRule {
+            price: None,
+            percentage: Some(BigDecimal::from_str("12.5").unwrap()),
+            ..rule("Weekend", night, night, 0, 0)
+        };
+        assert_eq!(PricingService::nightly_price(std::slice::from_ref(&weekend), &room, night), vnd(1_125_000));
+
+        let discount = PricingRule {
+            percentage: Some(BigDecimal::from(-20)),
+            ..weekend
+        };
+        assert_eq!(PricingService::nightly_price(&[discount], &room, night), vnd(800_000));
+    }
+
+    #[test]
+    fn test_stay_sums_each_night() {
+        let room = room(RoomType::DOUBLE);
+        let rules = [rule("Tet", date(2026, 2, 17), date(2026, 2, 17), 2_500_000, 0)];
+        // Nights of the 15th, 16th, 17th and 18th; check-out day is not a night
+        assert_eq!(
+            PricingService::stay_price(&rules, &room, date(2026, 2, 15), date(2026, 2, 19)),
+            vnd(5_500_000)
+        );
+        assert_eq!(
+            PricingService::stay_price(&rules, &room, date(2026, 2, 18), date(2026, 2, 19)),
+            vnd(1_000_000)
+        );
+        // Leaving on the arrival day is charged one night
+        assert_eq!(
+            PricingService::stay_price(&rules, &room, date(2026, 2, 17), date(2026, 2, 17)),
+            vnd(2_500_000)
+        );
+    }
+}
+
+fn request(room_id: Uuid, from: NaiveDate, to: NaiveDate, priority: i32) -> PricingRuleRequest {
+    PricingRuleRequest {
+        name: "Festival".to_string(),
+        room_type_id: None,
+        room_id: Some(room_id),
+        start_date: from,
+        end_date: to,
+        price: Some(vnd(2_000_000)),
+        percentage: None,
+        priority,
+    }
+}
+
+mod validation_tests {
+    use super::*;
+
+    #[test]
+    fn test_rules_need_one_scope_one_modifier_and_ordered_dates() {
+        let ok = request(Uuid::new_v4(), date(2026, 1, 1), date(2026, 1, 1), 0);
+        assert!(PricingService::validate_rule(&ok).is_ok());
+
+        let both_scopes = PricingRuleRequest {
+            room_type_id: Some(type_id(&RoomType::SINGLE)),
+            ..ok.clone()
+        };
+        let reversed = PricingRuleRequest {
+            end_date: date(2025, 12, 31),
+            ..ok.clone()
+        };
+        let both_modifiers = PricingRuleRequest {
+            percentage: Some(vnd(10)),
+            ..ok.clone()
+        };
+        let neither = PricingRuleRequest {
+            price: None,
+            ..ok.clone()
+        };
+        let free = PricingRuleRequest {
+            price: Some(vnd(0)),
+            ..ok.clone()
+        };
+        let too_cheap = PricingRuleRequest {
+            price: None,
+            percentage: Some(vnd(-100)),
+            ..ok.clone()
+        };
+        let blank = PricingRuleRequest {
+            name: "  ".to_string(),
+            ..ok
+        };
+        for bad in [both_scopes, reversed, both_modifiers, neither, free, too_cheap, blank] {
+            assert!(
+                matches!(
+                    PricingService::validate_rule(&bad),
+                    Err(AppError::ValidationError(_) | AppError::FieldErrors(_))
+                ),
+                "{:?}",
+                bad
+            );
+        }
+    }
+}
+
+fn test_pool() -> Option<DbPool> {
+    match std::env::var("TEST_DATABASE_URL") {
+        Ok(url) => Some(create_pool(&url)),
+        Err(_) => {
+            eprintln!("TEST_DATABASE_URL not set, skipping database test");
+            None
+        }
+    }
+}
+
+fn stored_room(pool: &DbPool) -> Room {
+    let number = format!("P{}", &Uuid::new_v4().simple().to_string()[..8]);
+    RoomService::new(pool.clone()).create_room(&number, RoomType::DOUBLE).unwrap()
+}
+
+mod service_tests {
+    use super::*;
+
+    #[test]
+    fn test_overlapping_rules_need_different_priorities() {
+        let Some(pool) = test_pool() else { return };
+        let room = stored_room(&pool);
+        let service = PricingService::new(pool.clone());
+        let from = date(2030, 2, 1);
+
+        let first = service.create_rule(&request(room.id, from, from + Duration::days(6), 0)).unwrap();
+        assert!(matches!(
+            service.create_rule(&request(room.id, from + Duration::days(6), from + Duration::days(9), 0)),
+            Err(AppError::Conflict(_))
+        ));
+        // Adjacent dates or another priority are fine
+        service
+            .create_rule(&request(room.id, from + Duration::days(7), from + Duration::days(9), 0))
+            .unwrap();
+        let top = service
+            .create_rule(&request(room.id, from + Duration::days(3), from + Duration::days(4), 1))
+            .unwrap();
+
+        // A rule may be replaced by itself, but not moved onto a same-priority neighbour
+        service.update_rule(first.id, &request(room.id, from, from + Duration::days(5), 0)).unwrap();
+        assert!(matches!(
+            service.update_rule(top.id, &request(room.id, from, from, 0)),
+            Err(AppError::Conflict(_))
+        ));
+
+        assert!(matches!(
+            service.create_rule(&request(Uuid::new_v4(), from, from, 0)),
+            Err(AppError::NotFound(_))
+        ));
+        service.delete_rule(first.id).unwrap();
+        assert!(matches!(service.get_rule(first.id), Err(AppError::NotFound(_))));
+    }
+
+    #[test]
+    fn test_bookings_are_priced_night_by_night() {
+        let Some(pool) = test_pool() else { return };
+        let room = stored_room(&pool);
+        let pricing = PricingService::new(pool.clone());
+        let check_in = Utc::now().date_naive() + Duration::days(30);
+        pricing
+            .create_rule(&PricingRuleRequest {
+                price: None,
+                percentage: Some(vnd(50)),
+                ..request(room.id, check_in + Duration::days(1), check_in + Duration::days(1), 0)
+            })
+            .unwrap();
+
+        assert_eq!(pricing.price_for_night(&room, check_in).unwrap(), room.price);
+        let busy_night = pricing.price_for_night(&room, check_in + Duration::days(1)).unwrap();
+        assert_eq!(busy_night, &room.price * BigDecimal::from_str("1.5").unwrap());
+
+        let booking = BookingService::new(pool.clone())
+            .create_booking(
+                &StaffBookingRequest {
+                    guest_name: format!("Pricing {}", room.number),
+                    room_id: room.id,
+                    check_in_date: check_in,
+                    check_out_date: check_in + Duration::days(3),
+                    price: None,
+                    allow_duplicate: false,
+                    override_conflict: false,
+                },
+                None,
+            )
+            .unwrap();
+        assert_eq!(booking.price, &room.price * BigDecimal::from(2) + busy_night);
+    }
+}
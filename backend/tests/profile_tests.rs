@@ -0,0 +1,248 @@
+//! Profile tests
+//!
+//! Tests for reading and editing the signed-in user's own profile at
+//! `/auth/me`. Guests may change their name and phone number, staff only
+//! their name. They need a migrated PostgreSQL database and only run when
+//! TEST_DATABASE_URL is set.
+
+use std::sync::Arc;
+
+use axum::body::{to_bytes, Body};
+use axum::http::{header::AUTHORIZATION, Method, Request, StatusCode};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use hotel_management_backend::api::public_bookings::PublicLookupLimits;
+use hotel_management_backend::api::{chat::ChatState, create_router, AppState};
+use hotel_management_backend::config::BodyLimits;
+use hotel_management_backend::db::{create_pool, DbPool};
+use hotel_management_backend::errors::AppError;
+use hotel_management_backend::models::{ProfileResponse, User, UserRole};
+use hotel_management_backend::scheduler::JobBoard;
+use hotel_management_backend::services::auth_service::{
+    CreateUserRequest, GuestRegisterRequest, LoginRequest, UpdateProfileRequest,
+    DEFAULT_ACCESS_TOKEN_LIFETIME,
+};
+use hotel_management_backend::services::rate_limit_service::InMemoryLoginAttempts;
+use hotel_management_backend::services::{
+    AuthService, ReadOnlyMode, RevocationCache, SessionTracker, TokenIssuer,
+};
+
+const JWT_SECRET: &str = "test-secret";
+const PASSWORD: &str = "Profile-password-1";
+
+fn test_pool() -> Option<DbPool> {
+    match std::env::var("TEST_DATABASE_URL") {
+        Ok(url) => Some(create_pool(&url)),
+        Err(_) => {
+            eprintln!("TEST_DATABASE_URL not set, skipping database test");
+            None
+        }
+    }
+}
+
+fn auth(pool: &DbPool) -> AuthService {
+    AuthService::new(pool.clone(), JWT_SECRET.to_string())
+}
+
+fn suffix() -> String {
+    Uuid::new_v4().simple().to_string()[..8].to_string()
+}
+
+/// Token and id of a new guest
+fn guest(pool: &DbPool) -> (String, Uuid) {
+    let response = auth(pool)
+        .register_guest(&GuestRegisterRequest {
+            email: format!("profile-{}@example.com", suffix()),
+            password: PASSWORD.to_string(),
+            full_name: "Profile Guest".to_string(),
+        })
+        .unwrap();
+    (response.token, response.user.id)
+}
+
+/// Token and id of a new receptionist
+fn receptionist(pool: &DbPool) -> (String, Uuid) {
+    let username = format!("profile-{}", suffix());
+    let user = auth(pool)
+        .create_user(&CreateUserRequest {
+            username: username.clone(),
+            password: PASSWORD.to_string(),
+            role: UserRole::Receptionist,
+            must_change_password: false,
+        })
+        .unwrap();
+    let token = auth(pool)
+        .login(&LoginRequest {
+            username,
+            password: PASSWORD.to_string(),
+        })
+        .unwrap()
+        .token;
+    (token, user.id)
+}
+
+fn update(full_name: Option<&str>, phone: Option<&str>) -> UpdateProfileRequest {
+    UpdateProfileRequest {
+        full_name: full_name.map(str::to_string),
+        phone: phone.map(str::to_string),
+    }
+}
+
+fn assert_validation(result: Result<User, AppError>) {
+    let err = result.unwrap_err();
+    assert!(matches!(err, AppError::ValidationError(_)), "{:?}", err);
+}
+
+mod service_tests {
+    use super::*;
+
+    #[test]
+    fn test_guest_updates_name_and_phone() {
+        let Some(pool) = test_pool() else { return };
+        let (_, id) = guest(&pool);
+
+        let user = auth(&pool)
+            .update_profile(id, &update(Some("  Renamed Guest "), Some("+84 90 123 4567")))
+            .unwrap();
+        assert_eq!(user.full_name.as_deref(), Some("Renamed Guest"));
+        assert_eq!(user.phone.as_deref(), Some("+84 90 123 4567"));
+
+        // Fields left out are kept and an empty phone clears it
+        let user = auth(&pool).update_profile(id, &update(None, Some(""))).unwrap();
+        assert_eq!(user.full_name.as_deref(), Some("Renamed Guest"));
+        assert_eq!(user.phone, None);
+
+        assert_validation(auth(&pool).update_profile(id, &update(None, Some("call me"))));
+        assert_validation(auth(&pool).update_profile(id, &update(Some("   "), None)));
+        assert_validation(auth(&pool).update_profile(id, &update(Some(&"x".repeat(101)), None)));
+    }
+
+    #[test]
+    fn test_staff_may_only_update_their_name() {
+        let Some(pool) = test_pool() else { return };
+        let (_, id) = receptionist(&pool);
+
+        let user = auth(&pool).update_profile(id, &update(Some("Front Desk"), None)).unwrap();
+        assert_eq!(user.full_name.as_deref(), Some("Front Desk"));
+
+        let err = auth(&pool)
+            .update_profile(id, &update(Some("Other Name"), Some("0901234567")))
+            .unwrap_err();
+        assert!(matches!(err, AppError::Forbidden(_)), "{:?}", err);
+        let user = auth(&pool).get_user_by_id(id).unwrap();
+        assert_eq!(user.full_name.as_deref(), Some("Front Desk"));
+        assert_eq!(user.phone, None);
+    }
+
+    #[test]
+    fn test_profile_shows_only_the_role_specific_fields() {
+        let Some(pool) = test_pool() else { return };
+        let (_, guest_id) = guest(&pool);
+        let (_, staff_id) = receptionist(&pool);
+
+        let profile = ProfileResponse::from(auth(&pool).get_user_by_id(guest_id).unwrap());
+        let json = serde_json::to_value(&profile).unwrap();
+        assert_eq!(json["full_name"], "Profile Guest");
+        assert!(json["email"].as_str().unwrap().starts_with("profile-"));
+        assert_eq!(json["deactivated"], false);
+        assert!(json.get("username").is_none());
+        assert!(json.get("password_hash").is_none());
+
+        let profile = ProfileResponse::from(auth(&pool).get_user_by_id(staff_id).unwrap());
+        let json = serde_json::to_value(&profile).unwrap();
+        assert!(json["username"].as_str().unwrap().starts_with("profile-"));
+        assert!(json.get("id_number").is_none());
+        assert!(json.get("password_hash").is_none());
+    }
+}
+
+fn router(pool: DbPool) -> axum::Router {
+    let s3_config = aws_sdk_s3::config::Builder::new()
+        .region(aws_sdk_s3::config::Region::new("us-east-1"))
+        .behavior_version_latest()
+        .build();
+
+    create_router(AppState {
+        pool,
+        jwt_secret: JWT_SECRET.to_string(),
+        chat_state: Arc::new(ChatState::default()),
+        s3_client: aws_sdk_s3::Client::from_conf(s3_config),
+        read_only: Arc::new(ReadOnlyMode::new(Some(false))),
+        public_lookup: Arc::new(PublicLookupLimits::default()),
+        jobs: Arc::new(JobBoard::default()),
+        body_limits: BodyLimits::default(),
+        sessions: Arc::new(SessionTracker::default()),
+        access_token_lifetime: DEFAULT_ACCESS_TOKEN_LIFETIME,
+        login_attempts: Arc::new(InMemoryLoginAttempts::default()),
+        revocations: Arc::new(RevocationCache::default()),
+        trust_proxy_headers: false,
+        token_issuer: TokenIssuer::default(),
+    })
+}
+
+async fn send(
+    pool: &DbPool,
+    method: Method,
+    token: &str,
+    body: Option<serde_json::Value>,
+) -> (StatusCode, serde_json::Value) {
+    let builder = Request::builder()
+        .method(method)
+        .uri("/auth/me")
+        .header(AUTHORIZATION, format!("Bearer {}", token));
+    let request = match body {
+        Some(body) => builder
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap(),
+        None => builder.body(Body::empty()).unwrap(),
+    };
+    let response = router(pool.clone()).oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&bytes).unwrap_or(serde_json::Value::Null))
+}
+
+mod endpoint_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_guest_reads_and_edits_their_profile() {
+        let Some(pool) = test_pool() else { return };
+        let (token, id) = guest(&pool);
+
+        let (status, body) = send(&pool, Method::GET, &token, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["id"], id.to_string());
+        assert_eq!(body["role"], "guest");
+        assert_eq!(body["full_name"], "Profile Guest");
+
+        let change = serde_json::json!({ "full_name": "Edited Guest", "phone": "0901234567" });
+        let (status, body) = send(&pool, Method::PATCH, &token, Some(change)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["full_name"], "Edited Guest");
+        assert_eq!(body["phone"], "0901234567");
+
+        // The role is not the guest's to change and is ignored
+        let change = serde_json::json!({ "role": "admin" });
+        let (status, body) = send(&pool, Method::PATCH, &token, Some(change)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["role"], "guest");
+    }
+
+    #[tokio::test]
+    async fn test_staff_cannot_set_a_phone_number() {
+        let Some(pool) = test_pool() else { return };
+        let (token, _) = receptionist(&pool);
+
+        let change = serde_json::json!({ "full_name": "Desk Clerk" });
+        let (status, body) = send(&pool, Method::PATCH, &token, Some(change)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["full_name"], "Desk Clerk");
+
+        let change = serde_json::json!({ "phone": "0901234567" });
+        let (status, _) = send(&pool, Method::PATCH, &token, Some(change)).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+    }
+}
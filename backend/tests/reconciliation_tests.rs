@@ -0,0 +1,137 @@
+//! Cash reconciliation tests
+//!
+//! Tests for shift windows, grouping payments by method and the discrepancy
+//! threshold used to notify admins.
+
+use std::str::FromStr;
+
+use bigdecimal::BigDecimal;
+use chrono::{NaiveDate, TimeZone, Utc};
+use chrono_tz::Tz;
+
+use hotel_management_backend::models::{PaymentType, Shift};
+use hotel_management_backend::services::reconciliation_service::{
+    exceeds_threshold, shift_window, summarize_payments, VoidedPayment,
+};
+
+fn vnd(amount: i64) -> BigDecimal {
+    BigDecimal::from(amount)
+}
+
+fn hanoi() -> Tz {
+    chrono_tz::Asia::Ho_Chi_Minh
+}
+
+fn date() -> NaiveDate {
+    NaiveDate::from_ymd_opt(2025, 4, 1).unwrap()
+}
+
+mod shift_window_tests {
+    use super::*;
+
+    #[test]
+    fn test_whole_day_uses_local_midnights() {
+        let (start, end) = shift_window(date(), None, hanoi());
+        assert_eq!(start, Utc.with_ymd_and_hms(2025, 3, 31, 17, 0, 0).unwrap());
+        assert_eq!(end, Utc.with_ymd_and_hms(2025, 4, 1, 17, 0, 0).unwrap());
+    }
+
+    #[test]
+    fn test_morning_shift() {
+        let (start, end) = shift_window(date(), Some(Shift::Morning), hanoi());
+        assert_eq!(start, Utc.with_ymd_and_hms(2025, 3, 31, 23, 0, 0).unwrap());
+        assert_eq!(end, Utc.with_ymd_and_hms(2025, 4, 1, 7, 0, 0).unwrap());
+    }
+
+    #[test]
+    fn test_night_shift_runs_into_next_day() {
+        let (start, end) = shift_window(date(), Some(Shift::Night), Tz::UTC);
+        assert_eq!(start, Utc.with_ymd_and_hms(2025, 4, 1, 22, 0, 0).unwrap());
+        assert_eq!(end, Utc.with_ymd_and_hms(2025, 4, 2, 6, 0, 0).unwrap());
+    }
+
+    #[test]
+    fn test_shift_parsing() {
+        assert_eq!(Shift::from_str("afternoon").unwrap(), Shift::Afternoon);
+        assert!(Shift::from_str("evening").is_err());
+    }
+}
+
+mod summary_tests {
+    use super::*;
+
+    #[test]
+    fn test_groups_payments_and_refunds_by_method() {
+        let payments = vec![
+            ("cash".to_string(), PaymentType::Deposit, vnd(500_000)),
+            ("cash".to_string(), PaymentType::Full, vnd(1_000_000)),
+            ("cash".to_string(), PaymentType::Refund, vnd(-200_000)),
+            ("card".to_string(), PaymentType::Full, vnd(2_500_000)),
+        ];
+
+        let (methods, voids) = summarize_payments(&payments, &[]);
+
+        let cash = methods.iter().find(|m| m.method == "cash").unwrap();
+        assert_eq!(cash.payment_count, 2);
+        assert_eq!(cash.payments_total, vnd(1_500_000));
+        assert_eq!(cash.refund_count, 1);
+        assert_eq!(cash.refunds_total, vnd(-200_000));
+        assert_eq!(cash.net_total, vnd(1_300_000));
+
+        let card = methods.iter().find(|m| m.method == "card").unwrap();
+        assert_eq!(card.net_total, vnd(2_500_000));
+        assert!(voids.is_empty());
+    }
+
+    #[test]
+    fn test_all_methods_listed_even_without_activity() {
+        let (methods, _) = summarize_payments(&[], &[]);
+        let names: Vec<&str> = methods.iter().map(|m| m.method.as_str()).collect();
+        assert_eq!(names, vec!["cash", "card", "bank_transfer", "other"]);
+        assert!(methods.iter().all(|m| m.net_total == vnd(0)));
+    }
+
+    #[test]
+    fn test_voids_are_reported_separately() {
+        let payments = vec![("cash".to_string(), PaymentType::Full, vnd(1_000_000))];
+        let voids = vec![
+            VoidedPayment { payment_method: "cash".to_string(), amount: vnd(300_000) },
+            VoidedPayment { payment_method: "cash".to_string(), amount: vnd(200_000) },
+        ];
+
+        let (methods, voids) = summarize_payments(&payments, &voids);
+
+        let cash = methods.iter().find(|m| m.method == "cash").unwrap();
+        assert_eq!(cash.net_total, vnd(1_000_000));
+        assert_eq!(voids.len(), 1);
+        assert_eq!(voids[0].void_count, 2);
+        assert_eq!(voids[0].voided_total, vnd(500_000));
+    }
+
+    #[test]
+    fn test_void_parsed_from_audit_details() {
+        let details = r#"{"booking_id":"6c1d1f0e-2b0c-4c59-8d2a-1f6b6a3f8e11","amount":"750000","payment_type":"full","payment_method":"card","recorded_at":"2025-04-01T03:00:00Z"}"#;
+        let void: VoidedPayment = serde_json::from_str(details).unwrap();
+        assert_eq!(void.payment_method, "card");
+        assert_eq!(void.amount, vnd(750_000));
+    }
+}
+
+mod threshold_tests {
+    use super::*;
+
+    #[test]
+    fn test_shortage_and_overage_both_count() {
+        let threshold = vnd(100_000);
+        assert!(exceeds_threshold(&vnd(-150_000), &threshold));
+        assert!(exceeds_threshold(&vnd(150_000), &threshold));
+    }
+
+    #[test]
+    fn test_threshold_is_exclusive() {
+        let threshold = vnd(100_000);
+        assert!(!exceeds_threshold(&vnd(100_000), &threshold));
+        assert!(!exceeds_threshold(&vnd(-100_000), &threshold));
+        assert!(!exceeds_threshold(&vnd(0), &threshold));
+    }
+}
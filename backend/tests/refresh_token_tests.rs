@@ -0,0 +1,252 @@
+//! Refresh token tests
+//!
+//! Tests for keeping a staff login alive past its access token: rotating
+//! refresh tokens, refusing expired ones, revoking the whole family when a
+//! rotated token is reused, and logging out. They need a migrated PostgreSQL
+//! database and only run when TEST_DATABASE_URL is set.
+
+use std::sync::Arc;
+
+use axum::body::{to_bytes, Body};
+use axum::http::{header::AUTHORIZATION, Method, Request, StatusCode};
+use chrono::{Duration, Utc};
+use diesel::prelude::*;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use hotel_management_backend::api::public_bookings::PublicLookupLimits;
+use hotel_management_backend::api::{chat::ChatState, create_router, AppState};
+use hotel_management_backend::config::BodyLimits;
+use hotel_management_backend::db::{create_pool, DbPool};
+use hotel_management_backend::errors::AppError;
+use hotel_management_backend::models::UserRole;
+use hotel_management_backend::scheduler::JobBoard;
+use hotel_management_backend::schema::refresh_tokens;
+use hotel_management_backend::services::auth_service::{
+    CreateUserRequest, LoginRequest, LoginResponse, DEFAULT_ACCESS_TOKEN_LIFETIME, REFRESH_TOKEN_LIFETIME,
+};
+use hotel_management_backend::services::rate_limit_service::InMemoryLoginAttempts;
+use hotel_management_backend::services::{AuthService, ReadOnlyMode, RevocationCache, SessionService, SessionTracker, TokenIssuer};
+
+const JWT_SECRET: &str = "test-secret";
+const PASSWORD: &str = "desk-password-1";
+
+fn test_pool() -> Option<DbPool> {
+    match std::env::var("TEST_DATABASE_URL") {
+        Ok(url) => Some(create_pool(&url)),
+        Err(_) => {
+            eprintln!("TEST_DATABASE_URL not set, skipping database test");
+            None
+        }
+    }
+}
+
+/// Username of a new receptionist
+fn receptionist(pool: &DbPool) -> String {
+    let username = format!("refresh-{}", &Uuid::new_v4().simple().to_string()[..8]);
+    AuthService::new(pool.clone(), JWT_SECRET.to_string())
+        .create_user(&CreateUserRequest {
+            username: username.clone(),
+            password: PASSWORD.to_string(),
+            role: UserRole::Receptionist,
+            must_change_password: false,
+        })
+        .unwrap();
+    username
+}
+
+fn login(auth: &AuthService, username: &str) -> LoginResponse {
+    auth.login(&LoginRequest {
+        username: username.to_string(),
+        password: PASSWORD.to_string(),
+    })
+    .unwrap()
+}
+
+fn assert_unauthorized<T: std::fmt::Debug>(result: Result<T, AppError>, message: &str) {
+    let err = result.unwrap_err();
+    assert!(matches!(&err, AppError::Unauthorized(m) if m == message), "{:?}", err);
+}
+
+mod service_tests {
+    use super::*;
+
+    #[test]
+    fn test_refresh_rotates_within_the_session() {
+        let Some(pool) = test_pool() else { return };
+        let auth = AuthService::with_token_lifetime(pool.clone(), JWT_SECRET.to_string(), Duration::minutes(15));
+        let login = login(&auth, &receptionist(&pool));
+        let claims = auth.validate_token(&login.token).unwrap();
+        assert_eq!(claims.exp - claims.iat, 15 * 60);
+
+        let refreshed = auth.refresh(&login.refresh_token).unwrap();
+        assert_ne!(refreshed.refresh_token, login.refresh_token);
+        let refreshed_claims = auth.validate_token(&refreshed.token).unwrap();
+        assert_eq!(refreshed_claims.sub, claims.sub);
+        assert_eq!(refreshed_claims.sid, claims.sid);
+
+        // Only the hash is stored, and rotation keeps the family's expiry
+        let mut conn = pool.get().unwrap();
+        let stored: Vec<(String, chrono::DateTime<Utc>)> = refresh_tokens::table
+            .filter(refresh_tokens::user_id.eq(claims.sub))
+            .select((refresh_tokens::token_hash, refresh_tokens::expires_at))
+            .load(&mut conn)
+            .unwrap();
+        assert_eq!(stored.len(), 2);
+        assert!(stored.iter().all(|(hash, _)| *hash != login.refresh_token));
+        assert_eq!(stored[0].1, stored[1].1);
+        assert!(stored[0].1 <= Utc::now() + REFRESH_TOKEN_LIFETIME);
+
+        let again = auth.refresh(&refreshed.refresh_token).unwrap();
+        assert_eq!(auth.validate_token(&again.token).unwrap().sid, claims.sid);
+    }
+
+    #[test]
+    fn test_reused_token_revokes_the_family() {
+        let Some(pool) = test_pool() else { return };
+        let auth = AuthService::new(pool.clone(), JWT_SECRET.to_string());
+        let username = receptionist(&pool);
+        let stolen = login(&auth, &username);
+        let other_login = login(&auth, &username);
+        let rotated = auth.refresh(&stolen.refresh_token).unwrap();
+
+        assert_unauthorized(auth.refresh(&stolen.refresh_token), "Refresh token has been revoked");
+        // The legitimate successor is gone too, and with it the session
+        assert_unauthorized(auth.refresh(&rotated.refresh_token), "Refresh token has been revoked");
+        let sid = auth.validate_token(&stolen.token).unwrap().sid.unwrap();
+        assert_eq!(SessionService::new(pool.clone()).last_seen(sid).unwrap(), None);
+
+        // Other logins of the same user are untouched
+        auth.refresh(&other_login.refresh_token).unwrap();
+        assert_unauthorized(auth.refresh("not-a-refresh-token"), "Invalid refresh token");
+    }
+
+    #[test]
+    fn test_expired_and_logged_out_tokens_are_refused() {
+        let Some(pool) = test_pool() else { return };
+        let auth = AuthService::new(pool.clone(), JWT_SECRET.to_string());
+        let username = receptionist(&pool);
+
+        let expiring = login(&auth, &username);
+        let user_id = expiring.user.id;
+        let mut conn = pool.get().unwrap();
+        diesel::update(refresh_tokens::table.filter(refresh_tokens::user_id.eq(user_id)))
+            .set(refresh_tokens::expires_at.eq(Utc::now() - Duration::minutes(1)))
+            .execute(&mut conn)
+            .unwrap();
+        assert_unauthorized(auth.refresh(&expiring.refresh_token), "Refresh token has expired");
+
+        let leaving = login(&auth, &username);
+        auth.logout(&leaving.refresh_token).unwrap();
+        assert_unauthorized(auth.refresh(&leaving.refresh_token), "Refresh token has been revoked");
+        let sid = auth.validate_token(&leaving.token).unwrap().sid.unwrap();
+        assert_eq!(SessionService::new(pool.clone()).last_seen(sid).unwrap(), None);
+
+        // Logging out twice, or with an unknown token, is not an error
+        auth.logout(&leaving.refresh_token).unwrap();
+        auth.logout("not-a-refresh-token").unwrap();
+    }
+}
+
+fn router(pool: DbPool) -> axum::Router {
+    let s3_config = aws_sdk_s3::config::Builder::new()
+        .region(aws_sdk_s3::config::Region::new("us-east-1"))
+        .behavior_version_latest()
+        .build();
+
+    create_router(AppState {
+        pool,
+        jwt_secret: JWT_SECRET.to_string(),
+        chat_state: Arc::new(ChatState::default()),
+        s3_client: aws_sdk_s3::Client::from_conf(s3_config),
+        read_only: Arc::new(ReadOnlyMode::new(Some(false))),
+        public_lookup: Arc::new(PublicLookupLimits::default()),
+        jobs: Arc::new(JobBoard::default()),
+        body_limits: BodyLimits::default(),
+        sessions: Arc::new(SessionTracker::default()),
+        access_token_lifetime: DEFAULT_ACCESS_TOKEN_LIFETIME,
+        login_attempts: Arc::new(InMemoryLoginAttempts::default()),
+        revocations: Arc::new(RevocationCache::default()),
+        trust_proxy_headers: false,
+        token_issuer: TokenIssuer::default(),
+    })
+}
+
+async fn send(
+    pool: &DbPool,
+    method: Method,
+    uri: &str,
+    token: Option<&str>,
+    body: Option<serde_json::Value>,
+) -> (StatusCode, serde_json::Value) {
+    let mut request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("content-type", "application/json");
+    if let Some(token) = token {
+        request = request.header(AUTHORIZATION, format!("Bearer {}", token));
+    }
+    let body = body.map_or(Body::empty(), |json| Body::from(json.to_string()));
+    let response = router(pool.clone()).oneshot(request.body(body).unwrap()).await.unwrap();
+    let status = response.status();
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&bytes).unwrap_or(serde_json::Value::Null))
+}
+
+mod endpoint_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_refresh_and_logout_over_http() {
+        let Some(pool) = test_pool() else { return };
+        let username = receptionist(&pool);
+
+        let (status, login) = send(
+            &pool,
+            Method::POST,
+            "/auth/login",
+            None,
+            Some(serde_json::json!({ "username": username, "password": PASSWORD })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let refresh_token = login["refresh_token"].as_str().unwrap();
+
+        let (status, refreshed) = send(
+            &pool,
+            Method::POST,
+            "/auth/refresh",
+            None,
+            Some(serde_json::json!({ "refresh_token": refresh_token })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let token = refreshed["token"].as_str().unwrap();
+        let (status, _) = send(&pool, Method::GET, "/rooms/summary", Some(token), None).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let (status, _) = send(
+            &pool,
+            Method::POST,
+            "/auth/logout",
+            None,
+            Some(serde_json::json!({ "refresh_token": refreshed["refresh_token"] })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+
+        // The access token dies with its session
+        let (status, body) = send(&pool, Method::GET, "/rooms/summary", Some(token), None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body["code"], "SESSION_EXPIRED");
+        let (status, _) = send(
+            &pool,
+            Method::POST,
+            "/auth/refresh",
+            None,
+            Some(serde_json::json!({ "refresh_token": refreshed["refresh_token"] })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+}
@@ -0,0 +1,191 @@
+//! Scheduled report tests
+//!
+//! Tests for report period scheduling, retry backoff, occupancy math, email
+//! rendering and the background job board.
+
+use std::sync::Arc;
+
+use bigdecimal::BigDecimal;
+use chrono::{Duration, FixedOffset, NaiveDate, NaiveDateTime, Utc};
+
+use hotel_management_backend::models::ReportType;
+use hotel_management_backend::scheduler::JobBoard;
+use hotel_management_backend::services::report_service::{
+    latest_due_period, parse_utc_offset, render_report_email, retry_delay, room_nights_within,
+    ReportSummary, RETRY_MAX_MINUTES,
+};
+
+fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(y, m, d).unwrap()
+}
+
+fn local(y: i32, m: u32, d: u32, hour: u32) -> NaiveDateTime {
+    date(y, m, d).and_hms_opt(hour, 0, 0).unwrap()
+}
+
+mod period_tests {
+    use super::*;
+
+    #[test]
+    fn test_daily_covers_yesterday_after_send_hour() {
+        assert_eq!(
+            latest_due_period(ReportType::Daily, local(2025, 5, 14, 8)),
+            (date(2025, 5, 13), date(2025, 5, 13))
+        );
+    }
+
+    #[test]
+    fn test_daily_before_send_hour_is_previous_period() {
+        assert_eq!(
+            latest_due_period(ReportType::Daily, local(2025, 5, 14, 6)),
+            (date(2025, 5, 12), date(2025, 5, 12))
+        );
+    }
+
+    #[test]
+    fn test_weekly_covers_previous_monday_to_sunday() {
+        // 2025-05-14 is a Wednesday
+        assert_eq!(
+            latest_due_period(ReportType::Weekly, local(2025, 5, 14, 12)),
+            (date(2025, 5, 5), date(2025, 5, 11))
+        );
+        // Monday before the send hour still reports the week before last
+        assert_eq!(
+            latest_due_period(ReportType::Weekly, local(2025, 5, 12, 6)),
+            (date(2025, 4, 28), date(2025, 5, 4))
+        );
+    }
+
+    #[test]
+    fn test_monthly_covers_previous_calendar_month() {
+        assert_eq!(
+            latest_due_period(ReportType::Monthly, local(2025, 1, 1, 9)),
+            (date(2024, 12, 1), date(2024, 12, 31))
+        );
+        assert_eq!(
+            latest_due_period(ReportType::Monthly, local(2025, 3, 1, 6)),
+            (date(2025, 1, 1), date(2025, 1, 31))
+        );
+    }
+}
+
+mod timezone_tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_utc_offset() {
+        assert_eq!(parse_utc_offset("+07:00"), FixedOffset::east_opt(7 * 3600));
+        assert_eq!(parse_utc_offset("-05:30"), FixedOffset::east_opt(-(5 * 3600 + 30 * 60)));
+        assert_eq!(parse_utc_offset("UTC"), FixedOffset::east_opt(0));
+        assert_eq!(parse_utc_offset("+7"), FixedOffset::east_opt(7 * 3600));
+        assert!(parse_utc_offset("Asia/Ho_Chi_Minh").is_none());
+        assert!(parse_utc_offset("+25:00").is_none());
+    }
+
+    #[test]
+    fn test_hotel_time_decides_due_period() {
+        // 2025-05-14 01:00 UTC is 08:00 in +07:00, past the send hour there
+        let now = date(2025, 5, 14).and_hms_opt(1, 0, 0).unwrap().and_utc();
+        let offset = parse_utc_offset("+07:00").unwrap();
+
+        assert_eq!(
+            latest_due_period(ReportType::Daily, now.with_timezone(&offset).naive_local()),
+            (date(2025, 5, 13), date(2025, 5, 13))
+        );
+        assert_eq!(
+            latest_due_period(ReportType::Daily, now.naive_utc()),
+            (date(2025, 5, 12), date(2025, 5, 12))
+        );
+    }
+}
+
+mod retry_tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_delay_doubles_and_caps() {
+        assert_eq!(retry_delay(1), Duration::minutes(5));
+        assert_eq!(retry_delay(2), Duration::minutes(10));
+        assert_eq!(retry_delay(3), Duration::minutes(20));
+        assert_eq!(retry_delay(30), Duration::minutes(RETRY_MAX_MINUTES));
+    }
+}
+
+mod summary_tests {
+    use super::*;
+
+    #[test]
+    fn test_room_nights_are_clipped_to_period() {
+        let stays = vec![
+            // Starts before the week, 2 nights inside
+            (date(2025, 5, 3), date(2025, 5, 7)),
+            // Fully inside, 3 nights
+            (date(2025, 5, 8), date(2025, 5, 11)),
+            // Runs past the week, 1 night inside (the 11th)
+            (date(2025, 5, 11), date(2025, 5, 14)),
+        ];
+
+        assert_eq!(room_nights_within(&stays, date(2025, 5, 5), date(2025, 5, 11)), 6);
+    }
+
+    #[test]
+    fn test_render_report_email() {
+        let summary = ReportSummary {
+            report_type: ReportType::Weekly,
+            period_start: date(2025, 5, 5),
+            period_end: date(2025, 5, 11),
+            room_count: 10,
+            room_nights_available: 70,
+            room_nights_sold: 35,
+            occupancy_rate: 0.5,
+            revenue: BigDecimal::from(12500000),
+            payments_collected: BigDecimal::from(9000000),
+            new_bookings: 4,
+        };
+
+        let email = render_report_email("owner@example.com", &summary);
+
+        assert_eq!(email.to, "owner@example.com");
+        assert_eq!(email.subject, "Pupinn weekly report: 2025-05-05 to 2025-05-11");
+        assert!(email.html.contains("50.0%"));
+        assert!(email.html.contains("35 of 70"));
+        assert!(email.html.contains("12,500,000 VND"));
+    }
+}
+
+mod job_board_tests {
+    use super::*;
+
+    #[test]
+    fn test_job_board_tracks_runs_and_errors() {
+        let board = JobBoard::default();
+
+        board.record_run("report_emails", Utc::now(), Err("mail API down".to_string()));
+        board.record_paused("stale_booking_sweep");
+        board.record_run("report_emails", Utc::now(), Ok(()));
+
+        let jobs = board.snapshot();
+        let reports = jobs.iter().find(|j| j.name == "report_emails").unwrap();
+        assert_eq!(reports.runs, 2);
+        assert!(reports.last_error.is_none());
+
+        let sweep = jobs.iter().find(|j| j.name == "stale_booking_sweep").unwrap();
+        assert_eq!(sweep.runs, 0);
+        assert!(sweep.paused_at.is_some());
+    }
+
+    #[test]
+    fn test_only_one_run_of_a_job_at_a_time() {
+        let board = Arc::new(JobBoard::default());
+        board.record_paused("stale_booking_sweep");
+
+        let running = board.try_start("stale_booking_sweep").unwrap();
+        assert!(board.try_start("stale_booking_sweep").is_none());
+        assert!(board.try_start("report_emails").is_some());
+        assert!(board.snapshot().iter().any(|j| j.name == "stale_booking_sweep" && j.running));
+
+        drop(running);
+        assert!(board.try_start("stale_booking_sweep").is_some());
+        assert!(board.snapshot().iter().all(|j| !j.running));
+    }
+}
@@ -0,0 +1,227 @@
+//! Role guard tests
+//!
+//! Tests for the `require_roles` middleware: missing tokens get 401, users
+//! outside the allowed roles get 403, the staff-only booking, room, front
+//! desk and reconciliation routes refuse guests and cleaners, and only
+//! admins create users. They need a migrated PostgreSQL database and
+//! only run when TEST_DATABASE_URL is set.
+
+use std::sync::Arc;
+
+use axum::body::{to_bytes, Body};
+use axum::http::{header::AUTHORIZATION, Method, Request, StatusCode};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use hotel_management_backend::api::public_bookings::PublicLookupLimits;
+use hotel_management_backend::api::{chat::ChatState, create_router, AppState};
+use hotel_management_backend::config::BodyLimits;
+use hotel_management_backend::db::{create_pool, DbPool};
+use hotel_management_backend::models::UserRole;
+use hotel_management_backend::scheduler::JobBoard;
+use hotel_management_backend::services::auth_service::{
+    CreateUserRequest, GuestRegisterRequest, LoginRequest, DEFAULT_ACCESS_TOKEN_LIFETIME,
+};
+use hotel_management_backend::services::rate_limit_service::InMemoryLoginAttempts;
+use hotel_management_backend::services::{AuthService, ReadOnlyMode, RevocationCache, SessionTracker, TokenIssuer};
+
+const JWT_SECRET: &str = "test-secret";
+const PASSWORD: &str = "guard-password-1";
+
+fn test_pool() -> Option<DbPool> {
+    match std::env::var("TEST_DATABASE_URL") {
+        Ok(url) => Some(create_pool(&url)),
+        Err(_) => {
+            eprintln!("TEST_DATABASE_URL not set, skipping database test");
+            None
+        }
+    }
+}
+
+fn router(pool: DbPool) -> axum::Router {
+    let s3_config = aws_sdk_s3::config::Builder::new()
+        .region(aws_sdk_s3::config::Region::new("us-east-1"))
+        .behavior_version_latest()
+        .build();
+
+    create_router(AppState {
+        pool,
+        jwt_secret: JWT_SECRET.to_string(),
+        chat_state: Arc::new(ChatState::default()),
+        s3_client: aws_sdk_s3::Client::from_conf(s3_config),
+        read_only: Arc::new(ReadOnlyMode::new(Some(false))),
+        public_lookup: Arc::new(PublicLookupLimits::default()),
+        jobs: Arc::new(JobBoard::default()),
+        body_limits: BodyLimits::default(),
+        sessions: Arc::new(SessionTracker::default()),
+        access_token_lifetime: DEFAULT_ACCESS_TOKEN_LIFETIME,
+        login_attempts: Arc::new(InMemoryLoginAttempts::default()),
+        revocations: Arc::new(RevocationCache::default()),
+        trust_proxy_headers: false,
+        token_issuer: TokenIssuer::default(),
+    })
+}
+
+/// Access token of a new staff member
+fn staff_token(pool: &DbPool, role: UserRole) -> String {
+    let auth = AuthService::new(pool.clone(), JWT_SECRET.to_string());
+    let username = format!("guard-{}", &Uuid::new_v4().simple().to_string()[..8]);
+    auth.create_user(&CreateUserRequest {
+        username: username.clone(),
+        password: PASSWORD.to_string(),
+        role,
+        must_change_password: false,
+    })
+    .unwrap();
+    auth.login(&LoginRequest {
+        username,
+        password: PASSWORD.to_string(),
+    })
+    .unwrap()
+    .token
+}
+
+/// Access token of a new guest
+fn guest_token(pool: &DbPool) -> String {
+    AuthService::new(pool.clone(), JWT_SECRET.to_string())
+        .register_guest(&GuestRegisterRequest {
+            email: format!("guard-{}@example.com", &Uuid::new_v4().simple().to_string()[..8]),
+            password: PASSWORD.to_string(),
+            full_name: "Guard Guest".to_string(),
+        })
+        .unwrap()
+        .token
+}
+
+async fn send(
+    pool: &DbPool,
+    method: Method,
+    uri: &str,
+    token: Option<&str>,
+    body: Option<serde_json::Value>,
+) -> (StatusCode, serde_json::Value) {
+    let mut builder = Request::builder().method(method).uri(uri);
+    if let Some(token) = token {
+        builder = builder.header(AUTHORIZATION, format!("Bearer {}", token));
+    }
+    let request = match body {
+        Some(body) => builder
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap(),
+        None => builder.body(Body::empty()).unwrap(),
+    };
+    let response = router(pool.clone()).oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&bytes).unwrap_or(serde_json::Value::Null))
+}
+
+mod endpoint_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_booking_desk_routes_need_front_desk_staff() {
+        let Some(pool) = test_pool() else { return };
+        let booking = format!("/bookings/{}", Uuid::new_v4());
+        let edit = serde_json::json!({ "guest_name": "Someone Else" });
+
+        let (status, _) = send(&pool, Method::GET, "/bookings", None, None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let (status, _) = send(&pool, Method::PATCH, &booking, None, Some(edit.clone())).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        for token in [guest_token(&pool), staff_token(&pool, UserRole::Cleaner)] {
+            let (status, body) = send(&pool, Method::GET, "/bookings", Some(&token), None).await;
+            assert_eq!(status, StatusCode::FORBIDDEN);
+            assert_eq!(body["message"], "admin or receptionist access required");
+            let (status, _) = send(&pool, Method::GET, &booking, Some(&token), None).await;
+            assert_eq!(status, StatusCode::FORBIDDEN);
+            let (status, _) = send(&pool, Method::PATCH, &booking, Some(&token), Some(edit.clone())).await;
+            assert_eq!(status, StatusCode::FORBIDDEN);
+        }
+
+        let desk = staff_token(&pool, UserRole::Receptionist);
+        let (status, _) = send(&pool, Method::GET, "/bookings", Some(&desk), None).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = send(&pool, Method::GET, &booking, Some(&desk), None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_staff_routes_are_guarded_before_the_handler() {
+        let Some(pool) = test_pool() else { return };
+        let id = Uuid::new_v4();
+        let routes = [
+            (Method::POST, format!("/bookings/{}/check-in", id)),
+            (Method::GET, "/bookings/search?q=guard".to_string()),
+            (Method::GET, format!("/bookings/{}/modifications", id)),
+            (Method::GET, "/staff/in-house".to_string()),
+            (Method::PATCH, format!("/rooms/{}", id)),
+            (Method::GET, "/rooms/summary".to_string()),
+            (Method::GET, "/admin/rooms/overstays".to_string()),
+            (Method::GET, "/admin/financial/reconciliations".to_string()),
+        ];
+
+        for token in [guest_token(&pool), staff_token(&pool, UserRole::Cleaner)] {
+            for (method, uri) in &routes {
+                let (status, body) = send(&pool, method.clone(), uri, Some(&token), None).await;
+                assert_eq!(status, StatusCode::FORBIDDEN, "{} {}", method, uri);
+                assert_eq!(body["message"], "admin or receptionist access required", "{} {}", method, uri);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_only_admins_create_users() {
+        let Some(pool) = test_pool() else { return };
+        let user = serde_json::json!({
+            "username": format!("guard-{}", &Uuid::new_v4().simple().to_string()[..8]),
+            "password": PASSWORD,
+            "role": "cleaner",
+        });
+
+        let (status, _) = send(&pool, Method::POST, "/auth/users", None, Some(user.clone())).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let desk = staff_token(&pool, UserRole::Receptionist);
+        let (status, body) = send(&pool, Method::POST, "/auth/users", Some(&desk), Some(user)).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["message"], "admin access required");
+    }
+
+    #[tokio::test]
+    async fn test_other_roles_are_forbidden() {
+        let Some(pool) = test_pool() else { return };
+        let guest = guest_token(&pool);
+        let cleaner = staff_token(&pool, UserRole::Cleaner);
+        let desk = staff_token(&pool, UserRole::Receptionist);
+
+        let room = serde_json::json!({ "number": "G-1", "room_type": "single", "price": "100.00" });
+        for token in [&guest, &desk] {
+            let (status, body) = send(&pool, Method::POST, "/rooms", Some(token), Some(room.clone())).await;
+            assert_eq!(status, StatusCode::FORBIDDEN);
+            assert_eq!(body["message"], "admin access required");
+        }
+
+        let stay = serde_json::json!({
+            "guest_name": "Guard Guest",
+            "room_id": Uuid::new_v4(),
+            "check_in_date": "2030-04-01",
+            "check_out_date": "2030-04-03",
+        });
+        let (status, _) = send(&pool, Method::POST, "/bookings", Some(&cleaner), Some(stay)).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        let (status, _) = send(&pool, Method::GET, "/cleaner/rooms", Some(&guest), None).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        let (status, body) = send(&pool, Method::GET, "/guest/bookings", Some(&cleaner), None).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["message"], "guest access required");
+        let (status, body) = send(&pool, Method::GET, "/inventory", Some(&desk), None).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["message"], "admin or cleaner access required");
+
+        let (status, _) = send(&pool, Method::GET, "/inventory", Some(&cleaner), None).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+}
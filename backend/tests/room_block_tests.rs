@@ -0,0 +1,319 @@
+//! Room block tests
+//!
+//! Tests for keeping a room off sale on some nights without a booking or
+//! maintenance: validating blocks, refusing blocks over booked nights, and
+//! treating blocked nights as unavailable when booking, moving a stay or
+//! searching for rooms. The database tests need a migrated PostgreSQL
+//! database and only run when TEST_DATABASE_URL is set.
+
+use std::sync::Arc;
+
+use axum::body::{to_bytes, Body};
+use axum::http::{header::AUTHORIZATION, Method, Request, StatusCode};
+use chrono::{Duration, NaiveDate, Utc};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use hotel_management_backend::api::public_bookings::PublicLookupLimits;
+use hotel_management_backend::api::{chat::ChatState, create_router, AppState};
+use hotel_management_backend::config::BodyLimits;
+use hotel_management_backend::db::{create_pool, DbPool};
+use hotel_management_backend::errors::AppError;
+use hotel_management_backend::models::{Booking, Room, RoomType, UserRole};
+use hotel_management_backend::scheduler::JobBoard;
+use hotel_management_backend::services::auth_service::{CreateUserRequest, LoginRequest, DEFAULT_ACCESS_TOKEN_LIFETIME};
+use hotel_management_backend::services::booking_service::StaffBookingRequest;
+use hotel_management_backend::services::rate_limit_service::InMemoryLoginAttempts;
+use hotel_management_backend::services::room_block_service::{RoomBlockRequest, MAX_BLOCK_REASON_LEN};
+use hotel_management_backend::services::{
+    AuthService, BookingService, ReadOnlyMode, RevocationCache, RoomBlockService, RoomService, SessionTracker, TokenIssuer,
+};
+
+const JWT_SECRET: &str = "test-secret";
+
+fn block(start_date: NaiveDate, end_date: NaiveDate) -> RoomBlockRequest {
+    RoomBlockRequest {
+        start_date,
+        end_date,
+        reason: Some("Owner's family visiting".to_string()),
+    }
+}
+
+mod validation_tests {
+    use super::*;
+
+    #[test]
+    fn test_blocks_need_ordered_dates_and_a_short_reason() {
+        let day = NaiveDate::from_ymd_opt(2026, 12, 24).unwrap();
+        let single_night = RoomBlockService::validate_block(&block(day, day)).unwrap();
+        assert_eq!(single_night.end_date, day);
+
+        let blank = RoomBlockRequest {
+            reason: Some("   ".to_string()),
+            ..block(day, day)
+        };
+        assert_eq!(RoomBlockService::validate_block(&blank).unwrap().reason, None);
+
+        let reversed = block(day, day - Duration::days(1));
+        let long = RoomBlockRequest {
+            reason: Some("a".repeat(MAX_BLOCK_REASON_LEN + 1)),
+            ..block(day, day)
+        };
+        for bad in [reversed, long] {
+            assert!(matches!(
+                RoomBlockService::validate_block(&bad),
+                Err(AppError::FieldErrors(_))
+            ));
+        }
+    }
+}
+
+fn test_pool() -> Option<DbPool> {
+    match std::env::var("TEST_DATABASE_URL") {
+        Ok(url) => Some(create_pool(&url)),
+        Err(_) => {
+            eprintln!("TEST_DATABASE_URL not set, skipping database test");
+            None
+        }
+    }
+}
+
+fn room(pool: &DbPool) -> Room {
+    let number = format!("K{}", &Uuid::new_v4().simple().to_string()[..8]);
+    RoomService::new(pool.clone()).create_room(&number, RoomType::SINGLE).unwrap()
+}
+
+fn stay(room: &Room, check_in_date: NaiveDate, nights: i64, override_conflict: bool) -> StaffBookingRequest {
+    StaffBookingRequest {
+        guest_name: format!("Block {} {}", room.number, check_in_date),
+        room_id: room.id,
+        check_in_date,
+        check_out_date: check_in_date + Duration::days(nights),
+        price: None,
+        allow_duplicate: true,
+        override_conflict,
+    }
+}
+
+/// A staff member's id
+fn staff(pool: &DbPool) -> Uuid {
+    AuthService::new(pool.clone(), JWT_SECRET.to_string())
+        .create_user(&CreateUserRequest {
+            username: format!("block-{}", &Uuid::new_v4().simple().to_string()[..8]),
+            password: "desk-password-1".to_string(),
+            role: UserRole::Receptionist,
+            must_change_password: false,
+        })
+        .unwrap()
+        .id
+}
+
+mod service_tests {
+    use super::*;
+
+    #[test]
+    fn test_blocks_cannot_cover_booked_nights() {
+        let Some(pool) = test_pool() else { return };
+        let room = room(&pool);
+        let blocks = RoomBlockService::new(pool.clone());
+        let bookings = BookingService::new(pool.clone());
+        let arrival = Utc::now().date_naive() + Duration::days(40);
+        let booked: Booking = bookings.create_booking(&stay(&room, arrival, 3, false), None).unwrap();
+
+        // The last booked night is arrival + 2; the check-out day is free
+        let err = blocks
+            .create_block(room.id, &block(arrival + Duration::days(2), arrival + Duration::days(4)), None)
+            .unwrap_err();
+        assert!(matches!(&err, AppError::Conflict(m) if m.contains(&booked.reference)), "{:?}", err);
+        blocks
+            .create_block(room.id, &block(arrival + Duration::days(3), arrival + Duration::days(4)), None)
+            .unwrap();
+        blocks
+            .create_block(room.id, &block(arrival - Duration::days(2), arrival - Duration::days(1)), None)
+            .unwrap();
+
+        // Cancelled stays give their nights back
+        bookings.cancel(booked.id, staff(&pool)).unwrap();
+        let over_cancelled = blocks
+            .create_block(room.id, &block(arrival, arrival + Duration::days(1)), None)
+            .unwrap();
+
+        // Blocks may overlap each other and are listed earliest first
+        let overlapping = blocks
+            .create_block(room.id, &block(arrival, arrival + Duration::days(5)), None)
+            .unwrap();
+        let listed: Vec<NaiveDate> = blocks.list_room_blocks(room.id).unwrap().iter().map(|b| b.start_date).collect();
+        assert_eq!(listed.len(), 4);
+        assert!(listed.windows(2).all(|w| w[0] <= w[1]));
+
+        let moved = blocks
+            .update_block(room.id, overlapping.id, &block(arrival + Duration::days(10), arrival + Duration::days(10)))
+            .unwrap();
+        assert_eq!(moved.end_date, arrival + Duration::days(10));
+
+        let other = self::room(&pool);
+        assert!(matches!(blocks.get_block(other.id, over_cancelled.id), Err(AppError::NotFound(_))));
+        assert!(matches!(blocks.delete_block(other.id, over_cancelled.id), Err(AppError::NotFound(_))));
+        assert!(matches!(
+            blocks.create_block(Uuid::new_v4(), &block(arrival, arrival), None),
+            Err(AppError::NotFound(_))
+        ));
+        blocks.delete_block(room.id, over_cancelled.id).unwrap();
+        assert_eq!(blocks.list_room_blocks(room.id).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_blocked_nights_cannot_be_booked() {
+        let Some(pool) = test_pool() else { return };
+        let room = room(&pool);
+        let bookings = BookingService::new(pool.clone());
+        let first_night = Utc::now().date_naive() + Duration::days(50);
+        RoomBlockService::new(pool.clone())
+            .create_block(room.id, &block(first_night, first_night + Duration::days(1)), None)
+            .unwrap();
+
+        // Ending on the first blocked night or starting after the last is fine
+        assert!(bookings
+            .check_availability(room.id, first_night - Duration::days(2), first_night, None, None)
+            .unwrap());
+        assert!(bookings
+            .check_availability(room.id, first_night + Duration::days(2), first_night + Duration::days(3), None, None)
+            .unwrap());
+        assert!(!bookings
+            .check_availability(room.id, first_night + Duration::days(1), first_night + Duration::days(3), None, None)
+            .unwrap());
+
+        // Overriding a conflict does not book over a block either
+        for override_conflict in [false, true] {
+            let err = bookings
+                .create_booking(&stay(&room, first_night - Duration::days(1), 2, override_conflict), None)
+                .unwrap_err();
+            assert!(
+                matches!(&err, AppError::RoomUnavailable(m) if m.contains("is blocked for the selected dates")),
+                "{:?}",
+                err
+            );
+        }
+
+        // Nor can a stay be moved into it
+        let other = self::room(&pool);
+        let elsewhere: Booking = bookings
+            .create_booking(&stay(&other, first_night, 1, false), None)
+            .unwrap();
+        let err = bookings.move_room(elsewhere.id, room.id, staff(&pool)).unwrap_err();
+        assert!(matches!(&err, AppError::RoomUnavailable(m) if m.contains("is blocked")), "{:?}", err);
+        assert_eq!(bookings.get_booking_by_id(elsewhere.id).unwrap().room_id, other.id);
+    }
+}
+
+fn router(pool: DbPool) -> axum::Router {
+    let s3_config = aws_sdk_s3::config::Builder::new()
+        .region(aws_sdk_s3::config::Region::new("us-east-1"))
+        .behavior_version_latest()
+        .build();
+
+    create_router(AppState {
+        pool,
+        jwt_secret: JWT_SECRET.to_string(),
+        chat_state: Arc::new(ChatState::default()),
+        s3_client: aws_sdk_s3::Client::from_conf(s3_config),
+        read_only: Arc::new(ReadOnlyMode::new(Some(false))),
+        public_lookup: Arc::new(PublicLookupLimits::default()),
+        jobs: Arc::new(JobBoard::default()),
+        body_limits: BodyLimits::default(),
+        sessions: Arc::new(SessionTracker::default()),
+        access_token_lifetime: DEFAULT_ACCESS_TOKEN_LIFETIME,
+        login_attempts: Arc::new(InMemoryLoginAttempts::default()),
+        revocations: Arc::new(RevocationCache::default()),
+        trust_proxy_headers: false,
+        token_issuer: TokenIssuer::default(),
+    })
+}
+
+/// Login token of a new user with the given role
+fn token(pool: &DbPool, role: UserRole) -> String {
+    let auth = AuthService::new(pool.clone(), JWT_SECRET.to_string());
+    let username = format!("block-{}", &Uuid::new_v4().simple().to_string()[..8]);
+    auth.create_user(&CreateUserRequest {
+        username: username.clone(),
+        password: "desk-password-1".to_string(),
+        role,
+        must_change_password: false,
+    })
+    .unwrap();
+    auth.login(&LoginRequest {
+        username,
+        password: "desk-password-1".to_string(),
+    })
+    .unwrap()
+    .token
+}
+
+async fn send(
+    pool: &DbPool,
+    method: Method,
+    uri: &str,
+    token: Option<&str>,
+    body: Option<serde_json::Value>,
+) -> (StatusCode, serde_json::Value) {
+    let mut request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("content-type", "application/json");
+    if let Some(token) = token {
+        request = request.header(AUTHORIZATION, format!("Bearer {}", token));
+    }
+    let body = body.map_or(Body::empty(), |json| Body::from(json.to_string()));
+    let response = router(pool.clone()).oneshot(request.body(body).unwrap()).await.unwrap();
+    let status = response.status();
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&bytes).unwrap_or(serde_json::Value::Null))
+}
+
+mod endpoint_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_only_admins_block_rooms_and_search_skips_them() {
+        let Some(pool) = test_pool() else { return };
+        let room = room(&pool);
+        let night = Utc::now().date_naive() + Duration::days(60);
+        let uri = format!("/admin/rooms/{}/blocks", room.id);
+        let body = serde_json::json!({ "start_date": night, "end_date": night, "reason": "Owner stay" });
+
+        let receptionist = token(&pool, UserRole::Receptionist);
+        for method in [Method::GET, Method::POST] {
+            let (status, _) = send(&pool, method, &uri, Some(&receptionist), Some(body.clone())).await;
+            assert_eq!(status, StatusCode::FORBIDDEN);
+        }
+
+        let blocked = RoomBlockService::new(pool.clone())
+            .create_block(room.id, &block(night, night), None)
+            .unwrap();
+        let search = format!(
+            "/rooms/available?check_in_date={}&check_out_date={}&room_type=single",
+            night,
+            night + Duration::days(1)
+        );
+        let (status, rooms) = send(&pool, Method::GET, &search, None, None).await;
+        assert_eq!(status, StatusCode::OK);
+        let listed = rooms
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|r| r["id"] == room.id.to_string())
+            .unwrap();
+        assert_eq!(listed["is_available"], false);
+
+        // Lifting the block puts the room back on sale
+        RoomBlockService::new(pool.clone()).delete_block(room.id, blocked.id).unwrap();
+        let (_, rooms) = send(&pool, Method::GET, &search, None, None).await;
+        let listed = rooms
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|r| r["id"] == room.id.to_string())
+            .unwrap();
+        assert_eq!(listed["is_available"], true);
+    }
+}
@@ -0,0 +1,200 @@
+//! Bulk room creation tests
+//!
+//! Tests for expanding a floor range or room list, skipping numbers that
+//! repeat or already exist, and inserting the rest together. The database
+//! tests need a migrated PostgreSQL database and only run when
+//! TEST_DATABASE_URL is set.
+
+use std::str::FromStr;
+
+use bigdecimal::BigDecimal;
+use uuid::Uuid;
+
+use hotel_management_backend::db::{create_pool, DbPool};
+use hotel_management_backend::errors::AppError;
+use hotel_management_backend::models::RoomType;
+use hotel_management_backend::services::room_service::{
+    BulkRoomRequest, BulkRoomSpec, RoomFilter, RoomLocation, MAX_BULK_ROOMS, MAX_FLOOR,
+};
+use hotel_management_backend::services::RoomService;
+
+fn spec(number: &str, room_type: RoomType, price: Option<&str>) -> BulkRoomSpec {
+    BulkRoomSpec {
+        number: number.to_string(),
+        room_type,
+        price: price.map(|p| BigDecimal::from_str(p).unwrap()),
+        location: RoomLocation::default(),
+    }
+}
+
+mod request_tests {
+    use super::*;
+
+    #[test]
+    fn test_both_request_shapes_deserialize() {
+        let list: BulkRoomRequest = serde_json::from_value(serde_json::json!([
+            { "number": "101", "room_type": "single" },
+            { "number": "102", "room_type": "suite", "price": 3_000_000 },
+        ]))
+        .unwrap();
+        let specs = RoomService::expand_bulk_request(list).unwrap();
+        assert_eq!(specs.len(), 2);
+        assert_eq!(specs[1].price, Some(BigDecimal::from(3_000_000)));
+
+        let range: BulkRoomRequest = serde_json::from_value(serde_json::json!({
+            "floor": 2, "from": 1, "to": 20, "room_type": "double"
+        }))
+        .unwrap();
+        let numbers: Vec<String> = RoomService::expand_bulk_request(range)
+            .unwrap()
+            .into_iter()
+            .map(|s| s.number)
+            .collect();
+        assert_eq!(numbers.len(), 20);
+        assert_eq!(numbers[0], "201");
+        assert_eq!(numbers[19], "220");
+
+        let annex: BulkRoomRequest = serde_json::from_value(serde_json::json!({
+            "floor": 3, "from": 1, "to": 2, "room_type": "double", "building": "Annex"
+        }))
+        .unwrap();
+        let specs = RoomService::expand_bulk_request(annex).unwrap();
+        assert_eq!(specs[1].location.floor, Some(3));
+        assert_eq!(specs[1].location.building.as_deref(), Some("Annex"));
+    }
+
+    #[test]
+    fn test_bad_ranges_and_sizes_are_rejected() {
+        for (floor, from, to) in [(3, 0, 5), (3, 10, 9), (3, 90, 100), (MAX_FLOOR as u32 + 1, 1, 5)] {
+            let range = BulkRoomRequest::Range {
+                floor,
+                from,
+                to,
+                room_type: RoomType::SINGLE,
+                price: None,
+                building: None,
+            };
+            assert!(matches!(
+                RoomService::expand_bulk_request(range),
+                Err(AppError::ValidationError(_) | AppError::FieldErrors(_))
+            ));
+        }
+        assert!(matches!(
+            RoomService::expand_bulk_request(BulkRoomRequest::Rooms(Vec::new())),
+            Err(AppError::ValidationError(_))
+        ));
+        let too_many = (0..=MAX_BULK_ROOMS)
+            .map(|n| spec(&n.to_string(), RoomType::SINGLE, None))
+            .collect();
+        assert!(matches!(
+            RoomService::expand_bulk_request(BulkRoomRequest::Rooms(too_many)),
+            Err(AppError::ValidationError(_))
+        ));
+    }
+}
+
+fn test_pool() -> Option<DbPool> {
+    match std::env::var("TEST_DATABASE_URL") {
+        Ok(url) => Some(create_pool(&url)),
+        Err(_) => {
+            eprintln!("TEST_DATABASE_URL not set, skipping database test");
+            None
+        }
+    }
+}
+
+/// Prefix for room numbers no other test run uses
+fn unique_prefix() -> String {
+    format!("B{}", &Uuid::new_v4().simple().to_string()[..6])
+}
+
+mod create_tests {
+    use super::*;
+
+    #[test]
+    fn test_duplicates_and_bad_rooms_are_skipped_with_reasons() {
+        let Some(pool) = test_pool() else { return };
+        let prefix = unique_prefix();
+        let service = RoomService::new(pool);
+        let taken = service.create_room(&format!("{}1", prefix), RoomType::SINGLE).unwrap();
+
+        let result = service
+            .create_rooms_bulk(BulkRoomRequest::Rooms(vec![
+                spec(&taken.number, RoomType::SINGLE, None),
+                spec(&format!("{}2", prefix), RoomType::DOUBLE, None),
+                spec(&format!("{}3", prefix), RoomType::SUITE, Some("3200000")),
+                spec(&format!("{}4", prefix), RoomType::SINGLE, None),
+                spec(&format!(" {}4 ", prefix), RoomType::DOUBLE, None),
+                spec(&format!("{}5", prefix), RoomType::SINGLE, Some("0")),
+                spec("ROOM-NUMBER-TOO-LONG", RoomType::SINGLE, None),
+                spec(&format!("{}6", prefix), RoomType::new("no_such_type").unwrap(), None),
+            ]))
+            .unwrap();
+
+        let created: Vec<&str> = result.created.iter().map(|r| r.number.as_str()).collect();
+        assert_eq!(created, vec![format!("{}2", prefix), format!("{}3", prefix)]);
+        assert_eq!(result.created[0].price, BigDecimal::from(1_500_000));
+        assert_eq!(result.created[1].price, BigDecimal::from(3_200_000));
+
+        let skipped: Vec<(&str, &str)> = result
+            .skipped
+            .iter()
+            .map(|s| (s.number.as_str(), s.reason.as_str()))
+            .collect();
+        assert_eq!(skipped.len(), 6, "{:?}", skipped);
+        assert_eq!(skipped[0], (taken.number.as_str(), "Room number already exists"));
+        assert!(skipped[1].1.contains("more than once"));
+        assert_eq!(skipped[1].0, skipped[2].0);
+        assert!(skipped[3].1.starts_with("price"), "{}", skipped[3].1);
+        assert!(skipped[4].1.contains("characters"));
+        assert!(skipped[5].1.starts_with("room_type unknown"), "{}", skipped[5].1);
+    }
+
+    #[test]
+    fn test_floor_range_creates_every_room() {
+        let Some(pool) = test_pool() else { return };
+        let service = RoomService::new(pool);
+        // A floor without rooms keeps the generated numbers free
+        let Some(floor) = (100..=MAX_FLOOR).find(|&floor| {
+            let filter = RoomFilter {
+                floor: Some(floor),
+                include_decommissioned: true,
+                ..RoomFilter::default()
+            };
+            service.list_rooms(&filter).unwrap().is_empty()
+        }) else {
+            eprintln!("no free floor left, skipping database test");
+            return;
+        };
+        let floor = floor as u32;
+
+        let result = service
+            .create_rooms_bulk(BulkRoomRequest::Range {
+                floor,
+                from: 1,
+                to: 12,
+                room_type: RoomType::DOUBLE,
+                price: None,
+                building: None,
+            })
+            .unwrap();
+        assert_eq!(result.created.len(), 12);
+        assert!(result.skipped.is_empty());
+        assert_eq!(result.created[11].number, format!("{}12", floor));
+        assert!(result.created.iter().all(|r| r.floor == floor as i32));
+
+        // Running it again creates nothing
+        let again = service
+            .create_rooms_bulk(BulkRoomRequest::Range {
+                floor,
+                from: 1,
+                to: 12,
+                room_type: RoomType::DOUBLE,
+                price: None,
+                building: None,
+            })
+            .unwrap();
+        assert!(again.created.is_empty());
+        assert_eq!(again.skipped.len(), 12);
+    }
+}
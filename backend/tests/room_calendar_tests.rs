@@ -0,0 +1,146 @@
+//! Room occupancy calendar tests
+//!
+//! Tests for expanding stays into per-night entries and for the range cap.
+//! The loading tests need a migrated PostgreSQL database and only run when
+//! TEST_DATABASE_URL is set.
+
+use chrono::{Duration, NaiveDate, Utc};
+use uuid::Uuid;
+
+use hotel_management_backend::db::{create_pool, DbPool};
+use hotel_management_backend::errors::AppError;
+use hotel_management_backend::models::{BookingStatus, RoomType};
+use hotel_management_backend::services::booking_service::{CalendarStay, StaffBookingRequest, MAX_ROOM_CALENDAR_DAYS};
+use hotel_management_backend::services::{BookingService, RoomService};
+
+fn date(day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2025, 6, day).unwrap()
+}
+
+fn stay(reference: &str, check_in: u32, check_out: u32, status: BookingStatus) -> CalendarStay {
+    (Uuid::nil(), reference.to_string(), date(check_in), date(check_out), status)
+}
+
+/// Reference occupying each night, "-" when free
+fn references(stays: &[CalendarStay], from: u32, to: u32, today: u32) -> Vec<String> {
+    BookingService::build_room_calendar(stays, date(from), date(to), date(today))
+        .into_iter()
+        .map(|night| night.booking.map_or("-".to_string(), |b| b.reference))
+        .collect()
+}
+
+mod build_tests {
+    use super::*;
+
+    #[test]
+    fn test_stay_covers_nights_before_check_out() {
+        let stays = [stay("A", 3, 5, BookingStatus::Upcoming)];
+        assert_eq!(references(&stays, 2, 6, 1), vec!["-", "A", "A", "-", "-"]);
+    }
+
+    #[test]
+    fn test_back_to_back_stays_share_no_night() {
+        let stays = [
+            stay("A", 1, 3, BookingStatus::CheckedOut),
+            stay("B", 3, 4, BookingStatus::Upcoming),
+        ];
+        assert_eq!(references(&stays, 1, 4, 1), vec!["A", "A", "B", "-"]);
+    }
+
+    #[test]
+    fn test_overstay_holds_room_through_today() {
+        let stays = [stay("A", 1, 3, BookingStatus::Overstay)];
+        assert_eq!(references(&stays, 1, 6, 4), vec!["A", "A", "A", "A", "-", "-"]);
+    }
+
+    #[test]
+    fn test_guest_in_house_wins_over_arrival() {
+        let stays = [
+            stay("NEXT", 3, 5, BookingStatus::Upcoming),
+            stay("STAYING", 1, 3, BookingStatus::Overstay),
+        ];
+        let nights = BookingService::build_room_calendar(&stays, date(3), date(3), date(3));
+        let booking = nights[0].booking.as_ref().unwrap();
+        assert_eq!(booking.reference, "STAYING");
+        assert_eq!(booking.status, BookingStatus::Overstay);
+    }
+
+    #[test]
+    fn test_range_is_capped() {
+        let from = date(1);
+        assert!(BookingService::validate_room_calendar_range(from, from).is_ok());
+        assert!(BookingService::validate_room_calendar_range(
+            from,
+            from + Duration::days(MAX_ROOM_CALENDAR_DAYS - 1)
+        )
+        .is_ok());
+
+        match BookingService::validate_room_calendar_range(from, from + Duration::days(MAX_ROOM_CALENDAR_DAYS)) {
+            Err(AppError::ValidationError(msg)) => assert!(msg.contains("92")),
+            other => panic!("Expected ValidationError, got {:?}", other),
+        }
+        assert!(BookingService::validate_room_calendar_range(date(2), date(1)).is_err());
+    }
+}
+
+fn test_pool() -> Option<DbPool> {
+    match std::env::var("TEST_DATABASE_URL") {
+        Ok(url) => Some(create_pool(&url)),
+        Err(_) => {
+            eprintln!("TEST_DATABASE_URL not set, skipping database test");
+            None
+        }
+    }
+}
+
+mod load_tests {
+    use super::*;
+
+    #[test]
+    fn test_room_and_aggregate_calendars_agree() {
+        let Some(pool) = test_pool() else { return };
+        let number = format!("C{}", &Uuid::new_v4().simple().to_string()[..8]);
+        let room = RoomService::new(pool.clone())
+            .create_room(&number, RoomType::SUITE)
+            .unwrap();
+        let service = BookingService::new(pool.clone());
+        let check_in = Utc::now().date_naive() + Duration::days(10);
+        let booking = service
+            .create_booking(
+                &StaffBookingRequest {
+                    guest_name: format!("Calendar {}", number),
+                    room_id: room.id,
+                    check_in_date: check_in,
+                    check_out_date: check_in + Duration::days(2),
+                    price: None,
+                    allow_duplicate: false,
+                    override_conflict: false,
+                },
+                None,
+            )
+            .unwrap();
+
+        let from = check_in - Duration::days(1);
+        let to = check_in + Duration::days(2);
+        let nights = service.get_room_calendar(room.id, from, to).unwrap();
+        let occupied: Vec<Option<&str>> = nights
+            .iter()
+            .map(|n| n.booking.as_ref().map(|b| b.reference.as_str()))
+            .collect();
+        let reference = Some(booking.reference.as_str());
+        assert_eq!(occupied, vec![None, reference, reference, None]);
+
+        let all = service.get_rooms_calendar(from, to).unwrap();
+        assert_eq!(all[&room.id], nights);
+    }
+
+    #[test]
+    fn test_unknown_room_is_not_found() {
+        let Some(pool) = test_pool() else { return };
+        let today = Utc::now().date_naive();
+        assert!(matches!(
+            BookingService::new(pool).get_room_calendar(Uuid::new_v4(), today, today),
+            Err(AppError::NotFound(_))
+        ));
+    }
+}
@@ -0,0 +1,288 @@
+//! Room decommissioning tests
+//!
+//! Tests for taking a room out of service: refusing while guests are booked,
+//! dropping the room from lists and availability, keeping its bookings
+//! resolvable, and keeping the decommissioned listing to admins. They need a
+//! migrated PostgreSQL database and only run when TEST_DATABASE_URL is set.
+
+use std::sync::Arc;
+
+use axum::body::{to_bytes, Body};
+use axum::http::{header::AUTHORIZATION, Method, Request, StatusCode};
+use bigdecimal::BigDecimal;
+use chrono::{Duration, NaiveDate, Utc};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use hotel_management_backend::api::public_bookings::PublicLookupLimits;
+use hotel_management_backend::api::{chat::ChatState, create_router, AppState};
+use hotel_management_backend::config::BodyLimits;
+use hotel_management_backend::db::{create_pool, DbPool};
+use hotel_management_backend::errors::AppError;
+use hotel_management_backend::models::{Booking, Room, RoomType, UserRole};
+use hotel_management_backend::scheduler::JobBoard;
+use hotel_management_backend::services::auth_service::{CreateUserRequest, LoginRequest, DEFAULT_ACCESS_TOKEN_LIFETIME};
+use hotel_management_backend::services::booking_service::StaffBookingRequest;
+use hotel_management_backend::services::rate_limit_service::InMemoryLoginAttempts;
+use hotel_management_backend::services::room_service::RoomFilter;
+use hotel_management_backend::services::room_type_service::CreateRoomTypeRequest;
+use hotel_management_backend::services::{
+    AuthService, BookingService, ReadOnlyMode, RevocationCache, RoomService, RoomTypeService, SessionTracker,
+    TokenIssuer,
+};
+
+const JWT_SECRET: &str = "test-secret";
+
+fn test_pool() -> Option<DbPool> {
+    match std::env::var("TEST_DATABASE_URL") {
+        Ok(url) => Some(create_pool(&url)),
+        Err(_) => {
+            eprintln!("TEST_DATABASE_URL not set, skipping database test");
+            None
+        }
+    }
+}
+
+fn room(pool: &DbPool) -> Room {
+    let number = format!("D{}", &Uuid::new_v4().simple().to_string()[..8]);
+    RoomService::new(pool.clone()).create_room(&number, RoomType::SINGLE).unwrap()
+}
+
+/// Single rooms, optionally with the decommissioned ones
+fn single(include_decommissioned: bool) -> RoomFilter {
+    RoomFilter {
+        room_type: Some(RoomType::SINGLE),
+        include_decommissioned,
+        ..RoomFilter::default()
+    }
+}
+
+fn request(room: &Room, check_in_date: NaiveDate, override_conflict: bool) -> StaffBookingRequest {
+    StaffBookingRequest {
+        guest_name: format!("Decommission {}", room.number),
+        room_id: room.id,
+        check_in_date,
+        check_out_date: check_in_date + Duration::days(2),
+        price: None,
+        allow_duplicate: false,
+        override_conflict,
+    }
+}
+
+/// A receptionist's id and login token
+fn receptionist(pool: &DbPool) -> (Uuid, String) {
+    let auth = AuthService::new(pool.clone(), JWT_SECRET.to_string());
+    let username = format!("decom-{}", &Uuid::new_v4().simple().to_string()[..8]);
+    let user = auth
+        .create_user(&CreateUserRequest {
+            username: username.clone(),
+            password: "desk-password-1".to_string(),
+            role: UserRole::Receptionist,
+            must_change_password: false,
+        })
+        .unwrap();
+    let token = auth
+        .login(&LoginRequest {
+            username,
+            password: "desk-password-1".to_string(),
+        })
+        .unwrap()
+        .token;
+    (user.id, token)
+}
+
+mod service_tests {
+    use super::*;
+
+    #[test]
+    fn test_refused_while_a_guest_is_booked() {
+        let Some(pool) = test_pool() else { return };
+        let room = room(&pool);
+        let bookings = BookingService::new(pool.clone());
+        let check_in = Utc::now().date_naive() + Duration::days(15);
+        let booking = bookings.create_booking(&request(&room, check_in, false), None).unwrap();
+        let (actor, _) = receptionist(&pool);
+        let service = RoomService::new(pool.clone());
+
+        match service.decommission_room(room.id) {
+            Err(AppError::Conflict(message)) => assert!(message.contains(&booking.reference), "{}", message),
+            other => panic!("Expected a conflict, got {:?}", other),
+        }
+        assert!(service.get_room_by_id(room.id).unwrap().decommissioned_at.is_none());
+
+        bookings.cancel(booking.id, actor).unwrap();
+        let decommissioned = service.decommission_room(room.id).unwrap();
+        assert!(decommissioned.decommissioned_at.is_some());
+        // Doing it again changes nothing
+        let again = service.decommission_room(room.id).unwrap();
+        assert_eq!(again.decommissioned_at, decommissioned.decommissioned_at);
+
+        assert!(matches!(service.decommission_room(Uuid::new_v4()), Err(AppError::NotFound(_))));
+    }
+
+    #[test]
+    fn test_decommissioned_room_is_hidden_and_unbookable() {
+        let Some(pool) = test_pool() else { return };
+        let room = room(&pool);
+        let bookings = BookingService::new(pool.clone());
+        let (actor, _) = receptionist(&pool);
+        let check_in = Utc::now().date_naive() + Duration::days(10);
+        let stay: Booking = bookings.create_booking(&request(&room, check_in, false), None).unwrap();
+        bookings.cancel(stay.id, actor).unwrap();
+        let service = RoomService::new(pool.clone());
+        service.decommission_room(room.id).unwrap();
+
+        let listed = service.list_rooms(&single(false)).unwrap();
+        assert!(listed.iter().all(|r| r.id != room.id));
+        let all = service.list_rooms(&single(true)).unwrap();
+        assert!(all.iter().any(|r| r.id == room.id));
+
+        assert!(!bookings
+            .check_availability(room.id, check_in, check_in + Duration::days(1), None, None)
+            .unwrap());
+        // Not even an override reopens the room
+        assert!(matches!(
+            bookings.create_staff_booking(&request(&room, check_in, true), None),
+            Err(AppError::RoomUnavailable(_))
+        ));
+
+        // Earlier bookings still resolve their room
+        let with_room = bookings.get_booking_with_room(stay.id).unwrap();
+        assert_eq!(with_room.room.unwrap().number, room.number);
+    }
+}
+
+fn router(pool: DbPool) -> axum::Router {
+    let s3_config = aws_sdk_s3::config::Builder::new()
+        .region(aws_sdk_s3::config::Region::new("us-east-1"))
+        .behavior_version_latest()
+        .build();
+
+    create_router(AppState {
+        pool,
+        jwt_secret: JWT_SECRET.to_string(),
+        chat_state: Arc::new(ChatState::default()),
+        s3_client: aws_sdk_s3::Client::from_conf(s3_config),
+        read_only: Arc::new(ReadOnlyMode::new(Some(false))),
+        public_lookup: Arc::new(PublicLookupLimits::default()),
+        jobs: Arc::new(JobBoard::default()),
+        body_limits: BodyLimits::default(),
+        sessions: Arc::new(SessionTracker::default()),
+        access_token_lifetime: DEFAULT_ACCESS_TOKEN_LIFETIME,
+        login_attempts: Arc::new(InMemoryLoginAttempts::default()),
+        revocations: Arc::new(RevocationCache::default()),
+        trust_proxy_headers: false,
+        token_issuer: TokenIssuer::default(),
+    })
+}
+
+async fn send(pool: &DbPool, method: Method, uri: &str, token: Option<&str>) -> StatusCode {
+    let mut request = Request::builder().method(method).uri(uri);
+    if let Some(token) = token {
+        request = request.header(AUTHORIZATION, format!("Bearer {}", token));
+    }
+    let request = request.body(Body::empty()).unwrap();
+    router(pool.clone()).oneshot(request).await.unwrap().status()
+}
+
+/// GET `uri` with a token and return the status and JSON body
+async fn get_json(pool: &DbPool, uri: &str, token: &str) -> (StatusCode, serde_json::Value) {
+    let request = Request::builder()
+        .uri(uri)
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .body(Body::empty())
+        .unwrap();
+    let response = router(pool.clone()).oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&bytes).unwrap_or(serde_json::Value::Null))
+}
+
+mod endpoint_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_decommissioned_room_leaves_the_calendar_and_quick_availability() {
+        let Some(pool) = test_pool() else { return };
+        // A type of its own, so other tests' rooms do not change the counts
+        let code = format!("decom_{}", &Uuid::new_v4().simple().to_string()[..8]);
+        let room_type = RoomType::new(&code).unwrap();
+        RoomTypeService::new(pool.clone())
+            .create_room_type(&CreateRoomTypeRequest {
+                code: room_type.clone(),
+                display_name: "Decommission".to_string(),
+                default_price: BigDecimal::from(900_000),
+                default_capacity: 2,
+                active: true,
+            })
+            .unwrap();
+        let number = format!("D{}", &Uuid::new_v4().simple().to_string()[..8]);
+        let room = RoomService::new(pool.clone()).create_room(&number, room_type).unwrap();
+        let (_, token) = receptionist(&pool);
+
+        let night = Utc::now().date_naive() + Duration::days(20);
+        let calendar = format!("/availability/calendar?room_type={}&start_date={}&end_date={}", code, night, night);
+        let quick = format!(
+            "/staff/quick-availability?check_in_date={}&check_out_date={}&guests=1",
+            night,
+            night + Duration::days(1)
+        );
+        let free_in_calendar = |body: &serde_json::Value| {
+            body["days"][0]["room_types"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .find(|t| t["room_type"] == code.as_str())
+                .map(|t| t["available"].as_i64().unwrap())
+        };
+        let offered = |body: &serde_json::Value| {
+            body["room_types"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .filter(|t| t["room_type"] == code.as_str())
+                .flat_map(|t| t["candidates"].as_array().unwrap().clone())
+                .any(|c| c["room_id"] == room.id.to_string().as_str())
+        };
+
+        let (status, body) = get_json(&pool, &calendar, &token).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(free_in_calendar(&body), Some(1));
+        let (status, body) = get_json(&pool, &quick, &token).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(offered(&body));
+
+        RoomService::new(pool.clone()).decommission_room(room.id).unwrap();
+
+        let (_, body) = get_json(&pool, &calendar, &token).await;
+        assert_eq!(free_in_calendar(&body), None);
+        let (_, body) = get_json(&pool, &quick, &token).await;
+        assert!(!offered(&body));
+    }
+
+    #[tokio::test]
+    async fn test_only_admins_may_decommission_or_list_decommissioned_rooms() {
+        let Some(pool) = test_pool() else { return };
+        let room = room(&pool);
+        let (_, token) = receptionist(&pool);
+
+        let uri = format!("/rooms/{}", room.id);
+        assert_eq!(send(&pool, Method::DELETE, &uri, Some(&token)).await, StatusCode::FORBIDDEN);
+        assert_eq!(send(&pool, Method::DELETE, &uri, None).await, StatusCode::UNAUTHORIZED);
+        // The public room routes still answer
+        assert_eq!(send(&pool, Method::GET, &uri, None).await, StatusCode::OK);
+        assert_eq!(send(&pool, Method::GET, "/rooms", None).await, StatusCode::OK);
+
+        for uri in [
+            "/rooms?include_decommissioned=true".to_string(),
+            format!(
+                "/rooms/available?check_in_date={}&check_out_date={}&include_decommissioned=true",
+                Utc::now().date_naive() + Duration::days(10),
+                Utc::now().date_naive() + Duration::days(11)
+            ),
+        ] {
+            assert_eq!(send(&pool, Method::GET, &uri, None).await, StatusCode::FORBIDDEN);
+            assert_eq!(send(&pool, Method::GET, &uri, Some(&token)).await, StatusCode::FORBIDDEN);
+        }
+    }
+}
@@ -0,0 +1,155 @@
+//! Room details tests
+//!
+//! Tests for validating room descriptions, amenities and photos, and for how
+//! those details are presented to the AI concierge.
+
+use bigdecimal::BigDecimal;
+use chrono::Utc;
+use uuid::Uuid;
+
+use hotel_management_backend::errors::AppError;
+use hotel_management_backend::models::{Room, RoomDetailsUpdate, RoomStatus, RoomType};
+use hotel_management_backend::services::ai_service::{
+    cap_tool_output, extract_room_photo, room_details_text, room_summary_line,
+    SEARCH_DESCRIPTION_CHARS,
+};
+use hotel_management_backend::services::room_service::MAX_PHOTOS;
+use hotel_management_backend::services::RoomService;
+
+fn suite() -> Room {
+    Room {
+        id: Uuid::new_v4(),
+        number: "501".to_string(),
+        room_type: RoomType::SUITE,
+        status: RoomStatus::Available,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+        price: BigDecimal::from(2500000),
+        assigned_cleaner_id: None,
+        description: Some("Corner suite with a private balcony over the river.".to_string()),
+        amenities: vec!["Balcony".to_string(), "Bathtub".to_string()],
+        photo_urls: vec![
+            "https://cdn.example.com/501-a.jpg".to_string(),
+            "https://cdn.example.com/501-b.jpg".to_string(),
+        ],
+        decommissioned_at: None,
+        floor: 1,
+        building: None,
+        room_type_id: Uuid::new_v4(),
+    }
+}
+
+mod validation_tests {
+    use super::*;
+
+    #[test]
+    fn test_amenities_are_trimmed_and_deduplicated() {
+        let details = RoomService::validate_details(RoomDetailsUpdate {
+            amenities: Some(vec![
+                " Balcony ".to_string(),
+                "".to_string(),
+                "balcony".to_string(),
+                "Wi-Fi".to_string(),
+            ]),
+            ..Default::default()
+        })
+        .unwrap();
+
+        assert_eq!(details.amenities, Some(vec!["Balcony".to_string(), "Wi-Fi".to_string()]));
+        assert!(details.description.is_none());
+        assert!(details.photo_urls.is_none());
+    }
+
+    #[test]
+    fn test_photo_urls_must_be_http() {
+        let result = RoomService::validate_details(RoomDetailsUpdate {
+            photo_urls: Some(vec!["javascript:alert(1)".to_string()]),
+            ..Default::default()
+        });
+        assert!(matches!(result, Err(AppError::ValidationError(_))));
+    }
+
+    #[test]
+    fn test_too_many_photos_rejected() {
+        let result = RoomService::validate_details(RoomDetailsUpdate {
+            photo_urls: Some(vec!["https://cdn.example.com/p.jpg".to_string(); MAX_PHOTOS + 1]),
+            ..Default::default()
+        });
+        assert!(matches!(result, Err(AppError::ValidationError(_))));
+    }
+
+    #[test]
+    fn test_blank_description_is_kept_empty_to_clear() {
+        let details = RoomService::validate_details(RoomDetailsUpdate {
+            description: Some("   ".to_string()),
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(details.description.as_deref(), Some(""));
+    }
+}
+
+mod concierge_output_tests {
+    use super::*;
+
+    #[test]
+    fn test_search_line_includes_description_and_amenities() {
+        let line = room_summary_line(&suite());
+        assert!(line.contains("Room 501: suite room"));
+        assert!(line.contains("private balcony"));
+        assert!(line.contains("Amenities: Balcony, Bathtub"));
+        assert!(!line.contains("cdn.example.com"));
+    }
+
+    #[test]
+    fn test_search_line_shortens_long_description() {
+        let mut room = suite();
+        room.description = Some("x".repeat(SEARCH_DESCRIPTION_CHARS * 3));
+        let line = room_summary_line(&room);
+        assert!(line.contains(&format!("{}..., Amenities", "x".repeat(SEARCH_DESCRIPTION_CHARS))));
+    }
+
+    #[test]
+    fn test_details_list_photos_as_markers() {
+        let text = room_details_text(&suite());
+        assert!(text.contains("Description: Corner suite"));
+        assert!(text.contains("ROOM_PHOTO:https://cdn.example.com/501-a.jpg"));
+        assert!(text.contains("ROOM_PHOTO:https://cdn.example.com/501-b.jpg"));
+    }
+
+    #[test]
+    fn test_details_without_extras_say_so() {
+        let mut room = suite();
+        room.description = None;
+        room.amenities.clear();
+        room.photo_urls.clear();
+        let text = room_details_text(&room);
+        assert!(text.contains("No description on file"));
+        assert!(text.contains("Amenities: none listed"));
+        assert!(text.contains("Photos: none"));
+    }
+
+    #[test]
+    fn test_tool_output_is_capped() {
+        let capped = cap_tool_output("é".repeat(5000), 100);
+        assert_eq!(capped.chars().count(), 100);
+        assert!(capped.ends_with("[truncated]"));
+
+        assert_eq!(cap_tool_output("short".to_string(), 100), "short");
+    }
+
+    #[test]
+    fn test_shared_photo_is_extracted_from_reply() {
+        let reply = "Yes, suite 501 has a balcony!\nROOM_PHOTO:https://cdn.example.com/501-a.jpg\nWant to book it?";
+        let (content, photo) = extract_room_photo(reply);
+        assert_eq!(content, "Yes, suite 501 has a balcony!\nWant to book it?");
+        assert_eq!(photo.as_deref(), Some("https://cdn.example.com/501-a.jpg"));
+    }
+
+    #[test]
+    fn test_reply_without_photo_is_unchanged() {
+        let (content, photo) = extract_room_photo("No photos on file, sorry.");
+        assert_eq!(content, "No photos on file, sorry.");
+        assert!(photo.is_none());
+    }
+}
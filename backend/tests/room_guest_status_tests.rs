@@ -0,0 +1,227 @@
+//! Room status tests against staying guests
+//!
+//! Tests for refusing room statuses that contradict a checked-in or
+//! overstaying guest, both on the admin room edit and the cleaner status
+//! endpoint. They need a migrated PostgreSQL database and only run when
+//! TEST_DATABASE_URL is set.
+
+use std::sync::Arc;
+
+use axum::body::{to_bytes, Body};
+use axum::http::{header::AUTHORIZATION, Method, Request, StatusCode};
+use chrono::Duration;
+use diesel::prelude::*;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use hotel_management_backend::api::public_bookings::PublicLookupLimits;
+use hotel_management_backend::api::{chat::ChatState, create_router, AppState};
+use hotel_management_backend::config::BodyLimits;
+use hotel_management_backend::db::{create_pool, DbPool};
+use hotel_management_backend::errors::AppError;
+use hotel_management_backend::models::{Booking, BookingStatus, Room, RoomStatus, RoomType, UserRole};
+use hotel_management_backend::scheduler::JobBoard;
+use hotel_management_backend::schema::{bookings, rooms};
+use hotel_management_backend::services::auth_service::{CreateUserRequest, LoginRequest, DEFAULT_ACCESS_TOKEN_LIFETIME};
+use hotel_management_backend::services::booking_service::StaffBookingRequest;
+use hotel_management_backend::services::rate_limit_service::InMemoryLoginAttempts;
+use hotel_management_backend::services::room_service::RoomEdit;
+use hotel_management_backend::services::{
+    AuthService, BookingService, ReadOnlyMode, RevocationCache, RoomService, SessionTracker, TokenIssuer,
+};
+
+const JWT_SECRET: &str = "test-secret";
+
+fn test_pool() -> Option<DbPool> {
+    match std::env::var("TEST_DATABASE_URL") {
+        Ok(url) => Some(create_pool(&url)),
+        Err(_) => {
+            eprintln!("TEST_DATABASE_URL not set, skipping database test");
+            None
+        }
+    }
+}
+
+/// A staff member's id
+fn staff(pool: &DbPool) -> Uuid {
+    AuthService::new(pool.clone(), JWT_SECRET.to_string())
+        .create_user(&CreateUserRequest {
+            username: format!("guest-status-{}", &Uuid::new_v4().simple().to_string()[..8]),
+            password: "desk-password-1".to_string(),
+            role: UserRole::Receptionist,
+            must_change_password: false,
+        })
+        .unwrap()
+        .id
+}
+
+/// A new room with a walk-in guest checked in
+fn occupied_room(pool: &DbPool, actor: Uuid) -> (Room, Booking) {
+    let number = format!("G{}", &Uuid::new_v4().simple().to_string()[..8]);
+    let room = RoomService::new(pool.clone()).create_room(&number, RoomType::SINGLE).unwrap();
+    let bookings = BookingService::new(pool.clone());
+    let today = bookings.today().unwrap();
+    let stay = bookings
+        .create_walk_in(
+            &StaffBookingRequest {
+                guest_name: format!("Guest {}", number),
+                room_id: room.id,
+                check_in_date: today,
+                check_out_date: today + Duration::days(2),
+                price: None,
+                allow_duplicate: true,
+                override_conflict: false,
+            },
+            actor,
+        )
+        .unwrap();
+    (room, stay.booking)
+}
+
+fn set_status(status: RoomStatus) -> RoomEdit {
+    RoomEdit {
+        status: Some(status),
+        maintenance_reason: Some("Leaking tap".to_string()),
+        ..RoomEdit::default()
+    }
+}
+
+fn assert_guest_conflict(result: Result<Room, AppError>, booking: &Booking) {
+    let err = result.unwrap_err();
+    assert!(
+        matches!(&err, AppError::Conflict(m) if m.contains(&booking.reference)),
+        "{:?}",
+        err
+    );
+}
+
+mod service_tests {
+    use super::*;
+
+    #[test]
+    fn test_checked_in_room_cannot_become_available() {
+        let Some(pool) = test_pool() else { return };
+        let actor = staff(&pool);
+        let rooms = RoomService::new(pool.clone());
+        let (room, booking) = occupied_room(&pool, actor);
+
+        // Marking the room dirty during the stay is fine; releasing it is not
+        rooms.update_room(room.id, set_status(RoomStatus::Dirty), None).unwrap();
+        assert_guest_conflict(rooms.update_room(room.id, set_status(RoomStatus::Available), None), &booking);
+
+        // Nor once the guest has overstayed
+        let mut conn = pool.get().unwrap();
+        diesel::update(bookings::table.find(booking.id))
+            .set(bookings::status.eq(BookingStatus::Overstay))
+            .execute(&mut conn)
+            .unwrap();
+        assert_guest_conflict(rooms.update_room(room.id, set_status(RoomStatus::Available), None), &booking);
+        assert_eq!(rooms.get_room_by_id(room.id).unwrap().status, RoomStatus::Dirty);
+
+        // After check-out the room is released as usual
+        BookingService::new(pool.clone()).check_out(booking.id, true, actor).unwrap();
+        let room = rooms.update_room(room.id, set_status(RoomStatus::Available), None).unwrap();
+        assert_eq!(room.status, RoomStatus::Available);
+    }
+
+    #[test]
+    fn test_checked_in_room_cannot_go_under_maintenance() {
+        let Some(pool) = test_pool() else { return };
+        let rooms = RoomService::new(pool.clone());
+        let (room, booking) = occupied_room(&pool, staff(&pool));
+
+        // A room left available by an older release with the guest still in
+        let mut conn = pool.get().unwrap();
+        diesel::update(rooms::table.find(room.id))
+            .set(rooms::status.eq(RoomStatus::Available))
+            .execute(&mut conn)
+            .unwrap();
+
+        assert_guest_conflict(rooms.update_room(room.id, set_status(RoomStatus::Maintenance), None), &booking);
+        let room = rooms.get_room_by_id(room.id).unwrap();
+        assert_eq!(room.status, RoomStatus::Available);
+
+        // Saving the status the room already has changes nothing
+        rooms.update_room(room.id, set_status(RoomStatus::Available), None).unwrap();
+    }
+}
+
+fn router(pool: DbPool) -> axum::Router {
+    let s3_config = aws_sdk_s3::config::Builder::new()
+        .region(aws_sdk_s3::config::Region::new("us-east-1"))
+        .behavior_version_latest()
+        .build();
+
+    create_router(AppState {
+        pool,
+        jwt_secret: JWT_SECRET.to_string(),
+        chat_state: Arc::new(ChatState::default()),
+        s3_client: aws_sdk_s3::Client::from_conf(s3_config),
+        read_only: Arc::new(ReadOnlyMode::new(Some(false))),
+        public_lookup: Arc::new(PublicLookupLimits::default()),
+        jobs: Arc::new(JobBoard::default()),
+        body_limits: BodyLimits::default(),
+        sessions: Arc::new(SessionTracker::default()),
+        access_token_lifetime: DEFAULT_ACCESS_TOKEN_LIFETIME,
+        login_attempts: Arc::new(InMemoryLoginAttempts::default()),
+        revocations: Arc::new(RevocationCache::default()),
+        trust_proxy_headers: false,
+        token_issuer: TokenIssuer::default(),
+    })
+}
+
+/// Login token of a new cleaner
+fn cleaner_token(pool: &DbPool) -> String {
+    let auth = AuthService::new(pool.clone(), JWT_SECRET.to_string());
+    let username = format!("cleaner-{}", &Uuid::new_v4().simple().to_string()[..8]);
+    auth.create_user(&CreateUserRequest {
+        username: username.clone(),
+        password: "clean-password-1".to_string(),
+        role: UserRole::Cleaner,
+        must_change_password: false,
+    })
+    .unwrap();
+    auth.login(&LoginRequest {
+        username,
+        password: "clean-password-1".to_string(),
+    })
+    .unwrap()
+    .token
+}
+
+async fn set_cleaner_status(pool: &DbPool, token: &str, room: &Room, status: &str) -> (StatusCode, serde_json::Value) {
+    let request = Request::builder()
+        .method(Method::PATCH)
+        .uri(format!("/cleaner/rooms/{}/status", room.id))
+        .header("content-type", "application/json")
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .body(Body::from(serde_json::json!({ "status": status }).to_string()))
+        .unwrap();
+    let response = router(pool.clone()).oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&bytes).unwrap_or(serde_json::Value::Null))
+}
+
+mod endpoint_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_cleaner_cannot_release_a_room_with_a_guest() {
+        let Some(pool) = test_pool() else { return };
+        let (room, booking) = occupied_room(&pool, staff(&pool));
+        RoomService::new(pool.clone())
+            .update_room(room.id, set_status(RoomStatus::Dirty), None)
+            .unwrap();
+        let token = cleaner_token(&pool);
+
+        let (status, _) = set_cleaner_status(&pool, &token, &room, "cleaning").await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, body) = set_cleaner_status(&pool, &token, &room, "available").await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert!(body.to_string().contains(&booking.reference), "{}", body);
+
+        let room = RoomService::new(pool.clone()).get_room_by_id(room.id).unwrap();
+        assert_eq!(room.status, RoomStatus::Cleaning);
+    }
+}
@@ -0,0 +1,182 @@
+//! Room booking history tests
+//!
+//! Tests for the staff view of who stayed in a room: price redaction, date and
+//! status filters, ordering and pagination. The loading tests need a migrated
+//! PostgreSQL database and only run when TEST_DATABASE_URL is set.
+
+use bigdecimal::BigDecimal;
+use chrono::{Duration, NaiveDate, Utc};
+use uuid::Uuid;
+
+use hotel_management_backend::db::{create_pool, DbPool};
+use hotel_management_backend::errors::AppError;
+use hotel_management_backend::models::{Booking, BookingStatus, BookingWithRoom, RoomType};
+use hotel_management_backend::services::booking_service::{
+    RoomHistoryEntry, RoomHistoryFilter, StaffBookingRequest, MAX_ROOM_HISTORY_PAGE_SIZE,
+};
+use hotel_management_backend::services::{BookingService, RoomService};
+
+fn booking_with_room() -> BookingWithRoom {
+    let check_in_date = NaiveDate::from_ymd_opt(2025, 6, 1).unwrap();
+    BookingWithRoom {
+        booking: Booking {
+            id: Uuid::new_v4(),
+            reference: "BK-20250601-HS01".to_string(),
+            guest_name: "Tran Thi B".to_string(),
+            room_id: Uuid::new_v4(),
+            check_in_date,
+            check_out_date: check_in_date + Duration::days(2),
+            status: BookingStatus::CheckedOut,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            created_by_user_id: None,
+            creation_source: "staff".to_string(),
+            price: BigDecimal::from(1_800_000),
+            group_reference: None,
+            checked_in_at: None,
+            checked_out_at: None,
+        },
+        room: None,
+        modification_count: 0,
+        notes: None,
+        created_by: Some("frontdesk".to_string()),
+    }
+}
+
+mod entry_tests {
+    use super::*;
+
+    #[test]
+    fn test_admin_entry_has_total_price() {
+        let entry = RoomHistoryEntry::new(booking_with_room(), true);
+        assert_eq!(entry.total_price.as_deref(), Some("1800000"));
+        assert_eq!(entry.nights, 2);
+        assert_eq!(entry.created_by.as_deref(), Some("frontdesk"));
+    }
+
+    #[test]
+    fn test_receptionist_entry_has_no_price_field() {
+        let entry = RoomHistoryEntry::new(booking_with_room(), false);
+        let json = serde_json::to_value(&entry).unwrap();
+
+        assert!(json.get("total_price").is_none());
+        assert!(json.get("price").is_none());
+        assert_eq!(json["guest_name"], "Tran Thi B");
+    }
+}
+
+fn test_pool() -> Option<DbPool> {
+    match std::env::var("TEST_DATABASE_URL") {
+        Ok(url) => Some(create_pool(&url)),
+        Err(_) => {
+            eprintln!("TEST_DATABASE_URL not set, skipping database test");
+            None
+        }
+    }
+}
+
+mod history_page_tests {
+    use super::*;
+
+    /// Room with three consecutive two-night stays, the middle one cancelled
+    fn room_with_stays(pool: &DbPool) -> (Uuid, NaiveDate) {
+        let number = format!("Y{}", &Uuid::new_v4().simple().to_string()[..8]);
+        let room = RoomService::new(pool.clone()).create_room(&number, RoomType::SINGLE).unwrap();
+        let service = BookingService::new(pool.clone());
+        let start = Utc::now().date_naive() + Duration::days(30);
+
+        for i in 0..3 {
+            let check_in = start + Duration::days(2 * i);
+            let booking = service
+                .create_booking(
+                    &StaffBookingRequest {
+                        guest_name: format!("History Guest {} {}", i, number),
+                        room_id: room.id,
+                        check_in_date: check_in,
+                        check_out_date: check_in + Duration::days(2),
+                        price: None,
+                        allow_duplicate: false,
+                        override_conflict: false,
+                    },
+                    None,
+                )
+                .unwrap();
+            if i == 1 {
+                service.cancel(booking.id, Uuid::nil()).unwrap();
+            }
+        }
+        (room.id, start)
+    }
+
+    #[test]
+    fn test_latest_check_in_first_and_paginated() {
+        let Some(pool) = test_pool() else { return };
+        let (room_id, start) = room_with_stays(&pool);
+        let service = BookingService::new(pool);
+
+        let first = service
+            .get_room_history_page(room_id, &RoomHistoryFilter::default(), false, Some(1), Some(2))
+            .unwrap();
+        assert_eq!(first.total, 3);
+        assert_eq!(first.bookings.len(), 2);
+        assert_eq!(first.bookings[0].check_in_date, start + Duration::days(4));
+        assert!(first.bookings.iter().all(|b| b.total_price.is_none()));
+
+        let second = service
+            .get_room_history_page(room_id, &RoomHistoryFilter::default(), true, Some(2), Some(2))
+            .unwrap();
+        assert_eq!(second.bookings.len(), 1);
+        assert_eq!(second.bookings[0].check_in_date, start);
+        assert!(second.bookings[0].total_price.is_some());
+
+        let capped = service
+            .get_room_history_page(room_id, &RoomHistoryFilter::default(), false, None, Some(10_000))
+            .unwrap();
+        assert_eq!(capped.per_page, MAX_ROOM_HISTORY_PAGE_SIZE);
+    }
+
+    #[test]
+    fn test_range_matches_overlapping_stays() {
+        let Some(pool) = test_pool() else { return };
+        let (room_id, start) = room_with_stays(&pool);
+        let service = BookingService::new(pool);
+
+        // Only the first night of the last stay
+        let filter = RoomHistoryFilter {
+            from: Some(start + Duration::days(4)),
+            to: Some(start + Duration::days(4)),
+            status: None,
+        };
+        let page = service.get_room_history_page(room_id, &filter, false, None, None).unwrap();
+        // The middle stay checks out that morning, so it overlaps by date too
+        assert_eq!(page.total, 2);
+
+        let filter = RoomHistoryFilter {
+            status: Some(BookingStatus::Upcoming),
+            ..RoomHistoryFilter::default()
+        };
+        let page = service.get_room_history_page(room_id, &filter, false, None, None).unwrap();
+        assert_eq!(page.total, 2);
+    }
+
+    #[test]
+    fn test_reversed_range_and_unknown_room_are_rejected() {
+        let Some(pool) = test_pool() else { return };
+        let service = BookingService::new(pool);
+        let today = Utc::now().date_naive();
+        let reversed = RoomHistoryFilter {
+            from: Some(today),
+            to: Some(today - Duration::days(1)),
+            status: None,
+        };
+
+        assert!(matches!(
+            service.get_room_history_page(Uuid::new_v4(), &reversed, false, None, None),
+            Err(AppError::ValidationError(_))
+        ));
+        assert!(matches!(
+            service.get_room_history_page(Uuid::new_v4(), &RoomHistoryFilter::default(), false, None, None),
+            Err(AppError::NotFound(_))
+        ));
+    }
+}
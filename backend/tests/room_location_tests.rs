@@ -0,0 +1,375 @@
+//! Room location tests
+//!
+//! Tests for the floor and building of a room: validating them, deriving the
+//! floor from the room number, filtering room lists by either (also on the
+//! cleaner dashboard, which still defaults to dirty rooms) and grouping rooms
+//! by building and floor. The database tests need a migrated PostgreSQL
+//! database and only run when TEST_DATABASE_URL is set; each one works in a
+//! building of its own.
+
+use std::sync::Arc;
+
+use axum::body::{to_bytes, Body};
+use axum::http::{header::AUTHORIZATION, Method, Request, StatusCode};
+use bigdecimal::BigDecimal;
+use chrono::Utc;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use hotel_management_backend::api::public_bookings::PublicLookupLimits;
+use hotel_management_backend::api::{chat::ChatState, create_router, AppState};
+use hotel_management_backend::config::BodyLimits;
+use hotel_management_backend::db::{create_pool, DbPool};
+use hotel_management_backend::errors::AppError;
+use hotel_management_backend::models::{Room, RoomStatus, RoomType, UserRole};
+use hotel_management_backend::scheduler::JobBoard;
+use hotel_management_backend::services::auth_service::{CreateUserRequest, LoginRequest, DEFAULT_ACCESS_TOKEN_LIFETIME};
+use hotel_management_backend::services::rate_limit_service::InMemoryLoginAttempts;
+use hotel_management_backend::services::room_service::{
+    RoomEdit, RoomFilter, RoomLocation, MAX_BUILDING_LEN, MAX_FLOOR, MIN_FLOOR,
+};
+use hotel_management_backend::services::{AuthService, ReadOnlyMode, RevocationCache, RoomService, SessionTracker, TokenIssuer};
+
+const JWT_SECRET: &str = "test-secret";
+
+fn located(number: &str, floor: i32, building: Option<&str>) -> Room {
+    Room {
+        id: Uuid::new_v4(),
+        number: number.to_string(),
+        room_type: RoomType::DOUBLE,
+        status: RoomStatus::Available,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+        price: BigDecimal::from(1_000_000),
+        assigned_cleaner_id: None,
+        description: None,
+        amenities: Vec::new(),
+        photo_urls: Vec::new(),
+        decommissioned_at: None,
+        room_type_id: Uuid::new_v4(),
+        floor,
+        building: building.map(str::to_string),
+    }
+}
+
+mod validation_tests {
+    use super::*;
+
+    #[test]
+    fn test_floors_are_bounded() {
+        assert_eq!(RoomService::validate_floor(MIN_FLOOR).unwrap(), MIN_FLOOR);
+        assert_eq!(RoomService::validate_floor(MAX_FLOOR).unwrap(), MAX_FLOOR);
+        for floor in [MIN_FLOOR - 1, MAX_FLOOR + 1] {
+            assert!(matches!(RoomService::validate_floor(floor), Err(AppError::FieldErrors(_))));
+        }
+    }
+
+    #[test]
+    fn test_buildings_are_trimmed_and_blank_means_main() {
+        assert_eq!(RoomService::validate_building("  Annex ").unwrap().as_deref(), Some("Annex"));
+        assert_eq!(RoomService::validate_building("   ").unwrap(), None);
+        assert!(RoomService::validate_building(&"a".repeat(MAX_BUILDING_LEN)).is_ok());
+        assert!(matches!(
+            RoomService::validate_building(&"a".repeat(MAX_BUILDING_LEN + 1)),
+            Err(AppError::FieldErrors(_))
+        ));
+    }
+
+    #[test]
+    fn test_floor_follows_the_room_number() {
+        assert_eq!(RoomService::floor_from_number("305"), 3);
+        assert_eq!(RoomService::floor_from_number("1204"), 12);
+        assert_eq!(RoomService::floor_from_number("012"), 0);
+        for number in ["7", "42", "10101", "B12", "PH-1"] {
+            assert_eq!(RoomService::floor_from_number(number), 1, "{}", number);
+        }
+    }
+}
+
+mod grouping_tests {
+    use super::*;
+
+    #[test]
+    fn test_rooms_group_by_building_then_floor() {
+        let rooms = vec![
+            located("101", 1, None),
+            located("102", 1, None),
+            located("A201", 2, Some("Annex")),
+            located("B01", -1, None),
+            located("A101", 1, Some("Annex")),
+            located("301", 3, None),
+        ];
+        let grouped = RoomService::group_rooms(rooms);
+
+        // (building, floor, room numbers) in the order they are grouped
+        let layout: Vec<(Option<&str>, i32, Vec<&str>)> = grouped
+            .iter()
+            .flat_map(|b| {
+                b.floors.iter().map(|f| {
+                    let numbers = f.rooms.iter().map(|r| r.number.as_str()).collect();
+                    (b.building.as_deref(), f.floor, numbers)
+                })
+            })
+            .collect();
+        assert_eq!(
+            layout,
+            vec![
+                (None, -1, vec!["B01"]),
+                (None, 1, vec!["101", "102"]),
+                (None, 3, vec!["301"]),
+                (Some("Annex"), 1, vec!["A101"]),
+                (Some("Annex"), 2, vec!["A201"]),
+            ]
+        );
+        assert_eq!(grouped.len(), 2);
+        assert!(RoomService::group_rooms(Vec::new()).is_empty());
+    }
+}
+
+fn test_pool() -> Option<DbPool> {
+    match std::env::var("TEST_DATABASE_URL") {
+        Ok(url) => Some(create_pool(&url)),
+        Err(_) => {
+            eprintln!("TEST_DATABASE_URL not set, skipping database test");
+            None
+        }
+    }
+}
+
+/// Building name no other test run uses
+fn unique_building() -> String {
+    format!("Wing {}", &Uuid::new_v4().simple().to_string()[..8])
+}
+
+fn room_at(pool: &DbPool, floor: Option<i32>, building: &str) -> Room {
+    let number = format!("L{}", &Uuid::new_v4().simple().to_string()[..8]);
+    let location = RoomLocation {
+        floor,
+        building: Some(building.to_string()),
+    };
+    RoomService::new(pool.clone())
+        .create_room_at(&number, RoomType::DOUBLE, &location)
+        .unwrap()
+}
+
+fn in_building(building: &str) -> RoomFilter {
+    RoomFilter {
+        building: Some(building.to_string()),
+        ..RoomFilter::default()
+    }
+}
+
+mod service_tests {
+    use super::*;
+
+    #[test]
+    fn test_rooms_are_created_where_asked_or_where_their_number_says() {
+        let Some(pool) = test_pool() else { return };
+        let service = RoomService::new(pool.clone());
+        let building = unique_building();
+
+        let room = room_at(&pool, Some(-2), &format!("  {} ", building));
+        assert_eq!(room.floor, -2);
+        assert_eq!(room.building.as_deref(), Some(building.as_str()));
+
+        // Letters in the number leave it on the first floor of the main building
+        let plain = service
+            .create_room(&format!("L{}", &Uuid::new_v4().simple().to_string()[..8]), RoomType::SINGLE)
+            .unwrap();
+        assert_eq!(plain.floor, 1);
+        assert_eq!(plain.building, None);
+
+        let too_high = RoomLocation {
+            floor: Some(MAX_FLOOR + 1),
+            building: None,
+        };
+        assert!(matches!(
+            service.create_room_at("L-too-high", RoomType::SINGLE, &too_high),
+            Err(AppError::FieldErrors(_))
+        ));
+    }
+
+    #[test]
+    fn test_edits_move_rooms_and_filters_find_them() {
+        let Some(pool) = test_pool() else { return };
+        let service = RoomService::new(pool.clone());
+        let building = unique_building();
+        let low = room_at(&pool, Some(2), &building);
+        let high = room_at(&pool, Some(5), &building);
+
+        let on_two = service
+            .list_rooms(&RoomFilter {
+                floor: Some(2),
+                ..in_building(&building)
+            })
+            .unwrap();
+        assert_eq!(on_two.iter().map(|r| r.id).collect::<Vec<_>>(), vec![low.id]);
+
+        let moved = service
+            .update_room(
+                high.id,
+                RoomEdit {
+                    floor: Some(2),
+                    ..RoomEdit::default()
+                },
+                None,
+            )
+            .unwrap();
+        assert_eq!(moved.floor, 2);
+        assert!(matches!(
+            service.update_room(
+                high.id,
+                RoomEdit {
+                    floor: Some(MIN_FLOOR - 1),
+                    ..RoomEdit::default()
+                },
+                None,
+            ),
+            Err(AppError::FieldErrors(_))
+        ));
+
+        // An empty building moves the room to the main building
+        let main = service
+            .update_room(
+                low.id,
+                RoomEdit {
+                    building: Some(String::new()),
+                    ..RoomEdit::default()
+                },
+                None,
+            )
+            .unwrap();
+        assert_eq!(main.building, None);
+        let left: Vec<Uuid> = service.list_rooms(&in_building(&building)).unwrap().iter().map(|r| r.id).collect();
+        assert_eq!(left, vec![high.id]);
+        let main_building = service.list_rooms(&in_building("")).unwrap();
+        assert!(main_building.iter().any(|r| r.id == low.id));
+        assert!(main_building.iter().all(|r| r.building.is_none()));
+
+        let grouped = service.list_rooms_grouped(&in_building(&building)).unwrap();
+        assert_eq!(grouped.len(), 1);
+        assert_eq!(grouped[0].floors.len(), 1);
+        assert_eq!(grouped[0].floors[0].floor, 2);
+    }
+}
+
+fn router(pool: DbPool) -> axum::Router {
+    let s3_config = aws_sdk_s3::config::Builder::new()
+        .region(aws_sdk_s3::config::Region::new("us-east-1"))
+        .behavior_version_latest()
+        .build();
+
+    create_router(AppState {
+        pool,
+        jwt_secret: JWT_SECRET.to_string(),
+        chat_state: Arc::new(ChatState::default()),
+        s3_client: aws_sdk_s3::Client::from_conf(s3_config),
+        read_only: Arc::new(ReadOnlyMode::new(Some(false))),
+        public_lookup: Arc::new(PublicLookupLimits::default()),
+        jobs: Arc::new(JobBoard::default()),
+        body_limits: BodyLimits::default(),
+        sessions: Arc::new(SessionTracker::default()),
+        access_token_lifetime: DEFAULT_ACCESS_TOKEN_LIFETIME,
+        login_attempts: Arc::new(InMemoryLoginAttempts::default()),
+        revocations: Arc::new(RevocationCache::default()),
+        trust_proxy_headers: false,
+        token_issuer: TokenIssuer::default(),
+    })
+}
+
+/// Login token of a new cleaner
+fn cleaner_token(pool: &DbPool) -> String {
+    let auth = AuthService::new(pool.clone(), JWT_SECRET.to_string());
+    let username = format!("location-{}", &Uuid::new_v4().simple().to_string()[..8]);
+    auth.create_user(&CreateUserRequest {
+        username: username.clone(),
+        password: "mop-password-1".to_string(),
+        role: UserRole::Cleaner,
+        must_change_password: false,
+    })
+    .unwrap();
+    auth.login(&LoginRequest {
+        username,
+        password: "mop-password-1".to_string(),
+    })
+    .unwrap()
+    .token
+}
+
+async fn get(pool: &DbPool, uri: &str, token: Option<&str>) -> (StatusCode, serde_json::Value) {
+    let mut request = Request::builder().method(Method::GET).uri(uri);
+    if let Some(token) = token {
+        request = request.header(AUTHORIZATION, format!("Bearer {}", token));
+    }
+    let response = router(pool.clone())
+        .oneshot(request.body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&bytes).unwrap())
+}
+
+mod endpoint_tests {
+    use super::*;
+
+    fn ids(rooms: &serde_json::Value) -> Vec<&str> {
+        rooms.as_array().unwrap().iter().map(|r| r["id"].as_str().unwrap()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_cleaner_rooms_stay_dirty_by_default_when_filtered() {
+        let Some(pool) = test_pool() else { return };
+        let service = RoomService::new(pool.clone());
+        let building = unique_building();
+        let query = building.replace(' ', "%20");
+        let dirty = room_at(&pool, Some(4), &building);
+        let clean = room_at(&pool, Some(4), &building);
+        let upstairs = room_at(&pool, Some(6), &building);
+        for room in [&dirty, &upstairs] {
+            let to_dirty = RoomEdit {
+                status: Some(RoomStatus::Dirty),
+                ..RoomEdit::default()
+            };
+            service.update_room(room.id, to_dirty, None).unwrap();
+        }
+        let token = cleaner_token(&pool);
+
+        let (status, rooms) = get(&pool, &format!("/cleaner/rooms?building={}&floor=4", query), Some(&token)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(ids(&rooms), vec![dirty.id.to_string()]);
+
+        let (_, rooms) = get(&pool, &format!("/cleaner/rooms?building={}", query), Some(&token)).await;
+        assert_eq!(rooms.as_array().unwrap().len(), 2);
+
+        let (_, rooms) = get(
+            &pool,
+            &format!("/cleaner/rooms?building={}&floor=4&status=available", query),
+            Some(&token),
+        )
+        .await;
+        assert_eq!(ids(&rooms), vec![clean.id.to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_grouped_rooms_nest_buildings_and_floors() {
+        let Some(pool) = test_pool() else { return };
+        let building = unique_building();
+        let query = building.replace(' ', "%20");
+        let upstairs = room_at(&pool, Some(3), &building);
+        let downstairs = room_at(&pool, Some(1), &building);
+
+        let (status, grouped) = get(&pool, &format!("/rooms/grouped?building={}", query), None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(grouped.as_array().unwrap().len(), 1);
+        assert_eq!(grouped[0]["building"], building.as_str());
+        let floors = grouped[0]["floors"].as_array().unwrap();
+        assert_eq!(floors.len(), 2);
+        assert_eq!(floors[0]["floor"], 1);
+        assert_eq!(ids(&floors[0]["rooms"]), vec![downstairs.id.to_string()]);
+        assert_eq!(ids(&floors[1]["rooms"]), vec![upstairs.id.to_string()]);
+
+        let (status, _) = get(&pool, "/rooms/grouped?include_decommissioned=true", None).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+    }
+}
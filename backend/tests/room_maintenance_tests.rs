@@ -0,0 +1,173 @@
+//! Room maintenance cancellation tests
+//!
+//! Tests for cancelling a room's upcoming bookings before maintenance: the
+//! date range, the audit note and skipping guests already in the room. They
+//! need a migrated PostgreSQL database and only run when TEST_DATABASE_URL is
+//! set.
+
+use chrono::{Duration, NaiveDate};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use hotel_management_backend::db::{create_pool, DbPool};
+use hotel_management_backend::errors::AppError;
+use hotel_management_backend::models::{BookingStatus, Room, RoomType, UserRole};
+use hotel_management_backend::schema::{booking_events, bookings};
+use hotel_management_backend::services::auth_service::CreateUserRequest;
+use hotel_management_backend::services::booking_service::{StaffBookingRequest, ROOM_MAINTENANCE_NOTE};
+use hotel_management_backend::services::{AuthService, BookingService, RoomService};
+
+fn test_pool() -> Option<DbPool> {
+    match std::env::var("TEST_DATABASE_URL") {
+        Ok(url) => Some(create_pool(&url)),
+        Err(_) => {
+            eprintln!("TEST_DATABASE_URL not set, skipping database test");
+            None
+        }
+    }
+}
+
+mod cancel_upcoming_tests {
+    use super::*;
+
+    struct Setup {
+        service: BookingService,
+        room: Room,
+        actor: Uuid,
+        today: NaiveDate,
+    }
+
+    fn setup(pool: &DbPool) -> Setup {
+        let suffix = &Uuid::new_v4().simple().to_string()[..8];
+        let room = RoomService::new(pool.clone())
+            .create_room(&format!("M{}", suffix), RoomType::DOUBLE)
+            .unwrap();
+        let actor = AuthService::new(pool.clone(), "test-secret".to_string())
+            .create_user(&CreateUserRequest {
+                username: format!("maint-{}", suffix),
+                password: "desk-password-1".to_string(),
+                role: UserRole::Receptionist,
+                must_change_password: false,
+            })
+            .unwrap()
+            .id;
+        let service = BookingService::new(pool.clone());
+        let today = service.today().unwrap();
+        Setup {
+            service,
+            room,
+            actor,
+            today,
+        }
+    }
+
+    fn request(setup: &Setup, name: &str, from_day: i64, nights: i64) -> StaffBookingRequest {
+        let check_in_date = setup.today + Duration::days(from_day);
+        StaffBookingRequest {
+            guest_name: format!("{} {}", name, setup.room.number),
+            room_id: setup.room.id,
+            check_in_date,
+            check_out_date: check_in_date + Duration::days(nights),
+            price: None,
+            allow_duplicate: false,
+            override_conflict: false,
+        }
+    }
+
+    fn book(setup: &Setup, name: &str, from_day: i64, nights: i64) -> String {
+        setup
+            .service
+            .create_booking(&request(setup, name, from_day, nights), Some(setup.actor))
+            .unwrap()
+            .reference
+    }
+
+    #[test]
+    fn test_range_limits_which_bookings_are_cancelled() {
+        let Some(pool) = test_pool() else { return };
+        let setup = setup(&pool);
+        let soon = book(&setup, "Soon", 10, 2);
+        let later = book(&setup, "Later", 30, 2);
+
+        let result = setup
+            .service
+            .cancel_upcoming_for_room(
+                setup.room.id,
+                Some(setup.today + Duration::days(5)),
+                Some(setup.today + Duration::days(20)),
+                setup.actor,
+            )
+            .unwrap();
+        assert_eq!(result.cancelled, vec![soon.clone()]);
+        assert!(result.skipped_checked_in.is_empty());
+
+        let mut conn = pool.get().unwrap();
+        let status_of = |conn: &mut PgConnection, reference: &str| -> BookingStatus {
+            bookings::table
+                .filter(bookings::reference.eq(reference))
+                .select(bookings::status)
+                .first(conn)
+                .unwrap()
+        };
+        assert_eq!(status_of(&mut conn, &soon), BookingStatus::Cancelled);
+        assert_eq!(status_of(&mut conn, &later), BookingStatus::Upcoming);
+
+        let note: Option<String> = booking_events::table
+            .inner_join(bookings::table)
+            .filter(bookings::reference.eq(&soon))
+            .filter(booking_events::to_status.eq(BookingStatus::Cancelled))
+            .select(booking_events::details)
+            .first(&mut conn)
+            .unwrap();
+        assert_eq!(note.as_deref(), Some(ROOM_MAINTENANCE_NOTE));
+    }
+
+    #[test]
+    fn test_guest_in_house_is_skipped_and_reported() {
+        let Some(pool) = test_pool() else { return };
+        let setup = setup(&pool);
+        let in_house = setup
+            .service
+            .create_walk_in(&request(&setup, "In House", 0, 3), setup.actor)
+            .unwrap()
+            .booking
+            .reference;
+        let upcoming = book(&setup, "Upcoming", 10, 2);
+
+        let result = setup
+            .service
+            .cancel_upcoming_for_room(setup.room.id, None, None, setup.actor)
+            .unwrap();
+        assert_eq!(result.cancelled, vec![upcoming]);
+        assert_eq!(result.skipped_checked_in, vec![in_house]);
+
+        // Nothing left to cancel
+        let again = setup
+            .service
+            .cancel_upcoming_for_room(setup.room.id, None, None, setup.actor)
+            .unwrap();
+        assert!(again.cancelled.is_empty());
+    }
+
+    #[test]
+    fn test_reversed_range_and_unknown_room_are_rejected() {
+        let Some(pool) = test_pool() else { return };
+        let setup = setup(&pool);
+
+        assert!(matches!(
+            setup.service.cancel_upcoming_for_room(
+                setup.room.id,
+                Some(setup.today),
+                Some(setup.today - Duration::days(1)),
+                setup.actor,
+            ),
+            Err(AppError::ValidationError(_))
+        ));
+        assert!(matches!(
+            setup
+                .service
+                .cancel_upcoming_for_room(Uuid::new_v4(), None, None, setup.actor),
+            Err(AppError::NotFound(_))
+        ));
+    }
+}
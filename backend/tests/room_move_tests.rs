@@ -0,0 +1,227 @@
+//! Room move tests
+//!
+//! Tests for which nights a move covers and for moving bookings between
+//! rooms. The move tests need a migrated PostgreSQL database and only run
+//! when TEST_DATABASE_URL is set.
+
+use bigdecimal::BigDecimal;
+use chrono::{Duration, NaiveDate, Utc};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use hotel_management_backend::db::{create_pool, DbPool};
+use hotel_management_backend::errors::AppError;
+use hotel_management_backend::models::{Booking, BookingStatus, Room, RoomStatus, RoomType, UserRole};
+use hotel_management_backend::schema::rooms;
+use hotel_management_backend::services::auth_service::CreateUserRequest;
+use hotel_management_backend::services::booking_service::StaffBookingRequest;
+use hotel_management_backend::services::{AuthService, BookingService, RoomService};
+
+fn date(day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2025, 6, day).unwrap()
+}
+
+fn booking(status: BookingStatus, check_in: u32, check_out: u32) -> Booking {
+    Booking {
+        id: Uuid::new_v4(),
+        reference: "BK-20250601-AB12".to_string(),
+        guest_name: "Tran Thi B".to_string(),
+        room_id: Uuid::new_v4(),
+        check_in_date: date(check_in),
+        check_out_date: date(check_out),
+        status,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+        created_by_user_id: None,
+        creation_source: "staff".to_string(),
+        price: BigDecimal::from(3_000_000),
+        group_reference: None,
+        checked_in_at: None,
+        checked_out_at: None,
+    }
+}
+
+mod window_tests {
+    use super::*;
+
+    #[test]
+    fn test_upcoming_booking_moves_whole_stay() {
+        let window = BookingService::room_move_window(&booking(BookingStatus::Upcoming, 10, 13), date(1));
+        assert_eq!(window.unwrap(), (date(10), date(13)));
+    }
+
+    #[test]
+    fn test_checked_in_booking_moves_remaining_nights() {
+        let window = BookingService::room_move_window(&booking(BookingStatus::CheckedIn, 10, 13), date(11));
+        assert_eq!(window.unwrap(), (date(11), date(13)));
+    }
+
+    #[test]
+    fn test_departure_day_has_nothing_to_move() {
+        assert!(matches!(
+            BookingService::room_move_window(&booking(BookingStatus::CheckedIn, 10, 13), date(13)),
+            Err(AppError::ValidationError(_))
+        ));
+    }
+
+    #[test]
+    fn test_finished_bookings_cannot_move() {
+        for status in [BookingStatus::CheckedOut, BookingStatus::Cancelled, BookingStatus::NoShow, BookingStatus::Overstay] {
+            assert!(matches!(
+                BookingService::room_move_window(&booking(status, 10, 13), date(11)),
+                Err(AppError::InvalidStatusTransition(_))
+            ));
+        }
+    }
+}
+
+fn test_pool() -> Option<DbPool> {
+    match std::env::var("TEST_DATABASE_URL") {
+        Ok(url) => Some(create_pool(&url)),
+        Err(_) => {
+            eprintln!("TEST_DATABASE_URL not set, skipping database test");
+            None
+        }
+    }
+}
+
+mod move_tests {
+    use super::*;
+
+    fn room(pool: &DbPool) -> Room {
+        let number = format!("M{}", &Uuid::new_v4().simple().to_string()[..8]);
+        RoomService::new(pool.clone())
+            .create_room(&number, RoomType::DOUBLE)
+            .unwrap()
+    }
+
+    fn receptionist(pool: &DbPool) -> Uuid {
+        AuthService::new(pool.clone(), "test-secret".to_string())
+            .create_user(&CreateUserRequest {
+                username: format!("mover-{}", &Uuid::new_v4().simple().to_string()[..8]),
+                password: "desk-password-1".to_string(),
+                role: UserRole::Receptionist,
+                must_change_password: false,
+            })
+            .unwrap()
+            .id
+    }
+
+    #[test]
+    fn test_checked_in_guest_moves_and_rooms_swap_status() {
+        let Some(pool) = test_pool() else { return };
+        let actor = receptionist(&pool);
+        let (old_room, new_room) = (room(&pool), room(&pool));
+        let service = BookingService::new(pool.clone());
+        let today = service.today().unwrap();
+        let stay = service
+            .create_walk_in(
+                &StaffBookingRequest {
+                    guest_name: format!("Mover {}", old_room.number),
+                    room_id: old_room.id,
+                    check_in_date: today,
+                    check_out_date: today + Duration::days(3),
+                    price: None,
+                    allow_duplicate: false,
+                    override_conflict: false,
+                },
+                actor,
+            )
+            .unwrap();
+
+        let moved = service.move_room(stay.booking.id, new_room.id, actor).unwrap();
+
+        assert_eq!(moved.booking.room_id, new_room.id);
+        assert_eq!(moved.booking.status, BookingStatus::CheckedIn);
+        assert_eq!(moved.room.unwrap().status, RoomStatus::Occupied);
+        assert_eq!(moved.modification_count, 1);
+        let rooms = RoomService::new(pool.clone());
+        assert_eq!(rooms.get_room_by_id(old_room.id).unwrap().status, RoomStatus::Dirty);
+
+        let events = service.list_events(stay.booking.id).unwrap();
+        let last = events.last().unwrap();
+        assert_eq!(last.event_type, "room_move");
+        assert!(last.details.as_deref().unwrap().contains(&new_room.number));
+    }
+
+    #[test]
+    fn test_upcoming_move_leaves_room_statuses_alone() {
+        let Some(pool) = test_pool() else { return };
+        let actor = receptionist(&pool);
+        let (old_room, new_room) = (room(&pool), room(&pool));
+        let service = BookingService::new(pool.clone());
+        let check_in = Utc::now().date_naive() + Duration::days(30);
+        let booking = service
+            .create_booking(
+                &StaffBookingRequest {
+                    guest_name: format!("Mover {}", old_room.number),
+                    room_id: old_room.id,
+                    check_in_date: check_in,
+                    check_out_date: check_in + Duration::days(2),
+                    price: None,
+                    allow_duplicate: false,
+                    override_conflict: false,
+                },
+                None,
+            )
+            .unwrap();
+
+        let moved = service.move_room(booking.id, new_room.id, actor).unwrap();
+
+        assert_eq!(moved.booking.room_id, new_room.id);
+        let rooms = RoomService::new(pool.clone());
+        assert_eq!(rooms.get_room_by_id(old_room.id).unwrap().status, RoomStatus::Available);
+        assert_eq!(rooms.get_room_by_id(new_room.id).unwrap().status, RoomStatus::Available);
+    }
+
+    #[test]
+    fn test_maintenance_and_booked_rooms_are_refused() {
+        let Some(pool) = test_pool() else { return };
+        let actor = receptionist(&pool);
+        let (old_room, booked_room, broken_room) = (room(&pool), room(&pool), room(&pool));
+        let service = BookingService::new(pool.clone());
+        let check_in = Utc::now().date_naive() + Duration::days(30);
+        let booking = service
+            .create_booking(
+                &StaffBookingRequest {
+                    guest_name: format!("Mover {}", old_room.number),
+                    room_id: old_room.id,
+                    check_in_date: check_in,
+                    check_out_date: check_in + Duration::days(2),
+                    price: None,
+                    allow_duplicate: false,
+                    override_conflict: false,
+                },
+                None,
+            )
+            .unwrap();
+        service
+            .create_booking(
+                &StaffBookingRequest {
+                    guest_name: format!("Blocker {}", booked_room.number),
+                    room_id: booked_room.id,
+                    check_in_date: check_in + Duration::days(1),
+                    check_out_date: check_in + Duration::days(4),
+                    price: None,
+                    allow_duplicate: false,
+                    override_conflict: false,
+                },
+                None,
+            )
+            .unwrap();
+        diesel::update(rooms::table.find(broken_room.id))
+            .set(rooms::status.eq(RoomStatus::Maintenance))
+            .execute(&mut pool.get().unwrap())
+            .unwrap();
+
+        match service.move_room(booking.id, broken_room.id, actor) {
+            Err(AppError::RoomUnavailable(msg)) => assert!(msg.contains("maintenance"), "{}", msg),
+            other => panic!("Expected RoomUnavailable, got {:?}", other),
+        }
+        assert!(matches!(
+            service.move_room(booking.id, booked_room.id, actor),
+            Err(AppError::RoomUnavailable(_))
+        ));
+        assert_eq!(service.get_booking_by_id(booking.id).unwrap().room_id, old_room.id);
+    }
+}
@@ -0,0 +1,166 @@
+//! Room overview tests
+//!
+//! Tests for picking the guest currently in a room and its next arrival,
+//! including overstays whose check-out date has passed. The database tests
+//! need a migrated PostgreSQL database and only run when TEST_DATABASE_URL is
+//! set.
+
+use bigdecimal::BigDecimal;
+use chrono::{Duration, NaiveDate, Utc};
+use uuid::Uuid;
+
+use hotel_management_backend::db::{create_pool, DbPool};
+use hotel_management_backend::errors::AppError;
+use hotel_management_backend::models::{BookingStatus, Room, RoomStatus, RoomType};
+use hotel_management_backend::services::booking_service::StaffBookingRequest;
+use hotel_management_backend::services::room_service::RoomOverviewBooking;
+use hotel_management_backend::services::{BookingService, RoomService};
+use hotel_management_backend::settings::{self, Settings};
+use hotel_management_backend::utils::clock::local_date;
+
+fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(y, m, d).unwrap()
+}
+
+fn room() -> Room {
+    Room {
+        id: Uuid::new_v4(),
+        number: "204".to_string(),
+        room_type: RoomType::DOUBLE,
+        status: RoomStatus::Occupied,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+        price: BigDecimal::from(1_500_000),
+        assigned_cleaner_id: None,
+        description: None,
+        amenities: Vec::new(),
+        photo_urls: Vec::new(),
+        decommissioned_at: None,
+        floor: 1,
+        building: None,
+        room_type_id: Uuid::new_v4(),
+    }
+}
+
+fn booking(guest_name: &str, check_in_date: NaiveDate, nights: i64, status: BookingStatus) -> RoomOverviewBooking {
+    RoomOverviewBooking {
+        id: Uuid::new_v4(),
+        reference: format!("BK-{}", guest_name.to_uppercase()),
+        guest_name: guest_name.to_string(),
+        check_in_date,
+        check_out_date: check_in_date + Duration::days(nights),
+        status,
+    }
+}
+
+mod build_tests {
+    use super::*;
+
+    #[test]
+    fn test_current_guest_and_next_arrival() {
+        let today = date(2025, 6, 10);
+        let bookings = [
+            booking("later", date(2025, 6, 20), 2, BookingStatus::Upcoming),
+            booking("staying", date(2025, 6, 9), 3, BookingStatus::CheckedIn),
+            booking("sooner", date(2025, 6, 12), 1, BookingStatus::Upcoming),
+            booking("no-show", date(2025, 6, 8), 1, BookingStatus::Upcoming),
+        ];
+
+        let overview = RoomService::build_overview(room(), &bookings, today);
+        assert_eq!(overview.current_booking.unwrap().guest_name, "staying");
+        assert_eq!(overview.next_booking.unwrap().guest_name, "sooner");
+    }
+
+    #[test]
+    fn test_overstaying_guest_is_still_current() {
+        let today = date(2025, 6, 10);
+        for status in [BookingStatus::Overstay, BookingStatus::CheckedIn] {
+            let bookings = [booking("overstaying", date(2025, 6, 5), 2, status)];
+            let overview = RoomService::build_overview(room(), &bookings, today);
+            assert_eq!(overview.current_booking.unwrap().guest_name, "overstaying");
+            assert_eq!(overview.next_booking, None);
+        }
+    }
+
+    #[test]
+    fn test_empty_room_and_arrival_today() {
+        let today = date(2025, 6, 10);
+        let overview = RoomService::build_overview(room(), &[], today);
+        assert_eq!(overview.current_booking, None);
+        assert_eq!(overview.next_booking, None);
+
+        let bookings = [booking("arriving", today, 1, BookingStatus::Upcoming)];
+        let overview = RoomService::build_overview(room(), &bookings, today);
+        assert_eq!(overview.current_booking, None);
+        assert_eq!(overview.next_booking.unwrap().guest_name, "arriving");
+
+        let json = serde_json::to_value(RoomService::build_overview(room(), &bookings, today)).unwrap();
+        assert_eq!(json["number"], "204");
+        assert_eq!(json["next_booking"]["check_out_date"], "2025-06-11");
+        assert!(json["current_booking"].is_null());
+        assert!(json["next_booking"].get("price").is_none());
+    }
+}
+
+fn test_pool() -> Option<DbPool> {
+    match std::env::var("TEST_DATABASE_URL") {
+        Ok(url) => Some(create_pool(&url)),
+        Err(_) => {
+            eprintln!("TEST_DATABASE_URL not set, skipping database test");
+            None
+        }
+    }
+}
+
+mod service_tests {
+    use super::*;
+
+    #[test]
+    fn test_overview_loads_current_and_next_booking() {
+        let Some(pool) = test_pool() else { return };
+        let tz = Settings::load(&mut pool.get().unwrap())
+            .unwrap()
+            .time_zone(settings::HOTEL_TIMEZONE);
+        let today = local_date(Utc::now(), tz);
+        let number = format!("O{}", &Uuid::new_v4().simple().to_string()[..8]);
+        let rooms = RoomService::new(pool.clone());
+        let room = rooms.create_room(&number, RoomType::SINGLE).unwrap();
+        let bookings = BookingService::new(pool.clone());
+        let book = |guest: &str, check_in_date: NaiveDate| {
+            bookings
+                .create_booking(
+                    &StaffBookingRequest {
+                        guest_name: format!("{} {}", guest, number),
+                        room_id: room.id,
+                        check_in_date,
+                        check_out_date: check_in_date + Duration::days(2),
+                        price: None,
+                        allow_duplicate: false,
+                        override_conflict: false,
+                    },
+                    None,
+                )
+                .unwrap()
+        };
+
+        let empty = rooms.get_room_overview(room.id).unwrap();
+        assert_eq!(empty.room.id, room.id);
+        assert_eq!((empty.current_booking, empty.next_booking), (None, None));
+
+        let staying = book("Staying", today);
+        bookings.check_in(staying.id, Uuid::nil()).unwrap();
+        let later = book("Later", today + Duration::days(9));
+        let sooner = book("Sooner", today + Duration::days(4));
+        let cancelled = book("Cancelled", today + Duration::days(2));
+        bookings.cancel(cancelled.id, Uuid::nil()).unwrap();
+
+        let overview = rooms.get_room_overview(room.id).unwrap();
+        assert_eq!(overview.current_booking.unwrap().reference, staying.reference);
+        let next = overview.next_booking.unwrap();
+        assert_eq!(next.reference, sooner.reference);
+        assert_ne!(next.reference, later.reference);
+        assert_eq!(next.check_in_date, sooner.check_in_date);
+
+        assert!(matches!(rooms.get_room_overview(Uuid::new_v4()), Err(AppError::NotFound(_))));
+    }
+}
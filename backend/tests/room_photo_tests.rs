@@ -0,0 +1,244 @@
+//! Room photo tests
+//!
+//! Tests for checking uploaded photos by their magic bytes and size, keeping
+//! a room to ten photos in order, and the public photo listing. The database
+//! tests need a migrated PostgreSQL database and only run when
+//! TEST_DATABASE_URL is set; none of them talk to object storage.
+
+use std::sync::Arc;
+
+use axum::body::Body;
+use axum::http::{header::AUTHORIZATION, Method, Request, StatusCode};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use hotel_management_backend::api::public_bookings::PublicLookupLimits;
+use hotel_management_backend::api::{chat::ChatState, create_router, AppState};
+use hotel_management_backend::config::BodyLimits;
+use hotel_management_backend::db::{create_pool, DbPool};
+use hotel_management_backend::errors::AppError;
+use hotel_management_backend::models::{Room, RoomType, UserRole};
+use hotel_management_backend::scheduler::JobBoard;
+use hotel_management_backend::services::auth_service::{CreateUserRequest, LoginRequest, DEFAULT_ACCESS_TOKEN_LIFETIME};
+use hotel_management_backend::services::rate_limit_service::InMemoryLoginAttempts;
+use hotel_management_backend::services::room_photo_service::{MAX_CAPTION_LEN, MAX_ROOM_PHOTO_BYTES};
+use hotel_management_backend::services::room_service::MAX_PHOTOS;
+use hotel_management_backend::services::storage_service::image_content_type;
+use hotel_management_backend::services::{
+    AuthService, ReadOnlyMode, RevocationCache, RoomPhotoService, RoomService, SessionTracker, TokenIssuer,
+};
+
+const JWT_SECRET: &str = "test-secret";
+
+const JPEG: &[u8] = &[0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x10, b'J', b'F', b'I', b'F'];
+const PNG: &[u8] = &[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, 0x00, 0x00];
+const WEBP: &[u8] = b"RIFF\x24\x00\x00\x00WEBPVP8 ";
+
+mod validation_tests {
+    use super::*;
+
+    #[test]
+    fn test_type_comes_from_magic_bytes() {
+        assert_eq!(image_content_type(JPEG), Some("image/jpeg"));
+        assert_eq!(image_content_type(PNG), Some("image/png"));
+        assert_eq!(image_content_type(WEBP), Some("image/webp"));
+        assert_eq!(RoomPhotoService::validate_photo(JPEG).unwrap(), "jpg");
+        assert_eq!(RoomPhotoService::validate_photo(PNG).unwrap(), "png");
+        assert_eq!(RoomPhotoService::validate_photo(WEBP).unwrap(), "webp");
+    }
+
+    #[test]
+    fn test_other_files_are_rejected() {
+        let gif = b"GIF89a\x01\x00\x01\x00";
+        let riff_audio = b"RIFF\x24\x00\x00\x00WAVEfmt ";
+        for data in [&gif[..], &riff_audio[..], b"<svg xmlns=", b"RIFF", b""] {
+            assert!(matches!(
+                RoomPhotoService::validate_photo(data),
+                Err(AppError::ValidationError(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_photos_over_five_megabytes_are_rejected() {
+        let mut data = JPEG.to_vec();
+        data.resize(MAX_ROOM_PHOTO_BYTES, 0);
+        assert!(RoomPhotoService::validate_photo(&data).is_ok());
+        data.push(0);
+        assert!(matches!(
+            RoomPhotoService::validate_photo(&data),
+            Err(AppError::PayloadTooLarge(_))
+        ));
+    }
+
+    #[test]
+    fn test_captions_are_trimmed_and_limited() {
+        assert_eq!(
+            RoomPhotoService::validate_caption(Some("  Sea view  ")).unwrap().as_deref(),
+            Some("Sea view")
+        );
+        assert_eq!(RoomPhotoService::validate_caption(Some("   ")).unwrap(), None);
+        assert_eq!(RoomPhotoService::validate_caption(None).unwrap(), None);
+        assert!(RoomPhotoService::validate_caption(Some(&"a".repeat(MAX_CAPTION_LEN + 1))).is_err());
+    }
+}
+
+fn test_pool() -> Option<DbPool> {
+    match std::env::var("TEST_DATABASE_URL") {
+        Ok(url) => Some(create_pool(&url)),
+        Err(_) => {
+            eprintln!("TEST_DATABASE_URL not set, skipping database test");
+            None
+        }
+    }
+}
+
+fn room(pool: &DbPool) -> Room {
+    let number = format!("F{}", &Uuid::new_v4().simple().to_string()[..8]);
+    RoomService::new(pool.clone()).create_room(&number, RoomType::DOUBLE).unwrap()
+}
+
+fn object_key(room: &Room) -> String {
+    format!("{}/{}.jpg", room.id, Uuid::new_v4())
+}
+
+mod service_tests {
+    use super::*;
+
+    #[test]
+    fn test_photos_are_listed_in_upload_order_up_to_the_limit() {
+        let Some(pool) = test_pool() else { return };
+        let room = room(&pool);
+        let service = RoomPhotoService::new(pool.clone());
+
+        for i in 0..MAX_PHOTOS {
+            let photo = service
+                .add_photo(room.id, &object_key(&room), Some(format!("Photo {}", i)))
+                .unwrap();
+            assert_eq!(photo.sort_order, i as i32);
+        }
+        assert!(matches!(service.ensure_room_has_space(room.id), Err(AppError::Conflict(_))));
+        assert!(matches!(
+            service.add_photo(room.id, &object_key(&room), None),
+            Err(AppError::Conflict(_))
+        ));
+
+        let photos = service.list_photos(room.id).unwrap();
+        assert_eq!(photos.len(), MAX_PHOTOS);
+        assert_eq!(photos[0].caption.as_deref(), Some("Photo 0"));
+
+        // Removing one makes room for another, placed last
+        service.delete_photo(room.id, photos[0].id).unwrap();
+        service.ensure_room_has_space(room.id).unwrap();
+        let added = service.add_photo(room.id, &object_key(&room), None).unwrap();
+        assert_eq!(added.sort_order, MAX_PHOTOS as i32);
+    }
+
+    #[test]
+    fn test_photos_belong_to_their_room() {
+        let Some(pool) = test_pool() else { return };
+        let room = room(&pool);
+        let other = self::room(&pool);
+        let service = RoomPhotoService::new(pool.clone());
+        let photo = service.add_photo(room.id, &object_key(&room), None).unwrap();
+
+        assert!(matches!(service.get_photo(other.id, photo.id), Err(AppError::NotFound(_))));
+        assert!(matches!(service.delete_photo(other.id, photo.id), Err(AppError::NotFound(_))));
+        assert_eq!(service.get_photo(room.id, photo.id).unwrap().object_key, photo.object_key);
+        assert!(matches!(service.list_photos(Uuid::new_v4()), Err(AppError::NotFound(_))));
+        assert!(matches!(
+            service.ensure_room_has_space(Uuid::new_v4()),
+            Err(AppError::NotFound(_))
+        ));
+    }
+}
+
+fn router(pool: DbPool) -> axum::Router {
+    let s3_config = aws_sdk_s3::config::Builder::new()
+        .region(aws_sdk_s3::config::Region::new("us-east-1"))
+        .behavior_version_latest()
+        .build();
+
+    create_router(AppState {
+        pool,
+        jwt_secret: JWT_SECRET.to_string(),
+        chat_state: Arc::new(ChatState::default()),
+        s3_client: aws_sdk_s3::Client::from_conf(s3_config),
+        read_only: Arc::new(ReadOnlyMode::new(Some(false))),
+        public_lookup: Arc::new(PublicLookupLimits::default()),
+        jobs: Arc::new(JobBoard::default()),
+        body_limits: BodyLimits::default(),
+        sessions: Arc::new(SessionTracker::default()),
+        access_token_lifetime: DEFAULT_ACCESS_TOKEN_LIFETIME,
+        login_attempts: Arc::new(InMemoryLoginAttempts::default()),
+        revocations: Arc::new(RevocationCache::default()),
+        trust_proxy_headers: false,
+        token_issuer: TokenIssuer::default(),
+    })
+}
+
+mod endpoint_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_photo_list_is_public_with_urls() {
+        let Some(pool) = test_pool() else { return };
+        let room = room(&pool);
+        let key = object_key(&room);
+        RoomPhotoService::new(pool.clone())
+            .add_photo(room.id, &key, Some("Balcony".to_string()))
+            .unwrap();
+
+        let request = Request::builder()
+            .uri(format!("/rooms/{}/photos", room.id))
+            .body(Body::empty())
+            .unwrap();
+        let response = router(pool.clone()).oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let photos: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(photos[0]["caption"], "Balcony");
+        let url = photos[0]["url"].as_str().unwrap();
+        assert!(url.ends_with(&format!("/room-photos/{}", key)), "{}", url);
+        assert!(photos[0].get("object_key").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_receptionists_cannot_change_photos() {
+        let Some(pool) = test_pool() else { return };
+        let room = room(&pool);
+        let photo = RoomPhotoService::new(pool.clone())
+            .add_photo(room.id, &object_key(&room), None)
+            .unwrap();
+        let auth = AuthService::new(pool.clone(), JWT_SECRET.to_string());
+        let username = format!("photo-{}", &Uuid::new_v4().simple().to_string()[..8]);
+        auth.create_user(&CreateUserRequest {
+            username: username.clone(),
+            password: "desk-password-1".to_string(),
+            role: UserRole::Receptionist,
+            must_change_password: false,
+        })
+        .unwrap();
+        let token = auth
+            .login(&LoginRequest {
+                username,
+                password: "desk-password-1".to_string(),
+            })
+            .unwrap()
+            .token;
+
+        for (method, uri) in [
+            (Method::POST, format!("/admin/rooms/{}/photos", room.id)),
+            (Method::DELETE, format!("/admin/rooms/{}/photos/{}", room.id, photo.id)),
+        ] {
+            let request = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header(AUTHORIZATION, format!("Bearer {}", token))
+                .body(Body::empty())
+                .unwrap();
+            let response = router(pool.clone()).oneshot(request).await.unwrap();
+            assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        }
+    }
+}
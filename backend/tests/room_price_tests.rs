@@ -0,0 +1,213 @@
+//! Room price tests
+//!
+//! Tests for validating nightly room prices, recording each change in the
+//! price history, and keeping price changes to admins. The database tests
+//! need a migrated PostgreSQL database and only run when TEST_DATABASE_URL is
+//! set.
+
+use std::str::FromStr;
+use std::sync::Arc;
+
+use axum::body::Body;
+use axum::http::{header::AUTHORIZATION, header::CONTENT_TYPE, Method, Request, StatusCode};
+use bigdecimal::BigDecimal;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use hotel_management_backend::api::public_bookings::PublicLookupLimits;
+use hotel_management_backend::api::{chat::ChatState, create_router, AppState};
+use hotel_management_backend::config::BodyLimits;
+use hotel_management_backend::db::{create_pool, DbPool};
+use hotel_management_backend::errors::{AppError, AppResult};
+use hotel_management_backend::models::{Room, RoomStatus, RoomType, UserRole};
+use hotel_management_backend::scheduler::JobBoard;
+use hotel_management_backend::services::auth_service::{CreateUserRequest, LoginRequest, DEFAULT_ACCESS_TOKEN_LIFETIME};
+use hotel_management_backend::services::rate_limit_service::InMemoryLoginAttempts;
+use hotel_management_backend::services::room_service::{RoomEdit, MAX_ROOM_PRICE};
+use hotel_management_backend::services::{AuthService, ReadOnlyMode, RevocationCache, RoomService, SessionTracker, TokenIssuer};
+
+const JWT_SECRET: &str = "test-secret";
+
+fn vnd(amount: &str) -> BigDecimal {
+    BigDecimal::from_str(amount).unwrap()
+}
+
+mod validation_tests {
+    use super::*;
+
+    #[test]
+    fn test_whole_positive_prices_are_accepted() {
+        assert_eq!(RoomService::validate_price(&vnd("1.2E+6")).unwrap(), vnd("1200000"));
+        assert!(RoomService::validate_price(&BigDecimal::from(MAX_ROOM_PRICE)).is_ok());
+    }
+
+    #[test]
+    fn test_zero_negative_fractional_and_oversized_prices_are_rejected() {
+        for price in ["0", "-500000", "1500000.5", "1000000000000"] {
+            match RoomService::validate_price(&vnd(price)) {
+                Err(AppError::FieldErrors(fields)) => assert!(fields.contains_key("price")),
+                other => panic!("Expected a price field error for {}, got {:?}", price, other),
+            }
+        }
+    }
+}
+
+fn test_pool() -> Option<DbPool> {
+    match std::env::var("TEST_DATABASE_URL") {
+        Ok(url) => Some(create_pool(&url)),
+        Err(_) => {
+            eprintln!("TEST_DATABASE_URL not set, skipping database test");
+            None
+        }
+    }
+}
+
+fn room(pool: &DbPool) -> Room {
+    let number = format!("P{}", &Uuid::new_v4().simple().to_string()[..8]);
+    RoomService::new(pool.clone()).create_room(&number, RoomType::SUITE).unwrap()
+}
+
+/// Receptionist id and a login token
+fn receptionist(pool: &DbPool) -> (Uuid, String) {
+    let auth = AuthService::new(pool.clone(), JWT_SECRET.to_string());
+    let username = format!("price-{}", &Uuid::new_v4().simple().to_string()[..8]);
+    let user = auth
+        .create_user(&CreateUserRequest {
+            username: username.clone(),
+            password: "desk-password-1".to_string(),
+            role: UserRole::Receptionist,
+            must_change_password: false,
+        })
+        .unwrap();
+    let token = auth
+        .login(&LoginRequest {
+            username,
+            password: "desk-password-1".to_string(),
+        })
+        .unwrap()
+        .token;
+    (user.id, token)
+}
+
+mod history_tests {
+    use super::*;
+
+    fn set_price(pool: &DbPool, room: &Room, price: &str, changed_by: Uuid) -> AppResult<Room> {
+        RoomService::new(pool.clone()).update_room(
+            room.id,
+            RoomEdit {
+                price: Some(vnd(price)),
+                ..RoomEdit::default()
+            },
+            Some(changed_by),
+        )
+    }
+
+    #[test]
+    fn test_each_change_is_recorded_newest_first() {
+        let Some(pool) = test_pool() else { return };
+        let room = room(&pool);
+        // Only one admin may exist, so a receptionist's id stands in for one
+        let (admin_stand_in, _) = receptionist(&pool);
+        let original = room.price.clone();
+
+        let updated = set_price(&pool, &room, "2345000", admin_stand_in).unwrap();
+        assert_eq!(updated.price, vnd("2345000"));
+        set_price(&pool, &room, "2890000", admin_stand_in).unwrap();
+        // The same price again is not a change
+        set_price(&pool, &room, "2890000", admin_stand_in).unwrap();
+
+        let history = RoomService::new(pool.clone()).get_price_history(room.id).unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].old_price, vnd("2345000"));
+        assert_eq!(history[0].new_price, vnd("2890000"));
+        assert_eq!(history[1].old_price, original);
+        assert_eq!(history[1].changed_by, Some(admin_stand_in));
+        assert!(history[0].changed_at >= history[1].changed_at);
+    }
+
+    #[test]
+    fn test_invalid_price_changes_nothing() {
+        let Some(pool) = test_pool() else { return };
+        let room = room(&pool);
+        let (user_id, _) = receptionist(&pool);
+
+        assert!(matches!(set_price(&pool, &room, "0", user_id), Err(AppError::FieldErrors(_))));
+        let service = RoomService::new(pool.clone());
+        assert_eq!(service.get_room_by_id(room.id).unwrap().price, room.price);
+        assert!(service.get_price_history(room.id).unwrap().is_empty());
+        assert!(matches!(service.get_price_history(Uuid::new_v4()), Err(AppError::NotFound(_))));
+    }
+}
+
+fn router(pool: DbPool) -> axum::Router {
+    let s3_config = aws_sdk_s3::config::Builder::new()
+        .region(aws_sdk_s3::config::Region::new("us-east-1"))
+        .behavior_version_latest()
+        .build();
+
+    create_router(AppState {
+        pool,
+        jwt_secret: JWT_SECRET.to_string(),
+        chat_state: Arc::new(ChatState::default()),
+        s3_client: aws_sdk_s3::Client::from_conf(s3_config),
+        read_only: Arc::new(ReadOnlyMode::new(Some(false))),
+        public_lookup: Arc::new(PublicLookupLimits::default()),
+        jobs: Arc::new(JobBoard::default()),
+        body_limits: BodyLimits::default(),
+        sessions: Arc::new(SessionTracker::default()),
+        access_token_lifetime: DEFAULT_ACCESS_TOKEN_LIFETIME,
+        login_attempts: Arc::new(InMemoryLoginAttempts::default()),
+        revocations: Arc::new(RevocationCache::default()),
+        trust_proxy_headers: false,
+        token_issuer: TokenIssuer::default(),
+    })
+}
+
+async fn send(pool: &DbPool, method: Method, uri: &str, token: &str, body: Option<serde_json::Value>) -> StatusCode {
+    let body = body.map_or(Body::empty(), |json| Body::from(json.to_string()));
+    let request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .header(CONTENT_TYPE, "application/json")
+        .body(body)
+        .unwrap();
+    router(pool.clone()).oneshot(request).await.unwrap().status()
+}
+
+mod endpoint_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_receptionist_may_change_status_but_not_price() {
+        let Some(pool) = test_pool() else { return };
+        let room = room(&pool);
+        let (_, token) = receptionist(&pool);
+        let uri = format!("/rooms/{}", room.id);
+
+        let status = send(
+            &pool,
+            Method::PATCH,
+            &uri,
+            &token,
+            Some(serde_json::json!({ "price": 3_000_000 })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        let status = send(
+            &pool,
+            Method::PATCH,
+            &uri,
+            &token,
+            Some(serde_json::json!({ "status": RoomStatus::Dirty })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+
+        let history_uri = format!("/admin/rooms/{}/price-history", room.id);
+        let status = send(&pool, Method::GET, &history_uri, &token, None).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+    }
+}
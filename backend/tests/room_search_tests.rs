@@ -0,0 +1,197 @@
+//! Room search tests
+//!
+//! Tests for the reception desk's room number search: prefix matching, the
+//! guest staying in each room, and the staff-only endpoint. The database
+//! tests need a migrated PostgreSQL database and only run when
+//! TEST_DATABASE_URL is set.
+
+use std::sync::Arc;
+
+use axum::body::{to_bytes, Body};
+use axum::http::{header::AUTHORIZATION, Method, Request, StatusCode};
+use chrono::Duration;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use hotel_management_backend::api::public_bookings::PublicLookupLimits;
+use hotel_management_backend::api::{chat::ChatState, create_router, AppState};
+use hotel_management_backend::config::BodyLimits;
+use hotel_management_backend::db::{create_pool, DbPool};
+use hotel_management_backend::errors::AppError;
+use hotel_management_backend::models::{RoomStatus, RoomType, UserRole};
+use hotel_management_backend::scheduler::JobBoard;
+use hotel_management_backend::services::auth_service::{CreateUserRequest, LoginRequest, DEFAULT_ACCESS_TOKEN_LIFETIME};
+use hotel_management_backend::services::booking_service::StaffBookingRequest;
+use hotel_management_backend::services::rate_limit_service::InMemoryLoginAttempts;
+use hotel_management_backend::services::room_service::ROOM_SEARCH_LIMIT;
+use hotel_management_backend::services::{
+    AuthService, BookingService, ReadOnlyMode, RevocationCache, RoomService, SessionTracker, TokenIssuer,
+};
+use hotel_management_backend::utils::validate_search_query;
+
+const JWT_SECRET: &str = "test-secret";
+
+mod validation_tests {
+    use super::*;
+
+    #[test]
+    fn test_a_single_digit_is_a_search() {
+        for q in ["2", " 7 ", "20", "ab"] {
+            assert!(validate_search_query(q).is_ok(), "{:?}", q);
+        }
+        for q in ["", "  ", "a", "%"] {
+            assert!(matches!(validate_search_query(q), Err(AppError::ValidationError(_))), "{:?}", q);
+        }
+    }
+}
+
+fn test_pool() -> Option<DbPool> {
+    match std::env::var("TEST_DATABASE_URL") {
+        Ok(url) => Some(create_pool(&url)),
+        Err(_) => {
+            eprintln!("TEST_DATABASE_URL not set, skipping database test");
+            None
+        }
+    }
+}
+
+mod service_tests {
+    use super::*;
+
+    #[test]
+    fn test_search_matches_number_prefix_with_guest() {
+        let Some(pool) = test_pool() else { return };
+        let rooms = RoomService::new(pool.clone());
+        let prefix = format!("Q{}", &Uuid::new_v4().simple().to_string()[..6]);
+        let created: Vec<_> = (0..=ROOM_SEARCH_LIMIT)
+            .rev()
+            .map(|n| rooms.create_room(&format!("{}{:02}", prefix, n), RoomType::SINGLE).unwrap())
+            .collect();
+        let first = created.last().unwrap();
+        rooms.decommission_room(created[0].id).unwrap();
+
+        let actor = AuthService::new(pool.clone(), JWT_SECRET.to_string())
+            .create_user(&CreateUserRequest {
+                username: format!("search-{}", &prefix[1..]),
+                password: "desk-password-1".to_string(),
+                role: UserRole::Receptionist,
+                must_change_password: false,
+            })
+            .unwrap()
+            .id;
+        let bookings = BookingService::new(pool.clone());
+        let today = bookings.today().unwrap();
+        bookings
+            .create_walk_in(
+                &StaffBookingRequest {
+                    guest_name: format!("Guest {}", prefix),
+                    room_id: first.id,
+                    check_in_date: today,
+                    check_out_date: today + Duration::days(1),
+                    price: None,
+                    allow_duplicate: true,
+                    override_conflict: false,
+                },
+                actor,
+            )
+            .unwrap();
+
+        // Case does not matter; the decommissioned last room is left out
+        let found = rooms.search_rooms(&prefix.to_lowercase()).unwrap();
+        assert_eq!(found.len() as i64, ROOM_SEARCH_LIMIT);
+        assert!(found.windows(2).all(|w| w[0].number < w[1].number));
+        assert_eq!(found[0].number, first.number);
+        assert_eq!(found[0].status, RoomStatus::Occupied);
+        assert_eq!(found[0].guest_name.as_deref(), Some(format!("Guest {}", prefix).as_str()));
+        assert!(found[1..].iter().all(|r| r.guest_name.is_none()));
+        assert!(!found.iter().any(|r| r.id == created[0].id));
+
+        let narrowed = rooms.search_rooms(&format!("{}1", prefix)).unwrap();
+        assert_eq!(narrowed.len(), 10);
+        assert!(rooms.search_rooms(&format!("x{}", prefix)).unwrap().is_empty());
+    }
+}
+
+fn router(pool: DbPool) -> axum::Router {
+    let s3_config = aws_sdk_s3::config::Builder::new()
+        .region(aws_sdk_s3::config::Region::new("us-east-1"))
+        .behavior_version_latest()
+        .build();
+
+    create_router(AppState {
+        pool,
+        jwt_secret: JWT_SECRET.to_string(),
+        chat_state: Arc::new(ChatState::default()),
+        s3_client: aws_sdk_s3::Client::from_conf(s3_config),
+        read_only: Arc::new(ReadOnlyMode::new(Some(false))),
+        public_lookup: Arc::new(PublicLookupLimits::default()),
+        jobs: Arc::new(JobBoard::default()),
+        body_limits: BodyLimits::default(),
+        sessions: Arc::new(SessionTracker::default()),
+        access_token_lifetime: DEFAULT_ACCESS_TOKEN_LIFETIME,
+        login_attempts: Arc::new(InMemoryLoginAttempts::default()),
+        revocations: Arc::new(RevocationCache::default()),
+        trust_proxy_headers: false,
+        token_issuer: TokenIssuer::default(),
+    })
+}
+
+/// Login token of a new user with the given role
+fn token(pool: &DbPool, role: UserRole) -> String {
+    let auth = AuthService::new(pool.clone(), JWT_SECRET.to_string());
+    let username = format!("search-{}", &Uuid::new_v4().simple().to_string()[..8]);
+    auth.create_user(&CreateUserRequest {
+        username: username.clone(),
+        password: "desk-password-1".to_string(),
+        role,
+        must_change_password: false,
+    })
+    .unwrap();
+    auth.login(&LoginRequest {
+        username,
+        password: "desk-password-1".to_string(),
+    })
+    .unwrap()
+    .token
+}
+
+async fn search(pool: &DbPool, q: &str, token: Option<&str>) -> (StatusCode, serde_json::Value) {
+    let mut request = Request::builder()
+        .method(Method::GET)
+        .uri(format!("/rooms/search?q={}", q));
+    if let Some(token) = token {
+        request = request.header(AUTHORIZATION, format!("Bearer {}", token));
+    }
+    let response = router(pool.clone())
+        .oneshot(request.body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&bytes).unwrap_or(serde_json::Value::Null))
+}
+
+mod endpoint_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_search_is_for_staff() {
+        let Some(pool) = test_pool() else { return };
+        let number = format!("Q{}", &Uuid::new_v4().simple().to_string()[..8]);
+        RoomService::new(pool.clone()).create_room(&number, RoomType::DOUBLE).unwrap();
+
+        let receptionist = token(&pool, UserRole::Receptionist);
+        let (status, rooms) = search(&pool, &number, Some(&receptionist)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(rooms[0]["number"], number.as_str());
+        assert_eq!(rooms[0]["status"], "available");
+        assert_eq!(rooms[0]["guest_name"], serde_json::Value::Null);
+
+        let (status, _) = search(&pool, "x", Some(&receptionist)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let (status, _) = search(&pool, &number, Some(&token(&pool, UserRole::Cleaner))).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        let (status, _) = search(&pool, &number, None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+}
@@ -0,0 +1,268 @@
+//! Room status history tests
+//!
+//! Tests for recording who changed a room's status: room edits, the cleaner
+//! dashboard, and check-in/check-out by the receptionist, plus the paged
+//! history endpoint. They need a migrated PostgreSQL database and only run
+//! when TEST_DATABASE_URL is set.
+
+use std::sync::Arc;
+
+use axum::body::Body;
+use axum::http::{header::AUTHORIZATION, header::CONTENT_TYPE, Method, Request, StatusCode};
+use chrono::Duration;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use hotel_management_backend::api::public_bookings::PublicLookupLimits;
+use hotel_management_backend::api::{chat::ChatState, create_router, AppState};
+use hotel_management_backend::config::BodyLimits;
+use hotel_management_backend::db::{create_pool, DbPool};
+use hotel_management_backend::errors::AppError;
+use hotel_management_backend::models::{Room, RoomStatus, RoomType, UserRole};
+use hotel_management_backend::scheduler::JobBoard;
+use hotel_management_backend::services::auth_service::{CreateUserRequest, LoginRequest, DEFAULT_ACCESS_TOKEN_LIFETIME};
+use hotel_management_backend::services::booking_service::StaffBookingRequest;
+use hotel_management_backend::services::rate_limit_service::InMemoryLoginAttempts;
+use hotel_management_backend::services::room_service::RoomEdit;
+use hotel_management_backend::services::{
+    AuthService, BookingService, ReadOnlyMode, RevocationCache, RoomService, SessionTracker, TokenIssuer,
+};
+
+const JWT_SECRET: &str = "test-secret";
+
+fn test_pool() -> Option<DbPool> {
+    match std::env::var("TEST_DATABASE_URL") {
+        Ok(url) => Some(create_pool(&url)),
+        Err(_) => {
+            eprintln!("TEST_DATABASE_URL not set, skipping database test");
+            None
+        }
+    }
+}
+
+fn room(pool: &DbPool) -> Room {
+    let number = format!("H{}", &Uuid::new_v4().simple().to_string()[..8]);
+    RoomService::new(pool.clone()).create_room(&number, RoomType::DOUBLE).unwrap()
+}
+
+/// A user's id, username and login token
+fn user(pool: &DbPool, role: UserRole) -> (Uuid, String, String) {
+    let auth = AuthService::new(pool.clone(), JWT_SECRET.to_string());
+    let username = format!("status-{}", &Uuid::new_v4().simple().to_string()[..8]);
+    let user = auth
+        .create_user(&CreateUserRequest {
+            username: username.clone(),
+            password: "staff-password-1".to_string(),
+            role,
+            must_change_password: false,
+        })
+        .unwrap();
+    let token = auth
+        .login(&LoginRequest {
+            username: username.clone(),
+            password: "staff-password-1".to_string(),
+        })
+        .unwrap()
+        .token;
+    (user.id, username, token)
+}
+
+mod service_tests {
+    use super::*;
+
+    #[test]
+    fn test_stay_records_the_receptionist_for_each_transition() {
+        let Some(pool) = test_pool() else { return };
+        let room = room(&pool);
+        let (desk_id, desk_name, _) = user(&pool, UserRole::Receptionist);
+        let bookings = BookingService::new(pool.clone());
+        let today = bookings.today().unwrap();
+
+        let stay = bookings
+            .create_walk_in(
+                &StaffBookingRequest {
+                    guest_name: format!("History {}", room.number),
+                    room_id: room.id,
+                    check_in_date: today,
+                    check_out_date: today + Duration::days(1),
+                    price: None,
+                    allow_duplicate: false,
+                    override_conflict: false,
+                },
+                desk_id,
+            )
+            .unwrap();
+        bookings.check_out(stay.booking.id, true, desk_id).unwrap();
+
+        let service = RoomService::new(pool.clone());
+        let history = service.get_status_history(room.id, None, None, None, None).unwrap();
+        let transitions: Vec<(RoomStatus, RoomStatus)> = history
+            .events
+            .iter()
+            .map(|e| (e.event.from_status, e.event.to_status))
+            .collect();
+        // Newest first
+        assert_eq!(
+            transitions,
+            vec![
+                (RoomStatus::Occupied, RoomStatus::Dirty),
+                (RoomStatus::Available, RoomStatus::Occupied),
+            ]
+        );
+        assert!(history.events.iter().all(|e| e.event.actor_user_id == Some(desk_id)));
+        assert!(history.events.iter().all(|e| e.actor_username.as_deref() == Some(desk_name.as_str())));
+    }
+
+    #[test]
+    fn test_system_changes_have_no_actor_and_unchanged_status_is_not_recorded() {
+        let Some(pool) = test_pool() else { return };
+        let room = room(&pool);
+        let service = RoomService::new(pool.clone());
+
+        service.update_room_status(room.id, RoomStatus::Maintenance, None).unwrap();
+        service
+            .update_room(
+                room.id,
+                RoomEdit {
+                    status: Some(RoomStatus::Maintenance),
+                    ..RoomEdit::default()
+                },
+                None,
+            )
+            .unwrap();
+
+        let history = service.get_status_history(room.id, None, None, None, None).unwrap();
+        assert_eq!(history.total, 1);
+        assert_eq!(history.events[0].event.actor_user_id, None);
+        assert_eq!(history.events[0].actor_username, None);
+    }
+
+    #[test]
+    fn test_pages_and_date_range() {
+        let Some(pool) = test_pool() else { return };
+        let room = room(&pool);
+        let (desk_id, _, _) = user(&pool, UserRole::Receptionist);
+        let service = RoomService::new(pool.clone());
+        for status in [RoomStatus::Dirty, RoomStatus::Cleaning, RoomStatus::Available] {
+            service
+                .update_room(
+                    room.id,
+                    RoomEdit {
+                        status: Some(status),
+                        ..RoomEdit::default()
+                    },
+                    Some(desk_id),
+                )
+                .unwrap();
+        }
+
+        let first = service.get_status_history(room.id, None, None, Some(1), Some(2)).unwrap();
+        assert_eq!(first.total, 3);
+        assert_eq!(first.events.len(), 2);
+        assert_eq!(first.events[0].event.to_status, RoomStatus::Available);
+        let second = service.get_status_history(room.id, None, None, Some(2), Some(2)).unwrap();
+        assert_eq!(second.events.len(), 1);
+        assert_eq!(second.events[0].event.to_status, RoomStatus::Dirty);
+
+        // Hotel days around now hold every change; a week from now holds none
+        let today = BookingService::new(pool.clone()).today().unwrap();
+        let around = service
+            .get_status_history(room.id, Some(today - Duration::days(1)), Some(today + Duration::days(1)), None, None)
+            .unwrap();
+        assert_eq!(around.total, 3);
+        let later = service
+            .get_status_history(room.id, Some(today + Duration::days(7)), None, None, None)
+            .unwrap();
+        assert_eq!(later.total, 0);
+
+        assert!(matches!(
+            service.get_status_history(room.id, Some(today), Some(today - Duration::days(1)), None, None),
+            Err(AppError::ValidationError(_))
+        ));
+        assert!(matches!(
+            service.get_status_history(Uuid::new_v4(), None, None, None, None),
+            Err(AppError::NotFound(_))
+        ));
+    }
+}
+
+fn router(pool: DbPool) -> axum::Router {
+    let s3_config = aws_sdk_s3::config::Builder::new()
+        .region(aws_sdk_s3::config::Region::new("us-east-1"))
+        .behavior_version_latest()
+        .build();
+
+    create_router(AppState {
+        pool,
+        jwt_secret: JWT_SECRET.to_string(),
+        chat_state: Arc::new(ChatState::default()),
+        s3_client: aws_sdk_s3::Client::from_conf(s3_config),
+        read_only: Arc::new(ReadOnlyMode::new(Some(false))),
+        public_lookup: Arc::new(PublicLookupLimits::default()),
+        jobs: Arc::new(JobBoard::default()),
+        body_limits: BodyLimits::default(),
+        sessions: Arc::new(SessionTracker::default()),
+        access_token_lifetime: DEFAULT_ACCESS_TOKEN_LIFETIME,
+        login_attempts: Arc::new(InMemoryLoginAttempts::default()),
+        revocations: Arc::new(RevocationCache::default()),
+        trust_proxy_headers: false,
+        token_issuer: TokenIssuer::default(),
+    })
+}
+
+async fn send(
+    pool: &DbPool,
+    method: Method,
+    uri: &str,
+    token: &str,
+    body: Option<serde_json::Value>,
+) -> (StatusCode, serde_json::Value) {
+    let body = body.map_or(Body::empty(), |json| Body::from(json.to_string()));
+    let request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .header(CONTENT_TYPE, "application/json")
+        .body(body)
+        .unwrap();
+    let response = router(pool.clone()).oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&bytes).unwrap_or(serde_json::Value::Null))
+}
+
+mod endpoint_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_cleaner_change_shows_in_the_staff_history() {
+        let Some(pool) = test_pool() else { return };
+        let room = room(&pool);
+        RoomService::new(pool.clone())
+            .update_room_status(room.id, RoomStatus::Dirty, None)
+            .unwrap();
+        let (cleaner_id, cleaner_name, cleaner_token) = user(&pool, UserRole::Cleaner);
+        let (_, _, desk_token) = user(&pool, UserRole::Receptionist);
+
+        let (status, _) = send(
+            &pool,
+            Method::PATCH,
+            &format!("/cleaner/rooms/{}/status", room.id),
+            &cleaner_token,
+            Some(serde_json::json!({ "status": "cleaning" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+
+        let uri = format!("/rooms/{}/status-history?per_page=1", room.id);
+        let (status, body) = send(&pool, Method::GET, &uri, &desk_token, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["total"], 2);
+        assert_eq!(body["events"][0]["to_status"], "cleaning");
+        assert_eq!(body["events"][0]["actor_user_id"], cleaner_id.to_string());
+        assert_eq!(body["events"][0]["actor_username"], cleaner_name.as_str());
+
+        let (status, _) = send(&pool, Method::GET, &uri, &cleaner_token, None).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+    }
+}
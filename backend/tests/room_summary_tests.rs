@@ -0,0 +1,175 @@
+//! Room summary tests
+//!
+//! Tests for the room counts behind the dashboard tiles: folding grouped
+//! counts into totals per status and per room type, and serving them to
+//! staff only. The database tests need a migrated PostgreSQL database and
+//! only run when TEST_DATABASE_URL is set.
+
+use std::sync::Arc;
+
+use axum::body::{to_bytes, Body};
+use axum::http::{header::AUTHORIZATION, Method, Request, StatusCode};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use hotel_management_backend::api::public_bookings::PublicLookupLimits;
+use hotel_management_backend::api::{chat::ChatState, create_router, AppState};
+use hotel_management_backend::config::BodyLimits;
+use hotel_management_backend::db::{create_pool, DbPool};
+use hotel_management_backend::models::{RoomStatus, RoomType, UserRole};
+use hotel_management_backend::scheduler::JobBoard;
+use hotel_management_backend::services::auth_service::{CreateUserRequest, LoginRequest, DEFAULT_ACCESS_TOKEN_LIFETIME};
+use hotel_management_backend::services::rate_limit_service::InMemoryLoginAttempts;
+use hotel_management_backend::services::room_service::RoomFilter;
+use hotel_management_backend::services::{AuthService, ReadOnlyMode, RevocationCache, RoomService, SessionTracker, TokenIssuer};
+
+const JWT_SECRET: &str = "test-secret";
+
+mod build_tests {
+    use super::*;
+
+    #[test]
+    fn test_counts_fold_by_status_and_type() {
+        let counts = [
+            (RoomStatus::Available, RoomType::SINGLE, 7),
+            (RoomStatus::Available, RoomType::SUITE, 5),
+            (RoomStatus::Occupied, RoomType::SINGLE, 4),
+            (RoomStatus::Dirty, RoomType::DOUBLE, 3),
+            (RoomStatus::Occupied, RoomType::DOUBLE, 1),
+        ];
+        let summary = RoomService::build_status_summary(&counts);
+
+        assert_eq!(summary.total, 20);
+        assert_eq!(summary.by_status["available"], 12);
+        assert_eq!(summary.by_status["occupied"], 5);
+        assert_eq!(summary.by_status["dirty"], 3);
+        // Statuses without rooms still get a tile
+        assert_eq!(summary.by_status["maintenance"], 0);
+        assert_eq!(summary.by_status["cleaning"], 0);
+        assert_eq!(summary.by_status.len(), RoomStatus::ALL.len());
+
+        assert_eq!(summary.by_room_type[&RoomType::SINGLE], 11);
+        assert_eq!(summary.by_room_type[&RoomType::DOUBLE], 4);
+        assert_eq!(summary.by_room_type[&RoomType::SUITE], 5);
+    }
+
+    #[test]
+    fn test_no_rooms_is_all_zeroes() {
+        let summary = RoomService::build_status_summary(&[]);
+        assert_eq!(summary.total, 0);
+        assert!(summary.by_status.values().all(|&count| count == 0));
+        assert!(summary.by_room_type.is_empty());
+
+        let json = serde_json::to_value(&summary).unwrap();
+        assert_eq!(json["by_status"]["available"], 0);
+        assert_eq!(json["by_room_type"], serde_json::json!({}));
+    }
+}
+
+fn test_pool() -> Option<DbPool> {
+    match std::env::var("TEST_DATABASE_URL") {
+        Ok(url) => Some(create_pool(&url)),
+        Err(_) => {
+            eprintln!("TEST_DATABASE_URL not set, skipping database test");
+            None
+        }
+    }
+}
+
+mod service_tests {
+    use super::*;
+
+    #[test]
+    fn test_summary_matches_the_room_list() {
+        let Some(pool) = test_pool() else { return };
+        let service = RoomService::new(pool.clone());
+        let number = format!("S{}", &Uuid::new_v4().simple().to_string()[..8]);
+        let room = service.create_room(&number, RoomType::SUITE).unwrap();
+        service.decommission_room(room.id).unwrap();
+
+        let summary = service.status_summary().unwrap();
+        let rooms = service.list_rooms(&RoomFilter::default()).unwrap();
+        assert_eq!(summary.total, rooms.len() as i64);
+        for status in RoomStatus::ALL {
+            let listed = rooms.iter().filter(|r| r.status == status).count() as i64;
+            assert_eq!(summary.by_status[status.as_str()], listed, "{}", status);
+        }
+        assert_eq!(summary.by_room_type.values().sum::<i64>(), summary.total);
+    }
+}
+
+fn router(pool: DbPool) -> axum::Router {
+    let s3_config = aws_sdk_s3::config::Builder::new()
+        .region(aws_sdk_s3::config::Region::new("us-east-1"))
+        .behavior_version_latest()
+        .build();
+
+    create_router(AppState {
+        pool,
+        jwt_secret: JWT_SECRET.to_string(),
+        chat_state: Arc::new(ChatState::default()),
+        s3_client: aws_sdk_s3::Client::from_conf(s3_config),
+        read_only: Arc::new(ReadOnlyMode::new(Some(false))),
+        public_lookup: Arc::new(PublicLookupLimits::default()),
+        jobs: Arc::new(JobBoard::default()),
+        body_limits: BodyLimits::default(),
+        sessions: Arc::new(SessionTracker::default()),
+        access_token_lifetime: DEFAULT_ACCESS_TOKEN_LIFETIME,
+        login_attempts: Arc::new(InMemoryLoginAttempts::default()),
+        revocations: Arc::new(RevocationCache::default()),
+        trust_proxy_headers: false,
+        token_issuer: TokenIssuer::default(),
+    })
+}
+
+/// Login token of a new user with the given role
+fn token(pool: &DbPool, role: UserRole) -> String {
+    let auth = AuthService::new(pool.clone(), JWT_SECRET.to_string());
+    let username = format!("summary-{}", &Uuid::new_v4().simple().to_string()[..8]);
+    auth.create_user(&CreateUserRequest {
+        username: username.clone(),
+        password: "desk-password-1".to_string(),
+        role,
+        must_change_password: false,
+    })
+    .unwrap();
+    auth.login(&LoginRequest {
+        username,
+        password: "desk-password-1".to_string(),
+    })
+    .unwrap()
+    .token
+}
+
+async fn get_summary(pool: &DbPool, token: Option<&str>) -> (StatusCode, serde_json::Value) {
+    let mut request = Request::builder().method(Method::GET).uri("/rooms/summary");
+    if let Some(token) = token {
+        request = request.header(AUTHORIZATION, format!("Bearer {}", token));
+    }
+    let response = router(pool.clone())
+        .oneshot(request.body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&bytes).unwrap_or(serde_json::Value::Null))
+}
+
+mod endpoint_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_summary_is_for_staff() {
+        let Some(pool) = test_pool() else { return };
+
+        let (status, summary) = get_summary(&pool, Some(&token(&pool, UserRole::Receptionist))).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(summary["total"].is_i64());
+        assert!(summary["by_status"]["dirty"].is_i64());
+
+        let (status, _) = get_summary(&pool, Some(&token(&pool, UserRole::Cleaner))).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        let (status, _) = get_summary(&pool, None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+}
@@ -105,17 +105,76 @@ mod room_status_transition_tests {
             "Cleaning room should be able to transition back to dirty for rework"
         );
     }
+
+    #[test]
+    fn test_full_transition_matrix() {
+        use RoomStatus::*;
+
+        // Every allowed change of status; anything else between two
+        // different statuses must be refused
+        let allowed = [
+            (Available, Occupied),
+            (Available, Maintenance),
+            (Available, Dirty),
+            (Occupied, Available),
+            (Occupied, Dirty),
+            (Maintenance, Available),
+            (Dirty, Cleaning),
+            (Dirty, Available),
+            (Dirty, Occupied),
+            (Cleaning, Available),
+            (Cleaning, Dirty),
+            (Cleaning, Occupied),
+        ];
+
+        for from in RoomStatus::ALL {
+            for to in RoomStatus::ALL {
+                let expected = from == to || allowed.contains(&(from, to));
+                assert_eq!(
+                    from.can_transition_to(to),
+                    expected,
+                    "{} -> {} should be {}",
+                    from,
+                    to,
+                    if expected { "allowed" } else { "refused" }
+                );
+            }
+        }
+    }
+}
+
+mod room_status_role_tests {
+    use hotel_management_backend::models::{RoomStatus, UserRole};
+
+    #[test]
+    fn test_cleaner_is_limited_to_the_cleaning_flow() {
+        for status in RoomStatus::ALL {
+            let expected = matches!(
+                status,
+                RoomStatus::Dirty | RoomStatus::Cleaning | RoomStatus::Available
+            );
+            assert_eq!(status.is_allowed_for_role(UserRole::Cleaner), expected, "{}", status);
+        }
+    }
+
+    #[test]
+    fn test_front_desk_may_set_any_status() {
+        for role in [UserRole::Admin, UserRole::Receptionist] {
+            for status in RoomStatus::ALL {
+                assert!(status.is_allowed_for_role(role), "{:?} {}", role, status);
+            }
+        }
+    }
 }
 
 mod room_type_tests {
     use hotel_management_backend::models::RoomType;
-    use serde_json;
 
     #[test]
     fn test_room_type_serialization() {
-        let single = RoomType::Single;
-        let double = RoomType::Double;
-        let suite = RoomType::Suite;
+        let single = RoomType::SINGLE;
+        let double = RoomType::DOUBLE;
+        let suite = RoomType::SUITE;
 
         let single_json = serde_json::to_string(&single).unwrap();
         let double_json = serde_json::to_string(&double).unwrap();
@@ -132,15 +191,14 @@ mod room_type_tests {
         let double: RoomType = serde_json::from_str("\"double\"").unwrap();
         let suite: RoomType = serde_json::from_str("\"suite\"").unwrap();
 
-        assert_eq!(single, RoomType::Single);
-        assert_eq!(double, RoomType::Double);
-        assert_eq!(suite, RoomType::Suite);
+        assert_eq!(single, RoomType::SINGLE);
+        assert_eq!(double, RoomType::DOUBLE);
+        assert_eq!(suite, RoomType::SUITE);
     }
 }
 
 mod room_status_serialization_tests {
     use hotel_management_backend::models::RoomStatus;
-    use serde_json;
 
     #[test]
     fn test_room_status_serialization() {
@@ -177,4 +235,42 @@ mod room_status_serialization_tests {
         assert_eq!(dirty, RoomStatus::Dirty);
         assert_eq!(cleaning, RoomStatus::Cleaning);
     }
-}
\ No newline at end of file
+}
+mod room_status_in_transaction_tests {
+    use hotel_management_backend::errors::AppError;
+    use hotel_management_backend::models::RoomStatus;
+    use hotel_management_backend::services::booking_service::app_error_to_diesel;
+    use hotel_management_backend::services::RoomService;
+
+    #[test]
+    fn test_refused_transition_is_an_error() {
+        let result = RoomService::check_status_transition(RoomStatus::Maintenance, RoomStatus::Occupied);
+        assert!(matches!(result, Err(AppError::InvalidStatusTransition(_))));
+
+        assert!(RoomService::check_status_transition(RoomStatus::Dirty, RoomStatus::Occupied).is_ok());
+    }
+
+    #[test]
+    fn test_refused_transition_survives_transaction_rollback() {
+        // check_in/check_out abort their transaction with the room error; the
+        // caller must still see the original variant, not a database error
+        let refused = RoomService::check_status_transition(RoomStatus::Maintenance, RoomStatus::Occupied)
+            .unwrap_err();
+
+        match AppError::from(app_error_to_diesel(refused)) {
+            AppError::InvalidStatusTransition(msg) => {
+                assert_eq!(msg, "Cannot transition room from maintenance to occupied")
+            }
+            other => panic!("expected InvalidStatusTransition, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_other_aborts_keep_their_variant() {
+        let room_unavailable = AppError::from(app_error_to_diesel(AppError::RoomUnavailable("busy".to_string())));
+        assert!(matches!(room_unavailable, AppError::RoomUnavailable(msg) if msg == "busy"));
+
+        let conflict = AppError::from(app_error_to_diesel(AppError::Conflict("raced".to_string())));
+        assert!(matches!(conflict, AppError::Conflict(msg) if msg == "raced"));
+    }
+}
@@ -0,0 +1,152 @@
+//! Room type tests
+//!
+//! Tests for configuring room types as data: adding types with their own
+//! codes, prices and capacities, creating rooms of them, and refusing types
+//! that are no longer offered or still have rooms. The tests need a migrated
+//! PostgreSQL database and only run when TEST_DATABASE_URL is set; they only
+//! touch the types they create, never the seeded single, double and suite.
+
+use bigdecimal::BigDecimal;
+use uuid::Uuid;
+
+use hotel_management_backend::db::{create_pool, DbPool};
+use hotel_management_backend::errors::AppError;
+use hotel_management_backend::models::{RoomType, RoomTypeConfig};
+use hotel_management_backend::services::room_service::RoomFilter;
+use hotel_management_backend::services::room_type_service::{CreateRoomTypeRequest, UpdateRoomTypeRequest};
+use hotel_management_backend::services::{RoomService, RoomTypeService};
+
+fn test_pool() -> Option<DbPool> {
+    match std::env::var("TEST_DATABASE_URL") {
+        Ok(url) => Some(create_pool(&url)),
+        Err(_) => {
+            eprintln!("TEST_DATABASE_URL not set, skipping database test");
+            None
+        }
+    }
+}
+
+fn request(code: &str) -> CreateRoomTypeRequest {
+    CreateRoomTypeRequest {
+        code: RoomType::new(code).unwrap(),
+        display_name: " Family Room ".to_string(),
+        default_price: BigDecimal::from(1_800_000),
+        default_capacity: 5,
+        active: true,
+    }
+}
+
+/// A room type with a code no other run uses
+fn new_type(service: &RoomTypeService) -> RoomTypeConfig {
+    let code = format!("family_{}", &Uuid::new_v4().simple().to_string()[..8]);
+    service.create_room_type(&request(&code)).unwrap()
+}
+
+fn room_number() -> String {
+    format!("R{}", &Uuid::new_v4().simple().to_string()[..8])
+}
+
+mod room_type_service_tests {
+    use super::*;
+
+    #[test]
+    fn test_legacy_types_are_seeded() {
+        let Some(pool) = test_pool() else { return };
+        let types = RoomTypeService::new(pool).list_room_types(true).unwrap();
+        for code in RoomType::LEGACY {
+            assert!(types.iter().any(|t| t.code == code), "{} missing", code);
+        }
+    }
+
+    #[test]
+    fn test_types_are_validated_and_codes_unique() {
+        let Some(pool) = test_pool() else { return };
+        let service = RoomTypeService::new(pool);
+        let created = new_type(&service);
+        assert_eq!(created.display_name, "Family Room");
+        assert!(created.active);
+
+        assert!(matches!(
+            service.create_room_type(&request(created.code.as_str())),
+            Err(AppError::Conflict(_))
+        ));
+        for bad in [
+            CreateRoomTypeRequest { display_name: "  ".to_string(), ..request("bad_name") },
+            CreateRoomTypeRequest { default_price: BigDecimal::from(0), ..request("bad_price") },
+            CreateRoomTypeRequest { default_capacity: 0, ..request("bad_capacity") },
+        ] {
+            assert!(matches!(service.create_room_type(&bad), Err(AppError::FieldErrors(_))));
+        }
+    }
+
+    #[test]
+    fn test_rooms_take_the_type_defaults() {
+        let Some(pool) = test_pool() else { return };
+        let service = RoomTypeService::new(pool.clone());
+        let rooms = RoomService::new(pool);
+        let family = new_type(&service);
+
+        let room = rooms.create_room(&room_number(), family.code.clone()).unwrap();
+        assert_eq!(room.room_type, family.code);
+        assert_eq!(room.room_type_id, family.id);
+        assert_eq!(room.price, family.default_price);
+
+        let listed = rooms.list_rooms(&RoomFilter {
+                room_type: Some(family.code.clone()),
+                ..RoomFilter::default()
+            }).unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].id, room.id);
+
+        assert!(matches!(
+            rooms.create_room(&room_number(), RoomType::new("no_such_type").unwrap()),
+            Err(AppError::FieldErrors(_))
+        ));
+    }
+
+    #[test]
+    fn test_inactive_types_take_no_new_rooms() {
+        let Some(pool) = test_pool() else { return };
+        let service = RoomTypeService::new(pool.clone());
+        let rooms = RoomService::new(pool);
+        let family = new_type(&service);
+        let existing = rooms.create_room(&room_number(), family.code.clone()).unwrap();
+
+        let retired = service
+            .update_room_type(
+                family.id,
+                &UpdateRoomTypeRequest {
+                    active: Some(false),
+                    default_price: Some(BigDecimal::from(2_000_000)),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        assert!(!retired.active);
+        assert_eq!(retired.code, family.code);
+        assert!(!service.list_room_types(false).unwrap().iter().any(|t| t.id == family.id));
+
+        assert!(matches!(
+            rooms.create_room(&room_number(), family.code.clone()),
+            Err(AppError::FieldErrors(_))
+        ));
+        // Rooms already of the type keep it and their own price
+        let kept = rooms.get_room_by_id(existing.id).unwrap();
+        assert_eq!(kept.room_type, family.code);
+        assert_eq!(kept.price, BigDecimal::from(1_800_000));
+    }
+
+    #[test]
+    fn test_types_with_rooms_cannot_be_deleted() {
+        let Some(pool) = test_pool() else { return };
+        let service = RoomTypeService::new(pool.clone());
+        let family = new_type(&service);
+        RoomService::new(pool).create_room(&room_number(), family.code.clone()).unwrap();
+        assert!(matches!(service.delete_room_type(family.id), Err(AppError::Conflict(_))));
+
+        let unused = new_type(&service);
+        service.delete_room_type(unused.id).unwrap();
+        assert!(matches!(service.get_room_type(unused.id), Err(AppError::NotFound(_))));
+        assert!(matches!(service.delete_room_type(unused.id), Err(AppError::NotFound(_))));
+    }
+}
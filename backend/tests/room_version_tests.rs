@@ -0,0 +1,212 @@
+//! Room edit conflict tests
+//!
+//! Tests for refusing a room edit made against an outdated copy of the room,
+//! so two admins editing at once cannot silently overwrite each other. The
+//! tests need a migrated PostgreSQL database and only run when
+//! TEST_DATABASE_URL is set.
+
+use std::sync::Arc;
+
+use axum::body::{to_bytes, Body};
+use axum::http::{header::AUTHORIZATION, header::CONTENT_TYPE, Method, Request, StatusCode};
+use chrono::Duration;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use hotel_management_backend::api::public_bookings::PublicLookupLimits;
+use hotel_management_backend::api::{chat::ChatState, create_router, AppState};
+use hotel_management_backend::config::BodyLimits;
+use hotel_management_backend::db::{create_pool, DbPool};
+use hotel_management_backend::errors::AppError;
+use hotel_management_backend::models::{Room, RoomDetailsUpdate, RoomStatus, RoomType, UserRole};
+use hotel_management_backend::scheduler::JobBoard;
+use hotel_management_backend::services::auth_service::{CreateUserRequest, LoginRequest, DEFAULT_ACCESS_TOKEN_LIFETIME};
+use hotel_management_backend::services::rate_limit_service::InMemoryLoginAttempts;
+use hotel_management_backend::services::room_service::RoomEdit;
+use hotel_management_backend::services::{AuthService, ReadOnlyMode, RevocationCache, RoomService, SessionTracker, TokenIssuer};
+
+const JWT_SECRET: &str = "test-secret";
+
+fn test_pool() -> Option<DbPool> {
+    match std::env::var("TEST_DATABASE_URL") {
+        Ok(url) => Some(create_pool(&url)),
+        Err(_) => {
+            eprintln!("TEST_DATABASE_URL not set, skipping database test");
+            None
+        }
+    }
+}
+
+fn room(pool: &DbPool) -> Room {
+    let number = format!("V{}", &Uuid::new_v4().simple().to_string()[..8]);
+    RoomService::new(pool.clone()).create_room(&number, RoomType::DOUBLE).unwrap()
+}
+
+fn describe(text: &str, seen: &Room) -> RoomEdit {
+    RoomEdit {
+        details: RoomDetailsUpdate {
+            description: Some(text.to_string()),
+            ..RoomDetailsUpdate::default()
+        },
+        expected_updated_at: Some(seen.updated_at),
+        ..RoomEdit::default()
+    }
+}
+
+mod service_tests {
+    use super::*;
+
+    #[test]
+    fn test_fresh_version_updates_and_stale_version_conflicts() {
+        let Some(pool) = test_pool() else { return };
+        let rooms = RoomService::new(pool.clone());
+        let seen = room(&pool);
+
+        // The first admin saves against the version both of them loaded
+        let first = rooms.update_room(seen.id, describe("Sea view", &seen), None).unwrap();
+        assert_eq!(first.description.as_deref(), Some("Sea view"));
+        assert!(first.updated_at > seen.updated_at);
+
+        // The second admin's copy is now outdated
+        match rooms.update_room(seen.id, describe("Garden view", &seen), None) {
+            Err(AppError::ConflictWith(_, current)) => {
+                assert_eq!(current["description"], "Sea view");
+                assert_eq!(current["id"], seen.id.to_string());
+            }
+            other => panic!("expected a conflict, got {:?}", other),
+        }
+        // Sending nothing new against an old version is refused too
+        assert!(matches!(
+            rooms.update_room(
+                seen.id,
+                RoomEdit {
+                    expected_updated_at: Some(seen.updated_at),
+                    ..RoomEdit::default()
+                },
+                None,
+            ),
+            Err(AppError::ConflictWith(..))
+        ));
+        assert_eq!(rooms.get_room_by_id(seen.id).unwrap().description.as_deref(), Some("Sea view"));
+
+        // Retrying with the current version succeeds
+        let second = rooms.update_room(seen.id, describe("Garden view", &first), None).unwrap();
+        assert_eq!(second.description.as_deref(), Some("Garden view"));
+    }
+
+    #[test]
+    fn test_stale_edit_leaves_no_side_effects() {
+        let Some(pool) = test_pool() else { return };
+        let rooms = RoomService::new(pool.clone());
+        let seen = room(&pool);
+        rooms.update_room(seen.id, describe("Renovated", &seen), None).unwrap();
+
+        let stale = RoomEdit {
+            status: Some(RoomStatus::Maintenance),
+            maintenance_reason: Some("Broken heater".to_string()),
+            expected_updated_at: Some(seen.updated_at - Duration::seconds(1)),
+            ..RoomEdit::default()
+        };
+        assert!(matches!(rooms.update_room(seen.id, stale, None), Err(AppError::ConflictWith(..))));
+        assert_eq!(rooms.get_room_by_id(seen.id).unwrap().status, RoomStatus::Available);
+        let tickets = hotel_management_backend::services::TicketService::new(pool)
+            .list_room_tickets(seen.id, None)
+            .unwrap();
+        assert!(tickets.is_empty());
+    }
+
+    #[test]
+    fn test_edits_without_a_version_always_apply() {
+        let Some(pool) = test_pool() else { return };
+        let rooms = RoomService::new(pool.clone());
+        let seen = room(&pool);
+        rooms.update_room(seen.id, describe("One", &seen), None).unwrap();
+
+        let unversioned = RoomEdit {
+            expected_updated_at: None,
+            ..describe("Two", &seen)
+        };
+        let room = rooms.update_room(seen.id, unversioned, None).unwrap();
+        assert_eq!(room.description.as_deref(), Some("Two"));
+    }
+}
+
+fn router(pool: DbPool) -> axum::Router {
+    let s3_config = aws_sdk_s3::config::Builder::new()
+        .region(aws_sdk_s3::config::Region::new("us-east-1"))
+        .behavior_version_latest()
+        .build();
+
+    create_router(AppState {
+        pool,
+        jwt_secret: JWT_SECRET.to_string(),
+        chat_state: Arc::new(ChatState::default()),
+        s3_client: aws_sdk_s3::Client::from_conf(s3_config),
+        read_only: Arc::new(ReadOnlyMode::new(Some(false))),
+        public_lookup: Arc::new(PublicLookupLimits::default()),
+        jobs: Arc::new(JobBoard::default()),
+        body_limits: BodyLimits::default(),
+        sessions: Arc::new(SessionTracker::default()),
+        access_token_lifetime: DEFAULT_ACCESS_TOKEN_LIFETIME,
+        login_attempts: Arc::new(InMemoryLoginAttempts::default()),
+        revocations: Arc::new(RevocationCache::default()),
+        trust_proxy_headers: false,
+        token_issuer: TokenIssuer::default(),
+    })
+}
+
+/// Login token of a new receptionist
+fn receptionist_token(pool: &DbPool) -> String {
+    let auth = AuthService::new(pool.clone(), JWT_SECRET.to_string());
+    let username = format!("version-{}", &Uuid::new_v4().simple().to_string()[..8]);
+    auth.create_user(&CreateUserRequest {
+        username: username.clone(),
+        password: "desk-password-1".to_string(),
+        role: UserRole::Receptionist,
+        must_change_password: false,
+    })
+    .unwrap();
+    auth.login(&LoginRequest {
+        username,
+        password: "desk-password-1".to_string(),
+    })
+    .unwrap()
+    .token
+}
+
+mod endpoint_tests {
+    use super::*;
+
+    async fn patch(pool: &DbPool, room: &Room, token: &str, body: serde_json::Value) -> (StatusCode, serde_json::Value) {
+        let request = Request::builder()
+            .method(Method::PATCH)
+            .uri(format!("/rooms/{}", room.id))
+            .header(AUTHORIZATION, format!("Bearer {}", token))
+            .header(CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        let response = router(pool.clone()).oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_stale_patch_returns_the_current_room() {
+        let Some(pool) = test_pool() else { return };
+        let room = room(&pool);
+        let token = receptionist_token(&pool);
+        let seen = serde_json::to_value(&room).unwrap()["updated_at"].clone();
+
+        let (status, first) =
+            patch(&pool, &room, &token, serde_json::json!({ "description": "Quiet", "expected_updated_at": seen })).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let (status, body) =
+            patch(&pool, &room, &token, serde_json::json!({ "description": "Noisy", "expected_updated_at": seen })).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(body["code"], "CONFLICT");
+        assert_eq!(body["current"]["description"], "Quiet");
+        assert_eq!(body["current"]["updated_at"], first["updated_at"]);
+    }
+}
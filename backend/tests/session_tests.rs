@@ -0,0 +1,232 @@
+//! Session idle timeout tests
+//!
+//! Tests for the in-memory session tracker (idle limits per role, write-behind
+//! of last activity) and for the SESSION_EXPIRED response from the auth
+//! middleware. The middleware tests need a migrated PostgreSQL database and
+//! only run when TEST_DATABASE_URL is set.
+
+use std::sync::Arc;
+
+use axum::body::Body;
+use axum::http::{header::AUTHORIZATION, Method, Request, StatusCode};
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use diesel::prelude::*;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use hotel_management_backend::api::public_bookings::PublicLookupLimits;
+use hotel_management_backend::api::{chat::ChatState, create_router, AppState};
+use hotel_management_backend::config::BodyLimits;
+use hotel_management_backend::db::{create_pool, DbPool};
+use hotel_management_backend::models::UserRole;
+use hotel_management_backend::scheduler::JobBoard;
+use hotel_management_backend::schema::user_sessions;
+use hotel_management_backend::services::auth_service::{GuestRegisterRequest, DEFAULT_ACCESS_TOKEN_LIFETIME};
+use hotel_management_backend::services::rate_limit_service::InMemoryLoginAttempts;
+use hotel_management_backend::services::session_service::{Activity, IdleLimits};
+use hotel_management_backend::services::{AuthService, ReadOnlyMode, RevocationCache, SessionService, SessionTracker, TokenIssuer};
+
+const JWT_SECRET: &str = "test-secret";
+
+fn at(minute: i64) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2025, 6, 1, 9, 0, 0).unwrap() + Duration::minutes(minute)
+}
+
+mod tracker_tests {
+    use super::*;
+
+    #[test]
+    fn test_unknown_session_must_be_resumed() {
+        let tracker = SessionTracker::default();
+        let session = Uuid::new_v4();
+
+        assert_eq!(tracker.touch(session, UserRole::Receptionist, at(0)), Activity::Unknown);
+        assert_eq!(
+            tracker.resume(session, UserRole::Receptionist, at(0), at(0)),
+            Activity::Active { flush: false }
+        );
+        assert_eq!(
+            tracker.touch(session, UserRole::Receptionist, at(10)),
+            Activity::Active { flush: true }
+        );
+    }
+
+    #[test]
+    fn test_activity_is_flushed_at_most_once_a_minute() {
+        let tracker = SessionTracker::default();
+        let session = Uuid::new_v4();
+        tracker.resume(session, UserRole::Receptionist, at(0), at(0));
+
+        let second = |s: i64| at(1) + Duration::seconds(s);
+        assert_eq!(
+            tracker.touch(session, UserRole::Receptionist, second(0)),
+            Activity::Active { flush: true }
+        );
+        assert_eq!(
+            tracker.touch(session, UserRole::Receptionist, second(30)),
+            Activity::Active { flush: false }
+        );
+        assert_eq!(
+            tracker.touch(session, UserRole::Receptionist, second(59)),
+            Activity::Active { flush: false }
+        );
+        assert_eq!(
+            tracker.touch(session, UserRole::Receptionist, second(60)),
+            Activity::Active { flush: true }
+        );
+    }
+
+    #[test]
+    fn test_idle_past_limit_expires_and_forgets_session() {
+        let tracker = SessionTracker::default();
+        let session = Uuid::new_v4();
+        tracker.resume(session, UserRole::Receptionist, at(0), at(0));
+
+        assert_eq!(
+            tracker.touch(session, UserRole::Receptionist, at(30)),
+            Activity::Active { flush: true }
+        );
+        assert_eq!(tracker.touch(session, UserRole::Receptionist, at(61)), Activity::Expired);
+        assert_eq!(tracker.touch(session, UserRole::Receptionist, at(62)), Activity::Unknown);
+    }
+
+    #[test]
+    fn test_stored_activity_older_than_limit_expires_on_resume() {
+        let tracker = SessionTracker::default();
+        assert_eq!(
+            tracker.resume(Uuid::new_v4(), UserRole::Guest, at(0), at(31)),
+            Activity::Expired
+        );
+    }
+
+    #[test]
+    fn test_roles_have_their_own_limits() {
+        let tracker = SessionTracker::new(IdleLimits {
+            default: Duration::minutes(30),
+            admin: Duration::minutes(10),
+            guest: Duration::minutes(120),
+        });
+
+        let admin = Uuid::new_v4();
+        let guest = Uuid::new_v4();
+        let cleaner = Uuid::new_v4();
+        tracker.resume(admin, UserRole::Admin, at(0), at(0));
+        tracker.resume(guest, UserRole::Guest, at(0), at(0));
+        tracker.resume(cleaner, UserRole::Cleaner, at(0), at(0));
+
+        assert_eq!(tracker.touch(admin, UserRole::Admin, at(11)), Activity::Expired);
+        assert_eq!(tracker.touch(cleaner, UserRole::Cleaner, at(31)), Activity::Expired);
+        assert_eq!(
+            tracker.touch(guest, UserRole::Guest, at(90)),
+            Activity::Active { flush: true }
+        );
+    }
+}
+
+fn test_pool() -> Option<DbPool> {
+    match std::env::var("TEST_DATABASE_URL") {
+        Ok(url) => Some(create_pool(&url)),
+        Err(_) => {
+            eprintln!("TEST_DATABASE_URL not set, skipping database test");
+            None
+        }
+    }
+}
+
+mod middleware_tests {
+    use super::*;
+
+    fn router(pool: DbPool) -> axum::Router {
+        let s3_config = aws_sdk_s3::config::Builder::new()
+            .region(aws_sdk_s3::config::Region::new("us-east-1"))
+            .behavior_version_latest()
+            .build();
+
+        create_router(AppState {
+            pool,
+            jwt_secret: JWT_SECRET.to_string(),
+            chat_state: Arc::new(ChatState::default()),
+            s3_client: aws_sdk_s3::Client::from_conf(s3_config),
+            read_only: Arc::new(ReadOnlyMode::new(Some(false))),
+            public_lookup: Arc::new(PublicLookupLimits::default()),
+            jobs: Arc::new(JobBoard::default()),
+            body_limits: BodyLimits::default(),
+            sessions: Arc::new(SessionTracker::default()),
+            access_token_lifetime: DEFAULT_ACCESS_TOKEN_LIFETIME,
+            login_attempts: Arc::new(InMemoryLoginAttempts::default()),
+            revocations: Arc::new(RevocationCache::default()),
+            trust_proxy_headers: false,
+            token_issuer: TokenIssuer::default(),
+        })
+    }
+
+    fn register_guest(pool: &DbPool) -> String {
+        let suffix = &Uuid::new_v4().simple().to_string()[..8];
+        AuthService::new(pool.clone(), JWT_SECRET.to_string())
+            .register_guest(&GuestRegisterRequest {
+                email: format!("idle-{}@example.com", suffix),
+                password: "guest-password-1".to_string(),
+                full_name: "Idle Guest".to_string(),
+            })
+            .unwrap()
+            .token
+    }
+
+    fn session_id(pool: &DbPool, token: &str) -> Uuid {
+        AuthService::new(pool.clone(), JWT_SECRET.to_string())
+            .validate_token(token)
+            .unwrap()
+            .sid
+            .expect("new tokens carry a session")
+    }
+
+    async fn guest_me(pool: &DbPool, token: &str) -> (StatusCode, serde_json::Value) {
+        let response = router(pool.clone())
+            .oneshot(
+                Request::builder()
+                    .method(Method::GET)
+                    .uri("/auth/guest/me")
+                    .header(AUTHORIZATION, format!("Bearer {}", token))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap_or_default())
+    }
+
+    #[tokio::test]
+    async fn test_recent_session_is_accepted() {
+        let Some(pool) = test_pool() else { return };
+        let token = register_guest(&pool);
+
+        let (status, _) = guest_me(&pool, &token).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_idle_session_gets_session_expired() {
+        let Some(pool) = test_pool() else { return };
+        let token = register_guest(&pool);
+        let session = session_id(&pool, &token);
+
+        SessionService::new(pool.clone())
+            .record_seen(session, Utc::now() - Duration::hours(2))
+            .unwrap();
+
+        let (status, body) = guest_me(&pool, &token).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body["code"], "SESSION_EXPIRED");
+
+        // The session is gone, so the token stays refused
+        let mut conn = pool.get().unwrap();
+        let remaining: i64 = user_sessions::table
+            .find(session)
+            .count()
+            .get_result(&mut conn)
+            .unwrap();
+        assert_eq!(remaining, 0);
+    }
+}
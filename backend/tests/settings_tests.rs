@@ -0,0 +1,229 @@
+//! Settings registry tests
+//!
+//! Tests for validating setting writes against the registry and for the typed
+//! accessors falling back to defaults on bad stored values.
+
+use std::collections::HashMap;
+
+use bigdecimal::BigDecimal;
+use chrono::Duration;
+use chrono_tz::Tz;
+use serde_json::{json, Map, Value};
+
+use hotel_management_backend::errors::AppError;
+use hotel_management_backend::settings::{
+    self, definition, parse_duration, parse_time_zone, schema, validate_updates, validate_value,
+    SettingDef, SettingType, Settings, REGISTRY,
+};
+
+fn updates(value: Value) -> Map<String, Value> {
+    value.as_object().unwrap().clone()
+}
+
+fn stored(pairs: &[(&str, &str)]) -> Settings {
+    Settings::from_values(
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect::<HashMap<_, _>>(),
+    )
+}
+
+mod registry_tests {
+    use super::*;
+
+    #[test]
+    fn test_every_default_is_valid() {
+        for def in REGISTRY {
+            assert!(
+                validate_value(def, &Value::String(def.default.to_string())).is_ok(),
+                "default of '{}' does not validate",
+                def.key
+            );
+        }
+    }
+
+    #[test]
+    fn test_keys_are_unique() {
+        let mut keys: Vec<&str> = REGISTRY.iter().map(|d| d.key).collect();
+        keys.sort();
+        keys.dedup();
+        assert_eq!(keys.len(), REGISTRY.len());
+    }
+
+    #[test]
+    fn test_schema_describes_types() {
+        let schema = schema();
+        let provider = schema.iter().find(|s| s.key == settings::AI_PROVIDER).unwrap();
+        assert_eq!(provider.setting_type, "enum");
+        assert_eq!(provider.options.unwrap(), &["openai", "gemini"]);
+
+        let enabled = schema.iter().find(|s| s.key == settings::AI_ENABLED).unwrap();
+        assert_eq!(enabled.setting_type, "bool");
+
+        let read_only = schema.iter().find(|s| s.key == settings::READ_ONLY_MODE).unwrap();
+        assert!(!read_only.writable);
+
+        let api_key = schema.iter().find(|s| s.key == settings::AI_API_KEY).unwrap();
+        assert!(api_key.secret);
+    }
+}
+
+mod validation_tests {
+    use super::*;
+
+    #[test]
+    fn test_typo_in_bool_is_rejected() {
+        let result = validate_updates(&updates(json!({ "ai_enabled": "ture" })));
+        match result {
+            Err(AppError::FieldErrors(fields)) => {
+                assert_eq!(fields.get("ai_enabled").unwrap(), "must be true or false");
+            }
+            other => panic!("expected field errors, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_unknown_and_managed_keys_are_rejected_together() {
+        let result = validate_updates(&updates(json!({
+            "ai_enabeld": true,
+            "read_only_mode": true,
+            "ai_provider": "anthropic",
+        })));
+        match result {
+            Err(AppError::FieldErrors(fields)) => {
+                assert_eq!(fields.len(), 3);
+                assert_eq!(fields.get("ai_enabeld").unwrap(), "unknown setting");
+                assert!(fields.get("read_only_mode").unwrap().contains("own endpoint"));
+                assert!(fields.get("ai_provider").unwrap().contains("openai, gemini"));
+            }
+            other => panic!("expected field errors, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_valid_values_are_normalized() {
+        let values = validate_updates(&updates(json!({
+            "ai_enabled": true,
+            "ai_provider": " Gemini ",
+            "hotel_timezone": "asia/bangkok",
+            "cash_discrepancy_threshold": 50000,
+        })))
+        .unwrap();
+
+        let get = |key: &str| values.iter().find(|(k, _)| *k == key).unwrap().1.clone();
+        assert_eq!(get("ai_enabled"), "true");
+        assert_eq!(get("ai_provider"), "gemini");
+        assert_eq!(get("hotel_timezone"), "Asia/Bangkok");
+        assert_eq!(get("cash_discrepancy_threshold"), "50000");
+    }
+
+    #[test]
+    fn test_decimal_bounds() {
+        let result = validate_updates(&updates(json!({ "cash_discrepancy_threshold": -1 })));
+        assert!(matches!(result, Err(AppError::FieldErrors(_))));
+    }
+
+    #[test]
+    fn test_duration_type() {
+        let def = SettingDef {
+            key: "example_window",
+            setting_type: SettingType::Duration { min_secs: 60, max_secs: 86_400 },
+            default: "15m",
+            description: "",
+            secret: false,
+            writable: true,
+        };
+        assert_eq!(validate_value(&def, &json!("2H")).unwrap(), "2h");
+        assert!(validate_value(&def, &json!("30s")).is_err());
+        assert!(validate_value(&def, &json!("soon")).is_err());
+
+        assert_eq!(parse_duration("15m"), Some(Duration::minutes(15)));
+        assert_eq!(parse_duration("1d"), Some(Duration::days(1)));
+        assert_eq!(parse_duration("90"), Some(Duration::seconds(90)));
+        assert!(parse_duration("m").is_none());
+    }
+
+    #[test]
+    fn test_integer_type() {
+        let result = validate_updates(&updates(json!({ "booking_max_nights": "45" }))).unwrap();
+        assert_eq!(result, vec![("booking_max_nights", "45".to_string())]);
+
+        for bad in [json!(0), json!(366), json!("2.5"), json!("month")] {
+            let result = validate_updates(&updates(json!({ "booking_min_nights": bad })));
+            assert!(matches!(result, Err(AppError::FieldErrors(_))));
+        }
+    }
+
+    #[test]
+    fn test_time_of_day_type() {
+        let def = definition(settings::CHECK_IN_TIME).unwrap();
+        assert_eq!(validate_value(def, &json!("9:30")).unwrap(), "09:30");
+        assert_eq!(validate_value(def, &json!(" 14:00 ")).unwrap(), "14:00");
+        assert!(validate_value(def, &json!("25:00")).is_err());
+        assert!(validate_value(def, &json!("2pm")).is_err());
+
+        let settings = stored(&[("check_out_time", "11:30")]);
+        assert_eq!(settings.time_of_day(settings::CHECK_OUT_TIME).to_string(), "11:30:00");
+        assert_eq!(settings.time_of_day(settings::CHECK_IN_TIME).to_string(), "14:00:00");
+    }
+
+    #[test]
+    fn test_time_zone_type() {
+        let def = definition(settings::HOTEL_TIMEZONE).unwrap();
+        assert_eq!(validate_value(def, &json!("Europe/Paris")).unwrap(), "Europe/Paris");
+        assert!(validate_value(def, &json!("Mars/Olympus_Mons")).is_err());
+
+        // Offsets stored before zone names were supported keep working
+        assert_eq!(parse_time_zone("+07:00"), Some(chrono_tz::Etc::GMTMinus7));
+        assert_eq!(parse_time_zone("-5"), Some(chrono_tz::Etc::GMTPlus5));
+        assert_eq!(parse_time_zone("UTC"), Some(Tz::UTC));
+        assert_eq!(parse_time_zone("+00:00"), Some(Tz::UTC));
+        // No fixed zone exists for a half-hour offset
+        assert!(parse_time_zone("+05:30").is_none());
+    }
+
+    #[test]
+    fn test_rejects_structured_values() {
+        let def = definition(settings::AI_MODEL).unwrap();
+        assert!(validate_value(def, &json!(["gpt"])).is_err());
+    }
+}
+
+mod accessor_tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_values_use_defaults() {
+        let settings = stored(&[]);
+        assert!(!settings.bool(settings::AI_ENABLED));
+        assert_eq!(settings.text(settings::AI_PROVIDER), "openai");
+        assert_eq!(settings.time_zone(settings::HOTEL_TIMEZONE), chrono_tz::Asia::Ho_Chi_Minh);
+        assert_eq!(settings.decimal(settings::CASH_DISCREPANCY_THRESHOLD), BigDecimal::from(100000));
+        assert_eq!(settings.integer(settings::BOOKING_MAX_NIGHTS), 30);
+    }
+
+    #[test]
+    fn test_invalid_stored_values_fall_back_to_defaults() {
+        let settings = stored(&[
+            ("ai_enabled", "ture"),
+            ("ai_provider", "skynet"),
+            ("cash_discrepancy_threshold", "lots"),
+        ]);
+        assert!(!settings.bool(settings::AI_ENABLED));
+        assert_eq!(settings.text(settings::AI_PROVIDER), "openai");
+        assert_eq!(settings.decimal(settings::CASH_DISCREPANCY_THRESHOLD), BigDecimal::from(100000));
+    }
+
+    #[test]
+    fn test_valid_stored_values_are_used() {
+        let settings = stored(&[
+            ("ai_enabled", "true"),
+            ("ai_provider", "gemini"),
+            ("hotel_timezone", "America/New_York"),
+        ]);
+        assert!(settings.bool(settings::AI_ENABLED));
+        assert_eq!(settings.text(settings::AI_PROVIDER), "gemini");
+        assert_eq!(settings.time_zone(settings::HOTEL_TIMEZONE), chrono_tz::America::New_York);
+    }
+}
@@ -0,0 +1,399 @@
+//! Single admin tests
+//!
+//! Tests for the one-active-admin rule: creating, promoting and reactivating
+//! a second admin is a conflict, the last admin cannot be deactivated or
+//! demoted, and the admin role moves with a transfer. Changes to who is
+//! admin lock the admin rows, so the race tests hold that lock and check
+//! both orders in which a transfer and a promotion can queue behind it.
+//! They need a migrated PostgreSQL database with an active admin and only
+//! run when TEST_DATABASE_URL is set.
+
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::Duration;
+
+use axum::body::{to_bytes, Body};
+use axum::http::{header::AUTHORIZATION, Method, Request, StatusCode};
+use chrono::Utc;
+use diesel::prelude::*;
+use jsonwebtoken::{encode, EncodingKey, Header};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use hotel_management_backend::api::public_bookings::PublicLookupLimits;
+use hotel_management_backend::api::{chat::ChatState, create_router, AppState};
+use hotel_management_backend::config::BodyLimits;
+use hotel_management_backend::db::{create_pool, DbPool};
+use hotel_management_backend::errors::AppError;
+use hotel_management_backend::models::{UpdateUser, UserRole};
+use hotel_management_backend::scheduler::JobBoard;
+use hotel_management_backend::schema::users;
+use hotel_management_backend::services::auth_service::{
+    Claims, CreateUserRequest, DEFAULT_ACCESS_TOKEN_LIFETIME, DEFAULT_JWT_ISSUER, LAST_ADMIN_MESSAGE, SINGLE_ADMIN_MESSAGE,
+};
+use hotel_management_backend::services::rate_limit_service::InMemoryLoginAttempts;
+use hotel_management_backend::services::{
+    AuthService, GuestRegisterRequest, ReadOnlyMode, RevocationCache, SessionTracker, TokenIssuer,
+};
+
+const JWT_SECRET: &str = "test-secret";
+
+/// Tests that move the admin role run one at a time
+static ADMIN_ROLE: Mutex<()> = Mutex::new(());
+
+fn admin_role() -> MutexGuard<'static, ()> {
+    ADMIN_ROLE.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn test_pool() -> Option<DbPool> {
+    match std::env::var("TEST_DATABASE_URL") {
+        Ok(url) => Some(create_pool(&url)),
+        Err(_) => {
+            eprintln!("TEST_DATABASE_URL not set, skipping database test");
+            None
+        }
+    }
+}
+
+fn auth(pool: &DbPool) -> AuthService {
+    AuthService::new(pool.clone(), JWT_SECRET.to_string())
+}
+
+/// Id of the active admin, if the database has one
+fn active_admin(pool: &DbPool) -> Option<Uuid> {
+    users::table
+        .filter(users::role.eq(UserRole::Admin))
+        .filter(users::deactivated_at.is_null())
+        .select(users::id)
+        .first(&mut pool.get().unwrap())
+        .optional()
+        .unwrap()
+}
+
+fn active_admins(pool: &DbPool) -> i64 {
+    users::table
+        .filter(users::role.eq(UserRole::Admin))
+        .filter(users::deactivated_at.is_null())
+        .count()
+        .get_result(&mut pool.get().unwrap())
+        .unwrap()
+}
+
+fn role_of(pool: &DbPool, user_id: Uuid) -> UserRole {
+    users::table
+        .find(user_id)
+        .select(users::role)
+        .first(&mut pool.get().unwrap())
+        .unwrap()
+}
+
+fn receptionist(pool: &DbPool) -> Uuid {
+    auth(pool)
+        .create_user(&CreateUserRequest {
+            username: format!("admin-{}", &Uuid::new_v4().simple().to_string()[..8]),
+            password: "desk-password-1".to_string(),
+            role: UserRole::Receptionist,
+            must_change_password: false,
+        })
+        .unwrap()
+        .id
+}
+
+fn promote(pool: &DbPool, user_id: Uuid) -> Result<(), AppError> {
+    auth(pool)
+        .update_employee(
+            user_id,
+            UpdateUser {
+                username: None,
+                role: Some(UserRole::Admin),
+                email: None,
+                full_name: None,
+                phone: None,
+                id_number: None,
+                deactivated_at: None,
+            },
+        )
+        .map(|_| ())
+}
+
+fn assert_second_admin_refused(result: Result<(), AppError>) {
+    let err = result.unwrap_err();
+    assert!(matches!(&err, AppError::Conflict(m) if m == SINGLE_ADMIN_MESSAGE), "{:?}", err);
+}
+
+/// Hand the admin role back to the admin a test started with. The transfer
+/// revokes the admin's tokens issued up to this second, so wait it out
+/// before a later test mints one.
+fn restore(pool: &DbPool, current: Uuid, admin: Uuid) {
+    auth(pool).transfer_admin(current, admin).unwrap();
+    thread::sleep(Duration::from_millis(1100));
+}
+
+/// Hold the lock on the admin rows while `first` and then `second` queue
+/// behind it, release it, and return both outcomes
+fn race<A, B>(pool: &DbPool, first: A, second: B) -> (Result<(), AppError>, Result<(), AppError>)
+where
+    A: FnOnce() -> Result<(), AppError> + Send + 'static,
+    B: FnOnce() -> Result<(), AppError> + Send + 'static,
+{
+    let mut conn = pool.get().unwrap();
+    let (first, second) = conn
+        .transaction::<_, diesel::result::Error, _>(|conn| {
+            users::table
+                .filter(users::role.eq(UserRole::Admin))
+                .filter(users::deactivated_at.is_null())
+                .select(users::id)
+                .for_update()
+                .load::<Uuid>(conn)?;
+            let first = thread::spawn(first);
+            thread::sleep(Duration::from_millis(300));
+            let second = thread::spawn(second);
+            thread::sleep(Duration::from_millis(300));
+            Ok((first, second))
+        })
+        .unwrap();
+    (first.join().unwrap(), second.join().unwrap())
+}
+
+mod service_tests {
+    use super::*;
+
+    #[test]
+    fn test_second_admin_is_a_conflict() {
+        let Some(pool) = test_pool() else { return };
+        let _guard = admin_role();
+        if active_admin(&pool).is_none() {
+            eprintln!("No active admin, skipping");
+            return;
+        }
+
+        let created = auth(&pool).create_user(&CreateUserRequest {
+            username: format!("admin-{}", &Uuid::new_v4().simple().to_string()[..8]),
+            password: "admin-password-1".to_string(),
+            role: UserRole::Admin,
+            must_change_password: false,
+        });
+        assert_second_admin_refused(created.map(|_| ()));
+
+        let employee = receptionist(&pool);
+        assert_second_admin_refused(promote(&pool, employee));
+        assert_eq!(role_of(&pool, employee), UserRole::Receptionist);
+        assert_eq!(active_admins(&pool), 1);
+    }
+
+    #[test]
+    fn test_last_admin_cannot_be_deactivated_or_demoted() {
+        let Some(pool) = test_pool() else { return };
+        let _guard = admin_role();
+        let Some(admin) = active_admin(&pool) else {
+            eprintln!("No active admin, skipping");
+            return;
+        };
+
+        let err = auth(&pool).delete_employee(admin).unwrap_err();
+        assert!(matches!(&err, AppError::Forbidden(m) if m == LAST_ADMIN_MESSAGE), "{:?}", err);
+
+        let demote = UpdateUser {
+            username: None,
+            role: Some(UserRole::Receptionist),
+            email: None,
+            full_name: None,
+            phone: None,
+            id_number: None,
+            deactivated_at: None,
+        };
+        let err = auth(&pool).update_employee(admin, demote).unwrap_err();
+        assert!(matches!(err, AppError::Forbidden(_)), "{:?}", err);
+        assert_eq!(active_admin(&pool), Some(admin));
+    }
+
+    #[test]
+    fn test_transfer_moves_the_role() {
+        let Some(pool) = test_pool() else { return };
+        let _guard = admin_role();
+        let Some(admin) = active_admin(&pool) else {
+            eprintln!("No active admin, skipping");
+            return;
+        };
+        let employee = receptionist(&pool);
+
+        let (previous, next) = auth(&pool).transfer_admin(admin, employee).unwrap();
+        assert_eq!((previous.id, previous.role), (admin, UserRole::Receptionist));
+        assert_eq!((next.id, next.role), (employee, UserRole::Admin));
+        assert_eq!(active_admins(&pool), 1);
+
+        // Only the admin can hand the role on, and only to an active employee
+        let err = auth(&pool).transfer_admin(admin, receptionist(&pool)).unwrap_err();
+        assert!(matches!(err, AppError::Forbidden(_)), "{:?}", err);
+        let guest = auth(&pool)
+            .register_guest(&GuestRegisterRequest {
+                email: format!("admin-{}@example.com", &Uuid::new_v4().simple().to_string()[..8]),
+                password: "guest-password-1".to_string(),
+                full_name: "Admin Guest".to_string(),
+            })
+            .unwrap();
+        let err = auth(&pool).transfer_admin(employee, guest.user.id).unwrap_err();
+        assert!(matches!(err, AppError::ValidationError(_)), "{:?}", err);
+        let err = auth(&pool).transfer_admin(employee, employee).unwrap_err();
+        assert!(matches!(err, AppError::ValidationError(_)), "{:?}", err);
+
+        // Deactivated accounts cannot take it and cannot come back as a second admin
+        restore(&pool, employee, admin);
+        auth(&pool).delete_employee(employee).unwrap();
+        let err = auth(&pool).transfer_admin(admin, employee).unwrap_err();
+        assert!(matches!(err, AppError::ValidationError(_)), "{:?}", err);
+        assert_eq!(active_admin(&pool), Some(admin));
+    }
+
+    #[test]
+    fn test_promotion_queued_behind_a_transfer_is_refused() {
+        let Some(pool) = test_pool() else { return };
+        let _guard = admin_role();
+        let Some(admin) = active_admin(&pool) else {
+            eprintln!("No active admin, skipping");
+            return;
+        };
+        let (successor, rival) = (receptionist(&pool), receptionist(&pool));
+
+        let (transfer_pool, promote_pool) = (pool.clone(), pool.clone());
+        let (transferred, promoted) = race(
+            &pool,
+            move || auth(&transfer_pool).transfer_admin(admin, successor).map(|_| ()),
+            move || promote(&promote_pool, rival),
+        );
+
+        // The promotion sees the admin the transfer made, not the row it
+        // was waiting on
+        transferred.unwrap();
+        assert_second_admin_refused(promoted);
+        assert_eq!(active_admin(&pool), Some(successor));
+        assert_eq!(role_of(&pool, rival), UserRole::Receptionist);
+        assert_eq!(active_admins(&pool), 1);
+
+        restore(&pool, successor, admin);
+    }
+
+    #[test]
+    fn test_transfer_queued_behind_a_promotion_goes_ahead() {
+        let Some(pool) = test_pool() else { return };
+        let _guard = admin_role();
+        let Some(admin) = active_admin(&pool) else {
+            eprintln!("No active admin, skipping");
+            return;
+        };
+        let (successor, rival) = (receptionist(&pool), receptionist(&pool));
+
+        let (transfer_pool, promote_pool) = (pool.clone(), pool.clone());
+        let (promoted, transferred) = race(
+            &pool,
+            move || promote(&promote_pool, rival),
+            move || auth(&transfer_pool).transfer_admin(admin, successor).map(|_| ()),
+        );
+
+        assert_second_admin_refused(promoted);
+        transferred.unwrap();
+        assert_eq!(active_admin(&pool), Some(successor));
+        assert_eq!(role_of(&pool, rival), UserRole::Receptionist);
+        assert_eq!(active_admins(&pool), 1);
+
+        restore(&pool, successor, admin);
+    }
+}
+
+fn router(pool: DbPool) -> axum::Router {
+    let s3_config = aws_sdk_s3::config::Builder::new()
+        .region(aws_sdk_s3::config::Region::new("us-east-1"))
+        .behavior_version_latest()
+        .build();
+
+    create_router(AppState {
+        pool,
+        jwt_secret: JWT_SECRET.to_string(),
+        chat_state: Arc::new(ChatState::default()),
+        s3_client: aws_sdk_s3::Client::from_conf(s3_config),
+        read_only: Arc::new(ReadOnlyMode::new(Some(false))),
+        public_lookup: Arc::new(PublicLookupLimits::default()),
+        jobs: Arc::new(JobBoard::default()),
+        body_limits: BodyLimits::default(),
+        sessions: Arc::new(SessionTracker::default()),
+        access_token_lifetime: DEFAULT_ACCESS_TOKEN_LIFETIME,
+        login_attempts: Arc::new(InMemoryLoginAttempts::default()),
+        revocations: Arc::new(RevocationCache::default()),
+        trust_proxy_headers: false,
+        token_issuer: TokenIssuer::default(),
+    })
+}
+
+fn token(user_id: Uuid, role: UserRole) -> String {
+    let now = Utc::now().timestamp();
+    let claims = Claims {
+        sub: user_id,
+        role,
+        exp: now + 3600,
+        iat: now,
+        sid: None,
+        jti: Some(Uuid::new_v4()),
+        must_change_password: false,
+        iss: Some(DEFAULT_JWT_ISSUER.to_string()),
+        aud: Some(DEFAULT_JWT_ISSUER.to_string()),
+    };
+    encode(&Header::default(), &claims, &EncodingKey::from_secret(JWT_SECRET.as_bytes())).unwrap()
+}
+
+async fn post(pool: &DbPool, uri: &str, token: &str, body: serde_json::Value) -> (StatusCode, serde_json::Value) {
+    let request = Request::builder()
+        .method(Method::POST)
+        .uri(uri)
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap();
+    let response = router(pool.clone()).oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&bytes).unwrap_or(serde_json::Value::Null))
+}
+
+mod endpoint_tests {
+    use super::*;
+
+    // The guard only keeps this apart from the other tests in the file,
+    // which run on their own threads
+    #[tokio::test]
+    #[allow(clippy::await_holding_lock)]
+    async fn test_admin_transfers_the_role() {
+        let Some(pool) = test_pool() else { return };
+        let _guard = admin_role();
+        let Some(admin) = active_admin(&pool) else {
+            eprintln!("No active admin, skipping");
+            return;
+        };
+        let admin_token = token(admin, UserRole::Admin);
+        let employee = receptionist(&pool);
+
+        let second_admin = serde_json::json!({
+            "username": format!("admin-{}", &Uuid::new_v4().simple().to_string()[..8]),
+            "password": "admin-password-1",
+            "role": "admin",
+        });
+        let (status, body) = post(&pool, "/admin/employees", &admin_token, second_admin).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(body["message"], SINGLE_ADMIN_MESSAGE);
+
+        let transfer = serde_json::json!({ "employee_id": employee });
+        let (status, _) = post(&pool, "/admin/transfer-admin", &token(employee, UserRole::Receptionist), transfer.clone()).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        let (status, body) = post(&pool, "/admin/transfer-admin", &admin_token, transfer.clone()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["admin"]["id"], employee.to_string());
+        assert_eq!(body["admin"]["role"], "admin");
+        assert_eq!(body["previous_admin"]["role"], "receptionist");
+
+        // The previous admin's token went with the role
+        let (status, _) = post(&pool, "/admin/transfer-admin", &admin_token, transfer).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        restore(&pool, employee, admin);
+    }
+}
@@ -0,0 +1,124 @@
+//! Startup tests
+//!
+//! Tests for the dependency readiness probes run before the server starts.
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use hotel_management_backend::startup::{probe_database, wait_for, Backoff, DATABASE_PROBE_TIMEOUT};
+
+fn fast_backoff() -> Backoff {
+    Backoff {
+        initial: Duration::from_millis(5),
+        max: Duration::from_millis(20),
+        factor: 2,
+    }
+}
+
+mod backoff_tests {
+    use super::*;
+
+    #[test]
+    fn test_delay_grows_exponentially() {
+        let backoff = Backoff {
+            initial: Duration::from_millis(100),
+            max: Duration::from_secs(10),
+            factor: 2,
+        };
+
+        assert_eq!(backoff.delay_for(0), Duration::from_millis(100));
+        assert_eq!(backoff.delay_for(1), Duration::from_millis(200));
+        assert_eq!(backoff.delay_for(2), Duration::from_millis(400));
+        assert_eq!(backoff.delay_for(3), Duration::from_millis(800));
+    }
+
+    #[test]
+    fn test_delay_is_capped_at_max() {
+        let backoff = Backoff {
+            initial: Duration::from_millis(500),
+            max: Duration::from_secs(10),
+            factor: 2,
+        };
+
+        assert_eq!(backoff.delay_for(5), Duration::from_secs(10));
+        assert_eq!(backoff.delay_for(100), Duration::from_secs(10));
+    }
+
+    #[test]
+    fn test_factor_of_zero_does_not_collapse_delay() {
+        let backoff = Backoff {
+            initial: Duration::from_millis(50),
+            max: Duration::from_secs(1),
+            factor: 0,
+        };
+
+        assert_eq!(backoff.delay_for(3), Duration::from_millis(50));
+    }
+}
+
+mod wait_for_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_delayed_dependency_becomes_ready() {
+        // Mock dependency that fails its first three probes
+        let calls = Arc::new(AtomicU32::new(0));
+        let probe_calls = calls.clone();
+
+        let result = wait_for("mock", Duration::from_secs(5), fast_backoff(), || {
+            let calls = probe_calls.clone();
+            async move {
+                if calls.fetch_add(1, Ordering::SeqCst) < 3 {
+                    Err("connection refused".to_string())
+                } else {
+                    Ok(())
+                }
+            }
+        })
+        .await;
+
+        assert_eq!(result, Ok(4));
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_after_deadline() {
+        let result = wait_for("mock", Duration::from_millis(50), fast_backoff(), || async {
+            Err::<(), _>("connection refused".to_string())
+        })
+        .await;
+
+        let err = result.unwrap_err();
+        assert!(err.contains("mock not ready"));
+        assert!(err.contains("connection refused"));
+    }
+}
+
+mod probe_database_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_unresponsive_database_fails_within_the_probe_timeout() {
+        // Accepts the connection but never answers the startup message
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("postgres://probe@{}/probe", listener.local_addr().unwrap());
+
+        let started = std::time::Instant::now();
+        let result = probe_database(url).await;
+
+        assert!(result.unwrap_err().contains("no answer"));
+        assert!(started.elapsed() >= DATABASE_PROBE_TIMEOUT);
+        assert!(started.elapsed() < DATABASE_PROBE_TIMEOUT + Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_reachable_database_is_ready() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("TEST_DATABASE_URL not set, skipping database test");
+            return;
+        };
+
+        assert_eq!(probe_database(url).await, Ok(()));
+    }
+}
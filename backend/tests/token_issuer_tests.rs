@@ -0,0 +1,214 @@
+//! Token issuer tests
+//!
+//! Tests for the `iss` and `aud` claims: tokens carry the deployment's
+//! issuer and are refused by a deployment with another one, and tokens
+//! issued before the claims existed only pass during the legacy grace. They
+//! need a migrated PostgreSQL database and only run when TEST_DATABASE_URL
+//! is set.
+
+use std::sync::Arc;
+
+use axum::body::Body;
+use axum::http::{header::AUTHORIZATION, Method, Request, StatusCode};
+use chrono::{Duration, Utc};
+use jsonwebtoken::{encode, EncodingKey, Header};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use hotel_management_backend::api::public_bookings::PublicLookupLimits;
+use hotel_management_backend::api::{chat::ChatState, create_router, AppState};
+use hotel_management_backend::config::BodyLimits;
+use hotel_management_backend::db::{create_pool, DbPool};
+use hotel_management_backend::errors::AppError;
+use hotel_management_backend::models::UserRole;
+use hotel_management_backend::scheduler::JobBoard;
+use hotel_management_backend::services::auth_service::{
+    Claims, CreateUserRequest, LoginRequest, DEFAULT_ACCESS_TOKEN_LIFETIME, LEGACY_TOKEN_GRACE,
+};
+use hotel_management_backend::services::rate_limit_service::InMemoryLoginAttempts;
+use hotel_management_backend::services::{
+    AuthService, ReadOnlyMode, RevocationCache, SessionTracker, TokenIssuer,
+};
+
+const JWT_SECRET: &str = "test-secret";
+const PASSWORD: &str = "issuer-password-1";
+
+fn test_pool() -> Option<DbPool> {
+    match std::env::var("TEST_DATABASE_URL") {
+        Ok(url) => Some(create_pool(&url)),
+        Err(_) => {
+            eprintln!("TEST_DATABASE_URL not set, skipping database test");
+            None
+        }
+    }
+}
+
+fn auth(pool: &DbPool, issuer: TokenIssuer) -> AuthService {
+    AuthService::new(pool.clone(), JWT_SECRET.to_string()).with_issuer(issuer)
+}
+
+/// Username and id of a new receptionist
+fn receptionist(pool: &DbPool) -> (String, Uuid) {
+    let username = format!("issuer-{}", &Uuid::new_v4().simple().to_string()[..8]);
+    let user = AuthService::new(pool.clone(), JWT_SECRET.to_string())
+        .create_user(&CreateUserRequest {
+            username: username.clone(),
+            password: PASSWORD.to_string(),
+            role: UserRole::Receptionist,
+            must_change_password: false,
+        })
+        .unwrap();
+    (username, user.id)
+}
+
+fn login(pool: &DbPool, issuer: TokenIssuer, username: &str) -> String {
+    auth(pool, issuer)
+        .login(&LoginRequest {
+            username: username.to_string(),
+            password: PASSWORD.to_string(),
+        })
+        .unwrap()
+        .token
+}
+
+/// Token signed with the shared secret carrying only the given extra claims
+fn token(user_id: Uuid, issued_at: chrono::DateTime<Utc>, extra: serde_json::Value) -> String {
+    let mut claims = serde_json::json!({
+        "sub": user_id,
+        "role": "receptionist",
+        "exp": (Utc::now() + Duration::hours(1)).timestamp(),
+        "iat": issued_at.timestamp(),
+        "jti": Uuid::new_v4(),
+    });
+    claims.as_object_mut().unwrap().extend(extra.as_object().unwrap().clone());
+    encode(&Header::default(), &claims, &EncodingKey::from_secret(JWT_SECRET.as_bytes())).unwrap()
+}
+
+fn assert_refused(result: Result<Claims, AppError>) {
+    let err = result.unwrap_err();
+    assert!(matches!(err, AppError::Unauthorized(_)), "{:?}", err);
+}
+
+mod service_tests {
+    use super::*;
+
+    #[test]
+    fn test_tokens_carry_and_require_the_issuer() {
+        let Some(pool) = test_pool() else { return };
+        let (username, _) = receptionist(&pool);
+        let production = TokenIssuer::new("pupinn-production");
+
+        let token = login(&pool, production.clone(), &username);
+        let claims = auth(&pool, production).validate_token(&token).unwrap();
+        assert_eq!(claims.iss.as_deref(), Some("pupinn-production"));
+        assert_eq!(claims.aud.as_deref(), Some("pupinn-production"));
+
+        // Staging shares the secret but not the issuer
+        assert_refused(auth(&pool, TokenIssuer::new("pupinn-staging")).validate_token(&token));
+        let staging = login(&pool, TokenIssuer::new("pupinn-staging"), &username);
+        assert_refused(auth(&pool, TokenIssuer::new("pupinn-production")).validate_token(&staging));
+    }
+
+    #[test]
+    fn test_issuer_and_audience_must_both_match() {
+        let Some(pool) = test_pool() else { return };
+        let (_, id) = receptionist(&pool);
+        let issuer = TokenIssuer::new("pupinn-production").with_legacy_grace(Utc::now());
+        let earlier = Utc::now() - Duration::minutes(5);
+
+        let other_audience = token(id, earlier, serde_json::json!({ "iss": "pupinn-production", "aud": "pupinn-staging" }));
+        assert_refused(auth(&pool, issuer.clone()).validate_token(&other_audience));
+
+        // Half the claims is not a legacy token, even during the grace
+        let issuer_only = token(id, earlier, serde_json::json!({ "iss": "pupinn-production" }));
+        assert_refused(auth(&pool, issuer.clone()).validate_token(&issuer_only));
+        let audience_only = token(id, earlier, serde_json::json!({ "aud": "pupinn-production" }));
+        assert_refused(auth(&pool, issuer).validate_token(&audience_only));
+    }
+
+    #[test]
+    fn test_legacy_tokens_pass_only_during_the_grace() {
+        let Some(pool) = test_pool() else { return };
+        let (_, id) = receptionist(&pool);
+        let deployed_at = Utc::now();
+        let legacy = token(id, deployed_at - Duration::minutes(5), serde_json::json!({}));
+
+        let claims = auth(&pool, TokenIssuer::new("pupinn").with_legacy_grace(deployed_at))
+            .validate_token(&legacy)
+            .unwrap();
+        assert_eq!(claims.sub, id);
+        assert!(claims.iss.is_none());
+
+        // Without the grace, after it, or for tokens issued since the deploy
+        assert_refused(auth(&pool, TokenIssuer::new("pupinn")).validate_token(&legacy));
+        let lapsed = Utc::now() - LEGACY_TOKEN_GRACE;
+        let old = token(id, lapsed - Duration::minutes(5), serde_json::json!({}));
+        assert_refused(auth(&pool, TokenIssuer::new("pupinn").with_legacy_grace(lapsed)).validate_token(&old));
+        let minted_since = token(id, deployed_at + Duration::seconds(5), serde_json::json!({}));
+        assert_refused(auth(&pool, TokenIssuer::new("pupinn").with_legacy_grace(deployed_at)).validate_token(&minted_since));
+    }
+}
+
+fn router(pool: DbPool, token_issuer: TokenIssuer) -> axum::Router {
+    let s3_config = aws_sdk_s3::config::Builder::new()
+        .region(aws_sdk_s3::config::Region::new("us-east-1"))
+        .behavior_version_latest()
+        .build();
+
+    create_router(AppState {
+        pool,
+        jwt_secret: JWT_SECRET.to_string(),
+        chat_state: Arc::new(ChatState::default()),
+        s3_client: aws_sdk_s3::Client::from_conf(s3_config),
+        read_only: Arc::new(ReadOnlyMode::new(Some(false))),
+        public_lookup: Arc::new(PublicLookupLimits::default()),
+        jobs: Arc::new(JobBoard::default()),
+        body_limits: BodyLimits::default(),
+        sessions: Arc::new(SessionTracker::default()),
+        access_token_lifetime: DEFAULT_ACCESS_TOKEN_LIFETIME,
+        login_attempts: Arc::new(InMemoryLoginAttempts::default()),
+        revocations: Arc::new(RevocationCache::default()),
+        trust_proxy_headers: false,
+        token_issuer,
+    })
+}
+
+async fn get(pool: &DbPool, token_issuer: TokenIssuer, uri: &str, token: &str) -> StatusCode {
+    let request = Request::builder()
+        .method(Method::GET)
+        .uri(uri)
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .body(Body::empty())
+        .unwrap();
+    router(pool.clone(), token_issuer).oneshot(request).await.unwrap().status()
+}
+
+mod endpoint_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_login_tokens_only_work_on_their_own_deployment() {
+        let Some(pool) = test_pool() else { return };
+        let (username, _) = receptionist(&pool);
+        let production = TokenIssuer::new("pupinn-production");
+
+        let body = serde_json::json!({ "username": username, "password": PASSWORD });
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri("/auth/login")
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        let response = router(pool.clone(), production.clone()).oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let login: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        let token = login["token"].as_str().unwrap();
+
+        assert_eq!(get(&pool, production, "/auth/me", token).await, StatusCode::OK);
+        assert_eq!(
+            get(&pool, TokenIssuer::new("pupinn-staging"), "/auth/me", token).await,
+            StatusCode::UNAUTHORIZED
+        );
+    }
+}
@@ -0,0 +1,326 @@
+//! Token revocation tests
+//!
+//! Tests for revoking access tokens before they expire: single tokens by JWT
+//! id, all of a user's tokens at once, the cache in front of the lookups,
+//! and the revoke-sessions and logout endpoints. They need a migrated
+//! PostgreSQL database and only run when TEST_DATABASE_URL is set.
+
+use std::sync::Arc;
+
+use axum::body::Body;
+use axum::http::{header::AUTHORIZATION, Method, Request, StatusCode};
+use chrono::Utc;
+use diesel::prelude::*;
+use jsonwebtoken::{encode, EncodingKey, Header};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use hotel_management_backend::api::public_bookings::PublicLookupLimits;
+use hotel_management_backend::api::{chat::ChatState, create_router, AppState};
+use hotel_management_backend::config::BodyLimits;
+use hotel_management_backend::db::{create_pool, DbPool};
+use hotel_management_backend::errors::AppError;
+use hotel_management_backend::models::UserRole;
+use hotel_management_backend::scheduler::JobBoard;
+use hotel_management_backend::schema::users;
+use hotel_management_backend::services::auth_service::{
+    Claims, CreateUserRequest, LoginRequest, LoginResponse, DEFAULT_ACCESS_TOKEN_LIFETIME, DEFAULT_JWT_ISSUER,
+};
+use hotel_management_backend::services::rate_limit_service::InMemoryLoginAttempts;
+use hotel_management_backend::services::token_revocation_service::TOKEN_REVOKED_MESSAGE;
+use hotel_management_backend::services::{AuthService, ReadOnlyMode, RevocationCache, SessionTracker, TokenIssuer};
+
+const JWT_SECRET: &str = "test-secret";
+const PASSWORD: &str = "revoke-password-1";
+
+fn test_pool() -> Option<DbPool> {
+    match std::env::var("TEST_DATABASE_URL") {
+        Ok(url) => Some(create_pool(&url)),
+        Err(_) => {
+            eprintln!("TEST_DATABASE_URL not set, skipping database test");
+            None
+        }
+    }
+}
+
+fn auth(pool: &DbPool, cache: &Arc<RevocationCache>) -> AuthService {
+    AuthService::new(pool.clone(), JWT_SECRET.to_string()).with_revocations(cache.clone())
+}
+
+/// Username of a new receptionist
+fn receptionist(pool: &DbPool) -> String {
+    let username = format!("revoke-{}", &Uuid::new_v4().simple().to_string()[..8]);
+    AuthService::new(pool.clone(), JWT_SECRET.to_string())
+        .create_user(&CreateUserRequest {
+            username: username.clone(),
+            password: PASSWORD.to_string(),
+            role: UserRole::Receptionist,
+            must_change_password: false,
+        })
+        .unwrap();
+    username
+}
+
+fn login(auth: &AuthService, username: &str) -> LoginResponse {
+    auth.login(&LoginRequest {
+        username: username.to_string(),
+        password: PASSWORD.to_string(),
+    })
+    .unwrap()
+}
+
+fn assert_revoked(result: Result<Claims, AppError>) {
+    let err = result.unwrap_err();
+    assert!(matches!(&err, AppError::Unauthorized(m) if m == TOKEN_REVOKED_MESSAGE), "{:?}", err);
+}
+
+/// Wait for the next second, since JWTs only carry whole seconds
+fn next_second() {
+    std::thread::sleep(std::time::Duration::from_millis(1100));
+}
+
+mod service_tests {
+    use super::*;
+
+    #[test]
+    fn test_single_token_is_revoked_by_jti() {
+        let Some(pool) = test_pool() else { return };
+        let cache = Arc::new(RevocationCache::default());
+        let auth = auth(&pool, &cache);
+        let username = receptionist(&pool);
+        let leaked = login(&auth, &username);
+        let other = login(&auth, &username);
+
+        let claims = auth.validate_token(&leaked.token).unwrap();
+        assert_ne!(claims.jti, auth.validate_token(&other.token).unwrap().jti);
+
+        auth.revocation_service().revoke_token(&claims).unwrap();
+        assert_revoked(auth.validate_token(&leaked.token));
+        auth.validate_token(&other.token).unwrap();
+        // Revoking twice is harmless
+        auth.revocation_service().revoke_token(&claims).unwrap();
+    }
+
+    #[test]
+    fn test_token_without_jti_is_revoked_with_the_users_tokens() {
+        let Some(pool) = test_pool() else { return };
+        let cache = Arc::new(RevocationCache::default());
+        let auth = auth(&pool, &cache);
+        let username = receptionist(&pool);
+        let current = login(&auth, &username);
+        let now = Utc::now().timestamp();
+        let legacy = encode(
+            &Header::default(),
+            &Claims {
+                sub: current.user.id,
+                role: UserRole::Receptionist,
+                exp: now + 3600,
+                iat: now,
+                sid: None,
+                jti: None,
+                must_change_password: false,
+                iss: Some(DEFAULT_JWT_ISSUER.to_string()),
+                aud: Some(DEFAULT_JWT_ISSUER.to_string()),
+            },
+            &EncodingKey::from_secret(JWT_SECRET.as_bytes()),
+        )
+        .unwrap();
+
+        let claims = auth.validate_token(&legacy).unwrap();
+        assert_eq!(claims.jti, None);
+
+        auth.revocation_service().revoke_token(&claims).unwrap();
+        assert_revoked(auth.validate_token(&legacy));
+        assert_revoked(auth.validate_token(&current.token));
+    }
+
+    #[test]
+    fn test_revoking_a_user_refuses_earlier_tokens() {
+        let Some(pool) = test_pool() else { return };
+        let cache = Arc::new(RevocationCache::default());
+        let auth = auth(&pool, &cache);
+        let username = receptionist(&pool);
+        let first = login(&auth, &username);
+        let second = login(&auth, &username);
+        let user_id = first.user.id;
+        auth.validate_token(&first.token).unwrap();
+
+        auth.revoke_employee_sessions(user_id).unwrap();
+        assert_revoked(auth.validate_token(&first.token));
+        assert_revoked(auth.validate_token(&second.token));
+        let err = auth.refresh(&second.refresh_token).unwrap_err();
+        assert!(matches!(err, AppError::Unauthorized(_)), "{:?}", err);
+
+        // Logging in again works
+        next_second();
+        let fresh = login(&auth, &username);
+        auth.validate_token(&fresh.token).unwrap();
+    }
+
+    #[test]
+    fn test_cache_serves_lookups_until_it_expires() {
+        let Some(pool) = test_pool() else { return };
+        let long = Arc::new(RevocationCache::new(std::time::Duration::from_secs(3600)));
+        let other_server = Arc::new(RevocationCache::default());
+        let username = receptionist(&pool);
+        let login = login(&auth(&pool, &long), &username);
+        auth(&pool, &long).validate_token(&login.token).unwrap();
+
+        // Revoked through another cache, this one only sees it once it expires
+        auth(&pool, &other_server)
+            .revoke_employee_sessions(login.user.id)
+            .unwrap();
+        auth(&pool, &long).validate_token(&login.token).unwrap();
+        long.forget(login.user.id);
+        assert_revoked(auth(&pool, &long).validate_token(&login.token));
+    }
+
+    #[test]
+    fn test_deactivation_revokes_tokens() {
+        let Some(pool) = test_pool() else { return };
+        let cache = Arc::new(RevocationCache::default());
+        let auth = auth(&pool, &cache);
+        let login = login(&auth, &receptionist(&pool));
+        auth.validate_token(&login.token).unwrap();
+
+        auth.delete_employee(login.user.id).unwrap();
+        assert_revoked(auth.validate_token(&login.token));
+
+        let mut conn = pool.get().unwrap();
+        let not_before: Option<chrono::DateTime<Utc>> = users::table
+            .find(login.user.id)
+            .select(users::tokens_not_before)
+            .first(&mut conn)
+            .unwrap();
+        assert!(not_before.is_some());
+    }
+}
+
+fn router(pool: DbPool, revocations: Arc<RevocationCache>) -> axum::Router {
+    let s3_config = aws_sdk_s3::config::Builder::new()
+        .region(aws_sdk_s3::config::Region::new("us-east-1"))
+        .behavior_version_latest()
+        .build();
+
+    create_router(AppState {
+        pool,
+        jwt_secret: JWT_SECRET.to_string(),
+        chat_state: Arc::new(ChatState::default()),
+        s3_client: aws_sdk_s3::Client::from_conf(s3_config),
+        read_only: Arc::new(ReadOnlyMode::new(Some(false))),
+        public_lookup: Arc::new(PublicLookupLimits::default()),
+        jobs: Arc::new(JobBoard::default()),
+        body_limits: BodyLimits::default(),
+        sessions: Arc::new(SessionTracker::default()),
+        access_token_lifetime: DEFAULT_ACCESS_TOKEN_LIFETIME,
+        login_attempts: Arc::new(InMemoryLoginAttempts::default()),
+        revocations,
+        trust_proxy_headers: false,
+        token_issuer: TokenIssuer::default(),
+    })
+}
+
+/// Token of the active admin, if the database has one; only one admin may exist
+fn admin_token(pool: &DbPool) -> Option<String> {
+    let mut conn = pool.get().unwrap();
+    let admin: Uuid = users::table
+        .filter(users::role.eq(UserRole::Admin))
+        .filter(users::deactivated_at.is_null())
+        .select(users::id)
+        .first(&mut conn)
+        .optional()
+        .unwrap()?;
+    let now = Utc::now().timestamp();
+    let claims = Claims {
+        sub: admin,
+        role: UserRole::Admin,
+        exp: now + 3600,
+        iat: now,
+        sid: None,
+        jti: Some(Uuid::new_v4()),
+        must_change_password: false,
+        iss: Some(DEFAULT_JWT_ISSUER.to_string()),
+        aud: Some(DEFAULT_JWT_ISSUER.to_string()),
+    };
+    Some(encode(&Header::default(), &claims, &EncodingKey::from_secret(JWT_SECRET.as_bytes())).unwrap())
+}
+
+async fn send(
+    pool: &DbPool,
+    revocations: &Arc<RevocationCache>,
+    method: Method,
+    uri: &str,
+    token: &str,
+    body: Option<serde_json::Value>,
+) -> StatusCode {
+    let builder = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(AUTHORIZATION, format!("Bearer {}", token));
+    let request = match body {
+        Some(body) => builder
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap(),
+        None => builder.body(Body::empty()).unwrap(),
+    };
+    router(pool.clone(), revocations.clone())
+        .oneshot(request)
+        .await
+        .unwrap()
+        .status()
+}
+
+mod endpoint_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_admin_revokes_an_employees_sessions() {
+        let Some(pool) = test_pool() else { return };
+        let Some(admin) = admin_token(&pool) else {
+            eprintln!("No active admin, skipping");
+            return;
+        };
+        let revocations = Arc::new(RevocationCache::default());
+        let login = login(&auth(&pool, &revocations), &receptionist(&pool));
+
+        let status = send(&pool, &revocations, Method::GET, "/bookings", &login.token, None).await;
+        assert_eq!(status, StatusCode::OK);
+
+        // Employees cannot sign each other out
+        let uri = format!("/admin/employees/{}/revoke-sessions", login.user.id);
+        let status = send(&pool, &revocations, Method::POST, &uri, &login.token, None).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        let status = send(&pool, &revocations, Method::POST, &uri, &admin, None).await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        let status = send(&pool, &revocations, Method::GET, "/bookings", &login.token, None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        let unknown = format!("/admin/employees/{}/revoke-sessions", Uuid::new_v4());
+        let status = send(&pool, &revocations, Method::POST, &unknown, &admin, None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_logout_revokes_the_access_token() {
+        let Some(pool) = test_pool() else { return };
+        let revocations = Arc::new(RevocationCache::default());
+        let auth = auth(&pool, &revocations);
+        let login = login(&auth, &receptionist(&pool));
+        let claims = auth.validate_token(&login.token).unwrap();
+
+        let body = serde_json::json!({ "refresh_token": login.refresh_token });
+        let status = send(&pool, &revocations, Method::POST, "/auth/logout", &login.token, Some(body)).await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+
+        // Refused for the jti itself, not only for the ended session
+        let fresh_cache = Arc::new(RevocationCache::default());
+        let err = AuthService::new(pool.clone(), JWT_SECRET.to_string())
+            .with_revocations(fresh_cache)
+            .revocation_service()
+            .check(&claims)
+            .unwrap_err();
+        assert!(matches!(&err, AppError::Unauthorized(m) if m == TOKEN_REVOKED_MESSAGE), "{:?}", err);
+    }
+}
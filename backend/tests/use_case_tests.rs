@@ -20,14 +20,11 @@ mod admin_room_management {
     fn test_admin_can_add_room() {
         // Use case: Add / Remove Rooms
         // Admin should be able to create new rooms with valid room types
-        let room_types = vec![RoomType::Single, RoomType::Double, RoomType::Suite];
+        let room_types = vec!["single", "double", "suite", "family_room"];
         
         for room_type in room_types {
-            // Verify room type is valid
-            assert!(matches!(
-                room_type,
-                RoomType::Single | RoomType::Double | RoomType::Suite
-            ));
+            // Verify room type code is valid
+            assert_eq!(RoomType::new(room_type).unwrap().as_str(), room_type);
         }
     }
 
@@ -50,7 +47,7 @@ mod admin_room_management {
     fn test_room_status_display_values() {
         // Use case: View Room Status
         // All room statuses should be representable
-        let statuses = vec![
+        let statuses = [
             RoomStatus::Available,
             RoomStatus::Occupied,
             RoomStatus::Maintenance,
@@ -65,7 +62,7 @@ mod admin_room_management {
     fn test_booking_status_for_view() {
         // Use case: View Bookings
         // All booking statuses should be filterable
-        let statuses = vec![
+        let statuses = [
             BookingStatus::Upcoming,
             BookingStatus::CheckedIn,
             BookingStatus::CheckedOut,
@@ -329,7 +326,7 @@ mod system_authentication {
     fn test_all_roles_can_login() {
         // Use case: Login / Logout
         // All user roles should be able to authenticate
-        let roles = vec![
+        let roles = [
             UserRole::Admin,
             UserRole::Receptionist,
             UserRole::Cleaner,
@@ -0,0 +1,169 @@
+//! Walk-in booking tests
+//!
+//! Tests for which rooms can take a walk-in guest and for creating the
+//! booking already checked in. The creation tests need a migrated PostgreSQL
+//! database and only run when TEST_DATABASE_URL is set.
+
+use bigdecimal::BigDecimal;
+use chrono::{Duration, Utc};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use hotel_management_backend::db::{create_pool, DbPool};
+use hotel_management_backend::errors::AppError;
+use hotel_management_backend::models::{BookingStatus, Room, RoomStatus, RoomType, UserRole};
+use hotel_management_backend::schema::{bookings, rooms};
+use hotel_management_backend::services::auth_service::CreateUserRequest;
+use hotel_management_backend::services::booking_service::StaffBookingRequest;
+use hotel_management_backend::services::{AuthService, BookingService, RoomService};
+
+fn room_with_status(status: RoomStatus) -> Room {
+    Room {
+        id: Uuid::new_v4(),
+        number: "204".to_string(),
+        room_type: RoomType::DOUBLE,
+        status,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+        price: BigDecimal::from(1_500_000),
+        assigned_cleaner_id: None,
+        description: None,
+        amenities: vec![],
+        photo_urls: vec![],
+        decommissioned_at: None,
+        floor: 1,
+        building: None,
+        room_type_id: Uuid::new_v4(),
+    }
+}
+
+mod room_check_tests {
+    use super::*;
+
+    #[test]
+    fn test_available_room_takes_walk_in() {
+        assert!(BookingService::check_walk_in_room(&room_with_status(RoomStatus::Available)).is_ok());
+    }
+
+    #[test]
+    fn test_rooms_with_housekeeping_are_refused() {
+        for status in [RoomStatus::Dirty, RoomStatus::Cleaning] {
+            match BookingService::check_walk_in_room(&room_with_status(status)) {
+                Err(AppError::RoomUnavailable(msg)) => {
+                    assert!(msg.contains("204"));
+                    assert!(msg.contains("housekeeping"), "{}", msg);
+                }
+                other => panic!("Expected RoomUnavailable, got {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_occupied_and_maintenance_rooms_are_refused() {
+        for status in [RoomStatus::Occupied, RoomStatus::Maintenance] {
+            assert!(matches!(
+                BookingService::check_walk_in_room(&room_with_status(status)),
+                Err(AppError::RoomUnavailable(_))
+            ));
+        }
+    }
+}
+
+fn test_pool() -> Option<DbPool> {
+    match std::env::var("TEST_DATABASE_URL") {
+        Ok(url) => Some(create_pool(&url)),
+        Err(_) => {
+            eprintln!("TEST_DATABASE_URL not set, skipping database test");
+            None
+        }
+    }
+}
+
+mod create_walk_in_tests {
+    use super::*;
+
+    fn setup(pool: &DbPool) -> (Room, Uuid) {
+        let suffix = &Uuid::new_v4().simple().to_string()[..8];
+        let room = RoomService::new(pool.clone())
+            .create_room(&format!("W{}", suffix), RoomType::SINGLE)
+            .unwrap();
+        let receptionist = AuthService::new(pool.clone(), "test-secret".to_string())
+            .create_user(&CreateUserRequest {
+                username: format!("walkin-{}", suffix),
+                password: "desk-password-1".to_string(),
+                role: UserRole::Receptionist,
+                must_change_password: false,
+            })
+            .unwrap();
+        (room, receptionist.id)
+    }
+
+    fn request(pool: &DbPool, room: &Room, nights: i64) -> StaffBookingRequest {
+        let today = BookingService::new(pool.clone()).today().unwrap();
+        StaffBookingRequest {
+            guest_name: format!("Walk In {}", room.number),
+            room_id: room.id,
+            check_in_date: today,
+            check_out_date: today + Duration::days(nights),
+            price: None,
+            allow_duplicate: false,
+            override_conflict: false,
+        }
+    }
+
+    #[test]
+    fn test_walk_in_is_checked_in_and_room_occupied() {
+        let Some(pool) = test_pool() else { return };
+        let (room, actor) = setup(&pool);
+        let service = BookingService::new(pool.clone());
+
+        let created = service.create_walk_in(&request(&pool, &room, 2), actor).unwrap();
+
+        assert_eq!(created.booking.status, BookingStatus::CheckedIn);
+        assert_eq!(created.room.unwrap().status, RoomStatus::Occupied);
+        assert_eq!(created.booking.price, &room.price * BigDecimal::from(2));
+
+        let events = service.list_events(created.booking.id).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].to_status, Some(BookingStatus::CheckedIn));
+        assert_eq!(events[0].actor_user_id, Some(actor));
+    }
+
+    #[test]
+    fn test_walk_in_must_start_today() {
+        let Some(pool) = test_pool() else { return };
+        let (room, actor) = setup(&pool);
+
+        let mut tomorrow = request(&pool, &room, 2);
+        tomorrow.check_in_date += Duration::days(1);
+        tomorrow.check_out_date += Duration::days(1);
+
+        assert!(matches!(
+            BookingService::new(pool.clone()).create_walk_in(&tomorrow, actor),
+            Err(AppError::ValidationError(_))
+        ));
+    }
+
+    #[test]
+    fn test_dirty_room_leaves_nothing_behind() {
+        let Some(pool) = test_pool() else { return };
+        let (room, actor) = setup(&pool);
+        let mut conn = pool.get().unwrap();
+        diesel::update(rooms::table.find(room.id))
+            .set(rooms::status.eq(RoomStatus::Dirty))
+            .execute(&mut conn)
+            .unwrap();
+
+        assert!(matches!(
+            BookingService::new(pool.clone()).create_walk_in(&request(&pool, &room, 1), actor),
+            Err(AppError::RoomUnavailable(_))
+        ));
+
+        let booked: i64 = bookings::table
+            .filter(bookings::room_id.eq(room.id))
+            .count()
+            .get_result(&mut conn)
+            .unwrap();
+        assert_eq!(booked, 0);
+    }
+}
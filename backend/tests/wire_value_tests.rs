@@ -0,0 +1,104 @@
+//! Wire value tests
+//!
+//! Status, room type and role strings must round-trip through Display/FromStr using
+//! exactly the snake_case values serde and the database use.
+
+use hotel_management_backend::errors::AppError;
+use hotel_management_backend::models::{BookingStatus, RoomStatus, RoomType, UserRole};
+
+mod booking_status_tests {
+    use super::*;
+
+    #[test]
+    fn test_every_variant_round_trips() {
+        for status in BookingStatus::ALL {
+            let wire = status.to_string();
+            assert_eq!(wire.parse::<BookingStatus>().unwrap(), status);
+            assert_eq!(serde_json::to_string(&status).unwrap(), format!("\"{}\"", wire));
+        }
+    }
+
+    #[test]
+    fn test_typo_is_rejected_with_valid_values() {
+        let err = "canceled".parse::<BookingStatus>().unwrap_err();
+        match err {
+            AppError::ValidationError(msg) => {
+                assert!(msg.contains("canceled"));
+                assert!(msg.contains("cancelled"));
+                assert!(msg.contains("no_show"));
+            }
+            other => panic!("Expected ValidationError, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_pascal_case_is_rejected() {
+        assert!("CheckedIn".parse::<BookingStatus>().is_err());
+        assert!("checked_in".parse::<BookingStatus>().is_ok());
+    }
+}
+
+mod room_status_tests {
+    use super::*;
+
+    #[test]
+    fn test_every_variant_round_trips() {
+        for status in RoomStatus::ALL {
+            let wire = status.to_string();
+            assert_eq!(wire.parse::<RoomStatus>().unwrap(), status);
+            assert_eq!(serde_json::to_string(&status).unwrap(), format!("\"{}\"", wire));
+        }
+    }
+
+    #[test]
+    fn test_unknown_value_is_rejected() {
+        assert!("Available".parse::<RoomStatus>().is_err());
+        assert!("clean".parse::<RoomStatus>().is_err());
+    }
+}
+
+mod room_type_tests {
+    use super::*;
+
+    #[test]
+    fn test_legacy_codes_round_trip() {
+        for room_type in RoomType::LEGACY {
+            let wire = room_type.to_string();
+            assert_eq!(wire.parse::<RoomType>().unwrap(), room_type);
+            assert_eq!(serde_json::to_string(&room_type).unwrap(), format!("\"{}\"", wire));
+            assert_eq!(serde_json::from_str::<RoomType>(&format!("\"{}\"", wire)).unwrap(), room_type);
+        }
+    }
+
+    #[test]
+    fn test_codes_are_normalized() {
+        assert_eq!(" Family_Room ".parse::<RoomType>().unwrap().as_str(), "family_room");
+        assert_eq!(serde_json::from_str::<RoomType>("\"Suite\"").unwrap(), RoomType::SUITE);
+    }
+
+    #[test]
+    fn test_malformed_code_is_rejected() {
+        for bad in ["", "Twin Room!", "2beds", &"a".repeat(RoomType::MAX_LEN + 1)] {
+            assert!(matches!(bad.parse::<RoomType>(), Err(AppError::ValidationError(_))), "{:?}", bad);
+        }
+        assert!(serde_json::from_str::<RoomType>("\"twin-room\"").is_err());
+    }
+}
+
+mod user_role_tests {
+    use super::*;
+
+    #[test]
+    fn test_every_variant_round_trips() {
+        for role in UserRole::ALL {
+            let wire = role.to_string();
+            assert_eq!(wire.parse::<UserRole>().unwrap(), role);
+            assert_eq!(serde_json::to_string(&role).unwrap(), format!("\"{}\"", wire));
+        }
+    }
+
+    #[test]
+    fn test_pascal_case_is_rejected() {
+        assert!("Receptionist".parse::<UserRole>().is_err());
+    }
+}
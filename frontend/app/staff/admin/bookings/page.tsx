@@ -25,7 +25,11 @@ import {
   type BookingFiltersState,
 } from "@/components/booking-filters";
 import { CheckInPaymentDialog } from "@/components/check-in-payment-dialog";
-import { apiClient, getErrorMessage } from "@/lib/api-client";
+import {
+  apiClient,
+  EARLY_CHECK_OUT_MESSAGE,
+  getErrorMessage,
+} from "@/lib/api-client";
 import { toast } from "@/hooks/use-toast";
 import type { CreatePaymentRequest } from "@/lib/validators";
 
@@ -49,6 +53,8 @@ export default function AdminBookingsPage() {
   const [checkOutDialog, setCheckOutDialog] = useState<{
     open: boolean;
     bookingId: string | null;
+    // Set when the server asked to confirm leaving before the check-out date
+    early?: boolean;
   }>({ open: false, bookingId: null });
   const [cancelDialog, setCancelDialog] = useState<{
     open: boolean;
@@ -124,9 +130,17 @@ export default function AdminBookingsPage() {
   });
 
   const checkOutMutation = useMutation({
-    mutationFn: async (bookingId: string) => {
+    mutationFn: async ({
+      bookingId,
+      confirmEarly,
+    }: {
+      bookingId: string;
+      confirmEarly: boolean;
+    }) => {
       // POST an explicit JSON body so axum's JSON extractor can deserialize
-      const response = await apiClient.post(`/bookings/${bookingId}/check-out`, { confirm_early: false });
+      const response = await apiClient.post(`/bookings/${bookingId}/check-out`, {
+        confirm_early: confirmEarly,
+      });
       return response.data;
     },
     onSuccess: () => {
@@ -144,7 +158,10 @@ export default function AdminBookingsPage() {
     },
     onError: (error: Error) => {
       const message = getErrorMessage(error);
-      if (message.includes("CheckOut")) {
+      if (message.startsWith(EARLY_CHECK_OUT_MESSAGE)) {
+        // Keep the dialog open and ask the receptionist to confirm
+        setCheckOutDialog((dialog) => ({ ...dialog, early: true }));
+      } else if (message.includes("CheckOut")) {
         queryClient.invalidateQueries({ queryKey: ["bookings-rooms"] });
         toast({
           title: "Already Checked Out",
@@ -229,7 +246,10 @@ export default function AdminBookingsPage() {
 
   const confirmCheckOut = () => {
     if (checkOutDialog.bookingId) {
-      checkOutMutation.mutate(checkOutDialog.bookingId);
+      checkOutMutation.mutate({
+        bookingId: checkOutDialog.bookingId,
+        confirmEarly: checkOutDialog.early ?? false,
+      });
     }
   };
 
@@ -302,11 +322,12 @@ export default function AdminBookingsPage() {
         <DialogContent className="bg-slate-900 border-slate-700">
           <DialogHeader>
             <DialogTitle className="text-slate-100">
-              Confirm Check-out
+              {checkOutDialog.early ? "Confirm Early Check-out" : "Confirm Check-out"}
             </DialogTitle>
             <DialogDescription className="text-slate-400">
-              Are you sure you want to check out this guest? The room will be
-              marked as available.
+              {checkOutDialog.early
+                ? "This guest is leaving before the scheduled check-out date. The stay will end today and the remaining nights will be released."
+                : "Are you sure you want to check out this guest? The room will be marked as available."}
             </DialogDescription>
           </DialogHeader>
           <DialogFooter>
@@ -324,7 +345,11 @@ export default function AdminBookingsPage() {
               disabled={checkOutMutation.isPending}
               className="bg-blue-600 hover:bg-blue-700 text-white"
             >
-              {checkOutMutation.isPending ? "Processing..." : "Check Out"}
+              {checkOutMutation.isPending
+                ? "Processing..."
+                : checkOutDialog.early
+                  ? "Confirm Early Check-out"
+                  : "Check Out"}
             </Button>
           </DialogFooter>
         </DialogContent>
@@ -37,7 +37,7 @@ import {
 import { useAuth } from "@/components/auth-provider";
 import { RouteGuard } from "@/components/route-guard";
 import { apiClient } from "@/lib/api-client";
-import { type Room, type BookingStatus } from "@/lib/validators";
+import { type RoomStatusSummary, type BookingStatus } from "@/lib/validators";
 
 interface BookingWithRoom {
   id: string;
@@ -87,20 +87,22 @@ export default function AdminDashboardPage() {
     enabled: isAuthenticated,
   });
 
-  const { data: rooms, isLoading: roomsLoading } = useQuery({
-    queryKey: ["rooms"],
+  // Counts only, cheap enough to keep the tiles live
+  const { data: summary, isLoading: roomsLoading } = useQuery({
+    queryKey: ["rooms", "summary"],
     queryFn: async () => {
-      const response = await apiClient.get<Room[]>("/rooms");
+      const response = await apiClient.get<RoomStatusSummary>("/rooms/summary");
       return response.data;
     },
     enabled: isAuthenticated,
+    refetchInterval: 15_000,
   });
 
   const roomStats = {
-    total: rooms?.length || 0,
-    available: rooms?.filter((r) => r.status === "available").length || 0,
-    occupied: rooms?.filter((r) => r.status === "occupied").length || 0,
-    maintenance: rooms?.filter((r) => r.status === "maintenance").length || 0,
+    total: summary?.total || 0,
+    available: summary?.by_status.available || 0,
+    occupied: summary?.by_status.occupied || 0,
+    maintenance: summary?.by_status.maintenance || 0,
   };
 
   if (isLoading) {
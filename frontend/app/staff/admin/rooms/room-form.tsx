@@ -1,6 +1,6 @@
 "use client";
 
-import { useState } from "react";
+import { useEffect, useState } from "react";
 import { useForm, UseFormReturn } from "react-hook-form";
 import { zodResolver } from "@hookform/resolvers/zod";
 import { z } from "zod";
@@ -24,21 +24,28 @@ import {
 } from "@/components/ui/card";
 
 import { apiClient, getErrorMessage } from "@/lib/api-client";
-import { type RoomType, type RoomStatus, type Room } from "@/lib/validators";
+import {
+  RoomType,
+  type RoomStatus,
+  type Room,
+  type RoomTypeConfig,
+} from "@/lib/validators";
 
 const createRoomSchema = z.object({
   number: z
     .string()
     .min(1, "Room number is required")
     .max(10, "Room number too long"),
-  room_type: z.enum(["single", "double", "suite"], {
-    required_error: "Please select a room type",
-  }),
+  room_type: z
+    .string({ required_error: "Please select a room type" })
+    .pipe(RoomType),
 });
 
 const updateRoomSchema = z.object({
-  room_type: z.enum(["single", "double", "suite"]).optional(),
+  room_type: RoomType.optional(),
   status: z.enum(["available", "occupied", "maintenance", "dirty", "cleaning"]).optional(),
+  // Opens a maintenance ticket when the room has none
+  reason: z.string().max(200, "Reason too long").optional(),
 });
 
 type CreateRoomData = z.infer<typeof createRoomSchema>;
@@ -53,8 +60,16 @@ interface RoomFormProps {
 export function RoomForm({ room, onSuccess, onCancel }: RoomFormProps) {
   const [isLoading, setIsLoading] = useState(false);
   const [error, setError] = useState<string | null>(null);
+  const [roomTypes, setRoomTypes] = useState<RoomTypeConfig[]>([]);
   const isEditMode = !!room;
 
+  useEffect(() => {
+    apiClient
+      .get<RoomTypeConfig[]>("/rooms/types")
+      .then((response) => setRoomTypes(response.data))
+      .catch((err: unknown) => setError(getErrorMessage(err)));
+  }, []);
+
   // Use separate forms for create and edit modes
   const createForm = useForm<CreateRoomData>({
     resolver: zodResolver(createRoomSchema),
@@ -87,7 +102,11 @@ export function RoomForm({ room, onSuccess, onCancel }: RoomFormProps) {
     try {
       let response;
       if (isEditMode) {
-        response = await apiClient.patch<Room>(`/rooms/${room.id}`, data);
+        // The server refuses the edit if the room changed since it was loaded
+        response = await apiClient.patch<Room>(`/rooms/${room.id}`, {
+          ...data,
+          expected_updated_at: room.updated_at,
+        });
       } else {
         response = await apiClient.post<Room>("/rooms", data);
       }
@@ -148,22 +167,22 @@ export function RoomForm({ room, onSuccess, onCancel }: RoomFormProps) {
             <Select
               value={selectedRoomType}
               onValueChange={(value) =>
-                setValue("room_type", value as RoomType)
+                setValue("room_type", value)
               }
             >
               <SelectTrigger className="bg-slate-700/50 border-slate-600 text-slate-100">
                 <SelectValue placeholder="Select room type" />
               </SelectTrigger>
               <SelectContent className="bg-slate-800 border-slate-700">
-                <SelectItem value="single" className="text-slate-100">
-                  Single
-                </SelectItem>
-                <SelectItem value="double" className="text-slate-100">
-                  Double
-                </SelectItem>
-                <SelectItem value="suite" className="text-slate-100">
-                  Suite
-                </SelectItem>
+                {roomTypes.map((type) => (
+                  <SelectItem
+                    key={type.id}
+                    value={type.code}
+                    className="text-slate-100"
+                  >
+                    {type.display_name}
+                  </SelectItem>
+                ))}
               </SelectContent>
             </Select>
             {errors.room_type && (
@@ -225,6 +244,31 @@ export function RoomForm({ room, onSuccess, onCancel }: RoomFormProps) {
             </div>
           )}
 
+          {/* Maintenance reason (only when moving a room into maintenance) */}
+          {isEditMode &&
+            selectedStatus === "maintenance" &&
+            room.status !== "maintenance" && (
+              <div className="space-y-2">
+                <Label htmlFor="reason" className="text-slate-300">
+                  Maintenance Reason
+                </Label>
+                <Input
+                  id="reason"
+                  placeholder="e.g., Leaking shower"
+                  className="bg-slate-700/50 border-slate-600 text-slate-100 placeholder:text-slate-500"
+                  {...register("reason")}
+                />
+                <p className="text-xs text-slate-500">
+                  Opens a maintenance ticket unless the room already has one.
+                </p>
+                {"reason" in errors && (
+                  <p className="text-sm text-red-400">
+                    {errors.reason?.message as string}
+                  </p>
+                )}
+              </div>
+            )}
+
           {/* Actions */}
           <div className="flex gap-4 pt-4">
             {onCancel && (
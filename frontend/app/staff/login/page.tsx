@@ -1,6 +1,6 @@
 "use client";
 
-import { useState } from "react";
+import { useEffect, useState } from "react";
 import { useForm } from "react-hook-form";
 import { zodResolver } from "@hookform/resolvers/zod";
 import { z } from "zod";
@@ -15,7 +15,11 @@ import {
   CardHeader,
   CardTitle,
 } from "@/components/ui/card";
-import { getErrorMessage } from "@/lib/api-client";
+import {
+  getErrorMessage,
+  INACTIVITY_MESSAGE,
+  INACTIVITY_REASON,
+} from "@/lib/api-client";
 import { PawPrint, Building2 } from "lucide-react";
 import Link from "next/link";
 
@@ -30,6 +34,13 @@ export default function StaffLoginPage() {
   const { login, isLoading } = useAuth();
   const [error, setError] = useState<string | null>(null);
 
+  useEffect(() => {
+    const reason = new URLSearchParams(window.location.search).get("reason");
+    if (reason === INACTIVITY_REASON) {
+      setError(INACTIVITY_MESSAGE);
+    }
+  }, []);
+
   const {
     register,
     handleSubmit,
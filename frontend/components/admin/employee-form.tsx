@@ -8,6 +8,7 @@ import { useQuery } from "@tanstack/react-query";
 import { Button } from "@/components/ui/button";
 import { Input } from "@/components/ui/input";
 import { Label } from "@/components/ui/label";
+import { Switch } from "@/components/ui/switch";
 import {
   Select,
   SelectContent,
@@ -65,6 +66,7 @@ export function EmployeeForm({
       role: "receptionist",
       email: null,
       full_name: null,
+      must_change_password: true,
     },
   });
 
@@ -184,6 +186,19 @@ export function EmployeeForm({
               {createForm.formState.errors.password?.message as string}
             </p>
           )}
+          <div className="flex items-center space-x-2 pt-1">
+            <Switch
+              id="must-change-password"
+              checked={createForm.watch("must_change_password") ?? false}
+              onCheckedChange={(checked: boolean) =>
+                createForm.setValue("must_change_password", checked)
+              }
+              disabled={isLoading}
+            />
+            <Label htmlFor="must-change-password" className="text-slate-300 text-sm cursor-pointer">
+              Require a new password on first login
+            </Label>
+          </div>
         </div>
       )}
 
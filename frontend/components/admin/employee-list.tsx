@@ -29,7 +29,12 @@ import {
 } from "@/components/ui/tooltip";
 
 import { type Employee, type UserRole } from "@/lib/validators";
-import { deleteEmployee, reactivateEmployee, getErrorMessage } from "@/lib/api-client";
+import {
+  deleteEmployee,
+  reactivateEmployee,
+  revokeEmployeeSessions,
+  getErrorMessage,
+} from "@/lib/api-client";
 import { EmployeeForm } from "./employee-form";
 
 interface EmployeeListProps {
@@ -50,6 +55,7 @@ export function EmployeeList({
   const [deleteError, setDeleteError] = useState<string | null>(null);
   const [deletingId, setDeletingId] = useState<string | null>(null);
   const [reactivatingId, setReactivatingId] = useState<string | null>(null);
+  const [revokingId, setRevokingId] = useState<string | null>(null);
 
   // Check if employee is the last admin
   const isLastAdmin = (employee: Employee): boolean => {
@@ -189,6 +195,27 @@ export function EmployeeList({
     }
   };
 
+  const handleRevokeSessions = async (employee: Employee) => {
+    if (
+      !confirm(
+        `Sign ${employee.username || employee.full_name || "this employee"} out on every device? They will have to log in again.`
+      )
+    ) {
+      return;
+    }
+
+    setRevokingId(employee.id);
+    setDeleteError(null);
+
+    try {
+      await revokeEmployeeSessions(employee.id);
+    } catch (err: unknown) {
+      setDeleteError(`Failed to sign employee out: ${getErrorMessage(err)}`);
+    } finally {
+      setRevokingId(null);
+    }
+  };
+
   if (isLoading) {
     return (
       <Card className="bg-slate-800/80 border-slate-700">
@@ -253,6 +280,7 @@ export function EmployeeList({
                 <TableHead className="text-slate-300">Email</TableHead>
                 <TableHead className="text-slate-300">Role</TableHead>
                 <TableHead className="text-slate-300">Created</TableHead>
+                <TableHead className="text-slate-300">Last Login</TableHead>
                 <TableHead className="text-slate-300">Status</TableHead>
                 <TableHead className="text-slate-300 text-right">Actions</TableHead>
               </TableRow>
@@ -276,6 +304,11 @@ export function EmployeeList({
                   <TableCell className="text-slate-400 text-sm">
                     {format(new Date(employee.created_at), "MMM d, yyyy")}
                   </TableCell>
+                  <TableCell className="text-slate-400 text-sm">
+                    {employee.last_login_at
+                      ? format(new Date(employee.last_login_at), "MMM d, yyyy HH:mm")
+                      : "Never"}
+                  </TableCell>
                   <TableCell>
                     {employee.deactivated_at ? (
                       <Badge className="bg-red-500 hover:bg-red-600">
@@ -298,6 +331,18 @@ export function EmployeeList({
                         <Edit className="h-4 w-4" />
                       </Button>
 
+                      {!employee.deactivated_at && (
+                        <Button
+                          variant="ghost"
+                          size="sm"
+                          onClick={() => handleRevokeSessions(employee)}
+                          disabled={revokingId === employee.id}
+                          className="h-8 text-xs text-slate-300 hover:text-slate-100 hover:bg-slate-700"
+                        >
+                          {revokingId === employee.id ? "..." : "Sign out"}
+                        </Button>
+                      )}
+
                       {/* <--- CHANGED: Replaced Trash Icon with explicit Deactivate Button */}
                       {!employee.deactivated_at && (
                         <TooltipProvider>
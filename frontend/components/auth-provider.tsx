@@ -53,7 +53,10 @@ export function AuthProvider({ children }: { children: React.ReactNode }) {
       authLogin(response.data);
       setUser(response.data.user);
       const role = response.data.user.role;
-      if (role === 'admin') {
+      if (response.data.must_change_password) {
+        // Nothing else works until the temporary password is replaced
+        router.push(`/staff/${role}/settings`);
+      } else if (role === 'admin') {
         router.push('/staff/admin/dashboard');
       } else if (role === 'receptionist') {
         router.push('/staff/receptionist/dashboard');
@@ -116,12 +116,12 @@ export function BookingForm({ onSuccess, onCancel }: BookingFormProps) {
   };
 
   const getRoomTypeLabel = (type: RoomType) => {
-    const labels: Record<RoomType, string> = {
+    const labels: Record<string, string> = {
       single: "Single",
       double: "Double",
       suite: "Suite",
     };
-    return labels[type];
+    return labels[type] || type;
   };
 
   const getStatusBadge = (isAvailable: boolean) => {
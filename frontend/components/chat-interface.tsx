@@ -17,13 +17,24 @@ interface Message {
   image_url?: string;
   is_read: boolean;
   created_at: string;
+  edited_at?: string | null;
+  deleted?: boolean;
 }
 
+// How long after sending a message its sender may edit or delete it
+const MESSAGE_CHANGE_WINDOW_MS = 15 * 60 * 1000;
+
 interface Contact {
   id: string;
   name: string;
   role: string;
   unread_count: number;
+  online: boolean;
+}
+
+interface ChatHistoryPage {
+  messages: Message[];
+  next_cursor: string | null;
 }
 
 interface ChatInterfaceProps {
@@ -57,6 +68,19 @@ export function ChatInterface({ currentUser, token }: ChatInterfaceProps) {
   const [bookingStatuses, setBookingStatuses] = useState<Map<string, 'booked' | 'cancelled'>>(new Map());
   const scrollRef = useRef<HTMLDivElement>(null);
   const fileInputRef = useRef<HTMLInputElement>(null);
+  // Newest message received over the socket, so a reconnect only syncs what came after it
+  const lastReceivedAtRef = useRef<string | null>(null);
+  // Presence changes seen since the contact list was fetched
+  const [online, setOnline] = useState<Record<string, boolean>>({});
+  // Contacts currently typing to us, cleared shortly after their last keystroke
+  const [typing, setTyping] = useState<Record<string, boolean>>({});
+  const typingTimersRef = useRef<Record<string, ReturnType<typeof setTimeout>>>({});
+  const lastTypingSentRef = useRef(0);
+  // Cursor for the next older history page, null once the first message is loaded
+  const [nextCursor, setNextCursor] = useState<string | null>(null);
+  const [isLoadingEarlier, setIsLoadingEarlier] = useState(false);
+  // Scroll height before older messages were prepended, to keep the view in place
+  const prependedFromHeightRef = useRef<number | null>(null);
 
   // Fetch contacts
   const { data: contacts = [], refetch: refetchContacts } = useQuery<Contact[]>({
@@ -84,12 +108,12 @@ export function ChatInterface({ currentUser, token }: ChatInterfaceProps) {
     enabled: !!currentUser.id && currentUser.role === "guest",
   });
 
-  // Fetch message history when active contact changes
-  const { data: historyMessages = [] } = useQuery<Message[]>({
+  // Fetch the latest page of message history when active contact changes
+  const { data: historyPage } = useQuery<ChatHistoryPage>({
     queryKey: ["chat", "history", activeContact?.id],
     queryFn: async () => {
-      if (!activeContact) return [];
-      const response = await apiClient.get<Message[]>("/chat/history", {
+      if (!activeContact) return { messages: [], next_cursor: null };
+      const response = await apiClient.get<ChatHistoryPage>("/chat/v2/history", {
         params: { other_user_id: activeContact.id },
       });
       return response.data;
@@ -97,48 +121,143 @@ export function ChatInterface({ currentUser, token }: ChatInterfaceProps) {
     enabled: !!activeContact && !!currentUser.id,
   });
 
-  // Clear messages when switching contacts and refetch contacts to update unread counts
+  // Clear messages when switching contacts
   useEffect(() => {
     setMessages([]);
-    if (activeContact) {
-      // Refetch contacts after a short delay to allow backend to mark messages as read
-      setTimeout(() => {
-        refetchContacts();
-      }, 300);
-    }
-  }, [activeContact?.id, refetchContacts]);
+    setNextCursor(null);
+  }, [activeContact?.id]);
 
   // Update messages when history changes
   useEffect(() => {
-    setMessages(historyMessages);
-  }, [historyMessages]);
+    if (!historyPage) return;
+    // Pages come newest first; the feed shows oldest first
+    setMessages([...historyPage.messages].reverse());
+    setNextCursor(historyPage.next_cursor);
+  }, [historyPage]);
+
+  const loadEarlier = async () => {
+    if (!activeContact || !nextCursor || isLoadingEarlier) return;
+    setIsLoadingEarlier(true);
+    try {
+      const response = await apiClient.get<ChatHistoryPage>("/chat/v2/history", {
+        params: { other_user_id: activeContact.id, before: nextCursor },
+      });
+      prependedFromHeightRef.current = scrollRef.current?.scrollHeight ?? null;
+      const older = [...response.data.messages].reverse();
+      setMessages((prev) => [...older.filter((m) => !prev.some((p) => p.id === m.id)), ...prev]);
+      setNextCursor(response.data.next_cursor);
+    } catch (error) {
+      console.error("Failed to load earlier messages:", error);
+    } finally {
+      setIsLoadingEarlier(false);
+    }
+  };
+
+  // Swap in an edited or deleted version of a message already shown
+  const replaceMessage = (updated: Message) => {
+    setMessages((prev) => prev.map((m) => (m.id === updated.id ? updated : m)));
+  };
+
+  // Mark the open conversation's incoming messages read once they are shown
+  useEffect(() => {
+    if (!ws || ws.readyState !== WebSocket.OPEN) return;
+    const unread = messages
+      .filter((m) => m.receiver_id === currentUser.id && !m.is_read)
+      .map((m) => m.id);
+    if (unread.length === 0) return;
+    ws.send(JSON.stringify({ type: "mark_read", message_ids: unread }));
+    setMessages((prev) =>
+      prev.map((m) => (unread.includes(m.id) ? { ...m, is_read: true } : m))
+    );
+    refetchContacts();
+  }, [messages, ws, currentUser.id, refetchContacts]);
 
   // WebSocket connection
   useEffect(() => {
     if (!currentUser.id || !token) return;
 
     const wsUrl = process.env.NEXT_PUBLIC_WS_URL || "ws://localhost:8080";
+    const since = lastReceivedAtRef.current
+      ? `&since=${encodeURIComponent(lastReceivedAtRef.current)}`
+      : "";
     const socket = new WebSocket(
-      `${wsUrl}/api/chat/ws?token=${encodeURIComponent(token)}`
+      `${wsUrl}/api/chat/ws?token=${encodeURIComponent(token)}${since}`
     );
 
     socket.onopen = () => {
       console.log("WebSocket connected");
     };
 
+    const receive = (msg: Message) => {
+      if (!lastReceivedAtRef.current || msg.created_at > lastReceivedAtRef.current) {
+        lastReceivedAtRef.current = msg.created_at;
+      }
+      // Only append if it belongs to current active conversation
+      if (
+        activeContact &&
+        (msg.sender_id === activeContact.id || msg.receiver_id === activeContact.id)
+      ) {
+        setMessages((prev) => {
+          // A message can arrive both in the sync and over the socket
+          if (prev.some((m) => m.id === msg.id)) return prev;
+          return [...prev, msg];
+        });
+      }
+    };
+
     socket.onmessage = (event) => {
       try {
-        const msg = JSON.parse(event.data) as Message;
-        // Only append if it belongs to current active conversation
+        const frame = JSON.parse(event.data);
+        if (frame.type === "sync") {
+          // Messages sent while this socket was not connected
+          (frame.messages as Message[]).forEach(receive);
+          if (frame.messages.length > 0) refetchContacts();
+          return;
+        }
+        if (frame.type === "read") {
+          // The other party has seen these of our messages
+          const seen = frame.message_ids as string[];
+          setMessages((prev) =>
+            prev.map((m) => (seen.includes(m.id) ? { ...m, is_read: true } : m))
+          );
+          return;
+        }
+        if (frame.type === "error") {
+          // A message or receipt the server could not handle
+          toast({
+            variant: "destructive",
+            title: "Error",
+            description: frame.message ?? "Something went wrong. Please try again.",
+          });
+          return;
+        }
+        if (frame.type === "message_updated" || frame.type === "message_deleted") {
+          replaceMessage(frame.message as Message);
+          return;
+        }
+        if (frame.type === "presence") {
+          setOnline((prev) => ({ ...prev, [frame.user_id]: frame.online }));
+          return;
+        }
+        if (frame.type === "typing") {
+          const senderId = frame.sender_id as string;
+          clearTimeout(typingTimersRef.current[senderId]);
+          setTyping((prev) => ({ ...prev, [senderId]: true }));
+          typingTimersRef.current[senderId] = setTimeout(() => {
+            setTyping((prev) => ({ ...prev, [senderId]: false }));
+          }, 4000);
+          return;
+        }
+        if (frame.type) return;
+        const msg = frame as Message;
+        receive(msg);
+        // A message ends the sender's typing indicator
+        clearTimeout(typingTimersRef.current[msg.sender_id]);
+        setTyping((prev) => ({ ...prev, [msg.sender_id]: false }));
         if (
           activeContact &&
           (msg.sender_id === activeContact.id || msg.receiver_id === activeContact.id)
         ) {
-          setMessages((prev) => {
-            // Check if message already exists
-            if (prev.some((m) => m.id === msg.id)) return prev;
-            return [...prev, msg];
-          });
           // Refetch contacts to update unread counts
           refetchContacts();
         }
@@ -162,10 +281,15 @@ export function ChatInterface({ currentUser, token }: ChatInterfaceProps) {
     };
   }, [currentUser.id, activeContact, refetchContacts, token]);
 
-  // Auto-scroll to bottom
+  // Auto-scroll to bottom, except when older messages were added above
   useEffect(() => {
     if (scrollRef.current) {
-      scrollRef.current.scrollTop = scrollRef.current.scrollHeight;
+      const fromHeight = prependedFromHeightRef.current;
+      prependedFromHeightRef.current = null;
+      scrollRef.current.scrollTop =
+        fromHeight === null
+          ? scrollRef.current.scrollHeight
+          : scrollRef.current.scrollHeight - fromHeight;
     }
   }, [messages, activeContact]);
 
@@ -193,6 +317,52 @@ export function ChatInterface({ currentUser, token }: ChatInterfaceProps) {
     }
   };
 
+  const canChange = (msg: Message) =>
+    msg.sender_id === currentUser.id &&
+    !msg.deleted &&
+    (currentUser.role === "admin" ||
+      Date.now() - new Date(msg.created_at).getTime() < MESSAGE_CHANGE_WINDOW_MS);
+
+  const editMessage = async (msg: Message) => {
+    const content = window.prompt("Edit message", msg.content);
+    if (content === null || content === msg.content) return;
+    try {
+      const res = await apiClient.patch<Message>(`/chat/messages/${msg.id}`, { content });
+      replaceMessage(res.data);
+    } catch (error) {
+      console.error("Failed to edit message:", error);
+      toast({
+        variant: "destructive",
+        title: "Error",
+        description: "Could not edit the message.",
+      });
+    }
+  };
+
+  const deleteMessage = async (msg: Message) => {
+    if (!window.confirm("Delete this message for everyone?")) return;
+    try {
+      const res = await apiClient.delete<Message>(`/chat/messages/${msg.id}`);
+      replaceMessage(res.data);
+    } catch (error) {
+      console.error("Failed to delete message:", error);
+      toast({
+        variant: "destructive",
+        title: "Error",
+        description: "Could not delete the message.",
+      });
+    }
+  };
+
+  // Let the active contact know we are typing, at most every two seconds
+  const notifyTyping = () => {
+    if (!ws || ws.readyState !== WebSocket.OPEN || !activeContact) return;
+    const now = Date.now();
+    if (now - lastTypingSentRef.current < 2000) return;
+    lastTypingSentRef.current = now;
+    ws.send(JSON.stringify({ type: "typing", receiver_id: activeContact.id }));
+  };
+
   const sendMessage = (imageUrl?: string) => {
     if (
       (!inputText.trim() && !imageUrl) ||
@@ -455,7 +625,7 @@ export function ChatInterface({ currentUser, token }: ChatInterfaceProps) {
                     {activeContact.name}
                   </h3>
                   <span className="text-xs text-slate-400 capitalize">
-                    {activeContact.role}
+                    {typing[activeContact.id] ? "typing…" : activeContact.role}
                   </span>
                 </div>
               </div>
@@ -467,6 +637,21 @@ export function ChatInterface({ currentUser, token }: ChatInterfaceProps) {
               ref={scrollRef}
             >
               <div className="flex flex-col gap-4 max-w-5xl mx-auto pb-4 pt-4">
+                {nextCursor && (
+                  <Button
+                    variant="ghost"
+                    size="sm"
+                    onClick={loadEarlier}
+                    disabled={isLoadingEarlier}
+                    className="self-center text-xs text-slate-400 hover:text-amber-300 hover:bg-amber-500/10"
+                  >
+                    {isLoadingEarlier ? (
+                      <Loader2 className="w-3 h-3 animate-spin" />
+                    ) : (
+                      "Load earlier messages"
+                    )}
+                  </Button>
+                )}
                 {messages.map((msg) => {
                   const isMe = msg.sender_id === currentUser.id;
                   const bookingProposal = !isMe ? parseBookingProposal(msg.content) : null;
@@ -514,11 +699,28 @@ export function ChatInterface({ currentUser, token }: ChatInterfaceProps) {
                                 className="mb-2 rounded-lg max-h-60 object-cover border border-white/10"
                               />
                             )}
-                            <p>{msg.content}</p>
+                            {msg.deleted ? (
+                              <p className="italic text-slate-400">Message deleted</p>
+                            ) : (
+                              <p>{msg.content}</p>
+                            )}
                           </div>
                         )}
                         <span className="text-[10px] text-slate-500 px-1">
                           {format(new Date(msg.created_at), "HH:mm")}
+                          {msg.edited_at && !msg.deleted && " · edited"}
+                          {canChange(msg) && (
+                            <>
+                              {" · "}
+                              <button onClick={() => editMessage(msg)} className="hover:text-amber-300">
+                                Edit
+                              </button>
+                              {" · "}
+                              <button onClick={() => deleteMessage(msg)} className="hover:text-amber-300">
+                                Delete
+                              </button>
+                            </>
+                          )}
                         </span>
                       </div>
                     </div>
@@ -553,7 +755,11 @@ export function ChatInterface({ currentUser, token }: ChatInterfaceProps) {
 
                 <textarea
                   value={inputText}
-                  onChange={(e) => setInputText(e.target.value)}
+                  maxLength={4000}
+                  onChange={(e) => {
+                    setInputText(e.target.value);
+                    notifyTyping();
+                  }}
                   onKeyDown={(e) => {
                     if (e.key === "Enter" && !e.shiftKey) {
                       e.preventDefault();
@@ -630,7 +836,7 @@ export function ChatInterface({ currentUser, token }: ChatInterfaceProps) {
             >
               {/* Avatar */}
               <div
-                className={`w-10 h-10 rounded-full flex items-center justify-center text-white font-medium shadow-sm
+                className={`relative w-10 h-10 rounded-full flex items-center justify-center text-white font-medium shadow-sm
                 ${
                   activeContact?.id === contact.id
                     ? "bg-amber-500/30"
@@ -639,6 +845,9 @@ export function ChatInterface({ currentUser, token }: ChatInterfaceProps) {
               `}
               >
                 {contact.name[0].toUpperCase()}
+                {(online[contact.id] ?? contact.online) && (
+                  <span className="absolute bottom-0 right-0 w-2.5 h-2.5 rounded-full bg-emerald-400 ring-2 ring-slate-900" />
+                )}
               </div>
               <div className="flex-1 overflow-hidden min-w-0">
                 <p className="font-medium truncate text-sm">{contact.name}</p>
@@ -51,7 +51,7 @@ describe("Admin: Add / Remove Rooms", () => {
   });
 
   it("should accept all room types", () => {
-    const roomTypes = ["single", "double", "suite"];
+    const roomTypes = ["single", "double", "suite", "family_room"];
 
     roomTypes.forEach((type) => {
       const result = RoomType.safeParse(type);
@@ -60,7 +60,7 @@ describe("Admin: Add / Remove Rooms", () => {
   });
 
   it("should reject invalid room type", () => {
-    const result = RoomType.safeParse("penthouse");
+    const result = RoomType.safeParse("Pent House!");
     expect(result.success).toBe(false);
   });
 
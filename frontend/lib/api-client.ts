@@ -14,12 +14,21 @@ export const apiClient = axios.create({
 
 // Staff auth keyss
 const TOKEN_KEY = "hms_token";
+const REFRESH_TOKEN_KEY = "hms_refresh_token";
 const USER_KEY = "hms_user";
 
 // Guest auth keys
 const GUEST_TOKEN_KEY = "guest_token";
 const GUEST_USER_KEY = "guest_user";
 
+// Login page query value set when the server ended an idle session
+export const INACTIVITY_REASON = "inactivity";
+export const INACTIVITY_MESSAGE = "You were logged out due to inactivity. Please sign in again.";
+
+// Start of the check-out error asking staff to confirm an early departure
+export const EARLY_CHECK_OUT_MESSAGE =
+  "Guest is checking out before the scheduled check-out date";
+
 // Request interceptor to add JWT token
 // Checks for both staff and guest tokens
 apiClient.interceptors.request.use(
@@ -41,10 +50,67 @@ apiClient.interceptors.request.use(
   }
 );
 
+// Staff refresh in flight, shared by requests that fail at the same time
+let refreshing: Promise<string> | null = null;
+
+// Exchange the stored refresh token for a new access token, storing both
+// new tokens
+function refreshAccessToken(refreshToken: string): Promise<string> {
+  refreshing ??= axios
+    .post<{ token: string; refresh_token: string }>(`${API_BASE_URL}/auth/refresh`, {
+      refresh_token: refreshToken,
+    })
+    .then(({ data }) => {
+      localStorage.setItem(TOKEN_KEY, data.token);
+      localStorage.setItem(REFRESH_TOKEN_KEY, data.refresh_token);
+      return data.token;
+    })
+    .finally(() => {
+      refreshing = null;
+    });
+  return refreshing;
+}
+
 // Response interceptor for error handling
 apiClient.interceptors.response.use(
   (response) => response,
-  (error: AxiosError<{ code: string; message: string }>) => {
+  async (error: AxiosError<{ code: string; message: string }>) => {
+    // An expired staff access token is refreshed once and the request
+    // retried; idle sessions are not, so the inactivity logout still applies
+    const config = error.config as (InternalAxiosRequestConfig & { _retried?: boolean }) | undefined;
+    const refreshToken =
+      typeof window !== "undefined" ? localStorage.getItem(REFRESH_TOKEN_KEY) : null;
+    if (
+      error.response?.status === 401 &&
+      error.response.data?.code !== "SESSION_EXPIRED" &&
+      refreshToken &&
+      config &&
+      !config._retried &&
+      !config.url?.includes("change-password")
+    ) {
+      config._retried = true;
+      try {
+        const token = await refreshAccessToken(refreshToken);
+        config.headers.Authorization = `Bearer ${token}`;
+        return apiClient(config);
+      } catch {
+        // Fall through to the logout below
+      }
+    }
+
+    // A temporary password set by an admin must be replaced first
+    if (
+      error.response?.status === 403 &&
+      error.response.data?.code === "PASSWORD_CHANGE_REQUIRED" &&
+      typeof window !== "undefined"
+    ) {
+      const role = JSON.parse(localStorage.getItem(USER_KEY) ?? "null")?.role;
+      const settings = role ? `/staff/${role}/settings` : "/staff/login";
+      if (window.location.pathname !== settings) {
+        window.location.href = settings;
+      }
+    }
+
     if (error.response?.status === 401) {
       // Check if this error is from a password change request - if so, don't automatically logout
       const isChangePassword = error.config?.url?.includes("change-password");
@@ -52,6 +118,7 @@ apiClient.interceptors.response.use(
       if (!isChangePassword && typeof window !== "undefined") {
         // Clear both staff and guest tokens
         localStorage.removeItem(TOKEN_KEY);
+        localStorage.removeItem(REFRESH_TOKEN_KEY);
         localStorage.removeItem(USER_KEY);
         localStorage.removeItem(GUEST_TOKEN_KEY);
         localStorage.removeItem(GUEST_USER_KEY);
@@ -59,11 +126,16 @@ apiClient.interceptors.response.use(
         // Only redirect if not already on login/register pages
         const path = window.location.pathname;
         if (!path.includes("/login") && !path.includes("/register")) {
+          // Tell the login page why the user was signed out
+          const reason =
+            error.response.data?.code === "SESSION_EXPIRED"
+              ? `?reason=${INACTIVITY_REASON}`
+              : "";
           // Redirect to appropriate login page based on current path
           if (path.startsWith("/guest")) {
-            window.location.href = "/login";
+            window.location.href = `/login${reason}`;
           } else {
-            window.location.href = "/staff/login";
+            window.location.href = `/staff/login${reason}`;
           }
         }
       }
@@ -91,16 +163,17 @@ export function getErrorMessage(error: unknown): string {
 }
 
 // === Cleaner API Methods ===
-import { type Room, type RoomStatus } from "./validators";
+import { type CleanerRoom, type Room, type RoomStatus } from "./validators";
 
 /**
  * Get rooms for cleaner dashboard
- * Defaults to showing dirty rooms if no status filter is provided
+ * Defaults to showing dirty rooms if no status filter is provided.
+ * Rooms with an arrival today come first, then the longest waiting.
  */
 export async function getCleanerRooms(
   status?: RoomStatus,
   room_type?: string
-): Promise<Room[]> {
+): Promise<CleanerRoom[]> {
   const params: Record<string, string> = {};
   if (status) {
     params.status = status;
@@ -108,7 +181,7 @@ export async function getCleanerRooms(
   if (room_type) {
     params.room_type = room_type;
   }
-  const response = await apiClient.get<Room[]>("/cleaner/rooms", { params });
+  const response = await apiClient.get<CleanerRoom[]>("/cleaner/rooms", { params });
   return response.data;
 }
 
@@ -210,6 +283,27 @@ export async function reactivateEmployee(employeeId: string): Promise<void> {
   await apiClient.post(`/admin/employees/${employeeId}/reactivate`);
 }
 
+/**
+ * Sign an employee out everywhere by revoking all of their tokens
+ */
+export async function revokeEmployeeSessions(employeeId: string): Promise<void> {
+  await apiClient.post(`/admin/employees/${employeeId}/revoke-sessions`);
+}
+
+/**
+ * Hand the admin role to another employee. The current admin becomes a
+ * receptionist and both accounts are signed out.
+ */
+export async function transferAdmin(
+  employeeId: string
+): Promise<{ previous_admin: Employee; admin: Employee }> {
+  const response = await apiClient.post<{ previous_admin: Employee; admin: Employee }>(
+    "/admin/transfer-admin",
+    { employee_id: employeeId }
+  );
+  return response.data;
+}
+
 /**
  * Reset an employee's password
  */
@@ -218,4 +312,34 @@ export async function resetEmployeePassword(
   request: ResetPasswordRequest
 ): Promise<void> {
   await apiClient.post(`/admin/employees/${employeeId}/reset-password`, request);
-}
\ No newline at end of file
+}
+// === API Key Methods ===
+import {
+  type ApiKey,
+  type CreatedApiKey,
+  type CreateApiKeyRequest,
+} from "./validators";
+
+/**
+ * List integration API keys, newest first
+ */
+export async function listApiKeys(): Promise<ApiKey[]> {
+  const response = await apiClient.get<ApiKey[]>("/admin/api-keys");
+  return response.data;
+}
+
+/**
+ * Create an integration API key. The returned key is not shown again.
+ */
+export async function createApiKey(request: CreateApiKeyRequest): Promise<CreatedApiKey> {
+  const response = await apiClient.post<CreatedApiKey>("/admin/api-keys", request);
+  return response.data;
+}
+
+/**
+ * Revoke an integration API key
+ */
+export async function revokeApiKey(keyId: string): Promise<ApiKey> {
+  const response = await apiClient.post<ApiKey>(`/admin/api-keys/${keyId}/revoke`);
+  return response.data;
+}
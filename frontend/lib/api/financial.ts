@@ -173,3 +173,44 @@ export async function getRoomBookingHistory(
   );
   return response.data;
 }
+
+/**
+ * Bookings created and cancelled on one day (hotel time)
+ */
+export interface BookingStatsDay {
+  date: string;
+  created: number;
+  created_by_guests: number;
+  created_by_staff: number;
+  cancelled: number;
+}
+
+/**
+ * Booking activity over a range of days
+ */
+export interface BookingStats {
+  from: string;
+  to: string;
+  total_bookings: number;
+  created_by_guests: number;
+  created_by_staff: number;
+  guest_share: number;
+  cancelled_bookings: number;
+  cancellation_rate: number;
+  total_nights: number;
+  average_nights: number;
+  days: BookingStatsDay[];
+}
+
+/**
+ * Get booking statistics aggregated by the server (range capped at one year)
+ */
+export async function getBookingStats(
+  from: string,
+  to: string
+): Promise<BookingStats> {
+  const response = await apiClient.get<BookingStats>("/admin/stats/bookings", {
+    params: { from, to },
+  });
+  return response.data;
+}
@@ -1,4 +1,7 @@
+import type { Profile, UpdateProfileRequest } from './validators';
+
 const TOKEN_KEY = 'hms_token';
+const REFRESH_TOKEN_KEY = 'hms_refresh_token';
 const USER_KEY = 'hms_user';
 
 /**
@@ -22,7 +25,10 @@ export interface User {
 
 export interface LoginResponse {
   token: string;
+  refresh_token: string;
   user: User;
+  /** Only changing the password is allowed until it is done */
+  must_change_password?: boolean;
 }
 
 /**
@@ -49,6 +55,30 @@ export function removeAuthToken(): void {
   localStorage.removeItem(TOKEN_KEY);
 }
 
+/**
+ * Get the stored refresh token
+ */
+export function getRefreshToken(): string | null {
+  if (typeof window === 'undefined') return null;
+  return localStorage.getItem(REFRESH_TOKEN_KEY);
+}
+
+/**
+ * Store the refresh token
+ */
+export function setRefreshToken(token: string): void {
+  if (typeof window === 'undefined') return;
+  localStorage.setItem(REFRESH_TOKEN_KEY, token);
+}
+
+/**
+ * Remove the stored refresh token
+ */
+export function removeRefreshToken(): void {
+  if (typeof window === 'undefined') return;
+  localStorage.removeItem(REFRESH_TOKEN_KEY);
+}
+
 /**
  * Get the stored user info
  */
@@ -91,14 +121,28 @@ export function isAuthenticated(): boolean {
  */
 export function login(response: LoginResponse): void {
   setAuthToken(response.token);
+  setRefreshToken(response.refresh_token);
   setStoredUser(response.user);
 }
 
 /**
- * Logout and clear credentials
+ * Logout and clear credentials. The server revokes the refresh token and the
+ * current access token and ends the session; the local credentials are
+ * cleared either way.
  */
 export function logout(): void {
+  const refreshToken = getRefreshToken();
+  const accessToken = getAuthToken();
+  if (refreshToken) {
+    // The credentials are cleared before the request goes out, so the
+    // access token is passed along explicitly
+    const headers = accessToken ? { Authorization: `Bearer ${accessToken}` } : undefined;
+    import("./api-client")
+      .then(({ apiClient }) => apiClient.post("/auth/logout", { refresh_token: refreshToken }, { headers }))
+      .catch(() => undefined);
+  }
   removeAuthToken();
+  removeRefreshToken();
   removeStoredUser();
 }
 
@@ -129,3 +173,21 @@ export async function changePassword(currentPassword: string, newPassword: strin
   });
 }
 
+
+/**
+ * Get the current user's profile
+ */
+export async function getProfile(): Promise<Profile> {
+  const { apiClient } = await import("./api-client");
+  const response = await apiClient.get<Profile>("/auth/me");
+  return response.data;
+}
+
+/**
+ * Update the current user's profile. Staff may only change their name.
+ */
+export async function updateProfile(request: UpdateProfileRequest): Promise<Profile> {
+  const { apiClient } = await import("./api-client");
+  const response = await apiClient.patch<Profile>("/auth/me", request);
+  return response.data;
+}
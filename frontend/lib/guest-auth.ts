@@ -175,3 +175,42 @@ export async function changePasswordGuest(currentPassword: string, newPassword:
 }
 
 
+
+/**
+ * Ask for a password reset link to be emailed. Succeeds whether or not an
+ * account uses the email.
+ *
+ * @param email - Email address of the guest account
+ */
+export async function requestPasswordReset(email: string): Promise<void> {
+  await apiClient.post("/auth/forgot-password", { email });
+}
+
+/**
+ * Set a new password with the token from a reset link
+ *
+ * @param token - Token from the emailed link
+ * @param newPassword - The new password
+ */
+export async function resetPassword(token: string, newPassword: string): Promise<void> {
+  await apiClient.post("/auth/reset-password", {
+    token,
+    new_password: newPassword,
+  });
+}
+
+/**
+ * Verify the guest's email address with the token from a verification link
+ *
+ * @param token - Token from the emailed link
+ */
+export async function verifyEmail(token: string): Promise<void> {
+  await apiClient.post("/auth/verify-email", { token });
+}
+
+/**
+ * Send the verification email again. Allowed once every five minutes.
+ */
+export async function resendVerification(): Promise<void> {
+  await apiClient.post("/auth/resend-verification");
+}
@@ -4,7 +4,10 @@ import { z } from "zod";
 export const UserRole = z.enum(["admin", "receptionist", "guest", "cleaner"]);
 export type UserRole = z.infer<typeof UserRole>;
 
-export const RoomType = z.enum(["single", "double", "suite"]);
+// Room types are configured by admins; codes are lowercase like "double"
+export const RoomType = z
+  .string()
+  .regex(/^[a-z][a-z0-9_]{0,29}$/, "Invalid room type");
 export type RoomType = z.infer<typeof RoomType>;
 
 export const RoomStatus = z.enum(["available", "occupied", "maintenance", "dirty", "cleaning"]);
@@ -22,6 +25,7 @@ export type LoginRequest = z.infer<typeof LoginRequestSchema>;
 
 export const LoginResponseSchema = z.object({
   token: z.string(),
+  refresh_token: z.string(),
   user: z.object({
     id: z.string().uuid(),
     username: z.string().nullable().optional(),
@@ -48,6 +52,25 @@ export const UserInfoSchema = z.object({
 });
 export type UserInfo = z.infer<typeof UserInfoSchema>;
 
+export const ProfileSchema = z.object({
+  id: z.string().uuid(),
+  role: UserRole,
+  username: z.string().optional(),
+  email: z.string().nullable(),
+  full_name: z.string().nullable(),
+  phone: z.string().nullable(),
+  id_number: z.string().optional(),
+  created_at: z.string(),
+  deactivated: z.boolean(),
+});
+export type Profile = z.infer<typeof ProfileSchema>;
+
+export const UpdateProfileRequestSchema = z.object({
+  full_name: z.string().min(1, "Full name is required").max(100).optional(),
+  phone: z.string().max(20).optional(),
+});
+export type UpdateProfileRequest = z.infer<typeof UpdateProfileRequestSchema>;
+
 export const CreateUserRequestSchema = z.object({
   username: z.string().min(3).max(50),
   password: z.string().min(8),
@@ -61,6 +84,7 @@ export const GuestUserSchema = z.object({
   email: z.string().email(),
   full_name: z.string(),
   role: z.literal("guest"),
+  email_verified_at: z.string().nullable().optional(),
 });
 export type GuestUser = z.infer<typeof GuestUserSchema>;
 
@@ -96,9 +120,46 @@ export const RoomSchema = z.object({
   created_at: z.string().datetime(),
   updated_at: z.string().datetime(),
   assigned_cleaner_id: z.string().uuid().nullable().optional(),
+  floor: z.number().int().optional(),
+  // null for the main building
+  building: z.string().nullable().optional(),
 });
 export type Room = z.infer<typeof RoomSchema>;
 
+// Cleaner list entry: priority rooms have a guest arriving today
+export const CleanerRoomSchema = RoomSchema.extend({
+  dirty_since: z.string().datetime(),
+  priority: z.boolean(),
+});
+export type CleanerRoom = z.infer<typeof CleanerRoomSchema>;
+
+// Room number search result; guest_name is set while a guest is checked in
+export const RoomSearchResultSchema = z.object({
+  id: z.string().uuid(),
+  number: z.string(),
+  room_type: RoomType,
+  status: RoomStatus,
+  guest_name: z.string().nullable(),
+});
+export type RoomSearchResult = z.infer<typeof RoomSearchResultSchema>;
+
+export const RoomStatusSummarySchema = z.object({
+  total: z.number().int(),
+  by_status: z.record(RoomStatus, z.number().int()),
+  by_room_type: z.record(z.string(), z.number().int()),
+});
+export type RoomStatusSummary = z.infer<typeof RoomStatusSummarySchema>;
+
+export const RoomTypeConfigSchema = z.object({
+  id: z.string().uuid(),
+  code: RoomType,
+  display_name: z.string(),
+  default_price: z.union([z.string(), z.number()]),
+  default_capacity: z.number().int(),
+  active: z.boolean(),
+});
+export type RoomTypeConfig = z.infer<typeof RoomTypeConfigSchema>;
+
 export const CreateRoomRequestSchema = z.object({
   number: z.string().min(1, "Room number is required").max(10),
   room_type: RoomType,
@@ -118,6 +179,16 @@ export const RoomListResponseSchema = z.object({
 export type RoomListResponse = z.infer<typeof RoomListResponseSchema>;
 
 // === Booking Schemas ===
+export const BookingNoteSchema = z.object({
+  id: z.string().uuid(),
+  booking_id: z.string().uuid(),
+  author_user_id: z.string().uuid(),
+  note: z.string(),
+  created_at: z.string().datetime(),
+  updated_at: z.string().datetime(),
+});
+export type BookingNote = z.infer<typeof BookingNoteSchema>;
+
 export const BookingSchema = z.object({
   id: z.string().uuid(),
   reference: z.string(),
@@ -131,6 +202,13 @@ export const BookingSchema = z.object({
   updated_at: z.string().datetime(),
   created_by_user_id: z.string().uuid().nullable().optional(),
   creation_source: z.string().optional(),
+  created_by: z.string().nullable().optional(),
+  group_reference: z.string().nullable().optional(),
+  checked_in_at: z.string().datetime().nullable().optional(),
+  checked_out_at: z.string().datetime().nullable().optional(),
+  notes: z.array(BookingNoteSchema).optional(),
+  nights: z.number().optional(),
+  total_price: z.string().nullable().optional(),
 });
 export type Booking = z.infer<typeof BookingSchema>;
 
@@ -261,6 +339,7 @@ export const EmployeeSchema = z.object({
   full_name: z.string().nullable().optional(),
   created_at: z.string().datetime(),
   deactivated_at: z.string().datetime().nullable().optional(),
+  last_login_at: z.string().datetime().nullable().optional(),
 });
 export type Employee = z.infer<typeof EmployeeSchema>;
 
@@ -280,6 +359,7 @@ export const CreateEmployeeRequestSchema = z.object({
   }),
   email: z.string().email("Invalid email format").optional().nullable(),
   full_name: z.string().max(100, "Full name must be 100 characters or less").optional().nullable(),
+  must_change_password: z.boolean().optional(),
 });
 export type CreateEmployeeRequest = z.infer<typeof CreateEmployeeRequestSchema>;
 
@@ -307,6 +387,31 @@ export const EmployeeFiltersSchema = z.object({
 });
 export type EmployeeFilters = z.infer<typeof EmployeeFiltersSchema>;
 
+// === API Key Schemas ===
+export const ApiKeySchema = z.object({
+  id: z.string().uuid(),
+  label: z.string(),
+  key_prefix: z.string(),
+  role: UserRole,
+  created_by: z.string().uuid(),
+  created_at: z.string().datetime(),
+  last_used_at: z.string().datetime().nullable(),
+  revoked_at: z.string().datetime().nullable(),
+});
+export type ApiKey = z.infer<typeof ApiKeySchema>;
+
+/** A new key; `key` is only returned this once */
+export const CreatedApiKeySchema = ApiKeySchema.extend({
+  key: z.string(),
+});
+export type CreatedApiKey = z.infer<typeof CreatedApiKeySchema>;
+
+export const CreateApiKeyRequestSchema = z.object({
+  label: z.string().min(1, "Label is required").max(100, "Label must be 100 characters or less"),
+  role: z.enum(["receptionist", "cleaner"]),
+});
+export type CreateApiKeyRequest = z.infer<typeof CreateApiKeyRequestSchema>;
+
 // === Financial Reporting Schemas ===
 export const RoomFinancialsResponseSchema = z.object({
   total_revenue: z.string(),